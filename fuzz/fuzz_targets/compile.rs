@@ -0,0 +1,15 @@
+//! Compiles arbitrary syntax trees down to the LIR.
+//!
+//! These programs can loop forever, so they are not evaluated.
+#![no_main]
+use arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+
+use pijama_ast::fuzz::program;
+use pijama_driver::{pipeline::Options, Compilation};
+
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(block) = program(&mut Unstructured::new(bytes)) {
+        let _ = Compilation::new(block, Options::default()).to_lir();
+    }
+});
@@ -0,0 +1,25 @@
+//! Evaluates well-typed programs with every optimization level, which must not fail to compile
+//! and can only fail at runtime.
+#![no_main]
+use arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+
+use pijama_ast::fuzz::well_typed;
+use pijama_driver::{pipeline::Options, Compilation, LangError};
+
+fuzz_target!(|bytes: &[u8]| {
+    let block = match well_typed(&mut Unstructured::new(bytes)) {
+        Ok(block) => block,
+        Err(_) => return,
+    };
+    for opt_level in 0..=2 {
+        let options = Options {
+            opt_level,
+            ..Options::default()
+        };
+        match Compilation::new(block.clone(), options).evaluate() {
+            Ok(_) | Err(LangError::Runtime(_)) => (),
+            Err(err) => panic!("a well-typed program failed to compile: {:?}", err),
+        }
+    }
+});
@@ -0,0 +1,9 @@
+//! Parses arbitrary text, which must fail with an error instead of panicking.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use pijama_core::parser::parse;
+
+fuzz_target!(|input: &str| {
+    let _ = parse(input);
+});
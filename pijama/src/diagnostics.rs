@@ -0,0 +1,334 @@
+//! Structured diagnostics.
+//!
+//! [`collect`] returns the warnings and errors of a program as plain data, with the same messages
+//! that [`render_diagnostics`](crate::render_diagnostics) shows to humans. Editors and other tools
+//! can read them as JSON, written by [`write_json`] with one diagnostic per line.
+use serde::Serialize;
+
+use std::{fmt::Display, io, ops::Range};
+
+use pijama_ast::{FileId, Location};
+use pijama_core::ty::TyError;
+use pijama_driver::{suggest::suggest, Call, LangError, SourceMap, Warning};
+
+use crate::messages::{
+    error_message, error_title, message, suggestion_message, warning_message, Locale,
+};
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The program cannot be run.
+    Error,
+    /// The program can be run but it probably has a mistake.
+    Warning,
+}
+
+/// Whether a label marks the cause of a diagnostic or some context for it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelStyle {
+    /// The code that caused the diagnostic.
+    Primary,
+    /// Code related to the cause of the diagnostic.
+    Secondary,
+}
+
+/// A message about a range of the source code.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Label {
+    pub style: LabelStyle,
+    /// The path of the file that the range refers to.
+    pub file: String,
+    /// The byte range of the source code.
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// A change to the source code that fixes the error of a diagnostic.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub message: String,
+    /// The byte range to be replaced. It is empty if the change is an insertion.
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// A warning or an error found in a file.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The stable code of the error, see [`pijama_driver::error_codes`]. Warnings have no code.
+    pub code: Option<&'static str>,
+    pub message: String,
+    /// The path of the file of the primary label, which the suggestions refer to too.
+    pub file: String,
+    /// The byte range of the primary label.
+    pub range: Range<usize>,
+    /// The labels of the diagnostic, starting with the primary one.
+    pub labels: Vec<Label>,
+    /// Additional information that is not about a specific range.
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Returns the diagnostics for the warnings and the error of a program, in the order
+/// [`render_diagnostics`](crate::render_diagnostics) renders them.
+///
+/// The locations are looked up in `files`, where the program is [`FileId::MAIN`]. Errors in
+/// imported modules refer to the file of the module instead.
+pub fn collect(
+    files: &SourceMap,
+    error: Option<&LangError>,
+    warnings: &[Warning],
+    deny_warnings: bool,
+    locale: Locale,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let program = Files::new(files, None);
+    for warning in warnings {
+        let (file, _) = program.get(warning.loc().file);
+        let range = warning.loc().start..warning.loc().end;
+        diagnostics.push(Diagnostic {
+            severity: if deny_warnings {
+                Severity::Error
+            } else {
+                Severity::Warning
+            },
+            code: None,
+            message: message(locale, "title.lint", &[]),
+            file: file.clone(),
+            range: range.clone(),
+            labels: vec![Label {
+                style: LabelStyle::Primary,
+                file,
+                range,
+                message: warning_message(locale, warning),
+            }],
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        });
+    }
+
+    if let Some(error) = error {
+        let (files, error) = match error {
+            LangError::Module { path, input, error } => (
+                Files::new(files, Some((path.display().to_string(), input.as_str()))),
+                error.as_ref(),
+            ),
+            error => (program, error),
+        };
+
+        // The diagnostic of the first use of each unbound name, by its position.
+        let mut unbound: Vec<(&str, usize)> = Vec::new();
+        for error in error.errors() {
+            let (error, calls) = match error {
+                LangError::Traced { error, calls } => (error.as_ref(), calls.as_slice()),
+                error => (error, &[][..]),
+            };
+
+            // Binding a name fixes every use of it, so the other uses are labels of the
+            // diagnostic of the first one.
+            if let LangError::Ty(TyError::Unbounded(name)) = error {
+                match unbound.iter().find(|(other, _)| *other == name.content) {
+                    Some((_, index)) => {
+                        let (file, input) = files.get(name.loc.file);
+                        diagnostics[*index].labels.push(Label {
+                            style: LabelStyle::Secondary,
+                            file,
+                            range: trim_end(input, name.loc),
+                            message: message(
+                                locale,
+                                "ty.unbounded_again",
+                                &[("name", &name.content)],
+                            ),
+                        });
+                        continue;
+                    }
+                    None => unbound.push((&name.content, diagnostics.len())),
+                }
+            }
+
+            let (file, input) = files.get(error.loc().file);
+            let range = error.loc().start..error.loc().end;
+
+            let mut labels = vec![Label {
+                style: LabelStyle::Primary,
+                file: file.clone(),
+                range: range.clone(),
+                message: error_message(locale, error),
+            }];
+
+            let mut notes = Vec::new();
+            if let LangError::Ty(error) = error {
+                if let (Some(expected), TyError::Mismatch { found, .. }) = (error.origin(), error) {
+                    let (origin_file, origin_input) = files.get(expected.loc.file);
+                    labels.push(Label {
+                        style: LabelStyle::Secondary,
+                        file: origin_file,
+                        range: trim_end(origin_input, expected.loc),
+                        message: message(locale, "ty.origin", &[("expected", &expected.content)]),
+                    });
+                    let types: &[(&str, &dyn Display)] =
+                        &[("expected", &expected.content), ("found", &found.content)];
+                    notes.push(message(locale, "ty.mismatch_note", types));
+                }
+            }
+
+            trace_labels(&files, calls, locale, &mut labels, &mut notes);
+
+            let suggestions = suggest(input, error)
+                .map(|suggestion| Suggestion {
+                    message: suggestion_message(locale, &suggestion),
+                    range: suggestion.loc.start..suggestion.loc.end,
+                    replacement: suggestion.replacement,
+                })
+                .into_iter()
+                .collect();
+
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: Some(error.code()),
+                message: error_title(locale, error),
+                file,
+                range,
+                labels,
+                notes,
+                suggestions,
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Keeps the first `limit` errors of `diagnostics` and every warning.
+///
+/// A note counting the errors left out is added to the last error kept.
+pub fn limit_errors(diagnostics: &mut Vec<Diagnostic>, limit: usize, locale: Locale) {
+    let mut errors = 0;
+    diagnostics.retain(|diagnostic| {
+        if diagnostic.severity == Severity::Error {
+            errors += 1;
+            errors <= limit
+        } else {
+            true
+        }
+    });
+    if errors > limit {
+        let last = diagnostics
+            .iter_mut()
+            .rev()
+            .find(|diagnostic| diagnostic.severity == Severity::Error);
+        if let Some(last) = last {
+            let hidden = errors - limit;
+            last.notes
+                .push(message(locale, "errors.hidden", &[("count", &hidden)]));
+        }
+    }
+}
+
+/// The maximum number of labels used for the stack trace of a runtime error.
+const MAX_TRACE_LABELS: usize = 10;
+
+/// Adds a secondary label for each call of the stack trace of a runtime error, innermost first.
+///
+/// Consecutive calls at the same location, like the ones of a recursive function, share a label.
+/// Only the [`MAX_TRACE_LABELS`] innermost labels are added, with a note counting the calls left.
+fn trace_labels(
+    files: &Files,
+    calls: &[Call],
+    locale: Locale,
+    labels: &mut Vec<Label>,
+    notes: &mut Vec<String>,
+) {
+    let mut groups: Vec<(&Call, usize)> = Vec::new();
+    for call in calls {
+        match groups.last_mut() {
+            Some((last, count)) if last.loc == call.loc => *count += 1,
+            _ => groups.push((call, 1)),
+        }
+    }
+
+    for (call, count) in groups.iter().take(MAX_TRACE_LABELS) {
+        let (file, input) = files.get(call.loc.file);
+        let message = match (&call.name, count) {
+            (Some(name), 1) => message(locale, "runtime.call", &[("name", name)]),
+            (None, 1) => message(locale, "runtime.anonymous_call", &[]),
+            (Some(name), count) => message(
+                locale,
+                "runtime.nested_calls",
+                &[("name", name), ("count", count)],
+            ),
+            (None, count) => message(
+                locale,
+                "runtime.nested_anonymous_calls",
+                &[("count", count)],
+            ),
+        };
+        labels.push(Label {
+            style: LabelStyle::Secondary,
+            file,
+            range: trim_end(input, call.loc),
+            message,
+        });
+    }
+
+    let hidden: usize = groups
+        .iter()
+        .skip(MAX_TRACE_LABELS)
+        .map(|(_, count)| count)
+        .sum();
+    if hidden > 0 {
+        notes.push(message(
+            locale,
+            "runtime.hidden_calls",
+            &[("count", &hidden)],
+        ));
+    }
+}
+
+/// The files that the locations of a diagnostic can refer to.
+struct Files<'m> {
+    map: &'m SourceMap,
+    /// The path and source code of the module checked on its own, which is [`FileId::MAIN`]
+    /// instead of the program.
+    module: Option<(String, &'m str)>,
+}
+
+impl<'m> Files<'m> {
+    fn new(map: &'m SourceMap, module: Option<(String, &'m str)>) -> Self {
+        Files { map, module }
+    }
+
+    /// Returns the path and source code of `file`, or the ones of [`FileId::MAIN`] if `file` is
+    /// not in the source map.
+    fn get(&self, file: FileId) -> (String, &'m str) {
+        match (&self.module, self.map.name(file), self.map.source(file)) {
+            (Some((path, input)), ..) if file == FileId::MAIN => (path.clone(), input),
+            (_, Some(name), Some(source)) => (name.to_owned(), source),
+            _ => self.get(FileId::MAIN),
+        }
+    }
+}
+
+/// Returns the range of `loc` without the trailing whitespace of its code in `input`.
+///
+/// The origin of conditions spans the keyword and the spaces after it.
+fn trim_end(input: &str, loc: Location) -> Range<usize> {
+    let end = input
+        .get(loc.start..loc.end)
+        .map_or(loc.end, |code| loc.start + code.trim_end().len());
+    loc.start..end
+}
+
+/// Writes `diagnostics` to `writer` as JSON, one object per line.
+pub fn write_json(writer: &mut impl io::Write, diagnostics: &[Diagnostic]) -> io::Result<()> {
+    for diagnostic in diagnostics {
+        serde_json::to_writer(&mut *writer, diagnostic)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,724 @@
+//! The source code formatter, run with `pijama fmt`.
+//!
+//! [`format`] parses a program and prints it back with a consistent layout:
+//!
+//! - Blocks are indented with four spaces and consecutive nodes are separated by at most one blank
+//!   line.
+//! - Binary operators, commas and colons are followed by a single space, and binary operators are
+//!   preceded by one too. Brackets are only kept where they are needed.
+//! - Conditionals, loops, anonymous functions and match arms stay in one line if they fit in the
+//!   width and have a single node in each block. Function definitions, enums and matches are
+//!   always laid out over several lines.
+//! - Argument, parameter and list element lists that do not fit in the width are broken with one
+//!   item per line.
+//!
+//! The parser does not keep comments, so the comment at the start of each block is found in the
+//! source code right before the location of the block. Doc comments are kept in the function
+//! definitions they document.
+use std::collections::{BTreeMap, HashMap};
+
+use pijama_ast::{
+    ty::{Ty, TyAnnotation},
+    visitor::NodeVisitor,
+    Arm, BinOp, Block, Branch, LineIndex, Literal, Located, Name, Node, Pattern,
+};
+use pijama_core::parser::parse;
+use pijama_driver::{LangError, LangResult};
+
+/// The width used by [`format`].
+pub const WIDTH: usize = 100;
+
+/// The number of columns that each level of indentation adds.
+const INDENT: usize = 4;
+
+/// Formats `input`, breaking the lines longer than [`WIDTH`] columns when possible.
+pub fn format(input: &str) -> LangResult<String> {
+    format_with_width(input, WIDTH)
+}
+
+/// Formats `input`, breaking the lines longer than `width` columns when possible.
+///
+/// The formatted program ends with a line break, unless it is empty.
+pub fn format_with_width(input: &str, width: usize) -> LangResult<String> {
+    let block = parse(input).map_err(LangError::from)?;
+    let mut printer = Printer::new(input, &block);
+    let doc = printer.block(&block);
+    debug_assert!(printer.comments.is_empty(), "Every comment is printed");
+    let mut output = render(&doc, width);
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// A document to be laid out.
+enum Doc {
+    Text(String),
+    /// A space if the innermost group around it fits in one line, or a line break otherwise.
+    Line,
+    /// Nothing if the innermost group around it fits in one line, or a line break otherwise.
+    SoftLine,
+    /// A line break, which does not fit in one line.
+    HardLine,
+    /// An empty line.
+    BlankLine,
+    /// A document whose line breaks are indented by one more level.
+    Nest(Box<Doc>),
+    /// A document laid out in one line if it fits in the remaining width.
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+fn text(text: impl ToString) -> Doc {
+    Doc::Text(text.to_string())
+}
+
+fn nest(docs: Vec<Doc>) -> Doc {
+    Doc::Nest(Box::new(Doc::Concat(docs)))
+}
+
+fn group(docs: Vec<Doc>) -> Doc {
+    Doc::Group(Box::new(Doc::Concat(docs)))
+}
+
+fn bracketed(doc: Doc) -> Doc {
+    Doc::Concat(vec![text("("), doc, text(")")])
+}
+
+/// Builds the documents of nodes, keeping the comments that were not printed yet.
+struct Printer<'a> {
+    input: &'a str,
+    lines: LineIndex<'a>,
+    /// The start of each comment, indexed by the position right after it.
+    comments: HashMap<usize, usize>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(input: &'a str, block: &Located<Block<'a>>) -> Self {
+        let mut quoted = Quoted::default();
+        quoted.visit_block(&block.content);
+        Printer {
+            input,
+            lines: LineIndex::new(input),
+            comments: comments(input, &quoted.0),
+        }
+    }
+
+    /// Returns the comment that ends at `pos`, if it was not printed already, and whether it is a
+    /// line comment.
+    fn comment(&mut self, pos: usize) -> Option<(Doc, bool)> {
+        let start = self.comments.remove(&pos)?;
+        let comment = self.input[start..pos].trim_end();
+        Some((text(comment), comment.starts_with('#')))
+    }
+
+    /// Returns the document of a block, with each node in its own line.
+    fn block(&mut self, block: &Located<Block<'a>>) -> Doc {
+        let mut docs = Vec::new();
+        if let Some((comment, line)) = self.comment(block.loc.start) {
+            docs.push(comment);
+            // Line comments end with a line break, block comments can be followed by the code.
+            docs.push(if line { Doc::HardLine } else { Doc::Line });
+        }
+        for (index, node) in block.content.iter().enumerate() {
+            if index > 0 && self.blank_line_before(node) {
+                docs.push(Doc::BlankLine);
+            } else if index > 0 {
+                docs.push(Doc::HardLine);
+            }
+            let doc = self.node(node, true);
+            // A line starting with `do` would be parsed as the body of a conditional or loop.
+            if starts_with_block(node) {
+                docs.push(bracketed(doc));
+            } else {
+                docs.push(doc);
+            }
+        }
+        Doc::Concat(docs)
+    }
+
+    /// Returns `true` if the line before `node` is empty.
+    ///
+    /// The location of function definitions does not include their doc comment, so its lines are
+    /// skipped.
+    fn blank_line_before(&self, node: &Located<Node<'a>>) -> bool {
+        let doc_lines = match &node.content {
+            Node::FnDef(.., doc) => doc.len(),
+            _ => 0,
+        };
+        self.lines
+            .line(node.loc.start)
+            .checked_sub(doc_lines + 1)
+            .and_then(|line| self.lines.line_text(line))
+            .is_some_and(|line| line.trim().is_empty())
+    }
+
+    /// Returns the document of a node.
+    ///
+    /// Unary operations take everything after them as their operand, so they must be in brackets
+    /// unless they are `last`, i.e., nothing that is part of the same node comes after them.
+    fn node(&mut self, node: &Located<Node<'a>>, last: bool) -> Doc {
+        match &node.content {
+            Node::BinaryOp(op, node1, node2) => {
+                let prec = precedence(*op);
+                Doc::Concat(vec![
+                    self.operand(node1, |other| other < prec, false),
+                    text(format!(" {} ", op)),
+                    self.operand(node2, |other| other <= prec, last),
+                ])
+            }
+            Node::Operator(op, node1, node2) => {
+                let prec = precedence(*op);
+                let docs = match (node1, node2) {
+                    (None, None) => vec![text(op)],
+                    (Some(node1), None) => vec![
+                        self.operand(node1, |other| other < prec, false),
+                        text(format!(" {} _", op)),
+                    ],
+                    (None, Some(node2)) => vec![
+                        text(format!("_ {} ", op)),
+                        self.operand(node2, |other| other <= prec, true),
+                    ],
+                    (Some(node1), Some(node2)) => vec![
+                        self.operand(node1, |other| other < prec, false),
+                        text(format!(" {} ", op)),
+                        self.operand(node2, |other| other <= prec, true),
+                    ],
+                };
+                bracketed(Doc::Concat(docs))
+            }
+            Node::UnaryOp(op, node) => {
+                let operand = match &node.content {
+                    // The operand would be the same without brackets, but it would look like only
+                    // the first operand of the binary operation is.
+                    Node::BinaryOp(..) => bracketed(self.node(node, true)),
+                    _ => self.node(node, true),
+                };
+                let doc = Doc::Concat(vec![text(op), operand]);
+                if last {
+                    doc
+                } else {
+                    bracketed(doc)
+                }
+            }
+            Node::LetBind(annotation, node) => Doc::Concat(vec![
+                self.annotation(annotation),
+                text(" = "),
+                self.node(node, true),
+            ]),
+            Node::LetMut(annotation, node) => Doc::Concat(vec![
+                text("let mut "),
+                self.annotation(annotation),
+                text(" = "),
+                self.node(node, true),
+            ]),
+            Node::LetIn(annotation, node1, node2) => {
+                // The second node takes every node after it, like unary operations.
+                let doc = Doc::Concat(vec![
+                    text("let "),
+                    self.annotation(annotation),
+                    text(" = "),
+                    self.node(node1, true),
+                    text(" in "),
+                    self.node(node2, true),
+                ]);
+                if last {
+                    doc
+                } else {
+                    bracketed(doc)
+                }
+            }
+            Node::Assign(name, node) => Doc::Concat(vec![
+                text(format!("{} = ", name.content)),
+                self.node(node, true),
+            ]),
+            Node::Cond(if_branch, branches, el_blk) => {
+                let mut docs = self.branch("if", if_branch);
+                for branch in branches {
+                    docs.push(Doc::Line);
+                    docs.extend(self.branch("elif", branch));
+                }
+                if !el_blk.content.is_empty() {
+                    docs.push(Doc::Line);
+                    docs.push(text("else"));
+                    docs.push(nest(vec![Doc::Line, self.block(el_blk)]));
+                }
+                docs.push(Doc::Line);
+                docs.push(text("end"));
+                group(docs)
+            }
+            Node::While(branch) => {
+                let mut docs = self.branch("while", branch);
+                docs.push(Doc::Line);
+                docs.push(text("end"));
+                group(docs)
+            }
+            Node::For(name, start, end, body) => group(vec![
+                text(format!("for {} in ", name.content)),
+                self.node(start, true),
+                text(".."),
+                self.node(end, true),
+                text(" do"),
+                nest(vec![Doc::Line, self.block(body)]),
+                Doc::Line,
+                text("end"),
+            ]),
+            Node::FnDef(name, args, body, doc) => {
+                let mut docs = Vec::new();
+                for line in doc {
+                    if line.is_empty() {
+                        docs.push(text("##"));
+                    } else {
+                        docs.push(text(format!("## {}", line)));
+                    }
+                    docs.push(Doc::HardLine);
+                }
+                docs.push(text(format!("fn {}", name.content)));
+                docs.extend(self.signature(args, body));
+                docs.push(nest(vec![Doc::HardLine, self.block(&body.item)]));
+                docs.push(Doc::HardLine);
+                docs.push(text("end"));
+                Doc::Concat(docs)
+            }
+            Node::AnonFn(args, body) => {
+                let mut docs = vec![text("fn")];
+                docs.extend(self.signature(args, body));
+                docs.push(nest(vec![Doc::Line, self.block(&body.item)]));
+                docs.push(Doc::Line);
+                docs.push(text("end"));
+                group(docs)
+            }
+            Node::Call(func, args) => {
+                let func_doc = self.node(func, true);
+                let func_doc = match &func.content {
+                    Node::Name(_) | Node::PrimFn(_) | Node::Ascription(..) => func_doc,
+                    _ => bracketed(func_doc),
+                };
+                let args = args.iter().map(|arg| self.node(arg, true)).collect();
+                Doc::Concat(vec![func_doc, list("(", args, ")")])
+            }
+            Node::Literal(literal) => self.literal(literal, node.loc.start),
+            Node::List(elems) => {
+                let elems = elems.iter().map(|elem| self.node(elem, true)).collect();
+                list("[", elems, "]")
+            }
+            Node::Interpolation(parts) => {
+                let mut docs = vec![text("\"")];
+                for part in parts {
+                    match &part.content {
+                        Node::Literal(Literal::Str(string)) => {
+                            let literal = Literal::Str(string.clone()).to_string();
+                            docs.push(text(&literal[1..literal.len() - 1]));
+                        }
+                        _ => {
+                            docs.push(text("{"));
+                            docs.push(self.node(part, true));
+                            docs.push(text("}"));
+                        }
+                    }
+                }
+                docs.push(text("\""));
+                Doc::Concat(docs)
+            }
+            Node::Ascription(node, ty) => bracketed(Doc::Concat(vec![
+                self.node(node, true),
+                text(format!(" : {}", Type(&ty.content))),
+            ])),
+            Node::Block(block) => group(vec![
+                text("do"),
+                nest(vec![Doc::Line, self.block(block)]),
+                Doc::Line,
+                text("end"),
+            ]),
+            Node::Lazy(node) => {
+                // The lazy term takes every node after it, like unary operations.
+                let doc = Doc::Concat(vec![text("lazy "), self.node(node, true)]);
+                if last {
+                    doc
+                } else {
+                    bracketed(doc)
+                }
+            }
+            Node::Name(name) => text(name),
+            Node::PrimFn(primitive) => text(primitive),
+            Node::EnumDef(name, variants) => {
+                let mut variant_docs = Vec::new();
+                for variant in variants {
+                    variant_docs.push(Doc::HardLine);
+                    let mut variant_doc = variant.name.content.to_string();
+                    if !variant.fields.is_empty() {
+                        let fields: Vec<_> = variant
+                            .fields
+                            .iter()
+                            .map(|field| Type(&field.content).to_string())
+                            .collect();
+                        variant_doc.push_str(&format!("({})", fields.join(", ")));
+                    }
+                    variant_docs.push(text(variant_doc));
+                }
+                Doc::Concat(vec![
+                    text(format!("enum {} do", name.content)),
+                    nest(variant_docs),
+                    Doc::HardLine,
+                    text("end"),
+                ])
+            }
+            Node::Match(node, arms) => {
+                let mut docs = vec![text("match "), self.node(node, true), text(" with")];
+                for arm in arms {
+                    docs.push(Doc::HardLine);
+                    docs.push(self.arm(arm));
+                }
+                docs.push(Doc::HardLine);
+                docs.push(text("end"));
+                Doc::Concat(docs)
+            }
+            Node::Import(path) => text(format!("import {}", Literal::Str(path.clone()))),
+            // Code that could not be parsed is kept as it was written.
+            Node::Error => text(&self.input[node.loc.start..node.loc.end]),
+        }
+    }
+
+    /// Returns the document of an operand of a binary operation, in brackets if `needs_brackets`
+    /// returns `true` for the precedence of its operator.
+    fn operand(
+        &mut self,
+        node: &Located<Node<'a>>,
+        needs_brackets: impl Fn(usize) -> bool,
+        last: bool,
+    ) -> Doc {
+        match &node.content {
+            Node::BinaryOp(op, ..) if needs_brackets(precedence(*op)) => {
+                bracketed(self.node(node, true))
+            }
+            _ => self.node(node, last),
+        }
+    }
+
+    /// Returns the documents of a branch starting with `keyword`, with the condition in the same
+    /// line as the keyword if it fits.
+    fn branch(&mut self, keyword: &str, branch: &Branch<'a>) -> Vec<Doc> {
+        vec![
+            group(vec![
+                text(keyword),
+                nest(vec![Doc::Line, self.block(&branch.cond)]),
+                Doc::Line,
+                text("do"),
+            ]),
+            nest(vec![Doc::Line, self.block(&branch.body)]),
+        ]
+    }
+
+    fn arm(&mut self, arm: &Arm<'a>) -> Doc {
+        let pattern = match &arm.pattern.content {
+            Pattern::Literal(literal) => self.literal(literal, arm.pattern.loc.start),
+            pattern => text(pattern),
+        };
+        group(vec![
+            pattern,
+            text(" do"),
+            nest(vec![Doc::Line, self.block(&arm.body)]),
+            Doc::Line,
+            text("end"),
+        ])
+    }
+
+    /// Returns the document of a literal starting at `start`.
+    ///
+    /// Numbers are written as in the source code, so their base and the underscores between their
+    /// digits are kept.
+    fn literal(&self, literal: &Literal, start: usize) -> Doc {
+        if let Literal::Number(_) | Literal::Float(_) = literal {
+            let code = &self.input[start..];
+            let hex = code.trim_start_matches('-').starts_with("0x");
+            let len = code
+                .char_indices()
+                .find(|&(index, c)| {
+                    let exponent = !hex && matches!(code[..index].chars().last(), Some('e' | 'E'));
+                    !(c.is_ascii_alphanumeric()
+                        || c == '_'
+                        || c == '.'
+                        || (c == '-' && index == 0)
+                        || ((c == '-' || c == '+') && exponent))
+                })
+                .map_or(code.len(), |(index, _)| index);
+            // The number might be in brackets or followed by a range.
+            let same = parse(&code[..len]).is_ok_and(|block| {
+                block.content.len() == 1
+                    && block.content[0].content == Node::Literal(literal.clone())
+            });
+            if same {
+                return text(&code[..len]);
+            }
+        }
+        text(literal)
+    }
+
+    /// Returns the documents of the parameters and the return type of a function, up to the `do`.
+    fn signature(
+        &mut self,
+        args: &[TyAnnotation<Name<'a>>],
+        body: &TyAnnotation<Block<'a>>,
+    ) -> Vec<Doc> {
+        let args = args.iter().map(|arg| self.annotation(arg)).collect();
+        let mut docs = vec![list("(", args, ")")];
+        if body.ty.content != Ty::Missing {
+            docs.push(text(format!(": {}", Type(&body.ty.content))));
+        }
+        docs.push(text(" do"));
+        docs
+    }
+
+    fn annotation(&mut self, annotation: &TyAnnotation<Name<'a>>) -> Doc {
+        match &annotation.ty.content {
+            Ty::Missing => text(annotation.item.content),
+            ty => text(format!("{}: {}", annotation.item.content, Type(ty))),
+        }
+    }
+}
+
+/// Returns the document of a list of items between `open` and `close`, with one item per line if
+/// they do not fit in one.
+fn list(open: &str, items: Vec<Doc>, close: &str) -> Doc {
+    if items.is_empty() {
+        return text(format!("{}{}", open, close));
+    }
+    let mut docs = vec![Doc::SoftLine];
+    for (index, item) in items.into_iter().enumerate() {
+        if index > 0 {
+            docs.push(text(","));
+            docs.push(Doc::Line);
+        }
+        docs.push(item);
+    }
+    group(vec![text(open), nest(docs), Doc::SoftLine, text(close)])
+}
+
+/// Returns `true` if the document of `node` starts with a `do` block.
+fn starts_with_block(node: &Located<Node<'_>>) -> bool {
+    match &node.content {
+        Node::Block(_) => true,
+        Node::BinaryOp(_, node1, _) => starts_with_block(node1),
+        _ => false,
+    }
+}
+
+/// Returns the precedence level of `op`, as described in the `bin_op` module of the parser.
+fn precedence(op: BinOp) -> usize {
+    use BinOp::*;
+    match op {
+        Pipe => 0,
+        And | Or => 1,
+        Lte | Gte | Lt | Gt | Eq | Neq => 2,
+        BitAnd | BitOr | BitXor | Shr | Shl => 3,
+        Add | Sub => 4,
+        Mul | Div | Rem => 5,
+    }
+}
+
+/// A type written as in the source code.
+struct Type<'t>(&'t Ty);
+
+impl std::fmt::Display for Type<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.0 {
+            Ty::Bool => write!(f, "Bool"),
+            Ty::Int => write!(f, "Int"),
+            Ty::Float => write!(f, "Float"),
+            Ty::Unit => write!(f, "Unit"),
+            Ty::Char => write!(f, "Char"),
+            Ty::String => write!(f, "String"),
+            Ty::List(ty) => write!(f, "[{}]", Type(ty)),
+            Ty::Chan(ty) => write!(f, "Chan({})", Type(ty)),
+            Ty::Lazy(ty) => write!(f, "Lazy({})", Type(ty)),
+            Ty::Arrow(ty1, ty2) if matches!(ty1.as_ref(), Ty::Arrow(..)) => {
+                write!(f, "({}) -> {}", Type(ty1), Type(ty2))
+            }
+            Ty::Arrow(ty1, ty2) => write!(f, "{} -> {}", Type(ty1), Type(ty2)),
+            Ty::Var(name) => write!(f, "{}", name),
+            Ty::Adt(name, args) => {
+                write!(f, "{}", name)?;
+                if !args.is_empty() {
+                    let args: Vec<_> = args.iter().map(|arg| Type(arg).to_string()).collect();
+                    write!(f, "({})", args.join(", "))?;
+                }
+                Ok(())
+            }
+            // Missing types are never written.
+            Ty::Missing => Ok(()),
+        }
+    }
+}
+
+/// Collects the start and end of the nodes whose code can have `#` or `/*` without being a
+/// comment: strings, characters, interpolations and imports.
+#[derive(Default)]
+struct Quoted(BTreeMap<usize, usize>);
+
+impl<'a> NodeVisitor<'a> for Quoted {
+    fn visit_node(&mut self, node: &Located<Node<'a>>) {
+        match &node.content {
+            Node::Literal(Literal::Str(_))
+            | Node::Literal(Literal::Char(_))
+            | Node::Interpolation(_)
+            | Node::Import(_)
+            | Node::Error => {
+                self.0.insert(node.loc.start, node.loc.end);
+            }
+            _ => self.super_node(node),
+        }
+    }
+
+    fn visit_arm(&mut self, arm: &Arm<'a>) {
+        if let Pattern::Literal(Literal::Str(_)) | Pattern::Literal(Literal::Char(_)) =
+            arm.pattern.content
+        {
+            self.0.insert(arm.pattern.loc.start, arm.pattern.loc.end);
+        }
+        self.super_arm(arm);
+    }
+}
+
+/// Returns the start of each comment in `input`, indexed by the position right after it.
+///
+/// The code in `quoted` is skipped, as well as doc comments. Line comments end after their line
+/// break.
+fn comments(input: &str, quoted: &BTreeMap<usize, usize>) -> HashMap<usize, usize> {
+    let line_end = |start: usize| {
+        input[start..]
+            .find('\n')
+            .map_or(input.len(), |i| start + i + 1)
+    };
+    let mut comments = HashMap::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if let Some(&end) = quoted.get(&pos) {
+            pos = end.max(pos + 1);
+        } else if rest.starts_with("##") {
+            pos = line_end(pos);
+        } else if rest.starts_with('#') {
+            let end = line_end(pos);
+            comments.insert(end, pos);
+            pos = end;
+        } else if rest.starts_with("/*") {
+            let end = block_comment_end(input, pos);
+            comments.insert(end, pos);
+            pos = end;
+        } else {
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    comments
+}
+
+/// Returns the position after the block comment starting at `start`, including the block
+/// comments inside it.
+fn block_comment_end(input: &str, start: usize) -> usize {
+    let mut depth = 0;
+    let mut pos = start;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with("/*") {
+            depth += 1;
+            pos += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            pos += 2;
+            if depth == 0 {
+                return pos;
+            }
+        } else {
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    input.len()
+}
+
+/// Lays out `doc` in lines of at most `width` columns when possible.
+///
+/// The indentation of a line is written with its first text, so empty lines have no trailing
+/// spaces.
+fn render(doc: &Doc, width: usize) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    // The indentation of the current line, if nothing was written in it yet.
+    let mut indentation = None;
+    // The documents left to lay out, with their indentation and whether they are in one line.
+    let mut stack = vec![(0, false, doc)];
+    while let Some((indent, flat, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(text) => {
+                if let Some(indent) = indentation.take() {
+                    output.extend(std::iter::repeat_n(' ', indent));
+                }
+                output.push_str(text);
+                column = match text.rfind('\n') {
+                    Some(index) => text[index + 1..].chars().count(),
+                    None => column + text.chars().count(),
+                };
+            }
+            Doc::Line if flat => {
+                output.push(' ');
+                column += 1;
+            }
+            Doc::SoftLine if flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine | Doc::BlankLine => {
+                // A line break right after another one does not add an empty line.
+                if indentation.is_none() {
+                    output.push('\n');
+                }
+                if let Doc::BlankLine = doc {
+                    output.push('\n');
+                }
+                indentation = Some(indent);
+                column = indent;
+            }
+            Doc::Nest(doc) => stack.push((indent + INDENT, flat, doc)),
+            Doc::Group(doc) => {
+                let flat = flat || fits(doc, width.saturating_sub(column), &stack);
+                stack.push((indent, flat, doc));
+            }
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+            }
+        }
+    }
+    output
+}
+
+/// Returns `true` if `doc` laid out in one line, followed by the documents in `rest` up to the
+/// next line break, is at most `width` columns long.
+fn fits(doc: &Doc, width: usize, rest: &[(usize, bool, &Doc)]) -> bool {
+    let mut remaining = width;
+    let mut rest = rest.iter().rev().map(|&(_, flat, doc)| (flat, doc));
+    let mut stack = vec![(true, doc)];
+    loop {
+        let (flat, doc) = match stack.pop().or_else(|| rest.next()) {
+            Some(next) => next,
+            None => return true,
+        };
+        let len = match doc {
+            Doc::Text(text) => match text.find('\n') {
+                Some(index) => return text[..index].chars().count() <= remaining,
+                None => text.chars().count(),
+            },
+            Doc::Line if flat => 1,
+            Doc::SoftLine if flat => 0,
+            Doc::HardLine | Doc::BlankLine if flat => return false,
+            Doc::Line | Doc::SoftLine | Doc::HardLine | Doc::BlankLine => return true,
+            Doc::Nest(doc) | Doc::Group(doc) => {
+                stack.push((flat, doc));
+                0
+            }
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (flat, doc)));
+                0
+            }
+        };
+        remaining = match remaining.checked_sub(len) {
+            Some(remaining) => remaining,
+            None => return false,
+        };
+    }
+}
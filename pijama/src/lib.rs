@@ -1,52 +1,535 @@
 use codespan_reporting::{
     diagnostic::{Diagnostic, Label},
-    files::SimpleFiles,
+    files::Files,
     term::{
         emit,
-        termcolor::{ColorChoice, StandardStream},
+        termcolor::{self, NoColor, StandardStream, WriteColor},
+        Chars,
     },
 };
-use structopt::StructOpt;
+use structopt::{
+    clap::{AppSettings, ArgSettings},
+    StructOpt,
+};
+
+use std::{
+    env,
+    io::{self, IsTerminal},
+    ops::Range,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use pijama_ast::LineIndex;
+use pijama_core::{bytecode, mir, ty::ty_check_terms};
+use pijama_driver::{
+    config::ColorChoice, pipeline::Options as CompileOptions, Compilation, LangError, LangResult,
+    Source, SourceMap, Warning,
+};
+
+use diagnostics::{LabelStyle, Severity};
+use messages::Locale;
 
-use pijama_driver::LangError;
+pub mod diagnostics;
+pub mod fmt;
+pub mod messages;
+pub mod repl;
+
+pub use pijama_core::lex;
 
 #[derive(Debug, StructOpt)]
-#[structopt(name = "pijama", about = "The Pijama compiler")]
+#[structopt(
+    name = "pijama",
+    about = "The Pijama compiler",
+    setting = AppSettings::SubcommandsNegateReqs
+)]
 pub struct Options {
-    #[structopt(name = "INPUT", help = "Path to the input file.")]
-    pub path: String,
+    #[structopt(name = "INPUT", help = "Path to the input file.", set = ArgSettings::Required)]
+    // The path is only optional when a subcommand is used.
+    pub path: Option<String>,
+    #[structopt(
+        long = "--config",
+        help = "Path to the configuration file. Defaults to `pijama.toml` if it exists."
+    )]
+    pub config: Option<PathBuf>,
+    #[structopt(
+        long = "--timings",
+        help = "Report how long each compilation phase took"
+    )]
+    pub timings: bool,
+    #[structopt(
+        long = "--emit-js",
+        help = "Print the program as JavaScript instead of running it"
+    )]
+    pub emit_js: bool,
+    #[structopt(
+        long = "--emit-rust",
+        help = "Print the program as a Rust crate instead of running it"
+    )]
+    pub emit_rust: bool,
+    #[structopt(
+        long = "--color",
+        help = "Whether diagnostics are colored: `auto`, `always` or `never`. Overrides the configuration file.",
+        parse(try_from_str = parse_color)
+    )]
+    pub color: Option<ColorChoice>,
+    #[structopt(
+        long = "--charset",
+        help = "Characters used to draw diagnostics: `unicode` or `ascii`",
+        default_value = "unicode"
+    )]
+    pub charset: Charset,
+    #[structopt(
+        long = "--error-format",
+        help = "Format of the diagnostics: `human` or `json`",
+        default_value = "human"
+    )]
+    pub error_format: ErrorFormat,
+    #[structopt(
+        long = "--locale",
+        help = "Language of the diagnostics: `en` or `es`",
+        default_value = "en"
+    )]
+    pub locale: Locale,
+    #[structopt(
+        long = "--error-limit",
+        help = "Maximum number of errors shown, the rest are only counted",
+        parse(try_from_str = parse_error_limit)
+    )]
+    pub error_limit: Option<usize>,
     #[structopt(flatten)]
     pub machine_opts: MachineOptions,
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    #[structopt(
+        about = "Run a program, which is the same as passing its path without a subcommand"
+    )]
+    Run {
+        #[structopt(name = "INPUT", help = "Path to the input file.")]
+        path: String,
+    },
+    #[structopt(about = "Type-check a program without running it")]
+    Check {
+        #[structopt(name = "INPUT", help = "Path to the input file.")]
+        path: String,
+    },
+    #[structopt(about = "Print a program at a stage of the compiler")]
+    Dump {
+        #[structopt(long = "--stage", help = "The stage to print: `ast`, `mir` or `lir`")]
+        stage: Stage,
+        #[structopt(name = "INPUT", help = "Path to the input file.")]
+        path: String,
+    },
+    #[structopt(about = "Print the bytecode that a program is compiled to")]
+    Disasm {
+        #[structopt(name = "INPUT", help = "Path to the input file.")]
+        path: String,
+    },
+    #[structopt(about = "Print the extended explanation of an error code")]
+    Explain {
+        #[structopt(name = "CODE", help = "The error code, for example `E0002`.")]
+        code: String,
+    },
+    #[structopt(about = "Start an interactive session")]
+    Repl,
+    #[structopt(about = "Format source files in place")]
+    Fmt {
+        #[structopt(
+            name = "FILES",
+            help = "Paths to the files to format.",
+            required = true
+        )]
+        paths: Vec<PathBuf>,
+        #[structopt(
+            long = "--check",
+            help = "Report the files that are not formatted instead of formatting them, failing if there are any"
+        )]
+        check: bool,
+    },
 }
 
 #[derive(Debug, StructOpt)]
 pub struct MachineOptions {
     #[structopt(
         long = "--overflow-check",
-        help = "Execution panics on integer overflow"
+        help = "Integer overflows stop the execution with an error"
     )]
     // If the flag is not passed, the default value is `false`.
     pub overflow_check: bool,
 }
 
-pub fn display_error<'a>(input: &str, path: &str, error: &LangError<'a>) {
-    let writer = StandardStream::stderr(ColorChoice::Always);
-    let config = codespan_reporting::term::Config::default();
-    let mut files = SimpleFiles::new();
+/// A stage of the compiler whose representation of a program can be printed with `pijama dump`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Stage {
+    /// The syntax tree, with the definitions of the prelude that the program uses.
+    Ast,
+    /// The MIR, before any pass is run over it, with the type and location of each term, see
+    /// [`mir::dump`].
+    Mir,
+    /// The LIR, after running the passes of the default optimization level.
+    Lir,
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "ast" => Ok(Stage::Ast),
+            "mir" => Ok(Stage::Mir),
+            "lir" => Ok(Stage::Lir),
+            _ => Err(format!("`{}` is not a valid stage", s)),
+        }
+    }
+}
+
+/// Returns the representation of `input` at `stage`, as printed by `pijama dump`.
+pub fn dump<'a>(input: impl Source<'a> + 'a, stage: Stage) -> LangResult<String> {
+    let mut compilation = Compilation::new(input, CompileOptions::default());
+    match stage {
+        Stage::Ast => Ok(format!("{:#?}", compilation.parse()?.content)),
+        Stage::Mir => {
+            let mir = compilation.lower()?;
+            Ok(mir::dump(mir, &ty_check_terms(mir)?))
+        }
+        Stage::Lir => Ok(compilation.to_lir()?.pretty().to_string()),
+    }
+}
+
+/// Returns the bytecode that `input` is compiled to, as printed by `pijama disasm`, or `None` if
+/// the program uses threads, which the bytecode does not support.
+pub fn disasm<'a>(input: impl Source<'a> + 'a) -> LangResult<Option<String>> {
+    let mut compilation = Compilation::new(input, CompileOptions::default());
+    let lir = compilation.to_lir()?;
+    if bytecode::supports(lir) {
+        Ok(Some(bytecode::compile(lir).to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_color(s: &str) -> Result<ColorChoice, String> {
+    ColorChoice::from_str(s).map_err(|()| format!("`{}` is not a valid color choice", s))
+}
+
+fn parse_error_limit(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) | Err(_) => Err(format!("`{}` is not a positive number", s)),
+        Ok(limit) => Ok(limit),
+    }
+}
+
+/// The characters used to draw the borders and underlines of diagnostics.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Charset {
+    /// Use box drawing characters.
+    Unicode,
+    /// Use only ASCII characters, for terminals that cannot display box drawing characters.
+    Ascii,
+}
+
+impl FromStr for Charset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "unicode" => Ok(Charset::Unicode),
+            "ascii" => Ok(Charset::Ascii),
+            _ => Err(format!("`{}` is not a valid character set", s)),
+        }
+    }
+}
+
+/// The format of the diagnostics displayed on stderr.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorFormat {
+    /// Diagnostics are rendered with the source code they refer to.
+    Human,
+    /// Diagnostics are written as JSON, see [`diagnostics::write_json`].
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "human" => Ok(ErrorFormat::Human),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("`{}` is not a valid error format", s)),
+        }
+    }
+}
+
+/// Options for rendering diagnostics.
+///
+/// The version of `codespan-reporting` used here only renders the lines that have labels, so the
+/// number of context lines around them cannot be configured yet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DiagnosticsConfig {
+    /// Whether diagnostics displayed on stderr are colored.
+    ///
+    /// Functions that render to a writer use the colors supported by the writer instead.
+    pub color: ColorChoice,
+    /// The characters used to draw diagnostics.
+    pub charset: Charset,
+    /// The language of the messages.
+    pub locale: Locale,
+    /// The format of the diagnostics displayed on stderr.
+    ///
+    /// Functions that render to a writer always use the human format.
+    pub format: ErrorFormat,
+    /// The maximum number of errors shown, see [`diagnostics::limit_errors`]. Every error is shown
+    /// if it is `None`.
+    pub error_limit: Option<usize>,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        DiagnosticsConfig {
+            color: ColorChoice::Auto,
+            charset: Charset::Unicode,
+            locale: Locale::English,
+            format: ErrorFormat::Human,
+            error_limit: None,
+        }
+    }
+}
+
+impl DiagnosticsConfig {
+    fn term_config(&self) -> codespan_reporting::term::Config {
+        let chars = match self.charset {
+            Charset::Unicode => Chars::default(),
+            Charset::Ascii => Chars {
+                source_border_top_left: '-',
+                source_border_top: '-',
+                source_border_left: '|',
+                source_border_left_break: '.',
+                note_bullet: '=',
+                single_primary_caret: '^',
+                single_secondary_caret: '-',
+                multi_primary_caret_start: '^',
+                multi_primary_caret_end: '^',
+                multi_secondary_caret_start: '\'',
+                multi_secondary_caret_end: '\'',
+                multi_top_left: '/',
+                multi_top: '-',
+                multi_bottom_left: '\\',
+                multi_bottom: '-',
+                multi_left: '|',
+                pointer_left: '|',
+            },
+        };
+        codespan_reporting::term::Config {
+            chars,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn display_error(files: &SourceMap, error: &LangError, config: &DiagnosticsConfig) {
+    display_diagnostics(files, Some(error), &[], false, config)
+}
+
+/// Displays the warnings and the error of a program in a single report on stderr.
+///
+/// See [`render_diagnostics`] for details on how the report is rendered, or
+/// [`diagnostics::write_json`] if the format in `config` is [`ErrorFormat::Json`].
+pub fn display_diagnostics(
+    files: &SourceMap,
+    error: Option<&LangError>,
+    warnings: &[Warning],
+    deny_warnings: bool,
+    config: &DiagnosticsConfig,
+) {
+    if config.format == ErrorFormat::Json {
+        let diagnostics = collect_diagnostics(files, error, warnings, deny_warnings, config);
+        diagnostics::write_json(&mut io::stderr().lock(), &diagnostics).unwrap();
+        return;
+    }
+
+    let writer = StandardStream::stderr(stderr_color(config.color));
+    render_diagnostics(
+        files,
+        error,
+        warnings,
+        deny_warnings,
+        config,
+        &mut writer.lock(),
+    )
+    .unwrap();
+}
+
+/// Resolves the color choice for diagnostics written to stderr.
+///
+/// With [`ColorChoice::Auto`], diagnostics are only colored if stderr is a terminal and the
+/// `NO_COLOR` environment variable is not set to a non-empty value.
+fn stderr_color(choice: ColorChoice) -> termcolor::ColorChoice {
+    match choice {
+        ColorChoice::Auto => {
+            let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            if io::stderr().is_terminal() && !no_color {
+                termcolor::ColorChoice::Auto
+            } else {
+                termcolor::ColorChoice::Never
+            }
+        }
+        ColorChoice::Always => termcolor::ColorChoice::Always,
+        ColorChoice::Never => termcolor::ColorChoice::Never,
+    }
+}
+
+/// Returns the diagnostics of a program, without the errors past the limit of `config`.
+fn collect_diagnostics(
+    files: &SourceMap,
+    error: Option<&LangError>,
+    warnings: &[Warning],
+    deny_warnings: bool,
+    config: &DiagnosticsConfig,
+) -> Vec<diagnostics::Diagnostic> {
+    let mut diagnostics =
+        diagnostics::collect(files, error, warnings, deny_warnings, config.locale);
+    if let Some(limit) = config.error_limit {
+        diagnostics::limit_errors(&mut diagnostics, limit, config.locale);
+    }
+    diagnostics
+}
+
+/// The files that diagnostics are rendered in, identified by their position.
+///
+/// The lines of each file are indexed once, when the file is added.
+#[derive(Default)]
+struct RenderedFiles<'s> {
+    files: Vec<(String, LineIndex<'s>)>,
+}
+
+impl<'s> RenderedFiles<'s> {
+    fn add(&mut self, name: String, source: &'s str) {
+        self.files.push((name, LineIndex::new(source)));
+    }
+}
+
+impl<'a, 's: 'a> Files<'a> for RenderedFiles<'s> {
+    type FileId = usize;
+    type Name = &'a str;
+    type Source = &'s str;
+
+    fn name(&'a self, id: usize) -> Option<&'a str> {
+        Some(self.files.get(id)?.0.as_str())
+    }
+
+    fn source(&'a self, id: usize) -> Option<&'s str> {
+        Some(self.files.get(id)?.1.input())
+    }
+
+    fn line_index(&'a self, id: usize, byte_index: usize) -> Option<usize> {
+        Some(self.files.get(id)?.1.line(byte_index))
+    }
+
+    fn line_range(&'a self, id: usize, line_index: usize) -> Option<Range<usize>> {
+        self.files.get(id)?.1.line_range(line_index)
+    }
+}
+
+/// Renders an error to `writer`.
+pub fn render_error(
+    files: &SourceMap,
+    error: &LangError,
+    config: &DiagnosticsConfig,
+    writer: &mut impl WriteColor,
+) -> io::Result<()> {
+    render_diagnostics(files, Some(error), &[], false, config, writer)
+}
+
+/// Renders an error as a string without colors.
+pub fn error_to_string(files: &SourceMap, error: &LangError, config: &DiagnosticsConfig) -> String {
+    let mut writer = NoColor::new(Vec::new());
+    // Writing to a `Vec` cannot fail.
+    render_error(files, error, config, &mut writer).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
 
-    let file_id = files.add(path, input);
+/// Renders the warnings and the error of a program in a single report to `writer`.
+///
+/// Warnings are rendered first, in the order they were found. If `deny_warnings` is `true`,
+/// warnings are rendered as errors. Then, each error inside `error` is rendered as its own
+/// diagnostic. Labels are rendered in the file of `files` they refer to.
+pub fn render_diagnostics(
+    files: &SourceMap,
+    error: Option<&LangError>,
+    warnings: &[Warning],
+    deny_warnings: bool,
+    config: &DiagnosticsConfig,
+    writer: &mut impl WriteColor,
+) -> io::Result<()> {
+    let diagnostics = collect_diagnostics(files, error, warnings, deny_warnings, config);
+    let config = config.term_config();
 
-    let (msg, loc) = match &error {
-        LangError::Ty(error) => ("Type error", error.loc()),
-        LangError::Parse(error) => ("Parsing error", error.span.into()),
-        LangError::Lower(error) => ("Lowering error", error.loc()),
+    // Errors in imported modules are reported in the file of the module, which might not be in
+    // the source map.
+    let mut rendered_files = RenderedFiles::default();
+    for (_, name, source) in files.files() {
+        rendered_files.add(name.to_owned(), source);
+    }
+    if let Some(LangError::Module { path, input, .. }) = error {
+        let path = path.display().to_string();
+        if !rendered_files.files.iter().any(|(name, _)| *name == path) {
+            rendered_files.add(path, input);
+        }
+    }
+    let file_id = |file: &str| {
+        rendered_files
+            .files
+            .iter()
+            .position(|(name, _)| name == file)
+            .unwrap_or(0)
     };
 
-    let diagnostic = Diagnostic::error()
-        .with_message(msg)
-        .with_labels(vec![
-            Label::primary(file_id, loc.start..loc.end).with_message(error.to_string())
-        ]);
+    for diagnostic in diagnostics {
+        let labels = diagnostic
+            .labels
+            .into_iter()
+            .map(
+                |diagnostics::Label {
+                     style,
+                     file,
+                     range,
+                     message,
+                 }| {
+                    let label = match style {
+                        LabelStyle::Primary => Label::primary(file_id(&file), range),
+                        LabelStyle::Secondary => Label::secondary(file_id(&file), range),
+                    };
+                    label.with_message(message)
+                },
+            )
+            .collect();
+        let notes = diagnostic
+            .notes
+            .into_iter()
+            .chain(
+                diagnostic
+                    .suggestions
+                    .into_iter()
+                    .map(|suggestion| suggestion.message),
+            )
+            .collect();
+        let mut rendered = match diagnostic.severity {
+            Severity::Error => Diagnostic::error(),
+            Severity::Warning => Diagnostic::warning(),
+        }
+        .with_message(diagnostic.message)
+        .with_labels(labels)
+        .with_notes(notes);
+        if let Some(code) = diagnostic.code {
+            rendered = rendered.with_code(code);
+        }
+        emit(writer, &config, &rendered_files, &rendered)?;
+    }
 
-    emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
+    Ok(())
 }
@@ -1,23 +1,250 @@
 use structopt::StructOpt;
 
-use std::fs::read_to_string;
+use std::{fs, io, path::PathBuf, process};
 
-use pijama::{display_error, Options};
-use pijama_driver::run;
+use pijama::{
+    disasm, display_diagnostics, display_error, dump, fmt::format, repl::Repl, Command,
+    DiagnosticsConfig, Options, Stage,
+};
+use pijama_driver::{
+    check_with_warnings, config::WarningLevel, emit_js, emit_rust, error_codes::explain, run_timed,
+    timings::Timings, Config, Modules, SourceMap,
+};
 
 fn main() {
     let options = Options::from_args();
+    // The exit code tells scripts whether the command succeeded.
+    if !execute(&options) {
+        process::exit(1);
+    }
+}
+
+/// Executes the command in `options` and returns whether it succeeded.
+fn execute(options: &Options) -> bool {
+    if let Some(Command::Explain { code }) = &options.command {
+        return match explain(code) {
+            Some(explanation) => {
+                print!("{}", explanation);
+                true
+            }
+            None => {
+                eprintln!("`{}` is not a valid error code", code);
+                false
+            }
+        };
+    }
+
+    let config = match Config::load(options.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return false;
+        }
+    };
+
+    let diagnostics = DiagnosticsConfig {
+        color: options.color.unwrap_or(config.color),
+        charset: options.charset,
+        locale: options.locale,
+        format: options.error_format,
+        error_limit: options.error_limit,
+    };
+
+    match &options.command {
+        Some(Command::Explain { .. }) => unreachable!(),
+        Some(Command::Repl) => {
+            let stdin = io::stdin();
+            Repl::new(diagnostics)
+                .run(stdin.lock(), io::stdout())
+                .unwrap();
+            true
+        }
+        Some(Command::Fmt { paths, check }) => fmt(paths, *check, &diagnostics),
+        Some(Command::Check { path }) => check(path, &config, &diagnostics),
+        Some(Command::Dump { stage, path }) => dump_stage(path, *stage, &diagnostics),
+        Some(Command::Disasm { path }) => disassemble(path, &diagnostics),
+        Some(Command::Run { path }) => run(path, options, &config, &diagnostics),
+        // The input path is required when there is no subcommand.
+        None => run(
+            options.path.as_deref().unwrap(),
+            options,
+            &config,
+            &diagnostics,
+        ),
+    }
+}
 
-    let input = match read_to_string(&options.path) {
-        Ok(input) => input,
+/// Loads the program at `path` with its modules, printing the error if it cannot be loaded.
+fn load(path: &str) -> Option<Modules> {
+    match Modules::load(path) {
+        Ok(modules) => Some(modules),
         Err(err) => {
             eprintln!("{}", err);
-            return;
+            None
+        }
+    }
+}
+
+fn run(path: &str, options: &Options, config: &Config, diagnostics: &DiagnosticsConfig) -> bool {
+    let modules = match load(path) {
+        Some(modules) => modules,
+        None => return false,
+    };
+    let files = modules.source_map();
+
+    if options.emit_js {
+        return match emit_js(&modules) {
+            Ok(js) => {
+                print!("{}", js);
+                true
+            }
+            Err(err) => {
+                display_error(&files, &err, diagnostics);
+                false
+            }
+        };
+    }
+
+    if options.emit_rust {
+        return match emit_rust(&modules, "run") {
+            Ok(rust) => {
+                print!("{}\nfn main() {{\n    run();\n}}\n", rust);
+                true
+            }
+            Err(err) => {
+                display_error(&files, &err, diagnostics);
+                false
+            }
+        };
+    }
+
+    // Denied warnings must stop the program before it is evaluated.
+    if config.warnings == WarningLevel::Deny {
+        let (_, warnings) = check_with_warnings(&modules);
+        if !warnings.is_empty() {
+            display_diagnostics(&files, None, &warnings, true, diagnostics);
+            return false;
+        }
+    }
+
+    let mut timings = Timings::default();
+    let mut warnings = Vec::new();
+
+    let config = Config {
+        overflow_check: options.machine_opts.overflow_check || config.overflow_check,
+        ..config.clone()
+    };
+
+    let result = run_timed(&modules, &config, &mut timings, &mut warnings);
+
+    if config.warnings == WarningLevel::Allow {
+        warnings.clear();
+    }
+
+    display_diagnostics(&files, result.as_ref().err(), &warnings, false, diagnostics);
+
+    if options.timings {
+        eprintln!("{}", timings);
+    }
+
+    result.is_ok()
+}
+
+/// Type-checks the program at `path`, failing if it has errors or denied warnings.
+fn check(path: &str, config: &Config, diagnostics: &DiagnosticsConfig) -> bool {
+    let modules = match load(path) {
+        Some(modules) => modules,
+        None => return false,
+    };
+    let (result, mut warnings) = check_with_warnings(&modules);
+    let deny = config.warnings == WarningLevel::Deny;
+    if config.warnings == WarningLevel::Allow {
+        warnings.clear();
+    }
+    display_diagnostics(
+        &modules.source_map(),
+        result.as_ref().err(),
+        &warnings,
+        deny,
+        diagnostics,
+    );
+    result.is_ok() && (!deny || warnings.is_empty())
+}
+
+/// Prints the program at `path` at `stage`.
+fn dump_stage(path: &str, stage: Stage, diagnostics: &DiagnosticsConfig) -> bool {
+    let modules = match load(path) {
+        Some(modules) => modules,
+        None => return false,
+    };
+    match dump(&modules, stage) {
+        Ok(output) => {
+            println!("{}", output);
+            true
+        }
+        Err(err) => {
+            display_error(&modules.source_map(), &err, diagnostics);
+            false
         }
+    }
+}
+
+/// Prints the bytecode of the program at `path`.
+fn disassemble(path: &str, diagnostics: &DiagnosticsConfig) -> bool {
+    let modules = match load(path) {
+        Some(modules) => modules,
+        None => return false,
     };
+    match disasm(&modules) {
+        Ok(Some(output)) => {
+            print!("{}", output);
+            true
+        }
+        Ok(None) => {
+            eprintln!(
+                "{} uses threads, which cannot be compiled to bytecode",
+                path
+            );
+            false
+        }
+        Err(err) => {
+            display_error(&modules.source_map(), &err, diagnostics);
+            false
+        }
+    }
+}
 
-    match run(&input, options.machine_opts.overflow_check) {
-        Ok(()) => (),
-        Err(err) => display_error(&input, &options.path, &err),
+/// Formats the files at `paths` in place, or only reports the ones that are not formatted if
+/// `check` is set.
+fn fmt(paths: &[PathBuf], check: bool, diagnostics: &DiagnosticsConfig) -> bool {
+    let mut formatted = true;
+    for path in paths {
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                formatted = false;
+                continue;
+            }
+        };
+        match format(&input) {
+            Ok(output) if output == input => (),
+            Ok(_) if check => {
+                println!("{} is not formatted", path.display());
+                formatted = false;
+            }
+            Ok(output) => {
+                if let Err(err) = fs::write(path, output) {
+                    eprintln!("{}: {}", path.display(), err);
+                    formatted = false;
+                }
+            }
+            Err(err) => {
+                let files = SourceMap::new(path.display().to_string(), input);
+                display_error(&files, &err, diagnostics);
+                formatted = false;
+            }
+        }
     }
+    formatted
 }
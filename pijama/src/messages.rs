@@ -0,0 +1,475 @@
+//! Catalog of the messages shown in diagnostics.
+//!
+//! Each message has a key and a template for each [`Locale`]. Templates can have named arguments
+//! between braces, like `{name}`, that are replaced when the message is formatted. Messages
+//! missing in a locale fall back to English.
+//!
+//! The descriptions that the parser attaches to parsing errors are only available in English.
+use std::{fmt::Display, str::FromStr};
+
+use pijama_core::{codegen::CodegenError, machine::RuntimeError, mir::LowerError, ty::TyError};
+use pijama_driver::{suggest::Suggestion, LangError, Warning};
+
+/// A language in which diagnostics can be shown.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Locale {
+    English,
+    Spanish,
+}
+
+impl Locale {
+    /// All the available locales.
+    pub const ALL: &'static [Locale] = &[Locale::English, Locale::Spanish];
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::English => EN,
+            Locale::Spanish => ES,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "en" => Ok(Locale::English),
+            "es" => Ok(Locale::Spanish),
+            _ => Err(format!("`{}` is not a supported locale", s)),
+        }
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("title.parse", "Parsing error"),
+    ("title.lower", "Lowering error"),
+    ("title.ty", "Type error"),
+    ("title.codegen", "Code generation error"),
+    ("title.runtime", "Runtime error"),
+    ("title.lint", "Lint"),
+    ("parse.context", "Parsing error: {context}"),
+    ("parse.expected_one", "Parsing error: expected {token}."),
+    (
+        "parse.expected",
+        "Parsing error: expected {tokens} or {last}.",
+    ),
+    ("parse.rule", "Parsing error: Parsing rule `{rule}` failed."),
+    (
+        "lower.anon_with_ty",
+        "Anonymous functions cannot have a return type annotation",
+    ),
+    (
+        "lower.unresolved_import",
+        "Imports can only be used at the top level of programs loaded from files",
+    ),
+    ("lower.unparsed", "This code could not be parsed"),
+    (
+        "ty.mismatch",
+        "Type mismatch: expected `{expected}`, found `{found}`",
+    ),
+    ("ty.unbounded", "Name `{name}` is not bounded"),
+    ("ty.unbounded_again", "`{name}` is not bounded here either"),
+    (
+        "ty.immutable",
+        "Name `{name}` is not mutable and cannot be assigned",
+    ),
+    (
+        "ty.not_printable",
+        "Values of type `{ty}` cannot be converted to a string",
+    ),
+    (
+        "ty.ambiguous_return",
+        "Cannot infer the return type of the recursive function `{name}`",
+    ),
+    (
+        "ty.int_out_of_range",
+        "Integer literal `{literal}` does not fit in a {bits}-bit `Int`",
+    ),
+    (
+        "ty.field_count",
+        "Variant `{variant}` has {expected} fields, but the pattern has {found}",
+    ),
+    (
+        "ty.non_exhaustive",
+        "Match is not exhaustive, missing {patterns}",
+    ),
+    ("ty.origin", "expected `{expected}` because of this"),
+    (
+        "ty.mismatch_note",
+        "expected type `{expected}`\n   found type `{found}`",
+    ),
+    (
+        "codegen.unsupported",
+        "{feature} is not supported by this backend",
+    ),
+    ("runtime.invalid_int", "Cannot read `{line}` as an integer"),
+    ("runtime.assertion_failed", "Assertion failed"),
+    ("runtime.division_by_zero", "Division by zero"),
+    ("runtime.overflow", "Integer overflow"),
+    ("runtime.out_of_fuel", "The evaluation ran out of steps"),
+    (
+        "runtime.capability_disabled",
+        "The `{capability}` capability is disabled",
+    ),
+    ("runtime.deadlock", "Every thread is waiting on a channel"),
+    ("runtime.stack_overflow", "The evaluation ran out of stack"),
+    (
+        "runtime.nondeterministic",
+        "The `{primitive}` primitive is not deterministic",
+    ),
+    ("runtime.invalid_char", "`{value}` is not a valid character"),
+    (
+        "runtime.index_out_of_bounds",
+        "Index {index} is out of bounds for a length of {len}",
+    ),
+    ("runtime.output_failed", "The output could not be written"),
+    (
+        "runtime.invalid_range",
+        "Range `{start}..{end}` ends before it starts",
+    ),
+    ("runtime.call", "while evaluating this call to `{name}`"),
+    ("runtime.anonymous_call", "while evaluating this call"),
+    (
+        "runtime.nested_calls",
+        "while evaluating {count} nested calls to `{name}` here",
+    ),
+    (
+        "runtime.nested_anonymous_calls",
+        "while evaluating {count} nested calls here",
+    ),
+    ("runtime.hidden_calls", "{count} outer calls are not shown"),
+    ("errors.hidden", "{count} more errors are not shown"),
+    ("lint.no_effect", "Expression has no effect"),
+    ("lint.constant_condition", "Condition is always `{value}`"),
+    ("lint.unreachable_arm", "Arm is never taken"),
+    ("lint.unused_binding", "Binding is never used"),
+    ("lint.unused_param", "Parameter is never used"),
+    ("lint.unused_function", "Function is never called"),
+    ("lint.unreachable_branch", "Branch is never taken"),
+    ("lint.self_assignment", "Name is assigned to itself"),
+    ("help.insert", "help: insert `{text}`"),
+    ("help.replace", "help: replace with `{text}`"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("title.parse", "Error de análisis sintáctico"),
+    ("title.lower", "Error de transformación"),
+    ("title.ty", "Error de tipos"),
+    ("title.codegen", "Error de generación de código"),
+    ("title.runtime", "Error de ejecución"),
+    ("title.lint", "Advertencia de estilo"),
+    ("parse.context", "Error de análisis sintáctico: {context}"),
+    ("parse.expected_one", "Error de análisis sintáctico: se esperaba {token}."),
+    (
+        "parse.expected",
+        "Error de análisis sintáctico: se esperaba {tokens} o {last}.",
+    ),
+    (
+        "parse.rule",
+        "Error de análisis sintáctico: la regla `{rule}` falló.",
+    ),
+    (
+        "lower.anon_with_ty",
+        "Las funciones anónimas no pueden tener una anotación del tipo de retorno",
+    ),
+    (
+        "lower.unresolved_import",
+        "Las importaciones solo pueden usarse en el nivel superior de programas cargados desde archivos",
+    ),
+    ("lower.unparsed", "Este código no pudo ser analizado"),
+    (
+        "ty.mismatch",
+        "Los tipos no coinciden: se esperaba `{expected}`, se encontró `{found}`",
+    ),
+    ("ty.unbounded", "El nombre `{name}` no está definido"),
+    ("ty.unbounded_again", "`{name}` tampoco está definido aquí"),
+    ("ty.immutable", "El nombre `{name}` no es mutable y no se puede asignar"),
+    (
+        "ty.not_printable",
+        "Los valores de tipo `{ty}` no se pueden convertir en una cadena",
+    ),
+    (
+        "ty.ambiguous_return",
+        "No se puede inferir el tipo de retorno de la función recursiva `{name}`",
+    ),
+    (
+        "ty.int_out_of_range",
+        "El literal entero `{literal}` no cabe en un `Int` de {bits} bits",
+    ),
+    (
+        "ty.field_count",
+        "La variante `{variant}` tiene {expected} campos, pero el patrón tiene {found}",
+    ),
+    (
+        "ty.non_exhaustive",
+        "La expresión match no es exhaustiva, faltan {patterns}",
+    ),
+    ("ty.origin", "se esperaba `{expected}` por esto"),
+    (
+        "ty.mismatch_note",
+        "tipo esperado `{expected}`\ntipo encontrado `{found}`",
+    ),
+    (
+        "codegen.unsupported",
+        "{feature} no es soportado por este backend",
+    ),
+    ("runtime.invalid_int", "No se puede leer `{line}` como un entero"),
+    ("runtime.assertion_failed", "La aserción falló"),
+    ("runtime.division_by_zero", "División por cero"),
+    ("runtime.overflow", "Desbordamiento de entero"),
+    ("runtime.out_of_fuel", "La evaluación se quedó sin pasos"),
+    (
+        "runtime.capability_disabled",
+        "La capacidad `{capability}` está desactivada",
+    ),
+    ("runtime.deadlock", "Todos los hilos están esperando en un canal"),
+    ("runtime.stack_overflow", "La evaluación se quedó sin pila"),
+    (
+        "runtime.nondeterministic",
+        "La primitiva `{primitive}` no es determinista",
+    ),
+    ("runtime.invalid_char", "`{value}` no es un carácter válido"),
+    (
+        "runtime.index_out_of_bounds",
+        "El índice {index} está fuera de los límites de una longitud {len}",
+    ),
+    ("runtime.output_failed", "No se pudo escribir la salida"),
+    ("runtime.invalid_range", "El rango `{start}..{end}` termina antes de empezar"),
+    ("runtime.call", "al evaluar esta llamada a `{name}`"),
+    ("runtime.anonymous_call", "al evaluar esta llamada"),
+    (
+        "runtime.nested_calls",
+        "al evaluar {count} llamadas anidadas a `{name}` aquí",
+    ),
+    (
+        "runtime.nested_anonymous_calls",
+        "al evaluar {count} llamadas anidadas aquí",
+    ),
+    ("runtime.hidden_calls", "no se muestran {count} llamadas externas"),
+    ("errors.hidden", "no se muestran {count} errores más"),
+    ("lint.no_effect", "La expresión no tiene efecto"),
+    (
+        "lint.constant_condition",
+        "La condición siempre es `{value}`",
+    ),
+    ("lint.unreachable_arm", "La rama nunca se toma"),
+    ("lint.unused_binding", "La variable nunca se usa"),
+    ("lint.unused_param", "El parámetro nunca se usa"),
+    ("lint.unused_function", "La función nunca se llama"),
+    ("lint.unreachable_branch", "Este bloque nunca se ejecuta"),
+    ("lint.self_assignment", "El nombre se asigna a sí mismo"),
+    ("help.insert", "ayuda: inserte `{text}`"),
+    ("help.replace", "ayuda: reemplace con `{text}`"),
+];
+
+/// Returns the keys of all the messages.
+pub fn keys() -> impl Iterator<Item = &'static str> {
+    EN.iter().map(|(key, _)| *key)
+}
+
+/// Returns the template of the message with `key` in `locale`, without falling back to English.
+pub fn translation(locale: Locale, key: &str) -> Option<&'static str> {
+    locale
+        .catalog()
+        .iter()
+        .find(|(other, _)| *other == key)
+        .map(|(_, template)| *template)
+}
+
+/// Formats the message with `key` in `locale`, replacing each argument by its value.
+///
+/// # Panics
+///
+/// This function panics if there is no message with `key`.
+pub fn message(locale: Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let template = translation(locale, key)
+        .or_else(|| translation(Locale::English, key))
+        .unwrap_or_else(|| panic!("There is no message with key `{}`", key));
+    let mut message = template.to_owned();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+
+/// Returns the title of the diagnostic for `error`.
+pub fn error_title(locale: Locale, error: &LangError) -> String {
+    let key = match error {
+        LangError::Parse(_) => "title.parse",
+        LangError::Lower(_) => "title.lower",
+        LangError::Ty(_) => "title.ty",
+        LangError::Codegen(_) => "title.codegen",
+        LangError::Runtime(_) => "title.runtime",
+        LangError::Module { error, .. } | LangError::Traced { error, .. } => {
+            return error_title(locale, error)
+        }
+        LangError::Multiple(errors) => return error_title(locale, &errors[0]),
+    };
+    message(locale, key, &[])
+}
+
+/// Returns the message describing `error`.
+pub fn error_message(locale: Locale, error: &LangError) -> String {
+    match error {
+        LangError::Parse(error) => {
+            let tokens: Vec<_> = error
+                .expected()
+                .iter()
+                .map(|token| format!("`{}`", token))
+                .collect();
+            match (tokens.split_last(), error.context()) {
+                (Some((token, [])), _) => {
+                    message(locale, "parse.expected_one", &[("token", token)])
+                }
+                (Some((last, tokens)), _) => message(
+                    locale,
+                    "parse.expected",
+                    &[("tokens", &tokens.join(", ")), ("last", last)],
+                ),
+                (None, Some(context)) => message(locale, "parse.context", &[("context", &context)]),
+                (None, None) => message(
+                    locale,
+                    "parse.rule",
+                    &[("rule", &format!("{:?}", error.kind()))],
+                ),
+            }
+        }
+        LangError::Lower(LowerError::AnonWithTy(_)) => message(locale, "lower.anon_with_ty", &[]),
+        LangError::Lower(LowerError::UnresolvedImport(_)) => {
+            message(locale, "lower.unresolved_import", &[])
+        }
+        LangError::Lower(LowerError::Unparsed(_)) => message(locale, "lower.unparsed", &[]),
+        LangError::Ty(TyError::Mismatch { expected, found }) => message(
+            locale,
+            "ty.mismatch",
+            &[("expected", &expected.content), ("found", &found.content)],
+        ),
+        LangError::Ty(TyError::Unbounded(name)) => {
+            message(locale, "ty.unbounded", &[("name", &name.content)])
+        }
+        LangError::Ty(TyError::Immutable(name)) => {
+            message(locale, "ty.immutable", &[("name", &name.content)])
+        }
+        LangError::Ty(TyError::NotPrintable(ty)) => {
+            message(locale, "ty.not_printable", &[("ty", &ty.content)])
+        }
+        LangError::Ty(TyError::AmbiguousReturn(name)) => {
+            message(locale, "ty.ambiguous_return", &[("name", &name.content)])
+        }
+        LangError::Ty(TyError::IntOutOfRange { literal, width }) => message(
+            locale,
+            "ty.int_out_of_range",
+            &[("literal", &literal.content), ("bits", &width.bits())],
+        ),
+        LangError::Ty(TyError::FieldCount {
+            variant,
+            expected,
+            found,
+        }) => message(
+            locale,
+            "ty.field_count",
+            &[
+                ("variant", &variant.content),
+                ("expected", expected),
+                ("found", found),
+            ],
+        ),
+        LangError::Ty(TyError::NonExhaustive(missing)) => {
+            let patterns: Vec<_> = missing
+                .content
+                .iter()
+                .map(|pattern| format!("`{}`", pattern))
+                .collect();
+            message(
+                locale,
+                "ty.non_exhaustive",
+                &[("patterns", &patterns.join(", "))],
+            )
+        }
+        LangError::Codegen(CodegenError::Unsupported(feature)) => message(
+            locale,
+            "codegen.unsupported",
+            &[("feature", &feature.content)],
+        ),
+        LangError::Runtime(RuntimeError::InvalidInt(_, line)) => {
+            message(locale, "runtime.invalid_int", &[("line", line)])
+        }
+        LangError::Runtime(RuntimeError::AssertionFailed(_)) => {
+            message(locale, "runtime.assertion_failed", &[])
+        }
+        LangError::Runtime(RuntimeError::DivisionByZero(_)) => {
+            message(locale, "runtime.division_by_zero", &[])
+        }
+        LangError::Runtime(RuntimeError::Overflow(_)) => message(locale, "runtime.overflow", &[]),
+        LangError::Runtime(RuntimeError::OutOfFuel(_)) => {
+            message(locale, "runtime.out_of_fuel", &[])
+        }
+        LangError::Runtime(RuntimeError::CapabilityDisabled(_, capability)) => message(
+            locale,
+            "runtime.capability_disabled",
+            &[("capability", &capability.to_string())],
+        ),
+        LangError::Runtime(RuntimeError::Deadlock(_)) => message(locale, "runtime.deadlock", &[]),
+        LangError::Runtime(RuntimeError::StackOverflow(_)) => {
+            message(locale, "runtime.stack_overflow", &[])
+        }
+        LangError::Runtime(RuntimeError::Nondeterministic(_, prim)) => message(
+            locale,
+            "runtime.nondeterministic",
+            &[("primitive", &prim.to_string())],
+        ),
+        LangError::Runtime(RuntimeError::InvalidChar(_, n)) => {
+            message(locale, "runtime.invalid_char", &[("value", &n.to_string())])
+        }
+        LangError::Runtime(RuntimeError::IndexOutOfBounds(_, index, len)) => message(
+            locale,
+            "runtime.index_out_of_bounds",
+            &[("index", &index.to_string()), ("len", &len.to_string())],
+        ),
+        LangError::Runtime(RuntimeError::OutputFailed(_)) => {
+            message(locale, "runtime.output_failed", &[])
+        }
+        LangError::Runtime(RuntimeError::InvalidRange(_, start, end)) => message(
+            locale,
+            "runtime.invalid_range",
+            &[("start", &start.to_string()), ("end", &end.to_string())],
+        ),
+        LangError::Module { error, .. } | LangError::Traced { error, .. } => {
+            error_message(locale, error)
+        }
+        LangError::Multiple(errors) => {
+            let messages: Vec<_> = errors
+                .iter()
+                .map(|error| error_message(locale, error))
+                .collect();
+            messages.join("\n")
+        }
+    }
+}
+
+/// Returns the message describing `warning`.
+pub fn warning_message(locale: Locale, warning: &Warning) -> String {
+    match warning {
+        Warning::NoEffect(_) => message(locale, "lint.no_effect", &[]),
+        Warning::ConstantCondition(_, value) => {
+            message(locale, "lint.constant_condition", &[("value", value)])
+        }
+        Warning::UnreachableArm(_) => message(locale, "lint.unreachable_arm", &[]),
+        Warning::UnusedBinding(_) => message(locale, "lint.unused_binding", &[]),
+        Warning::UnusedParam(_) => message(locale, "lint.unused_param", &[]),
+        Warning::UnusedFunction(_) => message(locale, "lint.unused_function", &[]),
+        Warning::UnreachableBranch(_) => message(locale, "lint.unreachable_branch", &[]),
+        Warning::SelfAssignment(_) => message(locale, "lint.self_assignment", &[]),
+    }
+}
+
+/// Returns the help message for `suggestion`.
+pub fn suggestion_message(locale: Locale, suggestion: &Suggestion) -> String {
+    let key = if suggestion.loc.start == suggestion.loc.end {
+        "help.insert"
+    } else {
+        "help.replace"
+    };
+    message(locale, key, &[("text", &suggestion.replacement.trim())])
+}
@@ -0,0 +1,212 @@
+//! The interactive mode of the compiler, started with `pijama repl`.
+//!
+//! Each input is compiled together with the definitions of the previous inputs, so they can use
+//! the functions, enums and bindings defined before them. The value and type of expressions are
+//! printed after evaluating them.
+//!
+//! Bindings to integers, booleans, characters and `unit` are kept as their value, so evaluating
+//! them again does not repeat their side effects. Other definitions are kept as they were
+//! written and evaluated again with each input. Inputs ending with an expression are not kept.
+//!
+//! Inputs starting with `:` are commands:
+//!
+//! - `:type <expression>` prints the type of the expression without evaluating it.
+//! - `:ast <code>` prints the syntax tree of the code.
+//! - `:quit` ends the session.
+use std::io::{self, BufRead, Write};
+
+use pijama_ast::{Located, Node};
+use pijama_core::{
+    lir::Term,
+    machine::{env::Env, MachineBuilder, Value},
+    parser::parse,
+    ty::Ty,
+};
+use pijama_driver::{
+    check,
+    pipeline::{compile, CompiledProgram, Options},
+    LangError, SourceMap,
+};
+
+use crate::{error_to_string, DiagnosticsConfig};
+
+/// The name of the inputs in diagnostics.
+const NAME: &str = "<repl>";
+
+/// A session of the interactive mode.
+#[derive(Debug, Clone, Default)]
+pub struct Repl {
+    /// The definitions of the previous inputs, one per line.
+    definitions: String,
+    config: DiagnosticsConfig,
+}
+
+impl Repl {
+    /// Creates a session without definitions, rendering errors with `config`.
+    pub fn new(config: DiagnosticsConfig) -> Self {
+        Repl {
+            definitions: String::new(),
+            config,
+        }
+    }
+
+    /// Reads inputs from `input` until it ends or `:quit` is entered, writing a prompt before each
+    /// one and their results to `output`.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "> ")?;
+        output.flush()?;
+        for line in input.lines() {
+            if !self.eval(&line?, &mut output)? {
+                break;
+            }
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Evaluates a single input or command, writing its result and the output of the program to
+    /// `output`.
+    ///
+    /// Returns `false` if the input is `:quit`.
+    pub fn eval(&mut self, input: &str, output: &mut impl Write) -> io::Result<bool> {
+        let input = input.trim();
+        if let Some(command) = input.strip_prefix(':') {
+            let (name, arg) = match command.find(char::is_whitespace) {
+                Some(index) => (&command[..index], command[index..].trim()),
+                None => (command, ""),
+            };
+            match name {
+                "quit" | "q" => return Ok(false),
+                "type" | "t" => self.show_type(arg, output)?,
+                "ast" => match parse(arg) {
+                    Ok(block) => writeln!(output, "{:#?}", block.content)?,
+                    Err(error) => self.report(arg, &error.into(), output)?,
+                },
+                _ => writeln!(output, "Unknown command `:{}`", name)?,
+            }
+        } else if !input.is_empty() {
+            self.eval_code(input, output)?;
+        }
+        Ok(true)
+    }
+
+    /// Prints the type of `expr`.
+    fn show_type(&self, expr: &str, output: &mut impl Write) -> io::Result<()> {
+        let program = self.program(expr);
+        match check(&program) {
+            Ok(ty) => writeln!(output, "{}", ty),
+            Err(error) => self.report(&program, &error, output),
+        }
+    }
+
+    /// Evaluates `input`, keeping it as a definition if it does not end with an expression.
+    fn eval_code(&mut self, input: &str, output: &mut impl Write) -> io::Result<()> {
+        let nodes = match parse(input) {
+            Ok(block) => block.content,
+            Err(error) => return self.report(input, &error.into(), output),
+        };
+        let last = nodes.back().map(defined);
+
+        // Definitions are evaluated followed by the name they define, to show its value.
+        let program = match &last {
+            Some(Some(Defined::Binding(name, _)) | Some(Defined::Function(name))) => {
+                self.program(&format!("{}\n{}", input, name))
+            }
+            Some(Some(Defined::Enum)) => self.program(&format!("{}\nunit", input)),
+            _ => self.program(input),
+        };
+        let (ty, term) = match evaluate(&program, output) {
+            Ok(result) => result,
+            Err(error) => return self.report(&program, &error, output),
+        };
+
+        match last {
+            Some(Some(Defined::Binding(name, mutable))) => {
+                match Value::from_term(&term, &ty) {
+                    // Bindings are kept as their value if it can be written as a literal.
+                    Some(value) if nodes.len() == 1 => {
+                        let keyword = if mutable { "let mut " } else { "" };
+                        self.define(&format!("{}{}: {} = {}", keyword, name, ty, value));
+                        writeln!(output, "{}: {} = {}", name, ty, value)?;
+                    }
+                    _ => {
+                        self.define(input);
+                        writeln!(output, "{}: {}", name, ty)?;
+                    }
+                }
+            }
+            Some(Some(Defined::Function(name))) => {
+                self.define(input);
+                writeln!(output, "{}: {}", name, ty)?;
+            }
+            Some(Some(Defined::Enum)) => self.define(input),
+            _ if matches!(ty, Ty::Arrow(..)) => writeln!(output, "<function>: {}", ty)?,
+            _ => writeln!(output, "{}: {}", show(&term, &ty), ty)?,
+        }
+        Ok(())
+    }
+
+    /// Returns the program with the previous definitions followed by `input`.
+    fn program(&self, input: &str) -> String {
+        format!("{}{}", self.definitions, input)
+    }
+
+    fn define(&mut self, definition: &str) {
+        self.definitions.push_str(definition);
+        self.definitions.push('\n');
+    }
+
+    /// Writes the error found in `program`.
+    fn report(&self, program: &str, error: &LangError, output: &mut impl Write) -> io::Result<()> {
+        let files = SourceMap::new(NAME, program);
+        write!(output, "{}", error_to_string(&files, error, &self.config))
+    }
+}
+
+/// What the last node of an input defines.
+enum Defined<'a> {
+    /// A binding with its name and whether it is mutable.
+    Binding(&'a str, bool),
+    Function(&'a str),
+    Enum,
+}
+
+/// Returns what `node` defines, or `None` if it is an expression.
+fn defined<'a>(node: &Located<Node<'a>>) -> Option<Defined<'a>> {
+    match &node.content {
+        Node::LetBind(annotation, _) => Some(Defined::Binding(annotation.item.content.0, false)),
+        Node::LetMut(annotation, _) => Some(Defined::Binding(annotation.item.content.0, true)),
+        Node::FnDef(name, ..) => Some(Defined::Function(name.content.0)),
+        Node::EnumDef(..) => Some(Defined::Enum),
+        _ => None,
+    }
+}
+
+/// Returns how the value `term` of type `ty` is written.
+///
+/// Values are untyped once compiled, e.g. `true` and `1` are the same value, so their type tells
+/// how to write them.
+fn show(term: &Term, ty: &Ty) -> String {
+    match (ty, term) {
+        (Ty::List(ty), Term::List(elems)) => {
+            let elems: Vec<_> = elems.iter().map(|elem| show(elem, ty)).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        _ => match Value::from_term(term, ty) {
+            Some(value) => value.to_string(),
+            None => term.to_string(),
+        },
+    }
+}
+
+/// Compiles and evaluates `program`, writing its output to `output`, and returns its type and
+/// the term it evaluates to.
+fn evaluate(program: &str, output: &mut impl Write) -> Result<(Ty, Term), LangError> {
+    let CompiledProgram { ty, lir, .. } = compile(program, &Options::default())?;
+    let mut machine = MachineBuilder::default()
+        .with_env(Env::new(&mut *output))
+        .build();
+    let term = machine.evaluate(lir)?;
+    Ok((ty, term))
+}
@@ -0,0 +1,721 @@
+use codespan_reporting::term::termcolor::Buffer;
+
+use pijama::{
+    diagnostics::{self, LabelStyle, Severity},
+    disasm, dump, error_to_string,
+    fmt::{format, format_with_width},
+    messages::{self, Locale},
+    render_diagnostics, render_error,
+    repl::Repl,
+    Charset, DiagnosticsConfig, Stage,
+};
+use pijama_ast::Location;
+use pijama_core::machine::RuntimeError;
+use pijama_driver::{check, check_with_warnings, run, Call, LangError, SourceMap};
+
+#[test]
+fn error_as_string() {
+    let input = "x: Int = true";
+    let error = check(input).unwrap_err();
+    let expected = "\
+error[E0002]: Type error
+  ┌─ main.pj:1:4
+  │
+1 │ x: Int = true
+  │    -     ^ Type mismatch: expected `Int`, found `Bool`
+  │    │      
+  │    expected `Int` because of this
+  │
+  = expected type `Int`
+       found type `Bool`
+
+";
+    assert_eq!(
+        expected,
+        error_to_string(
+            &SourceMap::new("main.pj", input),
+            &error,
+            &DiagnosticsConfig::default()
+        )
+    );
+}
+
+#[test]
+fn error_with_colors() {
+    let input = "print(x)";
+    let error = check(input).unwrap_err();
+    let mut buffer = Buffer::ansi();
+    render_error(
+        &SourceMap::new("main.pj", input),
+        &error,
+        &DiagnosticsConfig::default(),
+        &mut buffer,
+    )
+    .unwrap();
+    let output = String::from_utf8(buffer.into_inner()).unwrap();
+    assert!(output.contains("\u{1b}["), "{:?}", output);
+    assert!(output.contains("Name `x` is not bounded"), "{:?}", output);
+}
+
+#[test]
+fn warnings_before_error() {
+    let input = "unit\nprint(1)";
+    let (_, warnings) = check_with_warnings(input);
+    let error = check("print(y)").unwrap_err();
+    let mut buffer = Buffer::no_color();
+    render_diagnostics(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &warnings,
+        false,
+        &DiagnosticsConfig::default(),
+        &mut buffer,
+    )
+    .unwrap();
+    let output = String::from_utf8(buffer.into_inner()).unwrap();
+    let warning = output.find("warning: Lint").unwrap();
+    let error = output.find("error[E0003]").unwrap();
+    assert!(warning < error, "{}", output);
+}
+
+#[test]
+fn error_in_other_file() {
+    let mut files = SourceMap::new("main.pj", "print(divide::divide(1, 0))");
+    let divide = files.add(
+        "divide.pj",
+        "fn divide(a: Int, b: Int): Int do\n    a / b\nend\n",
+    );
+    let loc = Location::new(38, 43).in_file(divide);
+    let error = LangError::Runtime(RuntimeError::DivisionByZero(loc));
+    let output = error_to_string(&files, &error, &DiagnosticsConfig::default());
+    assert!(output.contains("┌─ divide.pj:2:5\n"), "{}", output);
+    assert!(output.contains("2 │     a / b\n"), "{}", output);
+
+    let diagnostics = diagnostics::collect(&files, Some(&error), &[], false, Locale::English);
+    assert_eq!("divide.pj", diagnostics[0].file);
+    assert_eq!("divide.pj", diagnostics[0].labels[0].file);
+}
+
+#[test]
+fn runtime_stack_trace() {
+    let input = "\
+fn divide(a: Int, b: Int): Int do
+    a / b
+end
+
+fn down(n: Int): Int do
+    if n == 0 do divide(n, 0) else down(n - 1) + 1 end
+end
+
+down(3)
+";
+    let error = run(input, false).unwrap_err();
+    let expected = "\
+error[E0014]: Runtime error
+  ┌─ main.pj:2:5
+  │
+2 │     a / b
+  │     ^^^^^ Division by zero
+  ·
+6 │     if n == 0 do divide(n, 0) else down(n - 1) + 1 end
+  │                  ------------      ----------- while evaluating 3 nested calls to `down` here
+  │                  │                  
+  │                  while evaluating this call to `divide`
+  ·
+9 │ down(3)
+  │ ------- while evaluating this call to `down`
+
+";
+    assert_eq!(
+        expected,
+        error_to_string(
+            &SourceMap::new("main.pj", input),
+            &error,
+            &DiagnosticsConfig::default()
+        )
+    );
+}
+
+#[test]
+fn long_stack_trace() {
+    let input = "1 / 0";
+    let calls = (0..12)
+        .map(|index| Call {
+            name: Some(format!("f{}", index)),
+            loc: Location::new(index % 5, 5),
+        })
+        .collect();
+    let error = LangError::Traced {
+        error: Box::new(LangError::Runtime(RuntimeError::DivisionByZero(
+            Location::new(0, 5),
+        ))),
+        calls,
+    };
+    let diagnostics = diagnostics::collect(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &[],
+        false,
+        Locale::English,
+    );
+    let labels = &diagnostics[0].labels;
+    assert_eq!(11, labels.len());
+    assert_eq!("while evaluating this call to `f0`", labels[1].message);
+    assert_eq!(["2 outer calls are not shown"], diagnostics[0].notes[..]);
+}
+
+#[test]
+fn one_diagnostic_per_error() {
+    let input = "print(x)\nprint(y)";
+    let error = check(input).unwrap_err();
+    let output = error_to_string(
+        &SourceMap::new("main.pj", input),
+        &error,
+        &DiagnosticsConfig::default(),
+    );
+    assert_eq!(2, output.matches("error[E0003]").count(), "{}", output);
+    assert!(output.contains("Name `x` is not bounded"), "{}", output);
+    assert!(output.contains("Name `y` is not bounded"), "{}", output);
+}
+
+#[test]
+fn unbound_name_used_again() {
+    let input = "print(x)\nprint(x + 1)\nprint(y)";
+    let error = check(input).unwrap_err();
+    let diagnostics = diagnostics::collect(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &[],
+        false,
+        Locale::English,
+    );
+    assert_eq!(2, diagnostics.len());
+    let labels = &diagnostics[0].labels;
+    assert_eq!(2, labels.len());
+    assert_eq!(LabelStyle::Secondary, labels[1].style);
+    assert_eq!(15..16, labels[1].range);
+    assert_eq!("`x` is not bounded here either", labels[1].message);
+}
+
+#[test]
+fn error_limit() {
+    let input = "print(x)\nprint(y)\nprint(z)";
+    let error = check(input).unwrap_err();
+    let (_, warnings) = check_with_warnings("unit\nprint(1)");
+    let mut diagnostics = diagnostics::collect(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &warnings,
+        false,
+        Locale::English,
+    );
+    diagnostics::limit_errors(&mut diagnostics, 1, Locale::English);
+    let severities: Vec<_> = diagnostics.iter().map(|d| d.severity).collect();
+    assert_eq!(vec![Severity::Warning, Severity::Error], severities);
+    assert_eq!(["2 more errors are not shown"], diagnostics[1].notes[..]);
+
+    let config = DiagnosticsConfig {
+        error_limit: Some(2),
+        ..Default::default()
+    };
+    let output = error_to_string(&SourceMap::new("main.pj", input), &error, &config);
+    assert_eq!(2, output.matches("error[E0003]").count(), "{}", output);
+    assert!(output.contains("1 more errors are not shown"), "{}", output);
+}
+
+#[test]
+fn ascii_charset() {
+    let input = "x: Int = true";
+    let error = check(input).unwrap_err();
+    let config = DiagnosticsConfig {
+        charset: Charset::Ascii,
+        ..Default::default()
+    };
+    let output = error_to_string(&SourceMap::new("main.pj", input), &error, &config);
+    assert!(output.is_ascii(), "{}", output);
+    assert!(output.contains("  -- main.pj:1:4\n"), "{}", output);
+}
+
+/// Returns the names of the arguments of a message template, sorted.
+fn placeholders(template: &str) -> Vec<&str> {
+    let mut names: Vec<_> = template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split('}').next())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn catalogs_are_complete() {
+    for key in messages::keys() {
+        let english = messages::translation(Locale::English, key).unwrap();
+        for locale in Locale::ALL {
+            let template = messages::translation(*locale, key)
+                .unwrap_or_else(|| panic!("{:?} has no message for `{}`", locale, key));
+            assert_eq!(placeholders(english), placeholders(template), "{}", key);
+        }
+    }
+}
+
+#[test]
+fn english_messages_match_errors() {
+    for input in [
+        "x: Int = true",
+        "print(x)",
+        "fn(x: Int): Int do x end",
+        "x = (1",
+        "f(1, 2 3)",
+        "print(x)\nprint(y)",
+    ] {
+        let error = check(input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            messages::error_message(Locale::English, &error)
+        );
+    }
+}
+
+#[test]
+fn localized_error() {
+    let input = "print(x)";
+    let error = check(input).unwrap_err();
+    let config = DiagnosticsConfig {
+        locale: Locale::Spanish,
+        ..Default::default()
+    };
+    let output = error_to_string(&SourceMap::new("main.pj", input), &error, &config);
+    assert!(
+        output.starts_with("error[E0003]: Error de tipos\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("El nombre `x` no está definido"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn structured_diagnostics() {
+    let input = "x: Int = true";
+    let error = check(input).unwrap_err();
+    let diagnostics = diagnostics::collect(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &[],
+        false,
+        Locale::English,
+    );
+    assert_eq!(1, diagnostics.len());
+    let diagnostic = &diagnostics[0];
+    assert_eq!(Severity::Error, diagnostic.severity);
+    assert_eq!(Some("E0002"), diagnostic.code);
+    assert_eq!("main.pj", diagnostic.file);
+    assert_eq!(9..10, diagnostic.range);
+    let styles: Vec<_> = diagnostic.labels.iter().map(|label| label.style).collect();
+    assert_eq!(vec![LabelStyle::Primary, LabelStyle::Secondary], styles);
+    assert_eq!(1, diagnostic.notes.len());
+}
+
+#[test]
+fn condition_origin() {
+    let input = "while  1 do unit end";
+    let error = check(input).unwrap_err();
+    let diagnostics = diagnostics::collect(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &[],
+        false,
+        Locale::English,
+    );
+    let origin = &diagnostics[0].labels[1];
+    assert_eq!(LabelStyle::Secondary, origin.style);
+    assert_eq!(0..5, origin.range);
+    assert_eq!("expected `Bool` because of this", origin.message);
+}
+
+#[test]
+fn json_diagnostics() {
+    let input = "unit\nfact = 3\nprint(fcat)";
+    let (_, warnings) = check_with_warnings("unit\nprint(1)");
+    let error = check(input).unwrap_err();
+    let diagnostics = diagnostics::collect(
+        &SourceMap::new("main.pj", input),
+        Some(&error),
+        &warnings,
+        false,
+        Locale::English,
+    );
+    let mut output = Vec::new();
+    diagnostics::write_json(&mut output, &diagnostics).unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(2, lines.len());
+    assert_eq!("warning", lines[0]["severity"]);
+    assert!(lines[0]["code"].is_null());
+    assert_eq!("error", lines[1]["severity"]);
+    assert_eq!("E0003", lines[1]["code"]);
+    assert_eq!(20, lines[1]["range"]["start"]);
+    assert_eq!("primary", lines[1]["labels"][0]["style"]);
+    assert_eq!("fact", lines[1]["suggestions"][0]["replacement"]);
+}
+
+/// Evaluates each input in a new REPL session and returns the output of each one.
+fn repl(inputs: &[&str]) -> Vec<String> {
+    let mut repl = Repl::new(DiagnosticsConfig::default());
+    inputs
+        .iter()
+        .map(|input| {
+            let mut output = Vec::new();
+            assert!(repl.eval(input, &mut output).unwrap());
+            String::from_utf8(output).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn repl_definitions() {
+    let outputs = repl(&[
+        "x = 2 * 3",
+        "fn double(n: Int): Int do n * 2 end",
+        "double(x) > 10",
+        "[x, x + 1]",
+        "double",
+    ]);
+    assert_eq!(
+        vec![
+            "x: Int = 6\n",
+            "double: Int -> Int\n",
+            "true: Bool\n",
+            "[6, 7]: [Int]\n",
+            "<function>: Int -> Int\n",
+        ],
+        outputs
+    );
+}
+
+#[test]
+fn repl_side_effects_run_once() {
+    let outputs = repl(&["x = print(1)", "print(2)", "x"]);
+    assert_eq!(
+        vec!["1\nx: Unit = unit\n", "2\nunit: Unit\n", "unit: Unit\n"],
+        outputs
+    );
+}
+
+#[test]
+fn repl_commands() {
+    let outputs = repl(&["x = true", ":type x", ":ast 1", ":nope"]);
+    assert_eq!("Bool\n", outputs[1]);
+    assert!(outputs[2].contains("Number("), "{}", outputs[2]);
+    assert_eq!("Unknown command `:nope`\n", outputs[3]);
+}
+
+#[test]
+fn repl_errors_are_not_kept() {
+    let outputs = repl(&["x: Int = true", "x"]);
+    assert!(outputs[0].starts_with("error[E0002]"), "{}", outputs[0]);
+    assert!(
+        outputs[1].contains("Name `x` is not bounded"),
+        "{}",
+        outputs[1]
+    );
+}
+
+#[test]
+fn repl_quit() {
+    let mut output = Vec::new();
+    Repl::default()
+        .run(&b"1\n:quit\n2\n"[..], &mut output)
+        .unwrap();
+    assert_eq!("> 1: Int\n> ", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn fmt_layout() {
+    let input = "\
+fn  fact(n:Int):Int do
+  if n<=0 do 1 else n*fact(n-1) end
+end
+
+
+x=-(1+2)*3
+print( [fact(x) ,(1-2)-(3-4)] )
+";
+    let expected = "\
+fn fact(n: Int): Int do
+    if n <= 0 do 1 else n * fact(n - 1) end
+end
+
+x = -((1 + 2) * 3)
+print([fact(x), 1 - 2 - (3 - 4)])
+";
+    assert_eq!(expected, format(input).unwrap());
+    assert_eq!(expected, format(expected).unwrap());
+}
+
+#[test]
+fn fmt_keeps_comments() {
+    let input = "\
+# Counts down.
+## Prints `n`.
+fn show(n: Int) do # not a `# comment` in a string: \"#\"
+print(\"#{n}\")
+end
+if /* fast */ n > 0 do show(0x1F) end
+";
+    let expected = "\
+# Counts down.
+## Prints `n`.
+fn show(n: Int) do
+    # not a `# comment` in a string: \"#\"
+    print(\"#{n}\")
+end
+if /* fast */ n > 0 do show(0x1F) end
+";
+    assert_eq!(expected, format(input).unwrap());
+}
+
+#[test]
+fn fmt_wraps_long_lines() {
+    let input = "total = add(first_argument, second_argument, fn(x) do x + 1 end)";
+    let expected = "\
+total = add(
+    first_argument,
+    second_argument,
+    fn(x) do x + 1 end
+)
+";
+    assert_eq!(expected, format_with_width(input, 40).unwrap());
+    assert_eq!(format!("{}\n", input), format(input).unwrap());
+}
+
+#[test]
+fn fmt_let_in() {
+    let input = "y = let  x=1 in x+1\nprint(let x: Int = 2 in\n    x*x)\n";
+    let expected = "y = let x = 1 in x + 1\nprint(let x: Int = 2 in x * x)\n";
+    assert_eq!(expected, format(input).unwrap());
+}
+
+#[test]
+fn fmt_block() {
+    let input = "y = 1 + do x=1\n  x*2 end\nz = (do 1 end) + 2\n(do 1 end) + 2\n";
+    let expected = "y = 1 + do\n    x = 1\n    x * 2\nend\nz = do 1 end + 2\n(do 1 end + 2)\n";
+    assert_eq!(expected, format(input).unwrap());
+}
+
+#[test]
+fn fmt_operator() {
+    let input = "add = ( + )\ninc = (_+1)\ndouble = ( (x+1)*_ )\nf = (_ - (1 - x))\n";
+    let expected = "add = (+)\ninc = (_ + 1)\ndouble = ((x + 1) * _)\nf = (_ - (1 - x))\n";
+    assert_eq!(expected, format(input).unwrap());
+}
+
+#[test]
+fn fmt_parsing_error() {
+    let error = format("x = (1 +").unwrap_err();
+    assert!(matches!(error, LangError::Parse(_)), "{:?}", error);
+}
+
+#[test]
+fn dump_stages() {
+    let input = "x = 1\nx + 2";
+    let ast = dump(input, Stage::Ast).unwrap();
+    assert!(
+        ast.starts_with("[\n") && ast.contains("LetBind("),
+        "{}",
+        ast
+    );
+    assert_eq!(
+        "let x : Int @0..5\n  lit 1 : Int @4..5\n  binary + : Int @6..11\n    var x : Int @6..7\n    lit 2 : Int @10..11",
+        dump(input, Stage::Mir).unwrap()
+    );
+    assert_eq!("(let a = 1 in (a + 2))", dump(input, Stage::Lir).unwrap());
+
+    for stage in [Stage::Mir, Stage::Lir] {
+        let error = dump("x: Int = true", stage).unwrap_err();
+        assert!(matches!(error, LangError::Ty(_)), "{:?}", error);
+    }
+    assert_eq!("ast".parse(), Ok(Stage::Ast));
+    assert!("hir".parse::<Stage>().is_err());
+}
+
+#[test]
+fn disasm_bytecode() {
+    let output = disasm("print(1 + 2)").unwrap().unwrap();
+    assert!(output.starts_with("chunk 0:\n"), "{}", output);
+    assert!(
+        output.contains("int 1") && output.contains("prim print"),
+        "{}",
+        output
+    );
+    assert_eq!(None, disasm("recv(channel())").unwrap());
+    assert!(matches!(disasm("x: Int = true"), Err(LangError::Ty(_))));
+}
+
+#[test]
+fn cli_exit_codes() {
+    let dir = std::env::temp_dir().join(format!("pijama_cli_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let valid = dir.join("valid.pj");
+    let invalid = dir.join("invalid.pj");
+    std::fs::write(&valid, "print(1 + 2)\n").unwrap();
+    std::fs::write(&invalid, "x: Int = true\n").unwrap();
+
+    let pijama = |args: &[&str], path: &std::path::Path| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_pijama"))
+            .args(args)
+            .arg(path)
+            .output()
+            .unwrap()
+    };
+
+    for args in [
+        &[][..],
+        &["run"],
+        &["check"],
+        &["dump", "--stage=lir"],
+        &["disasm"],
+    ]
+    .iter()
+    {
+        assert!(pijama(args, &valid).status.success(), "{:?}", args);
+        assert!(!pijama(args, &invalid).status.success(), "{:?}", args);
+    }
+    assert_eq!(b"3\n", &pijama(&["run"], &valid).stdout[..]);
+    assert!(pijama(&["check"], &valid).stdout.is_empty());
+    assert!(!pijama(&["run"], &dir.join("missing.pj")).status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn config_fuel() {
+    let dir = std::env::temp_dir().join(format!("pijama_fuel_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("loop.pj");
+    let config = dir.join("pijama.toml");
+    std::fs::write(
+        &program,
+        "fn spin(n: Int): Int do spin(n + 1) end\nspin(0)\n",
+    )
+    .unwrap();
+    std::fs::write(&config, "fuel = 1000\n").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_pijama"))
+        .arg("--config")
+        .arg(&config)
+        .arg("run")
+        .arg(&program)
+        .env_remove("PIJAMA_FUEL")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("ran out of steps"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn config_opt_level() {
+    let dir = std::env::temp_dir().join(format!("pijama_opt_level_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("main.pj");
+    std::fs::write(&program, "print(1 + 2)\n").unwrap();
+
+    let stderr = |opt_level: u8| {
+        let config = dir.join(format!("opt{}.toml", opt_level));
+        std::fs::write(&config, format!("opt_level = {}\n", opt_level)).unwrap();
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_pijama"))
+            .arg("--config")
+            .arg(&config)
+            .arg("--timings")
+            .arg("run")
+            .arg(&program)
+            .env_remove("PIJAMA_OPT_LEVEL")
+            .output()
+            .unwrap();
+        assert_eq!(b"3\n", &output.stdout[..]);
+        String::from_utf8(output.stderr).unwrap()
+    };
+    // The optimization phase is only timed if some pass runs.
+    assert!(!stderr(0).contains("optimization"));
+    assert!(stderr(1).contains("optimization"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn timings_are_printed_to_stderr() {
+    let dir = std::env::temp_dir().join(format!("pijama_timings_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let valid = dir.join("valid.pj");
+    let invalid = dir.join("invalid.pj");
+    std::fs::write(&valid, "print(1 + 2)\n").unwrap();
+    std::fs::write(&invalid, "print(1 + true)\n").unwrap();
+
+    let pijama = |args: &[&str], program: &std::path::Path| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_pijama"))
+            .args(args)
+            .arg("run")
+            .arg(program)
+            .output()
+            .unwrap()
+    };
+    let output = pijama(&["--timings"], &valid);
+    assert_eq!(b"3\n", &output.stdout[..]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for phase in &["parsing", "type checking", "evaluation", "total"] {
+        assert!(stderr.contains(phase), "{:?}", stderr);
+    }
+    assert!(pijama(&[], &valid).stderr.is_empty());
+
+    // The table is printed after the error, with the phases that ran before it.
+    let output = pijama(&["--timings", "--color", "never"], &invalid);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let error = stderr.find("error[E0002]").unwrap();
+    let table = stderr.find("type checking").unwrap();
+    assert!(error < table, "{:?}", stderr);
+    assert!(!stderr.contains("evaluation"), "{:?}", stderr);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn piped_stderr_is_not_colored() {
+    let dir = std::env::temp_dir().join(format!("pijama_color_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("invalid.pj");
+    std::fs::write(&program, "print(x)\n").unwrap();
+
+    let stderr = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_pijama"))
+            .args(args)
+            .arg("check")
+            .arg(&program)
+            .env_remove("NO_COLOR")
+            .env_remove("PIJAMA_COLOR")
+            .env("TERM", "xterm-256color")
+            .output()
+            .unwrap();
+        String::from_utf8(output.stderr).unwrap()
+    };
+    let auto = stderr(&[]);
+    assert!(auto.contains("Name `x` is not bounded"), "{:?}", auto);
+    assert!(!auto.contains('\u{1b}'), "{:?}", auto);
+    assert!(stderr(&["--color", "always"]).contains('\u{1b}'));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,5 +1,9 @@
 //! An assortment of checks that are done before lowering.
-use crate::{ty::TyAnnotation, visitor::NodeVisitor, Block, Located, Name, Node};
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    ty::TyAnnotation, visitor::NodeVisitor, Arm, Block, Located, Name, Node, Pattern, Variant,
+};
 
 /// Checks if a function is recursive or not.
 pub struct RecursionChecker<'a> {
@@ -73,6 +77,15 @@ impl<'a> NodeVisitor<'a> for RecursionChecker<'a> {
         self.super_let_bind(annotation, body);
     }
 
+    fn visit_let_mut(&mut self, annotation: &TyAnnotation<Name<'a>>, body: &Located<Node<'a>>) {
+        // Mutable bindings shadow the target name like any other binding.
+        if annotation.item.content == self.name {
+            self.is_shadowed = true;
+        }
+        // Keep visiting
+        self.super_let_mut(annotation, body);
+    }
+
     fn visit_fn_def(
         &mut self,
         name: &Located<Name<'a>>,
@@ -88,6 +101,51 @@ impl<'a> NodeVisitor<'a> for RecursionChecker<'a> {
         self.super_fn_def(name, args, body);
     }
 
+    fn visit_enum_def(&mut self, name: &Located<Name<'a>>, variants: &[Variant<'a>]) {
+        // If one of the variants has the target name, the latter is being shadowed by its
+        // constructor in the current scope.
+        if variants
+            .iter()
+            .any(|variant| variant.name.content == self.name)
+        {
+            self.is_shadowed = true;
+        }
+        // Keep visiting
+        self.super_enum_def(name, variants);
+    }
+
+    fn visit_arm(&mut self, arm: &Arm<'a>) {
+        // The names bound by the pattern only exist inside the body of the arm.
+        self.push_scope();
+        if let Pattern::Variant(_, binders) = &arm.pattern.content {
+            if binders.iter().any(|binder| binder.content == self.name) {
+                self.is_shadowed = true;
+            }
+        }
+        // Keep visiting
+        self.super_arm(arm);
+        self.pop_scope();
+    }
+
+    fn visit_for(
+        &mut self,
+        name: &Located<Name<'a>>,
+        start: &Located<Node<'a>>,
+        end: &Located<Node<'a>>,
+        body: &Located<Block<'a>>,
+    ) {
+        self.visit_node(start);
+        self.visit_node(end);
+        // The loop variable only exists inside the body of the loop, where it shadows the target
+        // name if they are the same.
+        self.push_scope();
+        if name.content == self.name {
+            self.is_shadowed = true;
+        }
+        self.visit_block(&body.content);
+        self.pop_scope();
+    }
+
     fn visit_block(&mut self, block: &Block<'a>) {
         // Entering a block means that we need to push a new scope into the stack because the
         // bindings done inside the block can only exist in that block.
@@ -99,3 +157,98 @@ impl<'a> NodeVisitor<'a> for RecursionChecker<'a> {
         self.pop_scope();
     }
 }
+
+/// Groups consecutive function definitions by the functions they call.
+///
+/// Each function is given by its name, parameters and body, and the names of the functions must
+/// be different. A function calls another one if the name of the latter appears in the body of
+/// the former and it is not shadowed by a parameter or a binding. The returned groups are the
+/// strongly connected components of the resulting call graph, which means that two functions are
+/// in the same group if and only if they call each other, directly or through other functions.
+///
+/// Each group has the indices of its functions in increasing order and only calls functions in
+/// itself or in the groups before it. Groups that do not call each other keep their original
+/// order.
+pub fn recursive_groups<'a>(
+    fns: &[(Name<'a>, &[TyAnnotation<Name<'a>>], &Block<'a>)],
+) -> Vec<Vec<usize>> {
+    let calls = fns
+        .iter()
+        .map(|(_, params, body)| {
+            (0..fns.len())
+                .filter(|&callee| {
+                    let name = fns[callee].0;
+                    params.iter().all(|param| param.item.content != name)
+                        && RecursionChecker::run(name, body)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut tarjan = Tarjan {
+        calls,
+        indices: vec![None; fns.len()],
+        low_links: vec![0; fns.len()],
+        stack: Vec::new(),
+        groups: Vec::new(),
+        next: 0,
+    };
+    for index in 0..fns.len() {
+        if tarjan.indices[index].is_none() {
+            tarjan.visit(index);
+        }
+    }
+    tarjan.groups
+}
+
+/// State of Tarjan's algorithm for strongly connected components.
+struct Tarjan {
+    /// The functions called by each function.
+    calls: Vec<Vec<usize>>,
+    /// The order in which each function was visited, if it was visited already.
+    indices: Vec<Option<usize>>,
+    /// The smallest index reachable from each function through the functions in the stack.
+    low_links: Vec<usize>,
+    /// The visited functions that are not in a group yet.
+    stack: Vec<usize>,
+    /// The groups found so far, a group is found after all the groups it calls.
+    groups: Vec<Vec<usize>>,
+    /// The index of the next function to be visited.
+    next: usize,
+}
+
+impl Tarjan {
+    fn visit(&mut self, function: usize) {
+        self.indices[function] = Some(self.next);
+        self.low_links[function] = self.next;
+        self.next += 1;
+        self.stack.push(function);
+
+        for position in 0..self.calls[function].len() {
+            let callee = self.calls[function][position];
+            match self.indices[callee] {
+                None => {
+                    self.visit(callee);
+                    self.low_links[function] = self.low_links[function].min(self.low_links[callee]);
+                }
+                Some(index) if self.stack.contains(&callee) => {
+                    self.low_links[function] = self.low_links[function].min(index);
+                }
+                Some(_) => (),
+            }
+        }
+
+        // The function is the first visited function of its group, which is on top of it in the
+        // stack.
+        if Some(self.low_links[function]) == self.indices[function] {
+            let start = self
+                .stack
+                .iter()
+                .rposition(|&member| member == function)
+                .expect("the function is in the stack");
+            let mut group = self.stack.split_off(start);
+            group.sort_unstable();
+            self.groups.push(group);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Construction of AST trees without source code.
+//!
+//! Tests and applications that build programs directly instead of parsing them can use the
+//! functions of this module and the [`node!`](crate::node) macro instead of wrapping every element
+//! of the tree in a [`Located`] by hand. Every location is [`DUMMY_LOC`], and since [`Located`]
+//! values are compared by their content only, the trees built here are equal to the ones returned
+//! by the parser for the same code.
+//!
+//! ```
+//! use pijama_ast::{build, node, ty::Ty, BinOp, Node};
+//!
+//! // fn double(x: Int): Int do x * 2 end
+//! let double = build::fn_def(
+//!     "double",
+//!     vec![("x", Ty::Int)],
+//!     Ty::Int,
+//!     vec![node!(x * 2)],
+//! );
+//! let call = node!(print(double(20 + 1)));
+//!
+//! assert_eq!(node!(x * 2), build::binary(BinOp::Mul, build::name("x"), build::lit(2)));
+//! assert!(matches!(call.content, Node::Call(..)));
+//! ```
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::Debug;
+
+use crate::{
+    ty::{Ty, TyAnnotation},
+    BinOp, Block, Branch, Literal, Located, Location, Name, Node, UnOp,
+};
+
+/// The location of every element built by this module.
+pub const DUMMY_LOC: Location = Location::new(0, 0);
+
+/// Wraps `content` in a [`Located`] with [`DUMMY_LOC`].
+pub fn located<T: Debug>(content: T) -> Located<T> {
+    Located::new(content, DUMMY_LOC)
+}
+
+/// Returns a block with `nodes`.
+pub fn block<'a>(nodes: impl IntoIterator<Item = Located<Node<'a>>>) -> Located<Block<'a>> {
+    located(nodes.into_iter().collect())
+}
+
+/// Returns `item` annotated with `ty`, which is [`Ty::Missing`] for items without annotation.
+pub fn annotated<I: Debug + Eq>(item: I, ty: Ty) -> TyAnnotation<I> {
+    TyAnnotation {
+        item: located(item),
+        ty: located(ty),
+    }
+}
+
+/// Returns a literal node.
+pub fn lit<'a>(literal: impl Into<Literal>) -> Located<Node<'a>> {
+    located(Node::Literal(literal.into()))
+}
+
+/// Returns a name node.
+///
+/// The names of primitives are names too, they are resolved when the tree is lowered.
+pub fn name(name: &str) -> Located<Node<'_>> {
+    located(Node::Name(Name(name)))
+}
+
+/// Returns a unary operation node.
+pub fn unary<'a>(op: UnOp, node: Located<Node<'a>>) -> Located<Node<'a>> {
+    located(Node::UnaryOp(op, Box::new(node)))
+}
+
+/// Returns a binary operation node.
+pub fn binary<'a>(
+    op: BinOp,
+    left: Located<Node<'a>>,
+    right: Located<Node<'a>>,
+) -> Located<Node<'a>> {
+    located(Node::BinaryOp(op, Box::new(left), Box::new(right)))
+}
+
+/// Returns a call node.
+pub fn call<'a>(
+    func: Located<Node<'a>>,
+    args: impl IntoIterator<Item = Located<Node<'a>>>,
+) -> Located<Node<'a>> {
+    located(Node::Call(Box::new(func), args.into_iter().collect()))
+}
+
+/// Returns a list node.
+pub fn list<'a>(elems: impl IntoIterator<Item = Located<Node<'a>>>) -> Located<Node<'a>> {
+    located(Node::List(elems.into_iter().collect()))
+}
+
+/// Returns a binding node, whose type is [`Ty::Missing`] if it has no annotation.
+pub fn let_bind<'a>(name: &'a str, ty: Ty, value: Located<Node<'a>>) -> Located<Node<'a>> {
+    located(Node::LetBind(annotated(Name(name), ty), Box::new(value)))
+}
+
+/// Returns a mutable binding node, whose type is [`Ty::Missing`] if it has no annotation.
+pub fn let_mut<'a>(name: &'a str, ty: Ty, value: Located<Node<'a>>) -> Located<Node<'a>> {
+    located(Node::LetMut(annotated(Name(name), ty), Box::new(value)))
+}
+
+/// Returns an assignment node.
+pub fn assign<'a>(name: &'a str, value: Located<Node<'a>>) -> Located<Node<'a>> {
+    located(Node::Assign(located(Name(name)), Box::new(value)))
+}
+
+/// Returns a conditional node without `elif` branches.
+///
+/// A conditional without `else` has an empty `otherwise` block.
+pub fn cond<'a>(
+    cond: impl IntoIterator<Item = Located<Node<'a>>>,
+    body: impl IntoIterator<Item = Located<Node<'a>>>,
+    otherwise: impl IntoIterator<Item = Located<Node<'a>>>,
+) -> Located<Node<'a>> {
+    let branch = Branch {
+        cond: block(cond),
+        body: block(body),
+    };
+    located(Node::Cond(branch, Vec::new(), block(otherwise)))
+}
+
+/// Returns a `while` loop node.
+pub fn while_loop<'a>(
+    cond: impl IntoIterator<Item = Located<Node<'a>>>,
+    body: impl IntoIterator<Item = Located<Node<'a>>>,
+) -> Located<Node<'a>> {
+    located(Node::While(Branch {
+        cond: block(cond),
+        body: block(body),
+    }))
+}
+
+/// Returns the definition of a function without doc comment.
+///
+/// The return type is [`Ty::Missing`] if it has no annotation, like the types of the parameters.
+pub fn fn_def<'a>(
+    name: &'a str,
+    params: impl IntoIterator<Item = (&'a str, Ty)>,
+    ret: Ty,
+    body: impl IntoIterator<Item = Located<Node<'a>>>,
+) -> Located<Node<'a>> {
+    located(Node::FnDef(
+        located(Name(name)),
+        params
+            .into_iter()
+            .map(|(name, ty)| annotated(Name(name), ty))
+            .collect(),
+        TyAnnotation {
+            item: block(body),
+            ty: located(ret),
+        },
+        Vec::new(),
+    ))
+}
+
+/// Returns an anonymous function node.
+pub fn anon_fn<'a>(
+    params: impl IntoIterator<Item = (&'a str, Ty)>,
+    ret: Ty,
+    body: impl IntoIterator<Item = Located<Node<'a>>>,
+) -> Located<Node<'a>> {
+    located(Node::AnonFn(
+        params
+            .into_iter()
+            .map(|(name, ty)| annotated(Name(name), ty))
+            .collect(),
+        TyAnnotation {
+            item: block(body),
+            ty: located(ret),
+        },
+    ))
+}
+
+/// Builds a [`Located`]`<`[`Node`]`>` from Pijama code written as Rust tokens, with every location
+/// set to [`DUMMY_LOC`].
+///
+/// The macro accepts:
+///
+/// - Literals, like `1`, `-1`, `2.5`, `'a'`, `"text"`, `true` and `unit`.
+/// - Names and primitives, like `x` or `print`.
+/// - Unary operations, like `-x` or `!done`, whose operand is the rest of the code like in the
+///   parser.
+/// - Binary operations, like `x + 1` or `xs |> len`. Operands must be a single token tree, so
+///   nested operations are written in brackets, like `(x * 2) + 1`.
+/// - Calls to names, like `f(x, y + 1)`, and lists, like `[1, 2, 3]`.
+/// - Bindings, like `x = 1`, `x: Int = 1` or `let mut x = 1`. Types are basic types like `Int`,
+///   lists like `[Int]` or any Rust expression of type [`Ty`] in braces.
+/// - Any Rust expression of type [`Located`]`<`[`Node`]`>` in braces, like `{ build::name("x") }`,
+///   to build the nodes this macro cannot write.
+///
+/// Other nodes, like conditionals or function definitions, are built with the functions of the
+/// [`build`](crate::build) module.
+#[macro_export]
+macro_rules! node {
+    // Splits the arguments of calls and the elements of lists at their commas.
+    (@list [$($done:expr,)*] [$($current:tt)+] , $($rest:tt)*) => {
+        $crate::node!(@list [$($done,)* $crate::node!($($current)+),] [] $($rest)*)
+    };
+    (@list [$($done:expr,)*] [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::node!(@list [$($done,)*] [$($current)* $next] $($rest)*)
+    };
+    (@list [$($done:expr,)*] [$($current:tt)+]) => {
+        [$($done,)* $crate::node!($($current)+)]
+    };
+    (@list [$($done:expr,)*] []) => {
+        [$($done),*]
+    };
+    (@ty) => {
+        $crate::ty::Ty::Missing
+    };
+    (@ty { $ty:expr }) => {
+        $ty
+    };
+    (@ty [ $elem:tt ]) => {
+        $crate::ty::Ty::List(::core::convert::From::from($crate::node!(@ty $elem)))
+    };
+    (@ty $ty:ident) => {
+        $crate::ty::Ty::$ty
+    };
+    (@op +) => { $crate::BinOp::Add };
+    (@op -) => { $crate::BinOp::Sub };
+    (@op *) => { $crate::BinOp::Mul };
+    (@op /) => { $crate::BinOp::Div };
+    (@op %) => { $crate::BinOp::Rem };
+    (@op &&) => { $crate::BinOp::And };
+    (@op ||) => { $crate::BinOp::Or };
+    (@op &) => { $crate::BinOp::BitAnd };
+    (@op |) => { $crate::BinOp::BitOr };
+    (@op ^) => { $crate::BinOp::BitXor };
+    (@op >>) => { $crate::BinOp::Shr };
+    (@op <<) => { $crate::BinOp::Shl };
+    (@op ==) => { $crate::BinOp::Eq };
+    (@op !=) => { $crate::BinOp::Neq };
+    (@op <) => { $crate::BinOp::Lt };
+    (@op >) => { $crate::BinOp::Gt };
+    (@op <=) => { $crate::BinOp::Lte };
+    (@op >=) => { $crate::BinOp::Gte };
+    ({ $node:expr }) => {
+        $node
+    };
+    (( $($inner:tt)+ )) => {
+        $crate::node!($($inner)+)
+    };
+    (unit) => {
+        $crate::build::lit($crate::Literal::Unit)
+    };
+    (let mut $name:ident $(: $ty:tt)? = $($value:tt)+) => {
+        $crate::build::let_mut(
+            stringify!($name),
+            $crate::node!(@ty $($ty)?),
+            $crate::node!($($value)+),
+        )
+    };
+    ($name:ident $(: $ty:tt)? = $($value:tt)+) => {
+        $crate::build::let_bind(
+            stringify!($name),
+            $crate::node!(@ty $($ty)?),
+            $crate::node!($($value)+),
+        )
+    };
+    (- $lit:literal) => {
+        $crate::build::lit(-$lit)
+    };
+    (- $($node:tt)+) => {
+        $crate::build::unary($crate::UnOp::Neg, $crate::node!($($node)+))
+    };
+    (! $($node:tt)+) => {
+        $crate::build::unary($crate::UnOp::Not, $crate::node!($($node)+))
+    };
+    (~ $($node:tt)+) => {
+        $crate::build::unary($crate::UnOp::BitNot, $crate::node!($($node)+))
+    };
+    // A minus sign followed by something else must not reach this rule, which fails instead of
+    // trying the next ones if the code is not a literal.
+    ($lit:literal) => {
+        $crate::build::lit($lit)
+    };
+    ([ $($elems:tt)* ]) => {
+        $crate::build::list($crate::node!(@list [] [] $($elems)*))
+    };
+    ($func:ident ( $($args:tt)* )) => {
+        $crate::build::call(
+            $crate::build::name(stringify!($func)),
+            $crate::node!(@list [] [] $($args)*),
+        )
+    };
+    ($name:ident) => {
+        $crate::build::name(stringify!($name))
+    };
+    ($left:tt | > $right:tt) => {
+        $crate::build::binary($crate::BinOp::Pipe, $crate::node!($left), $crate::node!($right))
+    };
+    ($left:tt $op:tt $right:tt) => {
+        $crate::build::binary($crate::node!(@op $op), $crate::node!($left), $crate::node!($right))
+    };
+}
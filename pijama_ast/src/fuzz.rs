@@ -0,0 +1,500 @@
+//! Random syntax trees for fuzzing.
+//!
+//! With the `arbitrary` feature, [`Node`], [`Ty`] and [`Literal`] implement [`Arbitrary`], so fuzz
+//! targets can turn their input into syntax trees and run them through the compiler. These trees
+//! are rarely valid programs, which exercises the errors of the type checker and the lowering
+//! passes. The [`well_typed`] function generates programs that type-check instead, so they can be
+//! evaluated too.
+//!
+//! Trees are at most [`MAX_DEPTH`] nodes deep and their names are taken from short lists, so the
+//! same names appear often enough to refer to each other. Imports are never generated, as they
+//! read files. Every location is [`DUMMY_LOC`](crate::build::DUMMY_LOC).
+use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+use core::fmt::Debug;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    build::{block, located},
+    ty::{Ty, TyAnnotation},
+    Arm, BinOp, Block, Branch, Literal, Located, Name, Node, Pattern, Primitive, UnOp, Variant,
+};
+
+/// The maximum depth of the generated trees.
+pub const MAX_DEPTH: usize = 4;
+
+/// The maximum number of nodes in the generated blocks.
+const MAX_BLOCK_LEN: usize = 3;
+
+const NAMES: &[&str] = &["x", "y", "z", "f", "g", "xs"];
+const ENUMS: &[&str] = &["Shape", "Option"];
+const VARIANTS: &[&str] = &["Circle", "Square", "Some", "None"];
+const TY_VARS: &[&str] = &["a", "b"];
+
+const BIN_OPS: &[BinOp] = &[
+    BinOp::Add,
+    BinOp::Sub,
+    BinOp::Mul,
+    BinOp::Div,
+    BinOp::Rem,
+    BinOp::And,
+    BinOp::Or,
+    BinOp::BitAnd,
+    BinOp::BitOr,
+    BinOp::BitXor,
+    BinOp::Shr,
+    BinOp::Shl,
+    BinOp::Eq,
+    BinOp::Neq,
+    BinOp::Lt,
+    BinOp::Gt,
+    BinOp::Lte,
+    BinOp::Gte,
+    BinOp::Pipe,
+];
+
+/// Operators between integers returning an integer.
+const INT_OPS: &[BinOp] = &[
+    BinOp::Add,
+    BinOp::Sub,
+    BinOp::Mul,
+    BinOp::Div,
+    BinOp::Rem,
+    BinOp::BitAnd,
+    BinOp::BitOr,
+    BinOp::BitXor,
+    BinOp::Shr,
+    BinOp::Shl,
+];
+
+/// Operators between integers returning a boolean.
+const COMPARISON_OPS: &[BinOp] = &[
+    BinOp::Eq,
+    BinOp::Neq,
+    BinOp::Lt,
+    BinOp::Gt,
+    BinOp::Lte,
+    BinOp::Gte,
+];
+
+const UN_OPS: &[UnOp] = &[UnOp::Neg, UnOp::Not, UnOp::BitNot];
+
+const PRIMITIVES: &[Primitive] = &[
+    Primitive::Print,
+    Primitive::Concat,
+    Primitive::IntToFloat,
+    Primitive::FloatToInt,
+    Primitive::CharToInt,
+    Primitive::IntToChar,
+    Primitive::Head,
+    Primitive::Tail,
+    Primitive::Len,
+    Primitive::Push,
+    Primitive::Get,
+    Primitive::ToString,
+    Primitive::ReadLine,
+    Primitive::ReadInt,
+    Primitive::Assert,
+    Primitive::Abs,
+    Primitive::Sign,
+    Primitive::Min,
+    Primitive::Max,
+    Primitive::Gcd,
+    Primitive::Pow,
+    Primitive::Substring,
+    Primitive::CharAt,
+    Primitive::ParseInt,
+    Primitive::Random,
+    Primitive::Spawn,
+    Primitive::Channel,
+    Primitive::Send,
+    Primitive::Recv,
+    Primitive::Force,
+];
+
+impl<'a> Arbitrary<'a> for Literal {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=5)? {
+            0 => Literal::Bool(u.arbitrary()?),
+            1 => Literal::Unit,
+            2 => Literal::Number(u.arbitrary()?),
+            3 => Literal::Float(u.arbitrary()?),
+            4 => Literal::Char(u.arbitrary()?),
+            _ => Literal::Str(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Ty {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        ty(u, MAX_DEPTH)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Node<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        node(u, MAX_DEPTH)
+    }
+}
+
+impl<'a, T: Arbitrary<'a> + Debug> Arbitrary<'a> for Located<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(located(u.arbitrary()?))
+    }
+}
+
+/// Returns a program with up to [`MAX_BLOCK_LEN`] arbitrary nodes, which is rarely valid.
+pub fn program<'a>(u: &mut Unstructured<'a>) -> Result<Located<Block<'a>>> {
+    nodes(u, MAX_DEPTH)
+}
+
+fn ty(u: &mut Unstructured, depth: usize) -> Result<Ty> {
+    // Only the types without other types inside them can be generated at the maximum depth.
+    let last = if depth == 0 { 6 } else { 11 };
+    Ok(match u.int_in_range(0..=last)? {
+        0 => Ty::Bool,
+        1 => Ty::Int,
+        2 => Ty::Float,
+        3 => Ty::Unit,
+        4 => Ty::Char,
+        5 => Ty::String,
+        6 => Ty::Var(u.choose(TY_VARS)?.to_string()),
+        7 => Ty::List(Box::new(ty(u, depth - 1)?)),
+        8 => Ty::Arrow(Box::new(ty(u, depth - 1)?), Box::new(ty(u, depth - 1)?)),
+        9 => Ty::Chan(Box::new(ty(u, depth - 1)?)),
+        10 => Ty::Lazy(Box::new(ty(u, depth - 1)?)),
+        _ => {
+            let name = u.choose(ENUMS)?.to_string();
+            let len = u.int_in_range(0..=2)?;
+            let args = (0..len).map(|_| ty(u, depth - 1)).collect::<Result<_>>()?;
+            Ty::Adt(name, args)
+        }
+    })
+}
+
+/// Returns `item` annotated with an arbitrary type, which is missing half of the time.
+fn annotation<'a, I: Debug + Eq>(u: &mut Unstructured<'a>, item: I) -> Result<TyAnnotation<I>> {
+    let ty = if u.arbitrary()? {
+        ty(u, MAX_DEPTH)?
+    } else {
+        Ty::Missing
+    };
+    Ok(TyAnnotation {
+        item: located(item),
+        ty: located(ty),
+    })
+}
+
+fn name<'a>(u: &mut Unstructured<'a>) -> Result<Name<'a>> {
+    Ok(Name(u.choose(NAMES)?))
+}
+
+fn boxed<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<Box<Located<Node<'a>>>> {
+    Ok(Box::new(located(node(u, depth)?)))
+}
+
+fn nodes<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<Located<Block<'a>>> {
+    let len = u.int_in_range(0..=MAX_BLOCK_LEN)?;
+    let nodes = (0..len)
+        .map(|_| Ok(located(node(u, depth)?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(block(nodes))
+}
+
+fn branch<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<Branch<'a>> {
+    Ok(Branch {
+        cond: nodes(u, depth)?,
+        body: nodes(u, depth)?,
+    })
+}
+
+fn params<'a>(u: &mut Unstructured<'a>) -> Result<Vec<TyAnnotation<Name<'a>>>> {
+    let len = u.int_in_range(0..=2)?;
+    (0..len)
+        .map(|_| {
+            let name = name(u)?;
+            annotation(u, name)
+        })
+        .collect()
+}
+
+fn body<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<TyAnnotation<Block<'a>>> {
+    let body = nodes(u, depth)?;
+    Ok(TyAnnotation {
+        ty: annotation(u, ())?.ty,
+        item: body,
+    })
+}
+
+fn pattern<'a>(u: &mut Unstructured<'a>) -> Result<Pattern<'a>> {
+    Ok(match u.int_in_range(0..=2)? {
+        0 => Pattern::Wildcard,
+        1 => Pattern::Literal(u.arbitrary()?),
+        _ => {
+            let variant = located(Name(u.choose(VARIANTS)?));
+            let len = u.int_in_range(0..=2)?;
+            let binders = (0..len)
+                .map(|_| Ok(located(name(u)?)))
+                .collect::<Result<_>>()?;
+            Pattern::Variant(variant, binders)
+        }
+    })
+}
+
+fn node<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<Node<'a>> {
+    // Only the nodes without other nodes inside them can be generated at the maximum depth.
+    let last = if depth == 0 { 2 } else { 22 };
+    let choice = u.int_in_range(0..=last)?;
+    let depth = depth.saturating_sub(1);
+    Ok(match choice {
+        0 => Node::Literal(u.arbitrary()?),
+        1 => Node::Name(name(u)?),
+        2 => Node::PrimFn(*u.choose(PRIMITIVES)?),
+        3 => Node::BinaryOp(*u.choose(BIN_OPS)?, boxed(u, depth)?, boxed(u, depth)?),
+        4 => Node::UnaryOp(*u.choose(UN_OPS)?, boxed(u, depth)?),
+        5 => {
+            let name = name(u)?;
+            Node::LetBind(annotation(u, name)?, boxed(u, depth)?)
+        }
+        6 => {
+            let name = name(u)?;
+            Node::LetMut(annotation(u, name)?, boxed(u, depth)?)
+        }
+        7 => Node::Assign(located(name(u)?), boxed(u, depth)?),
+        8 => {
+            let len = u.int_in_range(0..=1)?;
+            let elifs = (0..len).map(|_| branch(u, depth)).collect::<Result<_>>()?;
+            Node::Cond(branch(u, depth)?, elifs, nodes(u, depth)?)
+        }
+        9 => Node::While(branch(u, depth)?),
+        10 => Node::For(
+            located(name(u)?),
+            boxed(u, depth)?,
+            boxed(u, depth)?,
+            nodes(u, depth)?,
+        ),
+        11 => Node::FnDef(located(name(u)?), params(u)?, body(u, depth)?, Vec::new()),
+        12 => Node::AnonFn(params(u)?, body(u, depth)?),
+        13 => Node::Call(boxed(u, depth)?, nodes(u, depth)?.content),
+        14 => Node::List(nodes(u, depth)?.content),
+        15 => Node::Interpolation(nodes(u, depth)?.content),
+        16 => Node::Ascription(boxed(u, depth)?, located(ty(u, depth)?)),
+        17 => Node::Lazy(boxed(u, depth)?),
+        18 => {
+            let name = name(u)?;
+            Node::LetIn(annotation(u, name)?, boxed(u, depth)?, boxed(u, depth)?)
+        }
+        19 => Node::Block(nodes(u, depth)?),
+        20 => {
+            let op = *u.choose(BIN_OPS)?;
+            match u.int_in_range(0..=2)? {
+                0 => Node::Operator(op, None, None),
+                1 => Node::Operator(op, Some(boxed(u, depth)?), None),
+                _ => Node::Operator(op, None, Some(boxed(u, depth)?)),
+            }
+        }
+        21 => {
+            let len = u.int_in_range(1..=2)?;
+            let variants = (0..len)
+                .map(|_| {
+                    let name = located(Name(u.choose(VARIANTS)?));
+                    let len = u.int_in_range(0..=1)?;
+                    let fields = (0..len)
+                        .map(|_| Ok(located(ty(u, depth)?)))
+                        .collect::<Result<_>>()?;
+                    Ok(Variant { name, fields })
+                })
+                .collect::<Result<_>>()?;
+            Node::EnumDef(located(Name(u.choose(ENUMS)?)), variants)
+        }
+        _ => {
+            let len = u.int_in_range(1..=2)?;
+            let arms = (0..len)
+                .map(|_| {
+                    Ok(Arm {
+                        pattern: located(pattern(u)?),
+                        body: nodes(u, depth)?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            Node::Match(boxed(u, depth)?, arms)
+        }
+    })
+}
+
+/// Names of the bindings and functions of well-typed programs, which are never shadowed.
+const FRESH: &[&str] = &[
+    "v0", "v1", "v2", "v3", "v4", "v5", "v6", "v7", "v8", "v9", "v10", "v11", "v12", "v13", "v14",
+    "v15",
+];
+
+/// Returns a program that type-checks.
+///
+/// Programs are made of bindings and function definitions followed by an expression, using
+/// integers, booleans and functions between them. They always terminate, as there are no loops
+/// and functions are not recursive, but they can still fail at runtime, for example when dividing
+/// by zero.
+pub fn well_typed<'a>(u: &mut Unstructured<'a>) -> Result<Located<Block<'a>>> {
+    let mut generator = Generator {
+        scope: Vec::new(),
+        fresh: 0,
+    };
+    let ty = generator.value_ty(u)?;
+    generator.block(u, MAX_DEPTH, &ty)
+}
+
+/// A generator of well-typed programs.
+struct Generator {
+    /// The names that can be used, with their types.
+    scope: Vec<(&'static str, Ty)>,
+    /// The number of names of [`FRESH`] already used.
+    fresh: usize,
+}
+
+impl Generator {
+    /// Returns the type of a value, which is `Int` or `Bool`.
+    fn value_ty(&self, u: &mut Unstructured) -> Result<Ty> {
+        Ok(if u.arbitrary()? { Ty::Int } else { Ty::Bool })
+    }
+
+    /// Returns an unused name, if there are any left.
+    fn fresh(&mut self) -> Option<&'static str> {
+        let name = FRESH.get(self.fresh)?;
+        self.fresh += 1;
+        Some(name)
+    }
+
+    /// Returns a block with some definitions followed by an expression of type `ty`.
+    fn block<'a>(
+        &mut self,
+        u: &mut Unstructured<'a>,
+        depth: usize,
+        ty: &Ty,
+    ) -> Result<Located<Block<'a>>> {
+        let scope = self.scope.len();
+        let mut nodes = Vec::new();
+        for _ in 0..u.int_in_range(0..=MAX_BLOCK_LEN - 1)? {
+            if let Some(definition) = self.definition(u, depth)? {
+                nodes.push(definition);
+            }
+        }
+        nodes.push(self.expr(u, depth, ty)?);
+        self.scope.truncate(scope);
+        Ok(block(nodes))
+    }
+
+    /// Returns a binding or a function definition and adds its name to the scope.
+    fn definition<'a>(
+        &mut self,
+        u: &mut Unstructured<'a>,
+        depth: usize,
+    ) -> Result<Option<Located<Node<'a>>>> {
+        let name = match self.fresh() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let ty = self.value_ty(u)?;
+        let param = match self.fresh() {
+            Some(param) if u.arbitrary()? => param,
+            _ => {
+                let value = self.expr(u, depth, &ty)?;
+                self.scope.push((name, ty.clone()));
+                let ty = if u.arbitrary()? { ty } else { Ty::Missing };
+                return Ok(Some(located(Node::LetBind(
+                    TyAnnotation {
+                        item: located(Name(name)),
+                        ty: located(ty),
+                    },
+                    Box::new(value),
+                ))));
+            }
+        };
+        let param_ty = self.value_ty(u)?;
+        self.scope.push((param, param_ty.clone()));
+        let body = self.block(u, depth.saturating_sub(1), &ty)?;
+        self.scope.pop();
+        let fn_ty = Ty::Arrow(Box::new(param_ty.clone()), Box::new(ty.clone()));
+        self.scope.push((name, fn_ty));
+        Ok(Some(located(Node::FnDef(
+            located(Name(name)),
+            vec![TyAnnotation {
+                item: located(Name(param)),
+                ty: located(param_ty),
+            }],
+            TyAnnotation {
+                item: body,
+                ty: located(ty),
+            },
+            Vec::new(),
+        ))))
+    }
+
+    /// Returns an expression of type `ty`, which is `Int` or `Bool`.
+    fn expr<'a>(
+        &mut self,
+        u: &mut Unstructured<'a>,
+        depth: usize,
+        ty: &Ty,
+    ) -> Result<Located<Node<'a>>> {
+        let vars: Vec<_> = self
+            .scope
+            .iter()
+            .filter(|(_, var_ty)| var_ty == ty)
+            .map(|(name, _)| *name)
+            .collect();
+        let funcs: Vec<_> = self
+            .scope
+            .iter()
+            .filter_map(|(name, var_ty)| match var_ty {
+                Ty::Arrow(param, ret) if ret.as_ref() == ty => Some((*name, (**param).clone())),
+                _ => None,
+            })
+            .collect();
+
+        let last = if depth == 0 { 1 } else { 5 };
+        let depth = depth.saturating_sub(1);
+        let node = match u.int_in_range(0..=last)? {
+            1 if !vars.is_empty() => Node::Name(Name(u.choose(&vars)?)),
+            2 => {
+                let (op, operand) = match ty {
+                    Ty::Int => (*u.choose(&[UnOp::Neg, UnOp::BitNot])?, Ty::Int),
+                    _ => (UnOp::Not, Ty::Bool),
+                };
+                Node::UnaryOp(op, Box::new(self.expr(u, depth, &operand)?))
+            }
+            3 => {
+                let (op, operand) = match ty {
+                    Ty::Int => (*u.choose(INT_OPS)?, Ty::Int),
+                    _ if u.arbitrary()? => (*u.choose(&[BinOp::And, BinOp::Or])?, Ty::Bool),
+                    _ => (*u.choose(COMPARISON_OPS)?, Ty::Int),
+                };
+                Node::BinaryOp(
+                    op,
+                    Box::new(self.expr(u, depth, &operand)?),
+                    Box::new(self.expr(u, depth, &operand)?),
+                )
+            }
+            4 => {
+                let branch = Branch {
+                    cond: self.block(u, depth, &Ty::Bool)?,
+                    body: self.block(u, depth, ty)?,
+                };
+                Node::Cond(branch, Vec::new(), self.block(u, depth, ty)?)
+            }
+            5 if !funcs.is_empty() => {
+                let (func, param) = u.choose(&funcs)?.clone();
+                let arg = self.expr(u, depth, &param)?;
+                Node::Call(
+                    Box::new(located(Node::Name(Name(func)))),
+                    block([arg]).content,
+                )
+            }
+            _ => match ty {
+                // Integers fit in the default width, so the program type-checks.
+                Ty::Int => Node::Literal(Literal::Number(u.arbitrary::<i64>()?.into())),
+                _ => Node::Literal(Literal::Bool(u.arbitrary()?)),
+            },
+        };
+        Ok(located(node))
+    }
+}
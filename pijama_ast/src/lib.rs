@@ -1,25 +1,94 @@
 //! Crate encapsulating Pijama's AST and associated types.
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod analysis;
+pub mod build;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
 pub mod location;
+pub mod node_id;
+pub mod owned;
+pub mod symbol;
 pub mod ty;
 pub mod visitor;
 
-use std::{
-    collections::VecDeque,
-    fmt::{Debug, Display, Formatter, Result},
-};
+use alloc::{boxed::Box, collections::VecDeque, string::String, vec::Vec};
+use core::fmt::{Debug, Display, Formatter, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::ty::TyAnnotation;
+use crate::ty::{Ty, TyAnnotation};
 
 pub use location::*;
+pub use node_id::NodeId;
+
+/// The type of Pijama's integers.
+///
+/// Integers are stored with 128 bits, but a program only uses the bits given by its [`IntWidth`].
+pub type Int = i128;
+
+/// The width of the integers of a program.
+///
+/// The width is chosen when the program is type-checked, which rejects the integer literals that
+/// do not fit in it, and when it is evaluated, where results that do not fit in it overflow. There
+/// are no arbitrary-precision integers: every width has a fixed number of bits.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum IntWidth {
+    /// 64-bit integers, the default.
+    #[default]
+    I64,
+    /// 128-bit integers.
+    I128,
+}
+
+impl IntWidth {
+    /// Returns the number of bits of the integers.
+    pub const fn bits(self) -> u32 {
+        match self {
+            IntWidth::I64 => i64::BITS,
+            IntWidth::I128 => i128::BITS,
+        }
+    }
+
+    /// Returns the smallest integer.
+    pub const fn min(self) -> Int {
+        match self {
+            IntWidth::I64 => i64::MIN as Int,
+            IntWidth::I128 => i128::MIN,
+        }
+    }
+
+    /// Returns the largest integer.
+    pub const fn max(self) -> Int {
+        match self {
+            IntWidth::I64 => i64::MAX as Int,
+            IntWidth::I128 => i128::MAX,
+        }
+    }
+
+    /// Returns `true` if `n` fits in this width.
+    pub fn contains(self, n: Int) -> bool {
+        self.min() <= n && n <= self.max()
+    }
+}
+
+impl Display for IntWidth {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}-bit", self.bits())
+    }
+}
 
 /// A [`Block`] constitutes a collection of [`Node`]s.
 pub type Block<'a> = VecDeque<Located<Node<'a>>>;
 
 /// Represents the name of a variable or non-primitive
 /// function in the AST.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Name<'a>(pub &'a str);
 
@@ -31,6 +100,7 @@ impl<'a> Display for Name<'a> {
 
 /// The different binary operators that Pijama's
 /// syntax supports.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum BinOp {
     /// Add operator.
@@ -69,6 +139,10 @@ pub enum BinOp {
     Lte,
     /// Greater Than Or Equal operator.
     Gte,
+    /// Pipeline operator, which calls its second operand with the first one.
+    ///
+    /// It is lowered to a function application, so it never appears in the MIR.
+    Pipe,
 }
 
 impl<'a> Display for BinOp {
@@ -93,17 +167,21 @@ impl<'a> Display for BinOp {
             Gt => write!(f, ">"),
             Lte => write!(f, "<="),
             Gte => write!(f, ">="),
+            Pipe => write!(f, "|>"),
         }
     }
 }
 
 /// The unary operators that Pijama's syntax supports.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum UnOp {
     /// Numeric Negation operator.
     Neg,
     /// Logical Negation operator.
     Not,
+    /// Bit-wise Not operator.
+    BitNot,
 }
 
 impl<'a> Display for UnOp {
@@ -112,23 +190,50 @@ impl<'a> Display for UnOp {
         match self {
             Not => write!(f, "!"),
             Neg => write!(f, "-"),
+            BitNot => write!(f, "~"),
         }
     }
 }
 
 /// The literal values that Pijama's syntax supports.
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
 pub enum Literal {
     /// Boolean Literal.
     Bool(bool),
     /// Unit Literal.
     Unit,
     /// Numeric Literal.
-    Number(i64),
+    Number(Int),
+    /// Floating point Literal.
+    Float(f64),
+    /// Character Literal, with its escape sequence already replaced.
+    Char(char),
+    /// String Literal, with its escape sequences already replaced.
+    Str(String),
+}
+
+/// Floating point literals are equal if they have the same bits, so every literal is equal to
+/// itself.
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        use Literal::*;
+        match (self, other) {
+            (Bool(b1), Bool(b2)) => b1 == b2,
+            (Unit, Unit) => true,
+            (Number(n1), Number(n2)) => n1 == n2,
+            (Float(x1), Float(x2)) => x1.to_bits() == x2.to_bits(),
+            (Char(c1), Char(c2)) => c1 == c2,
+            (Str(s1), Str(s2)) => s1 == s2,
+            _ => false,
+        }
+    }
 }
 
-impl From<i64> for Literal {
-    fn from(n: i64) -> Self {
+impl Eq for Literal {}
+
+impl From<Int> for Literal {
+    fn from(n: Int) -> Self {
         Literal::Number(n)
     }
 }
@@ -139,6 +244,24 @@ impl From<bool> for Literal {
     }
 }
 
+impl From<f64> for Literal {
+    fn from(x: f64) -> Self {
+        Literal::Float(x)
+    }
+}
+
+impl From<char> for Literal {
+    fn from(c: char) -> Self {
+        Literal::Char(c)
+    }
+}
+
+impl From<&str> for Literal {
+    fn from(s: &str) -> Self {
+        Literal::Str(s.into())
+    }
+}
+
 impl<'a> Display for Literal {
     fn fmt(&self, f: &mut Formatter) -> Result {
         use Literal::*;
@@ -146,15 +269,171 @@ impl<'a> Display for Literal {
             Bool(b) => write!(f, "{}", b),
             Unit => write!(f, "unit"),
             Number(num) => write!(f, "{}", num),
+            // The debug representation always has a decimal point or an exponent, so it is not
+            // confused with an integer.
+            Float(num) => write!(f, "{:?}", num),
+            Char(c) => write_char_literal(f, *c),
+            Str(string) => write_str_literal(f, string),
         }
     }
 }
 
+/// Writes `string` between double quotes, escaping the characters that must be escaped in a
+/// string literal.
+pub fn write_str_literal(f: &mut Formatter, string: &str) -> Result {
+    write!(f, "\"")?;
+    for c in string.chars() {
+        write_escaped(f, c, '"')?;
+    }
+    write!(f, "\"")
+}
+
+/// Writes `c` between single quotes, escaping it if it must be escaped in a character literal.
+pub fn write_char_literal(f: &mut Formatter, c: char) -> Result {
+    write!(f, "'")?;
+    write_escaped(f, c, '\'')?;
+    write!(f, "'")
+}
+
+/// Writes `c` as part of a literal delimited by `quote`.
+///
+/// Opening braces are escaped in string literals so they are not parsed as interpolations.
+fn write_escaped(f: &mut Formatter, c: char, quote: char) -> Result {
+    match c {
+        '\n' => write!(f, "\\n"),
+        '\\' => write!(f, "\\\\"),
+        c if c == quote || (c == '{' && quote == '"') => write!(f, "\\{}", c),
+        c => write!(f, "{}", c),
+    }
+}
+
 /// The primitives that Pijama's syntax supports.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Primitive {
     /// Built-in Print primitive.
     Print,
+    /// Built-in primitive concatenating two strings.
+    Concat,
+    /// Built-in primitive converting an integer to a floating point number.
+    IntToFloat,
+    /// Built-in primitive converting a floating point number to an integer, rounding towards
+    /// zero.
+    FloatToInt,
+    /// Built-in primitive returning the code point of a character.
+    CharToInt,
+    /// Built-in primitive returning the character with a code point.
+    IntToChar,
+    /// Built-in primitive returning the first element of a list.
+    Head,
+    /// Built-in primitive returning a list without its first element.
+    Tail,
+    /// Built-in primitive returning the number of elements of a list.
+    Len,
+    /// Built-in primitive returning a list with an element added at its end.
+    Push,
+    /// Built-in primitive returning the element of a list at an index, starting from zero.
+    Get,
+    /// Built-in primitive converting a value of a basic type to a string, in the same way it is
+    /// printed.
+    ToString,
+    /// Built-in primitive reading a line from the input of the program.
+    ReadLine,
+    /// Built-in primitive reading a line from the input of the program as an integer.
+    ReadInt,
+    /// Built-in primitive stopping the evaluation if its argument is `false`.
+    Assert,
+    /// Built-in primitive returning the absolute value of an integer.
+    Abs,
+    /// Built-in primitive returning `-1`, `0` or `1` depending on the sign of an integer.
+    Sign,
+    /// Built-in primitive returning the smallest of two integers.
+    Min,
+    /// Built-in primitive returning the largest of two integers.
+    Max,
+    /// Built-in primitive returning the greatest common divisor of two integers, which is never
+    /// negative.
+    Gcd,
+    /// Built-in primitive raising an integer to the power of another one.
+    Pow,
+    /// Built-in primitive returning the characters of a string between a start index, included,
+    /// and an end index, excluded.
+    Substring,
+    /// Built-in primitive returning the character of a string at an index, starting from zero.
+    CharAt,
+    /// Built-in primitive converting a string to an integer.
+    ParseInt,
+    /// Built-in primitive returning a random integer between two bounds, both included.
+    Random,
+    /// Built-in primitive running a function without arguments in a new thread.
+    Spawn,
+    /// Built-in primitive creating a new channel.
+    Channel,
+    /// Built-in primitive sending a value through a channel.
+    Send,
+    /// Built-in primitive receiving a value from a channel, waiting until there is one.
+    Recv,
+    /// Built-in primitive evaluating a lazy expression the first time it is called and returning
+    /// the same value afterwards.
+    Force,
+}
+
+impl Primitive {
+    /// Returns the primitive called `name` in the source code, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        use Primitive::*;
+
+        let prim = match name {
+            "print" => Print,
+            "concat" => Concat,
+            "int_to_float" => IntToFloat,
+            "float_to_int" => FloatToInt,
+            "char_to_int" => CharToInt,
+            "int_to_char" => IntToChar,
+            "head" => Head,
+            "tail" => Tail,
+            "len" => Len,
+            "push" => Push,
+            "get" => Get,
+            "to_string" => ToString,
+            "read_line" => ReadLine,
+            "read_int" => ReadInt,
+            "assert" => Assert,
+            "abs" => Abs,
+            "sign" => Sign,
+            "min" => Min,
+            "max" => Max,
+            "gcd" => Gcd,
+            "pow" => Pow,
+            "substring" => Substring,
+            "char_at" => CharAt,
+            "parse_int" => ParseInt,
+            "random" => Random,
+            "spawn" => Spawn,
+            "channel" => Channel,
+            "send" => Send,
+            "recv" => Recv,
+            "force" => Force,
+            _ => return None,
+        };
+        Some(prim)
+    }
+
+    /// Returns the number of arguments this primitive takes.
+    ///
+    /// Primitives take their arguments one at a time, and they are values until they receive all
+    /// of them.
+    pub fn arity(self) -> usize {
+        use Primitive::*;
+
+        match self {
+            ReadLine | ReadInt | Channel => 0,
+            Print | IntToFloat | FloatToInt | CharToInt | IntToChar | Head | Tail | Len
+            | ToString | Assert | Abs | Sign | ParseInt | Spawn | Recv | Force => 1,
+            Concat | Push | Get | Min | Max | Gcd | Pow | CharAt | Random | Send => 2,
+            Substring => 3,
+        }
+    }
 }
 
 impl<'a> Display for Primitive {
@@ -163,13 +442,47 @@ impl<'a> Display for Primitive {
 
         match self {
             Print => write!(f, "print"),
+            Concat => write!(f, "concat"),
+            IntToFloat => write!(f, "int_to_float"),
+            FloatToInt => write!(f, "float_to_int"),
+            CharToInt => write!(f, "char_to_int"),
+            IntToChar => write!(f, "int_to_char"),
+            Head => write!(f, "head"),
+            Tail => write!(f, "tail"),
+            Len => write!(f, "len"),
+            Push => write!(f, "push"),
+            Get => write!(f, "get"),
+            ToString => write!(f, "to_string"),
+            ReadLine => write!(f, "read_line"),
+            ReadInt => write!(f, "read_int"),
+            Assert => write!(f, "assert"),
+            Abs => write!(f, "abs"),
+            Sign => write!(f, "sign"),
+            Min => write!(f, "min"),
+            Max => write!(f, "max"),
+            Gcd => write!(f, "gcd"),
+            Pow => write!(f, "pow"),
+            Substring => write!(f, "substring"),
+            CharAt => write!(f, "char_at"),
+            ParseInt => write!(f, "parse_int"),
+            Random => write!(f, "random"),
+            Spawn => write!(f, "spawn"),
+            Channel => write!(f, "channel"),
+            Send => write!(f, "send"),
+            Recv => write!(f, "recv"),
+            Force => write!(f, "force"),
         }
     }
 }
 
 /// Encapsulates a conditional statement in Pijama's syntax of the
 /// form "if `cond` then `body`".
-#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Branch<'a> {
     /// The conditional part of the Branch that is checked for truthiness.
     pub cond: Located<Block<'a>>,
@@ -177,23 +490,125 @@ pub struct Branch<'a> {
     pub body: Located<Block<'a>>,
 }
 
+/// A variant in the declaration of an enum, with the types of its fields.
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Variant<'a> {
+    /// The name of the variant.
+    pub name: Located<Name<'a>>,
+    /// The types of the fields of the variant, which can be empty.
+    pub fields: Vec<Located<Ty>>,
+}
+
+/// A pattern in an arm of a `match` expression.
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Pattern<'a> {
+    /// The `_` pattern, which matches any value.
+    Wildcard,
+    /// A pattern matching a single literal value.
+    Literal(Literal),
+    /// A pattern matching a variant of an enum, binding a name to each one of its fields.
+    Variant(Located<Name<'a>>, Vec<Located<Name<'a>>>),
+}
+
+impl<'a> Display for Pattern<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Literal(literal) => write!(f, "{}", literal),
+            Pattern::Variant(name, binders) => {
+                write!(f, "{}", name.content)?;
+                if !binders.is_empty() {
+                    write!(f, "(")?;
+                    for (index, binder) in binders.iter().enumerate() {
+                        if index > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", binder.content)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An arm of a `match` expression of the form "`pattern` do `body`".
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Arm<'a> {
+    /// The pattern that the matched value is compared against.
+    pub pattern: Located<Pattern<'a>>,
+    /// The body of the Arm that is executed if the value matches `pattern`.
+    pub body: Located<Block<'a>>,
+}
+
 /// A [`Node`] in the AST that encapsulates the different
 /// expressions and statements that Pijama's syntax supports.
-#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Node<'a> {
     /// Expression containing a binary operation.
     BinaryOp(BinOp, Box<Located<Node<'a>>>, Box<Located<Node<'a>>>),
     /// Expression containing a unary operator.
     UnaryOp(UnOp, Box<Located<Node<'a>>>),
+    /// Expression containing a binary Operator used as a function, like `(+)`, with the operand
+    /// given in a section, like the `1` of `(_ + 1)`. At most one of the operands is given.
+    Operator(
+        BinOp,
+        Option<Box<Located<Node<'a>>>>,
+        Option<Box<Located<Node<'a>>>>,
+    ),
     /// Statement containing a Let binding.
     LetBind(TyAnnotation<Name<'a>>, Box<Located<Node<'a>>>),
-    /// Expression containing a conditional.
+    /// Statement containing a mutable Let binding, whose name can be assigned afterwards.
+    LetMut(TyAnnotation<Name<'a>>, Box<Located<Node<'a>>>),
+    /// Expression containing a Let binding whose name is only bound inside the second node, which
+    /// is the value of the expression.
+    LetIn(
+        TyAnnotation<Name<'a>>,
+        Box<Located<Node<'a>>>,
+        Box<Located<Node<'a>>>,
+    ),
+    /// Statement containing an Assignment of a new value to a mutable binding.
+    Assign(Located<Name<'a>>, Box<Located<Node<'a>>>),
+    /// Expression containing a conditional. A missing `else` block is represented by an empty
+    /// block.
     Cond(Branch<'a>, Vec<Branch<'a>>, Located<Block<'a>>),
-    /// Statement containing a Function Definition.
+    /// Expression containing a While loop, whose body is evaluated while its condition is true.
+    While(Branch<'a>),
+    /// Expression containing a For loop, whose body is evaluated once for each integer from the
+    /// first node up to the second one, excluding it, with the name bound to that integer.
+    For(
+        Located<Name<'a>>,
+        Box<Located<Node<'a>>>,
+        Box<Located<Node<'a>>>,
+        Located<Block<'a>>,
+    ),
+    /// Statement containing a Function Definition, with the lines of its doc comment.
     FnDef(
         Located<Name<'a>>,
         Vec<TyAnnotation<Name<'a>>>,
         TyAnnotation<Block<'a>>,
+        Vec<&'a str>,
     ),
     /// Expression containing an Anonymous Function.
     AnonFn(Vec<TyAnnotation<Name<'a>>>, TyAnnotation<Block<'a>>),
@@ -201,8 +616,29 @@ pub enum Node<'a> {
     Call(Box<Located<Node<'a>>>, Block<'a>),
     /// Expression containing a Literal.
     Literal(Literal),
+    /// Expression containing a List literal with its elements.
+    List(Block<'a>),
+    /// Expression containing an Interpolated string with its parts in order, which are either
+    /// string literals or interpolated nodes.
+    Interpolation(Block<'a>),
+    /// Expression containing a Type ascription, which states the type of its node.
+    Ascription(Box<Located<Node<'a>>>, Located<Ty>),
+    /// Expression containing a `do ... end` Block, whose value is the value of its last node.
+    Block(Located<Block<'a>>),
+    /// Expression containing a Lazy expression, whose node is evaluated the first time it is
+    /// forced.
+    Lazy(Box<Located<Node<'a>>>),
     /// Expression containing a Name.
     Name(Name<'a>),
     /// Expression containing a Primitive function.
     PrimFn(Primitive),
+    /// Statement containing an Enum declaration.
+    EnumDef(Located<Name<'a>>, Vec<Variant<'a>>),
+    /// Expression containing a Match.
+    Match(Box<Located<Node<'a>>>, Vec<Arm<'a>>),
+    /// Statement containing an Import of the module in the given path.
+    Import(String),
+    /// Part of the program that could not be parsed, which is only produced by
+    /// `parse_recovering` and spans the code that was skipped.
+    Error,
 }
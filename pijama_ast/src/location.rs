@@ -1,6 +1,19 @@
 //! Utilities for capturing and representing the location
 //! of tokens in the source code file.
-use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+//!
+//! Locations only keep byte offsets. The lines and columns of those offsets are found with a
+//! [`LineIndex`] of the source code, which is built once and reused for every location of the
+//! same source.
+use alloc::vec::Vec;
+use core::{
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    ops::Range,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::node_id::NodeId;
 
 /// Type representing a length of tokens and their location
 /// in the source code file.
@@ -10,36 +23,179 @@ impl<'a> From<Span<'a>> for Location {
     /// Creates a `Location` instance from a `Span`.
     fn from(span: Span<'a>) -> Self {
         let start = span.location_offset();
-        let end = start + 1;
-        Location { start, end }
+        Location::new(start, start + 1)
+    }
+}
+
+/// Identifies the source file that a [`Location`] refers to.
+///
+/// The parser only knows about the input it is given, so its locations refer to
+/// [`FileId::MAIN`]. Code that combines several files, like the driver adding the definitions of
+/// imported modules to a program, numbers the other files and moves their locations to them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct FileId(pub u32);
+
+impl FileId {
+    /// The file being compiled.
+    pub const MAIN: FileId = FileId(0);
+
+    /// Returns whether this is [`FileId::MAIN`].
+    pub fn is_main(&self) -> bool {
+        *self == FileId::MAIN
     }
 }
 
 /// Represents a location in the source code file.
 ///
 /// Both the start and end correspond to locations reported by `nom_locate`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Location {
     /// Start of the location.
     pub start: usize,
     /// End of the location.
     pub end: usize,
+    /// The file of the location. It is only serialized if it is not [`FileId::MAIN`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "FileId::is_main")
+    )]
+    pub file: FileId,
 }
 
 impl Location {
-    /// Constructs a new `Location` instance.
+    /// Constructs a new `Location` instance in [`FileId::MAIN`].
     pub const fn new(start: usize, end: usize) -> Self {
-        Location { start, end }
+        Location {
+            start,
+            end,
+            file: FileId::MAIN,
+        }
+    }
+    /// Returns this location moved to `file`.
+    pub const fn in_file(self, file: FileId) -> Self {
+        Location { file, ..self }
     }
     /// Creates a new `Located` consuming this `Location`.
     pub fn with_content<T: Debug>(self, content: T) -> Located<T> {
         Located::new(content, self)
     }
+    /// Returns the line and column where this location starts in the source indexed by `index`.
+    pub fn to_line_col(&self, index: &LineIndex<'_>) -> LineCol {
+        index.line_col(self.start)
+    }
+    /// Returns the lines and columns where this location starts and ends in the source indexed by
+    /// `index`.
+    pub fn to_line_col_range(&self, index: &LineIndex<'_>) -> Range<LineCol> {
+        index.line_col(self.start)..index.line_col(self.end)
+    }
+}
+
+/// A line and a column of the source code.
+///
+/// Both of them start at zero, and columns count characters instead of bytes. Messages for users
+/// usually add one to each of them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct LineCol {
+    /// The line, starting at zero.
+    pub line: usize,
+    /// The number of characters before the position in its line.
+    pub column: usize,
+}
+
+/// The lines of a source code, used to convert byte offsets to lines and columns and back.
+///
+/// A line starts after each `\n` of the source code, so a source ending with a line break has an
+/// empty last line. Carriage returns are part of the lines they end.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LineIndex<'a> {
+    input: &'a str,
+    /// The byte offset where each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Creates the index of the lines of `input`.
+    pub fn new(input: &'a str) -> Self {
+        let line_starts = core::iter::once(0)
+            .chain(input.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        LineIndex { input, line_starts }
+    }
+
+    /// Returns the indexed source code.
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// Returns the number of lines of the source code, which is at least one.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the line of the byte at `offset`.
+    ///
+    /// Line breaks are part of the line they end. Offsets past the end of the source code are in
+    /// the last line.
+    pub fn line(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+
+    /// Returns the byte range of `line`, including its line break, or `None` if the source code
+    /// does not have that line.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.input.len());
+        Some(start..end)
+    }
+
+    /// Returns the code of `line` without its line break, or `None` if the source code does not
+    /// have that line.
+    pub fn line_text(&self, line: usize) -> Option<&'a str> {
+        let code = &self.input[self.line_range(line)?];
+        Some(code.strip_suffix('\n').unwrap_or(code))
+    }
+
+    /// Returns the line and column of `offset`.
+    ///
+    /// Offsets past the end of the source code are placed at its end, and offsets inside a
+    /// character are placed at the start of that character.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let mut offset = offset.min(self.input.len());
+        while !self.input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line(offset);
+        let column = self.input[self.line_starts[line]..offset].chars().count();
+        LineCol { line, column }
+    }
+
+    /// Returns the byte offset of `pos`, or `None` if the source code does not have its line or
+    /// the line does not have its column.
+    ///
+    /// The column right after the last character of a line is its end.
+    pub fn offset(&self, pos: LineCol) -> Option<usize> {
+        let start = *self.line_starts.get(pos.line)?;
+        let code = self.line_text(pos.line)?;
+        code.char_indices()
+            .map(|(offset, _)| offset)
+            .chain(core::iter::once(code.len()))
+            .nth(pos.column)
+            .map(|offset| start + offset)
+    }
 }
 
 /// Adding two locations `l1` and `l2` returns a location starting in `l1.start` and ending in
 /// `l2.end`.
-impl std::ops::Add for Location {
+impl core::ops::Add for Location {
     type Output = Self;
     fn add(mut self, other: Self) -> Self {
         self.end = other.end;
@@ -49,28 +205,43 @@ impl std::ops::Add for Location {
 
 /// Wrapper type with a `Location` field.
 ///
-/// It is used to add a location to elements in the AST and intermediate representations.
+/// It is used to add a location to elements in the AST and intermediate representations. The
+/// nodes of the AST and the terms lowered from them also have the [`NodeId`] given by the parser.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug)]
 pub struct Located<T: Debug> {
     /// Content of the wrapper.
     pub content: T,
     /// Location of `content` in the source file.
     pub loc: Location,
+    /// Id of the node `content` comes from, or [`NodeId::DUMMY`] if it was not parsed. It is
+    /// only serialized if it is not [`NodeId::DUMMY`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "NodeId::is_dummy")
+    )]
+    pub id: NodeId,
 }
 
 impl<T: Debug> Located<T> {
-    /// Creates a new `Located`.
+    /// Creates a new `Located` with the [`NodeId::DUMMY`] id.
     pub fn new(content: T, loc: impl Into<Location>) -> Self {
         Located {
             content,
             loc: loc.into(),
+            id: NodeId::DUMMY,
         }
     }
-    /// Maps the content of the `Located` leaving its location untouched.
+    /// Returns this `Located` with the id `id`.
+    pub fn with_id(self, id: NodeId) -> Self {
+        Located { id, ..self }
+    }
+    /// Maps the content of the `Located` leaving its location and id untouched.
     pub fn map<U: Debug, F: FnOnce(T) -> U>(self, f: F) -> Located<U> {
         Located {
             content: f(self.content),
             loc: self.loc,
+            id: self.id,
         }
     }
     /// Like `map` but the closure is allowed to return a `Result`.
@@ -78,18 +249,18 @@ impl<T: Debug> Located<T> {
         Ok(Located {
             content: f(self.content)?,
             loc: self.loc,
+            id: self.id,
         })
     }
     /// Joins two `Located`s by adding their locations and joining their contents using a closure.
+    ///
+    /// The joined `Located` has the [`NodeId::DUMMY`] id.
     pub fn zip_with<U: Debug, V: Debug, F: FnOnce(T, U) -> V>(
         self,
         other: Located<U>,
         f: F,
     ) -> Located<V> {
-        Located {
-            content: f(self.content, other.content),
-            loc: self.loc + other.loc,
-        }
+        Located::new(f(self.content, other.content), self.loc + other.loc)
     }
 }
 
@@ -99,9 +270,9 @@ impl<T: Display + Debug> Display for Located<T> {
     }
 }
 
-/// `Located`s are compared by their content only.
+/// `Located`s are compared by their content only, ignoring their locations and ids.
 impl<T: Eq + Debug> Eq for Located<T> {}
-/// `Located`s are compared by their content only.
+/// `Located`s are compared by their content only, ignoring their locations and ids.
 impl<T: PartialEq + Debug> PartialEq for Located<T> {
     fn eq(&self, other: &Self) -> bool {
         self.content == other.content
@@ -110,7 +281,7 @@ impl<T: PartialEq + Debug> PartialEq for Located<T> {
 
 impl<T: Clone + Debug> Clone for Located<T> {
     fn clone(&self) -> Self {
-        Located::new(self.content.clone(), self.loc)
+        Located::new(self.content.clone(), self.loc).with_id(self.id)
     }
 }
 
@@ -0,0 +1,128 @@
+//! Identifiers of the nodes of the AST.
+//!
+//! Locations cannot tell apart the nodes that share them, like the nodes added when desugaring,
+//! which get the location of the code they come from. The parser gives each node a [`NodeId`]
+//! instead, which is kept by the terms lowered from it, so the later phases can refer to a node
+//! without comparing locations.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Arm, Block, Branch, FileId, Located, Node};
+
+/// Identifies a node of the AST, and the terms lowered from it by the later phases.
+///
+/// The nodes of a file are numbered from zero by [`number_nodes`] in the order they appear in
+/// the source code, parents before their children. Terms that are not lowered from a parsed node
+/// have the [`NodeId::DUMMY`] id.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NodeId {
+    /// The file of the node, like the file of its location.
+    pub file: FileId,
+    /// The position of the node in its file.
+    pub index: u32,
+}
+
+impl NodeId {
+    /// The id of the nodes and terms that were not parsed.
+    pub const DUMMY: NodeId = NodeId {
+        file: FileId::MAIN,
+        index: u32::MAX,
+    };
+
+    /// Constructs the id of the node with `index` in [`FileId::MAIN`].
+    pub const fn new(index: u32) -> Self {
+        NodeId {
+            file: FileId::MAIN,
+            index,
+        }
+    }
+
+    /// Returns this id moved to `file`.
+    pub const fn in_file(self, file: FileId) -> Self {
+        NodeId { file, ..self }
+    }
+
+    /// Returns whether this is [`NodeId::DUMMY`].
+    pub fn is_dummy(&self) -> bool {
+        *self == NodeId::DUMMY
+    }
+}
+
+impl Default for NodeId {
+    fn default() -> Self {
+        NodeId::DUMMY
+    }
+}
+
+/// Gives a new id to each node of `blk`, numbering them from zero in [`FileId::MAIN`].
+///
+/// The ids follow the order of the nodes in the source code, parents before their children, so
+/// parsing the same code again gives the same ids.
+pub fn number_nodes(blk: &mut Block<'_>) {
+    let mut next = 0;
+    blk.iter_mut().for_each(|node| number(node, &mut next));
+}
+
+fn number(node: &mut Located<Node<'_>>, next: &mut u32) {
+    node.id = NodeId::new(*next);
+    *next += 1;
+    match &mut node.content {
+        Node::BinaryOp(_, node1, node2) | Node::LetIn(_, node1, node2) => {
+            number(node1, next);
+            number(node2, next);
+        }
+        Node::UnaryOp(_, node)
+        | Node::LetBind(_, node)
+        | Node::LetMut(_, node)
+        | Node::Assign(_, node)
+        | Node::Ascription(node, _)
+        | Node::Lazy(node) => number(node, next),
+        Node::Operator(_, node1, node2) => {
+            for node in node1.iter_mut().chain(node2) {
+                number(node, next);
+            }
+        }
+        Node::Cond(if_branch, branches, else_blk) => {
+            for Branch { cond, body } in Some(if_branch).into_iter().chain(branches) {
+                number_block(cond, next);
+                number_block(body, next);
+            }
+            number_block(else_blk, next);
+        }
+        Node::While(Branch { cond, body }) => {
+            number_block(cond, next);
+            number_block(body, next);
+        }
+        Node::For(_, start, end, body) => {
+            number(start, next);
+            number(end, next);
+            number_block(body, next);
+        }
+        Node::FnDef(_, _, body, _) | Node::AnonFn(_, body) => number_block(&mut body.item, next),
+        Node::Call(func, args) => {
+            number(func, next);
+            args.iter_mut().for_each(|node| number(node, next));
+        }
+        Node::List(elems) | Node::Interpolation(elems) => {
+            elems.iter_mut().for_each(|node| number(node, next))
+        }
+        Node::Block(blk) => number_block(blk, next),
+        Node::Match(node, arms) => {
+            number(node, next);
+            for Arm { body, .. } in arms {
+                number_block(body, next);
+            }
+        }
+        Node::Literal(_)
+        | Node::Name(_)
+        | Node::PrimFn(_)
+        | Node::EnumDef(_, _)
+        | Node::Import(_)
+        | Node::Error => (),
+    }
+}
+
+fn number_block(blk: &mut Located<Block<'_>>, next: &mut u32) {
+    blk.content.iter_mut().for_each(|node| number(node, next));
+}
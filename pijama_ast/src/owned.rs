@@ -0,0 +1,437 @@
+//! An owned version of the AST.
+//!
+//! The [`Node`] type borrows every name from the source code, so an AST cannot outlive the string
+//! it was parsed from. The types in this module mirror the ones in the crate root but own their
+//! names, which makes it possible to keep an AST around after the input is gone (e.g. in a REPL).
+//!
+//! An [`OwnedNode`] can be built from a [`Node`] using [`Node::to_owned`] and it can be borrowed
+//! back as a [`Node`] using [`OwnedNode::as_node`] to run any of the existing passes over it.
+use alloc::{borrow::ToOwned, boxed::Box, collections::VecDeque, string::String, vec::Vec};
+use core::fmt::{Display, Formatter, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ty::{Ty, TyAnnotation},
+    Arm, BinOp, Block, Branch, Literal, Located, Name, Node, Pattern, Primitive, UnOp, Variant,
+};
+
+/// The owned version of [`Block`].
+pub type OwnedBlock = VecDeque<Located<OwnedNode>>;
+
+/// The owned version of [`Name`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct OwnedName(pub String);
+
+impl OwnedName {
+    /// Borrows this name as a [`Name`].
+    pub fn as_name(&self) -> Name<'_> {
+        Name(&self.0)
+    }
+}
+
+impl<'a> From<Name<'a>> for OwnedName {
+    fn from(name: Name<'a>) -> Self {
+        OwnedName(name.0.to_owned())
+    }
+}
+
+impl Display for OwnedName {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The owned version of [`Branch`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OwnedBranch {
+    /// The conditional part of the Branch that is checked for truthiness.
+    pub cond: Located<OwnedBlock>,
+    /// The body of the Branch that is executed if `cond` is true.
+    pub body: Located<OwnedBlock>,
+}
+
+/// The owned version of [`Variant`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OwnedVariant {
+    /// The name of the variant.
+    pub name: Located<OwnedName>,
+    /// The types of the fields of the variant, which can be empty.
+    pub fields: Vec<Located<Ty>>,
+}
+
+/// The owned version of [`Pattern`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OwnedPattern {
+    /// The `_` pattern, which matches any value.
+    Wildcard,
+    /// A pattern matching a single literal value.
+    Literal(Literal),
+    /// A pattern matching a variant of an enum, binding a name to each one of its fields.
+    Variant(Located<OwnedName>, Vec<Located<OwnedName>>),
+}
+
+/// The owned version of [`Arm`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OwnedArm {
+    /// The pattern that the matched value is compared against.
+    pub pattern: Located<OwnedPattern>,
+    /// The body of the Arm that is executed if the value matches `pattern`.
+    pub body: Located<OwnedBlock>,
+}
+
+/// The owned version of [`Node`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OwnedNode {
+    /// Expression containing a binary operation.
+    BinaryOp(BinOp, Box<Located<OwnedNode>>, Box<Located<OwnedNode>>),
+    /// Expression containing a unary operator.
+    UnaryOp(UnOp, Box<Located<OwnedNode>>),
+    /// Expression containing a binary Operator used as a function.
+    Operator(
+        BinOp,
+        Option<Box<Located<OwnedNode>>>,
+        Option<Box<Located<OwnedNode>>>,
+    ),
+    /// Statement containing a Let binding.
+    LetBind(TyAnnotation<OwnedName>, Box<Located<OwnedNode>>),
+    /// Statement containing a mutable Let binding, whose name can be assigned afterwards.
+    LetMut(TyAnnotation<OwnedName>, Box<Located<OwnedNode>>),
+    /// Expression containing a Let binding whose name is only bound inside the second node, which
+    /// is the value of the expression.
+    LetIn(
+        TyAnnotation<OwnedName>,
+        Box<Located<OwnedNode>>,
+        Box<Located<OwnedNode>>,
+    ),
+    /// Statement containing an Assignment of a new value to a mutable binding.
+    Assign(Located<OwnedName>, Box<Located<OwnedNode>>),
+    /// Expression containing a conditional.
+    Cond(OwnedBranch, Vec<OwnedBranch>, Located<OwnedBlock>),
+    /// Expression containing a While loop, whose body is evaluated while its condition is true.
+    While(OwnedBranch),
+    /// Expression containing a For loop, whose body is evaluated once for each integer from the
+    /// first node up to the second one, excluding it, with the name bound to that integer.
+    For(
+        Located<OwnedName>,
+        Box<Located<OwnedNode>>,
+        Box<Located<OwnedNode>>,
+        Located<OwnedBlock>,
+    ),
+    /// Statement containing a Function Definition, with the lines of its doc comment.
+    FnDef(
+        Located<OwnedName>,
+        Vec<TyAnnotation<OwnedName>>,
+        TyAnnotation<OwnedBlock>,
+        Vec<String>,
+    ),
+    /// Expression containing an Anonymous Function.
+    AnonFn(Vec<TyAnnotation<OwnedName>>, TyAnnotation<OwnedBlock>),
+    /// Expression containing a Function Call.
+    Call(Box<Located<OwnedNode>>, OwnedBlock),
+    /// Expression containing a Literal.
+    Literal(Literal),
+    /// Expression containing a List literal with its elements.
+    List(OwnedBlock),
+    /// Expression containing an Interpolated string with its parts in order, which are either
+    /// string literals or interpolated nodes.
+    Interpolation(OwnedBlock),
+    /// Expression containing a Type ascription, which states the type of its node.
+    Ascription(Box<Located<OwnedNode>>, Located<Ty>),
+    /// Expression containing a `do ... end` Block.
+    Block(Located<OwnedBlock>),
+    /// Expression containing a Lazy expression, whose node is evaluated the first time it is
+    /// forced.
+    Lazy(Box<Located<OwnedNode>>),
+    /// Expression containing a Name.
+    Name(OwnedName),
+    /// Expression containing a Primitive function.
+    PrimFn(Primitive),
+    /// Statement containing an Enum declaration.
+    EnumDef(Located<OwnedName>, Vec<OwnedVariant>),
+    /// Expression containing a Match.
+    Match(Box<Located<OwnedNode>>, Vec<OwnedArm>),
+    /// Statement containing an Import of the module in the given path.
+    Import(String),
+    /// Part of the program that could not be parsed.
+    Error,
+}
+
+impl<'a> Node<'a> {
+    /// Returns a deep copy of this node that does not borrow from the source code.
+    pub fn to_owned(&self) -> OwnedNode {
+        match self {
+            Node::BinaryOp(op, node1, node2) => OwnedNode::BinaryOp(
+                *op,
+                Box::new(node_to_owned(node1)),
+                Box::new(node_to_owned(node2)),
+            ),
+            Node::UnaryOp(op, node) => OwnedNode::UnaryOp(*op, Box::new(node_to_owned(node))),
+            Node::Operator(op, node1, node2) => OwnedNode::Operator(
+                *op,
+                node1.as_ref().map(|node| Box::new(node_to_owned(node))),
+                node2.as_ref().map(|node| Box::new(node_to_owned(node))),
+            ),
+            Node::LetBind(annotation, node) => {
+                OwnedNode::LetBind(name_to_owned(annotation), Box::new(node_to_owned(node)))
+            }
+            Node::LetMut(annotation, node) => {
+                OwnedNode::LetMut(name_to_owned(annotation), Box::new(node_to_owned(node)))
+            }
+            Node::LetIn(annotation, node1, node2) => OwnedNode::LetIn(
+                name_to_owned(annotation),
+                Box::new(node_to_owned(node1)),
+                Box::new(node_to_owned(node2)),
+            ),
+            Node::Assign(name, node) => {
+                OwnedNode::Assign(name.map(OwnedName::from), Box::new(node_to_owned(node)))
+            }
+            Node::Cond(if_branch, branches, el_blk) => OwnedNode::Cond(
+                branch_to_owned(if_branch),
+                branches.iter().map(branch_to_owned).collect(),
+                located_block_to_owned(el_blk),
+            ),
+            Node::While(branch) => OwnedNode::While(branch_to_owned(branch)),
+            Node::For(name, start, end, body) => OwnedNode::For(
+                name.map(OwnedName::from),
+                Box::new(node_to_owned(start)),
+                Box::new(node_to_owned(end)),
+                located_block_to_owned(body),
+            ),
+            Node::FnDef(name, args, body, docs) => OwnedNode::FnDef(
+                name.map(OwnedName::from),
+                args.iter().map(name_to_owned).collect(),
+                body_to_owned(body),
+                docs.iter().map(|line| (*line).to_owned()).collect(),
+            ),
+            Node::AnonFn(args, body) => OwnedNode::AnonFn(
+                args.iter().map(name_to_owned).collect(),
+                body_to_owned(body),
+            ),
+            Node::Call(func, args) => {
+                OwnedNode::Call(Box::new(node_to_owned(func)), block_to_owned(args))
+            }
+            Node::Literal(literal) => OwnedNode::Literal(literal.clone()),
+            Node::List(elems) => OwnedNode::List(block_to_owned(elems)),
+            Node::Interpolation(parts) => OwnedNode::Interpolation(block_to_owned(parts)),
+            Node::Ascription(node, ty) => {
+                OwnedNode::Ascription(Box::new(node_to_owned(node)), ty.clone())
+            }
+            Node::Block(block) => OwnedNode::Block(located_block_to_owned(block)),
+            Node::Lazy(node) => OwnedNode::Lazy(Box::new(node_to_owned(node))),
+            Node::Name(name) => OwnedNode::Name((*name).into()),
+            Node::PrimFn(prim) => OwnedNode::PrimFn(*prim),
+            Node::EnumDef(name, variants) => OwnedNode::EnumDef(
+                name.map(OwnedName::from),
+                variants.iter().map(variant_to_owned).collect(),
+            ),
+            Node::Match(node, arms) => OwnedNode::Match(
+                Box::new(node_to_owned(node)),
+                arms.iter().map(arm_to_owned).collect(),
+            ),
+            Node::Import(path) => OwnedNode::Import(path.clone()),
+            Node::Error => OwnedNode::Error,
+        }
+    }
+}
+
+impl OwnedNode {
+    /// Borrows this node as a [`Node`].
+    ///
+    /// The returned node borrows its names from `self`, this function allocates a new tree but
+    /// does not copy any names.
+    pub fn as_node(&self) -> Node<'_> {
+        match self {
+            OwnedNode::BinaryOp(op, node1, node2) => {
+                Node::BinaryOp(*op, Box::new(as_node(node1)), Box::new(as_node(node2)))
+            }
+            OwnedNode::UnaryOp(op, node) => Node::UnaryOp(*op, Box::new(as_node(node))),
+            OwnedNode::Operator(op, node1, node2) => Node::Operator(
+                *op,
+                node1.as_ref().map(|node| Box::new(as_node(node))),
+                node2.as_ref().map(|node| Box::new(as_node(node))),
+            ),
+            OwnedNode::LetBind(annotation, node) => {
+                Node::LetBind(as_name(annotation), Box::new(as_node(node)))
+            }
+            OwnedNode::LetMut(annotation, node) => {
+                Node::LetMut(as_name(annotation), Box::new(as_node(node)))
+            }
+            OwnedNode::LetIn(annotation, node1, node2) => Node::LetIn(
+                as_name(annotation),
+                Box::new(as_node(node1)),
+                Box::new(as_node(node2)),
+            ),
+            OwnedNode::Assign(name, node) => {
+                Node::Assign(as_located_name(name), Box::new(as_node(node)))
+            }
+            OwnedNode::Cond(if_branch, branches, el_blk) => Node::Cond(
+                as_branch(if_branch),
+                branches.iter().map(as_branch).collect(),
+                as_located_block(el_blk),
+            ),
+            OwnedNode::While(branch) => Node::While(as_branch(branch)),
+            OwnedNode::For(name, start, end, body) => Node::For(
+                as_located_name(name),
+                Box::new(as_node(start)),
+                Box::new(as_node(end)),
+                as_located_block(body),
+            ),
+            OwnedNode::FnDef(name, args, body, docs) => Node::FnDef(
+                as_located_name(name),
+                args.iter().map(as_name).collect(),
+                as_body(body),
+                docs.iter().map(String::as_str).collect(),
+            ),
+            OwnedNode::AnonFn(args, body) => {
+                Node::AnonFn(args.iter().map(as_name).collect(), as_body(body))
+            }
+            OwnedNode::Call(func, args) => Node::Call(Box::new(as_node(func)), as_block(args)),
+            OwnedNode::Literal(literal) => Node::Literal(literal.clone()),
+            OwnedNode::List(elems) => Node::List(as_block(elems)),
+            OwnedNode::Interpolation(parts) => Node::Interpolation(as_block(parts)),
+            OwnedNode::Ascription(node, ty) => {
+                Node::Ascription(Box::new(as_node(node)), ty.clone())
+            }
+            OwnedNode::Block(block) => Node::Block(as_located_block(block)),
+            OwnedNode::Lazy(node) => Node::Lazy(Box::new(as_node(node))),
+            OwnedNode::Name(name) => Node::Name(name.as_name()),
+            OwnedNode::PrimFn(prim) => Node::PrimFn(*prim),
+            OwnedNode::EnumDef(name, variants) => Node::EnumDef(
+                as_located_name(name),
+                variants.iter().map(as_variant).collect(),
+            ),
+            OwnedNode::Match(node, arms) => {
+                Node::Match(Box::new(as_node(node)), arms.iter().map(as_arm).collect())
+            }
+            OwnedNode::Import(path) => Node::Import(path.clone()),
+            OwnedNode::Error => Node::Error,
+        }
+    }
+}
+
+/// Returns a deep copy of `block` that does not borrow from the source code.
+pub fn block_to_owned(block: &Block<'_>) -> OwnedBlock {
+    block.iter().map(node_to_owned).collect()
+}
+
+/// Borrows an [`OwnedBlock`] as a [`Block`].
+pub fn as_block(block: &OwnedBlock) -> Block<'_> {
+    block.iter().map(as_node).collect()
+}
+
+fn node_to_owned(node: &Located<Node<'_>>) -> Located<OwnedNode> {
+    Located::new(node.content.to_owned(), node.loc)
+}
+
+fn located_block_to_owned(block: &Located<Block<'_>>) -> Located<OwnedBlock> {
+    Located::new(block_to_owned(&block.content), block.loc)
+}
+
+fn branch_to_owned(branch: &Branch<'_>) -> OwnedBranch {
+    OwnedBranch {
+        cond: located_block_to_owned(&branch.cond),
+        body: located_block_to_owned(&branch.body),
+    }
+}
+
+fn name_to_owned(annotation: &TyAnnotation<Name<'_>>) -> TyAnnotation<OwnedName> {
+    TyAnnotation {
+        item: annotation.item.map(OwnedName::from),
+        ty: annotation.ty.clone(),
+    }
+}
+
+fn body_to_owned(annotation: &TyAnnotation<Block<'_>>) -> TyAnnotation<OwnedBlock> {
+    TyAnnotation {
+        item: located_block_to_owned(&annotation.item),
+        ty: annotation.ty.clone(),
+    }
+}
+
+fn variant_to_owned(variant: &Variant<'_>) -> OwnedVariant {
+    OwnedVariant {
+        name: variant.name.map(OwnedName::from),
+        fields: variant.fields.clone(),
+    }
+}
+
+fn arm_to_owned(arm: &Arm<'_>) -> OwnedArm {
+    let pattern = match &arm.pattern.content {
+        Pattern::Wildcard => OwnedPattern::Wildcard,
+        Pattern::Literal(literal) => OwnedPattern::Literal(literal.clone()),
+        Pattern::Variant(name, binders) => OwnedPattern::Variant(
+            name.map(OwnedName::from),
+            binders
+                .iter()
+                .map(|binder| binder.map(OwnedName::from))
+                .collect(),
+        ),
+    };
+    OwnedArm {
+        pattern: Located::new(pattern, arm.pattern.loc),
+        body: located_block_to_owned(&arm.body),
+    }
+}
+
+fn as_node(node: &Located<OwnedNode>) -> Located<Node<'_>> {
+    Located::new(node.content.as_node(), node.loc)
+}
+
+fn as_located_block(block: &Located<OwnedBlock>) -> Located<Block<'_>> {
+    Located::new(as_block(&block.content), block.loc)
+}
+
+fn as_branch(branch: &OwnedBranch) -> Branch<'_> {
+    Branch {
+        cond: as_located_block(&branch.cond),
+        body: as_located_block(&branch.body),
+    }
+}
+
+fn as_located_name(name: &Located<OwnedName>) -> Located<Name<'_>> {
+    Located::new(name.content.as_name(), name.loc)
+}
+
+fn as_variant(variant: &OwnedVariant) -> Variant<'_> {
+    Variant {
+        name: as_located_name(&variant.name),
+        fields: variant.fields.clone(),
+    }
+}
+
+fn as_arm(arm: &OwnedArm) -> Arm<'_> {
+    let pattern = match &arm.pattern.content {
+        OwnedPattern::Wildcard => Pattern::Wildcard,
+        OwnedPattern::Literal(literal) => Pattern::Literal(literal.clone()),
+        OwnedPattern::Variant(name, binders) => Pattern::Variant(
+            as_located_name(name),
+            binders.iter().map(as_located_name).collect(),
+        ),
+    };
+    Arm {
+        pattern: Located::new(pattern, arm.pattern.loc),
+        body: as_located_block(&arm.body),
+    }
+}
+
+fn as_name(annotation: &TyAnnotation<OwnedName>) -> TyAnnotation<Name<'_>> {
+    TyAnnotation {
+        item: as_located_name(&annotation.item),
+        ty: annotation.ty.clone(),
+    }
+}
+
+fn as_body(annotation: &TyAnnotation<OwnedBlock>) -> TyAnnotation<Block<'_>> {
+    TyAnnotation {
+        item: as_located_block(&annotation.item),
+        ty: annotation.ty.clone(),
+    }
+}
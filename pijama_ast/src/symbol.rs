@@ -0,0 +1,72 @@
+//! Interned names.
+//!
+//! A [`SymbolTable`] maps each distinct [`Name`] to a [`Symbol`], a small integer that can be
+//! copied and compared without looking at the characters of the name. Phases that compare names
+//! many times intern them once and work with symbols afterwards.
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::Name;
+
+/// An interned name.
+///
+/// Two symbols created by the same [`SymbolTable`] are equal if and only if their names are
+/// equal. Comparing symbols from different tables is meaningless.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the position of the symbol in its table.
+    ///
+    /// Symbols are numbered consecutively from zero in the order they were interned.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A table of interned names.
+#[derive(Debug, Default)]
+pub struct SymbolTable<'a> {
+    symbols: BTreeMap<&'a str, Symbol>,
+    names: Vec<Name<'a>>,
+}
+
+impl<'a> SymbolTable<'a> {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the symbol of `name`, adding it to the table if it was not interned before.
+    pub fn intern(&mut self, name: Name<'a>) -> Symbol {
+        let names = &mut self.names;
+        *self.symbols.entry(name.0).or_insert_with(|| {
+            let symbol = Symbol(names.len() as u32);
+            names.push(name);
+            symbol
+        })
+    }
+
+    /// Returns the symbol of `name` if it has been interned.
+    pub fn get(&self, name: Name<'_>) -> Option<Symbol> {
+        self.symbols.get(name.0).copied()
+    }
+
+    /// Returns the name of `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `symbol` was not created by this table.
+    pub fn resolve(&self, symbol: Symbol) -> Name<'a> {
+        self.names[symbol.index()]
+    }
+
+    /// Returns the number of interned names.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if no names have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -1,5 +1,9 @@
 //! The AST representation of types.
-use std::fmt::Debug;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::Located;
 
@@ -7,16 +11,37 @@ use crate::Located;
 ///
 /// This type must only represent the kinds of types that Pijama's AST can represent. Other `Ty`
 /// types exist with different purposes.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Ty {
     /// The type of booleans.
     Bool,
     /// The type of (signed) integers.
     Int,
+    /// The type of floating point numbers.
+    Float,
     /// The [unit type](https://en.wikipedia.org/wiki/Unit_type).
     Unit,
+    /// The type of characters.
+    Char,
+    /// The type of strings.
+    String,
+    /// The type of lists whose elements have the given type, written as `[Int]`.
+    List(Box<Ty>),
+    /// The type of channels carrying values of the given type, written as `Chan(Int)`.
+    Chan(Box<Ty>),
+    /// The type of lazy expressions whose value has the given type, written as `Lazy(Int)`.
+    Lazy(Box<Ty>),
     /// The type of functions between two types.
     Arrow(Box<Ty>, Box<Ty>),
+    /// A type variable, written as a name like `a`.
+    ///
+    /// Type variables with the same name in the annotations of a definition stand for the same
+    /// type.
+    Var(String),
+    /// An enum type with its type arguments, written as a name starting with an uppercase letter
+    /// like `Shape`, followed by its arguments in round brackets if it has any, like `Option(Int)`.
+    Adt(String, Vec<Ty>),
     /// A missing type. Used when an item in the AST did not have a type annotation.
     Missing,
 }
@@ -25,7 +50,8 @@ pub enum Ty {
 ///
 /// This represents an annotation of an AST item with a type and is used to represent any type
 /// annotations written by the user.
-#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TyAnnotation<I: Debug + Eq + PartialEq> {
     /// The annotated item.
     pub item: Located<I>,
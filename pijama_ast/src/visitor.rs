@@ -1,6 +1,7 @@
 //! Trait to traverse the AST.
 use crate::{
-    ty::TyAnnotation, BinOp, Block, Branch, Literal, Located, Name, Node, Primitive, UnOp,
+    ty::{Ty, TyAnnotation},
+    Arm, BinOp, Block, Branch, Literal, Located, Name, Node, Primitive, UnOp, Variant,
 };
 
 /// Trait for the node visitor pattern.
@@ -39,14 +40,35 @@ pub trait NodeVisitor<'a> {
                 self.visit_binary_op(*op, node1.as_ref(), node2.as_ref())
             }
             Node::UnaryOp(op, node) => self.visit_unary_op(*op, node.as_ref()),
+            Node::Operator(op, node1, node2) => {
+                self.visit_operator(*op, node1.as_deref(), node2.as_deref())
+            }
             Node::LetBind(annotation, node) => self.visit_let_bind(annotation, node.as_ref()),
+            Node::LetIn(annotation, node1, node2) => {
+                self.visit_let_in(annotation, node1.as_ref(), node2.as_ref())
+            }
+            Node::LetMut(annotation, node) => self.visit_let_mut(annotation, node.as_ref()),
+            Node::Assign(name, node) => self.visit_assign(name, node.as_ref()),
             Node::Cond(if_branch, branches, el_blk) => self.visit_cond(if_branch, branches, el_blk),
-            Node::FnDef(name, args, body) => self.visit_fn_def(name, args, body),
+            Node::While(branch) => self.visit_while(branch),
+            Node::For(name, start, end, body) => {
+                self.visit_for(name, start.as_ref(), end.as_ref(), body)
+            }
+            Node::FnDef(name, args, body, _) => self.visit_fn_def(name, args, body),
             Node::AnonFn(args, body) => self.visit_anon_fn(args, body),
             Node::Call(func, args) => self.visit_call(func.as_ref(), &args),
             Node::Literal(literal) => self.visit_literal(literal),
+            Node::List(elems) => self.visit_list(elems),
+            Node::Interpolation(parts) => self.visit_interpolation(parts),
+            Node::Ascription(node, ty) => self.visit_ascription(node.as_ref(), ty),
+            Node::Block(block) => self.visit_block(&block.content),
+            Node::Lazy(node) => self.visit_lazy(node.as_ref()),
             Node::Name(name) => self.visit_name(name),
             Node::PrimFn(primitive) => self.visit_prim_fn(*primitive),
+            Node::EnumDef(name, variants) => self.visit_enum_def(name, variants),
+            Node::Match(node, arms) => self.visit_match(node.as_ref(), arms),
+            Node::Import(path) => self.visit_import(path),
+            Node::Error => (),
         }
     }
 
@@ -66,12 +88,48 @@ pub trait NodeVisitor<'a> {
         self.visit_node(node);
     }
 
+    /// Visits a Node with an Operator used as a function.
+    fn super_operator(
+        &mut self,
+        _op: BinOp,
+        node1: Option<&Located<Node<'a>>>,
+        node2: Option<&Located<Node<'a>>>,
+    ) {
+        for node in node1.into_iter().chain(node2) {
+            self.visit_node(node);
+        }
+    }
+
     /// Visits a Node with a Let binding.
     fn super_let_bind(&mut self, annotation: &TyAnnotation<Name<'a>>, node: &Located<Node<'a>>) {
         self.visit_name(&annotation.item.content);
         self.visit_node(node);
     }
 
+    /// Visits a Node with a mutable Let binding.
+    fn super_let_mut(&mut self, annotation: &TyAnnotation<Name<'a>>, node: &Located<Node<'a>>) {
+        self.visit_name(&annotation.item.content);
+        self.visit_node(node);
+    }
+
+    /// Visits a Node with a Let binding expression.
+    fn super_let_in(
+        &mut self,
+        annotation: &TyAnnotation<Name<'a>>,
+        node1: &Located<Node<'a>>,
+        node2: &Located<Node<'a>>,
+    ) {
+        self.visit_name(&annotation.item.content);
+        self.visit_node(node1);
+        self.visit_node(node2);
+    }
+
+    /// Visits a Node with an Assignment.
+    fn super_assign(&mut self, name: &Located<Name<'a>>, node: &Located<Node<'a>>) {
+        self.visit_name(&name.content);
+        self.visit_node(node);
+    }
+
     /// Visits a Node with a Conditional.
     fn super_cond(
         &mut self,
@@ -88,6 +146,24 @@ pub trait NodeVisitor<'a> {
         self.visit_block(&el_blk.content);
     }
 
+    /// Visits a Node with a While loop.
+    fn super_while(&mut self, branch: &Branch<'a>) {
+        self.visit_branch(branch);
+    }
+
+    /// Visits a Node with a For loop.
+    fn super_for(
+        &mut self,
+        _name: &Located<Name<'a>>,
+        start: &Located<Node<'a>>,
+        end: &Located<Node<'a>>,
+        body: &Located<Block<'a>>,
+    ) {
+        self.visit_node(start);
+        self.visit_node(end);
+        self.visit_block(&body.content);
+    }
+
     /// Visits a Node with a single Branch.
     fn super_branch(&mut self, branch: &Branch<'a>) {
         let cond = &branch.cond;
@@ -119,9 +195,49 @@ pub trait NodeVisitor<'a> {
         self.visit_block(args);
     }
 
+    /// Visits a Node with an Enum declaration.
+    fn super_enum_def(&mut self, _name: &Located<Name<'a>>, _variants: &[Variant<'a>]) {}
+
+    /// Visits a Node with an Import.
+    fn super_import(&mut self, _path: &str) {}
+
+    /// Visits a Node with a Match.
+    fn super_match(&mut self, node: &Located<Node<'a>>, arms: &[Arm<'a>]) {
+        self.visit_node(node);
+
+        for arm in arms {
+            self.visit_arm(arm);
+        }
+    }
+
+    /// Visits a single Arm of a Match.
+    fn super_arm(&mut self, arm: &Arm<'a>) {
+        self.visit_block(&arm.body.content);
+    }
+
     /// Visits a Node with a Literal.
     fn super_literal(&mut self, _literal: &Literal) {}
 
+    /// Visits a Node with a List literal.
+    fn super_list(&mut self, elems: &Block<'a>) {
+        self.visit_block(elems);
+    }
+
+    /// Visits a Node with an Interpolated string.
+    fn super_interpolation(&mut self, parts: &Block<'a>) {
+        self.visit_block(parts);
+    }
+
+    /// Visits a Node with a Type ascription.
+    fn super_ascription(&mut self, node: &Located<Node<'a>>, _ty: &Located<Ty>) {
+        self.visit_node(node);
+    }
+
+    /// Visits a Node with a Lazy expression.
+    fn super_lazy(&mut self, node: &Located<Node<'a>>) {
+        self.visit_node(node);
+    }
+
     /// Vishts a Node with a Name.
     fn super_name(&mut self, _name: &Name<'a>) {}
 
@@ -148,11 +264,41 @@ pub trait NodeVisitor<'a> {
         self.super_unary_op(op, node);
     }
 
+    /// Specifies how Operators used as functions should be visited.
+    fn visit_operator(
+        &mut self,
+        op: BinOp,
+        node1: Option<&Located<Node<'a>>>,
+        node2: Option<&Located<Node<'a>>>,
+    ) {
+        self.super_operator(op, node1, node2);
+    }
+
     /// Specifies how Let bindings should be visited.
     fn visit_let_bind(&mut self, annotation: &TyAnnotation<Name<'a>>, node: &Located<Node<'a>>) {
         self.super_let_bind(annotation, node);
     }
 
+    /// Specifies how mutable Let bindings should be visited.
+    fn visit_let_mut(&mut self, annotation: &TyAnnotation<Name<'a>>, node: &Located<Node<'a>>) {
+        self.super_let_mut(annotation, node);
+    }
+
+    /// Specifies how Let binding expressions should be visited.
+    fn visit_let_in(
+        &mut self,
+        annotation: &TyAnnotation<Name<'a>>,
+        node1: &Located<Node<'a>>,
+        node2: &Located<Node<'a>>,
+    ) {
+        self.super_let_in(annotation, node1, node2);
+    }
+
+    /// Specifies how Assignments should be visited.
+    fn visit_assign(&mut self, name: &Located<Name<'a>>, node: &Located<Node<'a>>) {
+        self.super_assign(name, node);
+    }
+
     /// Specifies how Conditionals should be visited.
     fn visit_cond(
         &mut self,
@@ -163,6 +309,22 @@ pub trait NodeVisitor<'a> {
         self.super_cond(if_branch, branches, el_blk);
     }
 
+    /// Specifies how While loops should be visited.
+    fn visit_while(&mut self, branch: &Branch<'a>) {
+        self.super_while(branch);
+    }
+
+    /// Specifies how For loops should be visited.
+    fn visit_for(
+        &mut self,
+        name: &Located<Name<'a>>,
+        start: &Located<Node<'a>>,
+        end: &Located<Node<'a>>,
+        body: &Located<Block<'a>>,
+    ) {
+        self.super_for(name, start, end, body);
+    }
+
     /// Specifies how Branches should be visited.
     fn visit_branch(&mut self, branch: &Branch<'a>) {
         self.super_branch(branch);
@@ -188,11 +350,51 @@ pub trait NodeVisitor<'a> {
         self.super_call(func, args)
     }
 
+    /// Specifies how Enum declarations should be visited.
+    fn visit_enum_def(&mut self, name: &Located<Name<'a>>, variants: &[Variant<'a>]) {
+        self.super_enum_def(name, variants);
+    }
+
+    /// Specifies how Imports should be visited.
+    fn visit_import(&mut self, path: &str) {
+        self.super_import(path);
+    }
+
+    /// Specifies how Matches should be visited.
+    fn visit_match(&mut self, node: &Located<Node<'a>>, arms: &[Arm<'a>]) {
+        self.super_match(node, arms);
+    }
+
+    /// Specifies how Arms should be visited.
+    fn visit_arm(&mut self, arm: &Arm<'a>) {
+        self.super_arm(arm);
+    }
+
     /// Specifies how Literals should be visited.
     fn visit_literal(&mut self, literal: &Literal) {
         self.super_literal(literal);
     }
 
+    /// Specifies how List literals should be visited.
+    fn visit_list(&mut self, elems: &Block<'a>) {
+        self.super_list(elems);
+    }
+
+    /// Specifies how Type ascriptions should be visited.
+    fn visit_ascription(&mut self, node: &Located<Node<'a>>, ty: &Located<Ty>) {
+        self.super_ascription(node, ty);
+    }
+
+    /// Specifies how Lazy expressions should be visited.
+    fn visit_lazy(&mut self, node: &Located<Node<'a>>) {
+        self.super_lazy(node);
+    }
+
+    /// Specifies how Interpolated strings should be visited.
+    fn visit_interpolation(&mut self, parts: &Block<'a>) {
+        self.super_interpolation(parts);
+    }
+
     /// Specifies how Names should be visited.
     fn visit_name(&mut self, name: &Name<'a>) {
         self.super_name(name);
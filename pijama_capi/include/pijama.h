@@ -0,0 +1,49 @@
+/* C bindings for the Pijama interpreter. */
+#ifndef PIJAMA_H
+#define PIJAMA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum PijamaStatus {
+    PIJAMA_OK = 0,
+    PIJAMA_INVALID_INPUT = 1,
+    PIJAMA_PARSE_ERROR = 2,
+    PIJAMA_LOWER_ERROR = 3,
+    PIJAMA_TYPE_ERROR = 4,
+    PIJAMA_RUNTIME_ERROR = 5,
+    PIJAMA_CODEGEN_ERROR = 6,
+    PIJAMA_INTERNAL_ERROR = 7,
+} PijamaStatus;
+
+typedef struct PijamaError {
+    PijamaStatus status;
+    /* Byte offsets of the error in the source code. */
+    size_t start;
+    size_t end;
+    /* Owned by the library, valid until the next call in the same thread. */
+    const char *message;
+} PijamaError;
+
+/* Type-checks `source` without evaluating it. */
+PijamaStatus pijama_check(const char *source);
+
+/* Runs `source`. If `output` is not null, the output of the program is stored in `*output` and
+ * must be released with `pijama_string_free`. Otherwise it is written to the standard output. */
+PijamaStatus pijama_run(const char *source, bool overflow_check, char **output);
+
+/* Stores the last error of the current thread in `error`. Returns false if there is none. */
+bool pijama_last_error(PijamaError *error);
+
+/* Releases a string returned by the library. */
+void pijama_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PIJAMA_H */
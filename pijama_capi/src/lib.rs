@@ -0,0 +1,298 @@
+//! C bindings for the Pijama interpreter.
+//!
+//! This crate exposes the driver through a C ABI so Pijama can be embedded in programs that are
+//! not written in Rust. The declarations for C hosts are in `include/pijama.h`.
+//!
+//! [`pijama_check`] and [`pijama_run`] return a [`PijamaStatus`]. When they fail, the details of
+//! the error can be retrieved with [`pijama_last_error`] from the same thread.
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+use pijama_core::machine::{
+    arithmetic::{CheckedArithmetic, WrappingArithmetic},
+    env::Env,
+    MachineBuilder,
+};
+use pijama_driver::{check, run_with_machine, LangError};
+
+/// The result of calling a function of this crate.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PijamaStatus {
+    /// The function succeeded.
+    Ok = 0,
+    /// The source code pointer was null or the source code was not valid UTF-8.
+    InvalidInput = 1,
+    /// The source code could not be parsed.
+    ParseError = 2,
+    /// The source code could not be lowered.
+    LowerError = 3,
+    /// The program is not well-typed.
+    TypeError = 4,
+    /// The program stopped during evaluation, e.g. because of an integer overflow or because
+    /// `read_int` read a line or `parse_int` received a string that is not an integer.
+    RuntimeError = 5,
+    /// The program could not be translated by a code generation backend.
+    CodegenError = 6,
+    /// The compiler or the interpreter failed while checking or running the program, which is a
+    /// bug in Pijama.
+    InternalError = 7,
+}
+
+/// Details of the last error that happened in the current thread.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PijamaError {
+    /// The kind of the error.
+    pub status: PijamaStatus,
+    /// Byte offset in the source code where the error starts.
+    pub start: usize,
+    /// Byte offset in the source code where the error ends.
+    pub end: usize,
+    /// Null-terminated description of the error.
+    ///
+    /// The string is owned by the library and is valid until the next call to a function of this
+    /// crate in the same thread.
+    pub message: *const c_char,
+}
+
+/// An error stored until the host asks for it.
+struct LastError {
+    status: PijamaStatus,
+    start: usize,
+    end: usize,
+    message: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
+}
+
+/// Stores an error so it can be retrieved with [`pijama_last_error`] and returns its status.
+fn set_error(status: PijamaStatus, start: usize, end: usize, message: String) -> PijamaStatus {
+    let message = CString::new(message.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = Some(LastError {
+            status,
+            start,
+            end,
+            message,
+        })
+    });
+    status
+}
+
+fn set_lang_error(error: LangError) -> PijamaStatus {
+    let status = match error {
+        LangError::Parse(_) => PijamaStatus::ParseError,
+        LangError::Lower(_) => PijamaStatus::LowerError,
+        LangError::Ty(_) => PijamaStatus::TypeError,
+        LangError::Codegen(_) => PijamaStatus::CodegenError,
+        LangError::Runtime(_) => PijamaStatus::RuntimeError,
+        LangError::Module { error, .. } | LangError::Traced { error, .. } => {
+            return set_lang_error(*error)
+        }
+        // Only one error can be stored, so the first one is reported. This runs outside
+        // `catch_unwind`, so an empty list must not panic.
+        LangError::Multiple(errors) => {
+            return match errors.into_iter().next() {
+                Some(error) => set_lang_error(error),
+                None => set_error(
+                    PijamaStatus::InternalError,
+                    0,
+                    0,
+                    "An empty list of errors was reported".to_owned(),
+                ),
+            };
+        }
+    };
+    let loc = error.loc();
+    set_error(status, loc.start, loc.end, error.to_string())
+}
+
+/// Stores a caught panic as an internal error, using `default` as the message if the panic has
+/// none.
+fn set_panic_error(payload: Box<dyn Any + Send>, default: &str) -> PijamaStatus {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|msg| msg.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| default.to_owned());
+    set_error(PijamaStatus::InternalError, 0, 0, message)
+}
+
+/// Reads the null-terminated source code in `source`.
+unsafe fn read_source<'a>(source: *const c_char) -> Result<&'a str, PijamaStatus> {
+    if source.is_null() {
+        return Err(set_error(
+            PijamaStatus::InvalidInput,
+            0,
+            0,
+            "Source code pointer is null".to_owned(),
+        ));
+    }
+    CStr::from_ptr(source).to_str().map_err(|err| {
+        set_error(
+            PijamaStatus::InvalidInput,
+            err.valid_up_to(),
+            err.valid_up_to(),
+            "Source code is not valid UTF-8".to_owned(),
+        )
+    })
+}
+
+/// Type-checks the null-terminated source code in `source` without evaluating it.
+///
+/// # Safety
+///
+/// `source` must be null or point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pijama_check(source: *const c_char) -> PijamaStatus {
+    let input = match read_source(source) {
+        Ok(input) => input,
+        Err(status) => return status,
+    };
+    // Panics must not unwind into the host.
+    match catch_unwind(|| check(input)) {
+        Ok(Ok(_)) => PijamaStatus::Ok,
+        Ok(Err(error)) => set_lang_error(error),
+        Err(payload) => set_panic_error(payload, "Type checking failed"),
+    }
+}
+
+/// Runs the null-terminated source code in `source`.
+///
+/// If `overflow_check` is true, integer overflows stop the execution with a
+/// [`PijamaStatus::RuntimeError`].
+///
+/// If `output` is null, the output of the program is written to the standard output. Otherwise,
+/// the output is stored in a newly allocated null-terminated string and `*output` is set to point
+/// to it. This string must be released with [`pijama_string_free`]. `*output` is set to null if
+/// the program could not be run.
+///
+/// # Safety
+///
+/// `source` must be null or point to a null-terminated string. `output` must be null or point to
+/// a valid `char *`.
+#[no_mangle]
+pub unsafe extern "C" fn pijama_run(
+    source: *const c_char,
+    overflow_check: bool,
+    output: *mut *mut c_char,
+) -> PijamaStatus {
+    if !output.is_null() {
+        *output = ptr::null_mut();
+    }
+
+    let input = match read_source(source) {
+        Ok(input) => input,
+        Err(status) => return status,
+    };
+
+    let mut buffer = Vec::new();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        if output.is_null() {
+            run(input, overflow_check, MachineBuilder::default())
+        } else {
+            run(
+                input,
+                overflow_check,
+                MachineBuilder::new(Env::new(&mut buffer)),
+            )
+        }
+    }));
+
+    match result {
+        Ok(Ok(())) => {
+            if !output.is_null() {
+                let string = String::from_utf8_lossy(&buffer).replace('\0', "");
+                *output = CString::new(string).unwrap().into_raw();
+            }
+            PijamaStatus::Ok
+        }
+        Ok(Err(error)) => set_lang_error(error),
+        Err(payload) => set_panic_error(payload, "Evaluation failed"),
+    }
+}
+
+/// Runs `input` with a machine built from `builder`, choosing the arithmetic according to
+/// `overflow_check`.
+fn run<W: std::io::Write>(
+    input: &str,
+    overflow_check: bool,
+    builder: MachineBuilder<W, WrappingArithmetic>,
+) -> Result<(), LangError> {
+    if overflow_check {
+        run_with_machine(input, builder.with_arithmetic(CheckedArithmetic).build())
+    } else {
+        run_with_machine(input, builder.build())
+    }
+}
+
+/// Retrieves the last error that happened in the current thread.
+///
+/// Returns false and leaves `error` untouched if no function has failed yet in this thread.
+///
+/// # Safety
+///
+/// `error` must point to a valid `PijamaError`.
+#[no_mangle]
+pub unsafe extern "C" fn pijama_last_error(error: *mut PijamaError) -> bool {
+    if error.is_null() {
+        return false;
+    }
+    LAST_ERROR.with(|last| match &*last.borrow() {
+        Some(last) => {
+            *error = PijamaError {
+                status: last.status,
+                start: last.start,
+                end: last.end,
+                message: last.message.as_ptr(),
+            };
+            true
+        }
+        None => false,
+    })
+}
+
+/// Releases a string returned by this crate.
+///
+/// # Safety
+///
+/// `string` must be null or a pointer returned by this crate that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn pijama_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_are_internal_errors() {
+        let payload = catch_unwind(|| panic!("Missing return value")).unwrap_err();
+        assert_eq!(
+            PijamaStatus::InternalError,
+            set_panic_error(payload, "Evaluation failed")
+        );
+        let mut error = PijamaError {
+            status: PijamaStatus::Ok,
+            start: 0,
+            end: 0,
+            message: ptr::null(),
+        };
+        assert!(unsafe { pijama_last_error(&mut error) });
+        assert_eq!(PijamaStatus::InternalError, error.status);
+        let message = unsafe { CStr::from_ptr(error.message) };
+        assert_eq!("Missing return value", message.to_str().unwrap());
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    ffi::{CStr, CString},
+    ptr,
+};
+
+use pijama_capi::*;
+
+fn last_error() -> PijamaError {
+    let mut error = PijamaError {
+        status: PijamaStatus::Ok,
+        start: 0,
+        end: 0,
+        message: ptr::null(),
+    };
+    assert!(unsafe { pijama_last_error(&mut error) });
+    error
+}
+
+fn run(input: &str, overflow_check: bool) -> (PijamaStatus, Option<String>) {
+    let source = CString::new(input).unwrap();
+    let mut output = ptr::null_mut();
+    let status = unsafe { pijama_run(source.as_ptr(), overflow_check, &mut output) };
+    if output.is_null() {
+        (status, None)
+    } else {
+        let string = unsafe { CStr::from_ptr(output) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+        unsafe { pijama_string_free(output) };
+        (status, Some(string))
+    }
+}
+
+#[test]
+fn check_ok() {
+    let source = CString::new("fn id(x: Int) do x end\nid(1)").unwrap();
+    assert_eq!(PijamaStatus::Ok, unsafe { pijama_check(source.as_ptr()) });
+}
+
+#[test]
+fn check_type_error() {
+    let source = CString::new("x = 1 + true").unwrap();
+    assert_eq!(PijamaStatus::TypeError, unsafe {
+        pijama_check(source.as_ptr())
+    });
+    let error = last_error();
+    assert_eq!(PijamaStatus::TypeError, error.status);
+    assert_eq!(8, error.start);
+    let message = unsafe { CStr::from_ptr(error.message) }.to_str().unwrap();
+    assert!(message.starts_with("Type mismatch"), "{}", message);
+}
+
+#[test]
+fn check_null() {
+    assert_eq!(PijamaStatus::InvalidInput, unsafe {
+        pijama_check(ptr::null())
+    });
+    assert_eq!(PijamaStatus::InvalidInput, last_error().status);
+}
+
+#[test]
+fn run_captures_output() {
+    let (status, output) = run("print(1 + 2)", false);
+    assert_eq!(PijamaStatus::Ok, status);
+    assert_eq!(Some("3\n".to_owned()), output);
+}
+
+#[test]
+fn run_parse_error() {
+    let (status, output) = run("fn foo(x: Int do x end", false);
+    assert_eq!(PijamaStatus::ParseError, status);
+    assert_eq!(None, output);
+    assert_eq!(PijamaStatus::ParseError, last_error().status);
+}
+
+#[test]
+fn run_overflow() {
+    let (status, output) = run("print(9223372036854775807 + 1)", true);
+    assert_eq!(PijamaStatus::RuntimeError, status);
+    assert_eq!(None, output);
+    assert_eq!(PijamaStatus::RuntimeError, last_error().status);
+}
@@ -0,0 +1,427 @@
+//! Compilation of nameless terms to bytecode.
+//!
+//! [`compile`] translates a term into a [`Program`] for a stack machine: a list of chunks of
+//! instructions, with one chunk for the whole term and one for the code of each function, arm and
+//! argument inside it. [`Machine::evaluate_bytecode`](crate::machine::Machine::evaluate_bytecode)
+//! runs the program without rewriting any term, which is faster than evaluating the term but does
+//! not go through the steps of the evaluation.
+//!
+//! A program evaluates to the same value and stops with the same errors as its term. Arguments
+//! are still passed by name: an argument that is not a value or a variable is compiled to its own
+//! chunk, which is run each time the parameter is used.
+//!
+//! Programs using threads or channels cannot be compiled, since the stack machine runs a single
+//! thread. [`supports`] tells if a term can be compiled.
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt;
+
+use pijama_ast::{BinOp, Int, Location, Primitive, UnOp};
+
+use crate::lir::{Pattern, Term};
+
+/// A compiled term, returned by [`compile`].
+///
+/// The program is displayed as the instructions of each chunk, starting with the chunk of the
+/// whole term.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub(crate) chunks: Vec<Chunk>,
+}
+
+/// A sequence of instructions ending with [`Instr::Return`].
+#[derive(Debug, Clone)]
+pub(crate) struct Chunk {
+    pub(crate) code: Vec<Instr>,
+    /// The term compiled to this chunk, used to turn the functions and arguments built by the
+    /// program back into terms.
+    pub(crate) term: Arc<Term>,
+}
+
+/// The values captured by a function or an arm, which are placed after its parameters.
+#[derive(Debug, Clone)]
+pub(crate) enum Env {
+    /// Every local variable of the chunk creating the function.
+    All,
+    /// The given values, which are the environment of a closure.
+    Captures(Vec<Capture>),
+}
+
+/// A value captured by a closure.
+#[derive(Debug, Clone)]
+pub(crate) enum Capture {
+    /// The local variable with the given index, without evaluating it.
+    Local(usize),
+    /// The argument compiled to the chunk with the given index.
+    Chunk(usize),
+}
+
+/// An instruction of the stack machine.
+///
+/// The local variables of a chunk are numbered like the variables of its term: the variable `0`
+/// is the innermost parameter.
+#[derive(Debug, Clone)]
+pub(crate) enum Instr {
+    Int(Int),
+    Float(f64),
+    Char(char),
+    Str(Arc<str>),
+    /// Pushes a primitive, which reports its runtime errors at the given location.
+    Prim(Primitive, Location),
+    /// Pushes the value of a local variable, running its argument if it is not evaluated.
+    Load(usize),
+    /// Pushes a local variable as an argument, without evaluating it.
+    Arg(usize),
+    /// Pushes the chunk with the given index as an argument, which is run with the current local
+    /// variables each time it is used.
+    Thunk(usize),
+    /// Pushes a function with the code of the chunk with the given index.
+    Function(usize, Env),
+    /// Pops an argument and a function and applies the function. If the flag is set, the call is
+    /// in tail position and replaces the current chunk.
+    Apply(bool),
+    /// Pops a function and applies it to its own fixed point, in tail position if the flag is set.
+    Fix(bool),
+    /// Pushes the native function with the given index, applied to the given number of popped
+    /// values.
+    Native(usize, usize),
+    BinOp(BinOp, Location),
+    UnOp(UnOp, Location),
+    Jump(usize),
+    /// Pops a boolean and jumps if it is equal to the flag.
+    JumpIf(bool, usize),
+    /// Pops the given number of values and pushes them as the fields of a variant.
+    Variant(usize, Arc<str>, usize),
+    /// Pops the given number of values and pushes them as a list.
+    List(usize),
+    /// Jumps if the value on top of the stack does not match the pattern, keeping it.
+    Test(Pattern, usize),
+    Pop,
+    /// Pops a variant and runs the chunk of an arm with its fields as the first local variables,
+    /// in tail position if the flag is set.
+    Arm(usize, Env, bool),
+    /// Stops because no arm matches the value on top of the stack.
+    NoMatch,
+    Ref,
+    Deref,
+    Assign,
+    Assert(Location),
+    /// Reads a line of the input with `read_line` or `read_int`.
+    Read(Primitive, Location),
+    Return,
+}
+
+/// Returns `true` if `term` can be compiled to bytecode, which is the case unless it uses threads
+/// or channels.
+pub fn supports(term: &Term) -> bool {
+    match term {
+        Term::PrimFn(prim) => !matches!(
+            prim,
+            Primitive::Spawn | Primitive::Channel | Primitive::Send | Primitive::Recv
+        ),
+        Term::Chan(_) => false,
+        Term::Var(_)
+        | Term::Lit(_)
+        | Term::Float(_)
+        | Term::Char(_)
+        | Term::Str(_)
+        | Term::Cell(_) => true,
+        Term::Abs(t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Fix(t1)
+        | Term::Ref(t1)
+        | Term::Deref(t1)
+        | Term::Assert(_, t1)
+        | Term::Loc(_, t1) => supports(t1),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Assign(t1, t2) => {
+            supports(t1) && supports(t2)
+        }
+        Term::Cond(t1, t2, t3) => supports(t1) && supports(t2) && supports(t3),
+        Term::Closure(code, env) => supports(code) && env.iter().all(|term| supports(term)),
+        Term::Native(_, args) | Term::Variant(_, _, args) | Term::List(args) => {
+            args.iter().all(|arg| supports(arg))
+        }
+        Term::Match(t1, arms) => supports(t1) && arms.iter().all(|(_, body)| supports(body)),
+    }
+}
+
+/// Compiles `term` to a program.
+///
+/// # Panics
+///
+/// Panics if `term` has cells, which only exist during an evaluation, or if it is not
+/// [supported](supports).
+pub fn compile(term: &Term) -> Program {
+    let mut compiler = Compiler { chunks: Vec::new() };
+    compiler.chunk(&Arc::new(term.clone()), Location::new(0, 0));
+    Program {
+        chunks: compiler.chunks,
+    }
+}
+
+struct Compiler {
+    chunks: Vec<Chunk>,
+}
+
+impl Compiler {
+    /// Compiles `term` to a new chunk and returns its index. `loc` is the location of the
+    /// innermost located term around it.
+    fn chunk(&mut self, term: &Arc<Term>, loc: Location) -> usize {
+        let index = self.chunks.len();
+        self.chunks.push(Chunk {
+            code: Vec::new(),
+            term: Arc::clone(term),
+        });
+        let mut code = Vec::new();
+        self.term(&mut code, term, loc, true);
+        code.push(Instr::Return);
+        self.chunks[index].code = code;
+        index
+    }
+
+    /// Appends the instructions pushing the value of `term` to `code`.
+    fn term(&mut self, code: &mut Vec<Instr>, term: &Arc<Term>, loc: Location, tail: bool) {
+        match &**term {
+            Term::Var(index) => code.push(Instr::Load(*index)),
+            Term::Lit(n) => code.push(Instr::Int(*n)),
+            Term::Float(x) => code.push(Instr::Float(*x)),
+            Term::Char(c) => code.push(Instr::Char(*c)),
+            Term::Str(string) => code.push(Instr::Str(Arc::clone(string))),
+            Term::PrimFn(prim @ Primitive::ReadLine) | Term::PrimFn(prim @ Primitive::ReadInt) => {
+                code.push(Instr::Read(*prim, loc))
+            }
+            Term::PrimFn(prim) => code.push(Instr::Prim(*prim, loc)),
+            Term::Abs(body) => {
+                let chunk = self.chunk(body, loc);
+                code.push(Instr::Function(chunk, Env::All));
+            }
+            Term::Closure(body, env) => {
+                let chunk = self.chunk(body, loc);
+                let env = self.captures(env, loc);
+                code.push(Instr::Function(chunk, env));
+            }
+            Term::UnaryOp(op, t1) => {
+                self.term(code, t1, loc, false);
+                code.push(Instr::UnOp(*op, loc));
+            }
+            // The second operand is only evaluated if the first one does not decide the result.
+            Term::BinaryOp(op @ BinOp::And, t1, t2) | Term::BinaryOp(op @ BinOp::Or, t1, t2) => {
+                let decided = *op == BinOp::Or;
+                self.term(code, t1, loc, false);
+                let short = code.len();
+                code.push(Instr::JumpIf(decided, 0));
+                self.term(code, t2, loc, tail);
+                let end = code.len();
+                code.push(Instr::Jump(0));
+                patch(code, short);
+                code.push(Instr::Int(decided.into()));
+                patch(code, end);
+            }
+            Term::BinaryOp(op, t1, t2) => {
+                self.term(code, t1, loc, false);
+                self.term(code, t2, loc, false);
+                code.push(Instr::BinOp(*op, loc));
+            }
+            Term::App(t1, t2) => {
+                self.term(code, t1, loc, false);
+                self.arg(code, t2, loc);
+                code.push(Instr::Apply(tail));
+            }
+            Term::Cond(t1, t2, t3) => {
+                self.term(code, t1, loc, false);
+                let branch = code.len();
+                code.push(Instr::JumpIf(false, 0));
+                self.term(code, t2, loc, tail);
+                let end = code.len();
+                code.push(Instr::Jump(0));
+                patch(code, branch);
+                self.term(code, t3, loc, tail);
+                patch(code, end);
+            }
+            Term::Fix(t1) => {
+                self.term(code, t1, loc, false);
+                code.push(Instr::Fix(tail));
+            }
+            Term::Native(index, args) => {
+                for arg in args {
+                    self.term(code, arg, loc, false);
+                }
+                code.push(Instr::Native(*index, args.len()));
+            }
+            Term::Variant(index, name, args) => {
+                for arg in args {
+                    self.term(code, arg, loc, false);
+                }
+                code.push(Instr::Variant(*index, Arc::clone(name), args.len()));
+            }
+            Term::List(elems) => {
+                for elem in elems {
+                    self.term(code, elem, loc, false);
+                }
+                code.push(Instr::List(elems.len()));
+            }
+            Term::Match(t1, arms) => {
+                self.term(code, t1, loc, false);
+                let mut ends = Vec::new();
+                for (pattern, body) in arms {
+                    let test = code.len();
+                    code.push(Instr::Test(pattern.clone(), 0));
+                    match (pattern.binders(), &**body) {
+                        (0, _) => {
+                            code.push(Instr::Pop);
+                            self.term(code, body, loc, tail);
+                        }
+                        // The arms binding some names are closures after the closure conversion.
+                        (_, Term::Closure(body, env)) => {
+                            let chunk = self.chunk(body, loc);
+                            let env = self.captures(env, loc);
+                            code.push(Instr::Arm(chunk, env, tail));
+                        }
+                        _ => {
+                            let chunk = self.chunk(body, loc);
+                            code.push(Instr::Arm(chunk, Env::All, tail));
+                        }
+                    }
+                    ends.push(code.len());
+                    code.push(Instr::Jump(0));
+                    patch(code, test);
+                }
+                code.push(Instr::NoMatch);
+                for end in ends {
+                    patch(code, end);
+                }
+            }
+            Term::Ref(t1) => {
+                self.term(code, t1, loc, false);
+                code.push(Instr::Ref);
+            }
+            Term::Deref(t1) => {
+                self.term(code, t1, loc, false);
+                code.push(Instr::Deref);
+            }
+            Term::Assign(t1, t2) => {
+                self.term(code, t1, loc, false);
+                self.term(code, t2, loc, false);
+                code.push(Instr::Assign);
+            }
+            Term::Assert(loc, t1) => {
+                self.term(code, t1, *loc, false);
+                code.push(Instr::Assert(*loc));
+            }
+            Term::Loc(loc, t1) => self.term(code, t1, *loc, tail),
+            Term::Cell(_) => panic!("Cells cannot be compiled"),
+            Term::Chan(_) => panic!("Channels cannot be compiled"),
+        }
+    }
+
+    /// Appends the instructions pushing `arg` as an argument to `code`.
+    ///
+    /// Values and variables are pushed as they are, any other term is compiled to its own chunk.
+    fn arg(&mut self, code: &mut Vec<Instr>, arg: &Arc<Term>, loc: Location) {
+        match &**arg {
+            Term::Var(index) => code.push(Instr::Arg(*index)),
+            Term::Lit(_)
+            | Term::Float(_)
+            | Term::Char(_)
+            | Term::Str(_)
+            | Term::Abs(_)
+            | Term::Closure(_, _) => self.term(code, arg, loc, false),
+            Term::PrimFn(prim) if !matches!(prim, Primitive::ReadLine | Primitive::ReadInt) => {
+                self.term(code, arg, loc, false)
+            }
+            _ => {
+                let chunk = self.chunk(arg, loc);
+                code.push(Instr::Thunk(chunk));
+            }
+        }
+    }
+
+    /// Returns the environment of a closure capturing the terms of `env`.
+    fn captures(&mut self, env: &[Arc<Term>], loc: Location) -> Env {
+        let captures = env
+            .iter()
+            .map(|term| match &**term {
+                Term::Var(index) => Capture::Local(*index),
+                _ => Capture::Chunk(self.chunk(term, loc)),
+            })
+            .collect();
+        Env::Captures(captures)
+    }
+}
+
+/// Makes the jump at `position` of `code` go to the end of `code`.
+fn patch(code: &mut [Instr], position: usize) {
+    let target = code.len();
+    match &mut code[position] {
+        Instr::Jump(to) | Instr::JumpIf(_, to) | Instr::Test(_, to) => *to = target,
+        instr => unreachable!("`{}` is not a jump", instr),
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            writeln!(f, "chunk {}:", index)?;
+            for (position, instr) in chunk.code.iter().enumerate() {
+                writeln!(f, "{:>4} {}", position, instr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tail = |tail: &bool| if *tail { " tail" } else { "" };
+        match self {
+            Instr::Int(n) => write!(f, "int {}", n),
+            Instr::Float(x) => write!(f, "float {:?}", x),
+            Instr::Char(c) => write!(f, "char {:?}", c),
+            Instr::Str(string) => write!(f, "str {:?}", string),
+            Instr::Prim(prim, _) => write!(f, "prim {}", prim),
+            Instr::Load(index) => write!(f, "load {}", index),
+            Instr::Arg(index) => write!(f, "arg {}", index),
+            Instr::Thunk(chunk) => write!(f, "thunk {}", chunk),
+            Instr::Function(chunk, env) => write!(f, "function {}{}", chunk, env),
+            Instr::Apply(flag) => write!(f, "apply{}", tail(flag)),
+            Instr::Fix(flag) => write!(f, "fix{}", tail(flag)),
+            Instr::Native(index, args) => write!(f, "native {} {}", index, args),
+            Instr::BinOp(op, _) => write!(f, "op {}", op),
+            Instr::UnOp(op, _) => write!(f, "op {}", op),
+            Instr::Jump(target) => write!(f, "jump {}", target),
+            Instr::JumpIf(flag, target) => write!(f, "jump if {} {}", flag, target),
+            Instr::Variant(index, name, args) => write!(f, "variant {} {} {}", index, name, args),
+            Instr::List(elems) => write!(f, "list {}", elems),
+            Instr::Test(pattern, target) => write!(f, "test {} {}", pattern, target),
+            Instr::Pop => write!(f, "pop"),
+            Instr::Arm(chunk, env, flag) => write!(f, "arm {}{}{}", chunk, env, tail(flag)),
+            Instr::NoMatch => write!(f, "no match"),
+            Instr::Ref => write!(f, "ref"),
+            Instr::Deref => write!(f, "deref"),
+            Instr::Assign => write!(f, "assign"),
+            Instr::Assert(_) => write!(f, "assert"),
+            Instr::Read(prim, _) => write!(f, "{}", prim),
+            Instr::Return => write!(f, "return"),
+        }
+    }
+}
+
+impl fmt::Display for Env {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Env::All => write!(f, " with all"),
+            Env::Captures(captures) => {
+                write!(f, " with [")?;
+                for (index, capture) in captures.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match capture {
+                        Capture::Local(index) => write!(f, "{}", index)?,
+                        Capture::Chunk(chunk) => write!(f, "thunk {}", chunk)?,
+                    }
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
@@ -0,0 +1,695 @@
+//! JavaScript backend.
+//!
+//! This backend emits a JavaScript script from a type-checked MIR term. The emitted code is meant
+//! to be read by humans, so bindings keep their Pijama names and functions are emitted as arrow
+//! functions:
+//!
+//! - Integers are represented as `BigInt`s. Unlike the machine, arithmetic never overflows.
+//! - Floats are JavaScript numbers. Converting a float that is not finite to an integer throws
+//!   an exception.
+//! - Booleans are JavaScript booleans and the unit value is `undefined`.
+//! - Characters and strings are JavaScript strings and `concat` uses the `+` operator. Strings
+//!   are indexed by UTF-16 code units, so `len`, `char_at` and `substring` count the characters
+//!   outside of the Basic Multilingual Plane twice, unlike the machine.
+//! - Lists are JavaScript arrays, which are never modified. Unlike the machine, `head` and `get`
+//!   return `undefined` if there is no such element and `==` compares lists by reference.
+//! - `let` bindings become `const` declarations. Since JavaScript does not allow redeclaring a
+//!   name in the same scope, shadowed names get a `$n` suffix. The `::` of names defined in other
+//!   modules is replaced by `$`.
+//! - Conditionals in tail position become `if` statements and become ternary expressions
+//!   anywhere else.
+//! - `print` writes to the console using `console.log`. `read_line` and `read_int` read from the
+//!   standard input using the `fs` module of Node.js, and `read_int` throws an exception if the
+//!   line is not an integer. A failed `assert` throws an exception.
+//! - `random` uses `Math.random`, so it cannot be seeded and the numbers between bounds further
+//!   apart than 2^53 are not uniformly distributed.
+//! - Variants of enums are objects with the name of the variant as `tag` and an array of
+//!   `fields`. They are converted to strings like in the machine, e.g. `Rect(1, 2)`.
+//! - Matches become functions that are called immediately and test each pattern in order.
+//! - Lazy terms are objects holding a function that computes their value. `force` calls it the
+//!   first time and keeps the value in the object afterwards.
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use pijama_ast::{BinOp, Literal, Located, Location, Name, Pattern, Primitive, UnOp};
+
+use crate::{
+    codegen::{line, push_indent, unparen, unsupported, CodegenResult},
+    mir::{visit::Visitor, LetKind, Term},
+};
+
+/// Names that cannot be used as JavaScript identifiers or that are used by the emitted code.
+const RESERVED: &[&str] = &[
+    "BigInt",
+    "Buffer",
+    "Math",
+    "Number",
+    "String",
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "console",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "require",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Emits a JavaScript script that evaluates `term`.
+///
+/// The term must have been type-checked before. Emitting it returns an error if it uses threads
+/// or channels, since JavaScript functions cannot wait for a value without being asynchronous.
+///
+/// # Panics
+///
+/// Panics if `term` contains native functions, as they only exist inside a machine.
+pub fn emit(term: &Located<Term<'_>>) -> CodegenResult<String> {
+    let mut finder = ConcurrencyFinder(None);
+    finder.visit_term(term);
+    if let Some(loc) = finder.0 {
+        return Err(unsupported(loc, "Threads and channels"));
+    }
+    let mut emitter = Emitter::default();
+    let mut out = String::new();
+    emitter.block(&term.content, 0, false, &mut out);
+    Ok(out)
+}
+
+/// Visitor finding the location of the first concurrency primitive.
+struct ConcurrencyFinder(Option<Location>);
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for ConcurrencyFinder {
+    fn visit_term(&mut self, term: &'t Located<Term<'a>>) {
+        match term.content {
+            _ if self.0.is_some() => {}
+            Term::PrimFn(
+                Primitive::Spawn | Primitive::Channel | Primitive::Send | Primitive::Recv,
+            ) => self.0 = Some(term.loc),
+            _ => self.super_term(term),
+        }
+    }
+}
+
+struct Binding<'a> {
+    name: Name<'a>,
+    js_name: String,
+    /// Whether the binding holds a function that must be called to get its value.
+    thunk: bool,
+}
+
+#[derive(Default)]
+struct Emitter<'a> {
+    /// The bindings in scope, innermost last.
+    scope: Vec<Binding<'a>>,
+    /// The variants in scope with their number of fields, innermost last.
+    variants: Vec<(Name<'a>, usize)>,
+}
+
+impl<'a> Emitter<'a> {
+    /// Binds `name` and returns the JavaScript name used for it.
+    ///
+    /// The name is suffixed if it shadows a binding that is still in scope, so it does not clash
+    /// with a declaration in the same block and the shadowed binding can still be used while
+    /// defining it.
+    fn bind(&mut self, name: Name<'a>, thunk: bool) -> String {
+        let count = self.scope.iter().filter(|b| b.name == name).count();
+        let js_name = if count == 0 && !RESERVED.contains(&name.0) {
+            name.0.replace("::", "$")
+        } else {
+            format!("{}${}", name.0.replace("::", "$"), count)
+        };
+        self.scope.push(Binding {
+            name,
+            js_name: js_name.clone(),
+            thunk,
+        });
+        js_name
+    }
+
+    fn lookup(&self, name: Name<'a>) -> String {
+        match self.scope.iter().rev().find(|b| b.name == name) {
+            Some(Binding {
+                js_name,
+                thunk: true,
+                ..
+            }) => format!("{}()", js_name),
+            Some(binding) => binding.js_name.clone(),
+            None => name.0.to_string(),
+        }
+    }
+
+    /// Emits `term` as a sequence of statements indented `indent` levels. If `ret` is true, the
+    /// value of the last expression is returned.
+    fn block(&mut self, term: &Term<'a>, indent: usize, ret: bool, out: &mut String) {
+        let scope_len = self.scope.len();
+        let variants_len = self.variants.len();
+        let mut term = term;
+        loop {
+            match term {
+                Term::Let(kind, name, t1, t2) => {
+                    let value = match (kind, &t1.content) {
+                        // Recursive functions refer to themselves by name inside their body.
+                        (LetKind::Rec(_), Term::Abs(..)) => {
+                            let js_name = self.bind(name.content, false);
+                            let value = self.expr(&t1.content, indent);
+                            format!("const {} = {};", js_name, unparen(&value))
+                        }
+                        // Recursive functions without parameters are not abstractions, they are
+                        // wrapped in a function so they are only evaluated when used.
+                        (LetKind::Rec(_), body) => {
+                            let js_name = self.bind(name.content, true);
+                            let body = self.arrow_body(body, indent);
+                            format!("const {} = () => {};", js_name, body)
+                        }
+                        (LetKind::NonRec(_), value) => {
+                            let value = self.expr(value, indent);
+                            let js_name = self.bind(name.content, false);
+                            format!("const {} = {};", js_name, unparen(&value))
+                        }
+                        (LetKind::Mut(_), value) => {
+                            let value = self.expr(value, indent);
+                            let js_name = self.bind(name.content, false);
+                            format!("let {} = {};", js_name, unparen(&value))
+                        }
+                    };
+                    line(out, indent, &value);
+                    term = &t2.content;
+                }
+                // Mutually recursive functions are bound before any of them is declared, so they
+                // can refer to each other inside their bodies.
+                Term::LetRec(fns, t1) => {
+                    let js_names: Vec<_> = fns
+                        .iter()
+                        .map(|(name, _, body)| {
+                            self.bind(name.content, !matches!(body.content, Term::Abs(..)))
+                        })
+                        .collect();
+                    for (js_name, (_, _, body)) in js_names.iter().zip(fns) {
+                        let value = match &body.content {
+                            body @ Term::Abs(..) => unparen(&self.expr(body, indent)).to_string(),
+                            body => format!("() => {}", self.arrow_body(body, indent)),
+                        };
+                        line(out, indent, &format!("const {} = {};", js_name, value));
+                    }
+                    term = &t1.content;
+                }
+                Term::Seq(t1, t2) => {
+                    let value = self.statement(&t1.content, indent);
+                    line(out, indent, &format!("{};", value));
+                    term = &t2.content;
+                }
+                // Enums are not declared, only their constructors are.
+                Term::Enum(_, variants, t1) => {
+                    for variant in variants {
+                        self.variants
+                            .push((variant.name.content, variant.fields.len()));
+                    }
+                    term = &t1.content;
+                }
+                Term::Cond(t1, t2, t3) => {
+                    let cond = self.expr(&t1.content, indent);
+                    line(out, indent, &format!("if ({}) {{", unparen(&cond)));
+                    self.block(&t2.content, indent + 1, ret, out);
+                    line(out, indent, "} else {");
+                    self.block(&t3.content, indent + 1, ret, out);
+                    line(out, indent, "}");
+                    break;
+                }
+                // Assignments evaluate to `undefined`, which is what a function without a
+                // `return` statement returns.
+                term @ Term::Assign(..) => {
+                    let value = self.statement(term, indent);
+                    line(out, indent, &format!("{};", value));
+                    break;
+                }
+                term => {
+                    let value = self.expr(term, indent);
+                    let value = unparen(&value);
+                    if ret {
+                        line(out, indent, &format!("return {};", value));
+                    } else {
+                        line(out, indent, &format!("{};", value));
+                    }
+                    break;
+                }
+            }
+        }
+        self.scope.truncate(scope_len);
+        self.variants.truncate(variants_len);
+    }
+
+    /// Emits `term` as the expression of a statement, assignments are emitted as JavaScript
+    /// assignments.
+    fn statement(&mut self, term: &Term<'a>, indent: usize) -> String {
+        match term {
+            Term::Assign(name, t1) => {
+                let value = self.expr(&t1.content, indent);
+                format!("{} = {}", self.lookup(name.content), unparen(&value))
+            }
+            term => unparen(&self.expr(term, indent)).to_string(),
+        }
+    }
+
+    /// Emits `body` as the body of an arrow function.
+    fn arrow_body(&mut self, body: &Term<'a>, indent: usize) -> String {
+        match body {
+            Term::Let(..)
+            | Term::LetRec(..)
+            | Term::Seq(..)
+            | Term::Cond(..)
+            | Term::Enum(..)
+            | Term::Assign(..) => {
+                let mut out = String::from("{\n");
+                self.block(body, indent + 1, true, &mut out);
+                push_indent(&mut out, indent);
+                out.push('}');
+                out
+            }
+            body => unparen(&self.expr(body, indent)).to_string(),
+        }
+    }
+
+    /// Emits `term` as an expression. `indent` is the indentation of the line where the
+    /// expression starts.
+    fn expr(&mut self, term: &Term<'a>, indent: usize) -> String {
+        match term {
+            Term::Var(name) => self.lookup(*name),
+            Term::Lit(lit) => literal(lit),
+            Term::List(elems) => {
+                let elems: Vec<_> = elems
+                    .iter()
+                    .map(|elem| unparen(&self.expr(&elem.content, indent)).to_string())
+                    .collect();
+                format!("[{}]", elems.join(", "))
+            }
+            Term::Abs(name, _, body) => {
+                let param = self.bind(name.content, false);
+                let body = self.arrow_body(&body.content, indent);
+                self.scope.pop();
+                format!("({}) => {}", param, body)
+            }
+            Term::Ascription(t1, _) => self.expr(&t1.content, indent),
+            Term::Lazy(t1) => {
+                let body = self.arrow_body(&t1.content, indent);
+                format!("({{ thunk: () => {} }})", body)
+            }
+            Term::UnaryOp(op, t1) => {
+                let t1 = self.expr(&t1.content, indent);
+                match op {
+                    UnOp::Neg => format!("(-{})", t1),
+                    UnOp::Not => format!("(!{})", t1),
+                    UnOp::BitNot => format!("(~{})", t1),
+                }
+            }
+            Term::BinaryOp(op, t1, t2) => {
+                let t1 = self.expr(&t1.content, indent);
+                let t2 = self.expr(&t2.content, indent);
+                format!("({} {} {})", t1, bin_op(*op), t2)
+            }
+            Term::App(t1, t2) => {
+                let operand = self.expr(&t2.content, indent);
+                let arg = unparen(&operand);
+                match &t1.content {
+                    Term::PrimFn(Primitive::Print) => print(arg),
+                    Term::PrimFn(Primitive::FloatToInt) => float_to_int(arg),
+                    Term::PrimFn(Primitive::CharToInt) => char_to_int(&operand),
+                    Term::PrimFn(Primitive::IntToChar) => int_to_char(arg),
+                    Term::PrimFn(Primitive::Head) => head(&operand),
+                    Term::PrimFn(Primitive::Tail) => tail(&operand),
+                    Term::PrimFn(Primitive::Len) => len(&operand),
+                    Term::PrimFn(Primitive::ToString) => to_string(arg),
+                    Term::PrimFn(Primitive::ParseInt) => parse_int(&operand),
+                    Term::PrimFn(Primitive::Assert) => assert(&operand),
+                    Term::App(func, t1)
+                        if matches!(func.content, Term::PrimFn(Primitive::Concat)) =>
+                    {
+                        format!("({} + {})", self.expr(&t1.content, indent), operand)
+                    }
+                    Term::App(func, t1)
+                        if matches!(func.content, Term::PrimFn(Primitive::Push)) =>
+                    {
+                        push(&self.expr(&t1.content, indent), arg)
+                    }
+                    Term::App(func, t1) if matches!(func.content, Term::PrimFn(Primitive::Get)) => {
+                        get(&self.expr(&t1.content, indent), arg)
+                    }
+                    // Strings are indexed like lists.
+                    Term::App(func, t1)
+                        if matches!(func.content, Term::PrimFn(Primitive::CharAt)) =>
+                    {
+                        get(&self.expr(&t1.content, indent), arg)
+                    }
+                    Term::App(func, start) if matches!(&func.content, Term::App(prim, _) if matches!(prim.content, Term::PrimFn(Primitive::Substring))) =>
+                    {
+                        let string = match &func.content {
+                            Term::App(_, string) => self.expr(&string.content, indent),
+                            _ => unreachable!(),
+                        };
+                        let start = self.expr(&start.content, indent);
+                        substring(&string, unparen(&start), arg)
+                    }
+                    func @ Term::Abs(..) => format!("({})({})", self.expr(func, indent), arg),
+                    func => format!("{}({})", self.expr(func, indent), arg),
+                }
+            }
+            Term::Cond(t1, t2, t3) if is_simple(&t2.content) && is_simple(&t3.content) => {
+                let t1 = self.expr(&t1.content, indent);
+                let t2 = self.expr(&t2.content, indent);
+                let t3 = self.expr(&t3.content, indent);
+                format!("({} ? {} : {})", t1, t2, t3)
+            }
+            Term::Let(..)
+            | Term::LetRec(..)
+            | Term::Seq(..)
+            | Term::Cond(..)
+            | Term::Enum(..)
+            | Term::Assign(..) => {
+                // Blocks in expression position are wrapped in a function that is called
+                // immediately.
+                let mut out = String::from("(() => {\n");
+                self.block(term, indent + 1, true, &mut out);
+                push_indent(&mut out, indent);
+                out.push_str("})()");
+                out
+            }
+            Term::PrimFn(Primitive::Print) => format!("(x) => {}", print("x")),
+            Term::PrimFn(Primitive::Concat) => "((a) => (b) => a + b)".to_string(),
+            Term::PrimFn(Primitive::IntToFloat) => "Number".to_string(),
+            Term::PrimFn(Primitive::FloatToInt) => format!("((x) => {})", float_to_int("x")),
+            Term::PrimFn(Primitive::CharToInt) => format!("((c) => {})", char_to_int("c")),
+            Term::PrimFn(Primitive::IntToChar) => format!("((n) => {})", int_to_char("n")),
+            Term::PrimFn(Primitive::Head) => format!("((xs) => {})", head("xs")),
+            Term::PrimFn(Primitive::Tail) => format!("((xs) => {})", tail("xs")),
+            Term::PrimFn(Primitive::Len) => format!("((xs) => {})", len("xs")),
+            Term::PrimFn(Primitive::Push) => format!("((xs) => (x) => {})", push("xs", "x")),
+            Term::PrimFn(Primitive::Get) => format!("((xs) => (i) => {})", get("xs", "i")),
+            Term::PrimFn(Primitive::ToString) => "String".to_string(),
+            Term::PrimFn(Primitive::Assert) => format!("((c) => {})", assert("c")),
+            Term::PrimFn(Primitive::ReadLine) => read_line(),
+            Term::PrimFn(Primitive::ReadInt) => parse_int(&read_line()),
+            Term::PrimFn(Primitive::Substring) => {
+                format!("((s) => (i) => (j) => {})", substring("s", "i", "j"))
+            }
+            Term::PrimFn(Primitive::CharAt) => format!("((s) => (i) => {})", get("s", "i")),
+            Term::PrimFn(Primitive::ParseInt) => format!("((s) => {})", parse_int("s")),
+            Term::PrimFn(Primitive::Abs) => "((n) => (n < 0n ? -n : n))".to_string(),
+            Term::PrimFn(Primitive::Sign) => {
+                "((n) => (n > 0n ? 1n : n < 0n ? -1n : 0n))".to_string()
+            }
+            Term::PrimFn(Primitive::Min) => "((a) => (b) => (a < b ? a : b))".to_string(),
+            Term::PrimFn(Primitive::Max) => "((a) => (b) => (a > b ? a : b))".to_string(),
+            Term::PrimFn(Primitive::Gcd) => gcd(),
+            // Negative powers are rounded towards zero by the division, which throws an exception
+            // for zero like in the machine.
+            Term::PrimFn(Primitive::Pow) => {
+                "((a) => (b) => (b < 0n ? 1n / a ** -b : a ** b))".to_string()
+            }
+            Term::PrimFn(Primitive::Random) => random(),
+            Term::PrimFn(Primitive::Force) => concat!(
+                "((l) => { if (l.thunk) { l.value = l.thunk(); l.thunk = undefined; } ",
+                "return l.value; })",
+            )
+            .to_string(),
+            Term::PrimFn(
+                Primitive::Spawn | Primitive::Channel | Primitive::Send | Primitive::Recv,
+            ) => unreachable!(),
+            Term::Native(index, _) => {
+                panic!("Native function #{} cannot be emitted as JavaScript", index)
+            }
+            Term::Constructor(name) => self.constructor(*name),
+            Term::Match(t1, arms) => self.match_expr(&t1.content, arms, indent),
+        }
+    }
+
+    /// Emits the constructor of a variant, which is a function for each field of the variant.
+    fn constructor(&self, name: Name<'a>) -> String {
+        let arity = self
+            .variants
+            .iter()
+            .rev()
+            .find(|(name2, _)| *name2 == name)
+            .map(|(_, arity)| *arity)
+            .expect("Constructor of a variant that is not in scope");
+        let params: Vec<_> = (0..arity).map(|index| format!("x{}", index)).collect();
+        let to_string = if arity == 0 {
+            format!("\"{}\"", name)
+        } else {
+            format!("\"{}(\" + this.fields.join(\", \") + \")\"", name)
+        };
+        let mut out = format!(
+            "({{ tag: \"{}\", fields: [{}], toString() {{ return {}; }} }})",
+            name,
+            params.join(", "),
+            to_string
+        );
+        for param in params.iter().rev() {
+            out = format!("({}) => {}", param, out);
+        }
+        out
+    }
+
+    /// Emits a match as a function that is called immediately.
+    ///
+    /// The scrutinee is stored in `$m`, which cannot clash with a Pijama name.
+    fn match_expr(
+        &mut self,
+        t1: &Term<'a>,
+        arms: &[(Located<Pattern<'a>>, Located<Term<'a>>)],
+        indent: usize,
+    ) -> String {
+        let mut out = String::from("(() => {\n");
+        let value = self.expr(t1, indent + 1);
+        line(
+            &mut out,
+            indent + 1,
+            &format!("const $m = {};", unparen(&value)),
+        );
+        let mut exhaustive = false;
+        for (pattern, body) in arms {
+            let scope_len = self.scope.len();
+            let test = match &pattern.content {
+                Pattern::Wildcard => None,
+                Pattern::Literal(lit) => Some(format!("$m === {}", literal(lit))),
+                Pattern::Variant(name, _) => Some(format!("$m.tag === \"{}\"", name.content)),
+            };
+            let body_indent = if test.is_some() {
+                indent + 2
+            } else {
+                indent + 1
+            };
+            if let Some(test) = &test {
+                line(&mut out, indent + 1, &format!("if ({}) {{", test));
+            }
+            if let Pattern::Variant(_, binders) = &pattern.content {
+                for (index, binder) in binders.iter().enumerate() {
+                    let js_name = self.bind(binder.content, false);
+                    let field = format!("const {} = $m.fields[{}];", js_name, index);
+                    line(&mut out, body_indent, &field);
+                }
+            }
+            self.block(&body.content, body_indent, true, &mut out);
+            self.scope.truncate(scope_len);
+            if test.is_some() {
+                line(&mut out, indent + 1, "}");
+            } else {
+                // The arms after a wildcard are never taken.
+                exhaustive = true;
+                break;
+            }
+        }
+        if !exhaustive {
+            line(
+                &mut out,
+                indent + 1,
+                "throw new Error(\"No arm matches \" + String($m));",
+            );
+        }
+        push_indent(&mut out, indent);
+        out.push_str("})()");
+        out
+    }
+}
+
+/// Returns true if `term` can be emitted as an expression without wrapping it in a function.
+fn is_simple(term: &Term<'_>) -> bool {
+    match term {
+        Term::Let(..) | Term::LetRec(..) | Term::Seq(..) | Term::Enum(..) | Term::Assign(..) => {
+            false
+        }
+        Term::Cond(_, t2, t3) => is_simple(&t2.content) && is_simple(&t3.content),
+        _ => true,
+    }
+}
+
+fn literal(lit: &Literal) -> String {
+    match lit {
+        Literal::Bool(b) => b.to_string(),
+        Literal::Unit => "undefined".to_string(),
+        Literal::Number(n) if *n < 0 => format!("({}n)", n),
+        Literal::Number(n) => format!("{}n", n),
+        Literal::Float(x) if x.is_sign_negative() => format!("({:?})", x),
+        Literal::Float(x) => format!("{:?}", x),
+        Literal::Char(c) => Literal::Str(c.to_string()).to_string(),
+        // Pijama string literals use the same escapes as JavaScript ones.
+        Literal::Str(_) => lit.to_string(),
+    }
+}
+
+fn bin_op(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Eq => "===",
+        BinOp::Neq => "!==",
+        BinOp::Add => "+",
+        BinOp::Sub => "-",
+        BinOp::Mul => "*",
+        BinOp::Div => "/",
+        BinOp::Rem => "%",
+        BinOp::And => "&&",
+        BinOp::Or => "||",
+        BinOp::BitAnd => "&",
+        BinOp::BitOr => "|",
+        BinOp::BitXor => "^",
+        BinOp::Shr => ">>",
+        BinOp::Shl => "<<",
+        BinOp::Lt => "<",
+        BinOp::Gt => ">",
+        BinOp::Lte => "<=",
+        BinOp::Gte => ">=",
+        BinOp::Pipe => unreachable!("Pipes are lowered to applications"),
+    }
+}
+
+fn print(arg: &str) -> String {
+    format!("console.log(String({}))", arg)
+}
+
+/// Returns an expression that throws an exception if `cond` is `false` and evaluates to
+/// `undefined` otherwise.
+fn assert(cond: &str) -> String {
+    format!(
+        "(() => {{ if (!{}) {{ throw new Error(\"Assertion failed\"); }} }})()",
+        cond
+    )
+}
+
+/// Returns an expression reading a line from the standard input, without its line break.
+///
+/// The input is read one byte at a time, so the bytes after the line are left for the next read.
+fn read_line() -> String {
+    concat!(
+        "(() => { const fs = require(\"fs\"); const byte = Buffer.alloc(1); const bytes = []; ",
+        "while (fs.readSync(0, byte, 0, 1) === 1 && byte[0] !== 10) { bytes.push(byte[0]); } ",
+        "return Buffer.from(bytes).toString().replace(/\\r$/, \"\"); })()",
+    )
+    .to_string()
+}
+
+/// Returns a function computing the greatest common divisor of two integers.
+fn gcd() -> String {
+    concat!(
+        "((a) => (b) => { a = a < 0n ? -a : a; b = b < 0n ? -b : b; ",
+        "while (b !== 0n) { [a, b] = [b, a % b]; } return a; })",
+    )
+    .to_string()
+}
+
+/// Returns a function returning a random integer between two bounds, both included and given in
+/// any order.
+fn random() -> String {
+    concat!(
+        "((a) => (b) => { if (a > b) { [a, b] = [b, a]; } ",
+        "return a + BigInt(Math.floor(Math.random() * Number(b - a + 1n))); })",
+    )
+    .to_string()
+}
+
+/// Returns an expression converting a string to an integer, ignoring the spaces around it.
+fn parse_int(string: &str) -> String {
+    format!("BigInt({}.trim())", string)
+}
+
+fn substring(string: &str, start: &str, end: &str) -> String {
+    format!("{}.slice(Number({}), Number({}))", string, start, end)
+}
+
+fn to_string(arg: &str) -> String {
+    format!("String({})", arg)
+}
+
+fn float_to_int(arg: &str) -> String {
+    format!("BigInt(Math.trunc({}))", arg)
+}
+
+fn char_to_int(arg: &str) -> String {
+    format!("BigInt({}.codePointAt(0))", arg)
+}
+
+fn int_to_char(arg: &str) -> String {
+    format!("String.fromCodePoint(Number({}))", arg)
+}
+
+fn head(list: &str) -> String {
+    format!("{}[0]", list)
+}
+
+fn tail(list: &str) -> String {
+    format!("{}.slice(1)", list)
+}
+
+fn len(list: &str) -> String {
+    format!("BigInt({}.length)", list)
+}
+
+fn push(list: &str, elem: &str) -> String {
+    format!("[...{}, {}]", list, elem)
+}
+
+fn get(list: &str, index: &str) -> String {
+    format!("{}[Number({})]", list, index)
+}
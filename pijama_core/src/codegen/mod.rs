@@ -0,0 +1,91 @@
+//! Code generation backends.
+//!
+//! Each backend translates a type-checked term into a program that can be run without the Pijama
+//! machine. The JavaScript and Rust backends translate MIR terms and the WebAssembly backend
+//! compiles nameless terms.
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::{Located, Location};
+
+pub mod js;
+pub mod rust;
+pub mod wasm;
+
+/// The type returned by the backends that can fail.
+pub type CodegenResult<T> = core::result::Result<T, CodegenError>;
+
+/// A code generation error.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CodegenError {
+    /// Variant used when the term uses a feature that the backend cannot translate.
+    Unsupported(Located<String>),
+}
+
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            CodegenError::Unsupported(feature) => {
+                write!(f, "{} is not supported by this backend", feature)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodegenError {}
+
+impl CodegenError {
+    /// Returns the location of the error.
+    pub fn loc(&self) -> Location {
+        match self {
+            CodegenError::Unsupported(feature) => feature.loc,
+        }
+    }
+
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CodegenError::Unsupported(_) => "E0006",
+        }
+    }
+}
+
+fn unsupported(loc: Location, feature: &str) -> CodegenError {
+    CodegenError::Unsupported(loc.with_content(feature.to_string()))
+}
+
+/// Removes the parentheses around `expr` if they enclose the whole expression.
+fn unparen(expr: &str) -> &str {
+    let inner = match expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => return expr,
+    };
+    // The parentheses enclose the whole expression only if the inner expression is balanced.
+    let mut depth = 0usize;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return expr,
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    inner
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("    ");
+    }
+}
+
+fn line(out: &mut String, indent: usize, content: &str) {
+    push_indent(out, indent);
+    out.push_str(content);
+    out.push('\n');
+}
@@ -0,0 +1,1238 @@
+//! Rust backend.
+//!
+//! This backend emits a Rust function that evaluates a type-checked MIR term. The function only
+//! uses the standard library, so it can be added to any crate to run Pijama code without the
+//! machine:
+//!
+//! - `Int`, `Float`, `Bool`, `Char` and `Unit` become `i64`, `f64`, `bool`, `char` and `()`.
+//!   Arithmetic overflow behaves as in the rest of the crate the function is compiled in, so the
+//!   function only behaves like the machine if the machine has the default width.
+//! - Functions become `fn` items, unless they use local variables bound outside of them. Those
+//!   functions become closures instead. Functions take all their parameters at once.
+//! - Parameters with function types have type `&dyn Fn(..)`, so functions passed as arguments are
+//!   borrowed.
+//! - `print` writes to the standard output using `println!` and `read_int` reads a line of the
+//!   standard input, panicking if it is not an integer.
+//!
+//! Some programs cannot be translated this way. Emitting them returns an error if they use partial
+//! application, if a function returns another function, if a recursive function uses a
+//! variable bound outside of it that is not a function emitted as a `fn` item, if they use
+//! enums, `match` expressions, strings, lists, threads, channels, lazy terms or `random` or if
+//! they have integer literals that do not fit in an `i64`.
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{convert::TryFrom, mem};
+
+use pijama_ast::{BinOp, Int, Literal, Located, Location, Name, Primitive, UnOp};
+
+use crate::{
+    codegen::{line, push_indent, unparen, unsupported, CodegenResult},
+    mir::{LetKind, Term},
+    ty::{Ty, TyTable},
+};
+
+/// The Rust type of integers.
+const INT: &str = "i64";
+
+/// Rust keywords, which must be emitted as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield",
+];
+
+/// Keywords that cannot be used even as raw identifiers.
+const RESERVED: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Emits a public Rust function called `name` that evaluates `term` and returns its value.
+///
+/// The types of the terms of `term` are taken from `tys`, which must be the result of
+/// type-checking it.
+pub fn emit<'a>(
+    term: &Located<Term<'a>>,
+    tys: &TyTable<'_, 'a>,
+    name: &str,
+) -> CodegenResult<String> {
+    if let Some(loc) = enums(term) {
+        return Err(unsupported(loc, "Enums and `match` expressions"));
+    }
+    if let Some(loc) = strings(term) {
+        return Err(unsupported(loc, "Strings"));
+    }
+    if let Some(loc) = lists(term) {
+        return Err(unsupported(loc, "Lists"));
+    }
+    if let Some(loc) = channels(term) {
+        return Err(unsupported(loc, "Threads and channels"));
+    }
+    if let Some(loc) = lazy_terms(term) {
+        return Err(unsupported(loc, "Lazy terms"));
+    }
+    if let Some(loc) = inferred_ty(term) {
+        return Err(unsupported(loc, "Inferred or generic types"));
+    }
+    let mut emitter = Emitter {
+        tys,
+        scope: Vec::new(),
+        barrier: 0,
+        closure: 0,
+        names: Vec::new(),
+        items: Vec::new(),
+    };
+    emitter.collect_names(&term.content);
+    let ty = emitter.ty_of(&term.content);
+    if let Ty::Arrow(..) = ty {
+        return Err(unsupported(term.loc, "Evaluating a program to a function"));
+    }
+
+    let mut out = String::new();
+    // Overflows in constant expressions must behave at runtime like the other overflows.
+    line(&mut out, 0, "#[allow(unused, arithmetic_overflow)]");
+    line(&mut out, 0, &format!("pub fn {}(){} {{", name, ret_ty(&ty)));
+    emitter.block(term, 1, &mut out)?;
+    line(&mut out, 0, "}");
+    Ok(out)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// A local variable or a closure.
+    Local,
+    /// A local variable holding a reference to a function.
+    Ref,
+    /// A `fn` item.
+    Item,
+    /// A `fn` item without parameters that must be called to get its value.
+    Thunk,
+}
+
+struct Binding<'a> {
+    name: Name<'a>,
+    rust_name: String,
+    kind: Kind,
+}
+
+struct Emitter<'t, 'a> {
+    /// The types of the terms being emitted.
+    tys: &'t TyTable<'t, 'a>,
+    /// The bindings in scope, innermost last.
+    scope: Vec<Binding<'a>>,
+    /// Local bindings below this index were bound outside the `fn` item being emitted and cannot
+    /// be used.
+    barrier: usize,
+    /// Bindings below this index were bound outside the closure being emitted and cannot be
+    /// assigned.
+    closure: usize,
+    /// Every name bound in the term, once per binding.
+    names: Vec<&'a str>,
+    /// The names given to `fn` items.
+    items: Vec<String>,
+}
+
+impl<'t, 'a> Emitter<'t, 'a> {
+    fn collect_names(&mut self, term: &Term<'a>) {
+        match term {
+            Term::Abs(name, _, body) => {
+                self.names.push(name.content.0);
+                self.collect_names(&body.content);
+            }
+            Term::Let(_, name, t1, t2) => {
+                self.names.push(name.content.0);
+                self.collect_names(&t1.content);
+                self.collect_names(&t2.content);
+            }
+            Term::LetRec(fns, t1) => {
+                for (name, _, body) in fns {
+                    self.names.push(name.content.0);
+                    self.collect_names(&body.content);
+                }
+                self.collect_names(&t1.content);
+            }
+            Term::UnaryOp(_, t1) | Term::Assign(_, t1) | Term::Ascription(t1, _) => {
+                self.collect_names(&t1.content)
+            }
+            Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+                self.collect_names(&t1.content);
+                self.collect_names(&t2.content);
+            }
+            Term::Cond(t1, t2, t3) => {
+                self.collect_names(&t1.content);
+                self.collect_names(&t2.content);
+                self.collect_names(&t3.content);
+            }
+            Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) => (),
+            Term::Enum(..)
+            | Term::Constructor(_)
+            | Term::Match(..)
+            | Term::List(_)
+            | Term::Lazy(_) => unreachable!(),
+        }
+    }
+
+    /// Returns the name of a new `fn` item.
+    ///
+    /// Items are visible in the whole block where they are defined, so their name is changed if
+    /// any other binding in the term has the same name.
+    fn item_name(&mut self, name: Name<'a>) -> String {
+        let bound = self.names.iter().filter(|name2| **name2 == name.0).count();
+        let mut rust_name = ident(name.0);
+        let mut suffix = 0;
+        while (bound > 1 && suffix == 0)
+            || self.items.contains(&rust_name)
+            || (suffix > 0 && self.names.contains(&rust_name.as_str()))
+        {
+            suffix += 1;
+            rust_name = format!("{}_{}", name.0, suffix);
+        }
+        self.items.push(rust_name.clone());
+        rust_name
+    }
+
+    fn bind(&mut self, name: Name<'a>, rust_name: String, kind: Kind) {
+        self.scope.push(Binding {
+            name,
+            rust_name,
+            kind,
+        });
+    }
+
+    fn lookup(&self, loc: Location, name: Name<'a>) -> CodegenResult<String> {
+        let (index, binding) = self
+            .scope
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, binding)| binding.name == name)
+            .expect("Unbounded variable in a type-checked term");
+        match binding.kind {
+            Kind::Local | Kind::Ref if index < self.barrier => Err(unsupported(
+                loc,
+                "Using a variable bound outside of a recursive function",
+            )),
+            Kind::Local | Kind::Ref | Kind::Item => Ok(binding.rust_name.clone()),
+            Kind::Thunk => Ok(format!("{}()", binding.rust_name)),
+        }
+    }
+
+    /// Returns the type of `term` found by the type checker.
+    fn ty_of(&self, term: &Term<'a>) -> Ty {
+        self.tys
+            .get(term)
+            .expect("Term without a type in a type-checked term")
+            .clone()
+    }
+
+    /// Emits `term` as the statements and the final expression of a Rust block, indented
+    /// `indent` levels.
+    fn block(
+        &mut self,
+        term: &Located<Term<'a>>,
+        indent: usize,
+        out: &mut String,
+    ) -> CodegenResult<()> {
+        let scope_len = self.scope.len();
+        let mut term = term;
+        loop {
+            match &term.content {
+                Term::Let(LetKind::Rec(ty), name, t1, t2) => {
+                    self.item(name, &ty.content, t1, true, indent, out)?;
+                    term = t2;
+                }
+                // Every function of the group is bound before emitting any of them, so they can
+                // call each other.
+                Term::LetRec(fns, t1) => {
+                    let mut items = Vec::new();
+                    for (name, ty, body) in fns {
+                        let (rust_name, kind, ret) = self.new_item(name, &ty.content, body)?;
+                        self.bind(name.content, rust_name.clone(), kind);
+                        items.push((rust_name, ret));
+                    }
+                    for ((rust_name, ret), (_, _, body)) in items.iter().zip(fns) {
+                        self.item_body(rust_name, ret, body, indent, out)?;
+                    }
+                    term = t1;
+                }
+                Term::Let(LetKind::NonRec(_), name, t1, t2)
+                    if matches!(t1.content, Term::Abs(..))
+                        && self.try_item(name, t1, indent, out) =>
+                {
+                    term = t2;
+                }
+                Term::Let(LetKind::NonRec(_), name, t1, t2) => {
+                    let ty = self.ty_of(&t1.content);
+                    let value = match (&t1.content, &ty) {
+                        (Term::Abs(..), _) => self.closure(t1, indent)?,
+                        (_, Ty::Arrow(..)) => self.fn_value(t1, indent)?,
+                        _ => {
+                            let value = self.expr(t1, indent)?;
+                            format!("{} = {}", rust_ty(&ty), unparen(&value))
+                        }
+                    };
+                    let rust_name = ident(name.content.0);
+                    if let Ty::Arrow(..) = ty {
+                        line(out, indent, &format!("let {} = {};", rust_name, value));
+                    } else {
+                        line(out, indent, &format!("let {}: {};", rust_name, value));
+                    }
+                    let kind = match (&t1.content, &ty) {
+                        (Term::Abs(..), _) => Kind::Local,
+                        (_, Ty::Arrow(..)) => Kind::Ref,
+                        _ => Kind::Local,
+                    };
+                    self.bind(name.content, rust_name, kind);
+                    term = t2;
+                }
+                Term::Let(LetKind::Mut(_), name, t1, t2) => {
+                    let ty = self.ty_of(&t1.content);
+                    if let Ty::Arrow(..) = ty {
+                        return Err(unsupported(name.loc, "Mutable bindings of functions"));
+                    }
+                    let value = self.expr(t1, indent)?;
+                    let rust_name = ident(name.content.0);
+                    line(
+                        out,
+                        indent,
+                        &format!(
+                            "let mut {}: {} = {};",
+                            rust_name,
+                            rust_ty(&ty),
+                            unparen(&value)
+                        ),
+                    );
+                    self.bind(name.content, rust_name, Kind::Local);
+                    term = t2;
+                }
+                Term::Seq(t1, t2) => {
+                    let value = self.expr(t1, indent)?;
+                    line(out, indent, &format!("{};", unparen(&value)));
+                    term = t2;
+                }
+                _ => {
+                    let value = self.expr(term, indent)?;
+                    line(out, indent, unparen(&value));
+                    break;
+                }
+            }
+        }
+        self.scope.truncate(scope_len);
+        Ok(())
+    }
+
+    /// Emits a function as a `fn` item.
+    fn item(
+        &mut self,
+        name: &Located<Name<'a>>,
+        ty: &Ty,
+        term: &Located<Term<'a>>,
+        rec: bool,
+        indent: usize,
+        out: &mut String,
+    ) -> CodegenResult<()> {
+        let (rust_name, kind, ret) = self.new_item(name, ty, term)?;
+        if rec {
+            self.bind(name.content, rust_name.clone(), kind);
+        }
+
+        let result = self.item_body(&rust_name, &ret, term, indent, out);
+
+        if !rec {
+            self.bind(name.content, rust_name, kind);
+        }
+        result
+    }
+
+    /// Returns the name of the `fn` item for a function of type `ty`, the kind of its binding and
+    /// its return type.
+    fn new_item(
+        &mut self,
+        name: &Located<Name<'a>>,
+        ty: &Ty,
+        term: &Located<Term<'a>>,
+    ) -> CodegenResult<(String, Kind, Ty)> {
+        let (params, _) = params(term);
+        let ret = ret_of(ty, params.len())
+            .ok_or_else(|| unsupported(name.loc, "Returning a function from a function"))?;
+
+        let rust_name = self.item_name(name.content);
+        // Recursive functions without parameters are not abstractions, they are emitted as
+        // functions without parameters so they are only evaluated when used.
+        let kind = if params.is_empty() {
+            Kind::Thunk
+        } else {
+            Kind::Item
+        };
+        Ok((rust_name, kind, ret))
+    }
+
+    /// Emits the `fn` item called `rust_name` for a function returning `ret`.
+    fn item_body(
+        &mut self,
+        rust_name: &str,
+        ret: &Ty,
+        term: &Located<Term<'a>>,
+        indent: usize,
+        out: &mut String,
+    ) -> CodegenResult<()> {
+        let (params, body) = params(term);
+        let scope_len = self.scope.len();
+        let barrier = mem::replace(&mut self.barrier, scope_len);
+        let params = self.params(&params);
+        line(
+            out,
+            indent,
+            &format!("fn {}({}){} {{", rust_name, params, ret_ty(ret)),
+        );
+        let result = self.block(body, indent + 1, out);
+        line(out, indent, "}");
+        self.scope.truncate(scope_len);
+        self.barrier = barrier;
+        result
+    }
+
+    /// Emits a non-recursive function as a `fn` item if it does not use any local variable bound
+    /// outside of it. Returns false and emits nothing otherwise.
+    fn try_item(
+        &mut self,
+        name: &Located<Name<'a>>,
+        term: &Located<Term<'a>>,
+        indent: usize,
+        out: &mut String,
+    ) -> bool {
+        let scope_len = self.scope.len();
+        let items_len = self.items.len();
+        let ty = self.ty_of(&term.content);
+        let mut item = String::new();
+        if self.item(name, &ty, term, false, indent, &mut item).is_ok() {
+            out.push_str(&item);
+            true
+        } else {
+            self.scope.truncate(scope_len);
+            self.items.truncate(items_len);
+            false
+        }
+    }
+
+    /// Emits an abstraction as a closure.
+    fn closure(&mut self, term: &Located<Term<'a>>, indent: usize) -> CodegenResult<String> {
+        let (params, body) = params(term);
+        let scope_len = self.scope.len();
+        let params = self.params(&params);
+        let ret = self.ty_of(&body.content);
+        if let Ty::Arrow(..) = ret {
+            return Err(unsupported(
+                term.loc,
+                "Returning a function from a function",
+            ));
+        }
+
+        let closure = mem::replace(&mut self.closure, scope_len);
+        let result = self.closure_body(&params, &ret, body, indent);
+        self.closure = closure;
+        self.scope.truncate(scope_len);
+        result
+    }
+
+    /// Emits the closure with the given Rust parameters, return type and body.
+    fn closure_body(
+        &mut self,
+        params: &str,
+        ret: &Ty,
+        body: &Located<Term<'a>>,
+        indent: usize,
+    ) -> CodegenResult<String> {
+        let closure = match &body.content {
+            Term::Let(..) | Term::LetRec(..) | Term::Seq(..) | Term::Cond(..) => {
+                let mut out = format!("|{}|{} {{\n", params, ret_ty(ret));
+                self.block(body, indent + 1, &mut out)?;
+                push_indent(&mut out, indent);
+                out.push('}');
+                out
+            }
+            _ => {
+                let body = self.expr(body, indent)?;
+                format!("|{}|{} {{ {} }}", params, ret_ty(ret), unparen(&body))
+            }
+        };
+        Ok(closure)
+    }
+
+    /// Binds the parameters of a function and returns them as a list of Rust parameters.
+    fn params(&mut self, params: &[(Name<'a>, &Ty)]) -> String {
+        let mut rust_params = Vec::new();
+        for (name, ty) in params {
+            let rust_name = ident(name.0);
+            rust_params.push(format!("{}: {}", rust_name, rust_ty(ty)));
+            let kind = match ty {
+                Ty::Arrow(..) => Kind::Ref,
+                _ => Kind::Local,
+            };
+            self.bind(*name, rust_name, kind);
+        }
+        rust_params.join(", ")
+    }
+
+    /// Emits a term with a function type that is used as a value.
+    fn fn_value(&mut self, term: &Located<Term<'a>>, indent: usize) -> CodegenResult<String> {
+        match &term.content {
+            Term::Var(name) => {
+                let rust_name = self.lookup(term.loc, *name)?;
+                let by_ref = self
+                    .scope
+                    .iter()
+                    .rev()
+                    .find(|binding| binding.name == *name)
+                    .is_some_and(|binding| binding.kind == Kind::Ref);
+                if by_ref {
+                    Ok(rust_name)
+                } else {
+                    Ok(format!("&{}", rust_name))
+                }
+            }
+            Term::Abs(..) => Ok(format!("&{}", self.closure(term, indent)?)),
+            _ => Err(unsupported(term.loc, "Computing a function at runtime")),
+        }
+    }
+
+    /// Emits `term` as an expression. `indent` is the indentation of the line where the
+    /// expression starts.
+    fn expr(&mut self, term: &Located<Term<'a>>, indent: usize) -> CodegenResult<String> {
+        let expr = match &term.content {
+            Term::Var(name) => self.lookup(term.loc, *name)?,
+            Term::Lit(Literal::Number(n)) if i64::try_from(*n).is_err() => {
+                return Err(unsupported(term.loc, "Integers that do not fit in 64 bits"))
+            }
+            Term::Lit(lit) => literal(lit, ""),
+            Term::Abs(..) => self.closure(term, indent)?,
+            Term::Ascription(t1, _) => self.expr(t1, indent)?,
+            Term::UnaryOp(op, t1) => format!("({}{})", un_op(*op), self.expr(t1, indent)?),
+            Term::BinaryOp(op, t1, t2)
+                if !is_arithmetic(*op) && is_constant(t1) && is_constant(t2) =>
+            {
+                // The type of integer literals would default to `i32` if both operands are
+                // constant.
+                format!("({} {} {})", constant(t1), op, constant(t2))
+            }
+            Term::BinaryOp(op, t1, t2) => {
+                let t1 = self.expr(t1, indent)?;
+                let t2 = self.expr(t2, indent)?;
+                format!("({} {} {})", t1, op, t2)
+            }
+            Term::App(..) => self.app(term, indent)?,
+            Term::Cond(..) if matches!(self.ty_of(&term.content), Ty::Arrow(..)) => {
+                return Err(unsupported(term.loc, "Computing a function at runtime"));
+            }
+            Term::Cond(t1, t2, t3) => {
+                let cond = self.expr(t1, indent)?;
+                let mut out = format!("if {} {{\n", unparen(&cond));
+                self.block(t2, indent + 1, &mut out)?;
+                let mut el = t3;
+                // Nested conditionals in the else branch are emitted as `else if` branches.
+                while let Term::Cond(t1, t2, t3) = &el.content {
+                    let cond = self.expr(t1, indent)?;
+                    push_indent(&mut out, indent);
+                    out.push_str(&format!("}} else if {} {{\n", unparen(&cond)));
+                    self.block(t2, indent + 1, &mut out)?;
+                    el = t3;
+                }
+                push_indent(&mut out, indent);
+                out.push_str("} else {\n");
+                self.block(el, indent + 1, &mut out)?;
+                push_indent(&mut out, indent);
+                out.push('}');
+                out
+            }
+            Term::Assign(name, t1) => {
+                let index = self
+                    .scope
+                    .iter()
+                    .rposition(|binding| binding.name == name.content)
+                    .expect("Unbounded variable in a type-checked term");
+                // Closures assigning the variables they capture would have to be mutable.
+                if index < self.closure {
+                    return Err(unsupported(
+                        name.loc,
+                        "Assigning a variable bound outside of a closure",
+                    ));
+                }
+                let rust_name = self.lookup(name.loc, name.content)?;
+                let value = self.expr(t1, indent)?;
+                format!("({} = {})", rust_name, unparen(&value))
+            }
+            Term::Let(..) | Term::LetRec(..) | Term::Seq(..) => {
+                let mut out = String::from("{\n");
+                self.block(term, indent + 1, &mut out)?;
+                push_indent(&mut out, indent);
+                out.push('}');
+                out
+            }
+            Term::PrimFn(Primitive::ReadInt) => {
+                let mut out = String::from("{\n");
+                line(&mut out, indent + 1, "let mut line = String::new();");
+                line(
+                    &mut out,
+                    indent + 1,
+                    "std::io::stdin().read_line(&mut line).expect(\"Cannot read the input\");",
+                );
+                line(
+                    &mut out,
+                    indent + 1,
+                    &format!(
+                        "line.trim().parse::<{}>().expect(\"Cannot read the line as an integer\")",
+                        INT
+                    ),
+                );
+                push_indent(&mut out, indent);
+                out.push('}');
+                out
+            }
+            Term::PrimFn(prim) => {
+                return Err(unsupported(
+                    term.loc,
+                    &format!("Using `{}` as a value", prim),
+                ))
+            }
+            Term::Native(..) => return Err(unsupported(term.loc, "Calling a native function")),
+            Term::Enum(..)
+            | Term::Constructor(_)
+            | Term::Match(..)
+            | Term::List(_)
+            | Term::Lazy(_) => unreachable!(),
+        };
+        Ok(expr)
+    }
+
+    /// Emits an application, passing all the arguments at once.
+    fn app(&mut self, term: &Located<Term<'a>>, indent: usize) -> CodegenResult<String> {
+        let mut args = Vec::new();
+        let mut head = term;
+        while let Term::App(t1, t2) = &head.content {
+            args.push(t2.as_ref());
+            head = t1;
+        }
+        args.reverse();
+
+        if let Term::PrimFn(Primitive::Print) = head.content {
+            let arg = args[0];
+            let ty = self.ty_of(&arg.content);
+            let arg = match ty {
+                Ty::Arrow(..) => return Err(unsupported(arg.loc, "Printing a function")),
+                // The type of integer literals would default to `i32` if the argument is constant.
+                _ if is_constant(arg) => constant(arg),
+                _ => self.expr(arg, indent)?,
+            };
+            // Characters are printed without quotes like in the machine.
+            let format = if ty == Ty::Char { "{}" } else { "{:?}" };
+            return Ok(format!("println!(\"{}\", {})", format, unparen(&arg)));
+        }
+        // The standard library has no random number generator.
+        if let Term::PrimFn(Primitive::Random) = head.content {
+            return Err(unsupported(head.loc, "Random numbers"));
+        }
+        if let Term::PrimFn(
+            prim @ Primitive::Min
+            | prim @ Primitive::Max
+            | prim @ Primitive::Gcd
+            | prim @ Primitive::Pow,
+        ) = head.content
+        {
+            if args.len() != 2 {
+                return Err(unsupported(term.loc, "Partial application"));
+            }
+            let arg1 = self.expr(args[0], indent)?;
+            let arg2 = self.expr(args[1], indent)?;
+            return Ok(binary_math(prim, unparen(&arg1), unparen(&arg2), indent));
+        }
+        if let Term::PrimFn(prim) = head.content {
+            let mut arg = self.expr(args[0], indent)?;
+            if let Term::Cond(..) | Term::Let(..) | Term::LetRec(..) | Term::Seq(..) =
+                args[0].content
+            {
+                arg = format!("({})", arg);
+            }
+            return Ok(match prim {
+                Primitive::IntToFloat => format!("({} as f64)", arg),
+                // Casting a float to an integer rounds towards zero and saturates like the
+                // machine.
+                Primitive::FloatToInt | Primitive::CharToInt => format!("({} as {})", arg, INT),
+                Primitive::IntToChar => format!(
+                    "std::convert::TryFrom::try_from({}).ok().and_then(std::char::from_u32).expect(\"Invalid character\")",
+                    unparen(&arg)
+                ),
+                Primitive::Assert => format!("assert!({})", unparen(&arg)),
+                Primitive::Abs => format!("{}::abs({})", INT, unparen(&arg)),
+                Primitive::Sign => format!("{}::signum({})", INT, unparen(&arg)),
+                Primitive::Print
+                | Primitive::Concat
+                | Primitive::ToString
+                | Primitive::ReadLine
+                | Primitive::ReadInt
+                | Primitive::Head
+                | Primitive::Tail
+                | Primitive::Len
+                | Primitive::Push
+                | Primitive::Get
+                | Primitive::Min
+                | Primitive::Max
+                | Primitive::Gcd
+                | Primitive::Pow
+                | Primitive::Substring
+                | Primitive::CharAt
+                | Primitive::ParseInt
+                | Primitive::Random
+                | Primitive::Spawn
+                | Primitive::Channel
+                | Primitive::Send
+                | Primitive::Recv
+                | Primitive::Force => unreachable!(),
+            });
+        }
+
+        if arity(&self.ty_of(&head.content)) != args.len() {
+            return Err(unsupported(term.loc, "Partial application"));
+        }
+        let func = match &head.content {
+            Term::Var(name) => self.lookup(head.loc, *name)?,
+            Term::Abs(..) => format!("({})", self.closure(head, indent)?),
+            _ => return Err(unsupported(head.loc, "Computing a function at runtime")),
+        };
+        let mut rust_args = Vec::new();
+        for arg in args {
+            let arg = if let Ty::Arrow(..) = self.ty_of(&arg.content) {
+                self.fn_value(arg, indent)?
+            } else {
+                self.expr(arg, indent)?
+            };
+            rust_args.push(unparen(&arg).to_string());
+        }
+        Ok(format!("{}({})", func, rust_args.join(", ")))
+    }
+}
+
+/// Returns the parameters of a function and its body.
+fn params<'a, 't>(
+    mut term: &'t Located<Term<'a>>,
+) -> (Vec<(Name<'a>, &'t Ty)>, &'t Located<Term<'a>>) {
+    let mut params = Vec::new();
+    while let Term::Abs(name, ty, body) = &term.content {
+        params.push((name.content, ty));
+        term = body;
+    }
+    (params, term)
+}
+
+/// Returns the return type of a function of type `ty` with `arity` parameters, or `None` if it
+/// returns a function.
+fn ret_of(ty: &Ty, arity: usize) -> Option<Ty> {
+    let mut ty = ty;
+    for _ in 0..arity {
+        match ty {
+            Ty::Arrow(_, ret) => ty = ret,
+            _ => unreachable!(),
+        }
+    }
+    match ty {
+        Ty::Arrow(..) => None,
+        ty => Some(ty.clone()),
+    }
+}
+
+/// Returns the number of parameters a value of type `ty` takes.
+fn arity(ty: &Ty) -> usize {
+    match ty {
+        Ty::Arrow(_, ret) => 1 + arity(ret),
+        _ => 0,
+    }
+}
+
+fn rust_ty(ty: &Ty) -> String {
+    match ty {
+        Ty::Int => INT.to_string(),
+        Ty::Float => "f64".to_string(),
+        Ty::Char => "char".to_string(),
+        Ty::Bool => "bool".to_string(),
+        Ty::Unit => "()".to_string(),
+        Ty::Arrow(..) => {
+            let mut params = Vec::new();
+            let mut ty = ty;
+            while let Ty::Arrow(param, ret) = ty {
+                params.push(rust_ty(param));
+                ty = ret;
+            }
+            format!("&dyn Fn({}){}", params.join(", "), ret_ty(ty))
+        }
+        Ty::Var(_) => panic!("Type variable in a type-checked term"),
+        Ty::Adt(..) | Ty::String | Ty::List(_) | Ty::Chan(_) | Ty::Lazy(_) => unreachable!(),
+    }
+}
+
+fn ret_ty(ty: &Ty) -> String {
+    match ty {
+        Ty::Unit => String::new(),
+        ty => format!(" -> {}", rust_ty(ty)),
+    }
+}
+
+fn ident(name: &str) -> String {
+    if name.contains("::") {
+        // Names of other modules cannot have two consecutive underscores, so they do not clash
+        // with any other name.
+        name.replace("::", "__")
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else if RESERVED.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn literal(lit: &Literal, suffix: &str) -> String {
+    match lit {
+        Literal::Bool(b) => b.to_string(),
+        Literal::Unit => "()".to_string(),
+        // The magnitude of the minimum value does not fit in an integer literal.
+        Literal::Number(n) if *n == Int::from(i64::MIN) => format!("{}::MIN", INT),
+        Literal::Number(n) if *n < 0 => format!("({}{})", n, suffix),
+        Literal::Number(n) => format!("{}{}", n, suffix),
+        Literal::Float(x) if x.is_sign_negative() => format!("({:?})", x),
+        Literal::Float(x) => format!("{:?}", x),
+        Literal::Char(c) => format!("{:?}", c),
+        Literal::Str(_) => unreachable!(),
+    }
+}
+
+/// Returns the Rust operator for `op`, which uses `!` for both logical and bit-wise negation.
+fn un_op(op: UnOp) -> &'static str {
+    match op {
+        UnOp::Neg => "-",
+        UnOp::Not | UnOp::BitNot => "!",
+    }
+}
+
+fn is_arithmetic(op: BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add
+            | BinOp::Sub
+            | BinOp::Mul
+            | BinOp::Div
+            | BinOp::Rem
+            | BinOp::BitAnd
+            | BinOp::BitOr
+            | BinOp::BitXor
+            | BinOp::Shr
+            | BinOp::Shl
+    )
+}
+
+/// Returns true if `term` is an integer expression without variables.
+fn is_constant(term: &Located<Term<'_>>) -> bool {
+    match &term.content {
+        Term::Lit(Literal::Number(n)) => i64::try_from(*n).is_ok(),
+        Term::UnaryOp(UnOp::Neg | UnOp::BitNot, t1) => is_constant(t1),
+        Term::BinaryOp(op, t1, t2) => is_arithmetic(*op) && is_constant(t1) && is_constant(t2),
+        _ => false,
+    }
+}
+
+/// Emits a constant integer expression with explicitly typed literals.
+fn constant(term: &Located<Term<'_>>) -> String {
+    match &term.content {
+        Term::Lit(lit) => literal(lit, INT),
+        Term::UnaryOp(op, t1) => format!("({}{})", un_op(*op), constant(t1)),
+        Term::BinaryOp(op, t1, t2) => format!("({} {} {})", constant(t1), op, constant(t2)),
+        _ => unreachable!(),
+    }
+}
+
+/// Returns the location of the first function or let binding in `term` whose type annotation is
+/// missing or has type variables.
+///
+/// The types inferred for those annotations are not stored in the term, so they cannot be emitted.
+fn inferred_ty(term: &Located<Term<'_>>) -> Option<Location> {
+    match &term.content {
+        Term::Abs(_, ty, _) if !ty.is_concrete() => Some(term.loc),
+        Term::Let(LetKind::Rec(ty), ..)
+        | Term::Let(LetKind::NonRec(Some(ty)), ..)
+        | Term::Let(LetKind::Mut(Some(ty)), ..)
+            if !ty.content.is_concrete() =>
+        {
+            Some(ty.loc)
+        }
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _) => inferred_ty(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => inferred_ty(t1).or_else(|| inferred_ty(t2)),
+        Term::LetRec(fns, t1) => fns
+            .iter()
+            .find_map(|(_, ty, body)| {
+                if !ty.content.is_concrete() {
+                    Some(ty.loc)
+                } else {
+                    inferred_ty(body)
+                }
+            })
+            .or_else(|| inferred_ty(t1)),
+        Term::Cond(t1, t2, t3) => inferred_ty(t1)
+            .or_else(|| inferred_ty(t2))
+            .or_else(|| inferred_ty(t3)),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) => None,
+        Term::Enum(..) | Term::Constructor(_) | Term::Match(..) | Term::List(_) | Term::Lazy(_) => {
+            unreachable!()
+        }
+    }
+}
+
+/// Returns the location of the first enum declaration, `match` expression or annotation with an
+/// enum type in `term`.
+fn enums(term: &Located<Term<'_>>) -> Option<Location> {
+    match &term.content {
+        Term::Enum(..) | Term::Match(..) => Some(term.loc),
+        Term::Abs(_, ty, _) if has_adt(ty) => Some(term.loc),
+        Term::Let(LetKind::Rec(ty), ..)
+        | Term::Let(LetKind::NonRec(Some(ty)), ..)
+        | Term::Let(LetKind::Mut(Some(ty)), ..)
+            if has_adt(&ty.content) =>
+        {
+            Some(ty.loc)
+        }
+        Term::Ascription(_, ty) if has_adt(&ty.content) => Some(ty.loc),
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1) => enums(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => enums(t1).or_else(|| enums(t2)),
+        Term::LetRec(fns, t1) => fns
+            .iter()
+            .find_map(|(_, ty, body)| {
+                if has_adt(&ty.content) {
+                    Some(ty.loc)
+                } else {
+                    enums(body)
+                }
+            })
+            .or_else(|| enums(t1)),
+        Term::Cond(t1, t2, t3) => enums(t1).or_else(|| enums(t2)).or_else(|| enums(t3)),
+        Term::List(elems) => elems.iter().find_map(enums),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) | Term::Constructor(_) => {
+            None
+        }
+    }
+}
+
+/// Emits the application of `min`, `max`, `gcd` or `pow` to `arg1` and `arg2`. `indent` is the
+/// indentation of the line where the expression starts.
+///
+/// `gcd` and `pow` become blocks computing their result in the same way as the machine.
+fn binary_math(prim: Primitive, arg1: &str, arg2: &str, indent: usize) -> String {
+    let code: &[&str] = match prim {
+        Primitive::Min => return format!("{}::min({}, {})", INT, arg1, arg2),
+        Primitive::Max => return format!("{}::max({}, {})", INT, arg1, arg2),
+        Primitive::Gcd => &[
+            "let (mut a, mut b) = (n1.abs(), n2.abs());",
+            "while b != 0 {",
+            "    let r = a % b;",
+            "    a = b;",
+            "    b = r;",
+            "}",
+            "a",
+        ],
+        // Negative powers are rounded towards zero like divisions.
+        Primitive::Pow => &[
+            "let (mut base, mut exp, mut result) = (n1, n2, 1);",
+            "if exp < 0 {",
+            "    assert!(base != 0, \"Division by zero\");",
+            "    result = match base {",
+            "        1 => 1,",
+            "        -1 if exp % 2 == 0 => 1,",
+            "        -1 => -1,",
+            "        _ => 0,",
+            "    };",
+            "}",
+            "while exp > 0 {",
+            "    if exp & 1 == 1 {",
+            "        result *= base;",
+            "    }",
+            "    exp >>= 1;",
+            "    if exp > 0 {",
+            "        base *= base;",
+            "    }",
+            "}",
+            "result",
+        ],
+        prim => unreachable!("`{}` does not take two integers", prim),
+    };
+    let mut out = String::from("{\n");
+    line(
+        &mut out,
+        indent + 1,
+        &format!("let (n1, n2): ({}, {}) = ({}, {});", INT, INT, arg1, arg2),
+    );
+    for code in code {
+        line(&mut out, indent + 1, code);
+    }
+    push_indent(&mut out, indent);
+    out.push('}');
+    out
+}
+
+/// Returns true if `ty` has an enum type inside it.
+fn has_adt(ty: &Ty) -> bool {
+    match ty {
+        Ty::Adt(..) => true,
+        Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => has_adt(ty),
+        Ty::Arrow(ty1, ty2) => has_adt(ty1) || has_adt(ty2),
+        Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String | Ty::Var(_) => false,
+    }
+}
+
+/// Returns the location of the first string, use of a string primitive or annotation with a
+/// string type in `term`.
+fn strings(term: &Located<Term<'_>>) -> Option<Location> {
+    match &term.content {
+        Term::Lit(Literal::Str(_))
+        | Term::PrimFn(Primitive::Concat)
+        | Term::PrimFn(Primitive::ToString)
+        | Term::PrimFn(Primitive::ReadLine)
+        | Term::PrimFn(Primitive::Substring)
+        | Term::PrimFn(Primitive::CharAt)
+        | Term::PrimFn(Primitive::ParseInt) => Some(term.loc),
+        Term::Abs(_, ty, _) if has_string(ty) => Some(term.loc),
+        Term::Let(LetKind::Rec(ty), ..)
+        | Term::Let(LetKind::NonRec(Some(ty)), ..)
+        | Term::Let(LetKind::Mut(Some(ty)), ..)
+            if has_string(&ty.content) =>
+        {
+            Some(ty.loc)
+        }
+        Term::Ascription(_, ty) if has_string(&ty.content) => Some(ty.loc),
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1) => strings(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => strings(t1).or_else(|| strings(t2)),
+        Term::LetRec(fns, t1) => fns
+            .iter()
+            .find_map(|(_, ty, body)| {
+                if has_string(&ty.content) {
+                    Some(ty.loc)
+                } else {
+                    strings(body)
+                }
+            })
+            .or_else(|| strings(t1)),
+        Term::Cond(t1, t2, t3) => strings(t1).or_else(|| strings(t2)).or_else(|| strings(t3)),
+        Term::List(elems) => elems.iter().find_map(strings),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) => None,
+        Term::Enum(..) | Term::Constructor(_) | Term::Match(..) => unreachable!(),
+    }
+}
+
+/// Returns true if `ty` has a string type inside it.
+fn has_string(ty: &Ty) -> bool {
+    match ty {
+        Ty::String => true,
+        Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => has_string(ty),
+        Ty::Arrow(ty1, ty2) => has_string(ty1) || has_string(ty2),
+        Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::Adt(..) | Ty::Var(_) => false,
+    }
+}
+
+/// Returns the location of the first list, use of a list primitive or annotation with a list type
+/// in `term`.
+fn lists(term: &Located<Term<'_>>) -> Option<Location> {
+    match &term.content {
+        Term::List(_)
+        | Term::PrimFn(Primitive::Head)
+        | Term::PrimFn(Primitive::Tail)
+        | Term::PrimFn(Primitive::Len)
+        | Term::PrimFn(Primitive::Push)
+        | Term::PrimFn(Primitive::Get) => Some(term.loc),
+        Term::Abs(_, ty, _) if has_list(ty) => Some(term.loc),
+        Term::Let(LetKind::Rec(ty), ..)
+        | Term::Let(LetKind::NonRec(Some(ty)), ..)
+        | Term::Let(LetKind::Mut(Some(ty)), ..)
+            if has_list(&ty.content) =>
+        {
+            Some(ty.loc)
+        }
+        Term::Ascription(_, ty) if has_list(&ty.content) => Some(ty.loc),
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1) => lists(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => lists(t1).or_else(|| lists(t2)),
+        Term::LetRec(fns, t1) => fns
+            .iter()
+            .find_map(|(_, ty, body)| {
+                if has_list(&ty.content) {
+                    Some(ty.loc)
+                } else {
+                    lists(body)
+                }
+            })
+            .or_else(|| lists(t1)),
+        Term::Cond(t1, t2, t3) => lists(t1).or_else(|| lists(t2)).or_else(|| lists(t3)),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) => None,
+        Term::Enum(..) | Term::Constructor(_) | Term::Match(..) => unreachable!(),
+    }
+}
+
+/// Returns true if `ty` has a list type inside it.
+fn has_list(ty: &Ty) -> bool {
+    match ty {
+        Ty::List(_) => true,
+        Ty::Chan(ty) | Ty::Lazy(ty) => has_list(ty),
+        Ty::Arrow(ty1, ty2) => has_list(ty1) || has_list(ty2),
+        Ty::Bool
+        | Ty::Int
+        | Ty::Float
+        | Ty::Unit
+        | Ty::Char
+        | Ty::String
+        | Ty::Adt(..)
+        | Ty::Var(_) => false,
+    }
+}
+
+/// Returns the location of the first use of a concurrency primitive or annotation with a channel
+/// type in `term`.
+fn channels(term: &Located<Term<'_>>) -> Option<Location> {
+    match &term.content {
+        Term::PrimFn(Primitive::Spawn)
+        | Term::PrimFn(Primitive::Channel)
+        | Term::PrimFn(Primitive::Send)
+        | Term::PrimFn(Primitive::Recv) => Some(term.loc),
+        Term::Abs(_, ty, _) if has_chan(ty) => Some(term.loc),
+        Term::Let(LetKind::Rec(ty), ..)
+        | Term::Let(LetKind::NonRec(Some(ty)), ..)
+        | Term::Let(LetKind::Mut(Some(ty)), ..)
+            if has_chan(&ty.content) =>
+        {
+            Some(ty.loc)
+        }
+        Term::Ascription(_, ty) if has_chan(&ty.content) => Some(ty.loc),
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1) => channels(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => channels(t1).or_else(|| channels(t2)),
+        Term::LetRec(fns, t1) => fns
+            .iter()
+            .find_map(|(_, ty, body)| {
+                if has_chan(&ty.content) {
+                    Some(ty.loc)
+                } else {
+                    channels(body)
+                }
+            })
+            .or_else(|| channels(t1)),
+        Term::Cond(t1, t2, t3) => channels(t1)
+            .or_else(|| channels(t2))
+            .or_else(|| channels(t3)),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) => None,
+        // Lists are rejected before looking for channels.
+        Term::List(_) | Term::Enum(..) | Term::Constructor(_) | Term::Match(..) => unreachable!(),
+    }
+}
+
+/// Returns true if `ty` has a channel type inside it.
+fn has_chan(ty: &Ty) -> bool {
+    match ty {
+        Ty::Chan(_) => true,
+        Ty::List(ty) | Ty::Lazy(ty) => has_chan(ty),
+        Ty::Arrow(ty1, ty2) => has_chan(ty1) || has_chan(ty2),
+        Ty::Bool
+        | Ty::Int
+        | Ty::Float
+        | Ty::Unit
+        | Ty::Char
+        | Ty::String
+        | Ty::Adt(..)
+        | Ty::Var(_) => false,
+    }
+}
+
+/// Returns the location of the first lazy term, use of `force` or annotation with a lazy type in
+/// `term`.
+fn lazy_terms(term: &Located<Term<'_>>) -> Option<Location> {
+    match &term.content {
+        Term::Lazy(_) | Term::PrimFn(Primitive::Force) => Some(term.loc),
+        Term::Abs(_, ty, _) if has_lazy(ty) => Some(term.loc),
+        Term::Let(LetKind::Rec(ty), ..)
+        | Term::Let(LetKind::NonRec(Some(ty)), ..)
+        | Term::Let(LetKind::Mut(Some(ty)), ..)
+            if has_lazy(&ty.content) =>
+        {
+            Some(ty.loc)
+        }
+        Term::Ascription(_, ty) if has_lazy(&ty.content) => Some(ty.loc),
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _) => lazy_terms(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => lazy_terms(t1).or_else(|| lazy_terms(t2)),
+        Term::LetRec(fns, t1) => fns
+            .iter()
+            .find_map(|(_, ty, body)| {
+                if has_lazy(&ty.content) {
+                    Some(ty.loc)
+                } else {
+                    lazy_terms(body)
+                }
+            })
+            .or_else(|| lazy_terms(t1)),
+        Term::Cond(t1, t2, t3) => lazy_terms(t1)
+            .or_else(|| lazy_terms(t2))
+            .or_else(|| lazy_terms(t3)),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) => None,
+        // Lists are rejected before looking for lazy terms.
+        Term::List(_) | Term::Enum(..) | Term::Constructor(_) | Term::Match(..) => unreachable!(),
+    }
+}
+
+/// Returns true if `ty` has a lazy type inside it.
+fn has_lazy(ty: &Ty) -> bool {
+    match ty {
+        Ty::Lazy(_) => true,
+        Ty::Arrow(ty1, ty2) => has_lazy(ty1) || has_lazy(ty2),
+        // Lists and channels are rejected before looking for lazy terms.
+        Ty::List(_) | Ty::Chan(_) => unreachable!(),
+        Ty::Bool
+        | Ty::Int
+        | Ty::Float
+        | Ty::Unit
+        | Ty::Char
+        | Ty::String
+        | Ty::Adt(..)
+        | Ty::Var(_) => false,
+    }
+}
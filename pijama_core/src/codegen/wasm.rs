@@ -0,0 +1,685 @@
+//! WebAssembly backend.
+//!
+//! This backend compiles a nameless term produced by the closure conversion into the binary
+//! format of a standalone WebAssembly module. The module exports a `main` function without
+//! parameters that returns the value of the term, and the `memory` where closures are allocated:
+//!
+//! - Integers, booleans and `unit` are `i64`s. Booleans are `0` or `1` and `unit` is `0`.
+//!   Arithmetic wraps around on overflow. Dividing by zero or dividing `i64::MIN` by `-1` traps.
+//! - Functions are closures: the address of a record in memory holding the index of their code in
+//!   the table of the module followed by their environment. Applying a closure calls its code
+//!   indirectly with the address of the record and the argument. Records are never freed.
+//! - Arguments and `let` bindings are evaluated before their body, unlike in the machine.
+//! - `print` calls the function `print` imported from the `env` module with an `i64`, which is
+//!   only imported if the program prints. Printing a function prints the address of its record.
+//! - A failed `assert` traps.
+//!
+//! Compiling a term returns an error if it uses floats, characters, strings, lists, enums, cells,
+//! native functions, primitives other than `print`, recursive functions without parameters or
+//! integer literals that do not fit in an `i64`. The module always computes with 64-bit integers,
+//! so it only behaves like the machine if the machine has the default width.
+use alloc::{format, sync::Arc, vec, vec::Vec};
+use core::convert::TryFrom;
+
+use pijama_ast::{BinOp, Int, Location, Primitive, UnOp};
+
+use crate::{
+    codegen::{unsupported, CodegenResult},
+    lir::{Pattern, Term},
+};
+
+/// The type of the code of closures, which takes a record and an argument.
+const CLOSURE_TYPE: u32 = 0;
+/// The type of `main`.
+const MAIN_TYPE: u32 = 1;
+/// The type of the function allocating memory, which takes a size and returns an address.
+const ALLOC_TYPE: u32 = 2;
+/// The type of the imported `print` function.
+const PRINT_TYPE: u32 = 3;
+
+const I32: u8 = 0x7F;
+const I64: u8 = 0x7E;
+/// The block type of blocks that do not return a value.
+const EMPTY: u8 = 0x40;
+
+const UNREACHABLE: u8 = 0x00;
+const IF: u8 = 0x04;
+const ELSE: u8 = 0x05;
+const END: u8 = 0x0B;
+const CALL: u8 = 0x10;
+const CALL_INDIRECT: u8 = 0x11;
+const LOCAL_GET: u8 = 0x20;
+const LOCAL_SET: u8 = 0x21;
+const GLOBAL_GET: u8 = 0x23;
+const GLOBAL_SET: u8 = 0x24;
+const I64_LOAD: u8 = 0x29;
+const I64_STORE: u8 = 0x37;
+const MEMORY_SIZE: u8 = 0x3F;
+const MEMORY_GROW: u8 = 0x40;
+const I32_CONST: u8 = 0x41;
+const I64_CONST: u8 = 0x42;
+const I32_EQ: u8 = 0x46;
+const I32_GT_U: u8 = 0x4B;
+const I64_EQZ: u8 = 0x50;
+const I64_EQ: u8 = 0x51;
+const I32_ADD: u8 = 0x6A;
+const I32_SUB: u8 = 0x6B;
+const I32_SHL: u8 = 0x74;
+const I32_SHR_U: u8 = 0x76;
+const I64_SUB: u8 = 0x7D;
+const I64_XOR: u8 = 0x85;
+const I32_WRAP_I64: u8 = 0xA7;
+const I64_EXTEND_I32_U: u8 = 0xAD;
+
+/// Compiles `term` into a WebAssembly module.
+///
+/// The term must be closure converted, like the terms returned by
+/// [`Term::from_mir`](crate::lir::Term::from_mir).
+pub fn compile(term: &Term) -> CodegenResult<Vec<u8>> {
+    let imports = prints(term) as u32;
+    let mut compiler = Compiler {
+        imports,
+        closures: Vec::new(),
+    };
+    let mut main = Code::new(0);
+    compiler.term(&mut main, term, &[], Location::new(0, 0))?;
+
+    let mut module = b"\0asm".to_vec();
+    module.extend_from_slice(&1u32.to_le_bytes());
+
+    let mut types = Vec::new();
+    uleb(&mut types, 4);
+    for (params, results) in [
+        (&[I64, I64][..], &[I64][..]),
+        (&[], &[I64]),
+        (&[I32], &[I32]),
+        (&[I64], &[]),
+    ] {
+        types.push(0x60);
+        bytes(&mut types, params);
+        bytes(&mut types, results);
+    }
+    section(&mut module, 1, &types);
+
+    if imports > 0 {
+        let mut import = Vec::new();
+        uleb(&mut import, 1);
+        name(&mut import, "env");
+        name(&mut import, "print");
+        import.push(0x00);
+        uleb(&mut import, PRINT_TYPE.into());
+        section(&mut module, 2, &import);
+    }
+
+    let closures = compiler.closures.len() as u64;
+    let mut functions = Vec::new();
+    uleb(&mut functions, 2 + closures);
+    uleb(&mut functions, MAIN_TYPE.into());
+    uleb(&mut functions, ALLOC_TYPE.into());
+    for _ in 0..closures {
+        uleb(&mut functions, CLOSURE_TYPE.into());
+    }
+    section(&mut module, 3, &functions);
+
+    // A table of function references with exactly one entry for each closure.
+    let mut table = vec![1, 0x70, 0x01];
+    uleb(&mut table, closures);
+    uleb(&mut table, closures);
+    section(&mut module, 4, &table);
+
+    // A memory of at least one page, which is grown by the allocation function.
+    section(&mut module, 5, &[1, 0x00, 1]);
+
+    // The address where the next record is allocated.
+    section(&mut module, 6, &[1, I32, 0x01, I32_CONST, 0, END]);
+
+    let mut exports = Vec::new();
+    uleb(&mut exports, 2);
+    name(&mut exports, "main");
+    exports.push(0x00);
+    uleb(&mut exports, imports.into());
+    name(&mut exports, "memory");
+    exports.push(0x02);
+    uleb(&mut exports, 0);
+    section(&mut module, 7, &exports);
+
+    if closures > 0 {
+        let mut elements = vec![1, 0x00, I32_CONST, 0, END];
+        uleb(&mut elements, closures);
+        for index in 0..closures {
+            uleb(&mut elements, compiler.closure_index(index as u32).into());
+        }
+        section(&mut module, 9, &elements);
+    }
+
+    let mut code = Vec::new();
+    uleb(&mut code, 2 + closures);
+    bytes(&mut code, &main.finish());
+    bytes(&mut code, &alloc());
+    for closure in &compiler.closures {
+        bytes(&mut code, closure);
+    }
+    section(&mut module, 10, &code);
+
+    Ok(module)
+}
+
+/// Where the value of a variable is found.
+#[derive(Debug, Clone, Copy)]
+enum Access {
+    /// A local of the function.
+    Local(u32),
+    /// The value with the given position in the environment of the closure being run.
+    Env(u32),
+}
+
+/// The code of a function being compiled.
+struct Code {
+    params: u32,
+    /// The number of `i64` locals besides the parameters.
+    locals: u32,
+    bytes: Vec<u8>,
+}
+
+impl Code {
+    fn new(params: u32) -> Self {
+        Code {
+            params,
+            locals: 0,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Adds a new local, returning its index.
+    fn local(&mut self) -> u32 {
+        self.locals += 1;
+        self.params + self.locals - 1
+    }
+
+    fn op(&mut self, op: u8) {
+        self.bytes.push(op);
+    }
+
+    fn op_index(&mut self, op: u8, index: u32) {
+        self.bytes.push(op);
+        uleb(&mut self.bytes, index.into());
+    }
+
+    fn i64_const(&mut self, n: i64) {
+        self.bytes.push(I64_CONST);
+        sleb(&mut self.bytes, n);
+    }
+
+    /// Emits a load or store of an `i64` at the address on the stack plus `offset`.
+    fn memory(&mut self, op: u8, offset: u32) {
+        self.bytes.extend_from_slice(&[op, 3]);
+        uleb(&mut self.bytes, offset.into());
+    }
+
+    /// Pushes the address held by `local`.
+    fn address(&mut self, local: u32) {
+        self.op_index(LOCAL_GET, local);
+        self.op(I32_WRAP_I64);
+    }
+
+    fn get(&mut self, access: Access) {
+        match access {
+            Access::Local(local) => self.op_index(LOCAL_GET, local),
+            Access::Env(index) => {
+                self.address(0);
+                self.memory(I64_LOAD, 8 * (index + 1));
+            }
+        }
+    }
+
+    /// Returns the body of the function, with the declaration of its locals.
+    fn finish(mut self) -> Vec<u8> {
+        let mut body = Vec::new();
+        if self.locals > 0 {
+            uleb(&mut body, 1);
+            uleb(&mut body, self.locals.into());
+            body.push(I64);
+        } else {
+            uleb(&mut body, 0);
+        }
+        self.bytes.push(END);
+        body.append(&mut self.bytes);
+        body
+    }
+}
+
+struct Compiler {
+    /// The number of imported functions, which come before the others.
+    imports: u32,
+    /// The bodies of the code of the closures, in the order of the table.
+    closures: Vec<Vec<u8>>,
+}
+
+impl Compiler {
+    /// Emits the code pushing the value of `term`, where `loc` is the location of the innermost
+    /// located term.
+    fn term(
+        &mut self,
+        code: &mut Code,
+        term: &Term,
+        scope: &[Access],
+        loc: Location,
+    ) -> CodegenResult<()> {
+        match term {
+            Term::Var(index) => code.get(scope[*index]),
+            Term::Lit(n) => code.i64_const(int(*n, loc)?),
+            Term::Loc(loc, term) => self.term(code, term, scope, *loc)?,
+            Term::UnaryOp(op, term) => match op {
+                UnOp::Neg => {
+                    code.i64_const(0);
+                    self.term(code, term, scope, loc)?;
+                    code.op(I64_SUB);
+                }
+                UnOp::Not => {
+                    self.term(code, term, scope, loc)?;
+                    code.op(I64_EQZ);
+                    code.op(I64_EXTEND_I32_U);
+                }
+                UnOp::BitNot => {
+                    self.term(code, term, scope, loc)?;
+                    code.i64_const(-1);
+                    code.op(I64_XOR);
+                }
+            },
+            Term::BinaryOp(BinOp::And, t1, t2) => {
+                self.condition(code, t1, scope, loc)?;
+                self.term(code, t2, scope, loc)?;
+                code.op(ELSE);
+                code.i64_const(0);
+                code.op(END);
+            }
+            Term::BinaryOp(BinOp::Or, t1, t2) => {
+                self.condition(code, t1, scope, loc)?;
+                code.i64_const(1);
+                code.op(ELSE);
+                self.term(code, t2, scope, loc)?;
+                code.op(END);
+            }
+            Term::BinaryOp(op, t1, t2) => {
+                self.term(code, t1, scope, loc)?;
+                self.term(code, t2, scope, loc)?;
+                binary(code, *op);
+            }
+            Term::Cond(t1, t2, t3) => {
+                self.condition(code, t1, scope, loc)?;
+                self.term(code, t2, scope, loc)?;
+                code.op(ELSE);
+                self.term(code, t3, scope, loc)?;
+                code.op(END);
+            }
+            Term::Assert(loc, term) => {
+                self.term(code, term, scope, *loc)?;
+                code.op(I64_EQZ);
+                code.bytes.extend_from_slice(&[IF, EMPTY, UNREACHABLE, END]);
+                code.i64_const(0);
+            }
+            Term::Closure(body, env) => {
+                let record = code.local();
+                self.record(code, record, body, env, scope, loc)?;
+            }
+            Term::Fix(function) => match function.as_ref() {
+                Term::Closure(inner, env) => match inner.as_ref() {
+                    Term::Closure(body, inner_env) => {
+                        // The fixed point is the inner closure, whose environment can hold
+                        // itself.
+                        let record = code.local();
+                        let mut fix_scope = vec![Access::Local(record)];
+                        for term in env {
+                            fix_scope.push(self.var(term, scope, loc)?);
+                        }
+                        self.record(code, record, body, inner_env, &fix_scope, loc)?;
+                    }
+                    _ => return Err(unsupported(loc, "Recursive values")),
+                },
+                _ => {
+                    return Err(unsupported(
+                        loc,
+                        "Computing a recursive function at runtime",
+                    ))
+                }
+            },
+            Term::App(function, arg) => match function.as_ref() {
+                Term::Closure(body, env) => self.binding(code, arg, body, env, scope, loc)?,
+                Term::PrimFn(Primitive::Print) => {
+                    self.term(code, arg, scope, loc)?;
+                    code.op_index(CALL, 0);
+                    code.i64_const(0);
+                }
+                Term::PrimFn(prim) => {
+                    return Err(unsupported(loc, &format!("Calling `{}`", prim)));
+                }
+                _ => {
+                    let closure = code.local();
+                    let value = code.local();
+                    self.term(code, function, scope, loc)?;
+                    code.op_index(LOCAL_SET, closure);
+                    self.term(code, arg, scope, loc)?;
+                    code.op_index(LOCAL_SET, value);
+                    code.op_index(LOCAL_GET, closure);
+                    code.op_index(LOCAL_GET, value);
+                    code.address(closure);
+                    code.memory(I64_LOAD, 0);
+                    code.op(I32_WRAP_I64);
+                    code.op_index(CALL_INDIRECT, CLOSURE_TYPE);
+                    code.op(0);
+                }
+            },
+            Term::Match(scrutinee, arms) => match (scrutinee.as_ref(), arms.as_slice()) {
+                // A strict `let` binding.
+                (Term::Variant(0, _, fields), [(Pattern::Variant(0, 1), body)])
+                    if fields.len() == 1 =>
+                {
+                    match body.as_ref() {
+                        Term::Closure(body, env) => {
+                            self.binding(code, &fields[0], body, env, scope, loc)?
+                        }
+                        _ => return Err(unsupported(loc, "Enums")),
+                    }
+                }
+                _ => self.matching(code, scrutinee, arms, scope, loc)?,
+            },
+            Term::PrimFn(prim) => {
+                return Err(unsupported(loc, &format!("Using `{}` as a value", prim)))
+            }
+            Term::Float(_) => return Err(unsupported(loc, "Floats")),
+            Term::Char(_) => return Err(unsupported(loc, "Characters")),
+            Term::Str(_) => return Err(unsupported(loc, "Strings")),
+            Term::List(_) => return Err(unsupported(loc, "Lists")),
+            Term::Variant(..) => return Err(unsupported(loc, "Enums")),
+            Term::Ref(_) | Term::Cell(_) | Term::Deref(_) | Term::Assign(..) => {
+                return Err(unsupported(loc, "Mutable variables"))
+            }
+            Term::Chan(_) => return Err(unsupported(loc, "Threads and channels")),
+            Term::Native(..) => return Err(unsupported(loc, "Calling a native function")),
+            Term::Abs(_) => return Err(unsupported(loc, "Terms that are not closure converted")),
+        }
+        Ok(())
+    }
+
+    /// Emits the code testing the value of `term`, opening an `if` block returning an `i64`.
+    fn condition(
+        &mut self,
+        code: &mut Code,
+        term: &Term,
+        scope: &[Access],
+        loc: Location,
+    ) -> CodegenResult<()> {
+        self.term(code, term, scope, loc)?;
+        code.op(I32_WRAP_I64);
+        code.bytes.extend_from_slice(&[IF, I64]);
+        Ok(())
+    }
+
+    /// Emits the code evaluating `value` and then the closure with code `body` and environment
+    /// `env` with `value` as its argument, without creating the closure.
+    fn binding(
+        &mut self,
+        code: &mut Code,
+        value: &Term,
+        body: &Term,
+        env: &[Arc<Term>],
+        scope: &[Access],
+        loc: Location,
+    ) -> CodegenResult<()> {
+        let local = code.local();
+        self.term(code, value, scope, loc)?;
+        code.op_index(LOCAL_SET, local);
+        let mut body_scope = vec![Access::Local(local)];
+        for term in env {
+            body_scope.push(self.var(term, scope, loc)?);
+        }
+        self.term(code, body, &body_scope, loc)
+    }
+
+    /// Emits the code of a match over integers.
+    fn matching(
+        &mut self,
+        code: &mut Code,
+        scrutinee: &Term,
+        arms: &[(Pattern, Arc<Term>)],
+        scope: &[Access],
+        loc: Location,
+    ) -> CodegenResult<()> {
+        let value = code.local();
+        self.term(code, scrutinee, scope, loc)?;
+        code.op_index(LOCAL_SET, value);
+        let mut open = 0;
+        let mut exhaustive = false;
+        for (pattern, body) in arms {
+            match pattern {
+                Pattern::Lit(n) => {
+                    code.op_index(LOCAL_GET, value);
+                    code.i64_const(int(*n, loc)?);
+                    code.op(I64_EQ);
+                    code.bytes.extend_from_slice(&[IF, I64]);
+                    self.term(code, body, scope, loc)?;
+                    code.op(ELSE);
+                    open += 1;
+                }
+                Pattern::Wildcard => {
+                    self.term(code, body, scope, loc)?;
+                    exhaustive = true;
+                    break;
+                }
+                Pattern::Float(_) => return Err(unsupported(loc, "Floats")),
+                Pattern::Char(_) => return Err(unsupported(loc, "Characters")),
+                Pattern::Str(_) => return Err(unsupported(loc, "Strings")),
+                Pattern::Variant(..) => return Err(unsupported(loc, "Enums")),
+            }
+        }
+        if !exhaustive {
+            // The type checker only accepts exhaustive matches.
+            code.op(UNREACHABLE);
+        }
+        for _ in 0..open {
+            code.op(END);
+        }
+        Ok(())
+    }
+
+    /// Emits the code allocating the record of a closure in `record` and pushing its address.
+    fn record(
+        &mut self,
+        code: &mut Code,
+        record: u32,
+        body: &Term,
+        env: &[Arc<Term>],
+        scope: &[Access],
+        loc: Location,
+    ) -> CodegenResult<()> {
+        code.bytes.push(I32_CONST);
+        sleb(&mut code.bytes, 8 * (env.len() as i64 + 1));
+        code.op_index(CALL, self.imports + 1);
+        code.op(I64_EXTEND_I32_U);
+        code.op_index(LOCAL_SET, record);
+        let index = self.closure(body, env.len() as u32, loc)?;
+        code.address(record);
+        code.i64_const(index.into());
+        code.memory(I64_STORE, 0);
+        for (position, term) in env.iter().enumerate() {
+            code.address(record);
+            self.term(code, term, scope, loc)?;
+            code.memory(I64_STORE, 8 * (position as u32 + 1));
+        }
+        code.op_index(LOCAL_GET, record);
+        Ok(())
+    }
+
+    /// Compiles the code of a closure with `env` values in its environment, returning its
+    /// position in the table.
+    fn closure(&mut self, body: &Term, env: u32, loc: Location) -> CodegenResult<u32> {
+        let index = self.closures.len();
+        self.closures.push(Vec::new());
+        let mut code = Code::new(2);
+        let mut scope = vec![Access::Local(1)];
+        scope.extend((0..env).map(Access::Env));
+        self.term(&mut code, body, &scope, loc)?;
+        self.closures[index] = code.finish();
+        Ok(index as u32)
+    }
+
+    /// Returns where the variable `term` of an environment is found.
+    fn var(&self, term: &Term, scope: &[Access], loc: Location) -> CodegenResult<Access> {
+        match term {
+            Term::Var(index) => Ok(scope[*index]),
+            _ => Err(unsupported(loc, "Terms that are not closure converted")),
+        }
+    }
+
+    /// Returns the index of the function of the closure with the given position in the table.
+    fn closure_index(&self, position: u32) -> u32 {
+        self.imports + 2 + position
+    }
+}
+
+/// Returns the integer literal `n` as an `i64`, or an error if it does not fit.
+fn int(n: Int, loc: Location) -> CodegenResult<i64> {
+    i64::try_from(n).map_err(|_| unsupported(loc, "Integers that do not fit in 64 bits"))
+}
+
+/// Emits a binary operator other than `&&` and `||`.
+fn binary(code: &mut Code, op: BinOp) {
+    let (op, comparison) = match op {
+        BinOp::Add => (0x7C, false),
+        BinOp::Sub => (0x7D, false),
+        BinOp::Mul => (0x7E, false),
+        BinOp::Div => (0x7F, false),
+        BinOp::Rem => (0x81, false),
+        BinOp::BitAnd => (0x83, false),
+        BinOp::BitOr => (0x84, false),
+        BinOp::BitXor => (0x85, false),
+        BinOp::Shl => (0x86, false),
+        BinOp::Shr => (0x87, false),
+        BinOp::Eq => (0x51, true),
+        BinOp::Neq => (0x52, true),
+        BinOp::Lt => (0x53, true),
+        BinOp::Gt => (0x55, true),
+        BinOp::Lte => (0x57, true),
+        BinOp::Gte => (0x59, true),
+        BinOp::And | BinOp::Or | BinOp::Pipe => unreachable!("`{}` is not a simple operator", op),
+    };
+    code.op(op);
+    if comparison {
+        code.op(I64_EXTEND_I32_U);
+    }
+}
+
+/// Returns the body of the function allocating records, which bumps the address of the next
+/// record and grows the memory if needed.
+fn alloc() -> Vec<u8> {
+    let mut body = vec![1, 1, I32];
+    body.extend_from_slice(&[
+        GLOBAL_GET,
+        0,
+        LOCAL_SET,
+        1,
+        GLOBAL_GET,
+        0,
+        LOCAL_GET,
+        0,
+        I32_ADD,
+        GLOBAL_SET,
+        0,
+        GLOBAL_GET,
+        0,
+        MEMORY_SIZE,
+        0,
+        I32_CONST,
+        16,
+        I32_SHL,
+        I32_GT_U,
+        IF,
+        EMPTY,
+        GLOBAL_GET,
+        0,
+    ]);
+    body.push(I32_CONST);
+    sleb(&mut body, 0xFFFF);
+    body.extend_from_slice(&[
+        I32_ADD,
+        I32_CONST,
+        16,
+        I32_SHR_U,
+        MEMORY_SIZE,
+        0,
+        I32_SUB,
+        MEMORY_GROW,
+        0,
+    ]);
+    body.push(I32_CONST);
+    sleb(&mut body, -1);
+    body.extend_from_slice(&[I32_EQ, IF, EMPTY, UNREACHABLE, END, END, LOCAL_GET, 1, END]);
+    body
+}
+
+/// Returns `true` if `term` uses `print`.
+fn prints(term: &Term) -> bool {
+    match term {
+        Term::PrimFn(Primitive::Print) => true,
+        Term::List(terms) | Term::Native(_, terms) | Term::Variant(_, _, terms) => {
+            terms.iter().any(|term| prints(term))
+        }
+        Term::Closure(code, env) => prints(code) || env.iter().any(|term| prints(term)),
+        Term::Abs(term)
+        | Term::UnaryOp(_, term)
+        | Term::Fix(term)
+        | Term::Ref(term)
+        | Term::Deref(term)
+        | Term::Assert(_, term)
+        | Term::Loc(_, term) => prints(term),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Assign(t1, t2) => {
+            prints(t1) || prints(t2)
+        }
+        Term::Cond(t1, t2, t3) => prints(t1) || prints(t2) || prints(t3),
+        Term::Match(term, arms) => prints(term) || arms.iter().any(|(_, body)| prints(body)),
+        _ => false,
+    }
+}
+
+/// Appends a section with the given id and content.
+fn section(module: &mut Vec<u8>, id: u8, content: &[u8]) {
+    module.push(id);
+    bytes(module, content);
+}
+
+/// Appends `content` prefixed by its length.
+fn bytes(out: &mut Vec<u8>, content: &[u8]) {
+    uleb(out, content.len() as u64);
+    out.extend_from_slice(content);
+}
+
+fn name(out: &mut Vec<u8>, name: &str) {
+    bytes(out, name.as_bytes());
+}
+
+/// Appends `n` in the unsigned LEB128 encoding.
+fn uleb(out: &mut Vec<u8>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7F) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Appends `n` in the signed LEB128 encoding.
+fn sleb(out: &mut Vec<u8>, mut n: i64) {
+    loop {
+        let byte = (n & 0x7F) as u8;
+        n >>= 7;
+        let done = (n == 0 && byte & 0x40 == 0) || (n == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,951 @@
+//! Compilation of nameless terms to native code with Cranelift.
+//!
+//! This module is only available with the `jit` feature. Programs are compiled and run with
+//! [`Machine::evaluate_jit`](crate::machine::Machine::evaluate_jit), which evaluates the programs
+//! that cannot be compiled with the machine instead.
+//!
+//! Only first-order programs over 64-bit integers, booleans and `unit` can be compiled: literals,
+//! operators, conditionals, `let` bindings, matches over integers, assertions, `print` and calls
+//! to functions defined with `fn` that receive all their arguments. Programs using anything else,
+//! such as floats, strings, lists, enums, cells, functions used as values or functions capturing
+//! local variables, are not supported.
+//!
+//! Arguments are passed by name, like in the machine. An argument that does not call functions
+//! and only uses variables that are already evaluated is computed before the call, and the error
+//! it raises, if any, is only reported when the parameter is used. Any other argument is compiled
+//! to a function that is called when the parameter is used, so it cannot print anything. Calls in
+//! tail position do not grow the native stack unless they receive an argument of this kind.
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::{convert::TryFrom, mem};
+
+use cranelift_codegen::{
+    ir::{
+        condcodes::IntCC, types::I64, AbiParam, Block, InstBuilder, MemFlags, Signature, StackSlot,
+        StackSlotData, StackSlotKind, TrapCode, Value,
+    },
+    isa::CallConv,
+    settings::{self, Configurable},
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, FuncId, Module};
+
+use pijama_ast::{BinOp, Location, Primitive, UnOp};
+
+use crate::{
+    lir::{Pattern, Term},
+    machine::RuntimeError,
+};
+
+/// The operators computed by the arithmetic of the machine, identified by their position.
+pub(crate) const ARITHMETIC: [BinOp; 7] = [
+    BinOp::Add,
+    BinOp::Sub,
+    BinOp::Mul,
+    BinOp::Div,
+    BinOp::Rem,
+    BinOp::Shr,
+    BinOp::Shl,
+];
+
+/// Returns `true` if `term` can be compiled to native code.
+pub fn supports(term: &Term) -> bool {
+    let callbacks = Callbacks {
+        binary: core::ptr::null(),
+        negate: core::ptr::null(),
+        print: core::ptr::null(),
+    };
+    compile(term, callbacks).is_some()
+}
+
+/// The functions of the host called by the compiled code.
+pub(crate) struct Callbacks {
+    /// Computes the operator in [`ARITHMETIC`] with the given position on two integers, writing
+    /// the result through its last argument. Returns zero if the operation overflows.
+    ///
+    /// Its signature is `extern "C" fn(i64, i64, i64, *mut i64) -> i64`.
+    pub(crate) binary: *const u8,
+    /// Negates an integer like `binary`, with signature `extern "C" fn(i64, *mut i64) -> i64`.
+    pub(crate) negate: *const u8,
+    /// Prints an integer, with signature `extern "C" fn(*mut Context, i64) -> i64`. Returns zero
+    /// if the output cannot be written.
+    pub(crate) print: *const u8,
+}
+
+/// The state shared by the compiled code and the host.
+#[repr(C)]
+pub(crate) struct Context {
+    /// Zero, or one more than the position of the error that stopped the program.
+    error: i64,
+    /// The pointer given to [`Compiled::run`].
+    pub(crate) host: *mut u8,
+}
+
+/// A program compiled to native code.
+pub(crate) struct Compiled {
+    module: Option<JITModule>,
+    main: *const u8,
+    /// The errors the program can raise, identified by their position.
+    errors: Vec<RuntimeError>,
+}
+
+impl Compiled {
+    /// Runs the program, returning its value or the error that stopped it.
+    ///
+    /// # Safety
+    ///
+    /// The callbacks the program was compiled with must be valid for `host`.
+    pub(crate) unsafe fn run(&self, host: *mut u8) -> Result<i64, RuntimeError> {
+        let main: extern "C" fn(*mut Context) -> i64 = mem::transmute(self.main);
+        let mut context = Context { error: 0, host };
+        let value = main(&mut context);
+        match context.error {
+            0 => Ok(value),
+            site => Err(self.errors[site as usize - 1].clone()),
+        }
+    }
+}
+
+impl Drop for Compiled {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // The code of the program is not used after this.
+            unsafe { module.free_memory() }
+        }
+    }
+}
+
+/// Compiles `term`, returning `None` if it is not supported.
+pub(crate) fn compile(term: &Term, callbacks: Callbacks) -> Option<Compiled> {
+    let mut flags = settings::builder();
+    flags.set("opt_level", "speed").ok()?;
+    // Tail calls rely on frame pointers.
+    flags.set("preserve_frame_pointers", "true").ok()?;
+    let isa = cranelift_native::builder()
+        .ok()?
+        .finish(settings::Flags::new(flags))
+        .ok()?;
+    if isa.pointer_type() != I64 {
+        return None;
+    }
+    let module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+    let mut compiler = Compiler {
+        module,
+        callbacks,
+        errors: Vec::new(),
+        functions: Vec::new(),
+        jobs: Vec::new(),
+    };
+    match compiler.program(term) {
+        Some(main) => Some(Compiled {
+            module: Some(compiler.module),
+            main,
+            errors: compiler.errors,
+        }),
+        None => {
+            // Nothing was finalized, so no code can be running.
+            unsafe { compiler.module.free_memory() };
+            None
+        }
+    }
+}
+
+/// What a variable refers to in the code being compiled.
+#[derive(Debug, Clone, Copy)]
+enum Binding {
+    /// A function defined in the program, identified by its position.
+    Function(usize),
+    /// An evaluated integer.
+    Int(Value),
+    /// A parameter, with its tag and two words, and whether it refers to the stack frame of the
+    /// function being compiled.
+    ///
+    /// The tag is `0` for a value, held by the first word. It is `1` for an argument that is not
+    /// evaluated yet, whose first word is the function computing it and whose second word is its
+    /// environment. Otherwise, the argument raised the error with position `tag - 2`.
+    Arg([Value; 3], bool),
+    /// The parameter with the given position in the environment of an argument.
+    Captured(usize),
+    /// A variable that cannot be used by compiled code.
+    Unavailable,
+}
+
+/// A function defined in the program.
+struct Function {
+    id: FuncId,
+    arity: usize,
+    /// Whether the function and the functions it calls never print anything.
+    pure: bool,
+}
+
+/// Code that must be compiled after the current function.
+enum Job {
+    /// A function defined in the program, with its chain of closures and the scope where its
+    /// environment is resolved.
+    Function {
+        id: FuncId,
+        chain: Arc<Term>,
+        scope: Vec<Binding>,
+    },
+    /// An argument that is evaluated when it is used.
+    Thunk {
+        id: FuncId,
+        term: Arc<Term>,
+        scope: Vec<Binding>,
+        loc: Location,
+    },
+}
+
+struct Compiler {
+    module: JITModule,
+    callbacks: Callbacks,
+    errors: Vec<RuntimeError>,
+    functions: Vec<Function>,
+    jobs: Vec<Job>,
+}
+
+impl Compiler {
+    /// Compiles the whole program, returning the address of its main function.
+    fn program(&mut self, term: &Term) -> Option<*const u8> {
+        let call_conv = self.module.isa().default_call_conv();
+        let main_signature = signature(call_conv, 1, 1);
+        let main = self
+            .module
+            .declare_anonymous_function(&main_signature)
+            .ok()?;
+        self.function(main, main_signature, false, |emitter, _| {
+            emitter.term(term, &mut Vec::new(), Location::new(0, 0), false)
+        })?;
+        while let Some(job) = self.jobs.pop() {
+            match job {
+                Job::Function { id, chain, scope } => {
+                    let arity = arity(&chain);
+                    let signature = signature(CallConv::Tail, 1 + 3 * arity, 1);
+                    self.function(id, signature, true, |emitter, params| {
+                        let mut scope = scope;
+                        let mut code = &chain;
+                        let mut params = params.chunks(3);
+                        while let Term::Closure(body, env) = &**code {
+                            let param = params.next()?;
+                            let param = Binding::Arg([param[0], param[1], param[2]], false);
+                            scope = closure_scope(param, env, &scope)?;
+                            code = body;
+                        }
+                        emitter.term(code, &mut scope, Location::new(0, 0), true)
+                    })?;
+                }
+                Job::Thunk {
+                    id,
+                    term,
+                    scope,
+                    loc,
+                } => {
+                    let signature = signature(CallConv::Tail, 2, 1);
+                    self.function(id, signature, true, |emitter, params| {
+                        let mut scope = scope
+                            .into_iter()
+                            .map(|binding| match binding {
+                                Binding::Captured(index) => {
+                                    let offset = 24 * index as i32;
+                                    let param = [0, 8, 16].map(|word| {
+                                        emitter.builder.ins().load(
+                                            I64,
+                                            MemFlags::trusted(),
+                                            params[0],
+                                            offset + word,
+                                        )
+                                    });
+                                    Binding::Arg(param, false)
+                                }
+                                binding => binding,
+                            })
+                            .collect();
+                        emitter.term(&term, &mut scope, loc, true)
+                    })?;
+                }
+            }
+        }
+        self.module.finalize_definitions().ok()?;
+        Some(self.module.get_finalized_function(main))
+    }
+
+    /// Defines the function `id` returning the value computed by `body`, which receives the
+    /// parameters of the function but its context.
+    ///
+    /// Tail calls are only emitted if `tail_calls` is `true`, which requires the function to use
+    /// the tail calling convention.
+    fn function(
+        &mut self,
+        id: FuncId,
+        signature: Signature,
+        tail_calls: bool,
+        body: impl FnOnce(&mut Emitter, &[Value]) -> Option<Value>,
+    ) -> Option<()> {
+        let mut context = self.module.make_context();
+        context.func.signature = signature;
+        let mut builder_context = FunctionBuilderContext::new();
+        let mut builder = FunctionBuilder::new(&mut context.func, &mut builder_context);
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        let params = builder.block_params(entry).to_vec();
+        let mut emitter = Emitter {
+            compiler: self,
+            builder,
+            context: params[0],
+            tail_calls,
+            deferred: None,
+            scratch: None,
+        };
+        let value = body(&mut emitter, &params[1..])?;
+        emitter.builder.ins().return_(&[value]);
+        emitter.builder.seal_all_blocks();
+        emitter.builder.finalize();
+        self.module.define_function(id, &mut context).ok()?;
+        Some(())
+    }
+
+    /// Declares the function defined by `value` in `scope`, returning `None` if it is not
+    /// supported.
+    ///
+    /// Recursive functions are the fixed point of a closure whose environment only holds
+    /// functions. Other functions can use the functions in `scope`.
+    fn define(&mut self, value: &Term, scope: &[Binding]) -> Option<Binding> {
+        let index = self.functions.len();
+        let (chain, env, inner) = match value {
+            Term::Fix(function) => match &**function {
+                Term::Closure(chain, env) => {
+                    let inner = closure_scope(Binding::Function(index), env, scope)?;
+                    (Arc::clone(chain), env, inner)
+                }
+                _ => return None,
+            },
+            Term::Closure(_, env) => (Arc::new(value.clone()), env, scope.to_vec()),
+            _ => return None,
+        };
+        // The environment holds the functions called by this one.
+        let pure = !prints(&chain)
+            && env.iter().all(|term| match term.as_ref() {
+                Term::Var(index) => match scope.get(*index) {
+                    Some(Binding::Function(function)) => self.functions[*function].pure,
+                    _ => true,
+                },
+                _ => false,
+            });
+        let scope = inner
+            .into_iter()
+            .map(|binding| match binding {
+                Binding::Function(_) => binding,
+                _ => Binding::Unavailable,
+            })
+            .collect();
+        let arity = arity(&chain);
+        if arity == 0 {
+            return None;
+        }
+        let signature = signature(CallConv::Tail, 1 + 3 * arity, 1);
+        let id = self.module.declare_anonymous_function(&signature).ok()?;
+        self.functions.push(Function { id, arity, pure });
+        self.jobs.push(Job::Function { id, chain, scope });
+        Some(Binding::Function(index))
+    }
+
+    /// Returns `true` if evaluating `term` in `scope` cannot print anything.
+    fn pure(&self, term: &Term, scope: &[Binding]) -> bool {
+        match term {
+            Term::Lit(_) => true,
+            Term::Var(index) => match scope.get(*index) {
+                Some(Binding::Function(function)) => self.functions[*function].pure,
+                _ => true,
+            },
+            Term::UnaryOp(_, term) | Term::Assert(_, term) | Term::Loc(_, term) => {
+                self.pure(term, scope)
+            }
+            Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) => {
+                self.pure(t1, scope) && self.pure(t2, scope)
+            }
+            Term::Cond(t1, t2, t3) => {
+                self.pure(t1, scope) && self.pure(t2, scope) && self.pure(t3, scope)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Emits the code of a single function.
+struct Emitter<'c, 'f> {
+    compiler: &'c mut Compiler,
+    builder: FunctionBuilder<'f>,
+    /// The pointer to the [`Context`] of the program.
+    context: Value,
+    tail_calls: bool,
+    /// The block receiving the tag of the errors raised while computing an argument before the
+    /// call, if one is being computed.
+    deferred: Option<Block>,
+    /// The stack slot receiving the results of the callbacks.
+    scratch: Option<StackSlot>,
+}
+
+impl<'c, 'f> Emitter<'c, 'f> {
+    /// Emits the code computing `term` in `scope`, where `loc` is the location of the innermost
+    /// located term. Returns `None` if `term` is not supported.
+    ///
+    /// The variables of `scope` are replaced by their values once they are evaluated.
+    fn term(
+        &mut self,
+        term: &Term,
+        scope: &mut Vec<Binding>,
+        loc: Location,
+        tail: bool,
+    ) -> Option<Value> {
+        match term {
+            Term::Lit(n) => Some(self.int(i64::try_from(*n).ok()?)),
+            Term::Var(index) => self.var(*index, scope),
+            Term::Loc(loc, term) => self.term(term, scope, *loc, tail),
+            Term::UnaryOp(op, term) => {
+                let n = self.term(term, scope, loc, false)?;
+                self.unary(*op, n, loc)
+            }
+            Term::BinaryOp(op @ (BinOp::And | BinOp::Or), t1, t2) => {
+                let n1 = self.term(t1, scope, loc, false)?;
+                let rhs = self.builder.create_block();
+                let merge = self.builder.create_block();
+                self.builder.append_block_param(merge, I64);
+                if *op == BinOp::And {
+                    self.builder.ins().brif(n1, rhs, &[], merge, &[n1]);
+                } else {
+                    self.builder.ins().brif(n1, merge, &[n1], rhs, &[]);
+                }
+                self.builder.switch_to_block(rhs);
+                let saved = scope.clone();
+                let n2 = self.term(t2, scope, loc, tail)?;
+                *scope = saved;
+                self.builder.ins().jump(merge, &[n2]);
+                self.builder.switch_to_block(merge);
+                Some(self.builder.block_params(merge)[0])
+            }
+            Term::BinaryOp(op, t1, t2) => {
+                let n1 = self.term(t1, scope, loc, false)?;
+                let n2 = self.term(t2, scope, loc, false)?;
+                self.binary(*op, n1, n2, loc)
+            }
+            Term::Cond(t1, t2, t3) => {
+                let cond = self.term(t1, scope, loc, false)?;
+                let then = self.builder.create_block();
+                let otherwise = self.builder.create_block();
+                let merge = self.builder.create_block();
+                self.builder.append_block_param(merge, I64);
+                self.builder.ins().brif(cond, then, &[], otherwise, &[]);
+                for (block, term) in [(then, t2), (otherwise, t3)] {
+                    self.builder.switch_to_block(block);
+                    let saved = scope.clone();
+                    let value = self.term(term, scope, loc, tail)?;
+                    *scope = saved;
+                    self.builder.ins().jump(merge, &[value]);
+                }
+                self.builder.switch_to_block(merge);
+                Some(self.builder.block_params(merge)[0])
+            }
+            Term::Assert(loc, term) => {
+                let cond = self.term(term, scope, *loc, false)?;
+                let failed = self.builder.ins().icmp_imm(IntCC::Equal, cond, 0);
+                self.check(failed, RuntimeError::AssertionFailed(*loc));
+                Some(self.int(0))
+            }
+            Term::Match(scrutinee, arms) => match (&**scrutinee, arms.as_slice()) {
+                (Term::Variant(0, _, fields), [(Pattern::Variant(0, 1), body)])
+                    if fields.len() == 1 =>
+                {
+                    let value = &fields[0];
+                    let binding = if is_function(value) {
+                        self.compiler.define(value, scope)?
+                    } else {
+                        Binding::Int(self.term(value, scope, loc, false)?)
+                    };
+                    self.body(binding, body, scope, loc, tail)
+                }
+                _ => self.matching(scrutinee, arms, scope, loc, tail),
+            },
+            Term::App(function, arg) if matches!(**function, Term::Closure(..)) => {
+                let binding = if is_function(arg) {
+                    self.compiler.define(arg, scope)?
+                } else {
+                    let (arg, local) = self.arg(arg, scope, loc)?;
+                    Binding::Arg(arg, local)
+                };
+                self.body(binding, function, scope, loc, tail)
+            }
+            Term::App(..) => self.call(term, scope, loc, tail),
+            _ => None,
+        }
+    }
+
+    /// Emits the code of the closure `body` with its parameter bound to `binding`.
+    fn body(
+        &mut self,
+        binding: Binding,
+        body: &Term,
+        scope: &[Binding],
+        loc: Location,
+        tail: bool,
+    ) -> Option<Value> {
+        match body {
+            Term::Closure(code, env) => {
+                let mut scope = closure_scope(binding, env, scope)?;
+                self.term(code, &mut scope, loc, tail)
+            }
+            _ => None,
+        }
+    }
+
+    /// Emits the code of a match over integers.
+    fn matching(
+        &mut self,
+        scrutinee: &Term,
+        arms: &[(Pattern, Arc<Term>)],
+        scope: &mut Vec<Binding>,
+        loc: Location,
+        tail: bool,
+    ) -> Option<Value> {
+        let value = self.term(scrutinee, scope, loc, false)?;
+        let merge = self.builder.create_block();
+        self.builder.append_block_param(merge, I64);
+        let saved = scope.clone();
+        let mut exhaustive = false;
+        for (pattern, body) in arms {
+            match pattern {
+                Pattern::Lit(n) => {
+                    let arm = self.builder.create_block();
+                    let next = self.builder.create_block();
+                    let n = i64::try_from(*n).ok()?;
+                    let matched = self.builder.ins().icmp_imm(IntCC::Equal, value, n);
+                    self.builder.ins().brif(matched, arm, &[], next, &[]);
+                    self.builder.switch_to_block(arm);
+                    let result = self.term(body, scope, loc, tail)?;
+                    *scope = saved.clone();
+                    self.builder.ins().jump(merge, &[result]);
+                    self.builder.switch_to_block(next);
+                }
+                Pattern::Wildcard => {
+                    let result = self.term(body, scope, loc, tail)?;
+                    *scope = saved;
+                    self.builder.ins().jump(merge, &[result]);
+                    exhaustive = true;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+        if !exhaustive {
+            // The type checker only accepts exhaustive matches.
+            self.builder.ins().trap(TrapCode::unwrap_user(1));
+        }
+        self.builder.switch_to_block(merge);
+        Some(self.builder.block_params(merge)[0])
+    }
+
+    /// Emits the code of an application to all the parameters of a function or to `print`.
+    fn call(
+        &mut self,
+        term: &Term,
+        scope: &mut Vec<Binding>,
+        loc: Location,
+        tail: bool,
+    ) -> Option<Value> {
+        let mut args = Vec::new();
+        let mut head = term;
+        // Each application to one more argument keeps its own location.
+        while let Term::App(function, arg) = unlocated(head) {
+            args.push(arg);
+            head = function;
+        }
+        let head = unlocated(head);
+        args.reverse();
+        match head {
+            Term::PrimFn(Primitive::Print) if args.len() == 1 => {
+                let n = self.term(args[0], scope, loc, false)?;
+                let call_conv = self.compiler.module.isa().default_call_conv();
+                let signature = self.builder.import_signature(signature(call_conv, 2, 1));
+                let callee = self.int(self.compiler.callbacks.print as i64);
+                let call = self
+                    .builder
+                    .ins()
+                    .call_indirect(signature, callee, &[self.context, n]);
+                let ok = self.builder.inst_results(call)[0];
+                let failed = self.builder.ins().icmp_imm(IntCC::Equal, ok, 0);
+                self.check(failed, RuntimeError::OutputFailed(loc));
+                Some(self.int(0))
+            }
+            Term::Var(index) => {
+                let function = match scope.get(*index)? {
+                    Binding::Function(function) => &self.compiler.functions[*function],
+                    _ => return None,
+                };
+                if function.arity != args.len() {
+                    return None;
+                }
+                let id = function.id;
+                let mut values = vec![self.context];
+                let mut local = false;
+                for arg in args {
+                    let (arg, arg_local) = self.arg(arg, scope, loc)?;
+                    values.extend_from_slice(&arg);
+                    local |= arg_local;
+                }
+                let function = self
+                    .compiler
+                    .module
+                    .declare_func_in_func(id, self.builder.func);
+                if tail && self.tail_calls && !local {
+                    self.builder.ins().return_call(function, &values);
+                    // The rest of the code is unreachable.
+                    let block = self.builder.create_block();
+                    self.builder.switch_to_block(block);
+                    Some(self.int(0))
+                } else {
+                    let call = self.builder.ins().call(function, &values);
+                    let result = self.builder.inst_results(call)[0];
+                    self.propagate();
+                    Some(result)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Emits the code passing `term` as an argument, returning its tag and words and whether
+    /// they refer to the stack frame of the current function.
+    fn arg(
+        &mut self,
+        term: &Term,
+        scope: &mut Vec<Binding>,
+        loc: Location,
+    ) -> Option<([Value; 3], bool)> {
+        match term {
+            Term::Var(index) => match *scope.get(*index)? {
+                Binding::Int(n) => Some(([self.int(0), n, self.int(0)], false)),
+                Binding::Arg(arg, local) => Some((arg, local)),
+                _ => None,
+            },
+            Term::Lit(n) => {
+                let n = self.int(i64::try_from(*n).ok()?);
+                Some(([self.int(0), n, self.int(0)], false))
+            }
+            term if simple(term, scope) => {
+                let poison = self.builder.create_block();
+                self.builder.append_block_param(poison, I64);
+                let merge = self.builder.create_block();
+                self.builder.append_block_param(merge, I64);
+                self.builder.append_block_param(merge, I64);
+                let deferred = self.deferred.replace(poison);
+                let value = self.term(term, scope, loc, false);
+                self.deferred = deferred;
+                let value = value?;
+                let tag = self.int(0);
+                self.builder.ins().jump(merge, &[tag, value]);
+                self.builder.switch_to_block(poison);
+                let tag = self.builder.block_params(poison)[0];
+                let value = self.int(0);
+                self.builder.ins().jump(merge, &[tag, value]);
+                self.builder.switch_to_block(merge);
+                let params = self.builder.block_params(merge);
+                let (tag, value) = (params[0], params[1]);
+                Some(([tag, value, self.int(0)], false))
+            }
+            term if self.compiler.pure(term, scope) => {
+                let mut captured = Vec::new();
+                let thunk_scope = scope
+                    .iter()
+                    .map(|binding| match *binding {
+                        Binding::Int(n) => {
+                            captured.push([self.int(0), n, self.int(0)]);
+                            Binding::Captured(captured.len() - 1)
+                        }
+                        Binding::Arg(arg, _) => {
+                            captured.push(arg);
+                            Binding::Captured(captured.len() - 1)
+                        }
+                        Binding::Function(_) => *binding,
+                        _ => Binding::Unavailable,
+                    })
+                    .collect();
+                let signature = signature(CallConv::Tail, 2, 1);
+                let id = self
+                    .compiler
+                    .module
+                    .declare_anonymous_function(&signature)
+                    .ok()?;
+                self.compiler.jobs.push(Job::Thunk {
+                    id,
+                    term: Arc::new(term.clone()),
+                    scope: thunk_scope,
+                    loc,
+                });
+                let function = self
+                    .compiler
+                    .module
+                    .declare_func_in_func(id, self.builder.func);
+                let function = self.builder.ins().func_addr(I64, function);
+                if captured.is_empty() {
+                    return Some(([self.int(1), function, self.int(0)], false));
+                }
+                let size = 24 * captured.len() as u32;
+                let slot = self.builder.create_sized_stack_slot(StackSlotData::new(
+                    StackSlotKind::ExplicitSlot,
+                    size,
+                    3,
+                ));
+                for (index, arg) in captured.iter().enumerate() {
+                    for (word, value) in arg.iter().enumerate() {
+                        let offset = 24 * index as i32 + 8 * word as i32;
+                        self.builder.ins().stack_store(*value, slot, offset);
+                    }
+                }
+                let env = self.builder.ins().stack_addr(I64, slot, 0);
+                Some(([self.int(1), function, env], true))
+            }
+            _ => None,
+        }
+    }
+
+    /// Emits the code reading the variable `index`, evaluating it if it is a parameter.
+    fn var(&mut self, index: usize, scope: &mut [Binding]) -> Option<Value> {
+        match *scope.get(index)? {
+            Binding::Int(n) => Some(n),
+            Binding::Arg(arg, _) => {
+                let n = self.force(arg);
+                scope[index] = Binding::Int(n);
+                Some(n)
+            }
+            _ => None,
+        }
+    }
+
+    /// Emits the code evaluating a parameter.
+    fn force(&mut self, [tag, word1, word2]: [Value; 3]) -> Value {
+        let value = self.builder.create_block();
+        let other = self.builder.create_block();
+        let thunk = self.builder.create_block();
+        let poison = self.builder.create_block();
+        let merge = self.builder.create_block();
+        self.builder.append_block_param(merge, I64);
+        self.builder.ins().brif(tag, other, &[], value, &[]);
+        self.builder.switch_to_block(value);
+        self.builder.ins().jump(merge, &[word1]);
+        self.builder.switch_to_block(other);
+        let is_thunk = self.builder.ins().icmp_imm(IntCC::Equal, tag, 1);
+        self.builder.ins().brif(is_thunk, thunk, &[], poison, &[]);
+        self.builder.switch_to_block(thunk);
+        let signature = self
+            .builder
+            .import_signature(signature(CallConv::Tail, 2, 1));
+        let call = self
+            .builder
+            .ins()
+            .call_indirect(signature, word1, &[self.context, word2]);
+        let result = self.builder.inst_results(call)[0];
+        self.propagate();
+        self.builder.ins().jump(merge, &[result]);
+        self.builder.switch_to_block(poison);
+        let site = self.builder.ins().iadd_imm(tag, -1);
+        self.builder
+            .ins()
+            .store(MemFlags::trusted(), site, self.context, 0);
+        let zero = self.int(0);
+        self.builder.ins().return_(&[zero]);
+        self.builder.switch_to_block(merge);
+        self.builder.block_params(merge)[0]
+    }
+
+    /// Emits the code of a binary operator other than `&&` and `||`.
+    fn binary(&mut self, op: BinOp, n1: Value, n2: Value, loc: Location) -> Option<Value> {
+        let cond = match op {
+            BinOp::Eq => IntCC::Equal,
+            BinOp::Neq => IntCC::NotEqual,
+            BinOp::Lt => IntCC::SignedLessThan,
+            BinOp::Gt => IntCC::SignedGreaterThan,
+            BinOp::Lte => IntCC::SignedLessThanOrEqual,
+            BinOp::Gte => IntCC::SignedGreaterThanOrEqual,
+            BinOp::BitAnd => return Some(self.builder.ins().band(n1, n2)),
+            BinOp::BitOr => return Some(self.builder.ins().bor(n1, n2)),
+            BinOp::BitXor => return Some(self.builder.ins().bxor(n1, n2)),
+            op => {
+                let index = ARITHMETIC.iter().position(|other| *other == op)?;
+                if let BinOp::Div | BinOp::Rem = op {
+                    let failed = self.builder.ins().icmp_imm(IntCC::Equal, n2, 0);
+                    self.check(failed, RuntimeError::DivisionByZero(loc));
+                }
+                let index = self.int(index as i64);
+                let callee = self.compiler.callbacks.binary;
+                return Some(self.arithmetic(callee, &[index, n1, n2], loc));
+            }
+        };
+        let result = self.builder.ins().icmp(cond, n1, n2);
+        Some(self.builder.ins().uextend(I64, result))
+    }
+
+    /// Emits the code of a unary operator.
+    fn unary(&mut self, op: UnOp, n: Value, loc: Location) -> Option<Value> {
+        match op {
+            UnOp::Neg => {
+                let callee = self.compiler.callbacks.negate;
+                Some(self.arithmetic(callee, &[n], loc))
+            }
+            UnOp::Not => {
+                let result = self.builder.ins().icmp_imm(IntCC::Equal, n, 0);
+                Some(self.builder.ins().uextend(I64, result))
+            }
+            UnOp::BitNot => Some(self.builder.ins().bnot(n)),
+        }
+    }
+
+    /// Emits the call to an arithmetic callback, raising an overflow if it fails.
+    fn arithmetic(&mut self, callee: *const u8, args: &[Value], loc: Location) -> Value {
+        let scratch = match self.scratch {
+            Some(scratch) => scratch,
+            None => {
+                let data = StackSlotData::new(StackSlotKind::ExplicitSlot, 8, 3);
+                *self
+                    .scratch
+                    .insert(self.builder.create_sized_stack_slot(data))
+            }
+        };
+        let call_conv = self.compiler.module.isa().default_call_conv();
+        let signature = self
+            .builder
+            .import_signature(signature(call_conv, args.len() + 1, 1));
+        let callee = self.int(callee as i64);
+        let out = self.builder.ins().stack_addr(I64, scratch, 0);
+        let mut args = args.to_vec();
+        args.push(out);
+        let call = self.builder.ins().call_indirect(signature, callee, &args);
+        let ok = self.builder.inst_results(call)[0];
+        let failed = self.builder.ins().icmp_imm(IntCC::Equal, ok, 0);
+        self.check(failed, RuntimeError::Overflow(loc));
+        self.builder.ins().stack_load(I64, scratch, 0)
+    }
+
+    /// Emits the code raising `error` if `failed` is not zero.
+    fn check(&mut self, failed: Value, error: RuntimeError) {
+        let raise = self.builder.create_block();
+        let next = self.builder.create_block();
+        self.builder.ins().brif(failed, raise, &[], next, &[]);
+        self.builder.switch_to_block(raise);
+        let site = self.compiler.errors.len() as i64;
+        self.compiler.errors.push(error);
+        match self.deferred {
+            Some(poison) => {
+                let tag = self.int(site + 2);
+                self.builder.ins().jump(poison, &[tag]);
+            }
+            None => {
+                let site = self.int(site + 1);
+                self.builder
+                    .ins()
+                    .store(MemFlags::trusted(), site, self.context, 0);
+                let zero = self.int(0);
+                self.builder.ins().return_(&[zero]);
+            }
+        }
+        self.builder.switch_to_block(next);
+    }
+
+    /// Emits the code returning from the current function if a call raised an error.
+    fn propagate(&mut self) {
+        let error = self
+            .builder
+            .ins()
+            .load(I64, MemFlags::trusted(), self.context, 0);
+        let exit = self.builder.create_block();
+        let next = self.builder.create_block();
+        self.builder.ins().brif(error, exit, &[], next, &[]);
+        self.builder.switch_to_block(exit);
+        let zero = self.int(0);
+        self.builder.ins().return_(&[zero]);
+        self.builder.switch_to_block(next);
+    }
+
+    fn int(&mut self, n: i64) -> Value {
+        self.builder.ins().iconst(I64, n)
+    }
+}
+
+/// Returns a signature with `params` integer parameters and `returns` integer results.
+fn signature(call_conv: CallConv, params: usize, returns: usize) -> Signature {
+    let mut signature = Signature::new(call_conv);
+    signature.params = vec![AbiParam::new(I64); params];
+    signature.returns = vec![AbiParam::new(I64); returns];
+    signature
+}
+
+/// Returns the scope of the code of a closure whose parameter is `param` and whose environment
+/// `env` is resolved in `scope`.
+fn closure_scope(param: Binding, env: &[Arc<Term>], scope: &[Binding]) -> Option<Vec<Binding>> {
+    let mut result = vec![param];
+    for term in env {
+        match term.as_ref() {
+            Term::Var(index) => result.push(*scope.get(*index)?),
+            _ => return None,
+        }
+    }
+    Some(result)
+}
+
+/// Returns the number of parameters of a chain of closures.
+fn arity(mut term: &Term) -> usize {
+    let mut arity = 0;
+    while let Term::Closure(code, _) = term {
+        arity += 1;
+        term = code;
+    }
+    arity
+}
+
+/// Returns `term` without the locations around it.
+fn unlocated(mut term: &Term) -> &Term {
+    while let Term::Loc(_, inner) = term {
+        term = inner;
+    }
+    term
+}
+
+/// Returns `true` if `term` defines a function.
+fn is_function(term: &Term) -> bool {
+    matches!(term, Term::Closure(..) | Term::Fix(_))
+}
+
+/// Returns `true` if `term` does not call functions and only uses variables that are evaluated.
+fn simple(term: &Term, scope: &[Binding]) -> bool {
+    match term {
+        Term::Lit(_) => true,
+        Term::Var(index) => matches!(scope.get(*index), Some(Binding::Int(_))),
+        Term::UnaryOp(_, term) | Term::Assert(_, term) | Term::Loc(_, term) => simple(term, scope),
+        Term::BinaryOp(_, t1, t2) => simple(t1, scope) && simple(t2, scope),
+        Term::Cond(t1, t2, t3) => simple(t1, scope) && simple(t2, scope) && simple(t3, scope),
+        _ => false,
+    }
+}
+
+/// Returns `true` if `term` uses `print`.
+fn prints(term: &Term) -> bool {
+    match term {
+        Term::PrimFn(Primitive::Print) => true,
+        Term::List(terms) | Term::Native(_, terms) | Term::Variant(_, _, terms) => {
+            terms.iter().any(|term| prints(term))
+        }
+        Term::Closure(code, env) => prints(code) || env.iter().any(|term| prints(term)),
+        Term::Abs(term)
+        | Term::UnaryOp(_, term)
+        | Term::Fix(term)
+        | Term::Ref(term)
+        | Term::Deref(term)
+        | Term::Assert(_, term)
+        | Term::Loc(_, term) => prints(term),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Assign(t1, t2) => {
+            prints(t1) || prints(t2)
+        }
+        Term::Cond(t1, t2, t3) => prints(t1) || prints(t2) || prints(t3),
+        Term::Match(term, arms) => prints(term) || arms.iter().any(|(_, body)| prints(body)),
+        _ => false,
+    }
+}
@@ -0,0 +1,284 @@
+//! A tokenizer for syntax highlighting.
+//!
+//! The [`tokens`] function splits the source code in tokens without parsing it, so editors can
+//! highlight programs that have syntax errors, like the ones being written. Every character that
+//! is not a space or a line break is part of exactly one token, and the characters that cannot
+//! start a token are returned as [`Token::Unknown`].
+//!
+//! Interpolated strings are split in the [`Token::String`]s around each interpolation, the braces
+//! of the interpolation and the tokens of the code inside them.
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use unicode_xid::UnicodeXID;
+
+use pijama_ast::{Located, Location, Primitive};
+
+use crate::parser::name::KEYWORDS;
+
+/// The kind of a token.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Token {
+    /// A reserved word, like `fn` or `match`.
+    Keyword,
+    /// The name of a binding, a function, an enum, a variant or a module.
+    Name,
+    /// The name of a built-in function, like `print`, even where the program binds it again.
+    Primitive,
+    /// The name of a basic type, like `Int`.
+    Type,
+    /// The literals `true`, `false` and `unit`.
+    Constant,
+    /// An integer or floating point number.
+    Number,
+    /// A character literal, with its quotes.
+    Char,
+    /// A string literal, or the part of an interpolated string before, between or after its
+    /// interpolations.
+    String,
+    /// A unary or binary operator, or the `=` of a binding.
+    Operator,
+    /// Brackets, braces, commas, colons, `..` and `->`.
+    Punctuation,
+    /// A line or block comment.
+    Comment,
+    /// A line of a doc comment.
+    DocComment,
+    /// A character that cannot start any other token.
+    Unknown,
+}
+
+/// Operators and punctuation with more than one character, which are matched before the ones
+/// with a single character.
+const SYMBOLS: &[(&str, Token)] = &[
+    ("|>", Token::Operator),
+    ("&&", Token::Operator),
+    ("||", Token::Operator),
+    ("==", Token::Operator),
+    ("!=", Token::Operator),
+    ("<=", Token::Operator),
+    (">=", Token::Operator),
+    ("<<", Token::Operator),
+    (">>", Token::Operator),
+    ("->", Token::Punctuation),
+    ("..", Token::Punctuation),
+    ("::", Token::Punctuation),
+];
+
+/// Returns the tokens of `input` in order.
+///
+/// This function never fails, code that is not valid is still split in tokens.
+pub fn tokens(input: &str) -> impl Iterator<Item = Located<Token>> {
+    let mut lexer = Lexer {
+        input,
+        pos: 0,
+        tokens: Vec::new(),
+    };
+    lexer.code(false);
+    lexer.tokens.into_iter()
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+    tokens: Vec<Located<Token>>,
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) {
+        self.pos += self.peek().map_or(0, char::len_utf8);
+    }
+
+    /// Consumes characters while `cond` holds for them.
+    fn bump_while(&mut self, cond: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&cond) {
+            self.bump();
+        }
+    }
+
+    fn push(&mut self, token: Token, start: usize) {
+        self.tokens
+            .push(Location::new(start, self.pos).with_content(token));
+    }
+
+    /// Splits code in tokens until the end of the input or, if `interpolation` is set, until the
+    /// closing brace of an interpolation, which is not consumed.
+    fn code(&mut self, interpolation: bool) {
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            let rest = self.rest();
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '}' && interpolation {
+                return;
+            } else if rest.starts_with("##") {
+                self.bump_while(|c| c != '\n');
+                self.push(Token::DocComment, start);
+            } else if c == '#' {
+                self.bump_while(|c| c != '\n');
+                self.push(Token::Comment, start);
+            } else if rest.starts_with("/*") {
+                self.block_comment();
+                self.push(Token::Comment, start);
+            } else if c == '"' {
+                self.string();
+            } else if c == '\'' {
+                self.char();
+                self.push(Token::Char, start);
+            } else if c.is_ascii_digit() {
+                self.number();
+                self.push(Token::Number, start);
+            } else if c.is_xid_start() || c == '_' {
+                self.bump_while(UnicodeXID::is_xid_continue);
+                let token = word(&self.input[start..self.pos]);
+                self.push(token, start);
+            } else if let Some((symbol, token)) =
+                SYMBOLS.iter().find(|(symbol, _)| rest.starts_with(symbol))
+            {
+                self.pos += symbol.len();
+                self.push(*token, start);
+            } else {
+                self.bump();
+                let token = match c {
+                    '+' | '-' | '*' | '/' | '%' | '&' | '|' | '^' | '<' | '>' | '=' | '!' | '~' => {
+                        Token::Operator
+                    }
+                    '(' | ')' | '[' | ']' | '{' | '}' | ',' | ':' => Token::Punctuation,
+                    _ => Token::Unknown,
+                };
+                self.push(token, start);
+            }
+        }
+    }
+
+    /// Consumes a block comment, including the block comments inside it.
+    fn block_comment(&mut self) {
+        let mut depth = 0;
+        while !self.rest().is_empty() {
+            if self.rest().starts_with("/*") {
+                depth += 1;
+                self.pos += 2;
+            } else if self.rest().starts_with("*/") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return;
+                }
+            } else {
+                self.bump();
+            }
+        }
+    }
+
+    /// Splits a string in tokens, which ends at its closing quote or at the end of the line if it
+    /// is not closed.
+    fn string(&mut self) {
+        let mut start = self.pos;
+        self.bump();
+        while let Some(c) = self.peek() {
+            match c {
+                '\n' => break,
+                '"' => {
+                    self.bump();
+                    break;
+                }
+                '\\' => {
+                    self.bump();
+                    if self.peek() != Some('\n') {
+                        self.bump();
+                    }
+                }
+                '{' => {
+                    if start < self.pos {
+                        self.push(Token::String, start);
+                    }
+                    let brace = self.pos;
+                    self.bump();
+                    self.push(Token::Punctuation, brace);
+                    self.code(true);
+                    if self.peek() != Some('}') {
+                        return;
+                    }
+                    let brace = self.pos;
+                    self.bump();
+                    self.push(Token::Punctuation, brace);
+                    start = self.pos;
+                }
+                _ => self.bump(),
+            }
+        }
+        if start < self.pos {
+            self.push(Token::String, start);
+        }
+    }
+
+    /// Consumes a character literal, which might not be closed.
+    fn char(&mut self) {
+        self.bump();
+        match self.peek() {
+            Some('\\') => {
+                self.bump();
+                if self.peek() != Some('\n') {
+                    self.bump();
+                }
+            }
+            Some('\n') | Some('\'') | None => {}
+            Some(_) => self.bump(),
+        }
+        if self.peek() == Some('\'') {
+            self.bump();
+        }
+    }
+
+    /// Consumes an integer in any base or a floating point number.
+    fn number(&mut self) {
+        let rest = self.rest();
+        if ["0x", "0o", "0b"]
+            .iter()
+            .any(|prefix| rest.starts_with(prefix))
+        {
+            self.pos += 2;
+            self.bump_while(|c| c.is_ascii_hexdigit() || c == '_');
+            return;
+        }
+        self.bump_while(|c| c.is_ascii_digit() || c == '_');
+        // A dot not followed by a digit is part of a range.
+        let mut chars = self.rest().chars();
+        if chars.next() == Some('.') && chars.next().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+            self.bump_while(|c| c.is_ascii_digit());
+        }
+        let rest = self.rest();
+        let exponent = rest
+            .strip_prefix(['e', 'E'])
+            .map(|rest| rest.strip_prefix(['+', '-']).unwrap_or(rest));
+        if let Some(digits) =
+            exponent.filter(|digits| digits.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            self.pos = self.input.len() - digits.len();
+            self.bump_while(|c| c.is_ascii_digit());
+        }
+    }
+}
+
+/// Returns the kind of a word, which can be a keyword, a name or a primitive.
+fn word(word: &str) -> Token {
+    match word {
+        "true" | "false" | "unit" => Token::Constant,
+        "Bool" | "Int" | "Float" | "Unit" | "Char" | "String" => Token::Type,
+        word if KEYWORDS.contains(&word) => Token::Keyword,
+        word if Primitive::from_name(word).is_some() => Token::Primitive,
+        _ => Token::Name,
+    }
+}
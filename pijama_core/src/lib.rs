@@ -1,5 +1,16 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod bytecode;
+pub mod codegen;
+#[cfg(feature = "jit")]
+pub mod jit;
+pub mod lex;
+pub mod lint;
 pub mod lir;
 pub mod machine;
 pub mod mir;
 pub mod parser;
+mod stack;
 pub mod ty;
@@ -0,0 +1,350 @@
+//! Lints over the MIR.
+//!
+//! Lints report code that is valid but probably not what the user meant. Unlike errors, warnings
+//! do not stop the compilation.
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::{BinOp, FileId, Int, IntWidth, Literal, Located, Location, Name, Pattern, UnOp};
+
+use crate::{
+    mir::{
+        visit::{Function, Visitor},
+        LetKind, Term,
+    },
+    ty::{usefulness, Ty},
+};
+
+/// A warning found while linting a term.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Warning {
+    /// Variant used when an expression that is not the last one of a block has no side effects,
+    /// so its value is discarded without doing anything.
+    NoEffect(Location),
+    /// Variant used when the condition of a conditional always has the same value, so one of the
+    /// branches is never taken.
+    ConstantCondition(Location, bool),
+    /// Variant used when every value matched by the pattern of an arm is matched by a previous
+    /// arm, so the arm is never taken.
+    UnreachableArm(Location),
+    /// Variant used when the name bound by a let binding is never read.
+    UnusedBinding(Location),
+    /// Variant used when a parameter of a function is never read in its body.
+    UnusedParam(Location),
+    /// Variant used when a function is never called outside its own body.
+    UnusedFunction(Location),
+    /// Variant used when a branch of a conditional is never taken because its condition always
+    /// has the same value.
+    UnreachableBranch(Location),
+    /// Variant used when a mutable binding is assigned to itself, which does nothing.
+    SelfAssignment(Location),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Warning::NoEffect(_) => write!(f, "Expression has no effect"),
+            Warning::ConstantCondition(_, value) => write!(f, "Condition is always `{}`", value),
+            Warning::UnreachableArm(_) => write!(f, "Arm is never taken"),
+            Warning::UnusedBinding(_) => write!(f, "Binding is never used"),
+            Warning::UnusedParam(_) => write!(f, "Parameter is never used"),
+            Warning::UnusedFunction(_) => write!(f, "Function is never called"),
+            Warning::UnreachableBranch(_) => write!(f, "Branch is never taken"),
+            Warning::SelfAssignment(_) => write!(f, "Name is assigned to itself"),
+        }
+    }
+}
+
+impl Warning {
+    /// Returns the location of the warning.
+    pub fn loc(&self) -> Location {
+        match self {
+            Warning::NoEffect(loc)
+            | Warning::ConstantCondition(loc, _)
+            | Warning::UnreachableArm(loc)
+            | Warning::UnusedBinding(loc)
+            | Warning::UnusedParam(loc)
+            | Warning::UnusedFunction(loc)
+            | Warning::UnreachableBranch(loc)
+            | Warning::SelfAssignment(loc) => *loc,
+        }
+    }
+}
+
+/// Returns the warnings for `term`, in the order they appear in the source.
+///
+/// Only the code of [`FileId::MAIN`] is linted, the definitions added from other files like the
+/// prelude are not. The term must be well-typed.
+pub fn lint(term: &Located<Term<'_>>) -> Vec<Warning> {
+    let mut linter = Linter {
+        warnings: Vec::new(),
+    };
+    linter.visit_term(term);
+    let mut warnings = linter.warnings;
+    for coverage in usefulness::coverage(term) {
+        warnings.extend(
+            coverage
+                .unreachable
+                .into_iter()
+                .map(Warning::UnreachableArm),
+        );
+    }
+    warnings.retain(|warning| warning.loc().file == FileId::MAIN);
+    warnings.sort_by_key(|warning| warning.loc().start);
+    warnings
+}
+
+/// Visitor pushing the warnings of each term it visits.
+struct Linter {
+    warnings: Vec<Warning>,
+}
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for Linter {
+    fn visit_term(&mut self, term: &'t Located<Term<'a>>) {
+        if let Term::Assign(name, body) = &term.content {
+            if matches!(body.content, Term::Var(var) if var == name.content) {
+                self.warnings.push(Warning::SelfAssignment(term.loc));
+            }
+        }
+        self.super_term(term);
+    }
+
+    fn visit_abs(&mut self, param: &'t Located<Name<'a>>, ty: &'t Ty, body: &'t Located<Term<'a>>) {
+        if is_unused(param, &[body]) {
+            self.warnings.push(Warning::UnusedParam(param.loc));
+        }
+        self.super_abs(param, ty, body);
+    }
+
+    fn visit_let(
+        &mut self,
+        kind: &'t LetKind,
+        name: &'t Located<Name<'a>>,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+    ) {
+        // The constructors of the variants of an enum are bound by let bindings too, but a
+        // variant that is only matched is not unused.
+        let constructor = matches!(t1.content, Term::Constructor(_));
+        if !constructor && is_unused(name, &[t2]) {
+            self.warnings.push(if is_fn(t1) {
+                Warning::UnusedFunction(name.loc)
+            } else {
+                Warning::UnusedBinding(name.loc)
+            });
+        }
+        self.super_let(kind, name, t1, t2);
+    }
+
+    fn visit_let_rec(&mut self, fns: &'t [Function<'a>], term: &'t Located<Term<'a>>) {
+        for (index, (name, _, _)) in fns.iter().enumerate() {
+            // Calls from the other functions of the group count, but not recursive calls.
+            let mut scope: Vec<_> = fns
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != index)
+                .map(|(_, (_, _, body))| body)
+                .collect();
+            scope.push(term);
+            if is_unused(name, &scope) {
+                self.warnings.push(Warning::UnusedFunction(name.loc));
+            }
+        }
+        self.super_let_rec(fns, term);
+    }
+
+    fn visit_cond(
+        &mut self,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+        t3: &'t Located<Term<'a>>,
+    ) {
+        if let Some(value) = constant_condition(t1) {
+            self.warnings
+                .push(Warning::ConstantCondition(t1.loc, value));
+            let never = if value { t3 } else { t2 };
+            // Loops and conditionals without an `else` block have `unit` as their last branch,
+            // which is not written by the user.
+            if !matches!(never.content, Term::Lit(Literal::Unit)) {
+                self.warnings.push(Warning::UnreachableBranch(never.loc));
+            }
+        }
+        self.super_cond(t1, t2, t3);
+    }
+
+    fn visit_seq(&mut self, t1: &'t Located<Term<'a>>, t2: &'t Located<Term<'a>>) {
+        if is_pure(t1) {
+            self.warnings.push(Warning::NoEffect(t1.loc));
+        } else {
+            self.visit_term(t1);
+        }
+        self.visit_term(t2);
+    }
+}
+
+/// Returns whether the binding of `name` is never read by the terms in `scope`, where it is bound.
+///
+/// The bindings and native functions added before the program have empty locations and are never
+/// considered unused.
+fn is_unused(name: &Located<Name<'_>>, scope: &[&Located<Term<'_>>]) -> bool {
+    name.loc.start != name.loc.end && !scope.iter().any(|term| reads(term, name.content))
+}
+
+/// Returns whether `term` is a function, ignoring type ascriptions.
+fn is_fn(term: &Located<Term<'_>>) -> bool {
+    match &term.content {
+        Term::Abs(..) => true,
+        Term::Ascription(term, _) => is_fn(term),
+        _ => false,
+    }
+}
+
+/// Returns whether `term` reads the binding of `name` that is in scope where `term` starts.
+///
+/// Assignments do not read the binding, and uses of other bindings that shadow it do not count.
+fn reads(term: &Located<Term<'_>>, name: Name<'_>) -> bool {
+    match &term.content {
+        Term::Var(var) => *var == name,
+        Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) | Term::Constructor(_) => false,
+        Term::Abs(param, _, body) => param.content != name && reads(body, name),
+        Term::UnaryOp(_, t)
+        | Term::Ascription(t, _)
+        | Term::Lazy(t)
+        | Term::Enum(_, _, t)
+        | Term::Assign(_, t) => reads(t, name),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+            reads(t1, name) || reads(t2, name)
+        }
+        Term::Let(kind, bound, t1, t2) => {
+            let shadows = bound.content == name;
+            // The name of a recursive binding is already bound in its own body.
+            let rec = matches!(kind, LetKind::Rec(_));
+            (!(shadows && rec) && reads(t1, name)) || (!shadows && reads(t2, name))
+        }
+        Term::LetRec(fns, t) => {
+            !fns.iter().any(|(bound, _, _)| bound.content == name)
+                && (fns.iter().any(|(_, _, body)| reads(body, name)) || reads(t, name))
+        }
+        Term::Cond(t1, t2, t3) => reads(t1, name) || reads(t2, name) || reads(t3, name),
+        Term::List(elems) => elems.iter().any(|elem| reads(elem, name)),
+        Term::Match(t, arms) => {
+            reads(t, name)
+                || arms.iter().any(|(pattern, body)| {
+                    let shadows = match &pattern.content {
+                        Pattern::Variant(_, binders) => {
+                            binders.iter().any(|binder| binder.content == name)
+                        }
+                        _ => false,
+                    };
+                    !shadows && reads(body, name)
+                })
+        }
+    }
+}
+
+/// Returns whether evaluating `term` cannot have side effects.
+fn is_pure(term: &Located<Term<'_>>) -> bool {
+    match &term.content {
+        Term::Var(_)
+        | Term::Lit(_)
+        | Term::Abs(..)
+        | Term::Lazy(_)
+        | Term::PrimFn(_)
+        | Term::Native(..)
+        | Term::Constructor(_) => true,
+        Term::UnaryOp(_, t) | Term::Ascription(t, _) => is_pure(t),
+        Term::BinaryOp(_, t1, t2) => is_pure(t1) && is_pure(t2),
+        Term::List(elems) => elems.iter().all(is_pure),
+        _ => false,
+    }
+}
+
+/// Returns the value of `term` if it is a condition that always has the same value.
+///
+/// Only literals, comparisons between identical pure operands and operations over constant
+/// booleans and integers are considered constant.
+fn constant_condition(term: &Located<Term<'_>>) -> Option<bool> {
+    match &term.content {
+        Term::Lit(Literal::Bool(value)) => Some(*value),
+        Term::UnaryOp(UnOp::Not, t) => constant_condition(t).map(|value| !value),
+        Term::BinaryOp(op, t1, t2) if is_pure(t1) && same_term(t1, t2) => match op {
+            BinOp::Eq | BinOp::Lte | BinOp::Gte => Some(true),
+            BinOp::Neq | BinOp::Lt | BinOp::Gt => Some(false),
+            _ => None,
+        },
+        // A conjunction is false if any of its operands is, even if the other one is not
+        // constant, and a disjunction is true if any of its operands is.
+        Term::BinaryOp(op @ (BinOp::And | BinOp::Or), t1, t2) => {
+            let absorbing = *op == BinOp::Or;
+            match (constant_condition(t1), constant_condition(t2)) {
+                (Some(value), _) | (_, Some(value)) if value == absorbing => Some(absorbing),
+                (Some(value1), Some(value2)) => Some(value1 && value2),
+                _ => None,
+            }
+        }
+        Term::BinaryOp(op, t1, t2) => {
+            if let (Some(value1), Some(value2)) = (constant_condition(t1), constant_condition(t2)) {
+                return match op {
+                    BinOp::Eq => Some(value1 == value2),
+                    BinOp::Neq => Some(value1 != value2),
+                    _ => None,
+                };
+            }
+            let (n1, n2) = (constant_int(t1)?, constant_int(t2)?);
+            match op {
+                BinOp::Eq => Some(n1 == n2),
+                BinOp::Neq => Some(n1 != n2),
+                BinOp::Lt => Some(n1 < n2),
+                BinOp::Gt => Some(n1 > n2),
+                BinOp::Lte => Some(n1 <= n2),
+                BinOp::Gte => Some(n1 >= n2),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the value of `term` if it is an arithmetic operation over integer literals.
+///
+/// Operations that overflow or divide by zero are not folded. Integers that do not fit in 64
+/// bits are not folded either, since they overflow with the default width of the machine.
+fn constant_int(term: &Located<Term<'_>>) -> Option<Int> {
+    let n = match &term.content {
+        Term::Lit(Literal::Number(n)) => Some(*n),
+        Term::UnaryOp(UnOp::Neg, t) => constant_int(t)?.checked_neg(),
+        Term::BinaryOp(op, t1, t2) => {
+            let (n1, n2) = (constant_int(t1)?, constant_int(t2)?);
+            match op {
+                BinOp::Add => n1.checked_add(n2),
+                BinOp::Sub => n1.checked_sub(n2),
+                BinOp::Mul => n1.checked_mul(n2),
+                BinOp::Div => n1.checked_div(n2),
+                BinOp::Rem => n1.checked_rem(n2),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    n.filter(|n| IntWidth::I64.contains(*n))
+}
+
+/// Returns whether two terms are syntactically equal, ignoring their locations.
+///
+/// Only variables, literals and operations over them are compared, any other term is considered
+/// different from everything else.
+fn same_term(t1: &Located<Term<'_>>, t2: &Located<Term<'_>>) -> bool {
+    match (&t1.content, &t2.content) {
+        (Term::Var(name1), Term::Var(name2)) => name1 == name2,
+        (Term::Lit(lit1), Term::Lit(lit2)) => lit1 == lit2,
+        (Term::UnaryOp(op1, t1), Term::UnaryOp(op2, t2)) => op1 == op2 && same_term(t1, t2),
+        (Term::BinaryOp(op1, t1, u1), Term::BinaryOp(op2, t2, u2)) => {
+            op1 == op2 && same_term(t1, t2) && same_term(u1, u2)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,108 @@
+//! Closure conversion of nameless terms.
+//!
+//! Each abstraction is replaced by a closure with the code of its body and an environment with
+//! the variables bound outside of the abstraction that are used inside it. The code of a closure
+//! only refers to its parameter and its environment, so it can be compiled on its own and
+//! applying a closure only replaces the variables of its own code instead of the variables of
+//! every function nested inside it.
+//!
+//! The arms of matches binding some names are converted in the same way, so the fields of the
+//! scrutinee are placed in the code of the arm without traversing the terms it captured.
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::lir::Term::{self, *};
+
+/// Replaces every abstraction inside `term`, and the body of every arm of a match binding some
+/// names, by a closure.
+pub(super) fn convert(term: Term) -> Term {
+    match term {
+        Abs(body) => close(Term::take(body), 1),
+        Closure(code, env) => Closure(code, convert_all(env)),
+        UnaryOp(op, t1) => UnaryOp(op, convert_arc(t1)),
+        BinaryOp(op, t1, t2) => BinaryOp(op, convert_arc(t1), convert_arc(t2)),
+        App(t1, t2) => App(convert_arc(t1), convert_arc(t2)),
+        Cond(t1, t2, t3) => Cond(convert_arc(t1), convert_arc(t2), convert_arc(t3)),
+        Fix(t1) => Fix(convert_arc(t1)),
+        Native(index, args) => Native(index, convert_all(args)),
+        Variant(index, name, args) => Variant(index, name, convert_all(args)),
+        List(elems) => List(convert_all(elems)),
+        Match(t1, arms) => Match(
+            convert_arc(t1),
+            arms.into_iter()
+                .map(|(pattern, body)| {
+                    let body = match pattern.binders() {
+                        0 => convert(Term::take(body)),
+                        binders => close(Term::take(body), binders),
+                    };
+                    (pattern, Arc::new(body))
+                })
+                .collect(),
+        ),
+        Ref(t1) => Ref(convert_arc(t1)),
+        Deref(t1) => Deref(convert_arc(t1)),
+        Assign(t1, t2) => Assign(convert_arc(t1), convert_arc(t2)),
+        Assert(loc, t1) => Assert(loc, convert_arc(t1)),
+        Loc(loc, t1) => Loc(loc, convert_arc(t1)),
+        term @ Var(_)
+        | term @ Lit(_)
+        | term @ Float(_)
+        | term @ Char(_)
+        | term @ Str(_)
+        | term @ PrimFn(_)
+        | term @ Cell(_)
+        | term @ Chan(_) => term,
+    }
+}
+
+/// Returns a closure with the code of `body`, which is under `params` binders.
+///
+/// The variables bound outside of `body` are added to the environment of the closure, sorted by
+/// their index outside of `body`. The variable `params + k` of the code is the `k`-th term of the
+/// environment.
+fn close(body: Term, params: usize) -> Term {
+    // The closures nested inside the body are converted first, so the variables they capture are
+    // part of their environments, which belong to the body.
+    let body = Arc::new(convert(body));
+
+    let mut free = Vec::new();
+    Term::map_vars(&body, 0, &mut |index, depth| {
+        if index >= depth + params && !free.contains(&(index - depth - params)) {
+            free.push(index - depth - params);
+        }
+        None
+    });
+    free.sort_unstable();
+
+    let code = Term::map_vars(&body, 0, &mut |index, depth| {
+        if index >= depth + params {
+            let position = free.binary_search(&(index - depth - params)).unwrap();
+            Some(Arc::new(Var(depth + params + position)))
+        } else {
+            None
+        }
+    });
+    let env = free.into_iter().map(|var| Arc::new(Var(var))).collect();
+    Closure(code, env)
+}
+
+fn convert_arc(term: Arc<Term>) -> Arc<Term> {
+    Arc::new(convert(Term::take(term)))
+}
+
+fn convert_all(terms: Vec<Arc<Term>>) -> Vec<Arc<Term>> {
+    terms.into_iter().map(convert_arc).collect()
+}
+
+/// Returns the term a closure was converted from, which is under `params` binders, with the
+/// variables of its environment in place.
+pub(super) fn open(code: &Arc<Term>, env: &[Arc<Term>], params: usize) -> Arc<Term> {
+    Term::map_vars(code, 0, &mut |index, depth| {
+        if index >= depth + params {
+            // The term of the environment is placed under the binders of the closure and the
+            // binders of the code around the variable.
+            Some(Term::shift(&env[index - depth - params], depth + params, 0))
+        } else {
+            None
+        }
+    })
+}
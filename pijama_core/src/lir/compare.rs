@@ -0,0 +1,163 @@
+//! Structural comparison of nameless terms.
+//!
+//! Two terms are compared with [`Term::alpha_eq`] as the same program, without the details that
+//! change between the passes that produce them:
+//!
+//! - The locations of the terms are ignored, both the ones of located terms and the ones of
+//!   assertions.
+//! - Closures are compared as the abstractions they were converted from, so a term is equal to
+//!   itself before and after the closure conversion.
+//! - Cells and channels are equal if their indices can be renamed one to one, since the machine
+//!   numbers them in the order they are created.
+//! - Floats are equal if they have the same bits, so `NaN` is equal to itself.
+//!
+//! Variables are de Bruijn indices, so the names of the parameters are not compared either.
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::fmt;
+
+use crate::lir::{
+    closure,
+    Term::{self, *},
+};
+
+/// The first pair of subterms where two terms differ, found by [`Term::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// The subterm of the first term.
+    pub left: Arc<Term>,
+    /// The subterm of the second term.
+    pub right: Arc<Term>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` differs from `{}`", self.left, self.right)
+    }
+}
+
+impl Term {
+    /// Returns `true` if `self` and `other` are the same term, up to the differences explained in
+    /// the [`compare`](crate::lir::compare) module.
+    pub fn alpha_eq(&self, other: &Term) -> bool {
+        self.diff(other).is_none()
+    }
+
+    /// Returns the first pair of subterms of `self` and `other` that are not the same, or `None`
+    /// if the terms are equal according to [`Term::alpha_eq`].
+    ///
+    /// The subterms are traversed from left to right, parents before their children. Located
+    /// terms and closures are returned as they are compared, without their location and opened
+    /// as abstractions.
+    pub fn diff(&self, other: &Term) -> Option<Difference> {
+        Comparison::default().diff(&Arc::new(self.clone()), &Arc::new(other.clone()))
+    }
+}
+
+/// The renamings of cells and channels found while comparing two terms.
+#[derive(Default)]
+struct Comparison {
+    cells: Vec<(usize, usize)>,
+    chans: Vec<(usize, usize)>,
+}
+
+impl Comparison {
+    fn diff(&mut self, left: &Arc<Term>, right: &Arc<Term>) -> Option<Difference> {
+        let left = strip(left);
+        let right = strip(right);
+        let children = match (&*left, &*right) {
+            (Var(i1), Var(i2)) if i1 == i2 => vec![],
+            (Lit(n1), Lit(n2)) if n1 == n2 => vec![],
+            (Float(x1), Float(x2)) if x1.to_bits() == x2.to_bits() => vec![],
+            (Char(c1), Char(c2)) if c1 == c2 => vec![],
+            (Str(s1), Str(s2)) if s1 == s2 => vec![],
+            (PrimFn(p1), PrimFn(p2)) if p1 == p2 => vec![],
+            (Cell(i1), Cell(i2)) if rename(&mut self.cells, *i1, *i2) => vec![],
+            (Chan(i1), Chan(i2)) if rename(&mut self.chans, *i1, *i2) => vec![],
+            (Abs(t1), Abs(t2))
+            | (Fix(t1), Fix(t2))
+            | (Ref(t1), Ref(t2))
+            | (Deref(t1), Deref(t2))
+            | (Assert(_, t1), Assert(_, t2)) => vec![(Arc::clone(t1), Arc::clone(t2))],
+            (UnaryOp(op1, t1), UnaryOp(op2, t2)) if op1 == op2 => {
+                vec![(Arc::clone(t1), Arc::clone(t2))]
+            }
+            (BinaryOp(op1, t1, t2), BinaryOp(op2, t3, t4)) if op1 == op2 => {
+                vec![
+                    (Arc::clone(t1), Arc::clone(t3)),
+                    (Arc::clone(t2), Arc::clone(t4)),
+                ]
+            }
+            (App(t1, t2), App(t3, t4)) | (Assign(t1, t2), Assign(t3, t4)) => {
+                vec![
+                    (Arc::clone(t1), Arc::clone(t3)),
+                    (Arc::clone(t2), Arc::clone(t4)),
+                ]
+            }
+            (Cond(t1, t2, t3), Cond(t4, t5, t6)) => vec![
+                (Arc::clone(t1), Arc::clone(t4)),
+                (Arc::clone(t2), Arc::clone(t5)),
+                (Arc::clone(t3), Arc::clone(t6)),
+            ],
+            (List(args1), List(args2)) if args1.len() == args2.len() => zip(args1, args2),
+            (Native(i1, args1), Native(i2, args2)) if i1 == i2 && args1.len() == args2.len() => {
+                zip(args1, args2)
+            }
+            (Variant(i1, name1, args1), Variant(i2, name2, args2))
+                if i1 == i2 && name1 == name2 && args1.len() == args2.len() =>
+            {
+                zip(args1, args2)
+            }
+            (Match(t1, arms1), Match(t2, arms2))
+                if arms1.len() == arms2.len()
+                    && arms1.iter().zip(arms2).all(|((p1, _), (p2, _))| p1 == p2) =>
+            {
+                let mut children = vec![(Arc::clone(t1), Arc::clone(t2))];
+                children.extend(arms1.iter().zip(arms2).map(|((pattern, b1), (_, b2))| {
+                    (
+                        open_arm(pattern.binders(), b1),
+                        open_arm(pattern.binders(), b2),
+                    )
+                }));
+                children
+            }
+            _ => return Some(Difference { left, right }),
+        };
+        children
+            .iter()
+            .find_map(|(left, right)| self.diff(left, right))
+    }
+}
+
+/// Returns `term` without its locations, with a closure opened as the abstraction it was
+/// converted from.
+fn strip(term: &Arc<Term>) -> Arc<Term> {
+    match &**term {
+        Loc(_, t1) => strip(t1),
+        Closure(code, env) => Arc::new(Abs(closure::open(code, env, 1))),
+        _ => Arc::clone(term),
+    }
+}
+
+/// Returns the body of an arm binding `binders` names as it was before the closure conversion.
+fn open_arm(binders: usize, body: &Arc<Term>) -> Arc<Term> {
+    match &**body {
+        Closure(code, env) if binders > 0 => closure::open(code, env, binders),
+        _ => Arc::clone(body),
+    }
+}
+
+fn zip(terms1: &[Arc<Term>], terms2: &[Arc<Term>]) -> Vec<(Arc<Term>, Arc<Term>)> {
+    terms1.iter().cloned().zip(terms2.iter().cloned()).collect()
+}
+
+/// Returns `true` if the index `i1` of the first term can be renamed to the index `i2` of the
+/// second term, adding the renaming to `renamings` if it is new.
+fn rename(renamings: &mut Vec<(usize, usize)>, i1: usize, i2: usize) -> bool {
+    match renamings.iter().find(|(j1, j2)| *j1 == i1 || *j2 == i2) {
+        Some(&(j1, j2)) => j1 == i1 && j2 == i2,
+        None => {
+            renamings.push((i1, i2));
+            true
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Continuation-passing style transformation of nameless terms.
+//!
+//! The transformed program passes the value of each term to a continuation, a function receiving
+//! the rest of the evaluation, instead of returning it. Every application is in tail position, so
+//! the machine evaluates the program without nesting the evaluation of its subterms and deep
+//! recursion only grows the continuations.
+//!
+//! Evaluation is still call-by-name: functions receive their arguments as computations, which are
+//! functions taking a continuation, and run them each time the parameter is used. The terms bound
+//! by matches are values, and they are passed to the continuation directly.
+//!
+//! The pass is experimental: the transformed program takes several times as many steps as the
+//! original one. Both take a number of steps that grows quadratically with the depth of a
+//! recursion that passes an operation as an argument, like `sum(n - 1)`, because the parameter of
+//! each call runs the operations of all the calls around it. Binding the argument first, as in
+//! `m = n - 1`, or calling in tail position keeps the number of steps linear.
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use pijama_ast::{BinOp, Literal, Location, Primitive};
+
+use crate::lir::{
+    closure, Pattern,
+    Term::{self, *},
+};
+
+/// Transforms the closed term `term` into continuation-passing style.
+///
+/// The returned term runs the transformed term with a continuation returning its value, so it
+/// evaluates to the same value as `term`, except for functions, which take a continuation too.
+/// Natives must be applied to all their arguments, as they are in lowered programs.
+pub fn transform(term: Term) -> Term {
+    let mut cps = Cps::default();
+    let program = cps.computation(&term);
+    App(Arc::new(program), Arc::new(Abs(Arc::new(Var(0)))))
+}
+
+/// What a variable of the transformed term is bound to.
+#[derive(Clone, Copy)]
+enum Kind {
+    /// A computation taking a continuation, as the parameters of functions.
+    Computation,
+    /// A value, as the names bound by matches.
+    Value,
+}
+
+#[derive(Default)]
+struct Cps {
+    /// The number of abstractions and names bound by matches around the transformed term being
+    /// built. Variables are referred to by their level, the number of binders around them, and
+    /// their indices are computed from it.
+    level: usize,
+    /// The level and kind of each variable of the original term, innermost last.
+    vars: Vec<(usize, Kind)>,
+    /// The location of the innermost located term being transformed, which is kept by the
+    /// operations that can fail.
+    loc: Option<Location>,
+}
+
+impl Cps {
+    /// Returns the variable bound at `level`.
+    fn var(&self, level: usize) -> Arc<Term> {
+        Arc::new(Var(self.level - 1 - level))
+    }
+
+    /// Returns an abstraction whose body is built by `f` from the level of its variable.
+    fn abs(&mut self, f: impl FnOnce(&mut Self, usize) -> Term) -> Term {
+        let level = self.level;
+        self.level += 1;
+        let body = f(self, level);
+        self.level -= 1;
+        Abs(Arc::new(body))
+    }
+
+    /// Returns a computation running `term` with the continuation it receives.
+    fn computation(&mut self, term: &Term) -> Term {
+        self.abs(|cps, k| cps.run(term, k))
+    }
+
+    /// Returns a term running `term` with the continuation built by `f` from the level of its
+    /// variable, which is bound to the value of `term`.
+    fn then(&mut self, term: &Term, f: impl FnOnce(&mut Self, usize) -> Term) -> Term {
+        match term {
+            // Variables are not wrapped in a computation that would only be applied to the
+            // continuation: computations are applied to it directly and values are used as they
+            // are.
+            Var(index) => match self.vars[self.vars.len() - 1 - index] {
+                (level, Kind::Computation) => self.then_var(level, f),
+                (level, Kind::Value) => f(self, level),
+            },
+            _ => {
+                let computation = self.computation(term);
+                let cont = self.abs(f);
+                App(Arc::new(computation), Arc::new(cont))
+            }
+        }
+    }
+
+    /// Returns a term running the computation bound at level `x` with the continuation built by
+    /// `f` from the level of its variable.
+    fn then_var(&mut self, x: usize, f: impl FnOnce(&mut Self, usize) -> Term) -> Term {
+        let cont = self.abs(f);
+        App(self.var(x), Arc::new(cont))
+    }
+
+    /// Returns a term running each term of `terms` in order, and then the term built by `f` from
+    /// the levels of the variables bound to their values.
+    fn then_all(
+        &mut self,
+        terms: &[Arc<Term>],
+        levels: Vec<usize>,
+        f: impl FnOnce(&mut Self, Vec<usize>) -> Term,
+    ) -> Term {
+        match terms.split_first() {
+            None => f(self, levels),
+            Some((term, rest)) => self.then(term, |cps, level| {
+                let mut levels = levels;
+                levels.push(level);
+                cps.then_all(rest, levels, f)
+            }),
+        }
+    }
+
+    /// Returns a term passing `value` to the continuation at level `k`.
+    fn ret(&self, k: usize, value: Term) -> Term {
+        App(self.var(k), Arc::new(value))
+    }
+
+    /// Returns a term evaluating the operation `op` over values and passing its result to the
+    /// continuation at level `k`.
+    ///
+    /// Applications do not evaluate their arguments, so the result is bound by a match before it
+    /// is passed to the continuation and the operation is evaluated only once.
+    fn ret_op(&mut self, k: usize, op: Term) -> Term {
+        let op = match self.loc {
+            Some(loc) => Loc(loc, Arc::new(op)),
+            None => op,
+        };
+        let variant = Variant(0, Arc::from(""), vec![Arc::new(op)]);
+        self.level += 1;
+        let body = self.ret(k, Var(0));
+        self.level -= 1;
+        Match(
+            Arc::new(variant),
+            vec![(Pattern::Variant(0, 1), Arc::new(body))],
+        )
+    }
+
+    /// Returns a function that takes a computation as its parameter and a continuation, whose
+    /// body is built by `f` from the levels of both.
+    fn function(&mut self, f: impl FnOnce(&mut Self, usize, usize) -> Term) -> Term {
+        // The body of a function is not evaluated where it is defined.
+        let loc = self.loc.take();
+        let function = self.abs(|cps, x| cps.abs(|cps, k| f(cps, x, k)));
+        self.loc = loc;
+        function
+    }
+
+    /// Returns a term running `term` and passing its value to the continuation at level `k`.
+    fn run(&mut self, term: &Term, k: usize) -> Term {
+        match term {
+            Var(index) => match self.vars[self.vars.len() - 1 - index] {
+                (level, Kind::Computation) => App(self.var(level), self.var(k)),
+                (level, Kind::Value) => self.ret(k, Var(self.level - 1 - level)),
+            },
+            Lit(_) | Float(_) | Char(_) | Str(_) | Cell(_) | Chan(_) => self.ret(k, term.clone()),
+            Abs(body) => {
+                let function = self.function(|cps, x, k| {
+                    cps.vars.push((x, Kind::Computation));
+                    let body = cps.run(body, k);
+                    cps.vars.pop().unwrap();
+                    body
+                });
+                self.ret(k, function)
+            }
+            Closure(code, env) => self.run(&Abs(closure::open(code, env, 1)), k),
+            App(t1, t2) => match native_app(term) {
+                Some((index, args)) => self.then_all(&args, Vec::new(), |cps, levels| {
+                    let app = levels
+                        .into_iter()
+                        .fold(Native(index, Vec::new()), |app, level| {
+                            App(Arc::new(app), cps.var(level))
+                        });
+                    cps.ret_op(k, app)
+                }),
+                None => self.then(t1, |cps, f| {
+                    let arg = cps.computation(t2);
+                    let app = App(cps.var(f), Arc::new(arg));
+                    App(Arc::new(app), cps.var(k))
+                }),
+            },
+            UnaryOp(op, t1) => self.then(t1, |cps, x| cps.ret_op(k, UnaryOp(*op, cps.var(x)))),
+            // The second operand of `&&` and `||` is only run if it decides the result.
+            BinaryOp(op @ BinOp::And, t1, t2) | BinaryOp(op @ BinOp::Or, t1, t2) => {
+                self.then(t1, |cps, x| {
+                    let run_t2 = cps.run(t2, k);
+                    let result = cps.ret(k, (*op == BinOp::Or).into());
+                    match op {
+                        BinOp::And => Cond(cps.var(x), Arc::new(run_t2), Arc::new(result)),
+                        _ => Cond(cps.var(x), Arc::new(result), Arc::new(run_t2)),
+                    }
+                })
+            }
+            BinaryOp(op, t1, t2) => self.then(t1, |cps, x| {
+                cps.then(t2, |cps, y| {
+                    cps.ret_op(k, BinaryOp(*op, cps.var(x), cps.var(y)))
+                })
+            }),
+            Cond(t1, t2, t3) => self.then(t1, |cps, x| {
+                let t2 = cps.run(t2, k);
+                let t3 = cps.run(t3, k);
+                Cond(cps.var(x), Arc::new(t2), Arc::new(t3))
+            }),
+            Fix(t1) => {
+                // The fixed point is a computation that runs `t1` and applies its value to the
+                // computation itself.
+                let loc = self.loc.take();
+                let computation = self.abs(|cps, fix| {
+                    cps.abs(|cps, k| {
+                        cps.then(t1, |cps, f| {
+                            let app = App(cps.var(f), cps.var(fix));
+                            App(Arc::new(app), cps.var(k))
+                        })
+                    })
+                });
+                self.loc = loc;
+                App(Arc::new(Fix(Arc::new(computation))), self.var(k))
+            }
+            PrimFn(prim @ Primitive::ReadLine)
+            | PrimFn(prim @ Primitive::ReadInt)
+            | PrimFn(prim @ Primitive::Channel) => self.ret_op(k, PrimFn(*prim)),
+            PrimFn(
+                prim @ Primitive::Push
+                | prim @ Primitive::Get
+                | prim @ Primitive::Min
+                | prim @ Primitive::Max
+                | prim @ Primitive::Gcd
+                | prim @ Primitive::Pow
+                | prim @ Primitive::CharAt
+                | prim @ Primitive::Random
+                | prim @ Primitive::Send,
+            ) => {
+                // The primitive takes its arguments one at a time, so it is a function returning
+                // a function. Both arguments are run once the second one is received.
+                let loc = self.loc;
+                let function = self.function(|cps, x, k| {
+                    let inner = cps.function(|cps, y, k| {
+                        cps.loc = loc;
+                        cps.then_var(x, |cps, a| {
+                            cps.then_var(y, |cps, b| {
+                                let app = App(Arc::new(PrimFn(*prim)), cps.var(a));
+                                cps.ret_op(k, App(Arc::new(app), cps.var(b)))
+                            })
+                        })
+                    });
+                    cps.ret(k, inner)
+                });
+                self.ret(k, function)
+            }
+            PrimFn(prim @ Primitive::Substring) => {
+                // Like the primitives taking two arguments, with one more function for the third
+                // argument.
+                let loc = self.loc;
+                let function = self.function(|cps, x, k| {
+                    let inner = cps.function(|cps, y, k| {
+                        let innermost = cps.function(|cps, z, k| {
+                            cps.loc = loc;
+                            cps.then_var(x, |cps, a| {
+                                cps.then_var(y, |cps, b| {
+                                    cps.then_var(z, |cps, c| {
+                                        let app = App(Arc::new(PrimFn(*prim)), cps.var(a));
+                                        let app = App(Arc::new(app), cps.var(b));
+                                        cps.ret_op(k, App(Arc::new(app), cps.var(c)))
+                                    })
+                                })
+                            })
+                        });
+                        cps.ret(k, innermost)
+                    });
+                    cps.ret(k, inner)
+                });
+                self.ret(k, function)
+            }
+            PrimFn(prim @ Primitive::Spawn) => {
+                // The spawned function takes a computation and a continuation, so the thread runs
+                // a function that applies it to a computation returning `unit` and to a
+                // continuation returning its value.
+                let loc = self.loc;
+                let function = self.function(|cps, x, k| {
+                    cps.loc = loc;
+                    cps.then_var(x, |cps, f| {
+                        let thread = cps.abs(|cps, _| {
+                            let unit = cps.abs(|cps, k| cps.ret(k, Literal::Unit.into()));
+                            let app = App(cps.var(f), Arc::new(unit));
+                            App(Arc::new(app), Arc::new(Abs(Arc::new(Var(0)))))
+                        });
+                        cps.ret_op(k, App(Arc::new(PrimFn(*prim)), Arc::new(thread)))
+                    })
+                });
+                self.ret(k, function)
+            }
+            PrimFn(prim) => {
+                let loc = self.loc;
+                let function = self.function(|cps, x, k| {
+                    cps.loc = loc;
+                    cps.then_var(x, |cps, a| {
+                        cps.ret_op(k, App(Arc::new(PrimFn(*prim)), cps.var(a)))
+                    })
+                });
+                self.ret(k, function)
+            }
+            // Natives without parameters are values.
+            Native(..) => self.ret(k, term.clone()),
+            Variant(index, name, args) => self.then_all(args, Vec::new(), |cps, levels| {
+                let args = levels.into_iter().map(|level| cps.var(level)).collect();
+                cps.ret(k, Variant(*index, Arc::clone(name), args))
+            }),
+            List(elems) => self.then_all(elems, Vec::new(), |cps, levels| {
+                let elems = levels.into_iter().map(|level| cps.var(level)).collect();
+                cps.ret(k, List(elems))
+            }),
+            Match(t1, arms) => self.then(t1, |cps, x| {
+                let arms = arms
+                    .iter()
+                    .map(|(pattern, body)| {
+                        for _ in 0..pattern.binders() {
+                            cps.vars.push((cps.level, Kind::Value));
+                            cps.level += 1;
+                        }
+                        let body = match (pattern.binders(), &**body) {
+                            (1.., Closure(code, env)) => {
+                                cps.run(&closure::open(code, env, pattern.binders()), k)
+                            }
+                            _ => cps.run(body, k),
+                        };
+                        for _ in 0..pattern.binders() {
+                            cps.vars.pop().unwrap();
+                            cps.level -= 1;
+                        }
+                        (pattern.clone(), Arc::new(body))
+                    })
+                    .collect();
+                Match(cps.var(x), arms)
+            }),
+            Ref(t1) => self.then(t1, |cps, x| cps.ret_op(k, Ref(cps.var(x)))),
+            Deref(t1) => self.then(t1, |cps, x| cps.ret_op(k, Deref(cps.var(x)))),
+            Assign(t1, t2) => self.then(t1, |cps, x| {
+                cps.then(t2, |cps, y| cps.ret_op(k, Assign(cps.var(x), cps.var(y))))
+            }),
+            Assert(loc, t1) => self.then(t1, |cps, x| cps.ret_op(k, Assert(*loc, cps.var(x)))),
+            Loc(loc, t1) => {
+                let outer = self.loc.replace(*loc);
+                let term = self.run(t1, k);
+                self.loc = outer;
+                term
+            }
+        }
+    }
+}
+
+/// Returns the index of the native applied by `term` and its arguments, if `term` is an
+/// application of a native.
+fn native_app(term: &Term) -> Option<(usize, Vec<Arc<Term>>)> {
+    match term {
+        App(t1, t2) => {
+            let (index, mut args) = match &**t1 {
+                Native(index, args) if args.is_empty() => (*index, Vec::new()),
+                _ => native_app(t1)?,
+            };
+            args.push(Arc::clone(t2));
+            Some((index, args))
+        }
+        _ => None,
+    }
+}
@@ -1,89 +1,457 @@
-use pijama_ast::{Located, Name};
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use pijama_ast::{
+    symbol::{Symbol, SymbolTable},
+    BinOp, Located, Location, Name, NodeId, Pattern as PatternAST, Primitive,
+};
 
 use crate::{
-    lir::Term,
+    lir::{Pattern, SpanTable, Term},
     mir::{LetKind, Term as MirTerm},
+    ty::Ty,
 };
 
-pub fn remove_names(term: Located<MirTerm<'_>>) -> Term {
-    Context::default().remove_names(term.content)
+/// Lowers `term` to a nameless term, returning it with the nodes its located terms were lowered
+/// from.
+pub fn remove_names(term: Located<MirTerm<'_>>) -> (Term, SpanTable) {
+    let mut ctx = Context::default();
+    let term = ctx.remove_names(term);
+    (term, ctx.spans)
 }
 
 #[derive(Default)]
 struct Context<'a> {
-    inner: Vec<Name<'a>>,
+    /// The names bound by the abstractions around the current term, innermost last. Abstractions
+    /// that do not bind a name are `None`.
+    inner: Vec<Option<Symbol>>,
+    /// The positions in `inner` of the names bound by mutable let bindings, which are bound to
+    /// cells.
+    cells: Vec<usize>,
+    /// The positions in `inner` of the names bound to recursive functions.
+    recursive: Vec<usize>,
+    /// Whether the term being lowered is in tail position inside the body of a function.
+    tail: bool,
+    /// The variants of the enums declared in the current scope, with their position in the
+    /// declaration and their number of fields.
+    variants: Vec<(Symbol, usize, usize)>,
+    symbols: SymbolTable<'a>,
+    /// The nodes that the located terms lowered so far come from.
+    spans: SpanTable,
 }
 
 impl<'a> Context<'a> {
-    fn remove_names(&mut self, term: MirTerm<'a>) -> Term {
-        match term {
+    /// Returns the de Bruijn index of the variable bound to `name`.
+    fn index(&self, name: Name<'a>) -> usize {
+        let symbol = self.symbols.get(name).unwrap();
+        self.inner
+            .iter()
+            .rev()
+            .position(|symbol2| Some(symbol) == *symbol2)
+            .unwrap()
+    }
+
+    /// Returns the position and the number of fields of the variant bound to `name`.
+    fn variant(&self, name: Name<'a>) -> (usize, usize) {
+        let symbol = self.symbols.get(name).unwrap();
+        self.variants
+            .iter()
+            .rev()
+            .find(|(symbol2, _, _)| symbol == *symbol2)
+            .map(|(_, index, arity)| (*index, *arity))
+            .unwrap()
+    }
+
+    /// Lowers `term`, which is in tail position if `tail` is true.
+    fn remove_names_at(&mut self, term: Located<MirTerm<'a>>, tail: bool) -> Term {
+        self.tail = tail;
+        self.remove_names(term)
+    }
+
+    fn remove_names(&mut self, term: Located<MirTerm<'a>>) -> Term {
+        let loc = term.loc;
+        let id = term.id;
+        let tail = core::mem::take(&mut self.tail);
+        match term.content {
             MirTerm::Lit(lit) => lit.into(),
+            MirTerm::List(elems) => Term::List(
+                elems
+                    .into_iter()
+                    .map(|elem| Arc::new(self.remove_names(elem)))
+                    .collect(),
+            ),
             MirTerm::Var(name) => {
-                let (index, _) = self
-                    .inner
-                    .iter()
-                    .rev()
-                    .enumerate()
-                    .find(|(_, name2)| name == **name2)
-                    .unwrap();
-                Term::Var(index)
+                let index = self.index(name);
+                // mutable bindings are read from their cell each time they are used.
+                if self.cells.contains(&(self.inner.len() - 1 - index)) {
+                    Term::Deref(Arc::new(Term::Var(index)))
+                } else {
+                    Term::Var(index)
+                }
             }
             MirTerm::Abs(name, _, body) => {
-                self.inner.push(name);
-                let body = self.remove_names(body.content);
+                let symbol = self.symbols.intern(name.content);
+                self.inner.push(Some(symbol));
+                let body = self.remove_names_at(*body, true);
                 self.inner.pop().unwrap();
-                Term::Abs(Box::new(body))
+                Term::Abs(Arc::new(body))
+            }
+            MirTerm::Ascription(t1, _) => self.remove_names_at(*t1, tail),
+            MirTerm::Lazy(t1) => {
+                // the lazy term is a cell holding a thunk, an abstraction that ignores its
+                // argument, inside the first variant. Forcing it replaces the thunk by its value
+                // inside the second variant, so it is evaluated only once.
+                self.inner.push(None);
+                let t1 = self.remove_names(*t1);
+                self.inner.pop().unwrap();
+                let thunk = Term::Abs(Arc::new(t1));
+                Term::Ref(Arc::new(Term::Variant(
+                    0,
+                    Arc::from(""),
+                    vec![Arc::new(thunk)],
+                )))
             }
             MirTerm::UnaryOp(op, t1) => {
-                let t1 = self.remove_names(t1.content);
-                Term::UnaryOp(op, Box::new(t1))
+                let t1 = self.remove_names(*t1);
+                self.located(loc, id, Term::UnaryOp(op, Arc::new(t1)))
             }
             MirTerm::BinaryOp(op, t1, t2) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                Term::BinaryOp(op, Box::new(t1), Box::new(t2))
+                let t1 = self.remove_names(*t1);
+                let t2 = self.remove_names(*t2);
+                self.located(loc, id, Term::BinaryOp(op, Arc::new(t1), Arc::new(t2)))
+            }
+            MirTerm::App(t1, t2) if matches!(t1.content, MirTerm::PrimFn(Primitive::Assert)) => {
+                // the assertion keeps the location of the whole application to report it if it
+                // fails.
+                let t2 = self.remove_names(*t2);
+                self.spans.insert(loc, id);
+                Term::Assert(loc, Arc::new(t2))
+            }
+            MirTerm::App(t1, t2) if tail && self.calls_recursive(&t1.content) => {
+                self.remove_names_tail_call(*t1, *t2)
             }
             MirTerm::App(t1, t2) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                Term::App(Box::new(t1), Box::new(t2))
-            }
-            MirTerm::Let(kind, name, t1, t2) => {
-                let t1 = if let LetKind::Rec(_) = kind {
-                    // if the let binding is recursive we are dealing with a recursive function and
-                    // we need its name inside the context to lower its body.
-                    //
-                    // Also the indices must be shifted by one because the function will be wrapped
-                    // in an additional abstraction.
-                    //
-                    // Both things are satisfied by just pushing the name of the function into the
-                    // context.
-                    self.inner.push(name.content);
-                    Term::Fix(Box::new(Term::Abs(Box::new(self.remove_names(t1.content)))))
-                } else {
-                    // if the let binding is non-recursive, we first lower the binded term, and
-                    // then we make its name availabe by pushing it into the context
-                    let t1 = self.remove_names(t1.content);
-                    self.inner.push(name.content);
-                    t1
-                };
-
-                let t2 = self.remove_names(t2.content);
+                // applications can fail, either because they apply a primitive or because they
+                // nest too deeply, so they keep their location.
+                let t1 = self.remove_names(*t1);
+                let t2 = self.remove_names(*t2);
+                self.located(loc, id, Term::App(Arc::new(t1), Arc::new(t2)))
+            }
+            MirTerm::Let(LetKind::Mut(_), name, t1, t2) => {
+                // the name is bound to a new cell holding the value of `t1`. The cell must be
+                // created only once, so it is bound strictly.
+                let t1 = self.remove_names(*t1);
+                let symbol = self.symbols.intern(name.content);
+                self.inner.push(Some(symbol));
+                self.cells.push(self.inner.len() - 1);
+                let t2 = self.remove_names_at(*t2, tail);
+                self.cells.pop().unwrap();
+                self.inner.pop().unwrap();
+                strict(Term::Ref(Arc::new(t1)), t2)
+            }
+            MirTerm::Let(LetKind::NonRec(_), name, t1, t2) => {
+                // the value is bound strictly so reading a mutable binding gives the value it has
+                // at this point and not when the name is used.
+                let t1 = self.remove_names(*t1);
+                let symbol = self.symbols.intern(name.content);
+                self.inner.push(Some(symbol));
+                let t2 = self.remove_names_at(*t2, tail);
                 self.inner.pop().unwrap();
-                Term::App(Box::new(Term::Abs(Box::new(t2))), Box::new(t1))
+                strict(t1, t2)
+            }
+            MirTerm::Let(LetKind::Rec(_), name, t1, t2) => {
+                // if the let binding is recursive we are dealing with a recursive function and we
+                // need its name inside the context to lower its body.
+                //
+                // Also the indices must be shifted by one because the function will be wrapped in
+                // an additional abstraction.
+                //
+                // Both things are satisfied by just pushing the name of the function into the
+                // context.
+                let symbol = self.symbols.intern(name.content);
+                self.inner.push(Some(symbol));
+                self.recursive.push(self.inner.len() - 1);
+                let t1 = Term::Fix(Arc::new(Term::Abs(Arc::new(self.remove_names(*t1)))));
+                let t2 = self.remove_names_at(*t2, tail);
+                self.recursive.pop().unwrap();
+                self.inner.pop().unwrap();
+                Term::App(Arc::new(Term::Abs(Arc::new(t2))), Arc::new(t1))
+            }
+            MirTerm::LetRec(fns, t2) => {
+                // the group is lowered to a recursive variant with one field for each function.
+                // Each field is an abstraction that ignores its argument, so the variant is a
+                // value without evaluating the bodies of the functions.
+                let symbols: Vec<_> = fns
+                    .iter()
+                    .map(|(name, _, _)| self.symbols.intern(name.content))
+                    .collect();
+
+                let fields = fns
+                    .into_iter()
+                    .map(|(_, _, body)| {
+                        // the variant and the ignored argument are bound before the functions.
+                        self.inner.push(None);
+                        self.inner.push(None);
+                        let body = self.with_group(&symbols, 1, body, false);
+                        self.inner.pop().unwrap();
+                        self.inner.pop().unwrap();
+                        Arc::new(Term::Abs(Arc::new(body)))
+                    })
+                    .collect();
+                let group = Term::Fix(Arc::new(Term::Abs(Arc::new(Term::Variant(
+                    0,
+                    Arc::from(""),
+                    fields,
+                )))));
+
+                self.inner.push(None);
+                let t2 = self.with_group(&symbols, 0, *t2, tail);
+                self.inner.pop().unwrap();
+                Term::App(Arc::new(Term::Abs(Arc::new(t2))), Arc::new(group))
             }
             MirTerm::Cond(t1, t2, t3) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                let t3 = self.remove_names(t3.content);
-                Term::Cond(Box::new(t1), Box::new(t2), Box::new(t3))
+                let t1 = self.remove_names(*t1);
+                let t2 = self.remove_names_at(*t2, tail);
+                let t3 = self.remove_names_at(*t3, tail);
+                Term::Cond(Arc::new(t1), Arc::new(t2), Arc::new(t3))
             }
             MirTerm::Seq(t1, t2) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                Term::App(Box::new(Term::Abs(Box::new(t2))), Box::new(t1))
+                // matches evaluate their scrutinee, so the effects of `t1` happen before `t2` is
+                // evaluated.
+                let t1 = self.remove_names(*t1);
+                let t2 = self.remove_names_at(*t2, tail);
+                Term::Match(Arc::new(t1), vec![(Pattern::Wildcard, Arc::new(t2))])
+            }
+            MirTerm::Assign(name, t1) => {
+                let cell = Term::Var(self.index(name.content));
+                let t1 = self.remove_names(*t1);
+                Term::Assign(Arc::new(cell), Arc::new(t1))
+            }
+            MirTerm::PrimFn(Primitive::Concat) => {
+                // `concat` is a function that adds its two arguments, which the machine does by
+                // concatenating them when they are strings.
+                let add =
+                    Term::BinaryOp(BinOp::Add, Arc::new(Term::Var(1)), Arc::new(Term::Var(0)));
+                Term::Abs(Arc::new(Term::Abs(Arc::new(add))))
+            }
+            MirTerm::PrimFn(Primitive::Assert) => {
+                // `assert` is not applied here, so it is wrapped in an abstraction and the
+                // assertion is located at the primitive itself.
+                Term::Abs(Arc::new(Term::Assert(loc, Arc::new(Term::Var(0)))))
+            }
+            MirTerm::PrimFn(Primitive::Force) => {
+                // `force` takes the cell of a lazy term. If it holds a thunk, the thunk is applied
+                // and the cell is updated with its value, otherwise the cell already holds the
+                // value.
+                let value = Term::Variant(1, Arc::from(""), vec![Arc::new(Term::Var(0))]);
+                let update = Term::Match(
+                    Arc::new(Term::Assign(Arc::new(Term::Var(2)), Arc::new(value))),
+                    vec![(Pattern::Wildcard, Arc::new(Term::Var(0)))],
+                );
+                let apply = Term::App(Arc::new(Term::Var(0)), Arc::new(().into()));
+                let body = Term::Match(
+                    Arc::new(Term::Deref(Arc::new(Term::Var(0)))),
+                    vec![
+                        (Pattern::Variant(0, 1), Arc::new(strict(apply, update))),
+                        (Pattern::Variant(1, 1), Arc::new(Term::Var(0))),
+                    ],
+                );
+                Term::Abs(Arc::new(strict(Term::Var(0), body)))
+            }
+            MirTerm::PrimFn(prim @ Primitive::ReadLine)
+            | MirTerm::PrimFn(prim @ Primitive::ReadInt) => {
+                self.located(loc, id, Term::PrimFn(prim))
             }
             MirTerm::PrimFn(prim) => Term::PrimFn(prim),
+            MirTerm::Native(index, ty) => {
+                // the native is wrapped in one abstraction for each parameter, so it is always
+                // applied to all its arguments. The continuation-passing style transformation
+                // relies on this to call it directly.
+                let mut arity = 0;
+                let mut ret = &ty;
+                while let Ty::Arrow(_, ty2) = ret {
+                    arity += 1;
+                    ret = ty2;
+                }
+                let mut term = Term::Native(index, Vec::new());
+                for var in (0..arity).rev() {
+                    term = Term::App(Arc::new(term), Arc::new(Term::Var(var)));
+                }
+                for _ in 0..arity {
+                    term = Term::Abs(Arc::new(term));
+                }
+                term
+            }
+            MirTerm::Enum(_, variants, term) => {
+                let len = self.variants.len();
+                for (index, variant) in variants.iter().enumerate() {
+                    let symbol = self.symbols.intern(variant.name.content);
+                    self.variants.push((symbol, index, variant.fields.len()));
+                }
+                let term = self.remove_names_at(*term, tail);
+                self.variants.truncate(len);
+                term
+            }
+            MirTerm::Constructor(name) => {
+                // the constructor takes one argument for each field, the first field is bound by
+                // the outermost abstraction.
+                let (index, arity) = self.variant(name);
+                let args = (0..arity)
+                    .rev()
+                    .map(|var| Arc::new(Term::Var(var)))
+                    .collect();
+                let mut term = Term::Variant(index, Arc::from(name.0), args);
+                for _ in 0..arity {
+                    term = Term::Abs(Arc::new(term));
+                }
+                term
+            }
+            MirTerm::Match(t1, arms) => {
+                let t1 = self.remove_names(*t1);
+                let mut new_arms = Vec::with_capacity(arms.len());
+                for (pattern, body) in arms {
+                    let pattern = match pattern.content {
+                        PatternAST::Wildcard => Pattern::Wildcard,
+                        PatternAST::Literal(lit) => match Term::from(lit) {
+                            Term::Lit(lit) => Pattern::Lit(lit),
+                            Term::Float(x) => Pattern::Float(x),
+                            Term::Char(c) => Pattern::Char(c),
+                            Term::Str(string) => Pattern::Str(string),
+                            _ => unreachable!(),
+                        },
+                        PatternAST::Variant(name, binders) => {
+                            // the names bound by the pattern are in scope inside the body, the
+                            // last one is the innermost.
+                            for binder in &binders {
+                                let symbol = self.symbols.intern(binder.content);
+                                self.inner.push(Some(symbol));
+                            }
+                            let (index, _) = self.variant(name.content);
+                            Pattern::Variant(index, binders.len())
+                        }
+                    };
+                    let body = self.remove_names_at(body, tail);
+                    for _ in 0..pattern.binders() {
+                        self.inner.pop().unwrap();
+                    }
+                    new_arms.push((pattern, Arc::new(body)));
+                }
+                Term::Match(Arc::new(t1), new_arms)
+            }
         }
     }
+
+    /// Returns `term` with its location in the source code, lowered from the node with the id
+    /// `id`.
+    fn located(&mut self, loc: Location, id: NodeId, term: Term) -> Term {
+        self.spans.insert(loc, id);
+        Term::Loc(loc, Arc::new(term))
+    }
+
+    /// Returns `true` if `head` is a recursive function or a recursive function applied to some
+    /// of its arguments.
+    fn calls_recursive(&self, head: &MirTerm<'a>) -> bool {
+        match head {
+            MirTerm::Var(name) => {
+                let index = self.index(*name);
+                self.recursive.contains(&(self.inner.len() - 1 - index))
+            }
+            MirTerm::App(t1, _) => self.calls_recursive(&t1.content),
+            _ => false,
+        }
+    }
+
+    /// Lowers a call to a recursive function in tail position, where `t1` is the function applied
+    /// to every argument but the last one, `t2`.
+    ///
+    /// Applications replace the parameters by the arguments without evaluating them, so the
+    /// arguments of a recursive call would grow with each call and evaluating them would take
+    /// as much stack as the number of calls. Instead, the arguments are bound strictly, the first
+    /// one being the outermost, so the function is applied to values and the call is evaluated
+    /// in constant stack.
+    fn remove_names_tail_call(
+        &mut self,
+        t1: Located<MirTerm<'a>>,
+        t2: Located<MirTerm<'a>>,
+    ) -> Term {
+        let mut args = vec![t2];
+        let mut head = t1;
+        while let MirTerm::App(t1, t2) = head.content {
+            args.push(*t2);
+            head = *t1;
+        }
+
+        let len = args.len();
+        let mut values = Vec::with_capacity(len);
+        for arg in args.into_iter().rev() {
+            values.push(self.remove_names(arg));
+            self.inner.push(None);
+        }
+        let mut term = self.remove_names(head);
+        for index in (0..len).rev() {
+            term = Term::App(Arc::new(term), Arc::new(Term::Var(index)));
+        }
+        for value in values.into_iter().rev() {
+            self.inner.pop().unwrap();
+            term = strict(value, term);
+        }
+        term
+    }
+
+    /// Lowers `term` with the functions of a group bound to their names. The term is in tail
+    /// position if `tail` is true.
+    ///
+    /// The variant of the group must be bound `base` abstractions above `term`. Each function is
+    /// taken from its field and applied to a dummy argument, and it is bound by a new abstraction,
+    /// the last function being the innermost.
+    fn with_group(
+        &mut self,
+        symbols: &[Symbol],
+        base: usize,
+        term: Located<MirTerm<'a>>,
+        tail: bool,
+    ) -> Term {
+        let arity = symbols.len();
+        let projections: Vec<_> = (0..arity)
+            .map(|index| {
+                // the variant is one abstraction further for each function bound before this one.
+                let field = Term::Match(
+                    Arc::new(Term::Var(base + index)),
+                    vec![(
+                        Pattern::Variant(0, arity),
+                        Arc::new(Term::Var(arity - 1 - index)),
+                    )],
+                );
+                Term::App(Arc::new(field), Arc::new(().into()))
+            })
+            .collect();
+
+        for symbol in symbols {
+            self.inner.push(Some(*symbol));
+            self.recursive.push(self.inner.len() - 1);
+        }
+        let mut term = self.remove_names_at(term, tail);
+        for _ in symbols {
+            self.recursive.pop().unwrap();
+            self.inner.pop().unwrap();
+        }
+
+        for projection in projections.into_iter().rev() {
+            term = Term::App(Arc::new(Term::Abs(Arc::new(term))), Arc::new(projection));
+        }
+        term
+    }
+}
+
+/// Returns a term that evaluates `value` and then `body`, with the value bound to the variable `0`
+/// of `body`.
+///
+/// Applying an abstraction replaces its variable by the argument without evaluating it, so the
+/// value is wrapped in a variant with a single field and bound by a match instead, which evaluates
+/// its scrutinee and its fields.
+fn strict(value: Term, body: Term) -> Term {
+    let variant = Term::Variant(0, Arc::from(""), vec![Arc::new(value)]);
+    Term::Match(
+        Arc::new(variant),
+        vec![(Pattern::Variant(0, 1), Arc::new(body))],
+    )
 }
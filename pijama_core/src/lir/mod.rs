@@ -1,22 +1,121 @@
-use std::fmt;
+use alloc::{sync::Arc, vec::Vec};
+use core::{cmp::Ordering, fmt};
 
-use pijama_ast::{BinOp, Literal, Located, Primitive, UnOp};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::{
+    write_char_literal, write_str_literal, BinOp, Int, Literal, Located, Location, Primitive, UnOp,
+};
+
+use crate::stack;
 
 use Term::*;
 
+mod closure;
+pub mod compare;
+pub mod cps;
 mod lower;
+pub mod pretty;
+mod spans;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+pub use spans::SpanTable;
+
+/// A nameless term, where variables are represented by de Bruijn indices.
+///
+/// Subterms are reference-counted, so substituting a term into another shares it instead of
+/// copying it. The counts are atomic because compiled programs are shared between threads.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Term {
     Var(usize),
-    Lit(i64),
-    Abs(Box<Term>),
-    UnaryOp(UnOp, Box<Term>),
-    BinaryOp(BinOp, Box<Term>, Box<Term>),
-    App(Box<Term>, Box<Term>),
-    Cond(Box<Term>, Box<Term>, Box<Term>),
-    Fix(Box<Term>),
+    Lit(Int),
+    #[cfg_attr(feature = "serde", serde(with = "float_bits"))]
+    Float(f64),
+    Char(char),
+    Str(Arc<str>),
+    /// A list with its elements, which is only a value when its elements are values too.
+    List(Vec<Arc<Term>>),
+    Abs(Arc<Term>),
+    /// A function with the code of its body and its environment, produced by the closure
+    /// conversion. The code is closed: the variable `0` is the parameter of the function and the
+    /// variable `k + 1` is the `k`-th term of the environment.
+    ///
+    /// The body of an arm of a match binding `n` names is also a closure after the conversion.
+    /// The first `n` variables of its code are the names and the variable `n + k` is the `k`-th
+    /// term of the environment.
+    Closure(Arc<Term>, Vec<Arc<Term>>),
+    UnaryOp(UnOp, Arc<Term>),
+    BinaryOp(BinOp, Arc<Term>, Arc<Term>),
+    App(Arc<Term>, Arc<Term>),
+    Cond(Arc<Term>, Arc<Term>, Arc<Term>),
+    Fix(Arc<Term>),
     PrimFn(Primitive),
+    /// A function provided by the host, identified by its index in the machine, together with
+    /// the arguments it has received so far.
+    Native(usize, Vec<Arc<Term>>),
+    /// A variant of an enum, identified by its position in the declaration of the enum, together
+    /// with its name and its fields.
+    Variant(usize, Arc<str>, Vec<Arc<Term>>),
+    /// A match with its scrutinee and arms. The body of each arm is under one abstraction for
+    /// each name bound by its pattern.
+    Match(Arc<Term>, Vec<(Pattern, Arc<Term>)>),
+    /// A new mutable cell holding the value of its term.
+    Ref(Arc<Term>),
+    /// A mutable cell, identified by its index in the machine.
+    Cell(usize),
+    /// The value held by a mutable cell.
+    Deref(Arc<Term>),
+    /// A channel, identified by its index in the machine.
+    Chan(usize),
+    /// An assignment of the value of the second term to the cell of the first one, which
+    /// evaluates to `unit`.
+    Assign(Arc<Term>, Arc<Term>),
+    /// An assertion that its term is `true`, which evaluates to `unit`. The location of the
+    /// assertion in the source code is kept to report it if the assertion fails.
+    Assert(Location, Arc<Term>),
+    /// A term with the location of the source code it was lowered from, which evaluates to the
+    /// value of the term. Only the terms that can fail at runtime are located, so the machine can
+    /// report where the evaluation stopped.
+    Loc(Location, Arc<Term>),
+}
+
+/// An optional pass over the nameless terms, run by [`Term::from_mir_with`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Pass {
+    /// Transforms the term into continuation-passing style with [`cps::transform`].
+    ///
+    /// This pass is experimental and slows down evaluation, see the [`cps`] module.
+    Cps,
+}
+
+/// A pattern of an arm of a match.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Lit(Int),
+    #[cfg_attr(feature = "serde", serde(with = "float_bits"))]
+    Float(f64),
+    Char(char),
+    Str(Arc<str>),
+    /// A pattern matching the variant with the given position and binding its fields, whose
+    /// number is also given.
+    Variant(usize, usize),
+}
+
+impl Pattern {
+    /// Returns the number of names bound by the pattern.
+    pub(crate) fn binders(&self) -> usize {
+        match self {
+            Pattern::Wildcard
+            | Pattern::Lit(_)
+            | Pattern::Float(_)
+            | Pattern::Char(_)
+            | Pattern::Str(_) => 0,
+            Pattern::Variant(_, arity) => *arity,
+        }
+    }
 }
 
 impl Term {
@@ -35,12 +134,15 @@ impl From<Literal> for Term {
             Literal::Bool(b) => b.into(),
             Literal::Unit => ().into(),
             Literal::Number(n) => n.into(),
+            Literal::Float(x) => Float(x),
+            Literal::Char(c) => Char(c),
+            Literal::Str(string) => Str(string.into()),
         }
     }
 }
 
-impl From<i64> for Term {
-    fn from(i: i64) -> Self {
+impl From<Int> for Term {
+    fn from(i: Int) -> Self {
         Lit(i)
     }
 }
@@ -62,91 +164,515 @@ impl fmt::Display for Term {
         match self {
             Var(var) => write!(f, "_{}", var),
             Abs(term) => write!(f, "(λ. {})", term),
+            // A closure is shown as the abstraction it was converted from.
+            Closure(code, env) => write!(f, "(λ. {})", closure::open(code, env, 1)),
             UnaryOp(op, term) => write!(f, "({}{})", op, term),
             BinaryOp(op, t1, t2) => write!(f, "({} {} {})", t1, op, t2),
             App(t1, t2) => write!(f, "({} {})", t1, t2),
             Lit(literal) => write!(f, "{}", literal),
+            Float(literal) => write!(f, "{:?}", literal),
+            Char(c) => write_char_literal(f, *c),
+            Str(string) => write_str_literal(f, string),
+            List(elems) => {
+                write!(f, "[")?;
+                for (index, elem) in elems.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            }
             Cond(t1, t2, t3) => write!(f, "(if {} then {} else {})", t1, t2, t3),
             Fix(t1) => write!(f, "(fix {})", t1),
             PrimFn(prim) => write!(f, "{}", prim),
+            Native(index, args) => {
+                write!(f, "native#{}", index)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                Ok(())
+            }
+            Variant(_, name, args) => {
+                write!(f, "{}", name)?;
+                if !args.is_empty() {
+                    write!(f, "(")?;
+                    for (index, arg) in args.iter().enumerate() {
+                        if index > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", arg)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Match(t1, arms) => {
+                write!(f, "(match {} with", t1)?;
+                for (index, (pattern, body)) in arms.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { " |" };
+                    match (pattern.binders(), &**body) {
+                        (binders @ 1.., Closure(code, env)) => {
+                            let body = closure::open(code, env, binders);
+                            write!(f, "{} {} => {}", separator, pattern, body)?
+                        }
+                        _ => write!(f, "{} {} => {}", separator, pattern, body)?,
+                    }
+                }
+                write!(f, ")")
+            }
+            Ref(t1) => write!(f, "(ref {})", t1),
+            Cell(index) => write!(f, "cell#{}", index),
+            Chan(index) => write!(f, "chan#{}", index),
+            Deref(t1) => write!(f, "(!{})", t1),
+            Assign(t1, t2) => write!(f, "({} := {})", t1, t2),
+            Assert(_, t1) => write!(f, "(assert {})", t1),
+            Loc(_, t1) => write!(f, "{}", t1),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Lit(literal) => write!(f, "{}", literal),
+            Pattern::Float(literal) => write!(f, "{:?}", literal),
+            Pattern::Char(c) => write_char_literal(f, *c),
+            Pattern::Str(string) => write_str_literal(f, string),
+            Pattern::Variant(index, arity) => write!(f, "#{}/{}", index, arity),
         }
     }
 }
 
 impl Term {
     pub fn from_mir(mir: Located<crate::mir::Term>) -> Self {
-        lower::remove_names(mir)
+        Term::from_mir_with(mir, &[])
     }
 
-    pub(crate) fn shift(&mut self, up: bool, cutoff: usize) {
-        match self {
-            Lit(_) | PrimFn(_) => (),
-            Var(index) => {
-                if *index >= cutoff {
-                    if up {
-                        *index += 1;
-                    } else {
-                        *index -= 1;
-                    }
+    /// Lowers `mir` running `passes` in order over the nameless term, before its abstractions are
+    /// converted to closures.
+    pub fn from_mir_with(mir: Located<crate::mir::Term>, passes: &[Pass]) -> Self {
+        Term::from_mir_with_spans(mir, passes).0
+    }
+
+    /// Lowers `mir` like [`Term::from_mir_with`], returning the nameless term with the table of
+    /// the nodes that its located terms were lowered from.
+    pub fn from_mir_with_spans(
+        mir: Located<crate::mir::Term>,
+        passes: &[Pass],
+    ) -> (Self, SpanTable) {
+        let (term, spans) = lower::remove_names(mir);
+        let term = passes.iter().fold(term, |term, pass| match pass {
+            Pass::Cps => cps::transform(term),
+        });
+        (closure::convert(term), spans)
+    }
+
+    /// Moves the term out of `term`, cloning it only if it is shared.
+    ///
+    /// The clone is shallow: the subterms of the returned term are shared with `term`.
+    pub(crate) fn take(term: Arc<Term>) -> Term {
+        Arc::try_unwrap(term).unwrap_or_else(|term| (*term).clone())
+    }
+
+    /// Drops `term` one subterm at a time instead of recursively, so it can be nested deeper
+    /// than the stack of the host would allow to drop it.
+    pub(crate) fn drop_iteratively(term: Term) {
+        let mut pending = alloc::vec![term];
+        while let Some(term) = pending.pop() {
+            // Shared subterms are only released, the last reference drops them later.
+            let mut push = |term: Arc<Term>| {
+                if let Ok(term) = Arc::try_unwrap(term) {
+                    pending.push(term);
+                }
+            };
+            match term {
+                Var(_) | Lit(_) | Float(_) | Char(_) | Str(_) | PrimFn(_) | Cell(_) | Chan(_) => {}
+                Abs(t1)
+                | UnaryOp(_, t1)
+                | Fix(t1)
+                | Ref(t1)
+                | Deref(t1)
+                | Assert(_, t1)
+                | Loc(_, t1) => push(t1),
+                BinaryOp(_, t1, t2) | App(t1, t2) | Assign(t1, t2) => {
+                    push(t1);
+                    push(t2);
+                }
+                Cond(t1, t2, t3) => {
+                    push(t1);
+                    push(t2);
+                    push(t3);
+                }
+                List(terms) | Native(_, terms) | Variant(_, _, terms) => {
+                    terms.into_iter().for_each(push)
+                }
+                Closure(t1, env) => {
+                    push(t1);
+                    env.into_iter().for_each(push);
+                }
+                Match(t1, arms) => {
+                    push(t1);
+                    arms.into_iter().for_each(|(_, arm)| push(arm));
                 }
             }
-            Abs(body) => {
-                body.shift(up, cutoff + 1);
+        }
+    }
+
+    /// Drops `term` like [`Term::drop_iteratively`] if it is not shared, or releases it otherwise.
+    ///
+    /// Used for the subterms discarded by the machine, such as the branch of a conditional that is
+    /// not taken, which can hold arguments as deeply nested as the calls that built them.
+    pub(crate) fn discard(term: Arc<Term>) {
+        if let Ok(term) = Arc::try_unwrap(term) {
+            Term::drop_iteratively(term);
+        }
+    }
+
+    /// Returns `term` with the indices of the variables greater or equal than `cutoff` increased
+    /// by `amount`.
+    fn shift(term: &Arc<Term>, amount: usize, cutoff: usize) -> Arc<Term> {
+        Term::map_vars(term, 0, &mut |index, depth| {
+            if index >= cutoff + depth {
+                Some(Arc::new(Var(index + amount)))
+            } else {
+                None
             }
-            UnaryOp(_, t1) => {
-                t1.shift(up, cutoff);
+        })
+    }
+
+    /// Returns the body of an abstraction with its argument replaced by `arg`.
+    ///
+    /// The variable `0` of `body` is replaced by `arg` and the indices of the other free variables
+    /// of `body` are decreased, because the abstraction no longer exists.
+    pub(crate) fn substitute(body: &mut Arc<Term>, arg: &Arc<Term>) {
+        // The terms evaluated by the machine are closed, so the argument does not need to be
+        // shifted when it is placed under other abstractions in most cases.
+        let closed = Arc::ptr_eq(arg, &Term::shift(arg, 1, 0));
+        Term::map_vars_in_place(body, 0, &mut |index, depth| match index.cmp(&depth) {
+            Ordering::Less => None,
+            Ordering::Equal if closed => Some(Arc::clone(arg)),
+            // The indices of `arg` must be increased to account for the abstractions between
+            // `body` and the variable.
+            Ordering::Equal => Some(Term::shift(arg, depth, 0)),
+            Ordering::Greater => Some(Arc::new(Var(index - 1))),
+        });
+    }
+
+    /// Returns the code of a closure with its parameters replaced by `args` and the variables of
+    /// its environment replaced by the terms of `env`. The last argument replaces the variable
+    /// `0`.
+    ///
+    /// Only the code of the closure is traversed, the code of the closures nested inside it is
+    /// closed and it is shared instead. `args` and the terms of `env` must be closed, as every
+    /// term evaluated by the machine, so they are not shifted and they are not traversed either.
+    pub(crate) fn instantiate(
+        code: &Arc<Term>,
+        args: &[Arc<Term>],
+        env: &[Arc<Term>],
+    ) -> Arc<Term> {
+        let mut body = Arc::clone(code);
+        Term::map_vars_in_place(&mut body, 0, &mut |index, depth| {
+            let var = index.checked_sub(depth)?;
+            let term = match args.len().checked_sub(var + 1) {
+                Some(position) => &args[position],
+                None => &env[var - args.len()],
+            };
+            Some(Arc::clone(term))
+        });
+        body
+    }
+
+    /// Replaces each variable of `term` by the result of `f`, if any.
+    ///
+    /// Nodes that are not shared are modified in place. Shared nodes are left untouched and
+    /// replaced by a copy as in [`Term::map_vars`].
+    fn map_vars_in_place<F>(term: &mut Arc<Term>, depth: usize, f: &mut F)
+    where
+        F: FnMut(usize, usize) -> Option<Arc<Term>>,
+    {
+        stack::grow(|| Term::map_vars_in_place_nested(term, depth, f))
+    }
+
+    /// Replaces the variables of `term` like [`Term::map_vars_in_place`] in the current segment of
+    /// the stack of the host.
+    fn map_vars_in_place_nested<F>(term: &mut Arc<Term>, depth: usize, f: &mut F)
+    where
+        F: FnMut(usize, usize) -> Option<Arc<Term>>,
+    {
+        let inner = match Arc::get_mut(term) {
+            Some(inner) => inner,
+            None => {
+                *term = Term::map_vars(term, depth, f);
+                return;
             }
-            BinaryOp(_, t1, t2) => {
-                t1.shift(up, cutoff);
-                t2.shift(up, cutoff);
+        };
+        match inner {
+            Lit(_) | Float(_) | Char(_) | Str(_) | PrimFn(_) | Cell(_) | Chan(_) => (),
+            Var(index) => {
+                if let Some(new_term) = f(*index, depth) {
+                    *term = new_term;
+                }
             }
-            App(t1, t2) => {
-                t1.shift(up, cutoff);
-                t2.shift(up, cutoff);
+            Abs(body) => Term::map_vars_in_place(body, depth + 1, f),
+            // The code of a closure is closed, only its environment can have variables.
+            Closure(_, env) => {
+                for term in env {
+                    Term::map_vars_in_place(term, depth, f);
+                }
+            }
+            UnaryOp(_, t1) | Fix(t1) | Ref(t1) | Deref(t1) | Assert(_, t1) | Loc(_, t1) => {
+                Term::map_vars_in_place(t1, depth, f)
+            }
+            BinaryOp(_, t1, t2) | App(t1, t2) | Assign(t1, t2) => {
+                Term::map_vars_in_place(t1, depth, f);
+                Term::map_vars_in_place(t2, depth, f);
             }
             Cond(t1, t2, t3) => {
-                t1.shift(up, cutoff);
-                t2.shift(up, cutoff);
-                t3.shift(up, cutoff);
+                Term::map_vars_in_place(t1, depth, f);
+                Term::map_vars_in_place(t2, depth, f);
+                Term::map_vars_in_place(t3, depth, f);
             }
-            Fix(t1) => {
-                t1.shift(up, cutoff);
+            Native(_, args) | Variant(_, _, args) | List(args) => {
+                for arg in args {
+                    Term::map_vars_in_place(arg, depth, f);
+                }
+            }
+            Match(t1, arms) => {
+                Term::map_vars_in_place(t1, depth, f);
+                for (pattern, body) in arms {
+                    let binders = arm_binders(pattern, body);
+                    Term::map_vars_in_place(body, depth + binders, f);
+                }
             }
         }
     }
 
-    pub(crate) fn replace(&mut self, index: usize, subs: &mut Term) {
-        match self {
-            Lit(_) | PrimFn(_) => (),
-            Var(index2) => {
-                if index == *index2 {
-                    *self = subs.clone();
-                }
+    /// Returns `term` with each variable replaced by the result of `f`, if any.
+    ///
+    /// `f` receives the index of the variable and the number of abstractions between `term` and
+    /// the variable. Subterms without replaced variables are shared with `term` instead of being
+    /// copied, so only the nodes between `term` and the replaced variables are allocated.
+    fn map_vars<F>(term: &Arc<Term>, depth: usize, f: &mut F) -> Arc<Term>
+    where
+        F: FnMut(usize, usize) -> Option<Arc<Term>>,
+    {
+        stack::grow(|| Term::map_vars_nested(term, depth, f))
+    }
+
+    /// Returns `term` with its variables replaced like [`Term::map_vars`], in the current segment
+    /// of the stack of the host.
+    fn map_vars_nested<F>(term: &Arc<Term>, depth: usize, f: &mut F) -> Arc<Term>
+    where
+        F: FnMut(usize, usize) -> Option<Arc<Term>>,
+    {
+        match &**term {
+            Lit(_) | Float(_) | Char(_) | Str(_) | PrimFn(_) | Cell(_) | Chan(_) => {
+                Arc::clone(term)
             }
+            Var(index) => f(*index, depth).unwrap_or_else(|| Arc::clone(term)),
             Abs(body) => {
-                subs.shift(true, 0);
-                body.replace(index + 1, subs);
-                subs.shift(false, 0);
+                let new_body = Term::map_vars(body, depth + 1, f);
+                if Arc::ptr_eq(body, &new_body) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Abs(new_body))
+                }
             }
-            UnaryOp(_, t1) => {
-                t1.replace(index, subs);
+            Closure(code, env) => {
+                let new_env: Vec<_> = env
+                    .iter()
+                    .map(|term| Term::map_vars(term, depth, f))
+                    .collect();
+                if env
+                    .iter()
+                    .zip(&new_env)
+                    .all(|(term, new_term)| Arc::ptr_eq(term, new_term))
+                {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Closure(Arc::clone(code), new_env))
+                }
             }
-            BinaryOp(_, t1, t2) => {
-                t1.replace(index, subs);
-                t2.replace(index, subs);
+            UnaryOp(op, t1) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(UnaryOp(*op, new_t1))
+                }
+            }
+            BinaryOp(op, t1, t2) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                let new_t2 = Term::map_vars(t2, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) && Arc::ptr_eq(t2, &new_t2) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(BinaryOp(*op, new_t1, new_t2))
+                }
             }
             App(t1, t2) => {
-                t1.replace(index, subs);
-                t2.replace(index, subs);
+                let new_t1 = Term::map_vars(t1, depth, f);
+                let new_t2 = Term::map_vars(t2, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) && Arc::ptr_eq(t2, &new_t2) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(App(new_t1, new_t2))
+                }
+            }
+            Assign(t1, t2) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                let new_t2 = Term::map_vars(t2, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) && Arc::ptr_eq(t2, &new_t2) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Assign(new_t1, new_t2))
+                }
             }
             Cond(t1, t2, t3) => {
-                t1.replace(index, subs);
-                t2.replace(index, subs);
-                t3.replace(index, subs);
+                let new_t1 = Term::map_vars(t1, depth, f);
+                let new_t2 = Term::map_vars(t2, depth, f);
+                let new_t3 = Term::map_vars(t3, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) && Arc::ptr_eq(t2, &new_t2) && Arc::ptr_eq(t3, &new_t3)
+                {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Cond(new_t1, new_t2, new_t3))
+                }
             }
             Fix(t1) => {
-                t1.replace(index, subs);
+                let new_t1 = Term::map_vars(t1, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Fix(new_t1))
+                }
+            }
+            Ref(t1) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Ref(new_t1))
+                }
+            }
+            Deref(t1) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Deref(new_t1))
+                }
+            }
+            Assert(loc, t1) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Assert(*loc, new_t1))
+                }
+            }
+            Loc(loc, t1) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                if Arc::ptr_eq(t1, &new_t1) {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Loc(*loc, new_t1))
+                }
+            }
+            Native(index, args) => {
+                let new_args: Vec<_> = args
+                    .iter()
+                    .map(|arg| Term::map_vars(arg, depth, f))
+                    .collect();
+                if args
+                    .iter()
+                    .zip(&new_args)
+                    .all(|(arg, new_arg)| Arc::ptr_eq(arg, new_arg))
+                {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Native(*index, new_args))
+                }
+            }
+            Variant(index, name, args) => {
+                let new_args: Vec<_> = args
+                    .iter()
+                    .map(|arg| Term::map_vars(arg, depth, f))
+                    .collect();
+                if args
+                    .iter()
+                    .zip(&new_args)
+                    .all(|(arg, new_arg)| Arc::ptr_eq(arg, new_arg))
+                {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Variant(*index, Arc::clone(name), new_args))
+                }
+            }
+            List(elems) => {
+                let new_elems: Vec<_> = elems
+                    .iter()
+                    .map(|elem| Term::map_vars(elem, depth, f))
+                    .collect();
+                if elems
+                    .iter()
+                    .zip(&new_elems)
+                    .all(|(elem, new_elem)| Arc::ptr_eq(elem, new_elem))
+                {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(List(new_elems))
+                }
+            }
+            Match(t1, arms) => {
+                let new_t1 = Term::map_vars(t1, depth, f);
+                let new_arms: Vec<_> = arms
+                    .iter()
+                    .map(|(pattern, body)| {
+                        let binders = arm_binders(pattern, body);
+                        let new_body = Term::map_vars(body, depth + binders, f);
+                        (pattern.clone(), new_body)
+                    })
+                    .collect();
+                if Arc::ptr_eq(t1, &new_t1)
+                    && arms
+                        .iter()
+                        .zip(&new_arms)
+                        .all(|((_, body), (_, new_body))| Arc::ptr_eq(body, new_body))
+                {
+                    Arc::clone(term)
+                } else {
+                    Arc::new(Match(new_t1, new_arms))
+                }
             }
         }
     }
 }
+
+/// Returns the number of names bound around the body of an arm with `pattern`.
+///
+/// The body of an arm binding some names is a closure after the closure conversion, and the
+/// environment of the closure is outside of those names.
+fn arm_binders(pattern: &Pattern, body: &Term) -> usize {
+    match (pattern.binders(), body) {
+        (1.., Closure(_, _)) => 0,
+        (binders, _) => binders,
+    }
+}
+
+/// Serializes floats by their bit pattern, as formats like JSON cannot represent NaN or the
+/// infinities that a program can compute.
+#[cfg(feature = "serde")]
+mod float_bits {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(x: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(x.to_bits())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        u64::deserialize(deserializer).map(f64::from_bits)
+    }
+}
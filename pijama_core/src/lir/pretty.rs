@@ -0,0 +1,328 @@
+//! Readable layout of nameless terms.
+//!
+//! The [`Display`](fmt::Display) implementation of [`Term`] shows de Bruijn indices and keeps the
+//! whole term in a single line. The pretty-printer gives a name to each binder instead, shows the
+//! code of closures and arms in place of their environments, and breaks the terms that do not fit
+//! in the given width over several indented lines.
+//!
+//! Binders are named in the order they appear: `a` to `z`, followed by `a1` to `z1` and so on, so
+//! two binders never have the same name. The strict let bindings produced by
+//! the lowering are shown as `let` terms.
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::lir::{closure, Pattern, Term};
+
+/// The width used when a [`Pretty`] term is displayed without a width.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// A term displayed by the pretty-printer, returned by [`Term::pretty`].
+///
+/// The width of the formatter, if any, is the width of the layout, so `format!("{:40}", pretty)`
+/// lays out the term in 40 columns. Otherwise, [`DEFAULT_WIDTH`] is used.
+pub struct Pretty<'t>(&'t Term);
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = f.width().unwrap_or(DEFAULT_WIDTH);
+        f.write_str(&self.0.to_pretty_string(width))
+    }
+}
+
+impl Term {
+    /// Returns the term laid out by the pretty-printer, breaking the lines longer than `width`
+    /// columns when possible.
+    pub fn to_pretty_string(&self, width: usize) -> String {
+        let doc = Printer::default().doc(self);
+        render(&doc, width)
+    }
+
+    /// Returns a wrapper displaying the term with the pretty-printer.
+    pub fn pretty(&self) -> Pretty<'_> {
+        Pretty(self)
+    }
+}
+
+/// A document to be laid out.
+enum Doc {
+    Text(String),
+    /// A space if the innermost group around it fits in one line, or a new line otherwise.
+    Line,
+    /// A document whose new lines are indented by the given number of columns.
+    Nest(usize, Box<Doc>),
+    /// A document laid out in one line if it fits in the remaining width.
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+fn text(text: impl ToString) -> Doc {
+    Doc::Text(text.to_string())
+}
+
+fn nest(docs: Vec<Doc>) -> Doc {
+    Doc::Nest(2, Box::new(Doc::Concat(docs)))
+}
+
+fn group(docs: Vec<Doc>) -> Doc {
+    Doc::Group(Box::new(Doc::Concat(docs)))
+}
+
+/// Builds the documents of terms, keeping the names of the binders in scope.
+#[derive(Default)]
+struct Printer {
+    /// The names of the binders in scope, innermost last.
+    names: Vec<String>,
+    /// The number of binders named so far.
+    count: usize,
+}
+
+impl Printer {
+    fn doc(&mut self, term: &Term) -> Doc {
+        match term {
+            Term::Var(index) => match self.names.len().checked_sub(index + 1) {
+                Some(position) => text(&self.names[position]),
+                // The terms evaluated by the machine are closed, but any term can be printed.
+                None => text(format!("_{}", index - self.names.len())),
+            },
+            Term::Abs(body) => self.abs(body),
+            Term::Closure(code, env) => self.abs(&closure::open(code, env, 1)),
+            Term::UnaryOp(op, t1) => {
+                Doc::Concat(vec![text(format!("({}", op)), self.doc(t1), text(")")])
+            }
+            Term::BinaryOp(op, t1, t2) => group(vec![
+                text("("),
+                self.doc(t1),
+                nest(vec![Doc::Line, text(format!("{} ", op)), self.doc(t2)]),
+                text(")"),
+            ]),
+            Term::App(..) => {
+                // Applications to several arguments are shown together, even if the inner
+                // applications are located.
+                let mut args = Vec::new();
+                let mut head = term;
+                while let Term::App(t1, t2) = head {
+                    args.push(&**t2);
+                    head = t1;
+                    while let Term::Loc(_, t1) = head {
+                        head = t1;
+                    }
+                }
+                let head = self.doc(head);
+                self.call(head, args.into_iter().rev())
+            }
+            Term::Cond(t1, t2, t3) => group(vec![
+                text("(if "),
+                self.doc(t1),
+                Doc::Line,
+                text("then"),
+                nest(vec![Doc::Line, self.doc(t2)]),
+                Doc::Line,
+                text("else"),
+                nest(vec![Doc::Line, self.doc(t3)]),
+                text(")"),
+            ]),
+            Term::Fix(t1) => self.call(text("fix"), Some(&**t1)),
+            Term::Native(index, args) if args.is_empty() => text(format!("native#{}", index)),
+            Term::Native(index, args) => {
+                let head = text(format!("native#{}", index));
+                self.call(head, args.iter().map(|arg| &**arg))
+            }
+            Term::List(elems) => {
+                let elems = self.list(elems);
+                group(vec![text("["), elems, text("]")])
+            }
+            Term::Variant(_, name, args) if args.is_empty() => text(name),
+            Term::Variant(_, name, args) => {
+                let args = self.list(args);
+                group(vec![text(format!("{}(", name)), args, text(")")])
+            }
+            Term::Match(t1, arms) => match (&**t1, &arms[..]) {
+                (Term::Variant(0, name, values), [(Pattern::Variant(0, 1), body)])
+                    if name.is_empty() && values.len() == 1 =>
+                {
+                    self.strict_let(&values[0], body)
+                }
+                _ => self.match_(t1, arms),
+            },
+            Term::Ref(t1) => self.call(text("ref"), Some(&**t1)),
+            Term::Deref(t1) => Doc::Concat(vec![text("(!"), self.doc(t1), text(")")]),
+            Term::Assign(t1, t2) => group(vec![
+                text("("),
+                self.doc(t1),
+                text(" :="),
+                nest(vec![Doc::Line, self.doc(t2)]),
+                text(")"),
+            ]),
+            Term::Assert(_, t1) => self.call(text("assert"), Some(&**t1)),
+            Term::Loc(_, t1) => self.doc(t1),
+            Term::Lit(_)
+            | Term::Float(_)
+            | Term::Char(_)
+            | Term::Str(_)
+            | Term::PrimFn(_)
+            | Term::Cell(_)
+            | Term::Chan(_) => text(term),
+        }
+    }
+
+    /// Returns the document of an abstraction with the given body.
+    fn abs(&mut self, body: &Term) -> Doc {
+        let name = self.bind();
+        let body = self.doc(body);
+        self.names.pop();
+        group(vec![
+            text(format!("(λ{}.", name)),
+            nest(vec![Doc::Line, body]),
+            text(")"),
+        ])
+    }
+
+    /// Returns the document of `head` applied to `args`.
+    fn call<'t>(&mut self, head: Doc, args: impl IntoIterator<Item = &'t Term>) -> Doc {
+        let mut docs = Vec::new();
+        for arg in args {
+            docs.push(Doc::Line);
+            docs.push(self.doc(arg));
+        }
+        group(vec![text("("), head, nest(docs), text(")")])
+    }
+
+    /// Returns the document of the elements of a list or a variant, separated by commas.
+    fn list(&mut self, elems: &[Arc<Term>]) -> Doc {
+        let mut docs = Vec::new();
+        for (index, elem) in elems.iter().enumerate() {
+            if index > 0 {
+                docs.push(text(","));
+                docs.push(Doc::Line);
+            }
+            docs.push(self.doc(elem));
+        }
+        Doc::Nest(1, Box::new(Doc::Concat(docs)))
+    }
+
+    /// Returns the document of a strict let binding of `value` in `body`.
+    ///
+    /// The body of the binding goes in its own line if it does not fit, at the same indentation
+    /// as the binding, so a chain of bindings is shown as a block.
+    fn strict_let(&mut self, value: &Term, body: &Term) -> Doc {
+        let value = self.doc(value);
+        let (name, body) = self.arm_body(1, body);
+        group(vec![
+            text(format!("(let {} =", name[0])),
+            nest(vec![Doc::Line, value]),
+            text(" in"),
+            Doc::Line,
+            body,
+            text(")"),
+        ])
+    }
+
+    fn match_(&mut self, t1: &Term, arms: &[(Pattern, Arc<Term>)]) -> Doc {
+        let mut docs = vec![text("(match "), self.doc(t1), text(" with")];
+        let mut arm_docs = Vec::new();
+        for (pattern, body) in arms {
+            let (names, body) = self.arm_body(pattern.binders(), body);
+            let pattern = match pattern {
+                Pattern::Variant(index, _) => format!("#{}({})", index, names.join(", ")),
+                pattern => pattern.to_string(),
+            };
+            arm_docs.push(Doc::Line);
+            arm_docs.push(group(vec![
+                text(format!("| {} =>", pattern)),
+                nest(vec![Doc::Line, body]),
+            ]));
+        }
+        docs.push(nest(arm_docs));
+        docs.push(text(")"));
+        group(docs)
+    }
+
+    /// Returns the names bound by an arm with `binders` names and the document of its body.
+    fn arm_body(&mut self, binders: usize, body: &Term) -> (Vec<String>, Doc) {
+        let names: Vec<_> = (0..binders).map(|_| self.bind()).collect();
+        let doc = match body {
+            Term::Closure(code, env) if binders > 0 => self.doc(&closure::open(code, env, binders)),
+            body => self.doc(body),
+        };
+        self.names.truncate(self.names.len() - binders);
+        (names, doc)
+    }
+
+    /// Adds a new binder to the scope and returns its name.
+    fn bind(&mut self) -> String {
+        let letter = char::from(b'a' + (self.count % 26) as u8);
+        let name = match self.count / 26 {
+            0 => letter.to_string(),
+            suffix => format!("{}{}", letter, suffix),
+        };
+        self.count += 1;
+        self.names.push(name.clone());
+        name
+    }
+}
+
+/// Lays out `doc` in `width` columns.
+fn render(doc: &Doc, width: usize) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    // The documents left to lay out, with their indentation and whether they are in one line.
+    let mut stack = vec![(0, false, doc)];
+    while let Some((indent, flat, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(text) => {
+                output.push_str(text);
+                column += text.chars().count();
+            }
+            Doc::Line if flat => {
+                output.push(' ');
+                column += 1;
+            }
+            Doc::Line => {
+                output.push('\n');
+                output.extend(core::iter::repeat_n(' ', indent));
+                column = indent;
+            }
+            Doc::Nest(amount, doc) => stack.push((indent + amount, flat, doc)),
+            Doc::Group(doc) => {
+                let flat = flat || fits(doc, width.saturating_sub(column));
+                stack.push((indent, flat, doc));
+            }
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+            }
+        }
+    }
+    output
+}
+
+/// Returns `true` if `doc` laid out in one line is at most `width` columns long.
+fn fits(doc: &Doc, width: usize) -> bool {
+    let mut remaining = width;
+    let mut stack = vec![doc];
+    while let Some(doc) = stack.pop() {
+        let len = match doc {
+            Doc::Text(text) => text.chars().count(),
+            Doc::Line => 1,
+            Doc::Nest(_, doc) | Doc::Group(doc) => {
+                stack.push(doc);
+                0
+            }
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter());
+                0
+            }
+        };
+        remaining = match remaining.checked_sub(len) {
+            Some(remaining) => remaining,
+            None => return false,
+        };
+    }
+    true
+}
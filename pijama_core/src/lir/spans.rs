@@ -0,0 +1,61 @@
+//! The nodes that the located terms of a nameless term were lowered from.
+//!
+//! Nameless terms only keep the locations of the terms that can fail at runtime, see
+//! [`Term::Loc`]. The [`SpanTable`] returned by [`Term::from_mir_with_spans`] keeps the
+//! [`NodeId`] of the node each of these terms was lowered from, so a runtime error can be traced
+//! back to a node of the AST.
+//!
+//! [`Term::Loc`]: crate::lir::Term::Loc
+//! [`Term::from_mir_with_spans`]: crate::lir::Term::from_mir_with_spans
+use alloc::vec::Vec;
+
+use pijama_ast::{Location, NodeId};
+
+/// The id of the node each located term of a nameless term was lowered from.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SpanTable {
+    /// The location and node of each located term lowered from a node, in the order they were
+    /// lowered, which puts the terms inside another one before it.
+    spans: Vec<(Location, NodeId)>,
+}
+
+impl SpanTable {
+    /// Returns the id of the node that the located terms with the location `loc` were lowered
+    /// from, or `None` if no located term has that location or it was not lowered from a node.
+    ///
+    /// If terms lowered from different nodes have the same location, the innermost of them is
+    /// used.
+    pub fn node(&self, loc: Location) -> Option<NodeId> {
+        self.spans
+            .iter()
+            .find(|(other, _)| *other == loc)
+            .map(|(_, id)| *id)
+    }
+
+    /// Returns the location of the located terms lowered from the node with the id `id`, or
+    /// `None` if none of them was lowered from that node.
+    pub fn loc(&self, id: NodeId) -> Option<Location> {
+        self.spans
+            .iter()
+            .find(|(_, other)| *other == id)
+            .map(|(loc, _)| *loc)
+    }
+
+    /// Returns the number of located terms lowered from a node.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns `true` if no located term was lowered from a node.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Adds a located term with the location `loc` lowered from the node with the id `id`,
+    /// unless the id is [`NodeId::DUMMY`].
+    pub(super) fn insert(&mut self, loc: Location, id: NodeId) {
+        if !id.is_dummy() {
+            self.spans.push((loc, id));
+        }
+    }
+}
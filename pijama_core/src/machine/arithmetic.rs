@@ -1,97 +1,228 @@
-use std::convert::TryFrom;
+use core::convert::TryFrom;
 
-use pijama_ast::{BinOp, BinOp::*, UnOp, UnOp::*};
+use pijama_ast::{BinOp, BinOp::*, Int, IntWidth, Primitive, UnOp, UnOp::*};
 
 /// Trait determining how arithmetic operations should be handled.
+///
+/// The operands fit in the given [`IntWidth`], and so must the result. The operations return
+/// `None` when the result overflows and the evaluation must stop with a
+/// [`RuntimeError::Overflow`](crate::machine::RuntimeError). The machine stops with an error before
+/// dividing by zero, so the second operand of `/` and `%` is never zero.
 pub trait Arithmetic {
-    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> i64;
-    fn unary_operation(op: UnOp, n: i64) -> i64;
+    fn binary_operation(op: BinOp, n1: Int, n2: Int, width: IntWidth) -> Option<Int>;
+    fn unary_operation(op: UnOp, n: Int, width: IntWidth) -> Option<Int>;
 }
 
-/// Regular arithmetic that is allowed to overflow or panic when dividing by zero.
-pub struct OverflowArithmetic;
+/// Arithmetic that wraps around the bounds of integers when overflowing.
+///
+/// Shifts only use the lowest bits of their amount, six of them for 64-bit integers.
+pub struct WrappingArithmetic;
 
-impl Arithmetic for OverflowArithmetic {
-    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> i64 {
+impl Arithmetic for WrappingArithmetic {
+    fn binary_operation(op: BinOp, n1: Int, n2: Int, width: IntWidth) -> Option<Int> {
+        let result = match op {
+            Add => n1.wrapping_add(n2),
+            Sub => n1.wrapping_sub(n2),
+            Mul => n1.wrapping_mul(n2),
+            Div => n1.wrapping_div(n2),
+            Rem => n1.wrapping_rem(n2),
+            Shr => n1 >> (n2 as u32 & (width.bits() - 1)),
+            Shl => n1 << (n2 as u32 & (width.bits() - 1)),
+            op => return comparison_or_bitwise(op, n1, n2),
+        };
+        Some(wrap(result, width))
+    }
+
+    fn unary_operation(op: UnOp, n: Int, width: IntWidth) -> Option<Int> {
         match op {
-            Add => n1 + n2,
-            Sub => n1 - n2,
-            Mul => n1 * n2,
-            Div => n1 / n2,
-            Rem => n1 % n2,
-            Lt => (n1 < n2).into(),
-            Lte => (n1 <= n2).into(),
-            Gt => (n1 > n2).into(),
-            Gte => (n1 >= n2).into(),
-            Eq => (n1 == n2).into(),
-            Neq => (n1 != n2).into(),
-            BitAnd | And => n1 & n2,
-            BitOr | Or => n1 | n2,
-            BitXor => n1 ^ n2,
-            Shr => n1 >> n2,
-            Shl => n1 << n2,
+            Neg => Some(wrap(n.wrapping_neg(), width)),
+            op => Some(not(op, n)),
         }
     }
+}
+
+/// Arithmetic that gives the closest integer to the result when overflowing.
+///
+/// Shift amounts are clamped to be less than the number of bits, and left shifts saturate like
+/// multiplications by a power of two.
+pub struct SaturatingArithmetic;
+
+impl Arithmetic for SaturatingArithmetic {
+    fn binary_operation(op: BinOp, n1: Int, n2: Int, width: IntWidth) -> Option<Int> {
+        let max_shift = Int::from(width.bits() - 1);
+        let result = match op {
+            Add => n1.saturating_add(n2),
+            Sub => n1.saturating_sub(n2),
+            Mul => n1.saturating_mul(n2),
+            Div => n1.saturating_div(n2),
+            // The remainder of `MIN / -1` is zero, only its quotient overflows.
+            Rem => n1.wrapping_rem(n2),
+            Shr => n1 >> n2.clamp(0, max_shift),
+            Shl => {
+                let shifted = n1 << n2.clamp(0, max_shift);
+                if shifted >> n2.clamp(0, max_shift) == n1 {
+                    shifted
+                } else if n1 < 0 {
+                    Int::MIN
+                } else {
+                    Int::MAX
+                }
+            }
+            op => return comparison_or_bitwise(op, n1, n2),
+        };
+        Some(result.clamp(width.min(), width.max()))
+    }
 
-    fn unary_operation(op: UnOp, n: i64) -> i64 {
+    fn unary_operation(op: UnOp, n: Int, width: IntWidth) -> Option<Int> {
         match op {
-            Neg => -n,
-            Not => !n,
+            Neg => Some(n.saturating_neg().min(width.max())),
+            op => Some(not(op, n)),
         }
     }
 }
 
-/// Checked arithmetic that panics when overflowing or dividing by zero.
+/// Checked arithmetic that stops the evaluation when overflowing.
+///
+/// Shifting by a negative amount or by at least the number of bits is an overflow.
 pub struct CheckedArithmetic;
 
 impl Arithmetic for CheckedArithmetic {
-    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> i64 {
-        let (result, overflowed) = match op {
-            Add => n1.overflowing_add(n2),
-            Sub => n1.overflowing_sub(n2),
-            Mul => n1.overflowing_mul(n2),
-            Div => n1.overflowing_div(n2),
-            Rem => n1.overflowing_rem(n2),
-            Lt => ((n1 < n2).into(), false),
-            Lte => ((n1 <= n2).into(), false),
-            Gt => ((n1 > n2).into(), false),
-            Gte => ((n1 >= n2).into(), false),
-            Eq => ((n1 == n2).into(), false),
-            Neq => ((n1 != n2).into(), false),
-            BitAnd | And => (n1 & n2, false),
-            BitOr | Or => (n1 | n2, false),
-            BitXor => (n1 ^ n2, false),
-            Shr => n1.overflowing_shr(try_into_u32_or_panic(n2)),
-            Shl => n1.overflowing_shl(try_into_u32_or_panic(n2)),
+    fn binary_operation(op: BinOp, n1: Int, n2: Int, width: IntWidth) -> Option<Int> {
+        let result = match op {
+            Add => n1.checked_add(n2)?,
+            Sub => n1.checked_sub(n2)?,
+            Mul => n1.checked_mul(n2)?,
+            Div => n1.checked_div(n2)?,
+            // The remainder of `MIN / -1` overflows like its quotient.
+            Rem if n1 == width.min() && n2 == -1 => return None,
+            Rem => n1 % n2,
+            Shr => n1 >> shift_amount(n2, width)?,
+            // Like Rust's `checked_shl`, only the amount is checked and the bits shifted out are
+            // lost.
+            Shl => return Some(wrap(n1 << shift_amount(n2, width)?, width)),
+            op => return comparison_or_bitwise(op, n1, n2),
         };
+        Some(result).filter(|result| width.contains(*result))
+    }
 
-        if overflowed {
-            panic!(
-                "Binary operation `{}` overflowed with operands `{}` and `{}`",
-                op, n1, n2
-            )
+    fn unary_operation(op: UnOp, n: Int, width: IntWidth) -> Option<Int> {
+        match op {
+            Neg => n.checked_neg().filter(|n| width.contains(*n)),
+            op => Some(not(op, n)),
         }
+    }
+}
 
-        result
+/// Returns `n` wrapped around the bounds of `width`.
+fn wrap(n: Int, width: IntWidth) -> Int {
+    match width {
+        IntWidth::I64 => Int::from(n as i64),
+        IntWidth::I128 => n,
     }
+}
 
-    fn unary_operation(op: UnOp, n: i64) -> i64 {
-        let (result, overflowed) = match op {
-            Neg => n.overflowing_neg(),
-            Not => (!n, false),
-        };
+/// Returns the amount of a checked shift, or `None` if it is negative or not less than the number
+/// of bits of `width`.
+fn shift_amount(n: Int, width: IntWidth) -> Option<u32> {
+    u32::try_from(n)
+        .ok()
+        .filter(|amount| *amount < width.bits())
+}
 
-        if overflowed {
-            panic!("Unary operation `{}` overflowed with operand `{}`", op, n)
+/// Returns the result of a math primitive taking one integer (`abs` or `sign`), or `None` if it
+/// overflows.
+pub(super) fn unary_primitive<A: Arithmetic>(
+    prim: Primitive,
+    n: Int,
+    width: IntWidth,
+) -> Option<Int> {
+    match prim {
+        Primitive::Abs if n < 0 => A::unary_operation(Neg, n, width),
+        Primitive::Abs => Some(n),
+        Primitive::Sign => Some(n.signum()),
+        prim => unreachable!("`{}` does not take one integer", prim),
+    }
+}
+
+/// Returns the result of a math primitive taking two integers (`min`, `max`, `gcd` or `pow`), or
+/// `None` if it overflows.
+///
+/// Negative powers are rounded towards zero like divisions, so the machine must stop with an
+/// error before raising zero to a negative power.
+pub(super) fn binary_primitive<A: Arithmetic>(
+    prim: Primitive,
+    n1: Int,
+    n2: Int,
+    width: IntWidth,
+) -> Option<Int> {
+    match prim {
+        Primitive::Min => Some(n1.min(n2)),
+        Primitive::Max => Some(n1.max(n2)),
+        Primitive::Gcd => {
+            let (mut a, mut b) = (n1.unsigned_abs(), n2.unsigned_abs());
+            while b != 0 {
+                let r = a % b;
+                a = b;
+                b = r;
+            }
+            // The only result that does not fit is `-MIN`, when both numbers are `MIN` or zero.
+            match Int::try_from(a) {
+                Ok(gcd) if width.contains(gcd) => Some(gcd),
+                _ => A::unary_operation(Neg, width.min(), width),
+            }
         }
+        Primitive::Pow => pow::<A>(n1, n2, width),
+        prim => unreachable!("`{}` does not take two integers", prim),
+    }
+}
 
-        result
+/// Raises `base` to the power of `exp` by squaring, using the multiplication of `A`.
+fn pow<A: Arithmetic>(base: Int, exp: Int, width: IntWidth) -> Option<Int> {
+    if exp < 0 {
+        return match base {
+            1 => Some(1),
+            -1 if exp % 2 == 0 => Some(1),
+            -1 => Some(-1),
+            _ => Some(0),
+        };
     }
+    let (mut base, mut exp, mut result) = (base, exp, 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = A::binary_operation(Mul, result, base, width)?;
+        }
+        exp >>= 1;
+        // The base is only squared if it is used again, so it cannot overflow otherwise.
+        if exp > 0 {
+            base = A::binary_operation(Mul, base, base, width)?;
+        }
+    }
+    Some(result)
+}
+
+/// Returns the result of the binary operations that cannot overflow.
+fn comparison_or_bitwise(op: BinOp, n1: Int, n2: Int) -> Option<Int> {
+    let result = match op {
+        Lt => (n1 < n2).into(),
+        Lte => (n1 <= n2).into(),
+        Gt => (n1 > n2).into(),
+        Gte => (n1 >= n2).into(),
+        Eq => (n1 == n2).into(),
+        Neq => (n1 != n2).into(),
+        BitAnd | And => n1 & n2,
+        BitOr | Or => n1 | n2,
+        BitXor => n1 ^ n2,
+        Pipe => unreachable!("Pipes are lowered to applications"),
+        Add | Sub | Mul | Div | Rem | Shr | Shl => unreachable!("`{}` can overflow", op),
+    };
+    Some(result)
 }
 
-fn try_into_u32_or_panic(n: i64) -> u32 {
-    match u32::try_from(n) {
-        Ok(n) => n,
-        _ => panic!("Operand `{}` is negative", n),
+/// Returns the result of the unary operations that cannot overflow.
+fn not(op: UnOp, n: Int) -> Int {
+    match op {
+        Not => (n == 0).into(),
+        BitNot => !n,
+        Neg => unreachable!("Negation can overflow"),
     }
 }
@@ -1,44 +1,233 @@
-use std::io::{Stdout, Write};
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::io::Stdout;
+
+use pijama_ast::{IntWidth, Location};
 
 use crate::machine::{
-    arithmetic::{Arithmetic, OverflowArithmetic},
-    env::Env,
+    arithmetic::{Arithmetic, WrappingArithmetic},
+    capabilities::Capabilities,
+    env::{Env, Input, Output},
+    native::{NativeFn, NativeModule},
+    random::Rng,
+    threads::Threads,
     Machine,
 };
 
-pub struct MachineBuilder<W: Write, A: Arithmetic> {
+pub struct MachineBuilder<W: Output, A: Arithmetic> {
     env: Env<W>,
     arithmetic: A,
+    int_width: IntWidth,
+    natives: Vec<NativeFn>,
+    yield_interval: usize,
+    fuel: Option<u64>,
+    max_depth: usize,
+    seed: Option<u64>,
+    capabilities: Capabilities,
+    deterministic: bool,
 }
 
-impl Default for MachineBuilder<Stdout, OverflowArithmetic> {
-    fn default() -> Self {
+/// The default number of steps that `Machine::evaluate_async` runs before yielding.
+const DEFAULT_YIELD_INTERVAL: usize = 10_000;
+
+/// The default number of nested calls after which the evaluation stops with
+/// `RuntimeError::StackOverflow`.
+///
+/// With the `std` feature, deep evaluations continue in new segments of the stack allocated on
+/// the heap, so the limit bounds the memory they take, around 60 MiB in optimized builds.
+#[cfg(feature = "std")]
+const DEFAULT_MAX_DEPTH: usize = 20_000;
+
+/// The default number of nested calls without the `std` feature, where the stack of the host
+/// cannot grow. Each call nests around five evaluations, which can take a few kilobytes of the
+/// host stack each in unoptimized builds, so this fits in a stack of 8 MiB, the usual size of the
+/// stack of the main thread.
+#[cfg(not(feature = "std"))]
+const DEFAULT_MAX_DEPTH: usize = 200;
+
+impl<W: Output> MachineBuilder<W, WrappingArithmetic> {
+    pub fn new(env: Env<W>) -> Self {
         MachineBuilder {
-            env: Env::default(),
-            arithmetic: OverflowArithmetic,
+            env,
+            arithmetic: WrappingArithmetic,
+            int_width: IntWidth::default(),
+            natives: Vec::new(),
+            yield_interval: DEFAULT_YIELD_INTERVAL,
+            fuel: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            seed: None,
+            capabilities: Capabilities::all(),
+            deterministic: false,
         }
     }
 }
 
-impl<W: Write, A: Arithmetic> MachineBuilder<W, A> {
+#[cfg(feature = "std")]
+impl Default for MachineBuilder<Stdout, WrappingArithmetic> {
+    fn default() -> Self {
+        MachineBuilder::new(Env::default())
+    }
+}
+
+impl<W: Output, A: Arithmetic> MachineBuilder<W, A> {
     pub fn build(self) -> Machine<W, A> {
         Machine {
             env: self.env,
             _arithmetic: self.arithmetic,
+            int_width: self.int_width,
+            natives: self.natives,
+            yield_interval: self.yield_interval,
+            budget: None,
+            fuel: self.fuel,
+            cells: Vec::new(),
+            channels: Vec::new(),
+            threads: Threads::default(),
+            error: None,
+            loc: Location::new(0, 0),
+            depth: 0,
+            calls: Vec::new(),
+            stack_trace: Vec::new(),
+            max_depth: self.max_depth,
+            stats: None,
+            hook: None,
+            breakpoints: Vec::new(),
+            breakpoint: None,
+            frames: Vec::new(),
+            paused: false,
+            rng: self.seed.map_or_else(Rng::from_entropy, Rng::new),
+            capabilities: self.capabilities,
+            deterministic: self.deterministic,
         }
     }
 
-    pub fn with_env<W2: Write>(self, env: Env<W2>) -> MachineBuilder<W2, A> {
+    pub fn with_env<W2: Output>(self, env: Env<W2>) -> MachineBuilder<W2, A> {
         MachineBuilder {
             env,
             arithmetic: self.arithmetic,
+            int_width: self.int_width,
+            natives: self.natives,
+            yield_interval: self.yield_interval,
+            fuel: self.fuel,
+            max_depth: self.max_depth,
+            seed: self.seed,
+            capabilities: self.capabilities,
+            deterministic: self.deterministic,
         }
     }
 
+    /// Sets the source of the lines read by `read_line` and `read_int`.
+    pub fn with_input(mut self, input: impl Input + Send + 'static) -> Self {
+        self.env = self.env.with_input(input);
+        self
+    }
+
     pub fn with_arithmetic<A2: Arithmetic>(self, arithmetic: A2) -> MachineBuilder<W, A2> {
         MachineBuilder {
             env: self.env,
             arithmetic,
+            int_width: self.int_width,
+            natives: self.natives,
+            yield_interval: self.yield_interval,
+            fuel: self.fuel,
+            max_depth: self.max_depth,
+            seed: self.seed,
+            capabilities: self.capabilities,
+            deterministic: self.deterministic,
         }
     }
+
+    /// Sets the width of the integers, which is 64 bits by default.
+    ///
+    /// Results that do not fit in the width overflow as decided by the arithmetic of the machine.
+    /// Programs must be type-checked with the same width, see
+    /// [`ty_check_with_width`](crate::ty::ty_check_with_width), so their literals fit in it.
+    pub fn with_int_width(mut self, width: IntWidth) -> Self {
+        self.int_width = width;
+        self
+    }
+
+    /// Sets the number of evaluation steps that `Machine::evaluate_async` runs before yielding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `steps` is zero.
+    pub fn with_yield_interval(mut self, steps: usize) -> Self {
+        assert!(steps > 0, "The yield interval must be positive");
+        self.yield_interval = steps;
+        self
+    }
+
+    /// Limits the number of evaluation steps that the machine can run.
+    ///
+    /// The evaluation stops with `RuntimeError::OutOfFuel` when it needs more than `steps` steps.
+    /// The limit is shared by all the terms evaluated by the machine, so it can be used to stop
+    /// programs that do not terminate.
+    pub fn with_fuel(mut self, steps: u64) -> Self {
+        self.fuel = Some(steps);
+        self
+    }
+
+    /// Limits the number of nested calls, which grow with the calls that are not in tail
+    /// position.
+    ///
+    /// The evaluation stops with `RuntimeError::StackOverflow` when a call is entered while
+    /// `depth` calls are already being evaluated.
+    /// With the `std` feature the stack of the host grows as needed, so the limit only bounds the
+    /// memory used by deep evaluations. Without it, the default limit fits in a stack of 8 MiB,
+    /// so machines running on threads with smaller stacks must lower it, and those running on
+    /// threads with larger stacks can raise it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is zero.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        assert!(depth > 0, "The maximum depth must be positive");
+        self.max_depth = depth;
+        self
+    }
+
+    /// Seeds the generator of the numbers returned by `random`.
+    ///
+    /// Machines built with the same seed return the same numbers, so programs using `random` can
+    /// be run deterministically. Otherwise the generator is seeded by the operating system.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the effects that programs are allowed to have.
+    ///
+    /// Programs using a denied capability stop with `RuntimeError::CapabilityDisabled`. Every
+    /// capability is allowed by default.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Makes the machine reject the programs whose result could change between runs.
+    ///
+    /// Programs using `print`, `read_line`, `read_int` or `random` stop with
+    /// `RuntimeError::Nondeterministic` before they are evaluated, even if their capabilities are
+    /// mocked. Every other primitive computes the same result on every run and platform, so the
+    /// programs accepted by the machine can be used as reproducible configuration. Native
+    /// functions are not checked, so the embedder must only register deterministic ones.
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Registers a native function.
+    ///
+    /// If a function with the same name was registered before, the new one shadows it.
+    pub fn with_native(mut self, native: NativeFn) -> Self {
+        self.natives.push(native);
+        self
+    }
+
+    /// Registers all the functions of a native module.
+    pub fn with_module(mut self, module: impl NativeModule) -> Self {
+        self.natives.extend(module.functions());
+        self
+    }
 }
@@ -0,0 +1,204 @@
+//! The effects that programs are allowed to have.
+//!
+//! The primitives that reach outside of the machine, like `print`, `read_line` or `random`, each
+//! need a [`Capability`]. The [`Capabilities`] of a machine, set with
+//! [`MachineBuilder::with_capabilities`], allow or deny each of them, or replace them by a mock
+//! implementation chosen by the embedder. Using a denied capability stops the evaluation with
+//! [`RuntimeError::CapabilityDisabled`].
+//!
+//! [`MachineBuilder::with_capabilities`]: crate::machine::MachineBuilder::with_capabilities
+use alloc::{boxed::Box, string::String};
+use core::fmt::{self, Display, Formatter};
+
+use pijama_ast::{Int, Location, Primitive};
+
+use crate::machine::{
+    arithmetic::Arithmetic,
+    env::{Input, Output},
+    Machine, RuntimeError,
+};
+
+/// An effect that a program can have outside of the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Writing to the output with `print`.
+    Print,
+    /// Reading the input with `read_line` and `read_int`.
+    Read,
+    /// Getting random numbers with `random`.
+    Random,
+}
+
+impl Capability {
+    /// Returns the capability needed by the primitive `prim`, or `None` if it has no effects
+    /// outside of the machine.
+    pub fn of(prim: Primitive) -> Option<Self> {
+        match prim {
+            Primitive::Print => Some(Capability::Print),
+            Primitive::ReadLine | Primitive::ReadInt => Some(Capability::Read),
+            Primitive::Random => Some(Capability::Random),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Capability::Print => write!(f, "print"),
+            Capability::Read => write!(f, "read"),
+            Capability::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// How a program can use a capability.
+enum Access<T> {
+    Allowed,
+    Denied,
+    Mocked(T),
+}
+
+impl<T> Access<T> {
+    fn new(allowed: bool) -> Self {
+        if allowed {
+            Access::Allowed
+        } else {
+            Access::Denied
+        }
+    }
+}
+
+/// The signature of the mocks of `print`.
+type PrintFn = dyn FnMut(&str) + Send;
+
+/// The signature of the mocks of `random`.
+type RandomFn = dyn FnMut(Int, Int) -> Int + Send;
+
+/// The capabilities of a machine.
+///
+/// By default every capability is allowed.
+pub struct Capabilities {
+    print: Access<Box<PrintFn>>,
+    read: Access<Box<dyn Input + Send>>,
+    random: Access<Box<RandomFn>>,
+}
+
+impl Capabilities {
+    /// Returns capabilities allowing every effect.
+    pub fn all() -> Self {
+        Capabilities {
+            print: Access::Allowed,
+            read: Access::Allowed,
+            random: Access::Allowed,
+        }
+    }
+
+    /// Returns capabilities denying every effect.
+    pub fn none() -> Self {
+        Capabilities {
+            print: Access::Denied,
+            read: Access::Denied,
+            random: Access::Denied,
+        }
+    }
+
+    /// Allows `capability`, removing its mock if it had one.
+    pub fn allow(self, capability: Capability) -> Self {
+        self.set(capability, true)
+    }
+
+    /// Denies `capability`, removing its mock if it had one.
+    pub fn deny(self, capability: Capability) -> Self {
+        self.set(capability, false)
+    }
+
+    fn set(mut self, capability: Capability, allowed: bool) -> Self {
+        match capability {
+            Capability::Print => self.print = Access::new(allowed),
+            Capability::Read => self.read = Access::new(allowed),
+            Capability::Random => self.random = Access::new(allowed),
+        }
+        self
+    }
+
+    /// Returns `true` if `capability` is allowed or mocked.
+    pub fn allows(&self, capability: Capability) -> bool {
+        let denied = match capability {
+            Capability::Print => matches!(self.print, Access::Denied),
+            Capability::Read => matches!(self.read, Access::Denied),
+            Capability::Random => matches!(self.random, Access::Denied),
+        };
+        !denied
+    }
+
+    /// Allows printing, calling `print` with each printed line, without its line break, instead
+    /// of writing it to the output of the machine.
+    pub fn mock_print(mut self, print: impl FnMut(&str) + Send + 'static) -> Self {
+        self.print = Access::Mocked(Box::new(print));
+        self
+    }
+
+    /// Allows reading, reading the lines from `input` instead of the input of the machine.
+    pub fn mock_read(mut self, input: impl Input + Send + 'static) -> Self {
+        self.read = Access::Mocked(Box::new(input));
+        self
+    }
+
+    /// Allows random numbers, calling `random` with the bounds given to each `random` call
+    /// instead of using the generator of the machine.
+    pub fn mock_random(mut self, random: impl FnMut(Int, Int) -> Int + Send + 'static) -> Self {
+        self.random = Access::Mocked(Box::new(random));
+        self
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::all()
+    }
+}
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Prints `args` followed by a line break, as the `print` at `loc`.
+    pub(super) fn print_line(
+        &mut self,
+        loc: Location,
+        args: fmt::Arguments,
+    ) -> Result<(), RuntimeError> {
+        match &mut self.capabilities.print {
+            Access::Allowed => writeln!(self.env.stdout(), "{}", args)
+                .map_err(|_| RuntimeError::OutputFailed(loc))?,
+            Access::Denied => return Err(RuntimeError::CapabilityDisabled(loc, Capability::Print)),
+            Access::Mocked(print) => print(&alloc::fmt::format(args)),
+        }
+        Ok(())
+    }
+
+    /// Reads a line of the input, as the `read_line` or `read_int` at `loc`.
+    ///
+    /// Reading past the end of the input returns an empty line.
+    pub(super) fn read_line(&mut self, loc: Location) -> Result<String, RuntimeError> {
+        let line = match &mut self.capabilities.read {
+            Access::Allowed => self.env.stdin().read_line(),
+            Access::Denied => return Err(RuntimeError::CapabilityDisabled(loc, Capability::Read)),
+            Access::Mocked(input) => input.read_line(),
+        };
+        Ok(line.unwrap_or_default())
+    }
+
+    /// Returns a random number between `lo` and `hi`, as the `random` at `loc`.
+    pub(super) fn random(&mut self, loc: Location, lo: Int, hi: Int) -> Result<Int, RuntimeError> {
+        match &mut self.capabilities.random {
+            Access::Allowed => Ok(self.rng.between(lo, hi)),
+            Access::Denied => Err(RuntimeError::CapabilityDisabled(loc, Capability::Random)),
+            Access::Mocked(random) => Ok(random(lo, hi)),
+        }
+    }
+
+    /// Returns `true` if `print` writes to the output of the machine.
+    #[cfg(feature = "jit")]
+    pub(super) fn prints_to_output(&self) -> bool {
+        matches!(self.capabilities.print, Access::Allowed)
+    }
+}
@@ -0,0 +1,141 @@
+//! Hooks and breakpoints for debuggers.
+//!
+//! A hook set with [`Machine::set_hook`] is called with an [`EvalEvent`] each time a function is
+//! entered or exited and each time the evaluation reaches a breakpoint added with
+//! [`Machine::add_breakpoint`]. The evaluation waits for the hook to return, and the hook can
+//! pause it by returning [`Control::Pause`]. The paused term is returned by [`Machine::debug`], so
+//! it can be inspected before resuming the evaluation.
+use alloc::{boxed::Box, sync::Arc};
+
+use pijama_ast::Location;
+
+use crate::{
+    lir::Term,
+    machine::{arithmetic::Arithmetic, env::Output, threads::Threads, Machine, RuntimeError},
+};
+
+/// The type of the hooks called by the machine.
+pub type Hook = dyn FnMut(&EvalEvent) -> Control + Send;
+
+/// An event of the evaluation reported to the hook.
+///
+/// The locations are the ones of the innermost located term being evaluated, which are the
+/// operations and the applications of primitives.
+#[derive(Debug)]
+pub enum EvalEvent<'t> {
+    /// A function is applied to `arg`. `env` has the values captured by the function if it is a
+    /// closure.
+    Enter {
+        loc: Location,
+        arg: &'t Term,
+        env: &'t [Arc<Term>],
+    },
+    /// A function entered before evaluated to `value`. A function called in tail position exits
+    /// together with its caller.
+    Exit { loc: Location, value: &'t Term },
+    /// The evaluation reached the breakpoint `breakpoint`.
+    Breakpoint { loc: Location, breakpoint: Location },
+}
+
+/// What the machine must do after calling a hook.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Control {
+    /// Continue the evaluation.
+    Continue,
+    /// Pause the evaluation after the current step.
+    Pause,
+}
+
+/// The result of [`Machine::debug`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugState {
+    /// The evaluation was paused by the hook. Debugging this term resumes the evaluation.
+    Paused(Term),
+    /// The term cannot be evaluated any further.
+    Done(Term),
+}
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Sets the hook called with the events of the evaluation, replacing the previous one.
+    pub fn set_hook(&mut self, hook: impl FnMut(&EvalEvent) -> Control + Send + 'static) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Adds a breakpoint reached when the evaluation enters a term whose location starts inside
+    /// `loc`.
+    pub fn add_breakpoint(&mut self, loc: Location) {
+        self.breakpoints.push(loc);
+    }
+
+    /// Removes the breakpoints added with the location `loc`.
+    pub fn remove_breakpoint(&mut self, loc: Location) {
+        self.breakpoints.retain(|breakpoint| *breakpoint != loc);
+    }
+
+    /// Evaluates `term` until the hook pauses the evaluation or the term cannot be evaluated any
+    /// further.
+    ///
+    /// [`Machine::evaluate`] resumes the evaluation each time it is paused instead.
+    pub fn debug(&mut self, term: Term) -> Result<DebugState, RuntimeError> {
+        self.check_determinism(&term)?;
+        let term = self.run(term);
+        if let Some(error) = self.error.take() {
+            // The evaluation cannot be resumed, so its threads and its term are discarded. The
+            // term can be nested as deeply as the evaluation was.
+            Term::drop_iteratively(term);
+            self.threads = Threads::default();
+            Err(error)
+        } else if core::mem::take(&mut self.paused) {
+            Ok(DebugState::Paused(term))
+        } else {
+            Ok(DebugState::Done(term))
+        }
+    }
+
+    /// Calls the hook with `event` and pauses the evaluation if the hook asks to.
+    pub(super) fn notify(&mut self, event: EvalEvent) {
+        if let Some(hook) = &mut self.hook {
+            if hook(&event) == Control::Pause {
+                self.paused = true;
+            }
+        }
+    }
+
+    /// Notifies the hook if the evaluation enters a breakpoint with the current step.
+    pub(super) fn check_breakpoints(&mut self) {
+        let loc = self.loc;
+        let breakpoint = self
+            .breakpoints
+            .iter()
+            .find(|breakpoint| breakpoint.start <= loc.start && loc.start < breakpoint.end)
+            .copied();
+        if let Some(entered) = breakpoint.filter(|_| breakpoint != self.breakpoint) {
+            self.notify(EvalEvent::Breakpoint {
+                loc,
+                breakpoint: entered,
+            });
+        }
+        self.breakpoint = breakpoint;
+    }
+
+    /// Records that a function was entered in the current evaluation and notifies the hook.
+    pub(super) fn enter(&mut self, arg: &Term, env: &[Arc<Term>]) {
+        if self.hook.is_some() {
+            self.frames.push((self.depth, self.loc));
+            let loc = self.loc;
+            self.notify(EvalEvent::Enter { loc, arg, env });
+        }
+    }
+
+    /// Notifies the hook of the functions entered in the current evaluation, which ended with
+    /// `value`.
+    pub(super) fn exit(&mut self, value: &Term) {
+        while let Some(&(depth, loc)) = self.frames.last() {
+            if depth < self.depth {
+                break;
+            }
+            self.frames.pop();
+            self.notify(EvalEvent::Exit { loc, value });
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Rejection of the programs whose result could change between runs.
+//!
+//! The primitives that need a [`Capability`] are the only ones whose result depends on something
+//! outside of the machine. Machines built with
+//! [`MachineBuilder::deterministic`](crate::machine::MachineBuilder::deterministic) look for
+//! these primitives before evaluating a term or running a program, and stop with
+//! [`RuntimeError::Nondeterministic`] at the first one they find, so a program either runs
+//! without effects or does not run at all.
+use pijama_ast::{Location, Primitive};
+
+use crate::{
+    bytecode::{Instr, Program},
+    lir::Term::{self, *},
+    machine::{arithmetic::Arithmetic, env::Output, Capability, Machine, RuntimeError},
+};
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Returns the error rejecting `term` if the machine is deterministic and `term` uses a
+    /// primitive with effects.
+    pub(super) fn check_determinism(&self, term: &Term) -> Result<(), RuntimeError> {
+        if !self.deterministic {
+            return Ok(());
+        }
+        match effect(term, self.loc) {
+            Some((loc, prim)) => Err(RuntimeError::Nondeterministic(loc, prim)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the error rejecting `program` if the machine is deterministic and `program` uses a
+    /// primitive with effects.
+    pub(super) fn check_program_determinism(&self, program: &Program) -> Result<(), RuntimeError> {
+        if !self.deterministic {
+            return Ok(());
+        }
+        let effect = program
+            .chunks
+            .iter()
+            .flat_map(|chunk| &chunk.code)
+            .find_map(|instr| match instr {
+                Instr::Prim(prim, loc) | Instr::Read(prim, loc) => {
+                    Capability::of(*prim).map(|_| (*loc, *prim))
+                }
+                _ => None,
+            });
+        match effect {
+            Some((loc, prim)) => Err(RuntimeError::Nondeterministic(loc, prim)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the first primitive with effects used by `term`, with the location of the innermost
+/// located term around it, or `loc` if there is none.
+fn effect(term: &Term, loc: Location) -> Option<(Location, Primitive)> {
+    match term {
+        PrimFn(prim) => Capability::of(*prim).map(|_| (loc, *prim)),
+        Loc(loc, t1) => effect(t1, *loc),
+        Var(_) | Lit(_) | Float(_) | Char(_) | Str(_) | Cell(_) | Chan(_) => None,
+        Abs(t1) | UnaryOp(_, t1) | Fix(t1) | Ref(t1) | Deref(t1) | Assert(_, t1) => effect(t1, loc),
+        BinaryOp(_, t1, t2) | App(t1, t2) | Assign(t1, t2) => {
+            effect(t1, loc).or_else(|| effect(t2, loc))
+        }
+        Cond(t1, t2, t3) => effect(t1, loc)
+            .or_else(|| effect(t2, loc))
+            .or_else(|| effect(t3, loc)),
+        List(terms) | Native(_, terms) | Variant(_, _, terms) => {
+            terms.iter().find_map(|t| effect(t, loc))
+        }
+        Closure(code, env) => effect(code, loc).or_else(|| env.iter().find_map(|t| effect(t, loc))),
+        Match(t1, arms) => {
+            effect(t1, loc).or_else(|| arms.iter().find_map(|(_, body)| effect(body, loc)))
+        }
+    }
+}
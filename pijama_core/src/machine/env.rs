@@ -1,21 +1,124 @@
-use std::io::{stdout, Stdout, Write};
+use alloc::{boxed::Box, string::String};
+use core::fmt;
 
-pub struct Env<W: Write> {
+#[cfg(not(feature = "std"))]
+use alloc::{collections::VecDeque, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{stdout, Stdout};
+
+/// A destination for the output of the machine.
+///
+/// With the `std` feature this trait is implemented for every type implementing
+/// [`std::io::Write`]. Without it, it is implemented for `Vec<u8>` and `String`.
+pub trait Output {
+    /// Writes formatted data into this output.
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result;
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Output for W {
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        std::io::Write::write_fmt(self, args).map_err(|_| fmt::Error)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Output for String {
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        fmt::Write::write_fmt(self, args)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Output for Vec<u8> {
+    fn write_fmt(&mut self, args: fmt::Arguments) -> fmt::Result {
+        let mut buf = String::new();
+        fmt::Write::write_fmt(&mut buf, args)?;
+        self.extend_from_slice(buf.as_bytes());
+        Ok(())
+    }
+}
+
+/// A source for the input of the machine, which is read one line at a time.
+///
+/// With the `std` feature this trait is implemented for every type implementing
+/// [`std::io::BufRead`] and for [`Stdin`]. Without it, it is implemented for `VecDeque<String>`,
+/// whose elements are the lines of the input.
+pub trait Input {
+    /// Reads the next line of this input without its line break. Returns `None` if there are no
+    /// lines left.
+    fn read_line(&mut self) -> Option<String>;
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> Input for R {
+    fn read_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match std::io::BufRead::read_line(self, &mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+                line.truncate(len);
+                Some(line)
+            }
+        }
+    }
+}
+
+/// The standard input of the process.
+///
+/// The standard input is only locked while a line is being read, so other machines and the host
+/// can read from it too.
+#[cfg(feature = "std")]
+pub struct Stdin;
+
+#[cfg(feature = "std")]
+impl Input for Stdin {
+    fn read_line(&mut self) -> Option<String> {
+        Input::read_line(&mut std::io::stdin().lock())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Input for VecDeque<String> {
+    fn read_line(&mut self) -> Option<String> {
+        self.pop_front()
+    }
+}
+
+pub struct Env<W: Output> {
     stdout: W,
+    stdin: Box<dyn Input + Send>,
 }
 
-impl<W: Write> Env<W> {
+impl<W: Output> Env<W> {
+    /// Creates an environment writing to `stdout` whose input is empty.
     pub fn new(stdout: W) -> Self {
-        Env { stdout }
+        #[cfg(feature = "std")]
+        let stdin = Box::new(std::io::empty());
+        #[cfg(not(feature = "std"))]
+        let stdin = Box::new(VecDeque::new());
+        Env { stdout, stdin }
+    }
+
+    /// Replaces the input of this environment by `stdin`.
+    pub fn with_input(mut self, stdin: impl Input + Send + 'static) -> Self {
+        self.stdin = Box::new(stdin);
+        self
     }
 
     pub fn stdout(&mut self) -> &mut W {
         &mut self.stdout
     }
+
+    pub fn stdin(&mut self) -> &mut dyn Input {
+        &mut *self.stdin
+    }
 }
 
+#[cfg(feature = "std")]
 impl Default for Env<Stdout> {
     fn default() -> Self {
-        Env { stdout: stdout() }
+        Env::new(stdout()).with_input(Stdin)
     }
 }
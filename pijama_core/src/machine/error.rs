@@ -0,0 +1,129 @@
+//! Errors raised while evaluating a program.
+use alloc::string::String;
+use core::fmt::{Display, Formatter, Result};
+
+use pijama_ast::{Int, Location, Primitive};
+
+use crate::machine::Capability;
+
+/// An error that stops the evaluation of a program.
+///
+/// Runtime errors depend on the values computed by the program, so they cannot be found by the
+/// type checker.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RuntimeError {
+    /// Variant used when `read_int` reads a line that is not an integer or `parse_int` receives a
+    /// string that is not an integer.
+    InvalidInt(Location, String),
+    /// Variant used when the condition of an `assert` is `false`.
+    AssertionFailed(Location),
+    /// Variant used when an integer is divided by zero or its remainder by zero is computed.
+    DivisionByZero(Location),
+    /// Variant used when an integer operation overflows with
+    /// [`CheckedArithmetic`](crate::machine::arithmetic::CheckedArithmetic).
+    Overflow(Location),
+    /// Variant used when the machine runs out of the steps given with
+    /// [`MachineBuilder::with_fuel`](crate::machine::MachineBuilder::with_fuel).
+    OutOfFuel(Location),
+    /// Variant used when a program uses a capability denied by the
+    /// [`Capabilities`](crate::machine::Capabilities) of the machine.
+    CapabilityDisabled(Location, Capability),
+    /// Variant used when the main thread waits for a value on a channel and every other thread
+    /// is waiting too, so no value can ever be sent.
+    Deadlock(Location),
+    /// Variant used when the evaluation nests more calls than the limit set with
+    /// [`MachineBuilder::with_max_depth`](crate::machine::MachineBuilder::with_max_depth),
+    /// usually because of a recursive call that is not in tail position.
+    StackOverflow(Location),
+    /// Variant used when a machine built with
+    /// [`MachineBuilder::deterministic`](crate::machine::MachineBuilder::deterministic) is given
+    /// a program using a primitive with effects outside of the machine. The program is rejected
+    /// before it is evaluated.
+    Nondeterministic(Location, Primitive),
+    /// Variant used when `int_to_char` receives an integer that is not the code of a Unicode
+    /// character.
+    InvalidChar(Location, Int),
+    /// Variant used when a list or a string is indexed outside of its bounds, with the index and
+    /// the length of the list or the number of characters of the string. Taking the `head` or
+    /// `tail` of an empty list reads its element at index zero.
+    IndexOutOfBounds(Location, Int, usize),
+    /// Variant used when `print` cannot write to the output of the machine.
+    OutputFailed(Location),
+    /// Variant used when a `substring` ends before it starts, with its start and its end.
+    InvalidRange(Location, Int, Int),
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            RuntimeError::InvalidInt(_, line) => {
+                write!(f, "Cannot read `{}` as an integer", line)
+            }
+            RuntimeError::AssertionFailed(_) => write!(f, "Assertion failed"),
+            RuntimeError::DivisionByZero(_) => write!(f, "Division by zero"),
+            RuntimeError::Overflow(_) => write!(f, "Integer overflow"),
+            RuntimeError::OutOfFuel(_) => write!(f, "The evaluation ran out of steps"),
+            RuntimeError::CapabilityDisabled(_, capability) => {
+                write!(f, "The `{}` capability is disabled", capability)
+            }
+            RuntimeError::Deadlock(_) => write!(f, "Every thread is waiting on a channel"),
+            RuntimeError::StackOverflow(_) => write!(f, "The evaluation ran out of stack"),
+            RuntimeError::Nondeterministic(_, prim) => {
+                write!(f, "The `{}` primitive is not deterministic", prim)
+            }
+            RuntimeError::InvalidChar(_, n) => write!(f, "`{}` is not a valid character", n),
+            RuntimeError::IndexOutOfBounds(_, index, len) => write!(
+                f,
+                "Index {} is out of bounds for a length of {}",
+                index, len
+            ),
+            RuntimeError::OutputFailed(_) => write!(f, "The output could not be written"),
+            RuntimeError::InvalidRange(_, start, end) => {
+                write!(f, "Range `{}..{}` ends before it starts", start, end)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RuntimeError {}
+
+impl RuntimeError {
+    /// Returns the location of the error.
+    pub fn loc(&self) -> Location {
+        match self {
+            RuntimeError::InvalidInt(loc, _)
+            | RuntimeError::AssertionFailed(loc)
+            | RuntimeError::DivisionByZero(loc)
+            | RuntimeError::Overflow(loc)
+            | RuntimeError::OutOfFuel(loc)
+            | RuntimeError::CapabilityDisabled(loc, _)
+            | RuntimeError::Deadlock(loc)
+            | RuntimeError::StackOverflow(loc)
+            | RuntimeError::Nondeterministic(loc, _)
+            | RuntimeError::InvalidChar(loc, _)
+            | RuntimeError::IndexOutOfBounds(loc, _, _)
+            | RuntimeError::OutputFailed(loc)
+            | RuntimeError::InvalidRange(loc, _, _) => *loc,
+        }
+    }
+
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::InvalidInt(..) => "E0012",
+            RuntimeError::AssertionFailed(_) => "E0013",
+            RuntimeError::DivisionByZero(_) => "E0014",
+            RuntimeError::Overflow(_) => "E0015",
+            RuntimeError::OutOfFuel(_) => "E0016",
+            RuntimeError::CapabilityDisabled(..) => "E0017",
+            RuntimeError::Deadlock(_) => "E0018",
+            RuntimeError::StackOverflow(_) => "E0019",
+            RuntimeError::Nondeterministic(..) => "E0020",
+            RuntimeError::InvalidChar(..) => "E0021",
+            RuntimeError::IndexOutOfBounds(..) => "E0022",
+            RuntimeError::OutputFailed(_) => "E0024",
+            RuntimeError::InvalidRange(..) => "E0025",
+        }
+    }
+}
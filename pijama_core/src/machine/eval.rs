@@ -1,69 +1,230 @@
-use pijama_ast::{BinOp, Literal, Primitive, UnOp};
+use pijama_ast::{BinOp, Int, Literal, Location, Primitive, UnOp};
 
 use crate::{
-    lir::Term::{self, *},
-    machine::{arithmetic::Arithmetic, Machine},
+    lir::{
+        Pattern,
+        Term::{self, *},
+    },
+    machine::{
+        arithmetic::{self, Arithmetic},
+        env::Output,
+        Machine, RuntimeError, Value,
+    },
+    stack,
 };
 
-use std::{borrow::Borrow, io::Write};
+use alloc::{
+    collections::VecDeque,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::convert::TryFrom;
 
 /// Evaluate `$term` in place using the `$self` machine. Then return `(changed, $ret)` where
 /// `changed` states if the evaluation produced any changes and `$ret` is a `Term` (possibly
 /// including `$term`).
 macro_rules! eval_in_place {
     ($self:ident, $term:ident, $ret:expr) => {{
-        let (changed, new_t) = $self.eval(*$term);
-        *$term = new_t;
+        let (changed, new_t) = $self.eval(Term::take($term));
+        let $term = Arc::new(new_t);
         (changed, $ret)
     }};
 }
 
-impl<W: Write, A: Arithmetic> Machine<W, A> {
-    pub(super) fn eval(&mut self, mut term: Term) -> (bool, Term) {
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term` until it cannot be evaluated any further or the machine runs out of
+    /// steps.
+    ///
+    /// If the machine runs out of steps, the returned term is partially evaluated and evaluating
+    /// it again continues where the evaluation was paused.
+    pub(super) fn eval(&mut self, term: Term) -> (bool, Term) {
+        stack::grow(|| self.eval_nested(term))
+    }
+
+    /// Evaluates `term` like [`Machine::eval`] in the current segment of the stack of the host.
+    fn eval_nested(&mut self, mut term: Term) -> (bool, Term) {
+        self.depth += 1;
+        let depth = self.depth;
+        self.record(|stats| stats.max_depth = stats.max_depth.max(depth));
         let mut changed = false;
-        while {
+        while !self.exhausted() && {
             let (eval, new_term) = self.step(term);
             term = new_term;
             eval
         } {
             changed = true;
         }
+        if !self.frames.is_empty() && !self.exhausted() {
+            self.exit(&term);
+        }
+        self.depth -= 1;
         (changed, term)
     }
 
+    /// Consumes one step of the machine. Must be called by every step that reduces a term, as
+    /// opposed to steps that only evaluate one of its subterms.
+    ///
+    /// If the machine has no fuel left, the evaluation stops with an error instead. The steps
+    /// with effects must check if there is an error before doing them.
+    fn tick(&mut self) {
+        if self.fuel == Some(0) {
+            self.fail(RuntimeError::OutOfFuel(self.loc));
+            return;
+        }
+        if let Some(fuel) = &mut self.fuel {
+            *fuel -= 1;
+        }
+        self.record(|stats| stats.steps += 1);
+        if !self.breakpoints.is_empty() {
+            self.check_breakpoints();
+        }
+        if let Some(budget) = &mut self.budget {
+            *budget -= 1;
+        }
+        self.threads.tick();
+    }
+
+    /// Returns `true` if too many calls are being evaluated to enter a function, stopping the
+    /// evaluation with `RuntimeError::StackOverflow` at the call being evaluated.
+    ///
+    /// Only calls can nest evaluations without bound, so the limit counts the calls written in
+    /// the source code that are being evaluated, each of which nests a bounded number of
+    /// evaluations. The arguments passed by name to these calls nest some more evaluations, as
+    /// many as the calls that built them, so the memory taken stays proportional to the limit.
+    fn overflows(&mut self) -> bool {
+        if self.calls.len() < self.max_depth {
+            return false;
+        }
+        self.fail(RuntimeError::StackOverflow(self.loc));
+        true
+    }
+
     pub(super) fn step(&mut self, term: Term) -> (bool, Term) {
         match term {
             // Dispatch step for binary operations
             BinaryOp(op, t1, t2) => self.step_bin_op(op, t1, t2),
             // Dispatch step for unary operations
             UnaryOp(op, t1) => self.step_un_op(op, t1),
-            App(mut t1, arg) => match *t1 {
+            App(t1, arg) => match Term::take(t1) {
                 // Dispatch step for beta reduction
-                Abs(body) => self.step_beta_reduction(*body, arg),
+                Abs(body) => self.step_beta_reduction(body, arg),
+                // Dispatch step for closure application
+                Closure(code, env) => self.step_closure_app(code, env, arg),
                 // Dispatch step for primitive application
-                PrimFn(prim) => self.step_primitive_app(prim, *arg),
+                PrimFn(prim) => self.step_primitive_app(prim, Term::take(arg)),
+                // Dispatch step for native function application
+                Native(index, args) => self.step_native_app(index, args, Term::take(arg)),
+                // Dispatch step for application of primitives taking two arguments
+                App(t1, arg1) if matches!(*t1, PrimFn(prim) if prim.arity() == 2) => match *t1 {
+                    PrimFn(prim) => self.step_binary_primitive_app(prim, arg1, arg),
+                    _ => unreachable!(),
+                },
+                // Dispatch step for application of primitives taking three arguments
+                App(t1, arg2) if matches!(&*t1, App(t0, _) if matches!(**t0, PrimFn(prim) if prim.arity() == 3)) => {
+                    match Term::take(t1) {
+                        App(t0, arg1) => match *t0 {
+                            PrimFn(prim) => self.step_ternary_primitive_app(prim, arg1, arg2, arg),
+                            _ => unreachable!(),
+                        },
+                        _ => unreachable!(),
+                    }
+                }
                 // Application with unevaluated first term (t1 t2)
                 // Evaluate t1.
-                _ => eval_in_place!(self, t1, App(t1, arg)),
+                t1 => {
+                    let (changed, t1) = self.eval(t1);
+                    (changed, App(Arc::new(t1), arg))
+                }
             },
             // Dispatch step for conditionals
             Cond(t1, t2, t3) => self.step_cond(t1, t2, t3),
             // Dispatch step for fixed point operation
             Fix(t1) => self.step_fix(t1),
+            // Dispatch step for variants
+            Variant(index, name, args) => self.step_variant(index, name, args),
+            // Dispatch step for lists
+            List(elems) => self.step_list(elems),
+            // Dispatch step for matches
+            Match(t1, arms) => self.step_match(t1, arms),
+            // Dispatch step for cell creation
+            Ref(t1) => self.step_ref(t1),
+            // Dispatch step for cell reads
+            Deref(t1) => self.step_deref(t1),
+            // Dispatch step for assignments
+            Assign(t1, t2) => self.step_assign(t1, t2),
+            // Dispatch step for assertions
+            Assert(loc, t1) => self.step_assert(loc, t1),
+            // Dispatch step for located terms
+            Loc(loc, t1) => self.step_loc(loc, t1),
+            // Dispatch step for primitives without arguments
+            PrimFn(prim @ Primitive::ReadLine) | PrimFn(prim @ Primitive::ReadInt) => {
+                self.step_read(prim)
+            }
+            // Dispatch step for channel creation
+            PrimFn(Primitive::Channel) => self.step_channel(),
             // Any other term stops the evaluation.
-            Var(_) | Lit(_) | Abs(_) | PrimFn(_) => (false, term),
+            Var(_)
+            | Lit(_)
+            | Float(_)
+            | Char(_)
+            | Str(_)
+            | Abs(_)
+            | Closure(_, _)
+            | PrimFn(_)
+            | Native(_, _)
+            | Cell(_)
+            | Chan(_) => (false, term),
         }
     }
+
+    /// Evaluation step for channel creation (channel)
+    fn step_channel(&mut self) -> (bool, Term) {
+        self.tick();
+        self.record(|stats| stats.allocations += 1);
+        self.channels.push(VecDeque::new());
+        (true, Chan(self.channels.len() - 1))
+    }
+
+    /// Evaluation step for primitives reading a line of the input (read_line or read_int)
+    fn step_read(&mut self, prim: Primitive) -> (bool, Term) {
+        self.tick();
+        if self.error.is_some() {
+            return (false, PrimFn(prim));
+        }
+        let line = match self.read_line(self.loc) {
+            Ok(line) => line,
+            Err(error) => {
+                self.fail(error);
+                return (false, PrimFn(prim));
+            }
+        };
+        match prim {
+            Primitive::ReadLine => (true, Str(line.into())),
+            _ => match self.parse_int(&line) {
+                Some(n) => (true, Lit(n)),
+                None => {
+                    self.fail(RuntimeError::InvalidInt(self.loc, line));
+                    (false, PrimFn(prim))
+                }
+            },
+        }
+    }
+
     /// Evaluation step for conditionals (if t1 then t2 else t3)
-    fn step_cond(&mut self, mut t1: Box<Term>, t2: Box<Term>, t3: Box<Term>) -> (bool, Term) {
+    fn step_cond(&mut self, t1: Arc<Term>, t2: Arc<Term>, t3: Arc<Term>) -> (bool, Term) {
         // If t1 is a literal, we should be able to evaluate the conditional
-        if let lit @ Term::Lit(_) = t1.borrow() {
+        if let lit @ Term::Lit(_) = &*t1 {
+            self.tick();
             if lit.as_bool() {
                 // If t1 is true, evaluate to t2.
-                (true, *t2)
+                Term::discard(t3);
+                (true, Term::take(t2))
             } else {
                 // If t1 is false, evaluate to t3.
-                (true, *t3)
+                Term::discard(t2);
+                (true, Term::take(t3))
             }
         } else {
             // If t1 is not a literal, evaluate it in place and return (if t1 then t2 else t3)
@@ -71,47 +232,310 @@ impl<W: Write, A: Arithmetic> Machine<W, A> {
         }
     }
 
+    /// Evaluation step for variants (C(t1, ..., tn))
+    fn step_variant(&mut self, index: usize, name: Arc<str>, args: Vec<Arc<Term>>) -> (bool, Term) {
+        // Evaluate the fields, so a variant is only a value when its fields are values too.
+        let mut changed = false;
+        let args = args
+            .into_iter()
+            .map(|arg| {
+                let (arg_changed, arg) = self.eval(Term::take(arg));
+                changed |= arg_changed;
+                Arc::new(arg)
+            })
+            .collect();
+        (changed, Variant(index, name, args))
+    }
+
+    /// Evaluation step for lists ([t1, ..., tn])
+    fn step_list(&mut self, elems: Vec<Arc<Term>>) -> (bool, Term) {
+        // Evaluate the elements, so a list is only a value when its elements are values too.
+        let mut changed = false;
+        let elems = elems
+            .into_iter()
+            .map(|elem| {
+                let (elem_changed, elem) = self.eval(Term::take(elem));
+                changed |= elem_changed;
+                Arc::new(elem)
+            })
+            .collect();
+        (changed, List(elems))
+    }
+
+    /// Evaluation step for matches (match t1 with p1 => b1 | ... | pn => bn)
+    fn step_match(&mut self, t1: Arc<Term>, arms: Vec<(Pattern, Arc<Term>)>) -> (bool, Term) {
+        // Evaluate the scrutinee.
+        let (changed, t1) = self.eval(Term::take(t1));
+        // If the evaluation was paused, the scrutinee might not be a value yet.
+        if self.exhausted() {
+            return (changed, Match(Arc::new(t1), arms));
+        }
+        self.tick();
+        // Evaluate to the body of the first arm whose pattern matches t1, replacing the names
+        // bound by the pattern by the fields of t1. The other arms are discarded.
+        let mut matched = None;
+        for (pattern, mut body) in arms {
+            if matched.is_some() {
+                Term::discard(body);
+                continue;
+            }
+            matched = match (pattern, &t1) {
+                (Pattern::Wildcard, _) => Some(Term::take(body)),
+                (Pattern::Lit(lit), Lit(lit2)) if lit == *lit2 => Some(Term::take(body)),
+                (Pattern::Float(x), Float(x2)) if x == *x2 => Some(Term::take(body)),
+                (Pattern::Char(c), Char(c2)) if c == *c2 => Some(Term::take(body)),
+                (Pattern::Str(string), Str(string2)) if string == *string2 => {
+                    Some(Term::take(body))
+                }
+                (Pattern::Variant(index, arity), Variant(index2, _, args)) if index == *index2 => {
+                    // The arms binding some names are closures after the closure conversion.
+                    if let (1.., Closure(code, env)) = (arity, &*body) {
+                        Some(Term::take(Term::instantiate(code, args, env)))
+                    } else {
+                        // The last field is bound by the innermost name.
+                        for arg in args.iter().rev() {
+                            Term::substitute(&mut body, arg);
+                        }
+                        Some(Term::take(body))
+                    }
+                }
+                _ => {
+                    Term::discard(body);
+                    None
+                }
+            };
+        }
+        match matched {
+            Some(body) => (true, body),
+            None => panic!("No arm matches {}", t1),
+        }
+    }
+
+    /// Evaluation step for cell creation (ref t1)
+    fn step_ref(&mut self, t1: Arc<Term>) -> (bool, Term) {
+        // Evaluate t1, cells only hold values.
+        let (changed, t1) = self.eval(Term::take(t1));
+        // If the evaluation was paused, t1 might not be a value yet.
+        if self.exhausted() {
+            return (changed, Ref(Arc::new(t1)));
+        }
+        self.tick();
+        self.record(|stats| stats.allocations += 1);
+        self.cells.push(t1);
+        (true, Cell(self.cells.len() - 1))
+    }
+
+    /// Evaluation step for cell reads (!t1)
+    fn step_deref(&mut self, t1: Arc<Term>) -> (bool, Term) {
+        // If t1 is a cell, evaluate to its value.
+        if let Cell(index) = &*t1 {
+            self.tick();
+            (true, self.cells[*index].clone())
+        // If t1 is not a cell, evaluate it.
+        } else {
+            eval_in_place!(self, t1, Deref(t1))
+        }
+    }
+
+    /// Evaluation step for assignments (t1 := t2)
+    fn step_assign(&mut self, t1: Arc<Term>, t2: Arc<Term>) -> (bool, Term) {
+        let index = match &*t1 {
+            Cell(index) => *index,
+            // If t1 is not a cell, evaluate it.
+            _ => return eval_in_place!(self, t1, Assign(t1, t2)),
+        };
+        // Evaluate t2, cells only hold values.
+        let (changed, t2) = self.eval(Term::take(t2));
+        // If the evaluation was paused, t2 might not be a value yet.
+        if self.exhausted() {
+            return (changed, Assign(t1, Arc::new(t2)));
+        }
+        self.tick();
+        self.cells[index] = t2;
+        (true, Literal::Unit.into())
+    }
+
+    /// Evaluation step for assertions (assert t1)
+    fn step_assert(&mut self, loc: Location, t1: Arc<Term>) -> (bool, Term) {
+        // If t1 is a literal, evaluate to unit or stop the evaluation if it is false.
+        if let Lit(_) = &*t1 {
+            self.tick();
+            if t1.as_bool() {
+                (true, Literal::Unit.into())
+            } else {
+                self.fail(RuntimeError::AssertionFailed(loc));
+                (false, Assert(loc, t1))
+            }
+        // If t1 is not a literal, evaluate it.
+        } else {
+            eval_in_place!(self, t1, Assert(loc, t1))
+        }
+    }
+
+    /// Evaluation step for located terms.
+    ///
+    /// The term is evaluated with its location as the location of the machine, and the location
+    /// is dropped once the term is a value. This does not consume a step.
+    fn step_loc(&mut self, loc: Location, t1: Arc<Term>) -> (bool, Term) {
+        let outer = core::mem::replace(&mut self.loc, loc);
+        // Located applications are the calls written in the source code.
+        let call = matches!(*t1, App(..));
+        if call {
+            self.calls.push(loc);
+        }
+        let (changed, t1) = self.eval(Term::take(t1));
+        if call {
+            self.calls.pop();
+        }
+        self.loc = outer;
+        // If the evaluation was paused or stopped, the term might not be a value yet.
+        if self.exhausted() {
+            (changed, Loc(loc, Arc::new(t1)))
+        } else {
+            (true, t1)
+        }
+    }
+
     /// Evaluation step for binary operations (t1 op t2)
-    fn step_bin_op(&mut self, op: BinOp, mut t1: Box<Term>, mut t2: Box<Term>) -> (bool, Term) {
+    fn step_bin_op(&mut self, op: BinOp, t1: Arc<Term>, t2: Arc<Term>) -> (bool, Term) {
         use BinOp::*;
 
-        match (op, t1.borrow(), t2.borrow()) {
+        if let (List(_), List(_)) = (&*t1, &*t2) {
+            return self.step_list_comparison(op, t1, t2);
+        }
+
+        // Integers divided by zero stop the evaluation, floats are divided as usual.
+        if let (Div, Lit(_), Lit(0)) | (Rem, Lit(_), Lit(0)) = (op, &*t1, &*t2) {
+            self.fail(RuntimeError::DivisionByZero(self.loc));
+            return (false, BinaryOp(op, t1, t2));
+        }
+
+        if let (And, Lit(0), _)
+        | (Or, Lit(1), _)
+        | (_, Lit(_), Lit(_))
+        | (_, Float(_), Float(_))
+        | (_, Char(_), Char(_))
+        | (_, Str(_), Str(_)) = (op, &*t1, &*t2)
+        {
+            self.tick();
+        }
+
+        match (op, &*t1, &*t2) {
             // If op is && and t1 is false evaluate to false
-            (And, Lit(0), _) => (true, false.into()),
+            (And, Lit(0), _) => {
+                Term::discard(t2);
+                (true, false.into())
+            }
             // If op is || and t1 is true evaluate to true
-            (Or, Lit(1), _) => (true, true.into()),
+            (Or, Lit(1), _) => {
+                Term::discard(t2);
+                (true, true.into())
+            }
             // If both are literals evaluate with native operation
-            (_, Lit(l1), Lit(l2)) => (true, Lit(A::binary_operation(op, *l1, *l2))),
+            (_, Lit(l1), Lit(l2)) => match A::binary_operation(op, *l1, *l2, self.int_width) {
+                Some(n) => (true, Lit(n)),
+                None => self.overflow(BinaryOp(op, t1, t2)),
+            },
+            // If both are floats evaluate with the floating point operation
+            (_, Float(x1), Float(x2)) => (true, float_operation(op, *x1, *x2)),
+            // If both are characters, compare their code points
+            (_, Char(c1), Char(c2)) => {
+                let (n1, n2) = (Int::from(u32::from(*c1)), Int::from(u32::from(*c2)));
+                match A::binary_operation(op, n1, n2, self.int_width) {
+                    Some(n) => (true, Lit(n)),
+                    None => self.overflow(BinaryOp(op, t1, t2)),
+                }
+            }
+            // If both are strings, concatenate or compare them. The type checker rejects `+` over
+            // strings, so `Add` only reaches this point from the lowering of `concat`.
+            (Add, Str(s1), Str(s2)) => {
+                self.record(|stats| stats.allocations += 1);
+                let mut string = String::with_capacity(s1.len() + s2.len());
+                string.push_str(s1);
+                string.push_str(s2);
+                (true, Str(string.into()))
+            }
+            (Eq, Str(s1), Str(s2)) => (true, (s1 == s2).into()),
+            (Neq, Str(s1), Str(s2)) => (true, (s1 != s2).into()),
+            (_, Str(_), Str(_)) => unreachable!(
+                "Strings can only be concatenated with `concat` or compared for equality"
+            ),
             // If t2 is not a literal, evaluate it.
-            (_, Lit(_), _) => {
-                let (changed, new_t2) = self.eval(*t2);
-                *t2 = new_t2;
-                (changed, Term::BinaryOp(op, t1, t2))
+            (_, Lit(_), _)
+            | (_, Float(_), _)
+            | (_, Char(_), _)
+            | (_, Str(_), _)
+            | (_, List(_), _) => {
+                let (changed, new_t2) = self.eval(Term::take(t2));
+                (changed, Term::BinaryOp(op, t1, Arc::new(new_t2)))
             }
             // If t1 is not a literal, evaluate it.
             _ => eval_in_place!(self, t1, Term::BinaryOp(op, t1, t2)),
         }
     }
 
+    /// Evaluation step for comparisons between lists ([...] op [...])
+    fn step_list_comparison(&mut self, op: BinOp, t1: Arc<Term>, t2: Arc<Term>) -> (bool, Term) {
+        // Evaluate the elements of both lists before comparing them.
+        let (changed1, t1) = self.eval(Term::take(t1));
+        let (changed2, t2) = self.eval(Term::take(t2));
+        // If the evaluation was paused, the elements might not be values yet.
+        if self.exhausted() {
+            return (
+                changed1 || changed2,
+                BinaryOp(op, Arc::new(t1), Arc::new(t2)),
+            );
+        }
+        self.tick();
+        match op {
+            BinOp::Eq => (true, (t1 == t2).into()),
+            BinOp::Neq => (true, (t1 != t2).into()),
+            op => unreachable!("Operator `{}` does not take lists", op),
+        }
+    }
+
+    /// Stops the evaluation of an operation that overflowed.
+    fn overflow(&mut self, term: Term) -> (bool, Term) {
+        self.fail(RuntimeError::Overflow(self.loc));
+        (false, term)
+    }
+
     /// Evaluation step for unary operations (op t1)
-    fn step_un_op(&mut self, op: UnOp, mut t1: Box<Term>) -> (bool, Term) {
-        // If t1 is a literal, do the operation.
-        if let Term::Lit(lit) = t1.borrow() {
-            (true, Term::Lit(A::unary_operation(op, *lit)))
-        // If t1 is not a literal, evaluate it.
-        } else {
-            eval_in_place!(self, t1, Term::UnaryOp(op, t1))
+    fn step_un_op(&mut self, op: UnOp, t1: Arc<Term>) -> (bool, Term) {
+        match &*t1 {
+            // If t1 is a literal, do the operation.
+            Term::Lit(lit) => {
+                self.tick();
+                match A::unary_operation(op, *lit, self.int_width) {
+                    Some(n) => (true, Term::Lit(n)),
+                    None => self.overflow(Term::UnaryOp(op, t1)),
+                }
+            }
+            // Floats can only be negated.
+            Term::Float(x) => {
+                self.tick();
+                (true, Term::Float(-x))
+            }
+            // If t1 is not a literal, evaluate it.
+            _ => eval_in_place!(self, t1, Term::UnaryOp(op, t1)),
         }
     }
 
     /// Evaluation step for the fixed-point operation (fix t1)
-    fn step_fix(&mut self, mut t1: Box<Term>) -> (bool, Term) {
+    fn step_fix(&mut self, t1: Arc<Term>) -> (bool, Term) {
         // If t1 is an abstraction (\. t2), replace the argument of t1 by (fix t1) inside t2
         // and evaluate to t2.
-        if let Term::Abs(t2) = t1.borrow() {
-            let mut t2 = t2.clone();
-            t2.replace(0, &mut Term::Fix(t1));
-            (true, *t2)
+        if let Term::Abs(t2) = &*t1 {
+            self.tick();
+            let mut t2 = Arc::clone(t2);
+            Term::substitute(&mut t2, &Arc::new(Term::Fix(Arc::clone(&t1))));
+            (true, Term::take(t2))
+        // If t1 is a closure, replace its parameter by (fix t1) inside its code and evaluate to
+        // the code.
+        } else if let Term::Closure(code, env) = &*t1 {
+            self.tick();
+            let fix = Arc::new(Term::Fix(Arc::clone(&t1)));
+            (true, Term::take(Term::instantiate(code, &[fix], env)))
         // If t1 is not an abstraction, evaluate it.
         } else {
             eval_in_place!(self, t1, Term::Fix(t1))
@@ -119,26 +543,435 @@ impl<W: Write, A: Arithmetic> Machine<W, A> {
     }
 
     /// Evaluation step for beta reduction ((λ. body) arg)
-    fn step_beta_reduction(&mut self, mut body: Term, mut arg: Box<Term>) -> (bool, Term) {
-        // increase the indices of the argument so they can coincide with the indices of the body.
-        arg.shift(true, 0);
+    fn step_beta_reduction(&mut self, mut body: Arc<Term>, arg: Arc<Term>) -> (bool, Term) {
+        if self.overflows() {
+            return (false, App(Arc::new(Abs(body)), arg));
+        }
+        self.tick();
+        self.record(|stats| stats.applications += 1);
+        self.enter(&arg, &[]);
         // replace the index 0 by the argument inside the body.
-        body.replace(0, &mut arg);
-        // decrease the indices of the body to take into account the fact that the abstraction no
-        // longer exists.
-        body.shift(false, 0);
-        // return the body
+        Term::substitute(&mut body, &arg);
+        // The argument is discarded if the body does not use it.
+        Term::discard(arg);
+        (true, Term::take(body))
+    }
+    /// Evaluation step for closure application ((closure code env) arg)
+    fn step_closure_app(
+        &mut self,
+        code: Arc<Term>,
+        env: Vec<Arc<Term>>,
+        arg: Arc<Term>,
+    ) -> (bool, Term) {
+        if self.overflows() {
+            return (false, App(Arc::new(Closure(code, env)), arg));
+        }
+        self.tick();
+        self.record(|stats| stats.applications += 1);
+        self.enter(&arg, &env);
+        // replace the parameter and the environment inside the code.
+        let body = Term::take(Term::instantiate(&code, core::slice::from_ref(&arg), &env));
+        // The argument and the environment are discarded if the code does not use them.
+        Term::discard(arg);
+        env.into_iter().for_each(Term::discard);
         (true, body)
     }
+
     /// Evaluation step for application of primitive functions (prim arg)
     fn step_primitive_app(&mut self, prim: Primitive, arg: Term) -> (bool, Term) {
         match prim {
             Primitive::Print => {
                 // Evaluate argument
-                let (_, arg) = self.eval(arg);
-                writeln!(self.env.stdout(), "{}", arg).expect("Primitive print failed");
+                let (changed, arg) = self.eval(arg);
+                // If the evaluation was paused, the argument might not be a value yet.
+                if self.exhausted() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                self.tick();
+                if self.error.is_some() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                // Characters and strings are printed without quotes.
+                let printed = match &arg {
+                    Char(c) => self.print_line(self.loc, format_args!("{}", c)),
+                    Str(string) => self.print_line(self.loc, format_args!("{}", string)),
+                    arg => self.print_line(self.loc, format_args!("{}", arg)),
+                };
+                match printed {
+                    Ok(()) => (true, Literal::Unit.into()),
+                    Err(error) => {
+                        self.fail(error);
+                        (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)))
+                    }
+                }
+            }
+            Primitive::Concat | Primitive::Assert | Primitive::Force => {
+                unreachable!("`{}` is lowered to an abstraction", prim)
+            }
+            Primitive::ReadLine | Primitive::ReadInt | Primitive::Channel => {
+                unreachable!("`{}` does not take arguments", prim)
+            }
+            Primitive::Spawn => {
+                // Evaluate argument
+                let (changed, arg) = self.eval(arg);
+                // If the evaluation was paused, the argument might not be a value yet.
+                if self.exhausted() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                self.tick();
+                if self.error.is_some() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                self.spawn(arg);
                 (true, Literal::Unit.into())
             }
+            Primitive::Recv => {
+                // Evaluate argument
+                let (changed, arg) = self.eval(arg);
+                // If the evaluation was paused, the argument might not be a value yet.
+                if self.exhausted() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                let index = match arg {
+                    Chan(index) => index,
+                    arg => panic!("Invalid argument {} for `{}`", arg, prim),
+                };
+                // The thread waits without taking a step until a value is sent to the channel.
+                if self.channels[index].is_empty() {
+                    self.wait(index);
+                    return (
+                        changed,
+                        Term::App(Arc::new(PrimFn(prim)), Arc::new(Chan(index))),
+                    );
+                }
+                self.tick();
+                if self.error.is_some() {
+                    return (
+                        changed,
+                        Term::App(Arc::new(PrimFn(prim)), Arc::new(Chan(index))),
+                    );
+                }
+                let value = self.channels[index].pop_front();
+                (true, value.expect("The channel has a value"))
+            }
+            Primitive::IntToFloat
+            | Primitive::FloatToInt
+            | Primitive::CharToInt
+            | Primitive::IntToChar
+            | Primitive::ToString
+            | Primitive::ParseInt => {
+                // Evaluate argument
+                let (changed, arg) = self.eval(arg);
+                // If the evaluation was paused, the argument might not be a value yet.
+                if self.exhausted() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                self.tick();
+                match (prim, arg) {
+                    (Primitive::IntToFloat, Lit(n)) => (true, Float(n as f64)),
+                    // The conversion rounds towards zero and saturates if the number does not
+                    // fit in an integer.
+                    (Primitive::FloatToInt, Float(x)) => (true, Lit(self.float_to_int(x))),
+                    (Primitive::CharToInt, Char(c)) => (true, Lit(u32::from(c).into())),
+                    (Primitive::IntToChar, Lit(n)) => {
+                        match u32::try_from(n).ok().and_then(char::from_u32) {
+                            Some(c) => (true, Char(c)),
+                            None => {
+                                self.fail(RuntimeError::InvalidChar(self.loc, n));
+                                (false, Term::App(Arc::new(PrimFn(prim)), Arc::new(Lit(n))))
+                            }
+                        }
+                    }
+                    // Values are converted in the same way they are printed.
+                    (Primitive::ToString, Str(string)) => (true, Str(string)),
+                    (Primitive::ToString, Char(c)) => {
+                        self.record(|stats| stats.allocations += 1);
+                        (true, Str(c.to_string().into()))
+                    }
+                    (Primitive::ToString, arg) => {
+                        self.record(|stats| stats.allocations += 1);
+                        (true, Str(format!("{}", arg).into()))
+                    }
+                    // Integers are parsed like the lines read by `read_int`.
+                    (Primitive::ParseInt, Str(string)) => match self.parse_int(&string) {
+                        Some(n) => (true, Lit(n)),
+                        None => {
+                            self.fail(RuntimeError::InvalidInt(self.loc, string.to_string()));
+                            let arg = Arc::new(Str(string));
+                            (false, Term::App(Arc::new(PrimFn(prim)), arg))
+                        }
+                    },
+                    (prim, arg) => panic!("Invalid argument {} for `{}`", arg, prim),
+                }
+            }
+            Primitive::Head | Primitive::Tail | Primitive::Len => {
+                // Evaluate argument
+                let (changed, arg) = self.eval(arg);
+                // If the evaluation was paused, the argument might not be a value yet.
+                if self.exhausted() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                self.tick();
+                match (prim, arg) {
+                    (Primitive::Len, List(elems)) => (true, Lit(elems.len() as Int)),
+                    (Primitive::Len, Str(string)) => (true, Lit(string.chars().count() as Int)),
+                    (_, List(elems)) if elems.is_empty() => {
+                        self.fail(RuntimeError::IndexOutOfBounds(self.loc, 0, 0));
+                        (
+                            false,
+                            Term::App(Arc::new(PrimFn(prim)), Arc::new(List(elems))),
+                        )
+                    }
+                    (Primitive::Head, List(mut elems)) => (true, Term::take(elems.swap_remove(0))),
+                    (Primitive::Tail, List(mut elems)) => {
+                        elems.remove(0);
+                        (true, List(elems))
+                    }
+                    (prim, arg) => panic!("Invalid argument {} for `{}`", arg, prim),
+                }
+            }
+            Primitive::Abs | Primitive::Sign => {
+                // Evaluate argument
+                let (changed, arg) = self.eval(arg);
+                // If the evaluation was paused, the argument might not be a value yet.
+                if self.exhausted() {
+                    return (changed, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg)));
+                }
+                self.tick();
+                match arg {
+                    Lit(n) => match arithmetic::unary_primitive::<A>(prim, n, self.int_width) {
+                        Some(n) => (true, Lit(n)),
+                        None => self.overflow(Term::App(Arc::new(PrimFn(prim)), Arc::new(arg))),
+                    },
+                    arg => panic!("Invalid argument {} for `{}`", arg, prim),
+                }
+            }
+            // Primitives taking more arguments are values until they receive all of them.
+            Primitive::Push
+            | Primitive::Get
+            | Primitive::Min
+            | Primitive::Max
+            | Primitive::Gcd
+            | Primitive::Pow
+            | Primitive::Substring
+            | Primitive::CharAt
+            | Primitive::Random
+            | Primitive::Send => (false, Term::App(Arc::new(PrimFn(prim)), Arc::new(arg))),
+        }
+    }
+
+    /// Evaluation step for application of primitives taking two arguments (prim arg1 arg2)
+    fn step_binary_primitive_app(
+        &mut self,
+        prim: Primitive,
+        arg1: Arc<Term>,
+        arg2: Arc<Term>,
+    ) -> (bool, Term) {
+        // Evaluate both arguments
+        let (changed1, arg1) = self.eval(Term::take(arg1));
+        let (changed2, arg2) = self.eval(Term::take(arg2));
+        // If the evaluation was paused, the arguments might not be values yet.
+        if self.exhausted() {
+            return (changed1 || changed2, binary_app(prim, arg1, arg2));
+        }
+        self.tick();
+        match (prim, arg1, arg2) {
+            (Primitive::Push, List(mut elems), elem) => {
+                self.record(|stats| stats.allocations += 1);
+                elems.push(Arc::new(elem));
+                (true, List(elems))
+            }
+            (Primitive::Get, List(mut elems), Lit(index)) => {
+                let len = elems.len();
+                match usize::try_from(index) {
+                    Ok(i) if i < len => (true, Term::take(elems.swap_remove(i))),
+                    _ => {
+                        self.fail(RuntimeError::IndexOutOfBounds(self.loc, index, len));
+                        (false, binary_app(prim, List(elems), Lit(index)))
+                    }
+                }
+            }
+            (Primitive::CharAt, Str(string), Lit(index)) => match char_at(&string, index) {
+                Some(c) => (true, Char(c)),
+                None => {
+                    let len = string.chars().count();
+                    self.fail(RuntimeError::IndexOutOfBounds(self.loc, index, len));
+                    (false, binary_app(prim, Str(string), Lit(index)))
+                }
+            },
+            (Primitive::Random, Lit(lo), Lit(hi)) => match self.random(self.loc, lo, hi) {
+                Ok(n) => (true, Lit(n)),
+                Err(error) => {
+                    self.fail(error);
+                    (false, binary_app(prim, Lit(lo), Lit(hi)))
+                }
+            },
+            (Primitive::Send, Chan(index), value) => {
+                if self.error.is_none() {
+                    self.channels[index].push_back(value);
+                    return (true, Literal::Unit.into());
+                }
+                (false, binary_app(prim, Chan(index), value))
+            }
+            // Zero cannot be raised to a negative power, like it cannot divide.
+            (Primitive::Pow, Lit(0), Lit(n2)) if n2 < 0 => {
+                self.fail(RuntimeError::DivisionByZero(self.loc));
+                (false, binary_app(prim, Lit(0), Lit(n2)))
+            }
+            (
+                Primitive::Min | Primitive::Max | Primitive::Gcd | Primitive::Pow,
+                Lit(n1),
+                Lit(n2),
+            ) => match arithmetic::binary_primitive::<A>(prim, n1, n2, self.int_width) {
+                Some(n) => (true, Lit(n)),
+                None => self.overflow(binary_app(prim, Lit(n1), Lit(n2))),
+            },
+            (prim, arg1, arg2) => panic!("Invalid arguments {} and {} for `{}`", arg1, arg2, prim),
+        }
+    }
+
+    /// Evaluation step for application of primitives taking three arguments
+    /// (prim arg1 arg2 arg3)
+    fn step_ternary_primitive_app(
+        &mut self,
+        prim: Primitive,
+        arg1: Arc<Term>,
+        arg2: Arc<Term>,
+        arg3: Arc<Term>,
+    ) -> (bool, Term) {
+        // Evaluate all the arguments
+        let (changed1, arg1) = self.eval(Term::take(arg1));
+        let (changed2, arg2) = self.eval(Term::take(arg2));
+        let (changed3, arg3) = self.eval(Term::take(arg3));
+        // If the evaluation was paused, the arguments might not be values yet.
+        if self.exhausted() {
+            let app = binary_app(prim, arg1, arg2);
+            return (
+                changed1 || changed2 || changed3,
+                Term::App(Arc::new(app), Arc::new(arg3)),
+            );
+        }
+        self.tick();
+        match (prim, arg1, arg2, arg3) {
+            (Primitive::Substring, Str(string), Lit(start), Lit(end)) => {
+                match substring(self.loc, &string, start, end) {
+                    Ok(sub) => {
+                        self.record(|stats| stats.allocations += 1);
+                        (true, Str(sub.into()))
+                    }
+                    Err(error) => {
+                        self.fail(error);
+                        let app = binary_app(prim, Str(string), Lit(start));
+                        (false, Term::App(Arc::new(app), Arc::new(Lit(end))))
+                    }
+                }
+            }
+            (prim, arg1, arg2, arg3) => panic!(
+                "Invalid arguments {}, {} and {} for `{}`",
+                arg1, arg2, arg3, prim
+            ),
+        }
+    }
+
+    /// Evaluation step for application of native functions (native args arg)
+    fn step_native_app(
+        &mut self,
+        index: usize,
+        mut args: Vec<Arc<Term>>,
+        arg: Term,
+    ) -> (bool, Term) {
+        // Evaluate the argument, natives only receive values.
+        let (changed, arg) = self.eval(arg);
+        // If the evaluation was paused, the argument might not be a value yet.
+        if self.exhausted() {
+            return (changed, App(Arc::new(Native(index, args)), Arc::new(arg)));
+        }
+        self.tick();
+        if self.error.is_some() {
+            return (changed, App(Arc::new(Native(index, args)), Arc::new(arg)));
+        }
+        self.record(|stats| stats.applications += 1);
+        args.push(Arc::new(arg));
+        let native = &self.natives[index];
+        // If the function has not received all its arguments yet, evaluate to the partially
+        // applied function.
+        if args.len() < native.arity() {
+            return (true, Native(index, args));
+        }
+        let values: Vec<Value> = args
+            .iter()
+            .zip(native.params())
+            .map(|(arg, ty)| {
+                Value::from_term(arg, ty)
+                    .unwrap_or_else(|| panic!("Non-value argument {} for `{}`", arg, native.name()))
+            })
+            .collect();
+        (true, native.call(&values).into())
+    }
+}
+
+/// Returns the character of `string` at `index`, counting characters from zero.
+pub(super) fn char_at(string: &str, index: Int) -> Option<char> {
+    string.chars().nth(usize::try_from(index).ok()?)
+}
+
+/// Returns the characters of `string` from `start`, included, to `end`, excluded, as the
+/// `substring` at `loc`.
+///
+/// If `start` is out of bounds, the error is about `start`. Otherwise, an `end` before `start` is
+/// an invalid range and an `end` past the string is out of bounds.
+pub(super) fn substring(
+    loc: Location,
+    string: &str,
+    start: Int,
+    end: Int,
+) -> Result<&str, RuntimeError> {
+    let len = string.chars().count();
+    let bound = |index: Int| {
+        usize::try_from(index)
+            .ok()
+            .filter(|i| *i <= len)
+            .ok_or(RuntimeError::IndexOutOfBounds(loc, index, len))
+    };
+    let start_index = bound(start)?;
+    if end < start {
+        return Err(RuntimeError::InvalidRange(loc, start, end));
+    }
+    let end_index = bound(end)?;
+    // The byte offset of the character at `index`, or the length of the string past its end.
+    let offset = |index| {
+        string
+            .char_indices()
+            .nth(index)
+            .map_or(string.len(), |(offset, _)| offset)
+    };
+    Ok(&string[offset(start_index)..offset(end_index)])
+}
+
+/// Returns the application of `prim` to `arg1` and `arg2`.
+fn binary_app(prim: Primitive, arg1: Term, arg2: Term) -> Term {
+    let app = Term::App(Arc::new(PrimFn(prim)), Arc::new(arg1));
+    Term::App(Arc::new(app), Arc::new(arg2))
+}
+
+/// Evaluates a binary operation over floating point numbers.
+pub(super) fn float_operation(op: BinOp, x1: f64, x2: f64) -> Term {
+    use BinOp::*;
+
+    match op {
+        Add => Float(x1 + x2),
+        Sub => Float(x1 - x2),
+        Mul => Float(x1 * x2),
+        Div => Float(x1 / x2),
+        Rem => Float(x1 % x2),
+        Lt => (x1 < x2).into(),
+        Lte => (x1 <= x2).into(),
+        Gt => (x1 > x2).into(),
+        Gte => (x1 >= x2).into(),
+        Eq => (x1 == x2).into(),
+        Neq => (x1 != x2).into(),
+        And | Or | BitAnd | BitOr | BitXor | Shr | Shl | Pipe => {
+            unreachable!("Operator `{}` does not take floats", op)
         }
     }
 }
@@ -0,0 +1,121 @@
+//! Evaluation that does not block its caller.
+//!
+//! [`Machine::evaluate_async`] returns an [`Evaluation`] future that runs a bounded number of
+//! evaluation steps each time it is polled and then yields to the executor. A
+//! [`CancellationToken`] can be used to stop the evaluation the next time it yields.
+use alloc::sync::Arc;
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+};
+
+use crate::{
+    lir::Term,
+    machine::{arithmetic::Arithmetic, env::Output, threads::Threads, Machine, RuntimeError},
+};
+
+/// A handle to cancel an evaluation.
+///
+/// Clones of a token share its state, so the host can keep a clone and cancel the evaluation
+/// while the future is owned by an executor.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every evaluation using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The error returned by an [`Evaluation`] that was cancelled.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The evaluation was cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Cancelled {}
+
+/// A future that evaluates a term, created by [`Machine::evaluate_async`].
+pub struct Evaluation<'m, W: Output, A: Arithmetic> {
+    machine: &'m mut Machine<W, A>,
+    term: Option<Term>,
+    token: CancellationToken,
+    /// The error rejecting the term before its evaluation, if any.
+    rejected: Option<RuntimeError>,
+}
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term` without blocking the caller for long.
+    ///
+    /// Each time the returned future is polled, it runs at most as many evaluation steps as the
+    /// yield interval of the machine. If the term has not been fully evaluated by then, the future
+    /// wakes itself up and returns `Poll::Pending`.
+    ///
+    /// The future returns `Err(Cancelled)` if `token` is cancelled before the evaluation ends.
+    /// Otherwise, it returns the same result as [`Machine::evaluate`].
+    pub fn evaluate_async(&mut self, term: Term, token: CancellationToken) -> Evaluation<'_, W, A> {
+        let rejected = self.check_determinism(&term).err();
+        Evaluation {
+            machine: self,
+            term: Some(term),
+            token,
+            rejected,
+        }
+    }
+}
+
+impl<'m, W: Output, A: Arithmetic> Future for Evaluation<'m, W, A> {
+    type Output = Result<Result<Term, RuntimeError>, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.token.is_cancelled() {
+            return Poll::Ready(Err(Cancelled));
+        }
+        if let Some(error) = this.rejected.take() {
+            return Poll::Ready(Ok(Err(error)));
+        }
+
+        let term = this
+            .term
+            .take()
+            .expect("Evaluation polled after completion");
+        this.machine.budget = Some(this.machine.yield_interval);
+        let term = this.machine.run(term);
+        let exhausted = this.machine.exhausted();
+        this.machine.budget = None;
+        // Pausing the evaluation from a hook only yields to the executor.
+        this.machine.paused = false;
+
+        if let Some(error) = this.machine.error.take() {
+            Term::drop_iteratively(term);
+            this.machine.threads = Threads::default();
+            Poll::Ready(Ok(Err(error)))
+        } else if exhausted {
+            this.term = Some(term);
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(Ok(term)))
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! The callbacks of the programs compiled by the [`jit`](crate::jit) module.
+use core::convert::TryFrom;
+
+use pijama_ast::{IntWidth, UnOp};
+
+use crate::{
+    jit::{self, Callbacks, Context, ARITHMETIC},
+    lir::Term,
+    machine::{arithmetic::Arithmetic, env::Output, Machine, RuntimeError},
+};
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term` like [`Machine::evaluate`], compiling it to native code if it is
+    /// supported by the [`jit`](crate::jit) module.
+    ///
+    /// Compiled programs run with the output and arithmetic of the machine. They do not go
+    /// through the steps of the evaluation, so they do not consume fuel, collect statistics or
+    /// call the hook. Programs are not compiled if `print` is denied or mocked by the
+    /// capabilities of the machine, or if its integers are wider than 64 bits.
+    pub fn evaluate_jit(&mut self, term: Term) -> Result<Term, RuntimeError> {
+        self.check_determinism(&term)?;
+        if !self.prints_to_output() || self.int_width != IntWidth::I64 {
+            return self.evaluate(term);
+        }
+        let callbacks = Callbacks {
+            binary: binary::<A> as *const u8,
+            negate: negate::<A> as *const u8,
+            print: print::<W, A> as *const u8,
+        };
+        match jit::compile(&term, callbacks) {
+            // The callbacks receive the machine through the context of the program.
+            Some(program) => {
+                unsafe { program.run(self as *mut Self as *mut u8) }.map(|n| Term::Lit(n.into()))
+            }
+            None => self.evaluate(term),
+        }
+    }
+}
+
+extern "C" fn binary<A: Arithmetic>(op: i64, n1: i64, n2: i64, out: *mut i64) -> i64 {
+    let n = A::binary_operation(ARITHMETIC[op as usize], n1.into(), n2.into(), IntWidth::I64);
+    // The results of the arithmetic fit in the width it is given.
+    match n.and_then(|n| i64::try_from(n).ok()) {
+        Some(n) => {
+            unsafe { *out = n };
+            1
+        }
+        None => 0,
+    }
+}
+
+extern "C" fn negate<A: Arithmetic>(n: i64, out: *mut i64) -> i64 {
+    match A::unary_operation(UnOp::Neg, n.into(), IntWidth::I64).and_then(|n| i64::try_from(n).ok())
+    {
+        Some(n) => {
+            unsafe { *out = n };
+            1
+        }
+        None => 0,
+    }
+}
+
+extern "C" fn print<W: Output, A: Arithmetic>(context: *mut Context, n: i64) -> i64 {
+    let machine = unsafe { &mut *((*context).host as *mut Machine<W, A>) };
+    // Panics must not unwind out of this function, so a failed write is reported by the program.
+    writeln!(machine.env.stdout(), "{}", n).is_ok() as i64
+}
@@ -1,24 +1,181 @@
-use std::io::Write;
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+
+use pijama_ast::{Int, IntWidth, Location};
 
 use crate::{
     lir::Term,
-    machine::{arithmetic::Arithmetic, env::Env},
+    machine::{
+        arithmetic::Arithmetic,
+        debug::{DebugState, Hook},
+        env::{Env, Output},
+        native::NativeFn,
+        random::Rng,
+        threads::Threads,
+    },
 };
 
 pub mod arithmetic;
 mod builder;
+pub mod capabilities;
+pub mod debug;
+mod determinism;
 pub mod env;
+mod error;
 mod eval;
+pub mod future;
+#[cfg(feature = "jit")]
+mod jit;
+pub mod native;
+mod random;
+mod snapshot;
+mod stats;
+mod threads;
+mod trace;
+pub mod value;
+mod vm;
 
 pub use builder::MachineBuilder;
+pub use capabilities::{Capabilities, Capability};
+pub use error::RuntimeError;
+pub use future::{CancellationToken, Cancelled, Evaluation};
+pub use snapshot::{Progress, Snapshot};
+pub use stats::Stats;
+pub use trace::Steps;
+pub use value::Value;
 
-pub struct Machine<W: Write, A: Arithmetic> {
+pub struct Machine<W: Output, A: Arithmetic> {
     env: Env<W>,
     _arithmetic: A,
+    /// The width of the integers.
+    int_width: IntWidth,
+    natives: Vec<NativeFn>,
+    /// Number of steps that `evaluate_async` runs before yielding.
+    yield_interval: usize,
+    /// Number of steps the machine can run before pausing the evaluation. `None` if the
+    /// evaluation must not pause.
+    budget: Option<usize>,
+    /// Number of steps the machine can still run before stopping the evaluation with
+    /// `RuntimeError::OutOfFuel`. `None` if the number of steps is not limited.
+    fuel: Option<u64>,
+    /// The values held by the cells created during the evaluation. The position of each value is
+    /// the index used by `Cell` terms to refer to it.
+    cells: Vec<Term>,
+    /// The values sent to the channels created during the evaluation and not received yet. The
+    /// position of each channel is the index used by `Chan` terms to refer to it.
+    channels: Vec<VecDeque<Term>>,
+    /// The threads spawned during the evaluation.
+    threads: Threads,
+    /// The error that stopped the evaluation, if any.
+    error: Option<RuntimeError>,
+    /// The location of the innermost located term being evaluated, used for the errors that stop
+    /// the evaluation.
+    loc: Location,
+    /// The number of nested evaluations.
+    depth: usize,
+    /// The locations of the calls being evaluated, innermost last.
+    calls: Vec<Location>,
+    /// The locations of the calls that were being evaluated when the last error stopped the
+    /// evaluation, innermost first.
+    stack_trace: Vec<Location>,
+    /// The number of nested calls after which the evaluation stops with
+    /// `RuntimeError::StackOverflow`.
+    max_depth: usize,
+    /// The statistics of the current evaluation, if they are being collected.
+    stats: Option<Stats>,
+    /// The hook called with the events of the evaluation.
+    hook: Option<Box<Hook>>,
+    breakpoints: Vec<Location>,
+    /// The breakpoint reached by the last step, if any.
+    breakpoint: Option<Location>,
+    /// The functions entered and not exited yet, with the number of nested evaluations where they
+    /// were entered and their location.
+    frames: Vec<(usize, Location)>,
+    /// Whether the hook paused the evaluation.
+    paused: bool,
+    /// The generator of the numbers returned by `random`.
+    rng: Rng,
+    /// The effects that programs are allowed to have.
+    capabilities: Capabilities,
+    /// Whether programs with effects outside of the machine are rejected before their evaluation.
+    deterministic: bool,
 }
 
-impl<W: Write, A: Arithmetic> Machine<W, A> {
-    pub fn evaluate(&mut self, term: Term) -> Term {
-        self.eval(term).1
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term`, returning its value or the runtime error that stopped the evaluation.
+    pub fn evaluate(&mut self, mut term: Term) -> Result<Term, RuntimeError> {
+        loop {
+            match self.debug(term)? {
+                DebugState::Paused(paused) => term = paused,
+                DebugState::Done(value) => return Ok(value),
+            }
+        }
+    }
+
+    /// Evaluates `term` like [`Machine::evaluate`], and returns the statistics of the evaluation
+    /// alongside its result.
+    ///
+    /// The statistics are also returned if the evaluation stops with an error.
+    pub fn evaluate_with_stats(&mut self, term: Term) -> (Result<Term, RuntimeError>, Stats) {
+        self.stats = Some(Stats::default());
+        let result = self.evaluate(term);
+        let stats = self.stats.take().unwrap_or_default();
+        (result, stats)
+    }
+
+    /// Calls `f` with the statistics of the current evaluation if they are being collected.
+    fn record(&mut self, f: impl FnOnce(&mut Stats)) {
+        if let Some(stats) = &mut self.stats {
+            f(stats);
+        }
+    }
+
+    /// Returns true if the machine ran out of steps, found a runtime error or is switching to
+    /// another thread, so the evaluation is paused.
+    fn exhausted(&self) -> bool {
+        self.budget == Some(0) || self.error.is_some() || self.paused || self.threads.switching
+    }
+
+    /// Stops the evaluation because of `error`, keeping the calls being evaluated as the stack
+    /// trace of the error.
+    fn fail(&mut self, error: RuntimeError) {
+        self.error = Some(error);
+        self.frames.clear();
+        self.stack_trace = self.calls.iter().rev().copied().collect();
+    }
+
+    /// Returns the locations of the calls that were being evaluated when the last runtime error
+    /// stopped the evaluation, innermost first.
+    ///
+    /// Only the calls evaluated step by step are recorded, so the trace is empty if the error was
+    /// found by the bytecode or JIT backends.
+    pub fn stack_trace(&self) -> &[Location] {
+        &self.stack_trace
+    }
+
+    /// Returns the native functions registered in this machine.
+    ///
+    /// The position of each function in this slice is the index used by `Native` terms to refer
+    /// to it.
+    pub fn natives(&self) -> &[NativeFn] {
+        &self.natives
+    }
+
+    /// Returns the width of the integers, see
+    /// [`MachineBuilder::with_int_width`](crate::machine::MachineBuilder::with_int_width).
+    pub fn int_width(&self) -> IntWidth {
+        self.int_width
+    }
+
+    /// Parses a line read by `read_int` or a string given to `parse_int`, returning `None` if it
+    /// is not an integer that fits in the width of the integers.
+    fn parse_int(&self, string: &str) -> Option<Int> {
+        let n = string.trim().parse().ok()?;
+        Some(n).filter(|n| self.int_width.contains(*n))
+    }
+
+    /// Converts a float to an integer for `float_to_int`, rounding towards zero and saturating if
+    /// the float does not fit in the width of the integers. NaN becomes zero.
+    fn float_to_int(&self, x: f64) -> Int {
+        (x as Int).clamp(self.int_width.min(), self.int_width.max())
     }
 }
@@ -0,0 +1,176 @@
+//! Functions provided by the host.
+//!
+//! A [`NativeFn`] is a Rust function that Pijama programs can call as any other function. Native
+//! functions are grouped in [`NativeModule`]s, so a crate can ship a set of related functions that
+//! are registered on a machine in one call using [`MachineBuilder::with_module`].
+//!
+//! [`MachineBuilder::with_module`]: crate::machine::MachineBuilder::with_module
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::{
+    machine::{
+        value::{FromPijama, ToPijama},
+        Value,
+    },
+    ty::Ty,
+};
+
+/// The signature of the Rust function behind a [`NativeFn`].
+///
+/// The function receives one value per parameter, each one with the type declared for it.
+pub type NativeFnPtr = dyn Fn(&[Value]) -> Value + Send + Sync;
+
+/// A function provided by the host.
+pub struct NativeFn {
+    name: String,
+    params: Vec<Ty>,
+    ret: Ty,
+    func: Box<NativeFnPtr>,
+}
+
+impl NativeFn {
+    /// Creates a new native function called `name` with parameters of types `params` and return
+    /// type `ret`.
+    ///
+    /// Only the types of [`Value`]s can be used as parameter and return types: `Int`, `Float`,
+    /// `Bool`, `Unit`, `Char`, `String` and lists of these types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `params` is empty or if any of the types is not one of the types above. Functions
+    /// that do not need arguments can take a single `Unit` parameter.
+    pub fn new(
+        name: impl Into<String>,
+        params: Vec<Ty>,
+        ret: Ty,
+        func: impl Fn(&[Value]) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        let name = name.into();
+        assert!(
+            !params.is_empty(),
+            "Native function `{}` must have at least one parameter",
+            name
+        );
+        for ty in params.iter().chain(Some(&ret)) {
+            assert!(
+                is_value_ty(ty),
+                "Native function `{}` cannot use the type `{}`",
+                name,
+                ty
+            );
+        }
+        NativeFn {
+            name,
+            params,
+            ret,
+            func: Box::new(func),
+        }
+    }
+
+    /// Creates a new native function called `name` from a Rust function taking between one and
+    /// three arguments.
+    ///
+    /// The types of the native function are the ones of the [`FromPijama`] implementations of the
+    /// parameters and the [`ToPijama`] implementation of the return type, so `func` receives its
+    /// arguments already converted.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`NativeFn::new`].
+    pub fn from_fn<Args>(name: impl Into<String>, func: impl IntoNative<Args>) -> Self {
+        func.into_native(name.into())
+    }
+
+    /// Returns the name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of parameters of the function.
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns the types of the parameters of the function.
+    pub fn params(&self) -> &[Ty] {
+        &self.params
+    }
+
+    /// Returns the type of the function.
+    pub fn ty(&self) -> Ty {
+        self.params
+            .iter()
+            .rev()
+            .fold(self.ret.clone(), |ty, param| {
+                Ty::Arrow(Box::new(param.clone()), Box::new(ty))
+            })
+    }
+
+    /// Calls the function.
+    pub fn call(&self, args: &[Value]) -> Value {
+        (self.func)(args)
+    }
+}
+
+impl fmt::Debug for NativeFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NativeFn")
+            .field("name", &self.name)
+            .field("ty", &self.ty())
+            .finish()
+    }
+}
+
+/// Returns `true` if the values of `ty` can be exchanged as [`Value`]s.
+fn is_value_ty(ty: &Ty) -> bool {
+    match ty {
+        Ty::Int | Ty::Float | Ty::Bool | Ty::Unit | Ty::Char | Ty::String => true,
+        Ty::List(elem) => is_value_ty(elem),
+        _ => false,
+    }
+}
+
+/// Rust functions that can be turned into native functions with [`NativeFn::from_fn`].
+///
+/// `Args` is the tuple of the parameter types of the function.
+pub trait IntoNative<Args> {
+    /// Wraps this function in a native function called `name`.
+    fn into_native(self, name: String) -> NativeFn;
+}
+
+macro_rules! impl_into_native {
+    ($($arg:ident),+) => {
+        impl<F, R, $($arg),+> IntoNative<($($arg,)+)> for F
+        where
+            F: Fn($($arg),+) -> R + Send + Sync + 'static,
+            R: ToPijama,
+            $($arg: FromPijama,)+
+        {
+            fn into_native(self, name: String) -> NativeFn {
+                let params = vec![$(<$arg as FromPijama>::ty()),+];
+                NativeFn::new(name, params, R::ty(), move |args| {
+                    let mut args = args.iter();
+                    // The type checker ensures that each argument has the type of its parameter.
+                    self($($arg::from_value(args.next().unwrap().clone()).unwrap()),+).to_value()
+                })
+            }
+        }
+    };
+}
+
+impl_into_native!(A);
+impl_into_native!(A, B);
+impl_into_native!(A, B, C);
+
+/// A set of native functions that can be registered together.
+pub trait NativeModule {
+    /// Returns the functions provided by this module.
+    fn functions(self) -> Vec<NativeFn>;
+}
+
+impl NativeModule for Vec<NativeFn> {
+    fn functions(self) -> Vec<NativeFn> {
+        self
+    }
+}
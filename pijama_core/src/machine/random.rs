@@ -0,0 +1,99 @@
+//! The pseudorandom number generator used by the `random` primitive.
+//!
+//! The generator is a SplitMix64, which is small enough to be serialized with the snapshots of
+//! the machine and needs nothing from the standard library.
+use core::convert::TryFrom;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::Int;
+
+/// The state of a SplitMix64 generator.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Rng {
+    state: u64,
+}
+
+/// The seed used when the machine cannot get one from the operating system.
+#[cfg(not(feature = "std"))]
+const DEFAULT_SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+impl Rng {
+    pub(super) fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Returns a generator seeded by the operating system, or by a fixed seed without the `std`
+    /// feature.
+    pub(super) fn from_entropy() -> Self {
+        #[cfg(feature = "std")]
+        {
+            use std::{
+                collections::hash_map::RandomState,
+                hash::{BuildHasher, Hasher},
+            };
+            // Each `RandomState` has random keys, so hashing nothing gives a random number.
+            Rng::new(RandomState::new().build_hasher().finish())
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            Rng::new(DEFAULT_SEED)
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_u128(&mut self) -> u128 {
+        (u128::from(self.next_u64()) << 64) | u128::from(self.next_u64())
+    }
+
+    /// Returns a random number below `span`, which must not be zero.
+    fn below_u64(&mut self, span: u64) -> u64 {
+        // There are `2^64 % span` more numbers giving the first values of the range than the
+        // others, so rejecting as many numbers keeps the distribution uniform.
+        let skipped = span.wrapping_neg() % span;
+        let mut n = self.next_u64();
+        while n < skipped {
+            n = self.next_u64();
+        }
+        n % span
+    }
+
+    /// Returns a random number below `span`, which must not be zero.
+    fn below_u128(&mut self, span: u128) -> u128 {
+        let skipped = span.wrapping_neg() % span;
+        let mut n = self.next_u128();
+        while n < skipped {
+            n = self.next_u128();
+        }
+        n % span
+    }
+
+    /// Returns a random integer between `lo` and `hi`, both inclusive. The bounds can be given in
+    /// any order.
+    ///
+    /// Ranges with at most `2^64` values only draw 64-bit numbers, so a seeded machine returns the
+    /// same numbers whatever the width of its integers.
+    pub(super) fn between(&mut self, lo: Int, hi: Int) -> Int {
+        let (lo, hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+        // The number of values in the range, which is zero if the range has every `i128`.
+        let span = (hi as u128).wrapping_sub(lo as u128).wrapping_add(1);
+        if span == 0 {
+            return self.next_u128() as Int;
+        }
+        let offset = match u64::try_from(span) {
+            Ok(span) => u128::from(self.below_u64(span)),
+            Err(_) if span == 1 << 64 => u128::from(self.next_u64()),
+            Err(_) => self.below_u128(span),
+        };
+        lo.wrapping_add(offset as Int)
+    }
+}
@@ -0,0 +1,125 @@
+//! Snapshots of paused evaluations.
+//!
+//! [`Machine::evaluate_resumable`] evaluates a term like [`Machine::evaluate`], but when the
+//! machine runs out of fuel or the hook pauses the evaluation it returns a [`Snapshot`] of the
+//! machine instead. [`Machine::resume`] continues the evaluation from a snapshot, on the same
+//! machine or on another one with the same native functions, using the fuel of that machine.
+//!
+//! Snapshots can be serialized with the `serde` feature, and converted to and from bytes with the
+//! `json` feature too, so the evaluation can be resumed by another process.
+use alloc::{collections::VecDeque, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::Location;
+
+use crate::{
+    lir::Term,
+    machine::{
+        arithmetic::Arithmetic, env::Output, random::Rng, threads::Threads, Machine, RuntimeError,
+    },
+};
+
+/// The state of a paused evaluation.
+///
+/// Since the machine evaluates terms by substitution, the term being evaluated holds the whole
+/// control state of its thread. The snapshot also has the values of the cells and channels, the
+/// other threads, the functions entered and not exited yet and the state of the generator used
+/// by `random`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    term: Term,
+    cells: Vec<Term>,
+    channels: Vec<VecDeque<Term>>,
+    threads: Threads,
+    frames: Vec<(usize, Location)>,
+    rng: Rng,
+}
+
+impl Snapshot {
+    /// Returns the term being evaluated.
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
+    /// Serializes the snapshot as JSON.
+    #[cfg(all(feature = "serde", feature = "json"))]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Snapshots can always be serialized")
+    }
+
+    /// Deserializes a snapshot returned by [`Snapshot::to_bytes`].
+    #[cfg(all(feature = "serde", feature = "json"))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        // Terms can be nested deeper than the default limit.
+        deserializer.disable_recursion_limit();
+        let snapshot = Snapshot::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(snapshot)
+    }
+}
+
+/// The result of [`Machine::evaluate_resumable`] and [`Machine::resume`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Progress {
+    /// The evaluation ran out of fuel or was paused by the hook.
+    Paused(Snapshot),
+    /// The term cannot be evaluated any further.
+    Done(Term),
+}
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term` like [`Machine::evaluate`], returning a snapshot of the machine if it runs
+    /// out of fuel or the hook pauses the evaluation.
+    pub fn evaluate_resumable(&mut self, term: Term) -> Result<Progress, RuntimeError> {
+        self.check_determinism(&term)?;
+        let term = self.run(term);
+        let paused = core::mem::take(&mut self.paused);
+        match self.error.take() {
+            // Steps are not taken once the fuel runs out, so the term can be evaluated further.
+            Some(RuntimeError::OutOfFuel(_)) => Ok(Progress::Paused(self.snapshot(term))),
+            Some(error) => {
+                Term::drop_iteratively(term);
+                self.threads = Threads::default();
+                Err(error)
+            }
+            None if paused => Ok(Progress::Paused(self.snapshot(term))),
+            None => Ok(Progress::Done(term)),
+        }
+    }
+
+    /// Continues the evaluation paused in `snapshot`, replacing the cells, the channels, the
+    /// threads and the random number generator of the machine with the ones of the snapshot.
+    pub fn resume(&mut self, snapshot: Snapshot) -> Result<Progress, RuntimeError> {
+        self.cells = snapshot.cells;
+        self.channels = snapshot.channels;
+        self.threads = snapshot.threads;
+        self.frames = snapshot.frames;
+        self.rng = snapshot.rng;
+        self.evaluate_resumable(snapshot.term)
+    }
+
+    /// Sets the number of evaluation steps that the machine can still run, or removes the limit
+    /// if `steps` is `None`.
+    ///
+    /// See [`MachineBuilder::with_fuel`](crate::machine::MachineBuilder::with_fuel).
+    pub fn set_fuel(&mut self, steps: Option<u64>) {
+        self.fuel = steps;
+    }
+
+    /// Returns a snapshot of the evaluation of `term`, which takes the threads of the machine
+    /// since they only belong to this evaluation.
+    fn snapshot(&mut self, term: Term) -> Snapshot {
+        Snapshot {
+            term,
+            cells: self.cells.clone(),
+            channels: self.channels.clone(),
+            threads: core::mem::take(&mut self.threads),
+            frames: self.frames.clone(),
+            rng: self.rng,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Counters collected during the evaluation of a term.
+
+/// Statistics about an evaluation, returned by
+/// [`Machine::evaluate_with_stats`](crate::machine::Machine::evaluate_with_stats).
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// The number of reduction steps. It is the fuel needed to evaluate the term, see
+    /// [`MachineBuilder::with_fuel`](crate::machine::MachineBuilder::with_fuel).
+    pub steps: u64,
+    /// The number of functions, closures and native functions applied to an argument.
+    pub applications: u64,
+    /// The maximum number of nested evaluations, which grows with the subterms that must be
+    /// evaluated before their term, like the arguments of calls that are not in tail position.
+    pub max_depth: usize,
+    /// The number of cells created by `ref`, and of strings and lists built by operators and
+    /// primitives.
+    pub allocations: u64,
+}
@@ -0,0 +1,175 @@
+//! Green threads and channels.
+//!
+//! `spawn` starts a thread that applies a function to `unit`, and threads communicate through the
+//! channels created by `channel` with `send` and `recv`. Channels are unbounded, so only `recv`
+//! waits, until a value is sent to its channel.
+//!
+//! The machine runs one thread at a time and switches to the next one after [`TIME_SLICE`] steps
+//! or when the running thread waits on an empty channel. Since the term of a thread holds its
+//! whole control state, switching only pauses the evaluation of its term, like running out of
+//! steps does, and keeps the partially evaluated term until the thread runs again.
+//!
+//! The evaluation ends when the main thread does, discarding the threads that have not finished
+//! yet. If the main thread waits on a channel and no other thread can run, the evaluation stops
+//! with [`RuntimeError::Deadlock`].
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::mem;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::{Literal, Location};
+
+use crate::{
+    lir::Term,
+    machine::{arithmetic::Arithmetic, env::Output, Machine, RuntimeError},
+    stack,
+};
+
+/// The number of steps a thread runs before the machine switches to the next one.
+const TIME_SLICE: usize = 100;
+
+/// A thread that is not running.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+struct Thread {
+    term: Term,
+    main: bool,
+    /// The channel the thread waits on and the location of the `recv` waiting, if any.
+    waiting: Option<(usize, Location)>,
+    /// The functions entered by the thread and not exited yet.
+    frames: Vec<(usize, Location)>,
+}
+
+/// The threads of an evaluation.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Threads {
+    /// The threads that are not running, in the order they run next.
+    queue: VecDeque<Thread>,
+    /// Whether the running thread is the main thread.
+    main: bool,
+    /// The channel the running thread waits on and the location of the `recv` waiting, if any.
+    waiting: Option<(usize, Location)>,
+    /// The number of steps the running thread can run before switching to the next one.
+    slice: usize,
+    /// Whether the running thread stopped so the next one can run.
+    pub(super) switching: bool,
+}
+
+impl Default for Threads {
+    fn default() -> Self {
+        Threads {
+            queue: VecDeque::new(),
+            main: true,
+            waiting: None,
+            slice: TIME_SLICE,
+            switching: false,
+        }
+    }
+}
+
+impl Threads {
+    /// Consumes one step of the slice of the running thread, switching to the next thread when
+    /// the slice is over.
+    pub(super) fn tick(&mut self) {
+        if self.queue.is_empty() {
+            return;
+        }
+        self.slice -= 1;
+        if self.slice == 0 {
+            self.switching = true;
+        }
+    }
+}
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term` as the running thread, switching between threads until the main thread
+    /// finishes, the evaluation is paused or it stops with an error.
+    ///
+    /// If the evaluation is paused, the returned term is the one of the running thread, which
+    /// might not be the main thread.
+    pub(super) fn run(&mut self, term: Term) -> Term {
+        stack::segment(|| self.run_threads(term))
+    }
+
+    /// Evaluates `term` like [`Machine::run`] in the current segment of the stack of the host.
+    fn run_threads(&mut self, mut term: Term) -> Term {
+        loop {
+            let (_, new_term) = self.eval(term);
+            term = new_term;
+            let switching = mem::take(&mut self.threads.switching);
+            if self.error.is_some() {
+                return term;
+            }
+            if !switching {
+                if self.exhausted() {
+                    return term;
+                }
+                if self.threads.main {
+                    self.threads.queue.clear();
+                    return term;
+                }
+            }
+            // The running thread finished if it was not switched.
+            term = self.switch(term, !switching);
+            if self.exhausted() {
+                return term;
+            }
+        }
+    }
+
+    /// Switches from the running thread, whose term is `term`, to the next thread that can run
+    /// and returns its term. The running thread is discarded if it `finished`.
+    fn switch(&mut self, term: Term, finished: bool) -> Term {
+        let threads = &mut self.threads;
+        let waiting = threads.waiting.take();
+        if !finished {
+            threads.queue.push_back(Thread {
+                term,
+                main: threads.main,
+                waiting,
+                frames: mem::take(&mut self.frames),
+            });
+        }
+        threads.slice = TIME_SLICE;
+        let channels = &self.channels;
+        let next = threads.queue.iter().position(|thread| {
+            thread
+                .waiting
+                .is_none_or(|(index, _)| !channels[index].is_empty())
+        });
+        if let Some(next) = next.and_then(|next| threads.queue.remove(next)) {
+            threads.main = next.main;
+            self.frames = next.frames;
+            return next.term;
+        }
+        // The main thread is waiting, since the evaluation ends when it finishes.
+        let main = threads
+            .queue
+            .drain(..)
+            .find(|thread| thread.main)
+            .expect("The main thread has not finished");
+        threads.main = true;
+        let (_, loc) = main.waiting.expect("Every thread is waiting");
+        self.fail(RuntimeError::Deadlock(loc));
+        main.term
+    }
+
+    /// Starts a thread applying `function` to `unit`.
+    pub(super) fn spawn(&mut self, function: Term) {
+        let unit = Arc::new(Literal::Unit.into());
+        self.threads.queue.push_back(Thread {
+            term: Term::App(Arc::new(function), unit),
+            main: false,
+            waiting: None,
+            frames: Vec::new(),
+        });
+    }
+
+    /// Stops the running thread until a value is sent to the channel `index`.
+    pub(super) fn wait(&mut self, index: usize) {
+        self.threads.waiting = Some((index, self.loc));
+        self.threads.switching = true;
+    }
+}
@@ -0,0 +1,71 @@
+//! Evaluation one step at a time.
+//!
+//! [`Machine::steps`] returns a [`Steps`] iterator that yields the term being evaluated after each
+//! reduction step, which shows how the machine evaluates a program. Its items can be shown with
+//! the [pretty-printer](crate::lir::pretty) to make them readable.
+use crate::{
+    lir::Term,
+    machine::{arithmetic::Arithmetic, env::Output, threads::Threads, Machine, RuntimeError},
+};
+
+/// An iterator over the steps of an evaluation, created by [`Machine::steps`].
+pub struct Steps<'m, W: Output, A: Arithmetic> {
+    machine: &'m mut Machine<W, A>,
+    term: Option<Term>,
+    error: Option<RuntimeError>,
+}
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Evaluates `term` one step at a time.
+    ///
+    /// The returned iterator yields the term after each reduction step. It ends when the term
+    /// cannot be reduced any further or when the evaluation stops with an error, which is returned
+    /// by [`Steps::error`].
+    pub fn steps(&mut self, term: Term) -> Steps<'_, W, A> {
+        let (term, error) = match self.check_determinism(&term) {
+            Ok(()) => (Some(term), None),
+            Err(error) => (None, Some(error)),
+        };
+        Steps {
+            machine: self,
+            term,
+            error,
+        }
+    }
+}
+
+impl<W: Output, A: Arithmetic> Steps<'_, W, A> {
+    /// Returns the error that stopped the evaluation, if any.
+    pub fn error(&self) -> Option<&RuntimeError> {
+        self.error.as_ref()
+    }
+}
+
+impl<W: Output, A: Arithmetic> Iterator for Steps<'_, W, A> {
+    type Item = Term;
+
+    fn next(&mut self) -> Option<Term> {
+        let mut term = self.term.take()?;
+        loop {
+            self.machine.budget = Some(1);
+            term = self.machine.run(term);
+            // The budget is only consumed by reduction steps, the other steps only drop locations.
+            let reduced = self.machine.budget == Some(0);
+            self.machine.budget = None;
+            // Pausing the evaluation from a hook does not end the steps.
+            let paused = core::mem::take(&mut self.machine.paused);
+
+            if let Some(error) = self.machine.error.take() {
+                Term::drop_iteratively(term);
+                self.machine.threads = Threads::default();
+                self.error = Some(error);
+                return None;
+            } else if reduced {
+                self.term = Some(term.clone());
+                return Some(term);
+            } else if !paused {
+                return None;
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+#[cfg(feature = "json")]
+use core::convert::TryFrom;
+use core::fmt;
+
+use pijama_ast::{write_char_literal, write_str_literal, Int, Literal, Located, Location};
+
+use crate::{lir::Term, mir::Term as MirTerm, ty::Ty};
+
+/// A value that can be exchanged between Pijama programs and their host.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// An integer.
+    Int(Int),
+    /// A floating point number.
+    Float(f64),
+    /// A boolean.
+    Bool(bool),
+    /// The unit value.
+    Unit,
+    /// A character.
+    Char(char),
+    /// A string.
+    Str(String),
+    /// A list with the type of its elements.
+    List(Ty, Vec<Value>),
+}
+
+impl Value {
+    /// Returns the type of this value.
+    pub fn ty(&self) -> Ty {
+        match self {
+            Value::Int(_) => Ty::Int,
+            Value::Float(_) => Ty::Float,
+            Value::Bool(_) => Ty::Bool,
+            Value::Unit => Ty::Unit,
+            Value::Char(_) => Ty::Char,
+            Value::Str(_) => Ty::String,
+            Value::List(ty, _) => Ty::List(Box::new(ty.clone())),
+        }
+    }
+
+    /// Reads the result of evaluating a term of type `ty`.
+    ///
+    /// Returns `None` if `ty` is a function type or if `term` is not a literal, or a list of
+    /// values.
+    pub fn from_term(term: &Term, ty: &Ty) -> Option<Self> {
+        match (ty, term) {
+            (Ty::Int, Term::Lit(n)) => Some(Value::Int(*n)),
+            (Ty::Float, Term::Float(x)) => Some(Value::Float(*x)),
+            (Ty::Bool, Term::Lit(_)) => Some(Value::Bool(term.as_bool())),
+            (Ty::Unit, Term::Lit(_)) => Some(Value::Unit),
+            (Ty::Char, Term::Char(c)) => Some(Value::Char(*c)),
+            (Ty::String, Term::Str(string)) => Some(Value::Str(string.to_string())),
+            (Ty::List(ty), Term::List(elems)) => {
+                let elems = elems
+                    .iter()
+                    .map(|elem| Value::from_term(elem, ty))
+                    .collect::<Option<_>>()?;
+                Some(Value::List((**ty).clone(), elems))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the MIR term written as this value, located at `loc`.
+    pub fn to_mir<'a>(&self, loc: Location) -> Located<MirTerm<'a>> {
+        let term = match self {
+            Value::Int(n) => MirTerm::Lit(Literal::Number(*n)),
+            Value::Float(x) => MirTerm::Lit(Literal::Float(*x)),
+            Value::Bool(b) => MirTerm::Lit(Literal::Bool(*b)),
+            Value::Unit => MirTerm::Lit(Literal::Unit),
+            Value::Char(c) => MirTerm::Lit(Literal::Char(*c)),
+            Value::Str(string) => MirTerm::Lit(Literal::Str(string.clone())),
+            Value::List(_, elems) => {
+                MirTerm::List(elems.iter().map(|elem| elem.to_mir(loc)).collect())
+            }
+        };
+        loc.with_content(term)
+    }
+}
+
+impl From<Value> for Term {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Int(n) => Literal::Number(n).into(),
+            Value::Float(x) => Literal::Float(x).into(),
+            Value::Bool(b) => Literal::Bool(b).into(),
+            Value::Unit => Literal::Unit.into(),
+            Value::Char(c) => Literal::Char(c).into(),
+            Value::Str(string) => Term::Str(string.into()),
+            Value::List(_, elems) => Term::List(
+                elems
+                    .into_iter()
+                    .map(|elem| Arc::new(elem.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Int> for Value {
+    fn from(n: Int) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Float(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<char> for Value {
+    fn from(c: char) -> Self {
+        Value::Char(c)
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Unit
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::Str(string)
+    }
+}
+
+impl<T: ToPijama> From<Vec<T>> for Value {
+    fn from(elems: Vec<T>) -> Self {
+        Value::List(T::ty(), elems.into_iter().map(T::to_value).collect())
+    }
+}
+
+/// Rust types that can be passed to Pijama programs.
+///
+/// This is implemented for [`Int`], `f64`, `bool`, `()`, `char`, `String` and `Vec<T>`, which
+/// correspond to the `Int`, `Float`, `Bool`, `Unit`, `Char`, `String` and `[T]` types of Pijama.
+pub trait ToPijama {
+    /// Returns the Pijama type of the values of this type.
+    fn ty() -> Ty;
+
+    /// Converts this into a value.
+    fn to_value(self) -> Value;
+}
+
+/// Rust types that can be read from the values of Pijama programs.
+///
+/// This is implemented for the same types as [`ToPijama`].
+pub trait FromPijama: Sized {
+    /// Returns the Pijama type of the values that can be read as this type.
+    fn ty() -> Ty;
+
+    /// Reads `value`, returning `None` if it does not have the type returned by
+    /// [`FromPijama::ty`].
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+macro_rules! impl_pijama {
+    ($($rust:ty => $ty:ident, $value:pat => $content:expr;)*) => {
+        $(
+            impl ToPijama for $rust {
+                fn ty() -> Ty {
+                    Ty::$ty
+                }
+
+                fn to_value(self) -> Value {
+                    self.into()
+                }
+            }
+
+            impl FromPijama for $rust {
+                fn ty() -> Ty {
+                    Ty::$ty
+                }
+
+                fn from_value(value: Value) -> Option<Self> {
+                    match value {
+                        $value => Some($content),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_pijama! {
+    Int => Int, Value::Int(n) => n;
+    f64 => Float, Value::Float(x) => x;
+    bool => Bool, Value::Bool(b) => b;
+    () => Unit, Value::Unit => ();
+    char => Char, Value::Char(c) => c;
+    String => String, Value::Str(string) => string;
+}
+
+impl<T: ToPijama> ToPijama for Vec<T> {
+    fn ty() -> Ty {
+        Ty::List(Box::new(T::ty()))
+    }
+
+    fn to_value(self) -> Value {
+        self.into()
+    }
+}
+
+impl<T: FromPijama> FromPijama for Vec<T> {
+    fn ty() -> Ty {
+        Ty::List(Box::new(T::ty()))
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::List(_, elems) => elems.into_iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Unit => write!(f, "unit"),
+            Value::Char(c) => write_char_literal(f, *c),
+            Value::Str(string) => write_str_literal(f, string),
+            Value::List(_, elems) => {
+                write!(f, "[")?;
+                for (index, elem) in elems.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            // Integers outside the range of `i64` and `u64` become the nearest float.
+            Value::Int(n) => serde_json::Number::from_i128(n)
+                .map_or_else(|| (n as f64).into(), serde_json::Value::Number),
+            // Infinite and NaN floats become `null`.
+            Value::Float(x) => x.into(),
+            Value::Bool(b) => b.into(),
+            Value::Unit => serde_json::Value::Null,
+            Value::Char(c) => c.to_string().into(),
+            Value::Str(string) => string.into(),
+            Value::List(_, elems) => elems.into_iter().map(serde_json::Value::from).collect(),
+        }
+    }
+}
+
+/// The error returned when a JSON value cannot be converted into a [`Value`].
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FromJsonError(pub serde_json::Value);
+
+#[cfg(feature = "json")]
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "JSON value `{}` has no Pijama equivalent", self.0)
+    }
+}
+
+#[cfg(all(feature = "json", feature = "std"))]
+impl std::error::Error for FromJsonError {}
+
+/// Converts integers that fit in an `Int`, floats, booleans, strings, `null` and arrays into
+/// values.
+///
+/// Arrays become lists, so they must not be empty and all their elements must have the same
+/// type.
+#[cfg(feature = "json")]
+impl TryFrom<serde_json::Value> for Value {
+    type Error = FromJsonError;
+
+    fn try_from(json: serde_json::Value) -> Result<Self, Self::Error> {
+        match &json {
+            serde_json::Value::Null => Ok(Value::Unit),
+            serde_json::Value::Bool(b) => Ok(Value::Bool(*b)),
+            serde_json::Value::Number(n) if n.is_f64() => Ok(Value::Float(n.as_f64().unwrap())),
+            serde_json::Value::Number(n) => n.as_i128().map(Value::Int).ok_or(FromJsonError(json)),
+            serde_json::Value::String(string) => Ok(Value::Str(string.clone())),
+            serde_json::Value::Array(elems) => {
+                let elems = elems
+                    .iter()
+                    .map(|elem| Value::try_from(elem.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match elems.first().map(Value::ty) {
+                    Some(ty) if elems.iter().all(|elem| elem.ty() == ty) => {
+                        Ok(Value::List(ty, elems))
+                    }
+                    _ => Err(FromJsonError(json)),
+                }
+            }
+            serde_json::Value::Object(_) => Err(FromJsonError(json)),
+        }
+    }
+}
@@ -0,0 +1,709 @@
+//! The stack machine running the programs compiled by the [`bytecode`](crate::bytecode) module.
+use alloc::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::convert::TryFrom;
+
+use pijama_ast::{BinOp, Int, Location, Primitive};
+
+use crate::{
+    bytecode::{Capture, Env, Instr, Program},
+    lir::{Pattern, Term},
+    machine::{
+        arithmetic::{self, Arithmetic},
+        env::Output,
+        eval, Machine, RuntimeError, Value,
+    },
+};
+
+impl<W: Output, A: Arithmetic> Machine<W, A> {
+    /// Runs `program`, returning the value of its term or the runtime error that stopped it.
+    ///
+    /// The program runs with the output, input, arithmetic and native functions of the machine.
+    /// It does not go through the steps of the evaluation, so it does not consume fuel, collect
+    /// statistics or call the hook.
+    pub fn evaluate_bytecode(&mut self, program: &Program) -> Result<Term, RuntimeError> {
+        self.check_program_determinism(program)?;
+        let mut vm = Vm {
+            machine: self,
+            program,
+            cells: Vec::new(),
+        };
+        let value = vm.run(0, Arc::from([]))?;
+        Ok(vm.quote(&value))
+    }
+}
+
+/// A value computed by a program.
+#[derive(Debug, Clone)]
+enum Object {
+    Int(Int),
+    Float(f64),
+    Char(char),
+    Str(Arc<str>),
+    List(Arc<Vec<Object>>),
+    Variant(usize, Arc<str>, Arc<[Object]>),
+    Function(Arc<Function>),
+    /// A primitive with the location of its use and the arguments it has received so far.
+    Prim(Primitive, Location, Vec<Slot>),
+    /// A native function with the arguments it has received so far.
+    Native(usize, Vec<Object>),
+    /// A mutable cell, identified by its index in the running program.
+    Cell(usize),
+}
+
+/// A function with the chunk of its code and the values placed after its parameter.
+#[derive(Debug)]
+struct Function {
+    chunk: usize,
+    env: Arc<[Slot]>,
+}
+
+/// A local variable, or an argument in the stack.
+#[derive(Debug, Clone)]
+enum Slot {
+    Value(Object),
+    /// An argument that is not evaluated yet, with its chunk and the local variables of the
+    /// chunk creating it. It is run each time it is used.
+    Thunk(usize, Arc<[Slot]>),
+    /// The fixed point of a function.
+    Fix(Arc<Function>),
+}
+
+/// A chunk being run.
+struct Frame {
+    chunk: usize,
+    /// The position of the next instruction.
+    pc: usize,
+    locals: Arc<[Slot]>,
+    /// The height of the stack when the chunk started.
+    base: usize,
+}
+
+struct Vm<'m, W: Output, A: Arithmetic> {
+    machine: &'m mut Machine<W, A>,
+    program: &'m Program,
+    cells: Vec<Object>,
+}
+
+impl<'m, W: Output, A: Arithmetic> Vm<'m, W, A> {
+    /// Runs the chunk with index `chunk` until it returns.
+    fn run(&mut self, chunk: usize, locals: Arc<[Slot]>) -> Result<Object, RuntimeError> {
+        let program = self.program;
+        let mut stack = Vec::new();
+        let mut frames = Vec::new();
+        let mut frame = Frame {
+            chunk,
+            pc: 0,
+            locals,
+            base: 0,
+        };
+        loop {
+            let instr = &program.chunks[frame.chunk].code[frame.pc];
+            frame.pc += 1;
+            match instr {
+                Instr::Int(n) => push(&mut stack, Object::Int(*n)),
+                Instr::Float(x) => push(&mut stack, Object::Float(*x)),
+                Instr::Char(c) => push(&mut stack, Object::Char(*c)),
+                Instr::Str(string) => push(&mut stack, Object::Str(Arc::clone(string))),
+                Instr::Prim(prim, loc) => push(&mut stack, Object::Prim(*prim, *loc, Vec::new())),
+                Instr::Load(index) => match &frame.locals[*index] {
+                    Slot::Value(value) => push(&mut stack, value.clone()),
+                    Slot::Thunk(chunk, locals) => {
+                        let (chunk, locals) = (*chunk, Arc::clone(locals));
+                        call(&mut frames, &mut frame, &mut stack, chunk, locals, false);
+                    }
+                    Slot::Fix(function) => {
+                        let (chunk, locals) = fixed_point(function);
+                        call(&mut frames, &mut frame, &mut stack, chunk, locals, false);
+                    }
+                },
+                Instr::Arg(index) => stack.push(frame.locals[*index].clone()),
+                Instr::Thunk(chunk) => stack.push(Slot::Thunk(*chunk, Arc::clone(&frame.locals))),
+                Instr::Function(chunk, env) => {
+                    let env = captured(env, &frame.locals).collect();
+                    let function = Function { chunk: *chunk, env };
+                    push(&mut stack, Object::Function(Arc::new(function)));
+                }
+                Instr::Apply(tail) => {
+                    let arg = stack.pop().expect("Missing argument");
+                    match pop(&mut stack) {
+                        Object::Function(function) => {
+                            let locals = Some(arg).into_iter().chain(function.env.iter().cloned());
+                            let locals = locals.collect();
+                            call(
+                                &mut frames,
+                                &mut frame,
+                                &mut stack,
+                                function.chunk,
+                                locals,
+                                *tail,
+                            );
+                        }
+                        Object::Prim(prim, loc, args) => {
+                            let value = self.apply_prim(prim, loc, args, arg)?;
+                            push(&mut stack, value);
+                        }
+                        Object::Native(index, args) => {
+                            let value = self.apply_native(index, args, arg)?;
+                            push(&mut stack, value);
+                        }
+                        function => panic!("Cannot apply {}", self.quote(&function)),
+                    }
+                }
+                Instr::Fix(tail) => match pop(&mut stack) {
+                    Object::Function(function) => {
+                        let (chunk, locals) = fixed_point(&function);
+                        call(&mut frames, &mut frame, &mut stack, chunk, locals, *tail);
+                    }
+                    function => panic!("Cannot find the fixed point of {}", self.quote(&function)),
+                },
+                Instr::Native(index, args) => {
+                    let args = stack.split_off(stack.len() - args);
+                    let args = args.into_iter().map(into_object).collect();
+                    push(&mut stack, Object::Native(*index, args));
+                }
+                Instr::BinOp(op, loc) => {
+                    let v2 = pop(&mut stack);
+                    let v1 = pop(&mut stack);
+                    let value = self.binary_operation(*op, *loc, v1, v2)?;
+                    push(&mut stack, value);
+                }
+                Instr::UnOp(op, loc) => {
+                    let value = match pop(&mut stack) {
+                        Object::Int(n) => A::unary_operation(*op, n, self.machine.int_width)
+                            .map(Object::Int)
+                            .ok_or(RuntimeError::Overflow(*loc))?,
+                        // Floats can only be negated.
+                        Object::Float(x) => Object::Float(-x),
+                        value => {
+                            unreachable!("Invalid operand {} for `{}`", self.quote(&value), op)
+                        }
+                    };
+                    push(&mut stack, value);
+                }
+                Instr::Jump(target) => frame.pc = *target,
+                Instr::JumpIf(flag, target) => {
+                    if as_bool(&pop(&mut stack)) == *flag {
+                        frame.pc = *target;
+                    }
+                }
+                Instr::Variant(index, name, args) => {
+                    let args = stack.split_off(stack.len() - args);
+                    let args = args.into_iter().map(into_object).collect();
+                    push(&mut stack, Object::Variant(*index, Arc::clone(name), args));
+                }
+                Instr::List(elems) => {
+                    let elems = stack.split_off(stack.len() - elems);
+                    let elems = elems.into_iter().map(into_object).collect();
+                    push(&mut stack, Object::List(Arc::new(elems)));
+                }
+                Instr::Test(pattern, target) => {
+                    let value = match stack.last() {
+                        Some(Slot::Value(value)) => value,
+                        _ => unreachable!("Missing scrutinee"),
+                    };
+                    if !matches(pattern, value) {
+                        frame.pc = *target;
+                    }
+                }
+                Instr::Pop => {
+                    stack.pop();
+                }
+                Instr::Arm(chunk, env, tail) => {
+                    let fields = match pop(&mut stack) {
+                        Object::Variant(_, _, fields) => fields,
+                        value => unreachable!("Non-variant scrutinee {}", self.quote(&value)),
+                    };
+                    // The last field is bound by the innermost name.
+                    let fields = fields.iter().rev().cloned().map(Slot::Value);
+                    let locals = fields.chain(captured(env, &frame.locals)).collect();
+                    call(&mut frames, &mut frame, &mut stack, *chunk, locals, *tail);
+                }
+                Instr::NoMatch => panic!("No arm matches {}", self.quote(&pop(&mut stack))),
+                Instr::Ref => {
+                    let value = pop(&mut stack);
+                    self.cells.push(value);
+                    push(&mut stack, Object::Cell(self.cells.len() - 1));
+                }
+                Instr::Deref => {
+                    let index = cell(pop(&mut stack));
+                    push(&mut stack, self.cells[index].clone());
+                }
+                Instr::Assign => {
+                    let value = pop(&mut stack);
+                    let index = cell(pop(&mut stack));
+                    self.cells[index] = value;
+                    push(&mut stack, Object::Int(0));
+                }
+                Instr::Assert(loc) => {
+                    if !as_bool(&pop(&mut stack)) {
+                        return Err(RuntimeError::AssertionFailed(*loc));
+                    }
+                    push(&mut stack, Object::Int(0));
+                }
+                Instr::Read(prim, loc) => {
+                    let line = self.machine.read_line(*loc)?;
+                    let value = match prim {
+                        Primitive::ReadLine => Object::Str(line.into()),
+                        _ => match self.machine.parse_int(&line) {
+                            Some(n) => Object::Int(n),
+                            None => return Err(RuntimeError::InvalidInt(*loc, line)),
+                        },
+                    };
+                    push(&mut stack, value);
+                }
+                Instr::Return => {
+                    let value = stack.pop().expect("Missing return value");
+                    stack.truncate(frame.base);
+                    match frames.pop() {
+                        Some(caller) => {
+                            frame = caller;
+                            stack.push(value);
+                        }
+                        None => return Ok(into_object(value)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the value of an argument, running it if it is not evaluated.
+    fn force(&mut self, slot: Slot) -> Result<Object, RuntimeError> {
+        match slot {
+            Slot::Value(value) => Ok(value),
+            Slot::Thunk(chunk, locals) => self.run(chunk, locals),
+            Slot::Fix(function) => {
+                let (chunk, locals) = fixed_point(&function);
+                self.run(chunk, locals)
+            }
+        }
+    }
+
+    fn binary_operation(
+        &mut self,
+        op: BinOp,
+        loc: Location,
+        v1: Object,
+        v2: Object,
+    ) -> Result<Object, RuntimeError> {
+        use BinOp::*;
+
+        let value = match (op, v1, v2) {
+            // Integers divided by zero stop the evaluation, floats are divided as usual.
+            (Div, Object::Int(_), Object::Int(0)) | (Rem, Object::Int(_), Object::Int(0)) => {
+                return Err(RuntimeError::DivisionByZero(loc))
+            }
+            (op, Object::Int(n1), Object::Int(n2)) => {
+                A::binary_operation(op, n1, n2, self.machine.int_width)
+                    .map(Object::Int)
+                    .ok_or(RuntimeError::Overflow(loc))?
+            }
+            (op, Object::Float(x1), Object::Float(x2)) => match eval::float_operation(op, x1, x2) {
+                Term::Float(x) => Object::Float(x),
+                Term::Lit(n) => Object::Int(n),
+                _ => unreachable!(),
+            },
+            (op, Object::Char(c1), Object::Char(c2)) => {
+                let (n1, n2) = (Int::from(u32::from(c1)), Int::from(u32::from(c2)));
+                A::binary_operation(op, n1, n2, self.machine.int_width)
+                    .map(Object::Int)
+                    .ok_or(RuntimeError::Overflow(loc))?
+            }
+            (Add, Object::Str(s1), Object::Str(s2)) => {
+                let mut string = String::with_capacity(s1.len() + s2.len());
+                string.push_str(&s1);
+                string.push_str(&s2);
+                Object::Str(string.into())
+            }
+            (Eq, v1, v2) => Object::Int(self.equal(&v1, &v2).into()),
+            (Neq, v1, v2) => Object::Int((!self.equal(&v1, &v2)).into()),
+            (op, v1, v2) => unreachable!(
+                "Invalid operands {} and {} for `{}`",
+                self.quote(&v1),
+                self.quote(&v2),
+                op
+            ),
+        };
+        Ok(value)
+    }
+
+    /// Returns `true` if the values are equal, comparing lists like the machine compares their
+    /// terms.
+    fn equal(&self, v1: &Object, v2: &Object) -> bool {
+        match (v1, v2) {
+            (Object::Int(n1), Object::Int(n2)) => n1 == n2,
+            (Object::Float(x1), Object::Float(x2)) => x1 == x2,
+            (Object::Char(c1), Object::Char(c2)) => c1 == c2,
+            (Object::Str(s1), Object::Str(s2)) => s1 == s2,
+            (Object::List(elems1), Object::List(elems2)) => {
+                elems1.len() == elems2.len()
+                    && elems1
+                        .iter()
+                        .zip(elems2.iter())
+                        .all(|(e1, e2)| self.equal(e1, e2))
+            }
+            (Object::Variant(index1, _, args1), Object::Variant(index2, _, args2)) => {
+                index1 == index2
+                    && args1.len() == args2.len()
+                    && args1
+                        .iter()
+                        .zip(args2.iter())
+                        .all(|(a1, a2)| self.equal(a1, a2))
+            }
+            (v1, v2) => self.quote(v1) == self.quote(v2),
+        }
+    }
+
+    /// Applies a primitive used at `loc` that received `args` before to `arg`.
+    fn apply_prim(
+        &mut self,
+        prim: Primitive,
+        loc: Location,
+        mut args: Vec<Slot>,
+        arg: Slot,
+    ) -> Result<Object, RuntimeError> {
+        // Primitives taking several arguments are values until they receive all of them.
+        if args.len() + 1 < prim.arity() {
+            args.push(arg);
+            return Ok(Object::Prim(prim, loc, args));
+        }
+        let value = match prim {
+            Primitive::Print => {
+                let arg = self.force(arg)?;
+                // Characters and strings are printed without quotes.
+                match &arg {
+                    Object::Char(c) => self.machine.print_line(loc, format_args!("{}", c)),
+                    Object::Str(string) => self.machine.print_line(loc, format_args!("{}", string)),
+                    arg => {
+                        let arg = self.quote(arg);
+                        self.machine.print_line(loc, format_args!("{}", arg))
+                    }
+                }?;
+                Object::Int(0)
+            }
+            Primitive::Concat | Primitive::Assert | Primitive::Force => {
+                unreachable!("`{}` is lowered to an abstraction", prim)
+            }
+            Primitive::ReadLine | Primitive::ReadInt => {
+                unreachable!("`{}` does not take arguments", prim)
+            }
+            Primitive::Spawn | Primitive::Channel | Primitive::Send | Primitive::Recv => {
+                unreachable!("`{}` cannot be compiled", prim)
+            }
+            Primitive::IntToFloat
+            | Primitive::FloatToInt
+            | Primitive::CharToInt
+            | Primitive::IntToChar
+            | Primitive::ToString
+            | Primitive::ParseInt => match (prim, self.force(arg)?) {
+                (Primitive::IntToFloat, Object::Int(n)) => Object::Float(n as f64),
+                // The conversion rounds towards zero and saturates if the number does not fit in
+                // an integer.
+                (Primitive::FloatToInt, Object::Float(x)) => {
+                    Object::Int(self.machine.float_to_int(x))
+                }
+                (Primitive::CharToInt, Object::Char(c)) => Object::Int(u32::from(c).into()),
+                (Primitive::IntToChar, Object::Int(n)) => {
+                    match u32::try_from(n).ok().and_then(char::from_u32) {
+                        Some(c) => Object::Char(c),
+                        None => return Err(RuntimeError::InvalidChar(loc, n)),
+                    }
+                }
+                // Values are converted in the same way they are printed.
+                (Primitive::ToString, Object::Str(string)) => Object::Str(string),
+                (Primitive::ToString, Object::Char(c)) => Object::Str(c.to_string().into()),
+                (Primitive::ToString, arg) => Object::Str(format!("{}", self.quote(&arg)).into()),
+                // Integers are parsed like the lines read by `read_int`.
+                (Primitive::ParseInt, Object::Str(string)) => match self.machine.parse_int(&string)
+                {
+                    Some(n) => Object::Int(n),
+                    None => return Err(RuntimeError::InvalidInt(loc, string.to_string())),
+                },
+                (prim, arg) => panic!("Invalid argument {} for `{}`", self.quote(&arg), prim),
+            },
+            Primitive::Head | Primitive::Tail | Primitive::Len => match (prim, self.force(arg)?) {
+                (Primitive::Len, Object::List(elems)) => Object::Int(elems.len() as Int),
+                (Primitive::Len, Object::Str(string)) => Object::Int(string.chars().count() as Int),
+                (_, Object::List(elems)) if elems.is_empty() => {
+                    return Err(RuntimeError::IndexOutOfBounds(loc, 0, 0))
+                }
+                (Primitive::Head, Object::List(elems)) => elems[0].clone(),
+                (Primitive::Tail, Object::List(elems)) => {
+                    Object::List(Arc::new(elems[1..].to_vec()))
+                }
+                (prim, arg) => panic!("Invalid argument {} for `{}`", self.quote(&arg), prim),
+            },
+            Primitive::Abs | Primitive::Sign => match self.force(arg)? {
+                Object::Int(n) => arithmetic::unary_primitive::<A>(prim, n, self.machine.int_width)
+                    .map(Object::Int)
+                    .ok_or(RuntimeError::Overflow(loc))?,
+                arg => panic!("Invalid argument {} for `{}`", self.quote(&arg), prim),
+            },
+            Primitive::Min | Primitive::Max | Primitive::Gcd | Primitive::Pow => {
+                let n1 = self.force(args.remove(0))?;
+                let n2 = self.force(arg)?;
+                match (n1, n2) {
+                    // Zero cannot be raised to a negative power, like it cannot divide.
+                    (Object::Int(0), Object::Int(n2)) if prim == Primitive::Pow && n2 < 0 => {
+                        return Err(RuntimeError::DivisionByZero(loc))
+                    }
+                    (Object::Int(n1), Object::Int(n2)) => {
+                        arithmetic::binary_primitive::<A>(prim, n1, n2, self.machine.int_width)
+                            .map(Object::Int)
+                            .ok_or(RuntimeError::Overflow(loc))?
+                    }
+                    (arg1, arg2) => panic!(
+                        "Invalid arguments {} and {} for `{}`",
+                        self.quote(&arg1),
+                        self.quote(&arg2),
+                        prim
+                    ),
+                }
+            }
+            Primitive::Random => match (self.force(args.remove(0))?, self.force(arg)?) {
+                (Object::Int(lo), Object::Int(hi)) => {
+                    Object::Int(self.machine.random(loc, lo, hi)?)
+                }
+                (arg1, arg2) => panic!(
+                    "Invalid arguments {} and {} for `{}`",
+                    self.quote(&arg1),
+                    self.quote(&arg2),
+                    prim
+                ),
+            },
+            Primitive::Push | Primitive::Get | Primitive::CharAt => {
+                let arg1 = self.force(args.remove(0))?;
+                let arg2 = self.force(arg)?;
+                match (prim, arg1, arg2) {
+                    (Primitive::CharAt, Object::Str(string), Object::Int(index)) => {
+                        match eval::char_at(&string, index) {
+                            Some(c) => Object::Char(c),
+                            None => {
+                                let len = string.chars().count();
+                                return Err(RuntimeError::IndexOutOfBounds(loc, index, len));
+                            }
+                        }
+                    }
+                    (Primitive::Push, Object::List(mut elems), elem) => {
+                        Arc::make_mut(&mut elems).push(elem);
+                        Object::List(elems)
+                    }
+                    (Primitive::Get, Object::List(elems), Object::Int(index)) => {
+                        let len = elems.len();
+                        match usize::try_from(index) {
+                            Ok(i) if i < len => elems[i].clone(),
+                            _ => return Err(RuntimeError::IndexOutOfBounds(loc, index, len)),
+                        }
+                    }
+                    (prim, arg1, arg2) => panic!(
+                        "Invalid arguments {} and {} for `{}`",
+                        self.quote(&arg1),
+                        self.quote(&arg2),
+                        prim
+                    ),
+                }
+            }
+            Primitive::Substring => {
+                let arg1 = self.force(args.remove(0))?;
+                let arg2 = self.force(args.remove(0))?;
+                let arg3 = self.force(arg)?;
+                match (arg1, arg2, arg3) {
+                    (Object::Str(string), Object::Int(start), Object::Int(end)) => {
+                        Object::Str(eval::substring(loc, &string, start, end)?.into())
+                    }
+                    (arg1, arg2, arg3) => panic!(
+                        "Invalid arguments {}, {} and {} for `{}`",
+                        self.quote(&arg1),
+                        self.quote(&arg2),
+                        self.quote(&arg3),
+                        prim
+                    ),
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    /// Applies the native function with index `index` that received `args` before to `arg`.
+    fn apply_native(
+        &mut self,
+        index: usize,
+        mut args: Vec<Object>,
+        arg: Slot,
+    ) -> Result<Object, RuntimeError> {
+        // Natives only receive values.
+        args.push(self.force(arg)?);
+        let native = &self.machine.natives[index];
+        // If the function has not received all its arguments yet, return the partially applied
+        // function.
+        if args.len() < native.arity() {
+            return Ok(Object::Native(index, args));
+        }
+        let values: Vec<Value> = args
+            .iter()
+            .zip(native.params())
+            .map(|(arg, ty)| {
+                let arg = self.quote(arg);
+                Value::from_term(&arg, ty)
+                    .unwrap_or_else(|| panic!("Non-value argument {} for `{}`", arg, native.name()))
+            })
+            .collect();
+        Ok(object(native.call(&values)))
+    }
+
+    /// Returns the term of a value.
+    fn quote(&self, value: &Object) -> Term {
+        let quote_all =
+            |values: &[Object]| values.iter().map(|v| Arc::new(self.quote(v))).collect();
+        match value {
+            Object::Int(n) => Term::Lit(*n),
+            Object::Float(x) => Term::Float(*x),
+            Object::Char(c) => Term::Char(*c),
+            Object::Str(string) => Term::Str(Arc::clone(string)),
+            Object::List(elems) => Term::List(quote_all(elems)),
+            Object::Variant(index, name, args) => {
+                Term::Variant(*index, Arc::clone(name), quote_all(args))
+            }
+            Object::Function(function) => self.quote_function(function),
+            Object::Prim(prim, _, args) => args.iter().fold(Term::PrimFn(*prim), |head, arg| {
+                Term::App(Arc::new(head), Arc::new(self.quote_slot(arg)))
+            }),
+            Object::Native(index, args) => Term::Native(*index, quote_all(args)),
+            Object::Cell(index) => Term::Cell(*index),
+        }
+    }
+
+    fn quote_slot(&self, slot: &Slot) -> Term {
+        match slot {
+            Slot::Value(value) => self.quote(value),
+            Slot::Thunk(chunk, locals) => {
+                let locals: Vec<_> = locals
+                    .iter()
+                    .map(|s| Arc::new(self.quote_slot(s)))
+                    .collect();
+                let term = &self.program.chunks[*chunk].term;
+                Term::take(Term::instantiate(term, &[], &locals))
+            }
+            Slot::Fix(function) => Term::Fix(Arc::new(self.quote_function(function))),
+        }
+    }
+
+    /// Returns the term of a function as a closure, whose code is the term of its chunk.
+    fn quote_function(&self, function: &Function) -> Term {
+        let code = Arc::clone(&self.program.chunks[function.chunk].term);
+        let env = function
+            .env
+            .iter()
+            .map(|s| Arc::new(self.quote_slot(s)))
+            .collect();
+        Term::Closure(code, env)
+    }
+}
+
+/// Starts running the chunk with index `chunk`. If `tail` is set, it replaces the current chunk.
+fn call(
+    frames: &mut Vec<Frame>,
+    frame: &mut Frame,
+    stack: &mut Vec<Slot>,
+    chunk: usize,
+    locals: Arc<[Slot]>,
+    tail: bool,
+) {
+    if tail {
+        stack.truncate(frame.base);
+        *frame = Frame {
+            chunk,
+            pc: 0,
+            locals,
+            base: frame.base,
+        };
+    } else {
+        let callee = Frame {
+            chunk,
+            pc: 0,
+            locals,
+            base: stack.len(),
+        };
+        frames.push(core::mem::replace(frame, callee));
+    }
+}
+
+/// Returns the values captured by a function or an arm created by a chunk with `locals`.
+fn captured<'e>(env: &'e Env, locals: &'e Arc<[Slot]>) -> impl Iterator<Item = Slot> + 'e {
+    let (all, captures) = match env {
+        Env::All => (&locals[..], &[][..]),
+        Env::Captures(captures) => (&[][..], &captures[..]),
+    };
+    let captures = captures.iter().map(move |capture| match capture {
+        Capture::Local(index) => locals[*index].clone(),
+        Capture::Chunk(chunk) => Slot::Thunk(*chunk, Arc::clone(locals)),
+    });
+    all.iter().cloned().chain(captures)
+}
+
+/// Returns the chunk and the local variables of `function` applied to its own fixed point.
+fn fixed_point(function: &Arc<Function>) -> (usize, Arc<[Slot]>) {
+    let fix = Slot::Fix(Arc::clone(function));
+    let locals = Some(fix).into_iter().chain(function.env.iter().cloned());
+    (function.chunk, locals.collect())
+}
+
+fn push(stack: &mut Vec<Slot>, value: Object) {
+    stack.push(Slot::Value(value));
+}
+
+fn pop(stack: &mut Vec<Slot>) -> Object {
+    into_object(stack.pop().expect("Missing operand"))
+}
+
+/// Returns the value in `slot`. Only arguments can be unevaluated, and they are never operands.
+fn into_object(slot: Slot) -> Object {
+    match slot {
+        Slot::Value(value) => value,
+        slot => unreachable!("Unevaluated operand {:?}", slot),
+    }
+}
+
+/// Returns the object of a value returned by a native function.
+fn object(value: Value) -> Object {
+    match value {
+        Value::Int(n) => Object::Int(n),
+        Value::Float(x) => Object::Float(x),
+        Value::Bool(b) => Object::Int(b.into()),
+        Value::Unit => Object::Int(0),
+        Value::Char(c) => Object::Char(c),
+        Value::Str(string) => Object::Str(string.into()),
+        Value::List(_, elems) => Object::List(Arc::new(elems.into_iter().map(object).collect())),
+    }
+}
+
+fn as_bool(value: &Object) -> bool {
+    match value {
+        Object::Int(0) => false,
+        Object::Int(1) => true,
+        value => panic!("Non-boolean value {:?}", value),
+    }
+}
+
+fn cell(value: Object) -> usize {
+    match value {
+        Object::Cell(index) => index,
+        value => panic!("Non-cell value {:?}", value),
+    }
+}
+
+fn matches(pattern: &Pattern, value: &Object) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) => true,
+        (Pattern::Lit(lit), Object::Int(n)) => lit == n,
+        (Pattern::Float(x), Object::Float(x2)) => x == x2,
+        (Pattern::Char(c), Object::Char(c2)) => c == c2,
+        (Pattern::Str(string), Object::Str(string2)) => string == string2,
+        (Pattern::Variant(index, _), Object::Variant(index2, _, _)) => index == index2,
+        _ => false,
+    }
+}
@@ -0,0 +1,198 @@
+//! Annotated textual dump of the MIR.
+//!
+//! The [`Display`](core::fmt::Display) implementation of [`Term`] keeps the whole term in a single
+//! line. The [`dump`] function writes one term per line instead, indented under its parent, with
+//! the type inferred for it and its location:
+//!
+//! ```text
+//! let x : Int @0..11
+//!   lit 1 : Int @4..5
+//!   binary + : Int @6..11
+//!     var x : Int @6..7
+//!     lit 2 : Int @10..11
+//! ```
+//!
+//! Type annotations are written in brackets after the name they annotate, like `let (x : Int)`.
+//! The functions of a [`Term::LetRec`], the variants of a [`Term::Enum`] and the arms of a
+//! [`Term::Match`] get their own lines, with the location of their name or pattern and without a
+//! type. The output only depends on the term and its types, so it can be compared in golden
+//! tests.
+use alloc::{format, string::String};
+use core::fmt::Write;
+
+use pijama_ast::{Located, Location, Name};
+
+use crate::{
+    mir::{LetKind, Term},
+    ty::{Ty, TyTable},
+};
+
+/// The number of columns each level of nesting is indented by.
+const INDENT: usize = 2;
+
+/// Returns the annotated dump of `mir`, with the types of its terms in `tys`.
+///
+/// Terms without a type in `tys` are written without one.
+pub fn dump<'a>(mir: &Located<Term<'a>>, tys: &TyTable<'_, 'a>) -> String {
+    let mut dumper = Dumper {
+        tys,
+        output: String::new(),
+        depth: 0,
+    };
+    dumper.term(mir);
+    dumper.output
+}
+
+struct Dumper<'d, 't, 'a> {
+    tys: &'d TyTable<'t, 'a>,
+    output: String,
+    depth: usize,
+}
+
+impl<'d, 't, 'a> Dumper<'d, 't, 'a> {
+    fn term(&mut self, term: &Located<Term<'a>>) {
+        let ty = self.tys.get(&term.content);
+        match &term.content {
+            Term::Var(name) => self.line(&format!("var {}", name), ty, term.loc),
+            Term::Abs(param, param_ty, body) => {
+                let head = format!("abs ({} : {})", param.content, param_ty);
+                self.line(&head, ty, term.loc);
+                self.nested(|dumper| dumper.term(body));
+            }
+            Term::UnaryOp(op, t1) => {
+                self.line(&format!("unary {}", op), ty, term.loc);
+                self.nested(|dumper| dumper.term(t1));
+            }
+            Term::BinaryOp(op, t1, t2) => {
+                self.line(&format!("binary {}", op), ty, term.loc);
+                self.nested(|dumper| {
+                    dumper.term(t1);
+                    dumper.term(t2);
+                });
+            }
+            Term::App(t1, t2) => {
+                self.line("app", ty, term.loc);
+                self.nested(|dumper| {
+                    dumper.term(t1);
+                    dumper.term(t2);
+                });
+            }
+            Term::Lit(literal) => self.line(&format!("lit {}", literal), ty, term.loc),
+            Term::List(elems) => {
+                self.line("list", ty, term.loc);
+                self.nested(|dumper| elems.iter().for_each(|elem| dumper.term(elem)));
+            }
+            Term::Cond(t1, t2, t3) => {
+                self.line("if", ty, term.loc);
+                self.nested(|dumper| {
+                    dumper.term(t1);
+                    dumper.term(t2);
+                    dumper.term(t3);
+                });
+            }
+            Term::Let(kind, name, t1, t2) => {
+                let name = name.content;
+                match kind {
+                    LetKind::NonRec(None) => self.line(&format!("let {}", name), ty, term.loc),
+                    LetKind::NonRec(Some(ann)) => {
+                        self.line(&annotated("let", name, ann), ty, term.loc)
+                    }
+                    LetKind::Rec(ann) => self.line(&annotated("let rec", name, ann), ty, term.loc),
+                    LetKind::Mut(None) => self.line(&format!("let mut {}", name), ty, term.loc),
+                    LetKind::Mut(Some(ann)) => {
+                        self.line(&annotated("let mut", name, ann), ty, term.loc)
+                    }
+                }
+                self.nested(|dumper| {
+                    dumper.term(t1);
+                    dumper.term(t2);
+                });
+            }
+            Term::LetRec(fns, t1) => {
+                self.line("let rec", ty, term.loc);
+                self.nested(|dumper| {
+                    for (name, ann, body) in fns {
+                        dumper.line(&annotated("fn", name.content, ann), None, name.loc);
+                        dumper.nested(|dumper| dumper.term(body));
+                    }
+                    dumper.term(t1);
+                });
+            }
+            Term::Seq(t1, t2) => {
+                self.line("seq", ty, term.loc);
+                self.nested(|dumper| {
+                    dumper.term(t1);
+                    dumper.term(t2);
+                });
+            }
+            Term::Assign(name, t1) => {
+                self.line(&format!("assign {}", name.content), ty, term.loc);
+                self.nested(|dumper| dumper.term(t1));
+            }
+            Term::Ascription(t1, ann) => {
+                self.line(&format!("ascription ({})", ann.content), ty, term.loc);
+                self.nested(|dumper| dumper.term(t1));
+            }
+            Term::Lazy(t1) => {
+                self.line("lazy", ty, term.loc);
+                self.nested(|dumper| dumper.term(t1));
+            }
+            Term::PrimFn(prim) => self.line(&format!("prim {}", prim), ty, term.loc),
+            Term::Native(index, _) => self.line(&format!("native#{}", index), ty, term.loc),
+            Term::Enum(name, variants, t1) => {
+                self.line(&format!("enum {}", name.content), ty, term.loc);
+                self.nested(|dumper| {
+                    for variant in variants {
+                        let mut head = String::new();
+                        write!(head, "variant {}", variant.name.content).unwrap();
+                        if let Some((first, rest)) = variant.fields.split_first() {
+                            write!(head, "({}", first).unwrap();
+                            for field in rest {
+                                write!(head, ", {}", field).unwrap();
+                            }
+                            head.push(')');
+                        }
+                        dumper.line(&head, None, variant.name.loc);
+                    }
+                    dumper.term(t1);
+                });
+            }
+            Term::Constructor(name) => self.line(&format!("constructor {}", name), ty, term.loc),
+            Term::Match(t1, arms) => {
+                self.line("match", ty, term.loc);
+                self.nested(|dumper| {
+                    dumper.term(t1);
+                    for (pattern, body) in arms {
+                        dumper.line(&format!("arm {}", pattern.content), None, pattern.loc);
+                        dumper.nested(|dumper| dumper.term(body));
+                    }
+                });
+            }
+        }
+    }
+
+    /// Writes a line with `head`, the type `ty` if any and the location `loc`.
+    fn line(&mut self, head: &str, ty: Option<&Ty>, loc: Location) {
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        let indent = self.depth * INDENT;
+        write!(self.output, "{:indent$}{}", "", head, indent = indent).unwrap();
+        if let Some(ty) = ty {
+            write!(self.output, " : {}", ty).unwrap();
+        }
+        write!(self.output, " @{}..{}", loc.start, loc.end).unwrap();
+    }
+
+    /// Runs `f` with the lines it writes nested one level deeper.
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        f(self);
+        self.depth -= 1;
+    }
+}
+
+/// Returns the head of a line with `keyword` and `name` annotated with `ann`.
+fn annotated(keyword: &str, name: Name, ann: &Located<Ty>) -> String {
+    format!("{} ({} : {})", keyword, name, ann.content)
+}
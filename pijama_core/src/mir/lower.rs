@@ -1,30 +1,68 @@
-use std::mem::discriminant;
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::{
+    fmt::{self, Display, Formatter},
+    mem::discriminant,
+};
 
 use pijama_ast::{
-    analysis::RecursionChecker, ty::TyAnnotation, BinOp, Block, Branch, Literal, Located, Location,
-    Name, Node, UnOp,
+    analysis::{recursive_groups, RecursionChecker},
+    ty::{Ty as TyAST, TyAnnotation},
+    Arm, BinOp, Block, Branch, Literal, Located, Location, Name, Node, NodeId, Primitive, UnOp,
+    Variant as VariantAST,
 };
-use thiserror::Error;
 
 use crate::{
-    mir::{LetKind, Term},
+    mir::{LetKind, Term, Variant},
     ty::Ty,
 };
 
 pub type LowerResult<T> = Result<T, LowerError>;
 
-#[derive(Error, Debug)]
+#[derive(Debug, Clone)]
 pub enum LowerError {
-    #[error("Recursive functions need a return type annotation")]
-    RecWithoutTy(Location),
-    #[error("Anonymous functions cannot have a return type annotation")]
     AnonWithTy(Location),
+    /// An import that was not resolved before lowering.
+    UnresolvedImport(Location),
+    /// Code that could not be parsed, kept as a [`Node::Error`] by the recovering parser.
+    Unparsed(Location),
 }
 
+impl Display for LowerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LowerError::AnonWithTy(_) => {
+                write!(
+                    f,
+                    "Anonymous functions cannot have a return type annotation"
+                )
+            }
+            LowerError::UnresolvedImport(_) => write!(
+                f,
+                "Imports can only be used at the top level of programs loaded from files"
+            ),
+            LowerError::Unparsed(_) => write!(f, "This code could not be parsed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LowerError {}
+
 impl LowerError {
     pub fn loc(&self) -> Location {
         match self {
-            LowerError::RecWithoutTy(loc) | LowerError::AnonWithTy(loc) => *loc,
+            LowerError::AnonWithTy(loc)
+            | LowerError::UnresolvedImport(loc)
+            | LowerError::Unparsed(loc) => *loc,
+        }
+    }
+
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LowerError::AnonWithTy(_) => "E0005",
+            LowerError::UnresolvedImport(_) => "E0009",
+            LowerError::Unparsed(_) => "E0001",
         }
     }
 }
@@ -37,19 +75,49 @@ impl PartialEq for LowerError {
 
 impl Eq for LowerError {}
 
-pub fn lower_blk<'a>(mut blk: Located<Block<'a>>) -> LowerResult<Located<Term<'a>>> {
+pub fn lower_blk<'a>(
+    vars: &mut TyVars,
+    mut blk: Located<Block<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
     if let Some(node) = blk.content.pop_front() {
         match node.content {
-            Node::LetBind(annotation, body) => lower_let_bind(node.loc, annotation, *body, blk),
-            Node::FnDef(name, annotations, body) => {
-                lower_fn_def(node.loc, name, annotations, body, blk)
+            Node::LetBind(annotation, body) => {
+                lower_let_bind(vars, node.loc, LetKind::NonRec, annotation, *body, blk)
+            }
+            Node::LetMut(annotation, body) => {
+                lower_let_bind(vars, node.loc, LetKind::Mut, annotation, *body, blk)
             }
+            Node::FnDef(name, annotations, body, _) => {
+                // consecutive function definitions with different names can call each other.
+                let mut fns = vec![(node.loc, node.id, name, annotations, body)];
+                while let Some(Node::FnDef(name, _, _, _)) =
+                    blk.content.front().map(|node| &node.content)
+                {
+                    if fns
+                        .iter()
+                        .any(|(_, _, other, _, _)| other.content == name.content)
+                    {
+                        break;
+                    }
+                    let Some(Located {
+                        content: Node::FnDef(name, annotations, body, _),
+                        loc,
+                        id,
+                    }) = blk.content.pop_front()
+                    else {
+                        unreachable!()
+                    };
+                    fns.push((loc, id, name, annotations, body));
+                }
+                lower_fn_defs(vars, fns, blk)
+            }
+            Node::EnumDef(name, variants) => lower_enum_def(vars, node.loc, name, variants, blk),
             _ => {
                 if blk.content.is_empty() {
-                    lower_node(node)
+                    lower_node(vars, node)
                 } else {
-                    let head = lower_node(node)?;
-                    let tail = lower_blk(blk)?;
+                    let head = lower_node(vars, node)?;
+                    let tail = lower_blk(vars, blk)?;
                     let loc = head.loc + tail.loc;
                     Ok(loc.with_content(Term::Seq(Box::new(head), Box::new(tail))))
                 }
@@ -60,25 +128,90 @@ pub fn lower_blk<'a>(mut blk: Located<Block<'a>>) -> LowerResult<Located<Term<'a
     }
 }
 
-fn lower_node(node: Located<Node<'_>>) -> LowerResult<Located<Term<'_>>> {
-    let loc = node.loc;
-    match node.content {
+/// Lowers `node` to a term with the id of the node.
+///
+/// The terms added while lowering the node, like the ones desugaring a loop, have the
+/// [`NodeId::DUMMY`] id. So do the `let`s that statements binding a name are lowered to, because
+/// their type is the one of the terms after them. The abstraction of a function definition keeps
+/// its id instead, and the value of a binding keeps its own.
+fn lower_node<'a>(vars: &mut TyVars, node: Located<Node<'a>>) -> LowerResult<Located<Term<'a>>> {
+    let id = match node.content {
+        Node::LetBind(..) | Node::LetMut(..) | Node::FnDef(..) | Node::EnumDef(..) => NodeId::DUMMY,
+        _ => node.id,
+    };
+    lower_node_content(vars, node).map(|term| term.with_id(id))
+}
+
+fn lower_node_content<'a>(
+    vars: &mut TyVars,
+    node: Located<Node<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
+    let Located { content, loc, id } = node;
+    match content {
         Node::Name(name) => Ok(loc.with_content(Term::Var(name))),
         Node::Literal(lit) => Ok(loc.with_content(Term::Lit(lit))),
+        Node::List(elems) => {
+            let elems = elems
+                .into_iter()
+                .map(|node| lower_node(vars, node))
+                .collect::<LowerResult<_>>()?;
+            Ok(loc.with_content(Term::List(elems)))
+        }
+        Node::Interpolation(parts) => lower_interpolation(vars, loc, parts),
+        Node::Ascription(node, ty) => {
+            let term = lower_node(vars, *node)?;
+            let ty = ty.map(|ty| vars.lower(ty, &mut Vec::new()));
+            Ok(loc.with_content(Term::Ascription(Box::new(term), ty)))
+        }
+        Node::LetIn(annotation, node1, node2) => {
+            // the expression is lowered like a let binding followed by the second node in the
+            // same block.
+            let tail = node2.loc.with_content(Some(*node2).into_iter().collect());
+            lower_let_bind(vars, loc, LetKind::NonRec, annotation, *node1, tail)
+        }
+        Node::Block(blk) => {
+            let term = lower_blk(vars, blk)?;
+            Ok(loc.with_content(term.content))
+        }
+        Node::Lazy(node) => {
+            let term = lower_node(vars, *node)?;
+            Ok(loc.with_content(Term::Lazy(Box::new(term))))
+        }
         Node::PrimFn(prim) => Ok(loc.with_content(Term::PrimFn(prim))),
-        Node::Cond(if_branch, branches, el_blk) => lower_cond(loc, if_branch, branches, el_blk),
-        Node::Call(node, args) => lower_call(loc, *node, args),
-        Node::BinaryOp(bin_op, node1, node2) => lower_binary_op(loc, bin_op, *node1, *node2),
-        Node::UnaryOp(un_op, node) => lower_unary_op(loc, un_op, *node),
-        Node::AnonFn(binds, body) => lower_anon_fn(loc, binds, body),
-        node @ Node::LetBind(_, _) | node @ Node::FnDef(_, _, _) => {
+        Node::Import(_) => Err(LowerError::UnresolvedImport(loc)),
+        Node::Error => Err(LowerError::Unparsed(loc)),
+        Node::Cond(if_branch, branches, el_blk) => {
+            lower_cond(vars, loc, if_branch, branches, el_blk)
+        }
+        Node::While(branch) => lower_while(vars, loc, branch),
+        Node::For(name, start, end, body) => lower_for(vars, loc, name, *start, *end, body),
+        Node::Call(node, args) => lower_call(vars, loc, *node, args),
+        Node::BinaryOp(bin_op, node1, node2) => lower_binary_op(vars, loc, bin_op, *node1, *node2),
+        Node::UnaryOp(un_op, node) => lower_unary_op(vars, loc, un_op, *node),
+        Node::Operator(bin_op, node1, node2) => lower_operator(vars, loc, bin_op, node1, node2),
+        Node::AnonFn(binds, body) => lower_anon_fn(vars, loc, binds, body),
+        Node::Match(node, arms) => lower_match(vars, loc, *node, arms),
+        Node::Assign(name, node) => {
+            let term = lower_node(vars, *node)?;
+            Ok(loc.with_content(Term::Assign(name, Box::new(term))))
+        }
+        node @ Node::LetBind(_, _)
+        | node @ Node::LetMut(_, _)
+        | node @ Node::FnDef(_, _, _, _)
+        | node @ Node::EnumDef(_, _) => {
             let empty_blk = Location::new(loc.end, loc.end).with_content(Block::default());
             match node {
                 Node::LetBind(annotation, body) => {
-                    lower_let_bind(loc, annotation, *body, empty_blk)
+                    lower_let_bind(vars, loc, LetKind::NonRec, annotation, *body, empty_blk)
+                }
+                Node::LetMut(annotation, body) => {
+                    lower_let_bind(vars, loc, LetKind::Mut, annotation, *body, empty_blk)
+                }
+                Node::FnDef(name, annotations, body, _) => {
+                    lower_fn_defs(vars, vec![(loc, id, name, annotations, body)], empty_blk)
                 }
-                Node::FnDef(name, annotations, body) => {
-                    lower_fn_def(loc, name, annotations, body, empty_blk)
+                Node::EnumDef(name, variants) => {
+                    lower_enum_def(vars, loc, name, variants, empty_blk)
                 }
                 _ => unreachable!(),
             }
@@ -87,17 +220,22 @@ fn lower_node(node: Located<Node<'_>>) -> LowerResult<Located<Term<'_>>> {
 }
 
 fn lower_cond<'a>(
+    vars: &mut TyVars,
     loc: Location,
     if_branch: Branch<'a>,
     branches: Vec<Branch<'a>>,
     el_blk: Located<Block<'a>>,
 ) -> LowerResult<Located<Term<'a>>> {
-    let mut el_term = Box::new(lower_blk(el_blk)?);
+    let el_loc = el_blk.loc;
+    let mut el_term = Box::new(lower_blk(vars, el_blk)?);
 
+    // each `elif` branch is lowered to a conditional nested in the `else` branch of the previous
+    // one, located from the condition of the branch to the end of the `else` block.
     for branch in branches.into_iter().rev() {
+        let loc = branch.cond.loc + el_loc;
         el_term = Box::new(loc.with_content(Term::Cond(
-            Box::new(lower_blk(branch.cond)?),
-            Box::new(lower_blk(branch.body)?),
+            Box::new(lower_blk(vars, branch.cond)?),
+            Box::new(lower_blk(vars, branch.body)?),
             el_term,
         )));
     }
@@ -106,135 +244,561 @@ fn lower_cond<'a>(
     let do_blk = if_branch.body;
 
     Ok(loc.with_content(Term::Cond(
-        Box::new(lower_blk(if_blk)?),
-        Box::new(lower_blk(do_blk)?),
+        Box::new(lower_blk(vars, if_blk)?),
+        Box::new(lower_blk(vars, do_blk)?),
         el_term,
     )))
 }
 
+/// Lowers a while loop to a recursive binding of type `Unit` that evaluates the body and then
+/// itself while the condition is true. The binding is named `while`, which is a keyword, so it
+/// does not shadow any name used inside the loop.
+fn lower_while<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    branch: Branch<'a>,
+) -> LowerResult<Located<Term<'a>>> {
+    let name = loc.with_content(Name("while"));
+    let cond = lower_blk(vars, branch.cond)?;
+    let body = lower_blk(vars, branch.body)?;
+
+    let again = loc.with_content(Term::Var(name.content));
+    let body = body
+        .loc
+        .with_content(Term::Seq(Box::new(body), Box::new(again)));
+    let done = loc.with_content(Term::Lit(Literal::Unit));
+    let term = loc.with_content(Term::Cond(Box::new(cond), Box::new(body), Box::new(done)));
+
+    Ok(loc.with_content(Term::Let(
+        LetKind::Rec(loc.with_content(Ty::Unit)),
+        name,
+        Box::new(term),
+        Box::new(loc.with_content(Term::Var(name.content))),
+    )))
+}
+
+/// Lowers a for loop to a recursive function of type `Int -> Int -> Unit` that takes the end of
+/// the range and the current integer, and evaluates the body and then itself with the next integer
+/// while the current one is smaller than the end. The function and its first parameter are named
+/// `for` and `end`, which are keywords, so they do not shadow any name used inside the loop.
+///
+/// The end of the range is evaluated outside the loop, so it cannot use the loop variable.
+fn lower_for<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    name: Located<Name<'a>>,
+    start: Located<Node<'a>>,
+    end: Located<Node<'a>>,
+    body: Located<Block<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
+    let func = loc.with_content(Term::Var(Name("for")));
+    let limit = loc.with_content(Term::Var(Name("end")));
+    let var = name.loc.with_content(Term::Var(name.content));
+
+    let start = lower_node(vars, start)?;
+    let end = lower_node(vars, end)?;
+    let body = lower_blk(vars, body)?;
+
+    let next = loc.with_content(Term::BinaryOp(
+        BinOp::Add,
+        Box::new(var.clone()),
+        Box::new(loc.with_content(Term::Lit(Literal::Number(1)))),
+    ));
+    let again = loc.with_content(Term::App(
+        Box::new(loc.with_content(Term::App(Box::new(func.clone()), Box::new(limit.clone())))),
+        Box::new(next),
+    ));
+    let body = body
+        .loc
+        .with_content(Term::Seq(Box::new(body), Box::new(again)));
+    let cond = loc.with_content(Term::BinaryOp(BinOp::Lt, Box::new(var), Box::new(limit)));
+    let done = loc.with_content(Term::Lit(Literal::Unit));
+    let mut term = loc.with_content(Term::Cond(Box::new(cond), Box::new(body), Box::new(done)));
+    for param in [name, loc.with_content(Name("end"))].iter() {
+        term = loc.with_content(Term::Abs(*param, Ty::Int, Box::new(term)));
+    }
+
+    let ty = Ty::Arrow(
+        Box::new(Ty::Int),
+        Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Unit))),
+    );
+    let call = loc.with_content(Term::App(
+        Box::new(loc.with_content(Term::App(Box::new(func), Box::new(end)))),
+        Box::new(start),
+    ));
+
+    Ok(loc.with_content(Term::Let(
+        LetKind::Rec(loc.with_content(ty)),
+        loc.with_content(Name("for")),
+        Box::new(term),
+        Box::new(call),
+    )))
+}
+
 fn lower_call<'a>(
+    vars: &mut TyVars,
     loc: Location,
     node: Located<Node<'a>>,
     args: Block<'a>,
 ) -> LowerResult<Located<Term<'a>>> {
-    let mut term = lower_node(node)?;
+    let mut term = lower_node(vars, node)?;
+    // A call without arguments, like `read_int()`, is the called term located at the whole call.
+    if args.is_empty() {
+        term.loc = loc;
+    }
     for node in args {
-        term = loc.with_content(Term::App(Box::new(term), Box::new(lower_node(node)?)));
+        term = loc.with_content(Term::App(Box::new(term), Box::new(lower_node(vars, node)?)));
     }
     Ok(term)
 }
 
 fn lower_binary_op<'a>(
+    vars: &mut TyVars,
     loc: Location,
     bin_op: BinOp,
     node1: Located<Node<'a>>,
     node2: Located<Node<'a>>,
 ) -> LowerResult<Located<Term<'a>>> {
+    if let BinOp::Pipe = bin_op {
+        // `x |> f` is the same as `f(x)`.
+        return Ok(loc.with_content(Term::App(
+            Box::new(lower_node(vars, node2)?),
+            Box::new(lower_node(vars, node1)?),
+        )));
+    }
     Ok(loc.with_content(Term::BinaryOp(
         bin_op,
-        Box::new(lower_node(node1)?),
-        Box::new(lower_node(node2)?),
+        Box::new(lower_node(vars, node1)?),
+        Box::new(lower_node(vars, node2)?),
     )))
 }
 
-fn lower_unary_op(
+/// Lowers an operator used as a function to an anonymous function taking its missing operands,
+/// named `_1` and `_2`, which are not valid names, so they do not shadow any name used in the
+/// given operand.
+///
+/// The given operand of a section is bound before the function, so it is evaluated only once.
+fn lower_operator<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    bin_op: BinOp,
+    node1: Option<Box<Located<Node<'a>>>>,
+    node2: Option<Box<Located<Node<'a>>>>,
+) -> LowerResult<Located<Term<'a>>> {
+    let mut params = Vec::new();
+    let mut operands = Vec::new();
+    for (node, name) in IntoIterator::into_iter([(node1, Name("_1")), (node2, Name("_2"))]) {
+        let name = loc.with_content(name);
+        match node {
+            Some(node) => operands.push((name, lower_node(vars, *node)?)),
+            None => params.push(name),
+        }
+    }
+
+    let operand = |name: &'static str| loc.with_content(Node::Name(Name(name)));
+    let mut term = lower_binary_op(vars, loc, bin_op, operand("_1"), operand("_2"))?;
+    for param in params.into_iter().rev() {
+        let ty = vars.lower(TyAST::Missing, &mut Vec::new());
+        term = loc.with_content(Term::Abs(param, ty, Box::new(term)));
+    }
+    for (name, operand) in operands {
+        term = loc.with_content(Term::Let(
+            LetKind::NonRec(None),
+            name,
+            Box::new(operand),
+            Box::new(term),
+        ));
+    }
+    Ok(term)
+}
+
+fn lower_unary_op<'a>(
+    vars: &mut TyVars,
     loc: Location,
     un_op: UnOp,
-    node: Located<Node<'_>>,
-) -> LowerResult<Located<Term<'_>>> {
-    Ok(loc.with_content(Term::UnaryOp(un_op, Box::new(lower_node(node)?))))
+    node: Located<Node<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
+    Ok(loc.with_content(Term::UnaryOp(un_op, Box::new(lower_node(vars, node)?))))
 }
 
+/// Lowers an interpolated string into the concatenation of its parts.
+///
+/// The string literals of the interpolation are kept as they are, and every other part is
+/// converted to a string with the `to_string` primitive.
+fn lower_interpolation<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    parts: Block<'a>,
+) -> LowerResult<Located<Term<'a>>> {
+    let mut parts = parts.into_iter().map(|part| {
+        let part_loc = part.loc;
+        let term = lower_node(vars, part)?;
+        Ok(match term.content {
+            Term::Lit(Literal::Str(_)) => term,
+            _ => part_loc.with_content(Term::App(
+                Box::new(part_loc.with_content(Term::PrimFn(Primitive::ToString))),
+                Box::new(term),
+            )),
+        })
+    });
+    let mut acc = match parts.next() {
+        Some(part) => part?,
+        None => return Ok(loc.with_content(Term::Lit(Literal::Str(String::new())))),
+    };
+    for part in parts {
+        let part = part?;
+        let concat = acc.loc.with_content(Term::PrimFn(Primitive::Concat));
+        let app = acc
+            .loc
+            .with_content(Term::App(Box::new(concat), Box::new(acc)));
+        acc = (app.loc + part.loc).with_content(Term::App(Box::new(app), Box::new(part)));
+    }
+    acc.loc = loc;
+    Ok(acc)
+}
+
+/// Lowers a let binding whose kind is built from its optional type annotation by `kind`.
 fn lower_let_bind<'a>(
+    vars: &mut TyVars,
     loc: Location,
+    kind: fn(Option<Located<Ty>>) -> LetKind,
     annotation: TyAnnotation<Name<'a>>,
     body: Located<Node<'a>>,
     tail: Located<Block<'a>>,
 ) -> LowerResult<Located<Term<'a>>> {
-    let body = lower_node(body)?;
+    let body = lower_node(vars, body)?;
 
-    let opt_ty = if let Some(ty) = Ty::from_ast(annotation.ty.content) {
-        Some(annotation.ty.loc.with_content(ty))
-    } else {
-        None
+    let opt_ty = match annotation.ty.content {
+        TyAST::Missing => None,
+        ty => Some(
+            annotation
+                .ty
+                .loc
+                .with_content(vars.lower(ty, &mut Vec::new())),
+        ),
     };
 
-    let tail = lower_blk(tail)?;
+    let tail = lower_blk(vars, tail)?;
 
     Ok(loc.with_content(Term::Let(
-        LetKind::NonRec(opt_ty),
+        kind(opt_ty),
         annotation.item,
         Box::new(body),
         Box::new(tail),
     )))
 }
 
-fn lower_fn_def<'a>(
-    loc: Location,
-    name: Located<Name<'a>>,
-    annotations: Vec<TyAnnotation<Name<'a>>>,
-    body: TyAnnotation<Block<'a>>,
+/// A function definition with its location, node id, name, parameters and body.
+type FnDef<'a> = (
+    Location,
+    NodeId,
+    Located<Name<'a>>,
+    Vec<TyAnnotation<Name<'a>>>,
+    TyAnnotation<Block<'a>>,
+);
+
+/// A lowered function definition, or group of mutually recursive function definitions, without
+/// the term that follows it.
+enum LoweredFnDef<'a> {
+    Single(LetKind, Located<Name<'a>>, Box<Located<Term<'a>>>),
+    Group(Vec<(Located<Name<'a>>, Located<Ty>, Located<Term<'a>>)>),
+}
+
+fn lower_fn_defs<'a>(
+    vars: &mut TyVars,
+    fns: Vec<FnDef<'a>>,
     tail: Located<Block<'a>>,
 ) -> LowerResult<Located<Term<'a>>> {
+    // the functions are lowered in groups of functions that call each other, in an order where
+    // each group only calls the ones before it.
+    let groups = recursive_groups(
+        &fns.iter()
+            .map(|(_, _, name, annotations, body)| {
+                (name.content, annotations.as_slice(), &body.item.content)
+            })
+            .collect::<Vec<_>>(),
+    );
+    let mut fns: Vec<_> = fns.into_iter().map(Some).collect();
+
+    let mut defs = Vec::new();
+    for group in groups {
+        let mut group: Vec<_> = group
+            .into_iter()
+            .map(|index| {
+                fns[index]
+                    .take()
+                    .expect("each function is in a single group")
+            })
+            .collect();
+
+        if group.len() == 1 {
+            let (loc, id, name, annotations, body) = group.pop().unwrap();
+            let ret_loc = body.ty.loc;
+            let (params, opt_ty) = lower_signature(vars, annotations, body.ty);
+
+            // we need to decide if the function is recursive or not
+            let kind = if RecursionChecker::run(name.content, &body.item.content) {
+                // if the function is recursive, we need its type, even if the return type is
+                // inferred.
+                LetKind::Rec(opt_ty.unwrap_or_else(|| inferred_fn_ty(vars, &params, ret_loc)))
+            } else {
+                LetKind::NonRec(opt_ty)
+            };
+
+            let term = lower_fn_body(vars, loc, id, params, body.item)?;
+            defs.push((loc, LoweredFnDef::Single(kind, name, Box::new(term))));
+        } else {
+            let loc = group
+                .iter()
+                .map(|(loc, _, _, _, _)| *loc)
+                .reduce(|loc1, loc2| loc1 + loc2)
+                .unwrap();
+
+            let group = group
+                .into_iter()
+                .map(|(loc, id, name, annotations, body)| {
+                    let ret_loc = body.ty.loc;
+                    let (params, opt_ty) = lower_signature(vars, annotations, body.ty);
+                    // mutually recursive functions need their type as well.
+                    let ty = opt_ty.unwrap_or_else(|| inferred_fn_ty(vars, &params, ret_loc));
+                    let term = lower_fn_body(vars, loc, id, params, body.item)?;
+                    Ok((name, ty, term))
+                })
+                .collect::<LowerResult<_>>()?;
+            defs.push((loc, LoweredFnDef::Group(group)));
+        }
+    }
+
+    let mut term = lower_blk(vars, tail)?;
+
+    for (loc, def) in defs.into_iter().rev() {
+        term = loc.with_content(match def {
+            LoweredFnDef::Single(kind, name, body) => Term::Let(kind, name, body, Box::new(term)),
+            LoweredFnDef::Group(group) => Term::LetRec(group, Box::new(term)),
+        });
+    }
+
+    Ok(term)
+}
+
+/// The lowered parameters of a function, with their names and types.
+type Params<'a> = Vec<(Located<Name<'a>>, Ty)>;
+
+/// Lowers the parameters of a function and its type, if it has a return type annotation.
+fn lower_signature<'a>(
+    vars: &mut TyVars,
+    annotations: Vec<TyAnnotation<Name<'a>>>,
+    ret_ty: Located<TyAST>,
+) -> (Params<'a>, Option<Located<Ty>>) {
+    // the type variables named in the parameters and the return type are shared.
+    let mut scope = Vec::new();
+    let params: Vec<_> = annotations
+        .into_iter()
+        .map(|annotation| {
+            (
+                annotation.item,
+                vars.lower(annotation.ty.content, &mut scope),
+            )
+        })
+        .collect();
+
     // if the user added a return type annotation, we transform this type into the type of the
     // function using the bindings.
-    let ty_loc = body.ty.loc;
-    let opt_ty = if let Some(mut ty) = Ty::from_ast(body.ty.content) {
-        for annotation in annotations.iter().rev() {
-            // FIXME: There could be missing types here!
-            let ann_ty = Ty::from_ast(annotation.ty.content.clone()).unwrap();
-            ty = Ty::Arrow(Box::new(ann_ty), Box::new(ty));
-        }
-        Some(ty_loc.with_content(ty))
-    } else {
-        None
-    };
-
-    // we need to decide if the function is recursive or not
-    let kind = if RecursionChecker::run(name.content, &body.item.content) {
-        // if the function is recursive, we need the return type.
-        opt_ty
-            .map(LetKind::Rec)
-            .ok_or_else(|| LowerError::RecWithoutTy(name.loc))?
-    } else {
-        LetKind::NonRec(opt_ty)
+    let opt_ty = match ret_ty.content {
+        TyAST::Missing => None,
+        ty => {
+            let mut ty = vars.lower(ty, &mut scope);
+            for (_, param_ty) in params.iter().rev() {
+                ty = Ty::Arrow(Box::new(param_ty.clone()), Box::new(ty));
+            }
+            Some(ret_ty.loc.with_content(ty))
+        }
     };
 
-    let mut term = lower_blk(body.item)?;
+    (params, opt_ty)
+}
 
-    for annotation in annotations.into_iter().rev() {
-        term = loc.with_content(Term::Abs(
-            annotation.item.content,
-            Ty::from_ast(annotation.ty.content).unwrap(),
-            Box::new(term),
-        ));
+/// Returns the type of a function without a return type annotation, located at `loc`.
+///
+/// The return type is a new type variable, so it is inferred by the type checker like the types
+/// of parameters without annotations.
+fn inferred_fn_ty(
+    vars: &mut TyVars,
+    params: &[(Located<Name<'_>>, Ty)],
+    loc: Location,
+) -> Located<Ty> {
+    let mut ty = vars.new_var();
+    for (_, param_ty) in params.iter().rev() {
+        ty = Ty::Arrow(Box::new(param_ty.clone()), Box::new(ty));
     }
+    loc.with_content(ty)
+}
 
-    let tail = lower_blk(tail)?;
+fn lower_fn_body<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    id: NodeId,
+    params: Params<'a>,
+    body: Located<Block<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
+    let has_params = !params.is_empty();
+    let mut term = lower_blk(vars, body)?;
 
-    term = loc.with_content(Term::Let(kind, name, Box::new(term), Box::new(tail)));
+    for (param, param_ty) in params.into_iter().rev() {
+        term = loc.with_content(Term::Abs(param, param_ty, Box::new(term)));
+    }
+
+    // the outermost abstraction is the function itself. A function without parameters is lowered
+    // to its body, which keeps the ids of its own nodes.
+    if has_params {
+        term = term.with_id(id);
+    }
 
     Ok(term)
 }
 
 fn lower_anon_fn<'a>(
+    vars: &mut TyVars,
     loc: Location,
     annotations: Vec<TyAnnotation<Name<'a>>>,
     body: TyAnnotation<Block<'a>>,
 ) -> LowerResult<Located<Term<'a>>> {
-    if let Some(_) = Ty::from_ast(body.ty.content) {
+    if body.ty.content != TyAST::Missing {
         return Err(LowerError::AnonWithTy(body.ty.loc));
     }
 
-    let mut term = lower_blk(body.item)?;
+    let mut scope = Vec::new();
+    let params: Vec<_> = annotations
+        .into_iter()
+        .map(|annotation| {
+            (
+                annotation.item,
+                vars.lower(annotation.ty.content, &mut scope),
+            )
+        })
+        .collect();
+
+    let mut term = lower_blk(vars, body.item)?;
+
+    for (param, param_ty) in params.into_iter().rev() {
+        term = loc.with_content(Term::Abs(param, param_ty, Box::new(term)));
+    }
+
+    Ok(term)
+}
+
+fn lower_enum_def<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    name: Located<Name<'a>>,
+    variants: Vec<VariantAST<'a>>,
+    tail: Located<Block<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
+    // The type variables named in the fields are the type parameters of the enum, so they are
+    // shared by all its variants.
+    let mut scope = Vec::new();
+    let variants: Vec<_> = variants
+        .into_iter()
+        .map(|variant| Variant {
+            name: variant.name,
+            fields: variant
+                .fields
+                .into_iter()
+                .map(|field| vars.lower(field.content, &mut scope))
+                .collect(),
+        })
+        .collect();
 
-    for annotation in annotations.into_iter().rev() {
-        term = loc.with_content(Term::Abs(
-            annotation.item.content,
-            Ty::from_ast(annotation.ty.content).unwrap(),
+    // the constructor of each variant is bound to its name so it can be used like a function.
+    let mut term = lower_blk(vars, tail)?;
+    for variant in variants.iter().rev() {
+        let name = variant.name;
+        let constructor = name.loc.with_content(Term::Constructor(name.content));
+        term = loc.with_content(Term::Let(
+            LetKind::NonRec(None),
+            name,
+            Box::new(constructor),
             Box::new(term),
         ));
     }
 
-    Ok(term)
+    Ok(loc.with_content(Term::Enum(name, variants, Box::new(term))))
+}
+
+fn lower_match<'a>(
+    vars: &mut TyVars,
+    loc: Location,
+    node: Located<Node<'a>>,
+    arms: Vec<Arm<'a>>,
+) -> LowerResult<Located<Term<'a>>> {
+    let term = lower_node(vars, node)?;
+    let arms = arms
+        .into_iter()
+        .map(|arm| Ok((arm.pattern, lower_blk(vars, arm.body)?)))
+        .collect::<LowerResult<_>>()?;
+    Ok(loc.with_content(Term::Match(Box::new(term), arms)))
+}
+
+/// Lowers a type written outside of a program.
+///
+/// Each type variable named in the type gets a single variable, numbered from zero in the order
+/// they are found.
+pub(crate) fn lower_ty(ty: TyAST) -> Ty {
+    TyVars::default().lower(ty, &mut Vec::new())
+}
+
+/// The type variables of the type annotations of a program.
+///
+/// Missing annotations and type variables named by the user are lowered to type variables. Each
+/// missing annotation gets a new variable, and each name gets a single variable in all the
+/// annotations of a definition. The variables are numbered consecutively through the whole
+/// program, so the variables of different definitions are always different.
+#[derive(Default)]
+pub struct TyVars {
+    /// Number of created type variables.
+    count: usize,
+}
+
+impl TyVars {
+    /// Returns a new type variable.
+    fn new_var(&mut self) -> Ty {
+        let ty = Ty::Var(self.count);
+        self.count += 1;
+        ty
+    }
+
+    /// Lowers a type annotation of a definition.
+    ///
+    /// The `scope` has the type variables named in the annotations of the same definition that
+    /// were lowered before.
+    fn lower(&mut self, ty: TyAST, scope: &mut Vec<(String, Ty)>) -> Ty {
+        match ty {
+            TyAST::Bool => Ty::Bool,
+            TyAST::Int => Ty::Int,
+            TyAST::Float => Ty::Float,
+            TyAST::Unit => Ty::Unit,
+            TyAST::Char => Ty::Char,
+            TyAST::String => Ty::String,
+            TyAST::Adt(name, args) => Ty::Adt(
+                name,
+                args.into_iter().map(|arg| self.lower(arg, scope)).collect(),
+            ),
+            TyAST::List(ty) => Ty::List(Box::new(self.lower(*ty, scope))),
+            TyAST::Chan(ty) => Ty::Chan(Box::new(self.lower(*ty, scope))),
+            TyAST::Lazy(ty) => Ty::Lazy(Box::new(self.lower(*ty, scope))),
+            TyAST::Arrow(t1, t2) => Ty::Arrow(
+                Box::new(self.lower(*t1, scope)),
+                Box::new(self.lower(*t2, scope)),
+            ),
+            TyAST::Var(name) => match scope.iter().find(|(other, _)| *other == name) {
+                Some((_, ty)) => ty.clone(),
+                None => {
+                    let ty = self.new_var();
+                    scope.push((name, ty.clone()));
+                    ty
+                }
+            },
+            TyAST::Missing => self.new_var(),
+        }
+    }
 }
@@ -1,27 +1,69 @@
-use std::fmt::{Display, Formatter, Result};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::{Display, Formatter, Result};
 
-use pijama_ast::{BinOp, Block, Literal, Located, Name, Primitive, UnOp};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::{BinOp, Block, Literal, Located, Name, Pattern, Primitive, UnOp};
 
 use crate::ty::Ty;
 
+pub use dump::dump;
+pub(crate) use lower::lower_ty;
 pub use lower::{LowerError, LowerResult};
+pub use optimize::{optimize, optimize_with, Pass, INLINE_SIZE};
 
+mod dump;
 mod lower;
+mod optimize;
+mod resolve;
+pub mod visit;
 
-#[derive(Debug)]
+/// The kind of a let binding, with its type annotation.
+///
+/// Type variables in annotations stand for missing annotations or for type variables named by the
+/// user, see [`Term::Abs`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
 pub enum LetKind {
     NonRec(Option<Located<Ty>>),
     Rec(Located<Ty>),
+    /// A binding that can be assigned inside the term where it is visible. It is not polymorphic.
+    Mut(Option<Located<Ty>>),
+}
+
+/// A variant of an enum with the types of its fields.
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone)]
+pub struct Variant<'a> {
+    pub name: Located<Name<'a>>,
+    pub fields: Vec<Ty>,
 }
 
-#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+#[derive(Debug, Clone)]
 pub enum Term<'a> {
     Var(Name<'a>),
-    Abs(Name<'a>, Ty, Box<Located<Term<'a>>>),
+    /// An abstraction with the name and type of its parameter.
+    ///
+    /// If the parameter does not have a type annotation, its type is a new type variable. Type
+    /// variables named by the user are also lowered to type variables, which are the same for
+    /// all the annotations of a definition and different from the ones of other definitions.
+    Abs(Located<Name<'a>>, Ty, Box<Located<Term<'a>>>),
     UnaryOp(UnOp, Box<Located<Term<'a>>>),
     BinaryOp(BinOp, Box<Located<Term<'a>>>, Box<Located<Term<'a>>>),
     App(Box<Located<Term<'a>>>, Box<Located<Term<'a>>>),
     Lit(Literal),
+    /// A list literal with its elements.
+    List(Vec<Located<Term<'a>>>),
     Cond(
         Box<Located<Term<'a>>>,
         Box<Located<Term<'a>>>,
@@ -33,19 +75,61 @@ pub enum Term<'a> {
         Box<Located<Term<'a>>>,
         Box<Located<Term<'a>>>,
     ),
+    /// A group of mutually recursive functions, with their names, types and bodies, followed by
+    /// the term where they are visible.
+    ///
+    /// Every function of the group is visible in the bodies of all of them.
+    LetRec(
+        Vec<(Located<Name<'a>>, Located<Ty>, Located<Term<'a>>)>,
+        Box<Located<Term<'a>>>,
+    ),
     Seq(Box<Located<Term<'a>>>, Box<Located<Term<'a>>>),
+    /// An assignment of a new value to a mutable binding, which evaluates to `unit`.
+    Assign(Located<Name<'a>>, Box<Located<Term<'a>>>),
+    /// A term annotated with its type. Type variables in the annotation are lowered like the ones
+    /// of a let binding annotation.
+    Ascription(Box<Located<Term<'a>>>, Located<Ty>),
+    /// A term that is evaluated the first time it is forced instead of when it is reached.
+    Lazy(Box<Located<Term<'a>>>),
     PrimFn(Primitive),
+    /// A function provided by the host, identified by its index in the machine and annotated
+    /// with its type.
+    Native(usize, Ty),
+    /// The declaration of an enum, which is only visible in the term that follows it.
+    ///
+    /// The constructor of each variant is bound to the name of the variant inside that term.
+    Enum(Located<Name<'a>>, Vec<Variant<'a>>, Box<Located<Term<'a>>>),
+    /// The constructor of a variant, which takes the fields of the variant one at a time.
+    ///
+    /// A constructor of a variant without fields is the value of the variant itself.
+    Constructor(Name<'a>),
+    /// A match with its scrutinee and arms, the first arm whose pattern matches the scrutinee is
+    /// evaluated.
+    Match(
+        Box<Located<Term<'a>>>,
+        Vec<(Located<Pattern<'a>>, Located<Term<'a>>)>,
+    ),
 }
 
 impl<'a> Display for Term<'a> {
     fn fmt(&self, f: &mut Formatter) -> Result {
         match self {
             Term::Var(var) => write!(f, "{}", var),
-            Term::Abs(name, ty, term) => write!(f, "(λ{}:{}. {})", name, ty, term),
+            Term::Abs(name, ty, term) => write!(f, "(λ{}:{}. {})", name.content, ty, term),
             Term::UnaryOp(op, term) => write!(f, "({}{})", op, term),
             Term::BinaryOp(op, t1, t2) => write!(f, "({} {} {})", t1, op, t2),
             Term::App(t1, t2) => write!(f, "({} {})", t1, t2),
             Term::Lit(literal) => write!(f, "{}", literal),
+            Term::List(elems) => {
+                write!(f, "[")?;
+                for (index, elem) in elems.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            }
             Term::Cond(t1, t2, t3) => write!(f, "(if {} then {} else {})", t1, t2, t3),
             Term::Let(LetKind::Rec(ty), name, t1, t2) => {
                 write!(f, "(let rec {} : {} = {} in {})", name, ty.content, t1, t2)
@@ -56,14 +140,68 @@ impl<'a> Display for Term<'a> {
             Term::Let(LetKind::NonRec(None), name, t1, t2) => {
                 write!(f, "(let {} = {} in {})", name, t1, t2)
             }
+            Term::Let(LetKind::Mut(Some(ty)), name, t1, t2) => {
+                write!(f, "(let mut {} : {} = {} in {})", name, ty.content, t1, t2)
+            }
+            Term::Let(LetKind::Mut(None), name, t1, t2) => {
+                write!(f, "(let mut {} = {} in {})", name, t1, t2)
+            }
+            Term::LetRec(fns, term) => {
+                write!(f, "(let rec")?;
+                for (index, (name, ty, body)) in fns.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { " and" };
+                    write!(f, "{} {} : {} = {}", separator, name, ty.content, body)?;
+                }
+                write!(f, " in {})", term)
+            }
             Term::Seq(t1, t2) => write!(f, "{} ; {}", t1, t2),
+            Term::Assign(name, term) => write!(f, "({} := {})", name, term),
+            Term::Ascription(term, ty) => write!(f, "({} : {})", term, ty.content),
+            Term::Lazy(term) => write!(f, "(lazy {})", term),
             Term::PrimFn(prim) => write!(f, "{}", prim),
+            Term::Native(index, _) => write!(f, "native#{}", index),
+            Term::Enum(name, variants, term) => {
+                write!(f, "(enum {} =", name)?;
+                for (index, variant) in variants.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { " |" };
+                    write!(f, "{} {}", separator, variant.name)?;
+                    if !variant.fields.is_empty() {
+                        write!(f, "(")?;
+                        for (index, field) in variant.fields.iter().enumerate() {
+                            if index > 0 {
+                                write!(f, ", ")?;
+                            }
+                            write!(f, "{}", field)?;
+                        }
+                        write!(f, ")")?;
+                    }
+                }
+                write!(f, " in {})", term)
+            }
+            Term::Constructor(name) => write!(f, "(variant {})", name),
+            Term::Match(term, arms) => {
+                write!(f, "(match {} with", term)?;
+                for (index, (pattern, body)) in arms.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { " |" };
+                    write!(f, "{} {} => {}", separator, pattern, body)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
 
 impl<'a> Term<'a> {
+    /// Lowers `blk` to the MIR, replacing the names of primitives it does not bind by the
+    /// primitives themselves.
     pub fn from_ast(blk: Located<Block<'a>>) -> LowerResult<Located<Self>> {
-        lower::lower_blk(blk)
+        Self::from_ast_in(blk, &[])
+    }
+
+    /// Lowers `blk` like [`Term::from_ast`] for a program that will be wrapped in bindings for
+    /// `names`, which shadow the primitives with the same names.
+    pub fn from_ast_in(blk: Located<Block<'a>>, names: &[&str]) -> LowerResult<Located<Self>> {
+        let term = lower::lower_blk(&mut lower::TyVars::default(), blk)?;
+        Ok(resolve::resolve_primitives(term, names))
     }
 }
@@ -0,0 +1,667 @@
+//! Optimizations over the MIR.
+//!
+//! The entry point for this module is the [`optimize`] function, which runs every optimization
+//! pass in order. Each pass is described by a variant of [`Pass`], and [`optimize_with`] runs only
+//! the given passes, so their effect can be observed separately.
+//!
+//! Optimizations never change the value of a term or its effects. Operations are only folded if
+//! the machine would compute the same value with any arithmetic, so operations that overflow or
+//! divide by zero are kept to fail at runtime, and terms are only removed if evaluating them
+//! cannot print, read the input or fail.
+use alloc::{boxed::Box, vec::Vec};
+use core::convert::TryFrom;
+
+use pijama_ast::{
+    BinOp, Int, IntWidth, Literal, Located, Location, Name, Pattern, Primitive, UnOp,
+};
+
+use crate::mir::{
+    visit::{Folder, Function},
+    LetKind, Term,
+};
+
+use specialize::specialize;
+
+mod specialize;
+
+/// An optimization pass over the MIR.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Pass {
+    /// Folds the operations whose operands are literals, replaces conditionals with a literal
+    /// condition by the branch that is taken and substitutes the names bound to literals by let
+    /// bindings.
+    ConstantFolding,
+    /// Removes the let bindings whose names are not used and the terms whose values are
+    /// discarded, if evaluating them has no effects.
+    DeadCode,
+    /// Replaces the uses of non-recursive functions with at most [`INLINE_SIZE`] terms by their
+    /// definitions and the applications of abstractions by their bodies.
+    Inlining,
+    /// Replaces the applications of functions to literals by the bodies of the functions with the
+    /// arguments substituted, so the functions are specialized for the known arguments.
+    ///
+    /// The aggressiveness of the pass is the largest number of applications unfolded for each
+    /// application of the program, which includes the ones found inside the unfolded bodies. This
+    /// pass is not run by [`optimize`], since the unfolded bodies can make programs much larger.
+    Specialization { unfoldings: usize },
+}
+
+/// The largest number of terms of a function replaced by the [`Pass::Inlining`] pass.
+pub const INLINE_SIZE: usize = 16;
+
+impl Pass {
+    /// Every pass, in the order they are run by [`optimize`].
+    ///
+    /// Inlining runs first so the folding can use the arguments of the inlined functions, and the
+    /// functions that are no longer used are removed at the end.
+    pub const ALL: &'static [Pass] = &[Pass::Inlining, Pass::ConstantFolding, Pass::DeadCode];
+}
+
+/// Optimizes a type-checked term running every pass.
+pub fn optimize(term: Located<Term<'_>>) -> Located<Term<'_>> {
+    optimize_with(term, Pass::ALL)
+}
+
+/// Optimizes a type-checked term running `passes` in order.
+pub fn optimize_with<'a>(term: Located<Term<'a>>, passes: &[Pass]) -> Located<Term<'a>> {
+    passes.iter().fold(term, |term, pass| match pass {
+        Pass::ConstantFolding => fold_constants(term),
+        Pass::DeadCode => remove_dead_code(term),
+        Pass::Inlining => inline(term),
+        Pass::Specialization { unfoldings } => specialize(term, *unfoldings),
+    })
+}
+
+/// Replaces the uses of small non-recursive functions by their definitions, and the applications
+/// of abstractions by their bodies with the argument substituted.
+///
+/// The machine substitutes the argument of an application without evaluating it, so replacing the
+/// application by its body does not change when the argument is evaluated. A use of a function,
+/// or of the parameter of an abstraction, is only replaced if the names used by the function or
+/// the argument are not bound again between its definition and the use.
+fn inline(term: Located<Term<'_>>) -> Located<Term<'_>> {
+    Inliner.fold_term(term)
+}
+
+struct Inliner;
+
+impl<'a> Folder<'a> for Inliner {
+    fn fold_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+        mut t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        if !matches!(kind, LetKind::NonRec(_)) {
+            return self.super_let(loc, kind, name, t1, t2);
+        }
+        let t1 = self.fold_term(t1);
+        if matches!(t1.content, Term::Abs(..)) && size(&t1) <= INLINE_SIZE {
+            let mut free = Vec::new();
+            free_vars(&t1, &mut Vec::new(), &mut free);
+            substitute(&mut t2, name.content, &t1.content, &free);
+        }
+        let t2 = self.fold_term(t2);
+        loc.with_content(Term::Let(kind, name, Box::new(t1), Box::new(t2)))
+    }
+
+    fn fold_app(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        let content = match t1.content {
+            Term::Abs(param, ty, mut body) => {
+                let mut free = Vec::new();
+                free_vars(&t2, &mut Vec::new(), &mut free);
+                if captures(&body, param.content, &free) {
+                    let t1 = t1.loc.with_content(Term::Abs(param, ty, body));
+                    Term::App(Box::new(t1), Box::new(t2))
+                } else {
+                    substitute(&mut body, param.content, &t2.content, &free);
+                    // The argument can be an abstraction applied inside the body.
+                    self.fold_term(*body).content
+                }
+            }
+            content => Term::App(Box::new(t1.loc.with_content(content)), Box::new(t2)),
+        };
+        loc.with_content(content)
+    }
+}
+
+/// Returns the number of terms inside `term`, including itself.
+fn size(term: &Located<Term<'_>>) -> usize {
+    1 + match &term.content {
+        Term::Abs(_, _, t1)
+        | Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1)
+        | Term::Enum(_, _, t1) => size(t1),
+        Term::BinaryOp(_, t1, t2)
+        | Term::App(t1, t2)
+        | Term::Let(_, _, t1, t2)
+        | Term::Seq(t1, t2) => size(t1) + size(t2),
+        Term::Cond(t1, t2, t3) => size(t1) + size(t2) + size(t3),
+        Term::List(elems) => elems.iter().map(size).sum(),
+        Term::LetRec(fns, t1) => {
+            fns.iter().map(|(_, _, body)| size(body)).sum::<usize>() + size(t1)
+        }
+        Term::Match(t1, arms) => size(t1) + arms.iter().map(|(_, body)| size(body)).sum::<usize>(),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) | Term::Constructor(_) => {
+            0
+        }
+    }
+}
+
+/// Folds the operations over literals and substitutes the names bound to literals.
+fn fold_constants(term: Located<Term<'_>>) -> Located<Term<'_>> {
+    ConstantFolder.fold_term(term)
+}
+
+struct ConstantFolder;
+
+impl<'a> Folder<'a> for ConstantFolder {
+    fn fold_unary_op(
+        &mut self,
+        loc: Location,
+        op: UnOp,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let content = match &t1.content {
+            Term::Lit(lit) => match fold_un_op(op, lit) {
+                Some(lit) => Term::Lit(lit),
+                None => Term::UnaryOp(op, Box::new(t1)),
+            },
+            _ => Term::UnaryOp(op, Box::new(t1)),
+        };
+        loc.with_content(content)
+    }
+
+    fn fold_binary_op(
+        &mut self,
+        loc: Location,
+        op: BinOp,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        // The second operand of a logical operator is not evaluated if the first one decides the
+        // result.
+        match (op, &t1.content) {
+            (BinOp::And, Term::Lit(Literal::Bool(false)))
+            | (BinOp::Or, Term::Lit(Literal::Bool(true))) => return t1,
+            (BinOp::And, Term::Lit(Literal::Bool(true)))
+            | (BinOp::Or, Term::Lit(Literal::Bool(false))) => return self.fold_term(t2),
+            _ => (),
+        }
+        let t2 = self.fold_term(t2);
+        let content = match (&t1.content, &t2.content) {
+            (Term::Lit(lit1), Term::Lit(lit2)) => match fold_bin_op(op, lit1, lit2) {
+                Some(lit) => Term::Lit(lit),
+                None => Term::BinaryOp(op, Box::new(t1), Box::new(t2)),
+            },
+            _ => Term::BinaryOp(op, Box::new(t1), Box::new(t2)),
+        };
+        loc.with_content(content)
+    }
+
+    fn fold_cond(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+        t3: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        match t1.content {
+            Term::Lit(Literal::Bool(true)) => self.fold_term(t2),
+            Term::Lit(Literal::Bool(false)) => self.fold_term(t3),
+            content => {
+                let t2 = self.fold_term(t2);
+                let t3 = self.fold_term(t3);
+                let t1 = t1.loc.with_content(content);
+                loc.with_content(Term::Cond(Box::new(t1), Box::new(t2), Box::new(t3)))
+            }
+        }
+    }
+
+    fn fold_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+        mut t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        if !matches!(kind, LetKind::NonRec(_)) {
+            return self.super_let(loc, kind, name, t1, t2);
+        }
+        let t1 = self.fold_term(t1);
+        match &t1.content {
+            // Strings are not substituted so they are not allocated again at each use.
+            Term::Lit(lit) if !matches!(lit, Literal::Str(_)) => {
+                substitute(&mut t2, name.content, &Term::Lit(lit.clone()), &[]);
+                self.fold_term(t2)
+            }
+            _ => {
+                let t2 = self.fold_term(t2);
+                loc.with_content(Term::Let(kind, name, Box::new(t1), Box::new(t2)))
+            }
+        }
+    }
+}
+
+/// Removes the let bindings whose names are not used and the discarded terms without effects.
+fn remove_dead_code(term: Located<Term<'_>>) -> Located<Term<'_>> {
+    DeadCodeRemover.fold_term(term)
+}
+
+struct DeadCodeRemover;
+
+impl<'a> Folder<'a> for DeadCodeRemover {
+    fn fold_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        // Recursive let bindings are not evaluated until their name is used.
+        let rec = matches!(kind, LetKind::Rec(_));
+        if !occurs(&t2, name.content) && (rec || is_pure(&t1)) {
+            return t2;
+        }
+        loc.with_content(Term::Let(kind, name, Box::new(t1), Box::new(t2)))
+    }
+
+    fn fold_let_rec(
+        &mut self,
+        loc: Location,
+        fns: Vec<Function<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        // The functions can only be used by each other if none of them is used by `t1`.
+        if fns.iter().all(|(name, _, _)| !occurs(&t1, name.content)) {
+            return t1;
+        }
+        let fns = fns
+            .into_iter()
+            .map(|(name, ty, body)| (name, ty, self.fold_term(body)))
+            .collect();
+        loc.with_content(Term::LetRec(fns, Box::new(t1)))
+    }
+
+    fn fold_seq(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        if is_pure(&t1) {
+            return t2;
+        }
+        loc.with_content(Term::Seq(Box::new(t1), Box::new(t2)))
+    }
+}
+
+/// Returns `true` if evaluating `term` cannot print, read the input, assign a variable or fail.
+///
+/// Applications are never pure, since the function being applied is not known in general, and
+/// neither are arithmetic operations, which can overflow.
+fn is_pure(term: &Located<Term<'_>>) -> bool {
+    match &term.content {
+        Term::Var(_)
+        | Term::Lit(_)
+        | Term::Abs(..)
+        | Term::Lazy(_)
+        | Term::Native(..)
+        | Term::Constructor(_) => true,
+        // `read_line` and `read_int` are lowered without arguments.
+        Term::PrimFn(prim) => !matches!(prim, Primitive::ReadLine | Primitive::ReadInt),
+        Term::List(elems) => elems.iter().all(is_pure),
+        Term::Ascription(t1, _) | Term::UnaryOp(UnOp::Not, t1) => is_pure(t1),
+        Term::BinaryOp(op, t1, t2) => {
+            use BinOp::*;
+            matches!(op, Lt | Lte | Gt | Gte | Eq | Neq | And | Or) && is_pure(t1) && is_pure(t2)
+        }
+        Term::UnaryOp(..)
+        | Term::App(..)
+        | Term::Cond(..)
+        | Term::Let(..)
+        | Term::LetRec(..)
+        | Term::Seq(..)
+        | Term::Assign(..)
+        | Term::Enum(..)
+        | Term::Match(..) => false,
+    }
+}
+
+/// Returns `true` if the variable `name` bound outside of `term` is used or assigned in `term`.
+fn occurs<'a>(term: &Located<Term<'a>>, name: Name<'a>) -> bool {
+    match &term.content {
+        Term::Var(name2) => *name2 == name,
+        Term::Abs(param, _, body) => param.content != name && occurs(body, name),
+        Term::Let(kind, name2, t1, t2) => {
+            // The name of a recursive let binding is also bound in its first term.
+            let rec = matches!(kind, LetKind::Rec(_));
+            ((!rec || name2.content != name) && occurs(t1, name))
+                || (name2.content != name && occurs(t2, name))
+        }
+        Term::LetRec(fns, t1) => {
+            fns.iter().all(|(name2, _, _)| name2.content != name)
+                && (fns.iter().any(|(_, _, body)| occurs(body, name)) || occurs(t1, name))
+        }
+        Term::Match(t1, arms) => {
+            occurs(t1, name)
+                || arms.iter().any(|(pattern, body)| {
+                    !binders(pattern).iter().any(|binder| binder.content == name)
+                        && occurs(body, name)
+                })
+        }
+        Term::Assign(name2, t1) => name2.content == name || occurs(t1, name),
+        Term::UnaryOp(_, t1) | Term::Ascription(t1, _) | Term::Lazy(t1) | Term::Enum(_, _, t1) => {
+            occurs(t1, name)
+        }
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+            occurs(t1, name) || occurs(t2, name)
+        }
+        Term::Cond(t1, t2, t3) => occurs(t1, name) || occurs(t2, name) || occurs(t3, name),
+        Term::List(elems) => elems.iter().any(|elem| occurs(elem, name)),
+        Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) | Term::Constructor(_) => false,
+    }
+}
+
+/// Returns an integer literal with the result of a folded operation, or `None` if it does not fit
+/// in 64 bits.
+///
+/// The terms are optimized without knowing the width of the machine that evaluates them. The
+/// operands of a folded operation are literals, so a result that fits in the narrowest width is
+/// the same whatever the width, while any other result overflows in some machines.
+fn fold_int(n: Int) -> Option<Literal> {
+    Some(n)
+        .filter(|n| IntWidth::I64.contains(*n))
+        .map(Literal::Number)
+}
+
+/// Returns `n` as the amount of a folded shift, or `None` if it is negative or not smaller than
+/// 64, which overflows in some machines.
+fn shift_amount(n: Int) -> Option<u32> {
+    u32::try_from(n).ok().filter(|n| *n < IntWidth::I64.bits())
+}
+
+/// Returns the result of a unary operation over a literal, or `None` if it cannot be folded.
+fn fold_un_op(op: UnOp, lit: &Literal) -> Option<Literal> {
+    match (op, lit) {
+        (UnOp::Neg, Literal::Number(n)) => fold_int(n.checked_neg()?),
+        (UnOp::Neg, Literal::Float(x)) => Some(Literal::Float(-x)),
+        (UnOp::Not, Literal::Bool(b)) => Some(Literal::Bool(!b)),
+        (UnOp::BitNot, Literal::Number(n)) => fold_int(!n),
+        _ => None,
+    }
+}
+
+/// Returns the result of a binary operation over two literals, or `None` if it cannot be folded.
+///
+/// Operations over floats and strings are not folded.
+fn fold_bin_op(op: BinOp, lit1: &Literal, lit2: &Literal) -> Option<Literal> {
+    use BinOp::*;
+
+    let lit = match (lit1, lit2) {
+        (Literal::Number(n1), Literal::Number(n2)) => {
+            let (n1, n2) = (*n1, *n2);
+            match op {
+                Add => fold_int(n1.checked_add(n2)?)?,
+                Sub => fold_int(n1.checked_sub(n2)?)?,
+                Mul => fold_int(n1.checked_mul(n2)?)?,
+                Div => fold_int(n1.checked_div(n2)?)?,
+                Rem => fold_int(n1.checked_rem(n2)?)?,
+                BitAnd => fold_int(n1 & n2)?,
+                BitOr => fold_int(n1 | n2)?,
+                BitXor => fold_int(n1 ^ n2)?,
+                Shl => fold_int(n1 << shift_amount(n2)?)?,
+                Shr => fold_int(n1 >> shift_amount(n2)?)?,
+                op => Literal::Bool(compare(op, n1, n2)?),
+            }
+        }
+        (Literal::Char(c1), Literal::Char(c2)) => Literal::Bool(compare(op, *c1, *c2)?),
+        (Literal::Bool(b1), Literal::Bool(b2)) => match op {
+            And => Literal::Bool(*b1 && *b2),
+            Or => Literal::Bool(*b1 || *b2),
+            op => Literal::Bool(compare(op, *b1, *b2)?),
+        },
+        _ => return None,
+    };
+    Some(lit)
+}
+
+/// Returns the result of a comparison, or `None` if `op` is not a comparison operator.
+fn compare<T: PartialOrd>(op: BinOp, x: T, y: T) -> Option<bool> {
+    let b = match op {
+        BinOp::Lt => x < y,
+        BinOp::Lte => x <= y,
+        BinOp::Gt => x > y,
+        BinOp::Gte => x >= y,
+        BinOp::Eq => x == y,
+        BinOp::Neq => x != y,
+        _ => return None,
+    };
+    Some(b)
+}
+
+/// Replaces the variables called `name` that are bound outside of `term` by `value`, whose free
+/// variables are `free`.
+///
+/// The variables inside the scope of a binding of a name in `free` are not replaced, since
+/// `value` would use that binding instead of the one it was meant to use. [`captures`] returns
+/// `true` if there are such variables.
+fn substitute<'a>(
+    term: &mut Located<Term<'a>>,
+    name: Name<'a>,
+    value: &Term<'a>,
+    free: &[Name<'a>],
+) {
+    let stops = |binder: Name<'a>| binder == name || free.contains(&binder);
+    match &mut term.content {
+        Term::Var(name2) if *name2 == name => term.content = value.clone(),
+        Term::Abs(param, _, body) => {
+            if !stops(param.content) {
+                substitute(body, name, value, free);
+            }
+        }
+        Term::Let(kind, name2, t1, t2) => {
+            // The name of a recursive let binding is also bound in its first term.
+            let rec = matches!(kind, LetKind::Rec(_));
+            if !rec || !stops(name2.content) {
+                substitute(t1, name, value, free);
+            }
+            if !stops(name2.content) {
+                substitute(t2, name, value, free);
+            }
+        }
+        Term::LetRec(fns, t1) => {
+            if !fns.iter().any(|(name2, _, _)| stops(name2.content)) {
+                for (_, _, body) in fns {
+                    substitute(body, name, value, free);
+                }
+                substitute(t1, name, value, free);
+            }
+        }
+        Term::Match(t1, arms) => {
+            substitute(t1, name, value, free);
+            for (pattern, body) in arms {
+                if !binders(pattern).iter().any(|binder| stops(binder.content)) {
+                    substitute(body, name, value, free);
+                }
+            }
+        }
+        Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1)
+        | Term::Enum(_, _, t1) => substitute(t1, name, value, free),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+            substitute(t1, name, value, free);
+            substitute(t2, name, value, free);
+        }
+        Term::Cond(t1, t2, t3) => {
+            substitute(t1, name, value, free);
+            substitute(t2, name, value, free);
+            substitute(t3, name, value, free);
+        }
+        Term::List(elems) => {
+            for elem in elems {
+                substitute(elem, name, value, free);
+            }
+        }
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => (),
+        Term::Native(..) | Term::Constructor(_) => (),
+    }
+}
+
+/// Returns `true` if a variable called `name` bound outside of `term` is used inside the scope of
+/// a binding of a name in `free`.
+fn captures<'a>(term: &Located<Term<'a>>, name: Name<'a>, free: &[Name<'a>]) -> bool {
+    // Returns `true` if `term` is in the scope of `binder` and uses `name`.
+    let captured = |binder: Name<'a>, term: &Located<Term<'a>>| {
+        binder != name && free.contains(&binder) && occurs(term, name)
+    };
+    match &term.content {
+        Term::Abs(param, _, body) => {
+            param.content != name && (captured(param.content, body) || captures(body, name, free))
+        }
+        Term::Let(kind, name2, t1, t2) => {
+            let rec = matches!(kind, LetKind::Rec(_));
+            let in_t1 = if rec && name2.content != name {
+                captured(name2.content, t1) || captures(t1, name, free)
+            } else {
+                !rec && captures(t1, name, free)
+            };
+            in_t1
+                || (name2.content != name
+                    && (captured(name2.content, t2) || captures(t2, name, free)))
+        }
+        Term::LetRec(fns, t1) => {
+            if fns.iter().any(|(name2, _, _)| name2.content == name) {
+                return false;
+            }
+            let terms = || fns.iter().map(|(_, _, body)| body).chain(Some(&**t1));
+            fns.iter()
+                .any(|(name2, _, _)| terms().any(|term| captured(name2.content, term)))
+                || terms().any(|term| captures(term, name, free))
+        }
+        Term::Match(t1, arms) => {
+            captures(t1, name, free)
+                || arms.iter().any(|(pattern, body)| {
+                    let binders = binders(pattern);
+                    !binders.iter().any(|binder| binder.content == name)
+                        && (binders.iter().any(|binder| captured(binder.content, body))
+                            || captures(body, name, free))
+                })
+        }
+        Term::UnaryOp(_, t1)
+        | Term::Assign(_, t1)
+        | Term::Ascription(t1, _)
+        | Term::Lazy(t1)
+        | Term::Enum(_, _, t1) => captures(t1, name, free),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+            captures(t1, name, free) || captures(t2, name, free)
+        }
+        Term::Cond(t1, t2, t3) => {
+            captures(t1, name, free) || captures(t2, name, free) || captures(t3, name, free)
+        }
+        Term::List(elems) => elems.iter().any(|elem| captures(elem, name, free)),
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => false,
+        Term::Native(..) | Term::Constructor(_) => false,
+    }
+}
+
+/// Adds the names used or assigned in `term` that are not bound in `term` or in `bound` to
+/// `free`.
+fn free_vars<'a>(term: &Located<Term<'a>>, bound: &mut Vec<Name<'a>>, free: &mut Vec<Name<'a>>) {
+    let mut use_name = |name: Name<'a>, bound: &[Name<'a>]| {
+        if !bound.contains(&name) && !free.contains(&name) {
+            free.push(name);
+        }
+    };
+    match &term.content {
+        Term::Var(name) => use_name(*name, bound),
+        Term::Assign(name, t1) => {
+            use_name(name.content, bound);
+            free_vars(t1, bound, free);
+        }
+        Term::Abs(param, _, body) => {
+            bound.push(param.content);
+            free_vars(body, bound, free);
+            bound.pop();
+        }
+        Term::Let(kind, name, t1, t2) => {
+            let rec = matches!(kind, LetKind::Rec(_));
+            if !rec {
+                free_vars(t1, bound, free);
+            }
+            bound.push(name.content);
+            if rec {
+                free_vars(t1, bound, free);
+            }
+            free_vars(t2, bound, free);
+            bound.pop();
+        }
+        Term::LetRec(fns, t1) => {
+            let len = bound.len();
+            bound.extend(fns.iter().map(|(name, _, _)| name.content));
+            for (_, _, body) in fns {
+                free_vars(body, bound, free);
+            }
+            free_vars(t1, bound, free);
+            bound.truncate(len);
+        }
+        Term::Match(t1, arms) => {
+            free_vars(t1, bound, free);
+            for (pattern, body) in arms {
+                let len = bound.len();
+                bound.extend(binders(pattern).iter().map(|binder| binder.content));
+                free_vars(body, bound, free);
+                bound.truncate(len);
+            }
+        }
+        Term::UnaryOp(_, t1) | Term::Ascription(t1, _) | Term::Lazy(t1) | Term::Enum(_, _, t1) => {
+            free_vars(t1, bound, free)
+        }
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+            free_vars(t1, bound, free);
+            free_vars(t2, bound, free);
+        }
+        Term::Cond(t1, t2, t3) => {
+            free_vars(t1, bound, free);
+            free_vars(t2, bound, free);
+            free_vars(t3, bound, free);
+        }
+        Term::List(elems) => {
+            for elem in elems {
+                free_vars(elem, bound, free);
+            }
+        }
+        Term::Lit(_) | Term::PrimFn(_) | Term::Native(..) | Term::Constructor(_) => (),
+    }
+}
+
+/// Returns the names bound by the pattern of an arm.
+fn binders<'p, 'a>(pattern: &'p Located<Pattern<'a>>) -> &'p [Located<Name<'a>>] {
+    match &pattern.content {
+        Pattern::Variant(_, binders) => binders,
+        _ => &[],
+    }
+}
@@ -0,0 +1,276 @@
+//! Specialization of functions applied to literals.
+//!
+//! An application of a known function to at least one literal is replaced by the body of the
+//! function with the arguments substituted, and the body is folded and specialized again. The
+//! conditions that depend on the literals are decided while folding, so a recursive function
+//! applied to the argument that controls its recursion is unfolded until the recursion ends. For
+//! example, `pow(x, 3)` becomes `x * (x * (x * 1))`.
+//!
+//! Each application of the program unfolds at most a fixed number of applications, including the
+//! ones found inside the unfolded bodies, so specialization always ends, and the remaining
+//! applications call the function as before.
+use alloc::{boxed::Box, vec::Vec};
+
+use pijama_ast::{Literal, Located, Location, Name, Pattern};
+
+use crate::{
+    mir::{
+        visit::{Arm, Folder, Function},
+        LetKind, Term,
+    },
+    ty::Ty,
+};
+
+use super::{binders, captures, fold_constants, free_vars, is_pure, substitute};
+
+/// Specializes the applications of functions to literals in `term`, unfolding at most
+/// `unfoldings` applications for each application of `term`.
+pub(super) fn specialize(term: Located<Term<'_>>, unfoldings: usize) -> Located<Term<'_>> {
+    Specializer {
+        fns: Vec::new(),
+        bound: Vec::new(),
+        unfoldings,
+        fuel: 0,
+        depth: 0,
+    }
+    .fold_term(term)
+}
+
+/// A function that can be unfolded where its name is visible.
+struct KnownFunction<'a> {
+    name: Name<'a>,
+    params: Vec<Name<'a>>,
+    body: Located<Term<'a>>,
+    /// The names used by the function that are bound outside of it.
+    free: Vec<Name<'a>>,
+    /// The number of names bound where the function became visible.
+    scope: usize,
+}
+
+struct Specializer<'a> {
+    /// The functions that are visible, from the outermost to the innermost one.
+    fns: Vec<KnownFunction<'a>>,
+    /// The names bound by the terms around the current one.
+    bound: Vec<Name<'a>>,
+    unfoldings: usize,
+    /// The number of applications that can still be unfolded for the current application of the
+    /// program.
+    fuel: usize,
+    /// The number of unfolded bodies around the current term.
+    depth: usize,
+}
+
+impl<'a> Folder<'a> for Specializer<'a> {
+    fn fold_app(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.app(loc.with_content(Term::App(Box::new(t1), Box::new(t2))))
+    }
+
+    fn fold_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        mut t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let rec = matches!(kind, LetKind::Rec(_));
+        let len = self.fns.len();
+        if !rec {
+            t1 = self.fold_term(t1);
+        }
+        self.bound.push(name.content);
+        let function = match &kind {
+            // The name used by the function is bound outside of the let binding.
+            LetKind::NonRec(_) => self
+                .function(name.content, &t1)
+                .filter(|function| !function.free.contains(&name.content)),
+            LetKind::Rec(_) => self.function(name.content, &t1),
+            // Mutable bindings can be assigned another function.
+            LetKind::Mut(_) => None,
+        };
+        self.fns.extend(function);
+        if rec {
+            t1 = self.fold_term(t1);
+        }
+        let t2 = self.fold_term(t2);
+        self.fns.truncate(len);
+        self.bound.pop();
+        loc.with_content(Term::Let(kind, name, Box::new(t1), Box::new(t2)))
+    }
+
+    fn fold_let_rec(
+        &mut self,
+        loc: Location,
+        fns: Vec<Function<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let (len, bound) = (self.fns.len(), self.bound.len());
+        for (name, _, _) in &fns {
+            self.bound.push(name.content);
+        }
+        let functions: Vec<_> = fns
+            .iter()
+            .filter_map(|(name, _, body)| self.function(name.content, body))
+            .collect();
+        self.fns.extend(functions);
+        let term = self.super_let_rec(loc, fns, t1);
+        self.fns.truncate(len);
+        self.bound.truncate(bound);
+        term
+    }
+
+    fn fold_abs(
+        &mut self,
+        loc: Location,
+        param: Located<Name<'a>>,
+        ty: Ty,
+        body: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.bound.push(param.content);
+        let term = self.super_abs(loc, param, ty, body);
+        self.bound.pop();
+        term
+    }
+
+    fn fold_arm(&mut self, pattern: Located<Pattern<'a>>, body: Located<Term<'a>>) -> Arm<'a> {
+        let len = self.bound.len();
+        for binder in binders(&pattern) {
+            self.bound.push(binder.content);
+        }
+        let arm = self.super_arm(pattern, body);
+        self.bound.truncate(len);
+        arm
+    }
+}
+
+impl<'a> Specializer<'a> {
+    /// Specializes an application, unfolding it if its head is a known function.
+    fn app(&mut self, mut term: Located<Term<'a>>) -> Located<Term<'a>> {
+        // The arguments with the locations of their applications, from the last one.
+        let mut args = Vec::new();
+        loop {
+            let loc = term.loc;
+            match term.content {
+                Term::App(t1, t2) => {
+                    args.push((loc, *t2));
+                    term = *t1;
+                }
+                content => {
+                    term = loc.with_content(content);
+                    break;
+                }
+            }
+        }
+        let head = self.fold_term(term);
+        let args: Vec<_> = args
+            .into_iter()
+            .rev()
+            .map(|(loc, arg)| (loc, self.fold_term(arg)))
+            .collect();
+        match self.unfold(&head, &args) {
+            Some((body, len)) => apply(body, args.into_iter().skip(len)),
+            None => apply(head, args),
+        }
+    }
+
+    /// Returns the body of the function `head` with the first arguments of `args` substituted,
+    /// and the number of arguments used, if the application can be unfolded.
+    ///
+    /// The application is unfolded if at least one argument is a literal and the others are pure,
+    /// so substituting them does not change the effects of the program. Strings are not
+    /// considered literals so they are not allocated again at each use.
+    fn unfold(
+        &mut self,
+        head: &Located<Term<'a>>,
+        args: &[(Location, Located<Term<'a>>)],
+    ) -> Option<(Located<Term<'a>>, usize)> {
+        let name = match head.content {
+            Term::Var(name) => name,
+            _ => return None,
+        };
+        let function = self
+            .fns
+            .iter()
+            .rev()
+            .find(|function| function.name == name)?;
+        // The function cannot be used if its name or the names it uses are bound again.
+        if self.bound[function.scope..]
+            .iter()
+            .any(|binder| *binder == name || function.free.contains(binder))
+        {
+            return None;
+        }
+        let params = function.params.clone();
+        let args = args.get(..params.len())?;
+        let literal = |arg: &Located<Term<'_>>| matches!(&arg.content, Term::Lit(lit) if !matches!(lit, Literal::Str(_)));
+        if !args.iter().any(|(_, arg)| literal(arg)) || !args.iter().all(|(_, arg)| is_pure(arg)) {
+            return None;
+        }
+
+        let mut free = Vec::new();
+        for (_, arg) in args {
+            free_vars(arg, &mut Vec::new(), &mut free);
+        }
+        if free.iter().any(|name| params.contains(name)) {
+            return None;
+        }
+        let mut body = function.body.clone();
+        // The last parameter is bound by the innermost abstraction, so it is substituted first in
+        // case the parameters have the same name.
+        for (param, (_, arg)) in params.iter().zip(args).rev() {
+            if captures(&body, *param, &free) {
+                return None;
+            }
+            substitute(&mut body, *param, &arg.content, &free);
+        }
+
+        if self.depth == 0 {
+            self.fuel = self.unfoldings;
+        }
+        if self.fuel == 0 {
+            return None;
+        }
+        self.fuel -= 1;
+        self.depth += 1;
+        let body = self.fold_term(fold_constants(body));
+        self.depth -= 1;
+        Some((body, params.len()))
+    }
+
+    /// Returns the function bound to `name` if `term` is an abstraction.
+    fn function(&self, name: Name<'a>, term: &Located<Term<'a>>) -> Option<KnownFunction<'a>> {
+        let mut params = Vec::new();
+        let mut body = term;
+        while let Term::Abs(param, _, t1) = &body.content {
+            params.push(param.content);
+            body = t1;
+        }
+        if params.is_empty() {
+            return None;
+        }
+        let mut free = Vec::new();
+        free_vars(term, &mut Vec::new(), &mut free);
+        Some(KnownFunction {
+            name,
+            params,
+            body: body.clone(),
+            free,
+            scope: self.bound.len(),
+        })
+    }
+}
+
+/// Applies `head` to each of `args` in order.
+fn apply<'a>(
+    head: Located<Term<'a>>,
+    args: impl IntoIterator<Item = (Location, Located<Term<'a>>)>,
+) -> Located<Term<'a>> {
+    args.into_iter().fold(head, |t1, (loc, t2)| {
+        loc.with_content(Term::App(Box::new(t1), Box::new(t2)))
+    })
+}
@@ -0,0 +1,110 @@
+//! Resolution of the names of primitive functions.
+//!
+//! The names returned by [`Primitive::from_name`] are ordinary names for the parser. After
+//! lowering, each variable that uses one of them and is not bound by the program is replaced by
+//! the [`Term::PrimFn`] of the primitive, so a user binding called `max` or `len` shadows the
+//! primitive inside its scope.
+use alloc::{boxed::Box, vec::Vec};
+
+use pijama_ast::{Located, Location, Name, Pattern, Primitive};
+
+use crate::{
+    mir::{
+        visit::{Arm, Folder, Function},
+        LetKind, Term,
+    },
+    ty::Ty,
+};
+
+/// Replaces the free variables of `term` named after a primitive by the primitive.
+///
+/// The names in `bound` are treated as bound around the whole term, like the bindings that the
+/// driver adds before type-checking.
+pub(crate) fn resolve_primitives<'a>(term: Located<Term<'a>>, bound: &[&str]) -> Located<Term<'a>> {
+    Resolver {
+        bound: bound.to_vec(),
+    }
+    .fold_term(term)
+}
+
+/// Folder keeping the names bound at each point of the term.
+struct Resolver<'b> {
+    bound: Vec<&'b str>,
+}
+
+impl<'b> Resolver<'b> {
+    /// Folds `term` with `names` bound.
+    fn fold_with<'a: 'b>(
+        &mut self,
+        names: impl IntoIterator<Item = Name<'a>>,
+        term: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let len = self.bound.len();
+        self.bound.extend(names.into_iter().map(|name| name.0));
+        let term = self.fold_term(term);
+        self.bound.truncate(len);
+        term
+    }
+}
+
+impl<'a: 'b, 'b> Folder<'a> for Resolver<'b> {
+    fn fold_var(&mut self, loc: Location, name: Name<'a>) -> Located<Term<'a>> {
+        match Primitive::from_name(name.0) {
+            Some(prim) if !self.bound.contains(&name.0) => loc.with_content(Term::PrimFn(prim)),
+            _ => self.super_var(loc, name),
+        }
+    }
+
+    fn fold_abs(
+        &mut self,
+        loc: Location,
+        param: Located<Name<'a>>,
+        ty: Ty,
+        body: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let body = self.fold_with(Some(param.content), body);
+        loc.with_content(Term::Abs(param, ty, Box::new(body)))
+    }
+
+    fn fold_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = match kind {
+            LetKind::Rec(_) => self.fold_with(Some(name.content), t1),
+            LetKind::NonRec(_) | LetKind::Mut(_) => self.fold_term(t1),
+        };
+        let t2 = self.fold_with(Some(name.content), t2);
+        loc.with_content(Term::Let(kind, name, Box::new(t1), Box::new(t2)))
+    }
+
+    fn fold_let_rec(
+        &mut self,
+        loc: Location,
+        fns: Vec<Function<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let names: Vec<_> = fns.iter().map(|(name, _, _)| name.content).collect();
+        let fns = fns
+            .into_iter()
+            .map(|(name, ty, body)| (name, ty, self.fold_with(names.iter().copied(), body)))
+            .collect();
+        let t1 = self.fold_with(names, t1);
+        loc.with_content(Term::LetRec(fns, Box::new(t1)))
+    }
+
+    fn fold_arm(&mut self, pattern: Located<Pattern<'a>>, body: Located<Term<'a>>) -> Arm<'a> {
+        let body = match &pattern.content {
+            Pattern::Variant(_, binders) => {
+                let names: Vec<_> = binders.iter().map(|binder| binder.content).collect();
+                self.fold_with(names, body)
+            }
+            Pattern::Wildcard | Pattern::Literal(_) => self.fold_term(body),
+        };
+        (pattern, body)
+    }
+}
@@ -0,0 +1,719 @@
+//! Traits to traverse and rewrite the MIR.
+//!
+//! [`Visitor`] traverses a term by reference and [`Folder`] rebuilds a term by value. Both follow
+//! the [`NodeVisitor`](pijama_ast::visitor::NodeVisitor) of the AST: there is a method for each
+//! kind of term, and passes only override the methods of the terms they are interested in.
+use alloc::{boxed::Box, vec::Vec};
+
+use pijama_ast::{BinOp, Literal, Located, Location, Name, Pattern, Primitive, UnOp};
+
+use crate::{
+    mir::{LetKind, Term, Variant},
+    ty::Ty,
+};
+
+/// A function of a group of mutually recursive functions, see [`Term::LetRec`].
+pub type Function<'a> = (Located<Name<'a>>, Located<Ty>, Located<Term<'a>>);
+
+/// An arm of a match, see [`Term::Match`].
+pub type Arm<'a> = (Located<Pattern<'a>>, Located<Term<'a>>);
+
+/// Trait for the visitor pattern over the MIR.
+///
+/// There are two kinds of methods:
+/// - The `visit_<foo>` methods: where the code specific to your visiting resides.
+/// - The `super_<foo>` methods: that destructure each component and take care of the actual
+///   visiting.
+///
+/// The `visit_<foo>` methods are the ones that should be modified. You should always call the
+/// corresponding `super_<foo>` method inside your implementation of `visit_<foo>` to guarantee
+/// that your visitor will visit the whole term.
+///
+/// The terms are borrowed for `'t`, so visitors can keep references to the terms they visit.
+///
+/// Every update to the `Term` type should be reflected here too.
+pub trait Visitor<'t, 'a: 't> {
+    /// Visits a Term.
+    fn super_term(&mut self, term: &'t Located<Term<'a>>) {
+        match &term.content {
+            Term::Var(name) => self.visit_var(*name),
+            Term::Abs(param, ty, body) => self.visit_abs(param, ty, body),
+            Term::UnaryOp(op, t1) => self.visit_unary_op(*op, t1),
+            Term::BinaryOp(op, t1, t2) => self.visit_binary_op(*op, t1, t2),
+            Term::App(t1, t2) => self.visit_app(t1, t2),
+            Term::Lit(lit) => self.visit_lit(lit),
+            Term::List(elems) => self.visit_list(elems),
+            Term::Cond(t1, t2, t3) => self.visit_cond(t1, t2, t3),
+            Term::Let(kind, name, t1, t2) => self.visit_let(kind, name, t1, t2),
+            Term::LetRec(fns, t1) => self.visit_let_rec(fns, t1),
+            Term::Seq(t1, t2) => self.visit_seq(t1, t2),
+            Term::Assign(name, t1) => self.visit_assign(name, t1),
+            Term::Ascription(t1, ty) => self.visit_ascription(t1, ty),
+            Term::Lazy(t1) => self.visit_lazy(t1),
+            Term::PrimFn(prim) => self.visit_prim_fn(*prim),
+            Term::Native(index, ty) => self.visit_native(*index, ty),
+            Term::Enum(name, variants, t1) => self.visit_enum(name, variants, t1),
+            Term::Constructor(name) => self.visit_constructor(*name),
+            Term::Match(t1, arms) => self.visit_match(t1, arms),
+        }
+    }
+
+    /// Visits a Variable.
+    fn super_var(&mut self, _name: Name<'a>) {}
+
+    /// Visits an Abstraction.
+    fn super_abs(
+        &mut self,
+        _param: &'t Located<Name<'a>>,
+        _ty: &'t Ty,
+        body: &'t Located<Term<'a>>,
+    ) {
+        self.visit_term(body);
+    }
+
+    /// Visits a Unary operation.
+    fn super_unary_op(&mut self, _op: UnOp, t1: &'t Located<Term<'a>>) {
+        self.visit_term(t1);
+    }
+
+    /// Visits a Binary operation.
+    fn super_binary_op(
+        &mut self,
+        _op: BinOp,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+    ) {
+        self.visit_term(t1);
+        self.visit_term(t2);
+    }
+
+    /// Visits an Application.
+    fn super_app(&mut self, t1: &'t Located<Term<'a>>, t2: &'t Located<Term<'a>>) {
+        self.visit_term(t1);
+        self.visit_term(t2);
+    }
+
+    /// Visits a Literal.
+    fn super_lit(&mut self, _lit: &'t Literal) {}
+
+    /// Visits a List literal.
+    fn super_list(&mut self, elems: &'t [Located<Term<'a>>]) {
+        for elem in elems {
+            self.visit_term(elem);
+        }
+    }
+
+    /// Visits a Conditional.
+    fn super_cond(
+        &mut self,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+        t3: &'t Located<Term<'a>>,
+    ) {
+        self.visit_term(t1);
+        self.visit_term(t2);
+        self.visit_term(t3);
+    }
+
+    /// Visits a Let binding.
+    fn super_let(
+        &mut self,
+        _kind: &'t LetKind,
+        _name: &'t Located<Name<'a>>,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+    ) {
+        self.visit_term(t1);
+        self.visit_term(t2);
+    }
+
+    /// Visits a group of mutually recursive functions.
+    fn super_let_rec(&mut self, fns: &'t [Function<'a>], t1: &'t Located<Term<'a>>) {
+        for (_, _, body) in fns {
+            self.visit_term(body);
+        }
+        self.visit_term(t1);
+    }
+
+    /// Visits a Sequence.
+    fn super_seq(&mut self, t1: &'t Located<Term<'a>>, t2: &'t Located<Term<'a>>) {
+        self.visit_term(t1);
+        self.visit_term(t2);
+    }
+
+    /// Visits an Assignment.
+    fn super_assign(&mut self, _name: &'t Located<Name<'a>>, t1: &'t Located<Term<'a>>) {
+        self.visit_term(t1);
+    }
+
+    /// Visits a Type ascription.
+    fn super_ascription(&mut self, t1: &'t Located<Term<'a>>, _ty: &'t Located<Ty>) {
+        self.visit_term(t1);
+    }
+
+    /// Visits a Lazy term.
+    fn super_lazy(&mut self, t1: &'t Located<Term<'a>>) {
+        self.visit_term(t1);
+    }
+
+    /// Visits a Primitive function.
+    fn super_prim_fn(&mut self, _prim: Primitive) {}
+
+    /// Visits a Native function.
+    fn super_native(&mut self, _index: usize, _ty: &'t Ty) {}
+
+    /// Visits an Enum declaration.
+    fn super_enum(
+        &mut self,
+        _name: &'t Located<Name<'a>>,
+        _variants: &'t [Variant<'a>],
+        t1: &'t Located<Term<'a>>,
+    ) {
+        self.visit_term(t1);
+    }
+
+    /// Visits a Constructor.
+    fn super_constructor(&mut self, _name: Name<'a>) {}
+
+    /// Visits a Match.
+    fn super_match(&mut self, t1: &'t Located<Term<'a>>, arms: &'t [Arm<'a>]) {
+        self.visit_term(t1);
+        for (pattern, body) in arms {
+            self.visit_arm(pattern, body);
+        }
+    }
+
+    /// Visits a single Arm of a Match.
+    fn super_arm(&mut self, _pattern: &'t Located<Pattern<'a>>, body: &'t Located<Term<'a>>) {
+        self.visit_term(body);
+    }
+
+    /// Specifies how Terms should be visited.
+    fn visit_term(&mut self, term: &'t Located<Term<'a>>) {
+        self.super_term(term);
+    }
+
+    /// Specifies how Variables should be visited.
+    fn visit_var(&mut self, name: Name<'a>) {
+        self.super_var(name);
+    }
+
+    /// Specifies how Abstractions should be visited.
+    fn visit_abs(&mut self, param: &'t Located<Name<'a>>, ty: &'t Ty, body: &'t Located<Term<'a>>) {
+        self.super_abs(param, ty, body);
+    }
+
+    /// Specifies how Unary operations should be visited.
+    fn visit_unary_op(&mut self, op: UnOp, t1: &'t Located<Term<'a>>) {
+        self.super_unary_op(op, t1);
+    }
+
+    /// Specifies how Binary operations should be visited.
+    fn visit_binary_op(&mut self, op: BinOp, t1: &'t Located<Term<'a>>, t2: &'t Located<Term<'a>>) {
+        self.super_binary_op(op, t1, t2);
+    }
+
+    /// Specifies how Applications should be visited.
+    fn visit_app(&mut self, t1: &'t Located<Term<'a>>, t2: &'t Located<Term<'a>>) {
+        self.super_app(t1, t2);
+    }
+
+    /// Specifies how Literals should be visited.
+    fn visit_lit(&mut self, lit: &'t Literal) {
+        self.super_lit(lit);
+    }
+
+    /// Specifies how List literals should be visited.
+    fn visit_list(&mut self, elems: &'t [Located<Term<'a>>]) {
+        self.super_list(elems);
+    }
+
+    /// Specifies how Conditionals should be visited.
+    fn visit_cond(
+        &mut self,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+        t3: &'t Located<Term<'a>>,
+    ) {
+        self.super_cond(t1, t2, t3);
+    }
+
+    /// Specifies how Let bindings should be visited.
+    fn visit_let(
+        &mut self,
+        kind: &'t LetKind,
+        name: &'t Located<Name<'a>>,
+        t1: &'t Located<Term<'a>>,
+        t2: &'t Located<Term<'a>>,
+    ) {
+        self.super_let(kind, name, t1, t2);
+    }
+
+    /// Specifies how groups of mutually recursive functions should be visited.
+    fn visit_let_rec(&mut self, fns: &'t [Function<'a>], t1: &'t Located<Term<'a>>) {
+        self.super_let_rec(fns, t1);
+    }
+
+    /// Specifies how Sequences should be visited.
+    fn visit_seq(&mut self, t1: &'t Located<Term<'a>>, t2: &'t Located<Term<'a>>) {
+        self.super_seq(t1, t2);
+    }
+
+    /// Specifies how Assignments should be visited.
+    fn visit_assign(&mut self, name: &'t Located<Name<'a>>, t1: &'t Located<Term<'a>>) {
+        self.super_assign(name, t1);
+    }
+
+    /// Specifies how Type ascriptions should be visited.
+    fn visit_ascription(&mut self, t1: &'t Located<Term<'a>>, ty: &'t Located<Ty>) {
+        self.super_ascription(t1, ty);
+    }
+
+    /// Specifies how Lazy terms should be visited.
+    fn visit_lazy(&mut self, t1: &'t Located<Term<'a>>) {
+        self.super_lazy(t1);
+    }
+
+    /// Specifies how Primitive functions should be visited.
+    fn visit_prim_fn(&mut self, prim: Primitive) {
+        self.super_prim_fn(prim);
+    }
+
+    /// Specifies how Native functions should be visited.
+    fn visit_native(&mut self, index: usize, ty: &'t Ty) {
+        self.super_native(index, ty);
+    }
+
+    /// Specifies how Enum declarations should be visited.
+    fn visit_enum(
+        &mut self,
+        name: &'t Located<Name<'a>>,
+        variants: &'t [Variant<'a>],
+        t1: &'t Located<Term<'a>>,
+    ) {
+        self.super_enum(name, variants, t1);
+    }
+
+    /// Specifies how Constructors should be visited.
+    fn visit_constructor(&mut self, name: Name<'a>) {
+        self.super_constructor(name);
+    }
+
+    /// Specifies how Matches should be visited.
+    fn visit_match(&mut self, t1: &'t Located<Term<'a>>, arms: &'t [Arm<'a>]) {
+        self.super_match(t1, arms);
+    }
+
+    /// Specifies how Arms should be visited.
+    fn visit_arm(&mut self, pattern: &'t Located<Pattern<'a>>, body: &'t Located<Term<'a>>) {
+        self.super_arm(pattern, body);
+    }
+}
+
+/// Trait to rewrite the MIR.
+///
+/// A folder takes each term by value and returns the term that replaces it. The methods follow
+/// the ones of [`Visitor`]: the `fold_<foo>` methods are the ones that should be modified, and the
+/// `super_<foo>` methods fold the components of a term and build it again with the results. Each
+/// method receives the location of the term it folds. A term built again without an id keeps the
+/// id of the term it replaces.
+///
+/// The components of a term are folded in the order they appear in the source.
+pub trait Folder<'a> {
+    /// Folds a Term.
+    fn super_term(&mut self, term: Located<Term<'a>>) -> Located<Term<'a>> {
+        let loc = term.loc;
+        let id = term.id;
+        let folded = match term.content {
+            Term::Var(name) => self.fold_var(loc, name),
+            Term::Abs(param, ty, body) => self.fold_abs(loc, param, ty, *body),
+            Term::UnaryOp(op, t1) => self.fold_unary_op(loc, op, *t1),
+            Term::BinaryOp(op, t1, t2) => self.fold_binary_op(loc, op, *t1, *t2),
+            Term::App(t1, t2) => self.fold_app(loc, *t1, *t2),
+            Term::Lit(lit) => self.fold_lit(loc, lit),
+            Term::List(elems) => self.fold_list(loc, elems),
+            Term::Cond(t1, t2, t3) => self.fold_cond(loc, *t1, *t2, *t3),
+            Term::Let(kind, name, t1, t2) => self.fold_let(loc, kind, name, *t1, *t2),
+            Term::LetRec(fns, t1) => self.fold_let_rec(loc, fns, *t1),
+            Term::Seq(t1, t2) => self.fold_seq(loc, *t1, *t2),
+            Term::Assign(name, t1) => self.fold_assign(loc, name, *t1),
+            Term::Ascription(t1, ty) => self.fold_ascription(loc, *t1, ty),
+            Term::Lazy(t1) => self.fold_lazy(loc, *t1),
+            Term::PrimFn(prim) => self.fold_prim_fn(loc, prim),
+            Term::Native(index, ty) => self.fold_native(loc, index, ty),
+            Term::Enum(name, variants, t1) => self.fold_enum(loc, name, variants, *t1),
+            Term::Constructor(name) => self.fold_constructor(loc, name),
+            Term::Match(t1, arms) => self.fold_match(loc, *t1, arms),
+        };
+        if folded.id.is_dummy() {
+            folded.with_id(id)
+        } else {
+            folded
+        }
+    }
+
+    /// Folds a Variable.
+    fn super_var(&mut self, loc: Location, name: Name<'a>) -> Located<Term<'a>> {
+        loc.with_content(Term::Var(name))
+    }
+
+    /// Folds an Abstraction.
+    fn super_abs(
+        &mut self,
+        loc: Location,
+        param: Located<Name<'a>>,
+        ty: Ty,
+        body: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let body = self.fold_term(body);
+        loc.with_content(Term::Abs(param, ty, Box::new(body)))
+    }
+
+    /// Folds a Unary operation.
+    fn super_unary_op(
+        &mut self,
+        loc: Location,
+        op: UnOp,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        loc.with_content(Term::UnaryOp(op, Box::new(t1)))
+    }
+
+    /// Folds a Binary operation.
+    fn super_binary_op(
+        &mut self,
+        loc: Location,
+        op: BinOp,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        loc.with_content(Term::BinaryOp(op, Box::new(t1), Box::new(t2)))
+    }
+
+    /// Folds an Application.
+    fn super_app(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        loc.with_content(Term::App(Box::new(t1), Box::new(t2)))
+    }
+
+    /// Folds a Literal.
+    fn super_lit(&mut self, loc: Location, lit: Literal) -> Located<Term<'a>> {
+        loc.with_content(Term::Lit(lit))
+    }
+
+    /// Folds a List literal.
+    fn super_list(&mut self, loc: Location, elems: Vec<Located<Term<'a>>>) -> Located<Term<'a>> {
+        let elems = elems.into_iter().map(|elem| self.fold_term(elem)).collect();
+        loc.with_content(Term::List(elems))
+    }
+
+    /// Folds a Conditional.
+    fn super_cond(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+        t3: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        let t3 = self.fold_term(t3);
+        loc.with_content(Term::Cond(Box::new(t1), Box::new(t2), Box::new(t3)))
+    }
+
+    /// Folds a Let binding.
+    fn super_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        loc.with_content(Term::Let(kind, name, Box::new(t1), Box::new(t2)))
+    }
+
+    /// Folds a group of mutually recursive functions.
+    fn super_let_rec(
+        &mut self,
+        loc: Location,
+        fns: Vec<Function<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let fns = fns
+            .into_iter()
+            .map(|(name, ty, body)| (name, ty, self.fold_term(body)))
+            .collect();
+        let t1 = self.fold_term(t1);
+        loc.with_content(Term::LetRec(fns, Box::new(t1)))
+    }
+
+    /// Folds a Sequence.
+    fn super_seq(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let t2 = self.fold_term(t2);
+        loc.with_content(Term::Seq(Box::new(t1), Box::new(t2)))
+    }
+
+    /// Folds an Assignment.
+    fn super_assign(
+        &mut self,
+        loc: Location,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        loc.with_content(Term::Assign(name, Box::new(t1)))
+    }
+
+    /// Folds a Type ascription.
+    fn super_ascription(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        ty: Located<Ty>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        loc.with_content(Term::Ascription(Box::new(t1), ty))
+    }
+
+    /// Folds a Lazy term.
+    fn super_lazy(&mut self, loc: Location, t1: Located<Term<'a>>) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        loc.with_content(Term::Lazy(Box::new(t1)))
+    }
+
+    /// Folds a Primitive function.
+    fn super_prim_fn(&mut self, loc: Location, prim: Primitive) -> Located<Term<'a>> {
+        loc.with_content(Term::PrimFn(prim))
+    }
+
+    /// Folds a Native function.
+    fn super_native(&mut self, loc: Location, index: usize, ty: Ty) -> Located<Term<'a>> {
+        loc.with_content(Term::Native(index, ty))
+    }
+
+    /// Folds an Enum declaration.
+    fn super_enum(
+        &mut self,
+        loc: Location,
+        name: Located<Name<'a>>,
+        variants: Vec<Variant<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        loc.with_content(Term::Enum(name, variants, Box::new(t1)))
+    }
+
+    /// Folds a Constructor.
+    fn super_constructor(&mut self, loc: Location, name: Name<'a>) -> Located<Term<'a>> {
+        loc.with_content(Term::Constructor(name))
+    }
+
+    /// Folds a Match.
+    fn super_match(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        arms: Vec<Arm<'a>>,
+    ) -> Located<Term<'a>> {
+        let t1 = self.fold_term(t1);
+        let arms = arms
+            .into_iter()
+            .map(|(pattern, body)| self.fold_arm(pattern, body))
+            .collect();
+        loc.with_content(Term::Match(Box::new(t1), arms))
+    }
+
+    /// Folds a single Arm of a Match.
+    fn super_arm(&mut self, pattern: Located<Pattern<'a>>, body: Located<Term<'a>>) -> Arm<'a> {
+        (pattern, self.fold_term(body))
+    }
+
+    /// Specifies how Terms should be folded.
+    fn fold_term(&mut self, term: Located<Term<'a>>) -> Located<Term<'a>> {
+        self.super_term(term)
+    }
+
+    /// Specifies how Variables should be folded.
+    fn fold_var(&mut self, loc: Location, name: Name<'a>) -> Located<Term<'a>> {
+        self.super_var(loc, name)
+    }
+
+    /// Specifies how Abstractions should be folded.
+    fn fold_abs(
+        &mut self,
+        loc: Location,
+        param: Located<Name<'a>>,
+        ty: Ty,
+        body: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_abs(loc, param, ty, body)
+    }
+
+    /// Specifies how Unary operations should be folded.
+    fn fold_unary_op(
+        &mut self,
+        loc: Location,
+        op: UnOp,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_unary_op(loc, op, t1)
+    }
+
+    /// Specifies how Binary operations should be folded.
+    fn fold_binary_op(
+        &mut self,
+        loc: Location,
+        op: BinOp,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_binary_op(loc, op, t1, t2)
+    }
+
+    /// Specifies how Applications should be folded.
+    fn fold_app(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_app(loc, t1, t2)
+    }
+
+    /// Specifies how Literals should be folded.
+    fn fold_lit(&mut self, loc: Location, lit: Literal) -> Located<Term<'a>> {
+        self.super_lit(loc, lit)
+    }
+
+    /// Specifies how List literals should be folded.
+    fn fold_list(&mut self, loc: Location, elems: Vec<Located<Term<'a>>>) -> Located<Term<'a>> {
+        self.super_list(loc, elems)
+    }
+
+    /// Specifies how Conditionals should be folded.
+    fn fold_cond(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+        t3: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_cond(loc, t1, t2, t3)
+    }
+
+    /// Specifies how Let bindings should be folded.
+    fn fold_let(
+        &mut self,
+        loc: Location,
+        kind: LetKind,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_let(loc, kind, name, t1, t2)
+    }
+
+    /// Specifies how groups of mutually recursive functions should be folded.
+    fn fold_let_rec(
+        &mut self,
+        loc: Location,
+        fns: Vec<Function<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_let_rec(loc, fns, t1)
+    }
+
+    /// Specifies how Sequences should be folded.
+    fn fold_seq(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        t2: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_seq(loc, t1, t2)
+    }
+
+    /// Specifies how Assignments should be folded.
+    fn fold_assign(
+        &mut self,
+        loc: Location,
+        name: Located<Name<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_assign(loc, name, t1)
+    }
+
+    /// Specifies how Type ascriptions should be folded.
+    fn fold_ascription(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        ty: Located<Ty>,
+    ) -> Located<Term<'a>> {
+        self.super_ascription(loc, t1, ty)
+    }
+
+    /// Specifies how Lazy terms should be folded.
+    fn fold_lazy(&mut self, loc: Location, t1: Located<Term<'a>>) -> Located<Term<'a>> {
+        self.super_lazy(loc, t1)
+    }
+
+    /// Specifies how Primitive functions should be folded.
+    fn fold_prim_fn(&mut self, loc: Location, prim: Primitive) -> Located<Term<'a>> {
+        self.super_prim_fn(loc, prim)
+    }
+
+    /// Specifies how Native functions should be folded.
+    fn fold_native(&mut self, loc: Location, index: usize, ty: Ty) -> Located<Term<'a>> {
+        self.super_native(loc, index, ty)
+    }
+
+    /// Specifies how Enum declarations should be folded.
+    fn fold_enum(
+        &mut self,
+        loc: Location,
+        name: Located<Name<'a>>,
+        variants: Vec<Variant<'a>>,
+        t1: Located<Term<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_enum(loc, name, variants, t1)
+    }
+
+    /// Specifies how Constructors should be folded.
+    fn fold_constructor(&mut self, loc: Location, name: Name<'a>) -> Located<Term<'a>> {
+        self.super_constructor(loc, name)
+    }
+
+    /// Specifies how Matches should be folded.
+    fn fold_match(
+        &mut self,
+        loc: Location,
+        t1: Located<Term<'a>>,
+        arms: Vec<Arm<'a>>,
+    ) -> Located<Term<'a>> {
+        self.super_match(loc, t1, arms)
+    }
+
+    /// Specifies how Arms should be folded.
+    fn fold_arm(&mut self, pattern: Located<Pattern<'a>>, body: Located<Term<'a>>) -> Arm<'a> {
+        self.super_arm(pattern, body)
+    }
+}
@@ -0,0 +1,162 @@
+//! Resolution of assignments.
+//!
+//! The syntax of an assignment, `x = x + 1`, is the same as the syntax of a let binding that
+//! shadows `x`. Since the parser cannot know which names are bound when parsing a node, every
+//! `name = node` is parsed as a [`Node::LetBind`], and the [`resolve`] function replaces the ones
+//! that are assignments by [`Node::Assign`] after parsing the whole program.
+//!
+//! A let binding without a type annotation is an assignment if the innermost binding of its name
+//! is mutable, or if it is inside a loop and its name is bound outside the loop. The latter are
+//! assignments even if the binding is not mutable, so the type checker can report them instead of
+//! shadowing a name that would be bound again in each iteration. Any other let binding shadows
+//! its name, and so do the `let ... in` expressions.
+use alloc::vec::Vec;
+use core::mem;
+
+use pijama_ast::{ty::Ty, Block, Branch, Literal, Located, Name, Node, Pattern};
+
+/// Replaces the let bindings of `block` that are assignments by [`Node::Assign`].
+pub(super) fn resolve(block: &mut Block<'_>) {
+    Resolver::default().resolve_block(block);
+}
+
+#[derive(Default)]
+struct Resolver<'a> {
+    /// The names bound in the current scope and the scopes around it, innermost last, with
+    /// whether they are mutable.
+    names: Vec<(Name<'a>, bool)>,
+    /// The number of names bound outside each loop around the current node, innermost last.
+    ///
+    /// Functions are not evaluated where they are defined, so this stack is emptied while
+    /// resolving their bodies.
+    loops: Vec<usize>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Returns `true` if binding `name` again in the current scope must be an assignment.
+    fn is_assignment(&self, name: Name<'a>) -> bool {
+        let outside = self.loops.last().copied().unwrap_or(0);
+        self.names
+            .iter()
+            .rposition(|(name2, _)| name == *name2)
+            .is_some_and(|index| self.names[index].1 || index < outside)
+    }
+
+    fn resolve_block(&mut self, block: &mut Block<'a>) {
+        let len = self.names.len();
+        for node in block.iter_mut() {
+            self.resolve_node(node);
+        }
+        self.names.truncate(len);
+    }
+
+    fn resolve_branch(&mut self, branch: &mut Branch<'a>) {
+        self.resolve_block(&mut branch.cond.content);
+        self.resolve_block(&mut branch.body.content);
+    }
+
+    /// Resolves the body of a function whose parameters are `params`.
+    fn resolve_fn(&mut self, params: impl Iterator<Item = Name<'a>>, body: &mut Block<'a>) {
+        let len = self.names.len();
+        let loops = mem::take(&mut self.loops);
+        self.names.extend(params.map(|param| (param, false)));
+        self.resolve_block(body);
+        self.loops = loops;
+        self.names.truncate(len);
+    }
+
+    fn resolve_node(&mut self, node: &mut Located<Node<'a>>) {
+        match &mut node.content {
+            Node::BinaryOp(_, node1, node2) => {
+                self.resolve_node(node1);
+                self.resolve_node(node2);
+            }
+            Node::Operator(_, node1, node2) => {
+                for node in node1.iter_mut().chain(node2) {
+                    self.resolve_node(node);
+                }
+            }
+            Node::UnaryOp(_, node)
+            | Node::Assign(_, node)
+            | Node::Ascription(node, _)
+            | Node::Lazy(node) => self.resolve_node(node),
+            Node::LetBind(annotation, value) => {
+                self.resolve_node(value);
+                let name = annotation.item;
+                if annotation.ty.content != Ty::Missing || !self.is_assignment(name.content) {
+                    self.names.push((name.content, false));
+                } else if let Node::LetBind(_, value) =
+                    mem::replace(&mut node.content, Node::Literal(Literal::Unit))
+                {
+                    node.content = Node::Assign(name, value);
+                }
+            }
+            Node::LetIn(annotation, value, body) => {
+                // the name is only bound inside the body.
+                self.resolve_node(value);
+                let len = self.names.len();
+                self.names.push((annotation.item.content, false));
+                self.resolve_node(body);
+                self.names.truncate(len);
+            }
+            Node::LetMut(annotation, value) => {
+                self.resolve_node(value);
+                self.names.push((annotation.item.content, true));
+            }
+            Node::Cond(if_branch, branches, el_blk) => {
+                self.resolve_branch(if_branch);
+                for branch in branches {
+                    self.resolve_branch(branch);
+                }
+                self.resolve_block(&mut el_blk.content);
+            }
+            Node::While(branch) => {
+                self.loops.push(self.names.len());
+                self.resolve_branch(branch);
+                self.loops.pop().unwrap();
+            }
+            Node::For(name, start, end, body) => {
+                self.resolve_node(start);
+                self.resolve_node(end);
+                self.loops.push(self.names.len());
+                self.names.push((name.content, false));
+                self.resolve_block(&mut body.content);
+                self.names.pop().unwrap();
+                self.loops.pop().unwrap();
+            }
+            Node::FnDef(name, args, body, _) => {
+                // The name of the function is visible inside its body.
+                self.names.push((name.content, false));
+                let params = args.iter().map(|arg| arg.item.content);
+                self.resolve_fn(params, &mut body.item.content);
+            }
+            Node::AnonFn(args, body) => {
+                let params = args.iter().map(|arg| arg.item.content);
+                self.resolve_fn(params, &mut body.item.content);
+            }
+            Node::Call(func, args) => {
+                self.resolve_node(func);
+                self.resolve_block(args);
+            }
+            Node::Block(block) => self.resolve_block(&mut block.content),
+            Node::List(elems) | Node::Interpolation(elems) => self.resolve_block(elems),
+            Node::EnumDef(_, variants) => {
+                let names = variants.iter().map(|variant| (variant.name.content, false));
+                self.names.extend(names);
+            }
+            Node::Match(node, arms) => {
+                self.resolve_node(node);
+                for arm in arms {
+                    let len = self.names.len();
+                    if let Pattern::Variant(_, binders) = &arm.pattern.content {
+                        let binders = binders.iter().map(|binder| (binder.content, false));
+                        self.names.extend(binders);
+                    }
+                    self.resolve_block(&mut arm.body.content);
+                    self.names.truncate(len);
+                }
+            }
+            Node::Literal(_) | Node::Name(_) | Node::PrimFn(_) | Node::Import(_) | Node::Error => {}
+        }
+    }
+}
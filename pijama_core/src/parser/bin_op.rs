@@ -11,7 +11,7 @@
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{char, space0},
+    character::complete::{char, one_of, space0},
     combinator::{map, not, peek},
     sequence::terminated,
 };
@@ -23,6 +23,18 @@ use crate::parser::{
     IResult,
 };
 
+/// Parser for the binary operators with precedence level 0.
+///
+/// The only operator with this precedence is `|>`.
+///
+/// All the binary operators might be surrounded by zero or more spaces.
+pub fn bin_op_0(input: Span) -> IResult<BinOp> {
+    surrounded(
+        with_context("Expected pipeline operator (|>)", map(tag("|>"), |_| Pipe)),
+        space0,
+    )(input)
+}
+
 /// Parser for the binary operators with precedence level 1.
 ///
 /// These operators are `&&` and `||`.
@@ -67,8 +79,8 @@ pub fn bin_op_2(input: Span) -> IResult<BinOp> {
 ///
 /// These operators are `&`, `|`, `^`, `>>` and `<<`.
 ///
-/// An additional check is done for `&` and `|` to be sure they are not the beginning of the `&&`
-/// and `||` operators.
+/// An additional check is done for `&` and `|` to be sure they are not the beginning of the `&&`,
+/// `||` and `|>` operators.
 ///
 /// All the binary operators might be surrounded by zero or more spaces.
 pub fn bin_op_3(input: Span) -> IResult<BinOp> {
@@ -77,7 +89,7 @@ pub fn bin_op_3(input: Span) -> IResult<BinOp> {
             "Expected binary operator (&, |, ^, <<, >>)",
             alt((
                 map(terminated(char('&'), peek(not(char('&')))), |_| BitAnd),
-                map(terminated(char('|'), peek(not(char('|')))), |_| BitOr),
+                map(terminated(char('|'), peek(not(one_of("|>")))), |_| BitOr),
                 map(char('^'), |_| BitXor),
                 map(tag(">>"), |_| Shr),
                 map(tag("<<"), |_| Shl),
@@ -120,3 +132,22 @@ pub fn bin_op_5(input: Span) -> IResult<BinOp> {
         space0,
     )(input)
 }
+
+/// Parser for the binary operators of any precedence level.
+///
+/// All the binary operators might be surrounded by zero or more spaces.
+pub fn bin_op(input: Span) -> IResult<BinOp> {
+    alt((bin_op_0, bin_op_1, bin_op_2, bin_op_3, bin_op_4, bin_op_5))(input)
+}
+
+/// Returns the precedence level of `op`, which is the `n` of the `bin_op_<n>` parser for it.
+pub fn precedence(op: BinOp) -> usize {
+    match op {
+        Pipe => 0,
+        And | Or => 1,
+        Lte | Gte | Lt | Gt | Eq | Neq => 2,
+        BitAnd | BitOr | BitXor | Shr | Shl => 3,
+        Add | Sub => 4,
+        Mul | Div | Rem => 5,
+    }
+}
@@ -4,21 +4,34 @@
 //!
 //! [`parser`]: crate::parser
 use nom::{
-    character::complete::{line_ending, multispace0},
-    combinator::{map, opt},
-    multi::{separated_list, separated_nonempty_list},
-    sequence::{preceded, tuple},
+    bytes::complete::tag,
+    character::complete::{line_ending, multispace0, multispace1},
+    combinator::{map, not, opt},
+    multi::{separated_list0, separated_list1},
+    sequence::{pair, preceded, tuple},
 };
 
 use nom_locate::position;
 
-use pijama_ast::{Block, Located, Location, Span};
+use pijama_ast::{Block, Located, Location, Node, Span};
 
 use crate::parser::{
     node::{comment, node},
     IResult,
 };
 
+/// Parser for a node at the start of a line of a block, after any number of spaces or line
+/// breaks.
+///
+/// The node cannot be a `do` block, since the `do` keyword at the start of a line belongs to the
+/// conditional or loop whose condition ends in the previous line.
+pub(super) fn line(input: Span) -> IResult<Located<Node>> {
+    preceded(
+        multispace0,
+        preceded(not(pair(tag("do"), multispace1)), node),
+    )(input)
+}
+
 /// Parser for [`Block`]s which may or may not be empty.
 ///
 /// Nodes in the block can be separated by at least one line break and optional spaces.
@@ -31,7 +44,7 @@ pub fn block0(input: Span) -> IResult<Located<Block>> {
     map(
         tuple((
             preceded(opt(comment::comment), position),
-            separated_list(line_ending, preceded(multispace0, node)),
+            separated_list0(line_ending, line),
             position,
         )),
         |(sp1, block, sp2)| {
@@ -52,7 +65,7 @@ pub fn block1(input: Span) -> IResult<Located<Block>> {
     map(
         tuple((
             preceded(opt(comment::comment), position),
-            separated_nonempty_list(line_ending, preceded(multispace0, node)),
+            separated_list1(line_ending, line),
             position,
         )),
         |(sp1, block, sp2)| {
@@ -2,11 +2,14 @@
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{char, multispace0, multispace1},
+    character::complete::{char, multispace0, multispace1, space0},
     combinator::{cut, map, peek},
     error::ParseError,
+    multi::separated_list0,
     sequence::{delimited, pair, preceded, terminated, tuple},
-    Compare, IResult, InputLength,
+    Compare,
+    Err::{Error, Failure},
+    IResult, InputLength,
 };
 use nom_locate::position;
 
@@ -14,15 +17,16 @@ use pijama_ast::{Located, Location, Span};
 
 use crate::parser::ParsingError;
 
-use std::fmt::Display;
+use alloc::{format, string::ToString, vec::Vec};
+use core::fmt::Display;
 
 /// Helper parser for expressions surrounded by a delimiter.
 ///
 /// The output only contains the expression without the delimiters.
 pub fn surrounded<I, O, O2, E: ParseError<I>>(
-    content: impl Fn(I) -> IResult<I, O, E>,
-    delimiter: impl Fn(I) -> IResult<I, O2, E> + Copy,
-) -> impl Fn(I) -> IResult<I, O, E> {
+    content: impl FnMut(I) -> IResult<I, O, E>,
+    delimiter: impl FnMut(I) -> IResult<I, O2, E> + Copy,
+) -> impl FnMut(I) -> IResult<I, O, E> {
     delimited(delimiter, content, delimiter)
 }
 
@@ -32,14 +36,14 @@ pub fn surrounded<I, O, O2, E: ParseError<I>>(
 /// spaces or line breaks between the actual content and the brackets.
 ///
 /// The location of this element starts in the `(` and ends in the `)`.
-pub fn in_brackets<'a, O: std::fmt::Debug, E: ParseError<Span<'a>>>(
-    content: impl Fn(Span<'a>) -> IResult<Span<'a>, O, E>,
-) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Located<O>, E> {
+pub fn in_brackets<'a, O: core::fmt::Debug, E: ParseError<Span<'a>>>(
+    content: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, E>,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Located<O>, E> {
     map(
         tuple((
             terminated(position, char('(')),
             surrounded(content, multispace0),
-            preceded(char(')'), position),
+            terminated(position, char(')')),
         )),
         |(sp1, content, sp2)| (Location::from(sp1) + Location::from(sp2)).with_content(content),
     )
@@ -54,24 +58,24 @@ pub fn in_brackets<'a, O: std::fmt::Debug, E: ParseError<Span<'a>>>(
 /// This is particularly useful when you are sure that there is only one expression that can be
 /// parsed after a certain hint.
 pub fn lookahead<'a, O, O2, E: ParseError<Span<'a>>>(
-    hint: impl Fn(Span<'a>) -> IResult<Span<'a>, O2, E>,
-    content: impl Fn(Span<'a>) -> IResult<Span<'a>, O, E>,
-) -> impl Fn(Span<'a>) -> IResult<Span<'a>, O, E> {
+    hint: impl FnMut(Span<'a>) -> IResult<Span<'a>, O2, E>,
+    content: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, E>,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O, E> {
     preceded(peek(hint), cut(content))
 }
 
 /// Helper parser for a keyword.
 ///
 /// This parses using the ['tag'] combinator to check for an expected keyword
-/// and provides additional context in case it is missing.
-pub fn keyword<'a, T: 'a>(
-    t: T,
-) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>, ParsingError<'a>>
+/// and records the keyword as the expected token in case it is missing.
+pub fn keyword<'a, T: 'a>(t: T) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>, ParsingError>
 where
     T: InputLength + Clone + Display,
     Span<'a>: Compare<T>,
 {
-    with_context(format!("Expected keyword {}.", t), tag(t))
+    let token = t.to_string();
+    let tag = tag(t);
+    move |i| tag(i).map_err(|e| e.map(|error: ParsingError| error.expecting(i, &[&token])))
 }
 
 /// Helper parser for a keyword with at least one following whitespace.
@@ -80,7 +84,7 @@ where
 /// and provides additional context in case the keyword or the whitespace is missing.
 pub fn keyword_space<'a, T: 'a>(
     t: T,
-) -> impl Fn(Span<'a>) -> IResult<Span<'a>, (Span<'a>, Span<'a>), ParsingError<'a>>
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (Span<'a>, Span<'a>), ParsingError>
 where
     T: InputLength + Clone + Display,
     Span<'a>: Compare<T>,
@@ -91,13 +95,59 @@ where
     )
 }
 
+/// Helper function to record the `tokens` expected by `inner` when it fails at the start of its
+/// input.
+pub fn expect<'a, O>(
+    tokens: &'static [&'static str],
+    mut inner: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, ParsingError>,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O, ParsingError> {
+    move |i| inner(i).map_err(|e| e.map(|error: ParsingError| error.expecting(i, tokens)))
+}
+
+/// Helper parser for a list of items separated by commas and delimited by `open` and `close`.
+///
+/// There can be spaces or line breaks after `open` and before `close`, and spaces before and
+/// spaces or line breaks after each comma. If `close` is missing after an item, the parser fails
+/// without backtracking and the error expects a comma or `close`.
+///
+/// The location of the returned vector starts in `open` and ends in `close`.
+pub fn comma_list<'a, O: core::fmt::Debug>(
+    open: char,
+    mut item: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, ParsingError>,
+    close: char,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Located<Vec<O>>, ParsingError> {
+    let close_token = close.to_string();
+    move |input| {
+        let (rest, start) = terminated(position, char(open))(input)?;
+        let (rest, items) = surrounded(
+            separated_list0(delimited(space0, char(','), multispace0), &mut item),
+            multispace0,
+        )(rest)?;
+        // The location of a span covers its first character, so the position is taken before
+        // `close` to end the list right after it.
+        let (rest, end) = match terminated(position, char::<_, ParsingError>(close))(rest) {
+            // Nothing but a comma or `close` can follow an item.
+            Err(Error(error)) if !items.is_empty() => {
+                return Err(Failure(error.expecting(rest, &[",", &close_token])))
+            }
+            result => result?,
+        };
+        let loc = Location::from(start) + Location::from(end);
+        Ok((rest, loc.with_content(items)))
+    }
+}
+
 /// Helper function to associate a context to the `ParsingError` when `inner` fails.
+///
+/// Unrecoverable errors that already have a context keep it, as it describes the error better than
+/// the context of the parsers around it.
 pub fn with_context<'a, O>(
     context: impl ToString,
-    inner: impl Fn(Span<'a>) -> IResult<Span<'a>, O, ParsingError<'a>>,
-) -> impl Fn(Span<'a>) -> IResult<Span<'a>, O, ParsingError<'a>> {
+    mut inner: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, ParsingError>,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O, ParsingError> {
     move |i| match inner(i) {
         Ok(o) => Ok(o),
+        Err(Failure(error)) if error.context.is_some() => Err(Failure(error)),
         Err(e) => Err(e.map(|error| ParsingError::with_context(i, context.to_string(), error))),
     }
 }
@@ -3,40 +3,141 @@
 //! The entry point for this module is the [`literal`] parser.
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{char, hex_digit1},
-    combinator::{map, map_opt, opt, value},
-    sequence::tuple,
+    bytes::complete::{tag, take_while},
+    character::complete::{char, digit1, hex_digit1, none_of, one_of},
+    combinator::{map, map_opt, opt, recognize, value, verify},
+    error::{ErrorKind, ParseError},
+    multi::fold_many0,
+    sequence::{pair, preceded, tuple},
+    Err::{Error, Failure},
 };
 use nom_locate::position;
 
-use pijama_ast::{Literal, Located, Location, Span};
+use pijama_ast::{Int, Literal, Located, Location, Span};
 
-use crate::parser::{helpers::with_context, IResult};
+use crate::parser::{helpers::with_context, IResult, ParsingError};
 
-use std::borrow::Cow;
+use alloc::{borrow::ToOwned, string::String};
+use core::num::IntErrorKind;
 
 /// Parses a [`Literal`](crate::ast::Literal).
 ///
-/// The only valid inputs for this parser are `"true"`, `"false"`, `"unit"` or a signed integer
-/// (which is parsed by the [`number`](number) parser).
+/// The only valid inputs for this parser are `"true"`, `"false"`, `"unit"`, a signed floating
+/// point number (which is parsed by the [`float`](float) parser), a signed integer (which is
+/// parsed by the [`number`](number) parser), a character (which is parsed by the
+/// [`character`](character) parser) or a string (which is parsed by the [`string`](string)
+/// parser).
 ///
 /// The location of this element matches the start and end of the inputs mentioned above inside the
 /// source code.
 pub fn literal(input: Span) -> IResult<Located<Literal>> {
     with_context(
-        "Expected literal (true, false, unit) or number",
+        "Expected literal (true, false, unit), number, character or string",
         alt((
             map(tag("true"), |span| Located::new(Literal::Bool(true), span)),
             map(tag("false"), |span| {
                 Located::new(Literal::Bool(false), span)
             }),
             map(tag("unit"), |span| Located::new(Literal::Unit, span)),
+            // Floats are parsed first so their integer part is not parsed as an integer.
+            map(float, |located_num| located_num.map(Literal::Float)),
             map(number, |located_num| located_num.map(Literal::Number)),
+            map(character, |located_char| located_char.map(Literal::Char)),
+            map(string, |located_str| located_str.map(Literal::Str)),
         )),
     )(input)
 }
 
+/// Parses a character between single quotes.
+///
+/// The character cannot be a newline. The escape sequences `\n`, `\'` and `\\` are replaced by a
+/// newline, a single quote and a backslash respectively, any other backslash is an error.
+///
+/// The location of this element matches the start and end quotes.
+fn character(input: Span) -> IResult<Located<char>> {
+    map(
+        tuple((
+            position,
+            preceded(char('\''), quoted_char('\'')),
+            position,
+            char('\''),
+        )),
+        |(start, c, end, _)| (Location::from(start) + end.into()).with_content(c),
+    )(input)
+}
+
+/// Parses a string between double quotes.
+///
+/// Strings cannot span several lines. The escape sequences `\n`, `\"`, `\\` and `\{` are replaced
+/// by a newline, a double quote, a backslash and an opening brace respectively, any other
+/// backslash is an error. An unescaped opening brace starts an interpolation, which is not parsed
+/// here.
+///
+/// The location of this element matches the start and end quotes.
+pub(crate) fn string(input: Span) -> IResult<Located<String>> {
+    map(
+        tuple((
+            position,
+            preceded(
+                char('"'),
+                fold_many0(quoted_char('"'), String::new, |mut string, c| {
+                    string.push(c);
+                    string
+                }),
+            ),
+            position,
+            char('"'),
+        )),
+        |(start, string, end, _)| (Location::from(start) + end.into()).with_content(string),
+    )(input)
+}
+
+/// Returns a parser for a character of a literal delimited by `quote`, replacing escape sequences.
+///
+/// Opening braces start an interpolation inside strings, so they must be escaped there.
+pub(crate) fn quoted_char(quote: char) -> impl FnMut(Span) -> IResult<char> {
+    let is_string = quote == '"';
+    move |input| {
+        alt((
+            verify(none_of("\\\n"), |c| {
+                *c != quote && !(is_string && *c == '{')
+            }),
+            preceded(
+                char('\\'),
+                alt((
+                    value('\n', char('n')),
+                    value(quote, char(quote)),
+                    value('\\', char('\\')),
+                    value('{', verify(char('{'), |_| is_string)),
+                )),
+            ),
+        ))(input)
+    }
+}
+
+/// Parses a signed floating point number.
+///
+/// A floating point number is written in decimal notation and has a fractional part, an exponent
+/// or both, like `1.5`, `2e10` or `-1.5e-3`. As with integers, there cannot be spaces between the
+/// minus sign and the digits of the number.
+fn float(input: Span) -> IResult<Located<f64>> {
+    let exponent = || recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)));
+    map_opt(
+        recognize(tuple((
+            opt(char('-')),
+            digit1,
+            alt((
+                recognize(pair(preceded(char('.'), digit1), opt(exponent()))),
+                exponent(),
+            )),
+        ))),
+        |span: Span| {
+            let number = span.fragment().parse().ok()?;
+            Some(Located::new(number, span))
+        },
+    )(input)
+}
+
 /// Determines the radix of the next number in the input.
 ///
 /// Tries to parse a number prefix if possible otherwise it assumes 10 for decimal numbers.
@@ -51,8 +152,8 @@ fn number_radix(input: Span) -> IResult<u32> {
 
 /// Parses a signed integer.
 ///
-/// This integer must be in the valid range for the `i64` type. If the number is outside this
-/// range, the parser will return an error.
+/// This integer must be in the valid range for the [`Int`] type. If the number is outside this
+/// range, the parser will fail with an error located at the start of the number.
 ///
 /// If the number is negative, there cannot be spaces between the minus sign and the digits of the
 /// number. That kind of expression will be parsed as an unary operation.
@@ -62,24 +163,51 @@ fn number_radix(input: Span) -> IResult<u32> {
 /// * binary `0b`
 /// * octal `0o`
 /// * hexadecimal `0x`
-fn number(input: Span) -> IResult<Located<i64>> {
-    map_opt(
-        tuple((position, opt(char('-')), number_radix, hex_digit1)),
-        |(position, sign, radix, digits_span): (Span, Option<char>, u32, Span)| {
-            let number = if sign.is_some() {
-                // Create a string with enough capacity for the number plus the sign to avoid unnecessary allocations when prepending the sign
-                // This allows using the whole range of i64 numbers without handling the i64::min() case ourselves
-                let mut number = String::with_capacity(digits_span.fragment().len() + 1);
-                number.push('-');
-                number.push_str(digits_span.fragment());
-                Cow::from(number)
-            } else {
-                Cow::from(*digits_span.fragment())
-            };
-            let number = i64::from_str_radix(&number, radix).ok()?;
-            let loc = Location::from(position) + digits_span.into();
+///
+/// The digits of a number can be separated by underscores, like `1_000_000` or `0xFF_FF`, which
+/// are ignored.
+fn number(input: Span) -> IResult<Located<Int>> {
+    let (rem, (position, sign, radix, digits_span)) = tuple((
+        position,
+        opt(char('-')),
+        number_radix,
+        recognize(pair(
+            hex_digit1,
+            take_while(|c: char| c.is_ascii_hexdigit() || c == '_'),
+        )),
+    ))(input)?;
 
-            Some(loc.with_content(number))
-        },
-    )(input)
+    // Create a string with enough capacity for the number plus the sign to avoid unnecessary
+    // allocations when prepending the sign. This allows using the whole range of `Int` numbers
+    // without handling the `Int::MIN` case ourselves
+    let mut number = String::with_capacity(digits_span.fragment().len() + 1);
+    if sign.is_some() {
+        number.push('-');
+    }
+    number.extend(digits_span.fragment().chars().filter(|c| *c != '_'));
+
+    match Int::from_str_radix(&number, radix) {
+        Ok(number) => {
+            let loc = Location::from(position) + digits_span.into();
+            Ok((rem, loc.with_content(number)))
+        }
+        // A number out of range cannot be parsed as anything else, so the error is not
+        // recoverable.
+        Err(error)
+            if matches!(
+                error.kind(),
+                IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+            ) =>
+        {
+            Err(Failure(ParsingError::with_context(
+                input,
+                "Integer literal out of range for type Int".to_owned(),
+                ParsingError::from_error_kind(position, ErrorKind::TooLarge),
+            )))
+        }
+        Err(_) => Err(Error(ParsingError::from_error_kind(
+            input,
+            ErrorKind::MapOpt,
+        ))),
+    }
 }
@@ -9,7 +9,8 @@
 //! discussed in the documentation of each parser.
 //!
 //! The main entry point of this module is the [`parse`] function, which parses the source code in
-//! a string slice as a [`Block`].
+//! a string slice as a [`Block`]. The [`parse_recovering`] function parses programs with errors
+//! too, replacing the statements that cannot be parsed by error nodes.
 //!
 //! The whole parser is written in nom, if you have any doubts about the behavior of certain
 //! parsing combinators after reading this documentation, the [nom docs] are a good place to start.
@@ -21,102 +22,242 @@
 //!
 //! [ABNF]: https://en.wikipedia.org/wiki/Augmented_Backus–Naur_form
 //! [nom docs]: https://docs.rs/nom/
-use thiserror::Error;
-
 use nom::{
     character::complete::multispace0,
     combinator::all_consuming,
-    error::{ErrorKind, ParseError},
+    error::{ContextError, ErrorKind, ParseError},
     lib::std::fmt::Formatter,
     Err::*,
 };
 
-use pijama_ast::{Block, Located, Span};
+use pijama_ast::{node_id::number_nodes, ty::Ty, Block, Located, Location, Span};
+
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 use block::block0;
 use helpers::surrounded;
 
-use std::{fmt, fmt::Display};
+pub use recover::parse_recovering;
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, fmt::Display};
 
+mod assign;
 mod bin_op;
 mod block;
 mod helpers;
 mod literal;
-mod name;
+pub(crate) mod name;
 mod node;
-mod primitive;
+mod recover;
 mod ty;
 mod un_op;
 
-type IResult<'a, T> = nom::IResult<Span<'a>, T, ParsingError<'a>>;
+type IResult<'a, T> = nom::IResult<Span<'a>, T, ParsingError>;
 
 /// Produces a [`Block`] from a string slice.
 ///
 /// This function fails if the whole string is not consumed during parsing or if there is an error
-/// with the inner parsers.
-pub fn parse(input: &str) -> Result<Located<Block>, ParsingError<'_>> {
+/// with the inner parsers. The let bindings of the returned block that are assignments are
+/// replaced by [`Node::Assign`], see the [`assign`] module. Its nodes are numbered with
+/// [`number_nodes`].
+///
+/// [`Node::Assign`]: pijama_ast::Node::Assign
+/// [`assign`]: crate::parser::assign
+pub fn parse(input: &str) -> Result<Located<Block>, ParsingError> {
     let span = Span::new(input);
     let result: IResult<Located<Block>> = all_consuming(surrounded(block0, multispace0))(span);
     match result {
-        Ok((_, block)) => Ok(block),
+        Ok((_, mut block)) => {
+            assign::resolve(&mut block.content);
+            number_nodes(&mut block.content);
+            Ok(block)
+        }
         Err(Error(e)) | Err(Failure(e)) => Err(e),
         _ => unreachable!(),
     }
 }
 
-#[derive(Error, Debug, Eq, PartialEq)]
-pub struct ParsingError<'a> {
-    pub span: Span<'a>,
+/// Produces a [`Ty`] of the AST from a string slice with a type written as in a type annotation.
+///
+/// This function fails if the whole string, except for spaces and line breaks around the type, is
+/// not consumed during parsing.
+pub(crate) fn parse_ty(input: &str) -> Result<Located<Ty>, ParsingError> {
+    let span = Span::new(input);
+    let result: IResult<Located<Ty>> = all_consuming(surrounded(ty::ty, multispace0))(span);
+    match result {
+        Ok((_, ty)) => Ok(ty),
+        Err(Error(e)) | Err(Failure(e)) => Err(e),
+        _ => unreachable!(),
+    }
+}
+
+/// An error found while parsing.
+///
+/// The error does not borrow the input, it only keeps the position where the parser failed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParsingError {
+    loc: Location,
+    line: u32,
     kind: ErrorKind,
     context: Option<String>,
+    expected: Vec<String>,
 }
 
-impl<'a> Display for ParsingError<'a> {
+impl Display for ParsingError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "Parsing error: ")?;
-        match self.context.as_ref() {
-            Some(context) => write!(f, "{}", context),
-            None => write!(f, "Parsing rule `{:?}` failed.", self.kind),
+        match (self.expected.split_last(), self.context.as_ref()) {
+            (Some((last, [])), _) => write!(f, "expected `{}`.", last),
+            (Some((last, expected)), _) => {
+                write!(f, "expected ")?;
+                for (index, token) in expected.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { ", " };
+                    write!(f, "{}`{}`", separator, token)?;
+                }
+                write!(f, " or `{}`.", last)
+            }
+            (None, Some(context)) => write!(f, "{}", context),
+            (None, None) => write!(f, "Parsing rule `{:?}` failed.", self.kind),
         }
     }
 }
 
-impl<'a> ParsingError<'a> {
-    pub fn with_context(_: Span<'a>, context: String, other: Self) -> Self {
+#[cfg(feature = "std")]
+impl std::error::Error for ParsingError {}
+
+/// Parsing errors can only be serialized, since the parsing rules that fail are not serializable.
+///
+/// The serialized error has the location and line where the error happened, the name of the
+/// parsing rule that failed, its context and the tokens that were expected.
+#[cfg(feature = "serde")]
+impl Serialize for ParsingError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ParsingError", 5)?;
+        state.serialize_field("loc", &self.loc)?;
+        state.serialize_field("line", &self.line)?;
+        state.serialize_field("kind", &format!("{:?}", self.kind))?;
+        state.serialize_field("context", &self.context)?;
+        state.serialize_field("expected", &self.expected)?;
+        state.end()
+    }
+}
+
+impl ParsingError {
+    fn new(span: Span<'_>, kind: ErrorKind, context: Option<String>) -> Self {
+        ParsingError {
+            loc: span.into(),
+            line: span.location_line(),
+            kind,
+            context,
+            expected: Vec::new(),
+        }
+    }
+
+    /// Returns the stable code of the error.
+    ///
+    /// All parsing errors share the same code.
+    pub fn code(&self) -> &'static str {
+        "E0001"
+    }
+
+    /// Returns the location where the parser failed.
+    pub fn loc(&self) -> Location {
+        self.loc
+    }
+
+    /// Returns the line, starting at `1`, where the parser failed.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the name of the parsing rule that failed.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns a description of what the parser expected, if there is one.
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    /// Returns the tokens that the parser expected where it failed, without repetitions.
+    ///
+    /// Errors with a context that describes what was expected usually have no tokens.
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    /// Replaces the context of the error, along with the tokens it expected, since the context
+    /// describes the error better than the parsers inside the one that has it.
+    pub fn with_context(_: Span<'_>, context: String, other: Self) -> Self {
         ParsingError {
-            span: other.span,
-            kind: other.kind,
             context: Some(context),
+            expected: Vec::new(),
+            ..other
         }
     }
-}
 
-impl<'a> ParseError<Span<'a>> for ParsingError<'a> {
-    fn from_error_kind(span: Span<'a>, kind: ErrorKind) -> Self {
+    /// Replaces the tokens expected by the error if it happened at the start of `span`. Errors
+    /// found after the start of `span` are kept as they are.
+    fn expecting(self, span: Span<'_>, tokens: &[&str]) -> Self {
+        if self.loc.start != span.location_offset() {
+            return self;
+        }
         ParsingError {
-            span,
-            kind,
-            context: None,
+            expected: tokens.iter().map(ToString::to_string).collect(),
+            ..self
         }
     }
+}
+
+impl<'a> ParseError<Span<'a>> for ParsingError {
+    fn from_error_kind(span: Span<'a>, kind: ErrorKind) -> Self {
+        ParsingError::new(span, kind, None)
+    }
 
     fn append(_: Span<'a>, _: ErrorKind, other: Self) -> Self {
         other
     }
 
     fn from_char(span: Span<'a>, c: char) -> Self {
+        let context = format!("Expected character '{}'.", c);
         ParsingError {
-            span,
-            kind: ErrorKind::Char,
-            context: Some(format!("Expected character '{}'.", c)),
+            expected: vec![c.to_string()],
+            ..ParsingError::new(span, ErrorKind::Char, Some(context))
+        }
+    }
+
+    /// Alternatives that fail at the same position and expect some tokens expect any of them.
+    /// Otherwise, the error of the last alternative is kept.
+    fn or(self, other: Self) -> Self {
+        if self.loc.start != other.loc.start
+            || self.expected.is_empty()
+            || other.expected.is_empty()
+        {
+            return other;
+        }
+        let mut expected = self.expected;
+        for token in other.expected {
+            if !expected.contains(&token) {
+                expected.push(token);
+            }
         }
+        ParsingError { expected, ..other }
     }
+}
 
+impl<'a> ContextError<Span<'a>> for ParsingError {
     fn add_context(_: Span<'a>, context: &'static str, other: Self) -> Self {
         ParsingError {
-            span: other.span,
-            kind: other.kind,
             context: Some(context.to_string()),
+            ..other
         }
     }
 }
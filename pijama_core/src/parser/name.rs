@@ -1,21 +1,35 @@
 //! Parsers for names.
 //!
-//! The entry point for this module is the [`name`] function. Names of variables in Pijama must be
-//! alphabetic `snake_case` strings. Certain keywords such as `fn`, `do` and `end` and primitive
-//! functions cannot be names, these are listed in the [`KEYWORDS`] or [`PRIMITIVES`] constants.
+//! The entry point for this module is the [`name`] function. Names of variables in Pijama are
+//! Unicode identifiers, which start with a character with the `XID_Start` property and continue
+//! with characters with the `XID_Continue` property, like `snake_case`, `área` or `λ`. Certain
+//! keywords such as `fn`, `do` and `end`, listed in the [`KEYWORDS`] constant, cannot be names.
+//! The names of primitive functions are ordinary names that can be bound again by the program.
+//!
+//! Names defined in other modules are referenced with the [`qualified_name`] parser, which
+//! follows the rule
+//!
+//! ```abnf
+//! qualified_name = name ("::" name)*
+//! ```
 use nom::{
-    character::complete::{alpha1, char},
+    bytes::complete::{tag, take_while},
+    character::complete::anychar,
     combinator::{map, recognize, verify},
-    multi::separated_nonempty_list,
+    multi::separated_list1,
+    sequence::pair,
 };
+use unicode_xid::UnicodeXID;
 
-use pijama_ast::{Located, Name, Span};
+use pijama_ast::{Located, Location, Name, Span};
 
-use crate::parser::{primitive::PRIMITIVES, IResult};
+use crate::parser::IResult;
 
 /// Words that cannot be names to avoid ambiguities.
-const KEYWORDS: &[&str] = &[
-    "fn", "rec", "do", "end", "if", "elif", "else", "true", "false", "unit", "Bool", "Int", "Unit",
+pub(crate) const KEYWORDS: &[&str] = &[
+    "fn", "rec", "do", "end", "if", "elif", "else", "enum", "match", "with", "import", "while",
+    "for", "in", "let", "mut", "lazy", "true", "false", "unit", "Bool", "Int", "Float", "Unit",
+    "Char", "String",
 ];
 
 /// Parser for [`Name`]s.
@@ -26,11 +40,31 @@ const KEYWORDS: &[&str] = &[
 ///
 /// The location of this element matches the start and end of its string slice in the source code.
 pub fn name(input: Span) -> IResult<Located<Name>> {
-    verify(
-        map(
-            recognize(separated_nonempty_list(char('_'), alpha1)),
-            |span: Span| Located::new(Name(span.fragment()), span),
-        ),
-        |name| !KEYWORDS.contains(&name.content.0) && !PRIMITIVES.contains_key(&name.content.0),
-    )(input)
+    verify(map(word, located), |name| {
+        !KEYWORDS.contains(&name.content.0)
+    })(input)
+}
+
+/// Parses a Unicode identifier, which can be a name or a keyword.
+pub(crate) fn word(input: Span) -> IResult<Span> {
+    recognize(pair(
+        verify(anychar, |c| c.is_xid_start()),
+        take_while(UnicodeXID::is_xid_continue),
+    ))(input)
+}
+
+/// Parser for [`Name`]s that can be qualified by the namespace of the module defining them, like
+/// `geometry::area`.
+///
+/// Each segment of a qualified name must be a valid name. The location of this element matches
+/// the start of its first segment and the end of its last segment.
+pub fn qualified_name(input: Span) -> IResult<Located<Name>> {
+    map(recognize(separated_list1(tag("::"), name)), located)(input)
+}
+
+/// Returns the name in `span`, located at all of its bytes so the location does not end in the
+/// middle of a multi-byte character.
+fn located(span: Span) -> Located<Name> {
+    let start = span.location_offset();
+    Location::new(start, start + span.fragment().len()).with_content(Name(span.fragment()))
 }
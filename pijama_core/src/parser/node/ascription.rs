@@ -0,0 +1,56 @@
+//! Parsers for nodes in brackets and type ascriptions.
+//!
+//! The entry point for this module is the [`bracketed`] function. Nodes in brackets are parsed
+//! following the rule
+//!
+//! ```abnf
+//! bracketed = "(" node (":" ty)? ")"
+//! ```
+//!
+//! Meaning that a node in brackets can state its type, like `([] : [Int])`. The brackets are
+//! required so the colon cannot be confused with the type annotation of a let binding.
+//!
+//! Sections whose second operand is missing, like `(x + _)`, are parsed here too, as explained in
+//! the [`operator`] module.
+use nom::{
+    character::complete::{char, space0},
+    combinator::{cut, opt},
+    sequence::preceded,
+};
+
+use alloc::boxed::Box;
+
+use pijama_ast::{Located, Node, Span};
+
+use crate::parser::{
+    helpers::{in_brackets, surrounded},
+    node::{node, operator},
+    ty::ty,
+    IResult,
+};
+
+/// Parses a node in brackets, returning a [`Node::Ascription`] if it is followed by a type.
+///
+/// There can be any number of spaces surrounding the `:`, including no spaces at all.
+///
+/// A node followed by an operator and a `_` is a section, which returns a [`Node::Operator`] as
+/// explained in the [`operator`] module.
+///
+/// The location of the returned node starts in the `(` and ends in the `)`.
+pub fn bracketed(input: Span) -> IResult<Located<Node>> {
+    in_brackets(content)(input)
+}
+
+fn content(input: Span) -> IResult<Node> {
+    let (rem, node) = node(input)?;
+    if let (rem, Some(ty)) = opt(preceded(surrounded(char(':'), space0), cut(ty)))(rem)? {
+        return Ok((rem, Node::Ascription(Box::new(node), ty)));
+    }
+    if let (rem, Some(op)) = opt(operator::section_end)(rem)? {
+        if !operator::is_operand(&node, op, true) {
+            return Err(operator::operand_error(input));
+        }
+        return Ok((rem, Node::Operator(op, Some(Box::new(node)), None)));
+    }
+    Ok((rem, node.content))
+}
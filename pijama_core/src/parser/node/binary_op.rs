@@ -10,7 +10,8 @@
 //!
 //! ```abnf
 //! node = binary_op / unary_op / let_bind / cond / fn_def / fn_rec_def / call
-//! binary_op = binary_op_1 bin_op_1 binary_op_1
+//! binary_op = binary_op_0 bin_op_0 binary_op_0
+//! binary_op_0 = binary_op_1 bin_op_1 binary_op_1
 //! binary_op_1 = binary_op_2 bin_op_2 binary_op_2
 //! binary_op_2 = binary_op_3 bin_op_3 binary_op_3
 //! binary_op_3 = binary_op_4 bin_op_4 binary_op_4
@@ -22,7 +23,8 @@
 //!
 //! ```abnf
 //! node = binary_op
-//! binary_op = binary_op_1 (bin_op_1 binary_op_1)*
+//! binary_op = binary_op_0 (bin_op_0 binary_op_0)*
+//! binary_op_0 = binary_op_1 (bin_op_1 binary_op_1)*
 //! binary_op_1 = binary_op_2 (bin_op_2 binary_op_2)*
 //! binary_op_2 = binary_op_3 (bin_op_3 binary_op_3)*
 //! binary_op_3 = binary_op_4 (bin_op_4 binary_op_4)*
@@ -36,25 +38,49 @@
 //! Every binary operator here is considered to be left-associative, in contrast with the `->` for
 //! in the [`ty`] module which is right-associative.
 //!
+//! A binary operation ends before an operator followed by a `_` and a `)`, which is the end of a
+//! section as explained in the [`operator`] module.
+//!
 //! The location of the returned binary operations matches the start of the first operand and the
 //! end of the second.
 //!
 //! [`ty`]: crate::parser::ty
 //! [`node`]: crate::parser::node::node
 //! [`bin_op`]: crate::parser::bin_op
+//! [`operator`]: crate::parser::node::operator
 use nom::{
-    combinator::{cut, opt},
-    sequence::pair,
+    combinator::{cut, not, opt},
+    sequence::{pair, terminated},
 };
 
+use alloc::boxed::Box;
+
 use pijama_ast::{Located, Node, Span};
 
-use crate::parser::{bin_op::*, node::base_node, IResult};
+use crate::parser::{
+    bin_op::*,
+    node::{base_node, operator::hole},
+    IResult,
+};
 
 /// Parses a [`Node::BinaryOp`].
 pub fn binary_op(input: Span) -> IResult<Located<Node>> {
+    let (mut input, mut node) = binary_op_0(input)?;
+    while let (rem, Some((op, node2))) =
+        opt(pair(terminated(bin_op_0, not(hole)), cut(binary_op_0)))(input)?
+    {
+        input = rem;
+        let loc = node.loc + node2.loc;
+        node = Located::new(Node::BinaryOp(op, Box::new(node), Box::new(node2)), loc);
+    }
+    Ok((input, node))
+}
+
+fn binary_op_0(input: Span) -> IResult<Located<Node>> {
     let (mut input, mut node) = binary_op_1(input)?;
-    while let (rem, Some((op, node2))) = opt(pair(bin_op_1, cut(binary_op_1)))(input)? {
+    while let (rem, Some((op, node2))) =
+        opt(pair(terminated(bin_op_1, not(hole)), cut(binary_op_1)))(input)?
+    {
         input = rem;
         let loc = node.loc + node2.loc;
         node = Located::new(Node::BinaryOp(op, Box::new(node), Box::new(node2)), loc);
@@ -64,7 +90,9 @@ pub fn binary_op(input: Span) -> IResult<Located<Node>> {
 
 fn binary_op_1(input: Span) -> IResult<Located<Node>> {
     let (mut input, mut node) = binary_op_2(input)?;
-    while let (rem, Some((op, node2))) = opt(pair(bin_op_2, cut(binary_op_2)))(input)? {
+    while let (rem, Some((op, node2))) =
+        opt(pair(terminated(bin_op_2, not(hole)), cut(binary_op_2)))(input)?
+    {
         input = rem;
         let loc = node.loc + node2.loc;
         node = Located::new(Node::BinaryOp(op, Box::new(node), Box::new(node2)), loc);
@@ -74,7 +102,9 @@ fn binary_op_1(input: Span) -> IResult<Located<Node>> {
 
 fn binary_op_2(input: Span) -> IResult<Located<Node>> {
     let (mut input, mut node) = binary_op_3(input)?;
-    while let (rem, Some((op, node2))) = opt(pair(bin_op_3, cut(binary_op_3)))(input)? {
+    while let (rem, Some((op, node2))) =
+        opt(pair(terminated(bin_op_3, not(hole)), cut(binary_op_3)))(input)?
+    {
         input = rem;
         let loc = node.loc + node2.loc;
         node = Located::new(Node::BinaryOp(op, Box::new(node), Box::new(node2)), loc);
@@ -84,7 +114,9 @@ fn binary_op_2(input: Span) -> IResult<Located<Node>> {
 
 fn binary_op_3(input: Span) -> IResult<Located<Node>> {
     let (mut input, mut node) = binary_op_4(input)?;
-    while let (rem, Some((op, node2))) = opt(pair(bin_op_4, cut(binary_op_4)))(input)? {
+    while let (rem, Some((op, node2))) =
+        opt(pair(terminated(bin_op_4, not(hole)), cut(binary_op_4)))(input)?
+    {
         input = rem;
         let loc = node.loc + node2.loc;
         node = Located::new(Node::BinaryOp(op, Box::new(node), Box::new(node2)), loc);
@@ -94,7 +126,9 @@ fn binary_op_3(input: Span) -> IResult<Located<Node>> {
 
 fn binary_op_4(input: Span) -> IResult<Located<Node>> {
     let (mut input, mut node) = base_node(input)?;
-    while let (rem, Some((op, node2))) = opt(pair(bin_op_5, cut(base_node)))(input)? {
+    while let (rem, Some((op, node2))) =
+        opt(pair(terminated(bin_op_5, not(hole)), cut(base_node)))(input)?
+    {
         input = rem;
         let loc = node.loc + node2.loc;
         node = Located::new(Node::BinaryOp(op, Box::new(node), Box::new(node2)), loc);
@@ -4,17 +4,20 @@
 //! the rule
 //!
 //! ```abnf
-//! call = "(name / "(" node ")") "(" (node ("," node)*)? ")"
+//! call = (qualified_name / operator / bracketed) "(" (node ("," node)*)? ")"
 //! ```
+//!
+//! where `operator` is an operator used as a function as explained in the [`operator`] module and
+//! `bracketed` is a node in brackets as explained in the [`ascription`] module.
 use nom::{branch::alt, character::complete::space0, combinator::map, sequence::separated_pair};
 
+use alloc::boxed::Box;
+
 use pijama_ast::{Located, Node, Span};
 
 use crate::parser::{
-    helpers::in_brackets,
-    name::name,
-    node::{fn_def::args, node},
-    primitive::primitive,
+    name::qualified_name,
+    node::{ascription, fn_def::args, node, operator},
     IResult,
 };
 
@@ -28,12 +31,9 @@ use crate::parser::{
 /// the `=`.
 pub fn call(input: Span) -> IResult<Located<Node>> {
     let func = alt((
-        map(name, |located_name| located_name.map(Node::Name)),
-        map(primitive, |located_prim| located_prim.map(Node::PrimFn)),
-        map(in_brackets(node), |Located { mut content, loc }| {
-            content.loc = loc;
-            content
-        }),
+        map(qualified_name, |located_name| located_name.map(Node::Name)),
+        operator::operator,
+        ascription::bracketed,
     ));
     map(separated_pair(func, space0, args(node)), |(func, args)| {
         let loc = func.loc + args.loc;
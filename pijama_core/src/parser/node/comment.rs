@@ -1,19 +1,65 @@
 //! Parsers for comments.
 //!
 //! The entry point for this module is the [`comment`] function.
-//! Single-line comments are denoted with the `#` character.
+//! Single-line comments are denoted with the `#` character. Block comments start with `/*`, end
+//! with `*/` and can span several lines and contain other block comments.
+//!
+//! Lines starting with `##` are not comments but doc comments, which are parsed by the
+//! [`doc_comment`] function and must be followed by a function definition.
 
 use nom::{
-    character::complete::{char, line_ending, not_line_ending},
-    combinator::value,
-    sequence::delimited,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{anychar, char, line_ending, not_line_ending},
+    combinator::{cut, map, not, value},
+    multi::many0_count,
+    sequence::{delimited, pair, preceded, terminated},
 };
 
 use pijama_ast::Span;
 
-use crate::parser::IResult;
+use crate::parser::{helpers::with_context, IResult};
 
 /// Parses a comment, returning () if it finds one.
 pub fn comment(input: Span) -> IResult<()> {
-    value((), delimited(char('#'), not_line_ending, line_ending))(input)
+    alt((
+        value(
+            (),
+            delimited(
+                pair(char('#'), not(char('#'))),
+                not_line_ending,
+                line_ending,
+            ),
+        ),
+        block_comment,
+    ))(input)
+}
+
+/// Parses a doc comment line, returning its text without the `##` and the space after it.
+pub fn doc_comment<'a>(input: Span<'a>) -> IResult<'a, &'a str> {
+    map(
+        preceded(tag("##"), terminated(not_line_ending, line_ending)),
+        |text: Span| {
+            let text = *text.fragment();
+            text.strip_prefix(' ').unwrap_or(text)
+        },
+    )(input)
+}
+
+/// Parses a block comment, returning () if it finds one.
+///
+/// Each `/*` inside the comment must be closed by its own `*/`, so commenting out code that
+/// already has block comments does not end the comment early.
+fn block_comment(input: Span) -> IResult<()> {
+    value(
+        (),
+        delimited(
+            tag("/*"),
+            many0_count(alt((
+                block_comment,
+                value((), preceded(not(alt((tag("/*"), tag("*/")))), anychar)),
+            ))),
+            cut(with_context("Unterminated block comment", tag("*/"))),
+        ),
+    )(input)
 }
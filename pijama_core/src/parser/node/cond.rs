@@ -4,14 +4,15 @@
 //! rule
 //!
 //! ```abnf
-//! cond = "if" block1 "do" block1 ("elif" block1 "do" block1)* "else" block1 "end"
+//! cond = "if" block1 "do" block1 ("elif" block1 "do" block1)* ("else" block1)? "end"
 //! ```
 //!
 //! Thus, `elif` blocks are optional and are represented as empty [`Block`]s inside the
-//! [`Node::Cond`] variant.
+//! [`Node::Cond`] variant. The `else` block is optional too, a missing one is represented as an
+//! empty [`Block`] located right before the `end`, which evaluates to `unit`.
 use nom::{
     character::complete::multispace0,
-    combinator::map,
+    combinator::{map, opt},
     multi::many0,
     sequence::{delimited, pair, preceded, tuple},
 };
@@ -36,10 +37,15 @@ pub fn cond(input: Span) -> IResult<Located<Node>> {
             position,
             branch("if"),
             many0(branch("elif")),
-            keyword_block("else"),
+            opt(keyword_block("else")),
+            position,
             preceded(keyword("end"), position),
         )),
-        move |(sp1, if_branch, branches, else_block, sp2)| {
+        move |(sp1, if_branch, branches, else_block, end, sp2)| {
+            let else_block = else_block.unwrap_or_else(|| {
+                let offset = end.location_offset();
+                Located::new(Block::new(), Location::new(offset, offset))
+            });
             Located::new(
                 Node::Cond(if_branch, branches, else_block),
                 Location::from(sp1) + Location::from(sp2),
@@ -49,7 +55,7 @@ pub fn cond(input: Span) -> IResult<Located<Node>> {
 }
 
 /// Parses a `Branch` representing either an "if do" conditional statement or an "elif do" statement.
-fn branch<'a>(keyword: &'a str) -> impl Fn(Span<'a>) -> IResult<Branch<'a>> {
+fn branch<'a>(keyword: &'a str) -> impl FnMut(Span<'a>) -> IResult<Branch<'a>> {
     map(
         pair(keyword_block(keyword), keyword_block("do")),
         |(blk1, blk2)| Branch {
@@ -65,6 +71,6 @@ fn branch<'a>(keyword: &'a str) -> impl Fn(Span<'a>) -> IResult<Branch<'a>> {
 /// block. There can be spaces or line breaks at the end of the block.
 ///
 /// The location of the returned block ignores the `keyword` and spaces surrounding the block.
-fn keyword_block<'a>(keyword: &'a str) -> impl Fn(Span<'a>) -> IResult<Located<Block<'a>>> {
+fn keyword_block<'a>(keyword: &'a str) -> impl FnMut(Span<'a>) -> IResult<Located<Block<'a>>> {
     delimited(keyword_space(keyword), block1, multispace0)
 }
@@ -0,0 +1,47 @@
+//! Parsers for blocks used as expressions.
+//!
+//! The entry point for this module is the [`do_block`] function. Blocks are parsed following the
+//! rule
+//!
+//! ```abnf
+//! do_block = "do" block1 "end"
+//! ```
+//!
+//! A line of a block cannot start with one of these blocks, since the `do` keyword there would be
+//! the one of an `if`, `while` or `for` whose condition is written in the previous lines.
+use nom::{
+    character::complete::multispace0,
+    combinator::map,
+    sequence::{delimited, preceded, tuple},
+};
+use nom_locate::position;
+
+use pijama_ast::{Located, Location, Node, Span};
+
+use crate::parser::{
+    block::block1,
+    helpers::{keyword, keyword_space},
+    IResult,
+};
+
+/// Parses a [`Node::Block`].
+///
+/// There must be at least one space or line break after the `do` keyword. There can be spaces or
+/// line breaks at the end of the block.
+///
+/// The location of the returned node matches the start of the `do` and the end of the `end`.
+pub fn do_block(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            delimited(keyword_space("do"), block1, multispace0),
+            preceded(keyword("end"), position),
+        )),
+        |(sp1, block, sp2)| {
+            Located::new(
+                Node::Block(block),
+                Location::from(sp1) + Location::from(sp2),
+            )
+        },
+    )(input)
+}
@@ -0,0 +1,62 @@
+//! Parsers for enum declarations.
+//!
+//! The entry point for this module is the [`enum_def`] function. Enum declarations are parsed
+//! following the rules
+//!
+//! ```abnf
+//! enum_def = "enum" name "do" variant+ "end"
+//! variant = name ("(" (ty ("," ty)*)? ")")?
+//! ```
+//!
+//! Variants without brackets do not have fields.
+use nom::{
+    character::complete::{multispace1, space0, space1},
+    combinator::{map, opt},
+    multi::many1,
+    sequence::{pair, preceded, tuple},
+};
+use nom_locate::position;
+
+use alloc::vec::Vec;
+
+use pijama_ast::{Located, Location, Node, Span, Variant};
+
+use crate::parser::{helpers::keyword, name::name, node::fn_def::args, ty::ty, IResult};
+
+/// Parses a [`Node::EnumDef`].
+///
+/// There must be at least one space between the `enum` keyword and the name of the enum, and at
+/// least one space or line break between the name and the `do` keyword, between each variant and
+/// before the `end` keyword.
+///
+/// The location of the returned node matches the start of the `enum` and the end of the `end`.
+pub fn enum_def(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            preceded(pair(keyword("enum"), space1), name),
+            preceded(multispace1, keyword("do")),
+            many1(preceded(multispace1, variant)),
+            preceded(pair(multispace1, keyword("end")), position),
+        )),
+        |(sp1, name, _, variants, sp2)| {
+            Located::new(
+                Node::EnumDef(name, variants),
+                Location::from(sp1) + Location::from(sp2),
+            )
+        },
+    )(input)
+}
+
+/// Parses a [`Variant`].
+///
+/// There can be any number of spaces between the name of the variant and the types of its fields.
+fn variant(input: Span) -> IResult<Variant> {
+    map(
+        pair(name, opt(preceded(space0, args(ty)))),
+        |(name, fields)| Variant {
+            name,
+            fields: fields.map(|fields| fields.content).unwrap_or_else(Vec::new),
+        },
+    )(input)
+}
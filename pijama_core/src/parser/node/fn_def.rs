@@ -4,10 +4,17 @@
 //! functions are parsed following the rules
 //!
 //! ```abnf
-//! fn_def = "fn" name "(" (ty_annotation ("," ty_annotation)*)? ")" (":" ty)? "do" block1 "end"
-//! anon_fn = "fn" "(" (ty_annotation ("," ty_annotation)*)? ")" (":" ty)? "do" block1 "end"
+//! fn_def = "fn" name "(" (param ("," param)*)? ")" (":" ty)? "do" block1 "end"
+//! anon_fn = "fn" "(" (param ("," param)*)? ")" (":" ty)? "do" block1 "end"
+//! param = name (":" ty)?
 //! ```
 //!
+//! Parameters without a type annotation have type `Ty::Missing`, their types are inferred by the
+//! type checker.
+//!
+//! A function definition can be preceded by doc comment lines starting with `##`, which are
+//! parsed by the [`documented`] function and kept in the [`Node::FnDef`].
+//!
 //! The `fn_def` parser takes care of both rules: If the name is not given, the expression will be
 //! interpreted as an anonymous function.
 //!
@@ -15,20 +22,23 @@
 //!
 //! [`call`]: super::call
 use nom::{
-    character::complete::{char, multispace0, space0, space1},
-    combinator::{map, opt},
-    multi::separated_list,
-    sequence::{delimited, pair, preceded, terminated, tuple},
+    character::complete::{multispace0, space0, space1},
+    combinator::{map, opt, verify},
+    multi::many1,
+    sequence::{pair, preceded, terminated, tuple},
 };
 use nom_locate::position;
 
+use alloc::vec::Vec;
+
 use pijama_ast::{ty::TyAnnotation, Block, Located, Location, Node, Span};
 
 use crate::parser::{
     block::block0,
-    helpers::{in_brackets, keyword, keyword_space, surrounded},
+    helpers::{comma_list, keyword, keyword_space, surrounded, with_context},
     name::name,
-    ty::{colon_ty, ty_annotation},
+    node::comment::doc_comment,
+    ty::{colon_ty, opt_ty_annotation},
     IResult,
 };
 
@@ -46,7 +56,7 @@ pub fn fn_def(input: Span) -> IResult<Located<Node>> {
         tuple((
             keyword("fn"),
             opt(preceded(space1, name)),
-            surrounded(args(ty_annotation), space0),
+            surrounded(args(opt_ty_annotation), space0),
             terminated(colon_ty, multispace0),
             fn_body,
         )),
@@ -55,7 +65,8 @@ pub fn fn_def(input: Span) -> IResult<Located<Node>> {
                 .with_content(())
                 .zip_with(body, move |_, body| {
                     if let Some(name) = opt_name {
-                        Node::FnDef(name, args.content, TyAnnotation { item: body, ty })
+                        let body = TyAnnotation { item: body, ty };
+                        Node::FnDef(name, args.content, body, Vec::new())
                     } else {
                         Node::AnonFn(args.content, TyAnnotation { item: body, ty })
                     }
@@ -64,6 +75,30 @@ pub fn fn_def(input: Span) -> IResult<Located<Node>> {
     )(input)
 }
 
+/// Parses a [`Node::FnDef`] preceded by one or more doc comment lines.
+///
+/// There can be spaces or line breaks between the doc comment and the function definition. Doc
+/// comments cannot be followed by anything else, including anonymous functions.
+///
+/// The location of the returned node matches the start of the `fn` and the end of the `end`.
+pub fn documented(input: Span) -> IResult<Located<Node>> {
+    map(
+        pair(
+            many1(terminated(doc_comment, multispace0)),
+            with_context(
+                "Expected function definition after doc comment",
+                verify(fn_def, |node| matches!(node.content, Node::FnDef(..))),
+            ),
+        ),
+        |(lines, mut node)| {
+            if let Node::FnDef(_, _, _, docs) = &mut node.content {
+                *docs = lines;
+            }
+            node
+        },
+    )(input)
+}
+
 /// Parser for arguments of a function definition or function call.
 ///
 /// - For function definitions: the arguments are type annotations.
@@ -75,13 +110,10 @@ pub fn fn_def(input: Span) -> IResult<Located<Node>> {
 /// before the comma and spaces or line breaks after the comma.
 ///
 /// The location of the returned vector starts in `(` and ends in `)`.
-pub fn args<'a, O: std::fmt::Debug>(
-    content: impl Fn(Span<'a>) -> IResult<'a, O>,
-) -> impl Fn(Span<'a>) -> IResult<'a, Located<Vec<O>>> {
-    in_brackets(separated_list(
-        delimited(space0, char(','), multispace0),
-        content,
-    ))
+pub fn args<'a, O: core::fmt::Debug>(
+    content: impl FnMut(Span<'a>) -> IResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> IResult<'a, Located<Vec<O>>> {
+    comma_list('(', content, ')')
 }
 
 /// Parses the body of a function definition.
@@ -0,0 +1,63 @@
+//! Parsers for for loops.
+//!
+//! The entry point for this module is the [`for_loop`] function. For loops are parsed following
+//! the rules
+//!
+//! ```abnf
+//! for_loop = "for" name "in" range "do" block1 "end"
+//! range = node ".." node
+//! ```
+use nom::{
+    bytes::complete::tag,
+    character::complete::{multispace0, multispace1, space0},
+    combinator::map,
+    sequence::{delimited, preceded, separated_pair, tuple},
+};
+use nom_locate::position;
+
+use alloc::boxed::Box;
+
+use pijama_ast::{Located, Location, Node, Span};
+
+use crate::parser::{
+    block::block1,
+    helpers::{expect, keyword, keyword_space, surrounded},
+    name::name,
+    node::node,
+    IResult,
+};
+
+/// Parses a [`Node::For`].
+///
+/// There must be at least one space or line break after the `for` keyword, around the `in` and
+/// `do` keywords and before the final `end` keyword. There can be spaces or line breaks at the end
+/// of the body.
+///
+/// The location of the returned node matches the start of the `for` and the end of the `end`.
+pub fn for_loop(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            delimited(keyword_space("for"), name, multispace1),
+            preceded(keyword_space("in"), range),
+            preceded(
+                multispace1,
+                delimited(keyword_space("do"), block1, multispace0),
+            ),
+            preceded(keyword("end"), position),
+        )),
+        |(sp1, name, (start, end), body, sp2)| {
+            Located::new(
+                Node::For(name, Box::new(start), Box::new(end), body),
+                Location::from(sp1) + Location::from(sp2),
+            )
+        },
+    )(input)
+}
+
+/// Parses a range, returning its start and its end.
+///
+/// There can be any number of spaces surrounding the `..`, including no spaces at all.
+fn range(input: Span) -> IResult<(Located<Node>, Located<Node>)> {
+    separated_pair(node, surrounded(expect(&[".."], tag("..")), space0), node)(input)
+}
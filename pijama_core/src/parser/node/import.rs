@@ -0,0 +1,33 @@
+//! Parsers for imports.
+//!
+//! The entry point for this module is the [`import`] function. Imports are parsed following the
+//! rule
+//!
+//! ```abnf
+//! import = "import" string
+//! ```
+//!
+//! The string is the path of the imported module, relative to the module importing it.
+use nom::{
+    character::complete::space1,
+    combinator::map,
+    sequence::{pair, preceded, tuple},
+};
+use nom_locate::position;
+
+use pijama_ast::{Located, Location, Node, Span};
+
+use crate::parser::{helpers::keyword, literal::string, IResult};
+
+/// Parses a [`Node::Import`].
+///
+/// There must be at least one space between the `import` keyword and the path.
+///
+/// The location of the returned node matches the start of the `import` and the closing quote of
+/// the path.
+pub fn import(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((position, preceded(pair(keyword("import"), space1), string))),
+        |(span, path)| (Location::from(span) + path.loc).with_content(Node::Import(path.content)),
+    )(input)
+}
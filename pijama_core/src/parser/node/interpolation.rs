@@ -0,0 +1,68 @@
+//! Parsers for interpolated strings.
+//!
+//! The entry point for this module is the [`interpolation`] function. Interpolated strings are
+//! parsed following the rule
+//!
+//! ```abnf
+//! interpolation = "\"" (string_char+ / ("{" node "}"))* "\""
+//! ```
+//!
+//! where `string_char` is any character allowed inside a string literal.
+use nom::{
+    branch::alt,
+    character::complete::{char, space0},
+    combinator::{cut, map},
+    multi::{fold_many1, many0},
+    sequence::{delimited, preceded, tuple},
+};
+use nom_locate::position;
+
+use pijama_ast::{Literal, Located, Location, Node, Span};
+
+use alloc::string::String;
+
+use crate::parser::{helpers::surrounded, literal::quoted_char, node::node, IResult};
+
+/// Parses a [`Node::Interpolation`].
+///
+/// The parts of the returned node are string literals for the text between interpolations and
+/// the nodes between braces, which can be surrounded by spaces.
+///
+/// The location of the returned node matches the start and end quotes.
+pub fn interpolation(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            preceded(char('"'), many0(alt((fragment, interpolated)))),
+            position,
+            char('"'),
+        )),
+        |(start, parts, end, _)| {
+            (Location::from(start) + end.into())
+                .with_content(Node::Interpolation(parts.into_iter().collect()))
+        },
+    )(input)
+}
+
+/// Parses the text between two interpolations as a string literal.
+fn fragment(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            fold_many1(quoted_char('"'), String::new, |mut string, c| {
+                string.push(c);
+                string
+            }),
+            position,
+        )),
+        |(start, string, end)| {
+            Location::new(start.location_offset(), end.location_offset())
+                .with_content(Node::Literal(Literal::Str(string)))
+        },
+    )(input)
+}
+
+/// Parses a node between braces.
+fn interpolated(input: Span) -> IResult<Located<Node>> {
+    delimited(char('{'), cut(surrounded(node, space0)), cut(char('}')))(input)
+}
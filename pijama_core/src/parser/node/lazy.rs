@@ -0,0 +1,39 @@
+//! Parsers for lazy terms.
+//!
+//! The entry point for this module is the [`lazy`] function. Lazy terms are parsed following the
+//! rule
+//!
+//! ```abnf
+//! lazy = "lazy" node
+//! ```
+//!
+//! Like unary operations, the lazy term takes the whole node after the keyword, so `lazy x + 1`
+//! is the same as `lazy (x + 1)`.
+use nom::{
+    character::complete::space1,
+    combinator::map,
+    sequence::{pair, preceded, tuple},
+};
+use nom_locate::position;
+
+use alloc::boxed::Box;
+
+use pijama_ast::{Located, Location, Node, Span};
+
+use crate::parser::{helpers::keyword, node::node, IResult};
+
+/// Parses a [`Node::Lazy`].
+///
+/// There must be at least one space between the `lazy` keyword and the node.
+///
+/// The location of the returned node matches the start of the `lazy` and the end of the inner
+/// node.
+pub fn lazy(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((position, preceded(pair(keyword("lazy"), space1), node))),
+        |(span, node)| {
+            let loc = Location::from(span) + node.loc;
+            Located::new(Node::Lazy(Box::new(node)), loc)
+        },
+    )(input)
+}
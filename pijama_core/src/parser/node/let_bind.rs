@@ -1,22 +1,37 @@
 //! Parsers for let bindings.
 //!
-//! The entry point for this module is the [`let_bind`] function. Let bindings are parsed following
-//! the rule
+//! The entry points for this module are the [`let_bind`], [`let_mut`] and [`let_in`] functions.
+//! Let bindings are parsed following the rules
 //!
 //! ```abnf
 //! let_bind = name (":" ty)? "=" node
+//! let_mut = "let" "mut" name (":" ty)? "=" node
+//! let_in = "let" name (":" ty)? "=" node "in" node
 //! ```
 //!
-//! Meaning that type bindings are optional.
+//! Meaning that type bindings are optional. A `let_bind` whose name is bound by a `let_mut` is an
+//! assignment, but this is only decided after parsing, as explained in the [`assign`] module.
+//!
+//! [`assign`]: crate::parser::assign
 use nom::{
-    character::complete::{char, space0},
+    bytes::complete::tag,
+    character::complete::{char, multispace1, space0, space1},
     combinator::map,
-    sequence::{preceded, tuple},
+    sequence::{pair, preceded, terminated, tuple},
 };
+use nom_locate::position;
+
+use alloc::boxed::Box;
 
-use pijama_ast::{ty::TyAnnotation, Located, Node, Span};
+use pijama_ast::{ty::TyAnnotation, Located, Location, Node, Span};
 
-use crate::parser::{helpers::surrounded, name::name, node::node, ty::colon_ty, IResult};
+use crate::parser::{
+    helpers::{keyword_space, surrounded},
+    name::name,
+    node::node,
+    ty::colon_ty,
+    IResult,
+};
 
 /// Parses a [`Node::LetBind`].
 ///
@@ -38,3 +53,53 @@ pub fn let_bind(input: Span) -> IResult<Located<Node>> {
         },
     )(input)
 }
+
+/// Parses a [`Node::LetMut`].
+///
+/// There must be at least one space after the `let` and `mut` keywords and there can be any
+/// number of spaces surrounding the `=` sign.
+///
+/// The location of the returned node matches the start of the `let` keyword and the end of the
+/// node after the `=`.
+pub fn let_mut(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            terminated(position, tuple((tag("let"), space1, tag("mut"), space1))),
+            name,
+            colon_ty,
+            preceded(surrounded(char('='), space0), node),
+        )),
+        |(span, name, ty, node)| {
+            let loc = Location::from(span) + node.loc;
+            let annotation = TyAnnotation { item: name, ty };
+            Located::new(Node::LetMut(annotation, Box::new(node)), loc)
+        },
+    )(input)
+}
+
+/// Parses a [`Node::LetIn`].
+///
+/// There must be at least one space after the `let` keyword and at least one space or line break
+/// around the `in` keyword. There can be any number of spaces surrounding the `=` sign.
+///
+/// The location of the returned node matches the start of the `let` keyword and the end of the
+/// node after the `in`.
+pub fn let_in(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            terminated(position, pair(tag("let"), space1)),
+            name,
+            colon_ty,
+            preceded(surrounded(char('='), space0), node),
+            preceded(pair(multispace1, keyword_space("in")), node),
+        )),
+        |(span, name, ty, node1, node2)| {
+            let loc = Location::from(span) + node2.loc;
+            let annotation = TyAnnotation { item: name, ty };
+            Located::new(
+                Node::LetIn(annotation, Box::new(node1), Box::new(node2)),
+                loc,
+            )
+        },
+    )(input)
+}
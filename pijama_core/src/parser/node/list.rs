@@ -0,0 +1,26 @@
+//! Parsers for list literals.
+//!
+//! The entry point for this module is the [`list`] function. List literals are parsed following
+//! the rule
+//!
+//! ```abnf
+//! list = "[" (node ("," node)*)? "]"
+//! ```
+use nom::combinator::map;
+
+use pijama_ast::{Located, Node, Span};
+
+use crate::parser::{helpers::comma_list, node::node, IResult};
+
+/// Parses a [`Node::List`].
+///
+/// This parser admits:
+/// - Spaces or line breaks after the `[` and before the `]`.
+/// - Spaces before and spaces or line breaks after each comma.
+///
+/// The location of the returned node starts in the `[` and ends in the `]`.
+pub fn list(input: Span) -> IResult<Located<Node>> {
+    map(comma_list('[', node, ']'), |elems| {
+        elems.map(|elems| Node::List(elems.into_iter().collect()))
+    })(input)
+}
@@ -0,0 +1,101 @@
+//! Parsers for match expressions.
+//!
+//! The entry point for this module is the [`match_expr`] function. Match expressions are parsed
+//! following the rules
+//!
+//! ```abnf
+//! match_expr = "match" node "with" arm+ "end"
+//! arm = pattern "do" block1 "end"
+//! pattern = "_" / literal / qualified_name ("(" (name ("," name)*)? ")")?
+//! ```
+//!
+//! A name in a pattern is always the name of a variant, so a variant without fields is matched by
+//! its name alone.
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{multispace0, multispace1, space0},
+    combinator::{map, opt},
+    multi::many1,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+};
+use nom_locate::position;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use pijama_ast::{Arm, Located, Location, Node, Pattern, Span};
+
+use crate::parser::{
+    block::block1,
+    helpers::{keyword, keyword_space, with_context},
+    literal::literal,
+    name::{name, qualified_name},
+    node::{fn_def::args, node},
+    IResult,
+};
+
+/// Parses a [`Node::Match`].
+///
+/// There must be at least one space or line break after the `match` keyword, around the `with`
+/// keyword, between each arm and before the final `end` keyword.
+///
+/// The location of the returned node matches the start of the `match` and the end of the `end`.
+pub fn match_expr(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            delimited(keyword_space("match"), node, multispace1),
+            keyword("with"),
+            many1(preceded(multispace1, arm)),
+            preceded(pair(multispace1, keyword("end")), position),
+        )),
+        |(sp1, node, _, arms, sp2)| {
+            Located::new(
+                Node::Match(Box::new(node), arms),
+                Location::from(sp1) + Location::from(sp2),
+            )
+        },
+    )(input)
+}
+
+/// Parses an [`Arm`].
+///
+/// There must be at least one space or line break between the pattern and the `do` keyword and
+/// between the `do` keyword and the body. There can be spaces or line breaks at the end of the
+/// body.
+///
+/// The location of the body ignores the `do` and `end` keywords and the spaces surrounding it.
+fn arm(input: Span) -> IResult<Arm> {
+    map(
+        tuple((
+            terminated(pattern, multispace1),
+            delimited(keyword_space("do"), block1, multispace0),
+            keyword("end"),
+        )),
+        |(pattern, body, _)| Arm { pattern, body },
+    )(input)
+}
+
+/// Parses a [`Pattern`].
+///
+/// There can be any number of spaces between the name of a variant and the names of its fields.
+///
+/// The location of the returned pattern matches the start and end of the pattern in the source
+/// code.
+fn pattern(input: Span) -> IResult<Located<Pattern>> {
+    with_context(
+        "Expected pattern (_, literal or variant)",
+        alt((
+            map(tag("_"), |span: Span| Located::new(Pattern::Wildcard, span)),
+            map(literal, |literal| literal.map(Pattern::Literal)),
+            map(
+                pair(qualified_name, opt(preceded(space0, args(name)))),
+                |(name, binders)| match binders {
+                    Some(binders) => (name.loc + binders.loc)
+                        .with_content(Pattern::Variant(name, binders.content)),
+                    None => name.loc.with_content(Pattern::Variant(name, Vec::new())),
+                },
+            ),
+        )),
+    )(input)
+}
@@ -6,18 +6,29 @@
 //!
 //! The [`binary_op`] module is particularly important here so it is a good idea to check those
 //! module docs too.
+mod ascription;
 mod binary_op;
 mod call;
 pub mod comment;
 mod cond;
+mod do_block;
+mod enum_def;
 mod fn_def;
+mod for_loop;
+mod import;
+mod interpolation;
+mod lazy;
 mod let_bind;
+mod list;
+mod match_expr;
+mod operator;
 mod unary_op;
+mod while_loop;
 
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{char, multispace1},
+    character::complete::{char, multispace1, space1},
     combinator::map,
     sequence::pair,
 };
@@ -25,12 +36,7 @@ use nom::{
 use pijama_ast::{Located, Node, Span};
 
 use crate::parser::{
-    helpers::{in_brackets, lookahead},
-    literal::literal,
-    name::name,
-    primitive::primitive,
-    un_op::un_op,
-    IResult,
+    helpers::lookahead, literal::literal, name::qualified_name, un_op::un_op, IResult,
 };
 
 /// Parser for [`Node`]s.
@@ -46,13 +52,32 @@ pub fn node(input: Span) -> IResult<Located<Node>> {
 /// [`Node`] type are base nodes with the exception of the [`Node::BinaryOp`] variant.
 ///
 /// For nodes inside brackets, there can be any number of spaces between the brackets and the node.
+/// The node can be followed by a type ascription as explained in the [`ascription`] module.
+/// Binary operators and sections in brackets are operators used as functions, as explained in the
+/// [`operator`] module.
 ///
 /// This parser also does small lookaheads using the [`lookahead`] combinator. This improves
 /// significantly the error messages generated by nom. The lookaheads are the following:
 ///
 /// - If the input starts with `if` and a space or line break, the [`cond`] parser is applied.
+/// - If the input starts with `while` and a space or line break, the [`while_loop`] parser is
+///   applied.
+/// - If the input starts with `for` and a space or line break, the [`for_loop`] parser is
+///   applied.
 /// - If the input starts with `fn` and a space, the [`fn_def`] parser is applied.
-/// - If the input starts with a name, the [`let_bind`], [`call`] or [`name`] parser is applied.
+/// - If the input starts with `##`, the [`fn_def::documented`] parser is applied.
+/// - If the input starts with `enum` and a space, the [`enum_def`] parser is applied.
+/// - If the input starts with `match` and a space or line break, the [`match_expr`] parser is
+///   applied.
+/// - If the input starts with `import` and a space, the [`import`] parser is applied.
+/// - If the input starts with `let` and a space, the [`let_mut`] or [`let_in`] parser is applied.
+/// - If the input starts with `lazy` and a space, the [`lazy`] parser is applied.
+/// - If the input starts with `do` and a space or line break, the [`do_block`] parser is applied.
+/// - If the input starts with `"` and it is not a string literal, the [`interpolation`] parser is
+///   applied.
+/// - If the input starts with `[`, the [`list`] parser is applied.
+/// - If the input starts with a name, the [`let_bind`], [`call`] or [`qualified_name`] parser is
+///   applied.
 /// - If the input starts with a unary operator, the [`un_op`] parser is applied.
 ///
 /// This function is very order sensitive. Be careful if you swap the parsers order.
@@ -60,26 +85,33 @@ fn base_node(input: Span) -> IResult<Located<Node>> {
     alt((
         lookahead(
             char('('),
-            alt((
-                call::call,
-                map(in_brackets(node), |Located { mut content, loc }| {
-                    content.loc = loc;
-                    content
-                }),
-            )),
+            alt((call::call, operator::operator, ascription::bracketed)),
         ),
-        map(literal, |Located { content, loc }| {
+        map(literal, |Located { content, loc, .. }| {
             Located::new(Node::Literal(content), loc)
         }),
+        lookahead(char('"'), interpolation::interpolation),
+        lookahead(char('['), list::list),
         lookahead(pair(tag("if"), multispace1), cond::cond),
+        lookahead(pair(tag("while"), multispace1), while_loop::while_loop),
+        lookahead(pair(tag("for"), multispace1), for_loop::for_loop),
         lookahead(tag("fn"), fn_def::fn_def),
-        lookahead(primitive, call::call),
+        lookahead(tag("##"), fn_def::documented),
+        lookahead(pair(tag("enum"), space1), enum_def::enum_def),
+        lookahead(pair(tag("match"), multispace1), match_expr::match_expr),
+        lookahead(pair(tag("import"), space1), import::import),
+        lookahead(
+            pair(tag("let"), space1),
+            alt((let_bind::let_mut, let_bind::let_in)),
+        ),
+        lookahead(pair(tag("lazy"), space1), lazy::lazy),
+        lookahead(pair(tag("do"), multispace1), do_block::do_block),
         lookahead(
-            name,
+            qualified_name,
             alt((
                 let_bind::let_bind,
                 call::call,
-                map(name, |Located { content, loc }| {
+                map(qualified_name, |Located { content, loc, .. }| {
                     Located::new(Node::Name(content), loc)
                 }),
             )),
@@ -0,0 +1,102 @@
+//! Parsers for binary operators used as functions.
+//!
+//! The entry point for this module is the [`operator`] function. Operators used as functions and
+//! their sections are parsed following the rules
+//!
+//! ```abnf
+//! operator = "(" bin_op ")" / "(" "_" bin_op node ")"
+//! section = "(" node bin_op "_" ")"
+//! ```
+//!
+//! The `section` rule is parsed by the [`ascription::bracketed`] parser, since its operand cannot
+//! be told apart from a node in brackets until the `_` is reached. The binary operations stop
+//! before an operator followed by a `_` and a `)`, so the operand of these sections does not take
+//! the operator.
+//!
+//! The operand of a section must bind more tightly than its operator, as if the `_` was another
+//! operand, so `(_ * x + 1)` and `(-x * _)` must be written as `(_ * (x + 1))` and `((-x) * _)`.
+//!
+//! [`ascription::bracketed`]: crate::parser::node::ascription::bracketed
+use nom::{
+    branch::alt,
+    character::complete::{char, multispace0},
+    combinator::{map, value},
+    error::{ErrorKind, ParseError},
+    sequence::{preceded, terminated, tuple},
+    Err::Failure,
+};
+
+use alloc::{boxed::Box, string::ToString};
+
+use pijama_ast::{BinOp, Located, Node, Span};
+
+use crate::parser::{
+    bin_op::{bin_op, precedence},
+    helpers::in_brackets,
+    node::node,
+    IResult, ParsingError,
+};
+
+/// Parses a [`Node::Operator`] without operands or whose first operand is missing.
+///
+/// There can be any number of spaces or line breaks between the brackets and the section, and any
+/// number of spaces surrounding the operator.
+///
+/// The location of the returned node starts in the `(` and ends in the `)`.
+pub fn operator(input: Span) -> IResult<Located<Node>> {
+    in_brackets(alt((
+        map(bin_op, |op| Node::Operator(op, None, None)),
+        left_section,
+    )))(input)
+}
+
+fn left_section(input: Span) -> IResult<Node> {
+    let (rem, op) = preceded(char('_'), bin_op)(input)?;
+    let (rem, node) = node(rem)?;
+    if !is_operand(&node, op, false) {
+        return Err(operand_error(input));
+    }
+    Ok((rem, Node::Operator(op, None, Some(Box::new(node)))))
+}
+
+/// Parser for a `_` closing a section.
+pub(super) fn hole(input: Span) -> IResult<()> {
+    value((), tuple((char('_'), multispace0, char(')'))))(input)
+}
+
+/// Parser for the operator of a section whose second operand is missing, up to its `_`.
+pub(super) fn section_end(input: Span) -> IResult<BinOp> {
+    terminated(bin_op, char('_'))(input)
+}
+
+/// Returns `true` if `node` can be the `first` or second operand of a section with `op`, i.e., if
+/// it would be the same operand in a binary operation without brackets.
+///
+/// Nodes in brackets are located at their brackets, so they end after their last operand.
+pub(super) fn is_operand(node: &Located<Node>, op: BinOp, first: bool) -> bool {
+    match &node.content {
+        Node::BinaryOp(op2, _, last) if node.loc.end == last.loc.end => {
+            // Binary operators are left-associative.
+            precedence(*op2) > precedence(op) || (first && precedence(*op2) == precedence(op))
+        }
+        // These nodes take every node after them.
+        Node::UnaryOp(_, last) | Node::LetIn(_, _, last) | Node::Lazy(last)
+            if node.loc.end == last.loc.end =>
+        {
+            !first
+        }
+        _ => true,
+    }
+}
+
+/// Returns the error for a section whose operand starts at `input` and does not bind more tightly
+/// than its operator.
+pub(super) fn operand_error(input: Span) -> nom::Err<ParsingError> {
+    Failure(ParsingError::with_context(
+        input,
+        "The operand of a section must be in brackets unless it binds more tightly than the \
+         operator"
+            .to_string(),
+        ParsingError::from_error_kind(input, ErrorKind::Verify),
+    ))
+}
@@ -7,6 +7,8 @@
 use nom::{combinator::map, sequence::tuple};
 use nom_locate::position;
 
+use alloc::boxed::Box;
+
 use pijama_ast::{Located, Location, Node, Span};
 
 use crate::parser::{node::node, un_op::un_op, IResult};
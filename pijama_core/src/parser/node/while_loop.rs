@@ -0,0 +1,45 @@
+//! Parsers for while loops.
+//!
+//! The entry point for this module is the [`while_loop`] function. While loops are parsed
+//! following the rule
+//!
+//! ```abnf
+//! while_loop = "while" block1 "do" block1 "end"
+//! ```
+use nom::{
+    character::complete::multispace0,
+    combinator::map,
+    sequence::{delimited, preceded, tuple},
+};
+use nom_locate::position;
+
+use pijama_ast::{Branch, Located, Location, Node, Span};
+
+use crate::parser::{
+    block::block1,
+    helpers::{keyword, keyword_space},
+    IResult,
+};
+
+/// Parses a [`Node::While`].
+///
+/// There must be at least one space or line break after the `while` and `do` keywords. There can
+/// be spaces or line breaks at the end of each block.
+///
+/// The location of the returned node matches the start of the `while` and the end of the `end`.
+pub fn while_loop(input: Span) -> IResult<Located<Node>> {
+    map(
+        tuple((
+            position,
+            delimited(keyword_space("while"), block1, multispace0),
+            delimited(keyword_space("do"), block1, multispace0),
+            preceded(keyword("end"), position),
+        )),
+        |(sp1, cond, body, sp2)| {
+            Located::new(
+                Node::While(Branch { cond, body }),
+                Location::from(sp1) + Location::from(sp2),
+            )
+        },
+    )(input)
+}
@@ -0,0 +1,148 @@
+//! Parsing with error recovery.
+//!
+//! The [`parse`] function stops at the first error, which is not enough for editors: a program
+//! being written usually has an error somewhere and the diagnostics of the rest of it are still
+//! useful. The [`parse_recovering`] function splits programs that do not parse in their top-level
+//! statements and parses each one of them on its own, so an error in one statement does not hide
+//! the errors in the others.
+//!
+//! A statement ends at the first line break that is not inside brackets or inside a construct
+//! closed by `end`, i.e. conditionals, loops, functions, enums, matches, match arms and `do`
+//! blocks. The
+//! tokens of the [`lex`] module are used to find these recovery points, since they can be found
+//! even in code that does not parse.
+//!
+//! [`parse`]: crate::parser::parse
+//! [`lex`]: crate::lex
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use nom::{
+    character::complete::{line_ending, multispace0},
+    combinator::all_consuming,
+    multi::separated_list0,
+    Err::*,
+    Slice,
+};
+
+use pijama_ast::{node_id::number_nodes, Block, Located, Location, Node, Span};
+
+use crate::{
+    lex::{tokens, Token},
+    parser::{
+        assign,
+        block::{self, block0},
+        helpers::surrounded,
+        parse, IResult, ParsingError,
+    },
+};
+
+/// Produces a [`Block`] from a string slice, recovering from parsing errors.
+///
+/// If the whole string parses, this function returns the same block as [`parse`] and no errors.
+/// Otherwise, each top-level statement that cannot be parsed is replaced by a [`Node::Error`]
+/// located at its code, and the returned errors have one error per statement in order. The first
+/// error is always the one returned by [`parse`].
+///
+/// [`parse`]: crate::parser::parse
+pub fn parse_recovering(input: &str) -> (Located<Block<'_>>, Vec<ParsingError>) {
+    let first_error = match parse(input) {
+        Ok(block) => return (block, Vec::new()),
+        Err(error) => error,
+    };
+
+    let span = Span::new(input);
+    let mut block = Block::new();
+    let mut errors = Vec::new();
+    for (index, range) in statements(input).into_iter().enumerate() {
+        let statement = span.slice(range.clone());
+        // Only the first statement can start with the comment of the block.
+        let result = if index == 0 {
+            all_consuming(surrounded(block0, multispace0))(statement)
+                .map(|(_, block)| block.content.into_iter().collect())
+        } else {
+            all_consuming(surrounded(nodes, multispace0))(statement).map(|(_, nodes)| nodes)
+        };
+        match result {
+            Ok(nodes) => block.extend(nodes),
+            Err(Error(error)) | Err(Failure(error)) => {
+                let error = if errors.is_empty() {
+                    first_error.clone()
+                } else {
+                    error
+                };
+                errors.push(error);
+                block.push_back(unparsed(input, range));
+            }
+            Err(Incomplete(_)) => unreachable!(),
+        }
+    }
+
+    // The statements might parse on their own even if the whole program does not.
+    if errors.is_empty() {
+        block.clear();
+        block.push_back(unparsed(input, 0..input.len()));
+        errors.push(first_error);
+    }
+
+    assign::resolve(&mut block);
+    number_nodes(&mut block);
+    (Location::new(0, input.len()).with_content(block), errors)
+}
+
+/// Parser for the nodes of a statement that is not at the start of the program, which cannot
+/// start with a comment.
+fn nodes(input: Span<'_>) -> IResult<'_, Vec<Located<Node<'_>>>> {
+    separated_list0(line_ending, block::line)(input)
+}
+
+/// Returns an error node located at the code in `range` of `input` without its surrounding spaces.
+fn unparsed(input: &str, range: Range<usize>) -> Located<Node<'_>> {
+    let code = &input[range.clone()];
+    let start = range.start + code.len() - code.trim_start().len();
+    let end = range.start + code.trim_end().len();
+    Location::new(start, end.max(start)).with_content(Node::Error)
+}
+
+/// Returns the ranges of the top-level statements of `input`, which cover the whole input.
+fn statements(input: &str) -> Vec<Range<usize>> {
+    let mut starts = vec![0];
+    // Whether each construct that is waiting for its `end` is still waiting for its `do`.
+    let mut open = Vec::new();
+    let mut brackets = 0usize;
+    let mut last_end = None;
+
+    for token in tokens(input) {
+        let Location { start, end, .. } = token.loc;
+        let text = &input[start..end];
+        if let Some(last_end) = last_end {
+            if open.is_empty() && brackets == 0 && input[last_end..start].contains('\n') {
+                starts.push(start);
+            }
+        }
+        match (token.content, text) {
+            (Token::Keyword, "if" | "while" | "for" | "fn" | "enum") => open.push(true),
+            (Token::Keyword, "match") => open.push(false),
+            (Token::Keyword, "elif") => {
+                if let Some(waiting) = open.last_mut() {
+                    *waiting = true;
+                }
+            }
+            // The `do` of match arms and of blocks used as expressions have their own `end`.
+            (Token::Keyword, "do") => match open.last_mut() {
+                Some(waiting) if *waiting => *waiting = false,
+                _ => open.push(false),
+            },
+            (Token::Keyword, "end") => {
+                open.pop();
+            }
+            (Token::Punctuation, "(" | "[" | "{") => brackets += 1,
+            (Token::Punctuation, ")" | "]" | "}") => brackets = brackets.saturating_sub(1),
+            _ => (),
+        }
+        last_end = Some(end);
+    }
+
+    starts.push(input.len());
+    starts.windows(2).map(|pair| pair[0]..pair[1]).collect()
+}
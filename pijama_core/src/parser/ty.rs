@@ -5,7 +5,9 @@
 //! [left-recursive](https://en.wikipedia.org/wiki/Left_recursion) grammar:
 //!
 //! ```abnf
-//! ty = (ty "->" ty) / "Bool" / "Int" / "Unit" / ("(" ty ")")
+//! ty = (ty "->" ty) / "Bool" / "Int" / "Float" / "Unit" / "Char" / "String" / adt / name
+//!     / ("[" ty "]") / ("(" ty ")")
+//! adt = qualified_name ("(" ty ("," ty)* ")")?
 //! ```
 //!
 //! This means that, with the above grammar, the first thing that the parser tries when to do when
@@ -14,23 +16,30 @@
 //!
 //! ```abnf
 //! ty = base_ty ("->" ty)*
-//! base_ty = "Bool" / "Int" / "Unit" / ("(" ty ")")
+//! base_ty = "Bool" / "Int" / "Float" / "Unit" / "Char" / "String" / adt / name
+//!     / ("[" ty "]") / ("(" ty ")")
+//! adt = qualified_name ("(" ty ("," ty)* ")")?
 //! ```
 //!
 //! Now the first thing done when parsing a type is trying to parse a "base type" avoiding the loop
 //! completely. The [`ty`] and [`base_ty`] parsers in this module corresponds to each one of the
-//! rules in the grammar above.
+//! rules in the grammar above. Names starting with an uppercase letter are enum types, which can
+//! be qualified by the namespace of their module and followed by their type arguments, and any
+//! other name is a type variable. `Chan` followed by one type argument is a channel type, and
+//! `Lazy` followed by one type argument is the type of a lazy expression.
 //!
-//! In addition we have the [`ty_annotation`] parser, which parses expressions with the grammar
+//! In addition we have the [`opt_ty_annotation`] parser, which is used for function parameters
+//! and parses expressions with the grammar
 //!
 //! ```abnf
-//! ty_annotation = name ":" ty
+//! opt_ty_annotation = name (":" ty)?
 //! ```
+//!
 //! The parser for names is explained in the [`name`] module.
 //!
 //! [`ty`]: crate::parser::ty::ty
 //! [`base_ty`]: crate::parser::ty::base_ty
-//! [`ty_annotation`]: crate::parser::ty::ty_annotation
+//! [`opt_ty_annotation`]: crate::parser::ty::opt_ty_annotation
 //! [`Ty`]: crate::ty::Ty
 //! [`TyAnnotation`]: crate::ty::TyAnnotation
 //! [`name`]: crate::parser::name
@@ -38,12 +47,15 @@ use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{char, space0, space1},
-    combinator::{cut, map, opt},
-    sequence::{pair, preceded, separated_pair, terminated},
+    combinator::{cut, map, not, opt, verify},
+    multi::separated_list1,
+    sequence::{pair, preceded, terminated, tuple},
 };
 
 use nom_locate::position;
 
+use alloc::{borrow::ToOwned, boxed::Box, vec::Vec};
+
 use pijama_ast::{
     ty::{Ty, TyAnnotation},
     Located, Location, Name, Span,
@@ -51,7 +63,7 @@ use pijama_ast::{
 
 use crate::parser::{
     helpers::{in_brackets, surrounded, with_context},
-    name::name,
+    name::{name, qualified_name},
     IResult,
 };
 
@@ -78,14 +90,20 @@ pub fn ty(input: Span) -> IResult<Located<Ty>> {
     }
 }
 
-/// Parser for name type annotations.
+/// Parser for name type annotations where the type is optional.
 ///
 /// This parser returns a [`TyAnnotation<Name>`], there can be any number of spaces surrounding the `:`,
-/// including no spaces at all.
-pub fn ty_annotation(input: Span) -> IResult<TyAnnotation<Name<'_>>> {
+/// including no spaces at all. If the name is not followed by a colon it returns an annotation
+/// with type `Ty::Missing` located right after the name.
+pub fn opt_ty_annotation(input: Span) -> IResult<TyAnnotation<Name<'_>>> {
     map(
-        separated_pair(name, surrounded(char(':'), space0), ty),
-        |(name, ty)| TyAnnotation { item: name, ty },
+        pair(name, opt(preceded(surrounded(char(':'), space0), ty))),
+        |(name, opt_ty)| {
+            let ty = opt_ty.unwrap_or_else(|| {
+                Location::new(name.loc.end, name.loc.end).with_content(Ty::Missing)
+            });
+            TyAnnotation { item: name, ty }
+        },
     )(input)
 }
 
@@ -101,9 +119,12 @@ pub fn colon_ty(input: Span) -> IResult<Located<Ty>> {
     map(
         pair(
             position,
+            // A colon followed by another one is part of a qualified name.
             opt(preceded(
-                surrounded(char(':'), space0),
-                cut(terminated(ty, space1)),
+                surrounded(terminated(char(':'), not(char(':'))), space0),
+                // In an ascription like `(x : Int)` the type is not followed by a space, so
+                // only the type is required once the colon is found.
+                terminated(cut(ty), space1),
             )),
         ),
         |(span, opt_ty)| opt_ty.unwrap_or_else(|| Location::from(span).with_content(Ty::Missing)),
@@ -112,25 +133,85 @@ pub fn colon_ty(input: Span) -> IResult<Located<Ty>> {
 
 /// Parser for base types and types in brackets.
 ///
-/// The only valid inputs for this parser are `"Bool"`, `"Int"`, `"Unit"` and a type surrounded by
-/// round brackets. It returns a [`Ty`].
+/// The only valid inputs for this parser are `"Bool"`, `"Int"`, `"Float"`, `"Unit"`, `"Char"`,
+/// `"String"`, a name, a type surrounded by square brackets and a type surrounded by round
+/// brackets. It returns a [`Ty`], which is an enum type if the input is a name starting with an
+/// uppercase letter, a type variable if it is any other name and a list type if the input is
+/// surrounded by square brackets. The name of an enum type can be followed by its type arguments
+/// between round brackets and separated by commas, like `Result(Int, String)`.
 ///
 /// There can be any number of spaces between the brackets and its contents.
 ///
-/// If the returned type is one of the string slices mentioned above, the location matches the one
-/// of the slice. If the returned type is surrounded by brackets, the location matches the span of
-/// the brackets.
+/// If the returned type is one of the string slices mentioned above, an enum type without type
+/// arguments or a type variable, the location matches the one of the slice. If the returned type
+/// is surrounded by brackets or has type arguments, the location ends in the closing bracket.
 fn base_ty(input: Span) -> IResult<Located<Ty>> {
     with_context(
-        "Expected basic type (Bool, Int, Unit) or type in brackets",
+        "Expected basic type (Bool, Int, Float, Unit, Char, String), enum type, type variable, list type or type in brackets",
         alt((
+            // Names are parsed first so enum types starting with `Int` or any other basic type
+            // are not split.
+            name_ty,
             map(tag("Bool"), |span: Span| Located::new(Ty::Bool, span)),
             map(tag("Int"), |span: Span| Located::new(Ty::Int, span)),
+            map(tag("Float"), |span: Span| Located::new(Ty::Float, span)),
             map(tag("Unit"), |span: Span| Located::new(Ty::Unit, span)),
-            map(in_brackets(ty), |Located { mut content, loc }| {
+            map(tag("Char"), |span: Span| Located::new(Ty::Char, span)),
+            map(tag("String"), |span: Span| Located::new(Ty::String, span)),
+            map(
+                tuple((
+                    terminated(position, char('[')),
+                    surrounded(ty, space0),
+                    preceded(char(']'), position),
+                )),
+                |(sp1, ty, sp2)| {
+                    (Location::from(sp1) + Location::from(sp2))
+                        .with_content(Ty::List(Box::new(ty.content)))
+                },
+            ),
+            map(in_brackets(ty), |Located { mut content, loc, .. }| {
                 content.loc = loc;
                 content
             }),
         )),
     )(input)
 }
+
+/// Parser for enum types and type variables.
+///
+/// Only the names whose last segment starts with an uppercase letter can be qualified or followed
+/// by type arguments.
+fn name_ty(input: Span) -> IResult<Located<Ty>> {
+    let (rem, name) = verify(qualified_name, |name| {
+        is_enum_name(name.content.0) || !name.content.0.contains("::")
+    })(input)?;
+    if !is_enum_name(name.content.0) {
+        return Ok((rem, name.map(|name| Ty::Var(name.0.to_owned()))));
+    }
+    let (rem, args) = opt(in_brackets(separated_list1(
+        surrounded(char(','), space0),
+        ty,
+    )))(rem)?;
+    let ty = match args {
+        // Channels are written like enum types with one type argument.
+        Some(mut args) if name.content.0 == "Chan" && args.content.len() == 1 => {
+            (name.loc + args.loc).with_content(Ty::Chan(Box::new(args.content.remove(0).content)))
+        }
+        Some(mut args) if name.content.0 == "Lazy" && args.content.len() == 1 => {
+            (name.loc + args.loc).with_content(Ty::Lazy(Box::new(args.content.remove(0).content)))
+        }
+        Some(args) => (name.loc + args.loc).with_content(Ty::Adt(
+            name.content.0.to_owned(),
+            args.content.into_iter().map(|arg| arg.content).collect(),
+        )),
+        None => name.map(|name| Ty::Adt(name.0.to_owned(), Vec::new())),
+    };
+    Ok((rem, ty))
+}
+
+/// Returns `true` if the last segment of `name` starts with an uppercase letter.
+fn is_enum_name(name: &str) -> bool {
+    name.rsplit("::")
+        .next()
+        .is_some_and(|segment| segment.starts_with(char::is_uppercase))
+}
@@ -15,14 +15,18 @@ use pijama_ast::{Span, UnOp, UnOp::*};
 
 use crate::parser::{helpers::with_context, IResult};
 
-/// Parser for the unary operators `!` and `-`.
+/// Parser for the unary operators `!`, `-` and `~`.
 ///
 /// All the unary operators might be followed by zero or more spaces.
 pub fn un_op(input: Span) -> IResult<UnOp> {
     terminated(
         with_context(
-            "Expected unary operator (!, -)",
-            alt((map(char('!'), |_| Not), map(char('-'), |_| Neg))),
+            "Expected unary operator (!, -, ~)",
+            alt((
+                map(char('!'), |_| Not),
+                map(char('-'), |_| Neg),
+                map(char('~'), |_| BitNot),
+            )),
         ),
         space0,
     )(input)
@@ -0,0 +1,41 @@
+//! Growth of the stack of the host for the functions that recurse over terms.
+//!
+//! With the `std` feature, deep recursions continue in new segments of the stack allocated on the
+//! heap, so they are only bounded by memory. Without it the stack cannot grow, and the depth
+//! limit of the machine is the only protection against overflowing it.
+
+/// The space left in the stack of the host below which a recursion continues in a new segment of
+/// the stack.
+#[cfg(feature = "std")]
+const RED_ZONE: usize = 128 * 1024;
+
+/// The size of each new segment of the stack of the host.
+#[cfg(feature = "std")]
+const SEGMENT: usize = 64 * 1024 * 1024;
+
+/// Runs `f`, which recurses over a term, in a new segment of the stack of the host if the current
+/// one is about to run out.
+#[cfg(feature = "std")]
+pub(crate) fn grow<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(RED_ZONE, SEGMENT, f)
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn grow<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// Runs `f`, which starts a deep recursion, in a new segment of the stack of the host.
+///
+/// The machine gets shallower and deeper many times during an evaluation, for instance each time
+/// it evaluates an argument passed by name. Starting in a segment of its own keeps it from
+/// crossing the end of the current segment each time, which allocates a new one.
+#[cfg(feature = "std")]
+pub(crate) fn segment<R>(f: impl FnOnce() -> R) -> R {
+    stacker::grow(SEGMENT, f)
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn segment<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
@@ -3,38 +3,118 @@
 //! This module contains all the types and functions related to Pijama's type system.
 //!
 //! In particular, this module exposes the `Ty` type which is the type representation used by the
-//! type-checker. There is also the `ty_check` module which contains all the logic related to
-//! type-checking.
-use std::fmt;
+//! type-checker and the `Scheme` type used for polymorphic types. There is also the `ty_check`
+//! module which contains all the logic related to type-checking.
+//!
+//! Types written as in the type annotations of a program, like `Int -> [Bool]`, can be turned
+//! into `Ty`s with the [`parse`] function.
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use pijama_ast::ty::Ty as TyAST;
+use crate::{
+    mir::lower_ty,
+    parser::{parse_ty, ParsingError},
+};
 
 mod result;
 mod ty_check;
+pub(crate) mod usefulness;
 
 pub use result::{TyError, TyResult};
-pub use ty_check::ty_check;
+pub use ty_check::{ty_check, ty_check_terms, ty_check_with_width, TyTable};
 
 /// A type used by the type-checker.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Ty {
     /// The type of booleans.
     Bool,
     /// The type of (signed) integers.
     Int,
+    /// The type of floating point numbers.
+    Float,
     /// The [unit type](https://en.wikipedia.org/wiki/Unit_type).
     Unit,
+    /// The type of characters.
+    Char,
+    /// The type of strings.
+    String,
+    /// The type of lists whose elements have the given type.
+    List(Box<Ty>),
+    /// The type of channels carrying values of the given type.
+    Chan(Box<Ty>),
+    /// The type of lazy expressions whose value has the given type.
+    Lazy(Box<Ty>),
     /// The type of functions between two types.
     Arrow(Box<Ty>, Box<Ty>),
     /// Type variable, used for unification.
     Var(usize),
+    /// The type of the values of an enum, identified by its name, with its type arguments.
+    Adt(String, Box<[Ty]>),
 }
 
 impl Ty {
+    /// Returns `true` if the type does not have type variables.
+    pub fn is_concrete(&self) -> bool {
+        match self {
+            Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String => true,
+            Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => ty.is_concrete(),
+            Ty::Adt(_, args) => args.iter().all(Ty::is_concrete),
+            Ty::Arrow(ty1, ty2) => ty1.is_concrete() && ty2.is_concrete(),
+            Ty::Var(_) => false,
+        }
+    }
+
+    /// Pushes the indices of the type variables inside the type to `vars`, without duplicates and
+    /// in the order they are found.
+    fn vars(&self, vars: &mut Vec<usize>) {
+        match self {
+            Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String => (),
+            Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => ty.vars(vars),
+            Ty::Adt(_, args) => {
+                for arg in args {
+                    arg.vars(vars);
+                }
+            }
+            Ty::Arrow(ty1, ty2) => {
+                ty1.vars(vars);
+                ty2.vars(vars);
+            }
+            Ty::Var(index) => {
+                if !vars.contains(index) {
+                    vars.push(*index);
+                }
+            }
+        }
+    }
+
+    /// Stores the indices of the `Ty::Var`s that appear inside a channel type in `vars`.
+    fn chan_vars(&self, vars: &mut Vec<usize>) {
+        match self {
+            Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String | Ty::Var(_) => (),
+            Ty::Chan(ty) => ty.vars(vars),
+            Ty::List(ty) | Ty::Lazy(ty) => ty.chan_vars(vars),
+            Ty::Adt(_, args) => {
+                for arg in args {
+                    arg.chan_vars(vars);
+                }
+            }
+            Ty::Arrow(ty1, ty2) => {
+                ty1.chan_vars(vars);
+                ty2.chan_vars(vars);
+            }
+        }
+    }
+
     /// Checks if the index of a `Ty::Var` is contained inside the type.
     fn contains(&self, index: usize) -> bool {
         match self {
-            Ty::Bool | Ty::Int | Ty::Unit => false,
+            Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String => false,
+            Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => ty.contains(index),
+            Ty::Adt(_, args) => args.iter().any(|arg| arg.contains(index)),
             Ty::Arrow(ty1, ty2) => ty1.contains(index) || ty2.contains(index),
             Ty::Var(inner) => *inner == index,
         }
@@ -47,7 +127,13 @@ impl fmt::Display for Ty {
         match self {
             Bool => write!(f, "Bool"),
             Int => write!(f, "Int"),
+            Float => write!(f, "Float"),
             Unit => write!(f, "Unit"),
+            Char => write!(f, "Char"),
+            Ty::String => write!(f, "String"),
+            List(ty) => write!(f, "[{}]", ty),
+            Chan(ty) => write!(f, "Chan({})", ty),
+            Lazy(ty) => write!(f, "Lazy({})", ty),
             Arrow(t1, t2) => {
                 if let Arrow(_, _) = t1.as_ref() {
                     write!(f, "({}) -> {}", t1, t2)
@@ -56,23 +142,67 @@ impl fmt::Display for Ty {
                 }
             }
             Var(index) => write!(f, "?X{}", index),
+            Adt(name, args) => {
+                write!(f, "{}", name)?;
+                if let Some((first, rest)) = args.split_first() {
+                    write!(f, "({}", first)?;
+                    for arg in rest {
+                        write!(f, ", {}", arg)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-impl Ty {
-    pub fn from_ast(ty_ast: TyAST) -> Option<Self> {
-        match ty_ast {
-            // FIXME: In general we should translate missing types into type variables inside
-            // `ty_check::Context`.
-            TyAST::Missing => None,
-            TyAST::Bool => Some(Ty::Bool),
-            TyAST::Int => Some(Ty::Int),
-            TyAST::Unit => Some(Ty::Unit),
-            TyAST::Arrow(t1, t2) => Some(Ty::Arrow(
-                Box::new(Ty::from_ast(*t1)?),
-                Box::new(Ty::from_ast(*t2)?),
-            )),
+/// Parses a type written as in the type annotations of a program.
+///
+/// Function types are right-associative, so `Int -> Int -> Bool` is the type of functions taking
+/// an `Int` and returning an `Int -> Bool`. Each type variable gets its own `Ty::Var`, numbered
+/// from zero in the order they are found, so `a -> b -> a` is `?X0 -> ?X1 -> ?X0`.
+///
+/// There can be any number of spaces and line breaks around the type. This function fails if the
+/// string is not a single type.
+pub fn parse(input: &str) -> Result<Ty, ParsingError> {
+    Ok(lower_ty(parse_ty(input)?.content))
+}
+
+/// A type scheme, which is a type where some type variables are universally quantified.
+///
+/// Schemes are the types of polymorphic functions. The function `fn id(x: a): a do x end` has the
+/// scheme `forall ?X0. ?X0 -> ?X0`, so it can be used with any type of argument. Each use of a
+/// polymorphic function gets a copy of its type where the quantified variables are replaced by new
+/// ones.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheme {
+    /// The indices of the quantified type variables.
+    pub vars: Vec<usize>,
+    /// The quantified type.
+    pub ty: Ty,
+}
+
+impl Scheme {
+    /// Returns a scheme without quantified variables.
+    pub fn mono(ty: Ty) -> Self {
+        Scheme {
+            vars: Vec::new(),
+            ty,
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.vars.is_empty() {
+            write!(f, "forall")?;
+            for index in &self.vars {
+                write!(f, " {}", Ty::Var(*index))?;
+            }
+            write!(f, ". ")?;
         }
+        write!(f, "{}", self.ty)
     }
 }
@@ -1,33 +1,155 @@
 //! Error and Result types related to type-checking.
 
-use thiserror::Error;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::{Display, Formatter, Result};
 
-use pijama_ast::{Located, Location};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use pijama_ast::{Int, IntWidth, Located, Location};
 
 use crate::ty::Ty;
 
 /// The type returned by methods and functions in this module.
-pub type TyResult<T = Ty> = Result<T, TyError>;
+pub type TyResult<T = Ty> = core::result::Result<T, TyError>;
 
 /// A typing error.
 ///
 /// Each variant here represents a reason why the type-checker could fail.
-#[derive(Error, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TyError {
-    /// Variant used when two types that should be equal are not.
-    #[error("Type mismatch: expected `{expected}`, found `{found}`")]
-    Mismatch { expected: Ty, found: Located<Ty> },
+    /// Variant used when two types that should be equal are not. The types are boxed to keep the
+    /// error small.
+    Mismatch {
+        expected: Box<Located<Ty>>,
+        found: Box<Located<Ty>>,
+    },
     /// Variant used when a name has not been binded to any type in the current scope.
-    #[error("Name `{0}` is not bounded")]
     Unbounded(Located<String>),
+    /// Variant used when a pattern does not have one name for each field of its variant.
+    FieldCount {
+        variant: Located<String>,
+        expected: usize,
+        found: usize,
+    },
+    /// Variant used when a match does not have an arm for some values of its scrutinee. It has
+    /// patterns for the missing values, located at the scrutinee.
+    NonExhaustive(Located<Vec<String>>),
+    /// Variant used when a name that is not bound by a mutable binding is assigned.
+    Immutable(Located<String>),
+    /// Variant used when a value interpolated in a string has a type that cannot be converted to
+    /// a string.
+    NotPrintable(Located<Ty>),
+    /// Variant used when the return type of a recursive function is not determined by its body
+    /// or its parameters. It has the name of the function.
+    AmbiguousReturn(Located<String>),
+    /// Variant used when an integer literal does not fit in the width the program is checked
+    /// with.
+    IntOutOfRange {
+        literal: Located<Int>,
+        width: IntWidth,
+    },
+}
+
+impl Display for TyError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            TyError::Mismatch { expected, found } => write!(
+                f,
+                "Type mismatch: expected `{}`, found `{}`",
+                expected, found
+            ),
+            TyError::Unbounded(name) => write!(f, "Name `{}` is not bounded", name),
+            TyError::FieldCount {
+                variant,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Variant `{}` has {} fields, but the pattern has {}",
+                variant, expected, found
+            ),
+            TyError::NonExhaustive(missing) => {
+                write!(f, "Match is not exhaustive, missing ")?;
+                fmt_patterns(f, &missing.content)
+            }
+            TyError::Immutable(name) => {
+                write!(f, "Name `{}` is not mutable and cannot be assigned", name)
+            }
+            TyError::NotPrintable(ty) => {
+                write!(f, "Values of type `{}` cannot be converted to a string", ty)
+            }
+            TyError::AmbiguousReturn(name) => write!(
+                f,
+                "Cannot infer the return type of the recursive function `{}`",
+                name
+            ),
+            TyError::IntOutOfRange { literal, width } => write!(
+                f,
+                "Integer literal `{}` does not fit in a {} `Int`",
+                literal, width
+            ),
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for TyError {}
+
 impl TyError {
     /// Returns the location of the error.
     pub fn loc(&self) -> Location {
         match self {
             TyError::Mismatch { found, .. } => found.loc,
             TyError::Unbounded(name) => name.loc,
+            TyError::FieldCount { variant, .. } => variant.loc,
+            TyError::NonExhaustive(missing) => missing.loc,
+            TyError::Immutable(name) => name.loc,
+            TyError::NotPrintable(ty) => ty.loc,
+            TyError::AmbiguousReturn(name) => name.loc,
+            TyError::IntOutOfRange { literal, .. } => literal.loc,
+        }
+    }
+
+    /// Returns the expected type of a mismatch, located where the expectation comes from.
+    ///
+    /// The location points to the annotation or term imposing the expected type. Returns `None`
+    /// for other errors, or if the origin of the expected type is unknown or is the location of
+    /// the error itself.
+    pub fn origin(&self) -> Option<&Located<Ty>> {
+        match self {
+            TyError::Mismatch { expected, found }
+                if expected.loc != found.loc && expected.loc.start != expected.loc.end =>
+            {
+                Some(expected.as_ref())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TyError::Mismatch { .. } => "E0002",
+            TyError::Unbounded(_) => "E0003",
+            TyError::FieldCount { .. } => "E0007",
+            TyError::NonExhaustive(_) => "E0008",
+            TyError::Immutable(_) => "E0010",
+            TyError::NotPrintable(_) => "E0011",
+            TyError::AmbiguousReturn(_) => "E0004",
+            TyError::IntOutOfRange { .. } => "E0023",
+        }
+    }
+}
+
+/// Writes `patterns` between backticks and separated by commas.
+fn fmt_patterns(f: &mut Formatter, patterns: &[String]) -> Result {
+    for (index, pattern) in patterns.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
         }
+        write!(f, "`{}`", pattern)?;
     }
+    Ok(())
 }
@@ -6,42 +6,144 @@
 //!
 //! The entry-point for this module is the `ty_check` method which does the type checking of a
 //! whole program. However, most of the heavy lifting is done by the `Context` and `Unifier` types.
-use pijama_ast::{BinOp, Literal, Located, Location, Name, Primitive, UnOp};
+//! The `ty_check_terms` method does the same, but it also returns the type of each term of the
+//! program in a [`TyTable`].
+//!
+//! Let bindings are polymorphic: the constraints collected so far are solved when a name is bound
+//! by a let binding, and the type variables of its type that do not appear in the types of the
+//! other names in scope are quantified. Each use of the name instantiates them with new type
+//! variables.
+//!
+//! Enums are also lexically scoped: the variants of an enum can only be constructed and matched
+//! in the term that follows its declaration. Enum types are compared by name, so an enum can have
+//! fields of its own type or of the type of an enum declared after it. This makes recursive types
+//! iso-recursive: constructing a variant folds a value into its enum type and matching unfolds it,
+//! so unification never finds an infinite type. Once the types are solved, every match must be
+//! exhaustive as explained in the [`usefulness`] module.
+//!
+//! [`usefulness`]: crate::ty::usefulness
+use pijama_ast::{
+    symbol::{Symbol, SymbolTable},
+    BinOp, IntWidth, Literal, Located, Location, Name, Pattern, Primitive, UnOp,
+};
 
-use std::collections::VecDeque;
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    string::ToString,
+    vec::Vec,
+};
 
 use crate::{
-    mir::{LetKind, Term},
-    ty::{Ty, TyError, TyResult},
+    mir::{LetKind, Term, Variant},
+    ty::{usefulness, Scheme, Ty, TyError, TyResult},
 };
 
+mod table;
 mod unify;
 
+pub use table::TyTable;
 use unify::{Constraint, Unifier};
 
 /// Function that type-checks a term and returns its type.
 ///
 /// This function must always be called in the "root" term of the program. Otherwise, the type
 /// checker might not have all the bindings required to do its job.
-pub fn ty_check(term: &Located<Term<'_>>) -> TyResult<Located<Ty>> {
+///
+/// The type checker does not stop at the first error: every error found is returned, in the order
+/// they were found.
+///
+/// Integer literals must fit in the default [`IntWidth`], see [`ty_check_with_width`].
+pub fn ty_check(term: &Located<Term<'_>>) -> Result<Located<Ty>, Vec<TyError>> {
+    ty_check_with_width(term, IntWidth::default())
+}
+
+/// Function that type-checks a term like [`ty_check`] for a machine whose integers have `width`.
+///
+/// Integer literals that do not fit in `width` are reported as [`TyError::IntOutOfRange`].
+pub fn ty_check_with_width(
+    term: &Located<Term<'_>>,
+    width: IntWidth,
+) -> Result<Located<Ty>, Vec<TyError>> {
+    check(term, width).map(|table| table.ty().clone())
+}
+
+/// Function that type-checks a term and returns the type of each one of its subterms.
+///
+/// This does the same checks as [`ty_check`], but the types inferred for the terms inside the
+/// program are kept in a [`TyTable`] instead of being discarded, so later passes do not have to
+/// infer them again.
+pub fn ty_check_terms<'t, 'a>(
+    term: &'t Located<Term<'a>>,
+) -> Result<TyTable<'t, 'a>, Vec<TyError>> {
+    check(term, IntWidth::default())
+}
+
+/// Type-checks `term` with integers of `width`, keeping the types of its subterms.
+fn check<'t, 'a>(
+    term: &'t Located<Term<'a>>,
+    width: IntWidth,
+) -> Result<TyTable<'t, 'a>, Vec<TyError>> {
     // Create a new, empty context.
-    let mut ctx = Context::default();
+    let mut ctx = Context {
+        int_width: width,
+        ..Context::default()
+    };
     // Obtain typing constraints and the type of `term`.
-    let mut ty = ctx.type_of(&term)?;
-    // Solve the constraints using unification.
-    let unif = Unifier::from_ctx(ctx)?;
-    // Apply the substitutions found during unification over the type of `term`.
-    unif.replace(&mut ty.content);
-    Ok(ty)
+    let mut ty = ctx.type_of(&term);
+    // Solve the remaining constraints using unification.
+    ctx.solve();
+    if !ctx.errors.is_empty() {
+        // The errors of the constraints are found after the other ones, so they are sorted to
+        // be reported in the order they appear in the source code.
+        ctx.errors.sort_by_key(|error| {
+            let loc = error.loc();
+            (loc.file, loc.start, loc.end)
+        });
+        return Err(ctx.errors);
+    }
+    // Check that every match has an arm for each value of its scrutinee. This requires the term
+    // to be well-typed.
+    let errors: Vec<_> = usefulness::coverage(term)
+        .into_iter()
+        .filter(|coverage| !coverage.missing.is_empty())
+        .map(|coverage| TyError::NonExhaustive(coverage.loc.with_content(coverage.missing)))
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    // Apply the substitutions found during unification over the type of `term` and the types of
+    // its subterms.
+    ctx.unifier.replace(&mut ty.content);
+    let mut tys = BTreeMap::new();
+    for (term, mut ty) in ctx.tys {
+        ctx.unifier.replace(&mut ty);
+        tys.insert(term, ty);
+    }
+    Ok(TyTable::new(term, ty, tys))
 }
 
 /// A type binding.
 ///
-/// This represents the binding of a `Name` to a type and is used inside the type-checker to encode
-/// that a variable has a type in the current scope.
-struct TyBinding<'a> {
-    name: Name<'a>,
+/// This represents the binding of an interned `Name` to a type scheme and is used inside the
+/// type-checker to encode that a variable has a type in the current scope.
+struct TyBinding {
+    name: Symbol,
+    scheme: Scheme,
+    /// Whether the name can be assigned.
+    mutable: bool,
+}
+
+/// A variant binding.
+///
+/// This represents the binding of an interned variant name to the type of its enum and the types
+/// of its fields. The type variables in `params` are the type parameters of the enum, which are
+/// replaced by new ones each time the variant is used.
+struct VariantBinding {
+    name: Symbol,
     ty: Ty,
+    fields: Vec<Ty>,
+    params: Vec<usize>,
 }
 
 /// A typing context.
@@ -56,17 +158,37 @@ struct Context<'a> {
     ///
     /// Ever time a new binding is done via an abstraction or let binding term it is required to push
     /// that binding into this stack, and pop it after traversing the term.
-    inner: Vec<TyBinding<'a>>,
+    inner: Vec<TyBinding>,
+    /// Stack for the variants of the enums declared in the current scope.
+    variants: Vec<VariantBinding>,
+    /// Symbols of the names bound in the term.
+    ///
+    /// Names are interned when they are bound, so looking up a variable compares symbols instead
+    /// of strings.
+    symbols: SymbolTable<'a>,
     /// Number of created type variables.
     ///
     /// Every time a new variable is created with the `new_ty` method, this number is increased to
     /// guarantee all type variables are different.
     count: usize,
+    /// The type variable created for each type variable of the type annotations in the MIR.
+    annotations: BTreeMap<usize, Ty>,
     /// Typing constraints.
     ///
     /// Each typing constraint is introduced by a particular `type_of_*` method with a suitable
     /// location in case an error needs to be returned.
     constraints: VecDeque<Located<Constraint>>,
+    /// Substitutions found by solving the constraints collected so far.
+    unifier: Unifier,
+    /// Type errors found so far.
+    ///
+    /// Typing continues after an error, as explained in the `type_of` method, so every error in
+    /// the term can be reported at once.
+    errors: Vec<TyError>,
+    /// The type found for each term typed so far, identified by its address.
+    tys: Vec<(*const Term<'a>, Ty)>,
+    /// The width integer literals must fit in.
+    int_width: IntWidth,
 }
 
 impl<'a> Context<'a> {
@@ -79,15 +201,92 @@ impl<'a> Context<'a> {
         ty
     }
 
+    /// Returns the type of a type annotation taken from the MIR.
+    ///
+    /// Each type variable of the annotation is replaced by a new one the first time it is found
+    /// and by the same one afterwards. This way, the type variables of the annotations of a
+    /// definition stay linked, and they do not clash with the ones created by the `new_ty` method.
+    fn annotation_ty(&mut self, ty: &Ty) -> Ty {
+        match ty {
+            Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String => ty.clone(),
+            Ty::List(ty) => Ty::List(Box::new(self.annotation_ty(ty))),
+            Ty::Chan(ty) => Ty::Chan(Box::new(self.annotation_ty(ty))),
+            Ty::Lazy(ty) => Ty::Lazy(Box::new(self.annotation_ty(ty))),
+            Ty::Adt(name, args) => Ty::Adt(
+                name.clone(),
+                args.iter().map(|arg| self.annotation_ty(arg)).collect(),
+            ),
+            Ty::Arrow(ty1, ty2) => Ty::Arrow(
+                Box::new(self.annotation_ty(ty1)),
+                Box::new(self.annotation_ty(ty2)),
+            ),
+            Ty::Var(index) => match self.annotations.get(index) {
+                Some(ty) => ty.clone(),
+                None => {
+                    let ty = self.new_ty();
+                    self.annotations.insert(*index, ty.clone());
+                    ty
+                }
+            },
+        }
+    }
+
+    /// Returns the type of a scheme with each quantified variable replaced by a new one.
+    fn instantiate(&mut self, scheme: &Scheme) -> Ty {
+        let mut ty = scheme.ty.clone();
+        for index in &scheme.vars {
+            let new = self.new_ty();
+            replace_var(&mut ty, *index, &new);
+        }
+        ty
+    }
+
+    /// Solves the constraints collected so far.
+    ///
+    /// The constraints that cannot be satisfied are added to the errors of the context.
+    fn solve(&mut self) {
+        let constraints = core::mem::take(&mut self.constraints);
+        self.unifier.solve(constraints, &mut self.errors);
+    }
+
+    /// Returns the scheme of the type of a let binding.
+    ///
+    /// This solves the constraints collected so far and quantifies the type variables of `ty`
+    /// that are not in the type of any name in scope. Those variables can only be constrained
+    /// by the term bound to the name, so they can be replaced freely at each use of the name.
+    fn generalize(&mut self, mut ty: Ty) -> Scheme {
+        self.solve();
+        self.unifier.replace(&mut ty);
+
+        let mut env_vars = Vec::new();
+        for bind in &self.inner {
+            let mut bind_ty = bind.scheme.ty.clone();
+            self.unifier.replace(&mut bind_ty);
+            let mut vars = Vec::new();
+            bind_ty.vars(&mut vars);
+            env_vars.extend(
+                vars.into_iter()
+                    .filter(|index| !bind.scheme.vars.contains(index)),
+            );
+        }
+
+        let mut vars = Vec::new();
+        ty.vars(&mut vars);
+        vars.retain(|index| !env_vars.contains(index));
+        Scheme { vars, ty }
+    }
+
     /// Adds a new `Constraint`.
     ///
     /// A new constraint must be added when it is required to enforce an specific typing rule.
     /// Calling this method will not enforce the rule instantly. It only stores the restriction
-    /// inside the `Context` to be solved by the `Unifier` in a posterior stage. This constraint
-    /// has a location that will be used as the location of the error if the constraint is
-    /// impossible to satisfy.
-    pub fn add_constraint(&mut self, expected: Ty, found: Ty, loc: Location) {
-        let constr = Constraint::new(expected, found);
+    /// inside the `Context` to be solved by the `Unifier` in a posterior stage. The location of
+    /// `found` will be used as the location of the error if the constraint is impossible to
+    /// satisfy, and the location of `expected` points to the term or annotation imposing the
+    /// expected type.
+    pub fn add_constraint(&mut self, expected: Located<Ty>, found: Located<Ty>) {
+        let loc = found.loc;
+        let constr = Constraint::new(expected.content, found.content, expected.loc);
         // New constraints are front-pushed because the `Unifier` processes constraints by popping
         // them from the back. If we just back-push the constraints, we end up taking care of the
         // newer constraints first, which are more complex and can end up in less readable type
@@ -103,12 +302,32 @@ impl<'a> Context<'a> {
     ///
     /// Typing variables can appear in the type returned by this method (and any other type_of_*
     /// method) as unification has not taken place yet.
-    fn type_of(&mut self, term: &Located<Term<'a>>) -> TyResult<Located<Ty>> {
+    ///
+    /// If the term cannot be typed, the error is added to the errors of the context and the term
+    /// gets a new type variable instead. This variable can be unified with any type, so it does
+    /// not cause more errors and the rest of the program can still be checked.
+    fn type_of(&mut self, term: &Located<Term<'a>>) -> Located<Ty> {
+        let ty = match self.try_type_of(term) {
+            Ok(ty) => ty,
+            Err(error) => {
+                self.errors.push(error);
+                term.loc.with_content(self.new_ty())
+            }
+        };
+        self.tys.push((&term.content, ty.content.clone()));
+        ty
+    }
+
+    /// Returns the type of a term or the error that prevents typing it.
+    fn try_type_of(&mut self, term: &Located<Term<'a>>) -> TyResult<Located<Ty>> {
         let loc = term.loc;
         match &term.content {
             Term::Lit(lit) => self.type_of_lit(loc, lit),
+            Term::List(elems) => self.type_of_list(loc, elems),
             Term::Var(name) => self.type_of_var(loc, name),
-            Term::Abs(name, ty, body) => self.type_of_abs(loc, *name, ty, body.as_ref()),
+            Term::Abs(name, ty, body) => self.type_of_abs(loc, name.content, ty, body.as_ref()),
+            Term::Ascription(term, ty) => self.type_of_ascription(loc, term.as_ref(), ty),
+            Term::Lazy(term) => self.type_of_lazy(loc, term.as_ref()),
             Term::UnaryOp(op, term) => self.type_of_unary_op(loc, *op, term.as_ref()),
             Term::BinaryOp(op, t1, t2) => {
                 self.type_of_binary_op(loc, *op, t1.as_ref(), t2.as_ref())
@@ -117,42 +336,88 @@ impl<'a> Context<'a> {
             Term::Let(kind, name, t1, t2) => {
                 self.type_of_let(loc, kind, name, t1.as_ref(), t2.as_ref())
             }
+            Term::LetRec(fns, term) => self.type_of_let_rec(loc, fns, term),
             Term::Cond(t1, t2, t3) => self.type_of_cond(loc, t1.as_ref(), t2.as_ref(), t3.as_ref()),
             Term::Seq(t1, t2) => self.type_of_seq(loc, t1.as_ref(), t2.as_ref()),
+            Term::Assign(name, term) => self.type_of_assign(loc, name, term.as_ref()),
             Term::PrimFn(prim) => self.type_of_prim_fn(loc, *prim),
+            Term::Native(_, ty) => Ok(loc.with_content(ty.clone())),
+            Term::Enum(name, variants, term) => self.type_of_enum(loc, name, variants, term),
+            Term::Constructor(name) => self.type_of_constructor(loc, name),
+            Term::Match(term, arms) => self.type_of_match(loc, term, arms),
         }
     }
 
     /// Returns the type of a literal.
     ///
     /// This rule does not add new constraints because the type of a literal is only decided by its
-    /// variant.
+    /// variant. Integers that do not fit in the width of the context are reported, but they still
+    /// have type `Int` so the rest of the term can be checked.
     fn type_of_lit(&mut self, loc: Location, lit: &Literal) -> TyResult<Located<Ty>> {
         let ty = match lit {
             Literal::Unit => Ty::Unit,
             Literal::Bool(_) => Ty::Bool,
-            Literal::Number(_) => Ty::Int,
+            Literal::Number(n) => {
+                if !self.int_width.contains(*n) {
+                    self.errors.push(TyError::IntOutOfRange {
+                        literal: loc.with_content(*n),
+                        width: self.int_width,
+                    });
+                }
+                Ty::Int
+            }
+            Literal::Float(_) => Ty::Float,
+            Literal::Char(_) => Ty::Char,
+            Literal::Str(_) => Ty::String,
         };
         Ok(loc.with_content(ty))
     }
 
+    /// Returns the type of a list literal.
+    ///
+    /// Typing a list literal requires that all its elements have the same type `T`, so a
+    /// constraint is added for each element after the first one. The returned type is `[T]`. If
+    /// the list is empty, `T` is a new type variable that is inferred from the uses of the list.
+    fn type_of_list(
+        &mut self,
+        loc: Location,
+        elems: &[Located<Term<'a>>],
+    ) -> TyResult<Located<Ty>> {
+        let mut elem_ty: Option<Located<Ty>> = None;
+        for elem in elems {
+            let ty = self.type_of(elem);
+            match &elem_ty {
+                Some(elem_ty) => self.add_constraint(elem_ty.clone(), ty),
+                None => elem_ty = Some(ty),
+            }
+        }
+        let ty = match elem_ty {
+            Some(elem_ty) => elem_ty.content,
+            None => self.new_ty(),
+        };
+        Ok(loc.with_content(Ty::List(Box::new(ty))))
+    }
+
     /// Returns the type of a variable.
     ///
     /// To type a variable, it must have been binded beforehand using a let binding or an
     /// abstraction and added to the context. If the variable is not in the current context, this
     /// method returns an error stating that the variable is unbounded.
     ///
+    /// If the variable was bound to a polymorphic type, its quantified type variables are
+    /// replaced by new ones.
+    ///
     /// This rule does not add new constraints because the type of a variable is decided by the
     /// bindings done in the current scope.
     fn type_of_var(&mut self, loc: Location, name: &Name<'a>) -> TyResult<Located<Ty>> {
-        let ty = self
-            .inner
-            .iter()
-            .find(|bind| bind.name == *name)
+        let scheme = self
+            .symbols
+            .get(*name)
+            .and_then(|symbol| self.inner.iter().rev().find(|bind| bind.name == symbol))
             .ok_or_else(|| TyError::Unbounded(loc.with_content(name.0.to_string())))?
-            .ty
+            .scheme
             .clone();
-        Ok(loc.with_content(ty))
+        Ok(loc.with_content(self.instantiate(&scheme)))
     }
 
     /// Returns the type of an abstraction.
@@ -160,7 +425,8 @@ impl<'a> Context<'a> {
     /// To type an abstraction, we need to add the binding done by the abstraction to the current
     /// context and then type its body. If the body can be typed successfully, the type of the
     /// abstraction is `T` -> `U` where `T` is the type of the binding and `U` the type of the
-    /// body.
+    /// body. If the parameter does not have a type annotation, `T` is a new type variable that is
+    /// inferred from the uses of the parameter.
     ///
     /// Afterwards we need to remove the binding from the context because that binding is only
     /// valid inside the body of the function (lexical scoping). This function panics if it's not
@@ -176,21 +442,51 @@ impl<'a> Context<'a> {
         ty: &Ty,
         body: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
+        let name = self.symbols.intern(name);
+        let scheme = Scheme::mono(self.annotation_ty(ty));
         self.inner.push(TyBinding {
             name,
-            ty: ty.clone(),
+            scheme,
+            mutable: false,
         });
-        let ty = self.type_of(body)?;
+        let ty = self.type_of(body);
         let bind = self.inner.pop().unwrap();
 
-        Ok(ty.map(|ty| Ty::Arrow(Box::new(bind.ty), Box::new(ty))))
+        Ok(ty.map(|ty| Ty::Arrow(Box::new(bind.scheme.ty), Box::new(ty))))
+    }
+
+    /// Returns the type of a type ascription.
+    ///
+    /// This rule adds a constraint stating that the type of the term must match the ascribed type,
+    /// which is also the returned type. Type variables in the ascription are handled like the ones
+    /// in any other annotation.
+    fn type_of_ascription(
+        &mut self,
+        loc: Location,
+        term: &Located<Term<'a>>,
+        ty: &Located<Ty>,
+    ) -> TyResult<Located<Ty>> {
+        let ty1 = self.type_of(term);
+        let ty = ty.loc.with_content(self.annotation_ty(&ty.content));
+        self.add_constraint(ty.clone(), ty1);
+        Ok(loc.with_content(ty.content))
+    }
+
+    /// Returns the type of a lazy term.
+    ///
+    /// If the term has type `T`, the lazy term has type `Lazy(T)`.
+    fn type_of_lazy(&mut self, loc: Location, term: &Located<Term<'a>>) -> TyResult<Located<Ty>> {
+        let ty = self.type_of(term);
+        Ok(loc.with_content(Ty::Lazy(Box::new(ty.content))))
     }
 
     /// Returns the type of an unary operation.
     ///
     /// The type of an unary operation depends on its operator:
-    /// - If it is a negation, the operand must have type `Int`.
+    /// - If it is a negation, the operand must have type `Int` or `Float`, as decided by the
+    ///   `operand_ty` method.
     /// - If it is a logical not, the operand must have type `Bool`.
+    /// - If it is a bitwise not, the operand must have type `Int`.
     ///
     /// This rule adds a constraint stating that the type of the operand must match one of the
     /// types stated above. The returned type is the same type as the operand.
@@ -200,25 +496,29 @@ impl<'a> Context<'a> {
         op: UnOp,
         term: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        let ty = self.type_of(term)?.content;
+        let ty = self.type_of(term);
         let expected = match op {
-            UnOp::Neg => Ty::Int,
+            UnOp::Neg => self.operand_ty(&[&ty], &[Ty::Float]),
             UnOp::Not => Ty::Bool,
+            UnOp::BitNot => Ty::Int,
         };
-        self.add_constraint(expected, ty.clone(), loc);
-        Ok(loc.with_content(ty))
+        self.add_constraint(loc.with_content(expected), ty.clone());
+        Ok(loc.with_content(ty.content))
     }
 
     /// Returns the type of an binary operation.
     ///
     /// The type of a binary operation depends on its operator:
-    /// - If it is an arithmetic operator, the operands must have type `Int`.
+    /// - If it is `+`, `-`, `*`, `/` or `%`, the operands must both have type `Int` or both have
+    ///   type `Float`, as decided by the `operand_ty` method.
+    /// - If it is a bitwise operator, the operands must have type `Int`.
     /// - If it is a logic operator, the operands must have type `Bool`.
     /// - If it is `Eq` or `Neq`, the operands must have the same type.
-    /// - If it is any other comparison operator, the operands must have type `Bool`.
+    /// - If it is any other comparison operator, the operands must both have type `Int`, `Float`
+    ///   or `Char`.
     ///
     /// This rule adds one of the constraints stated above. The returned type is `Bool`, unless the
-    /// operation is an arithmetic operation, which has type `Int`.
+    /// operation is an arithmetic operation, which has the type of its operands.
     fn type_of_binary_op(
         &mut self,
         loc: Location,
@@ -226,40 +526,74 @@ impl<'a> Context<'a> {
         t1: &Located<Term<'a>>,
         t2: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        let ty1 = self.type_of(t1)?;
-        let ty2 = self.type_of(t2)?;
+        let ty1 = self.type_of(t1);
+        let ty2 = self.type_of(t2);
         let ty = match op {
-            BinOp::Add
-            | BinOp::Sub
-            | BinOp::Mul
-            | BinOp::Div
-            | BinOp::Rem
-            | BinOp::BitAnd
-            | BinOp::BitOr
-            | BinOp::BitXor
-            | BinOp::Shr
-            | BinOp::Shl => {
-                self.add_constraint(Ty::Int, ty1.content, ty1.loc);
-                self.add_constraint(Ty::Int, ty2.content, ty2.loc);
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Rem => {
+                let ty = self.operand_ty(&[&ty1, &ty2], &[Ty::Float]);
+                self.add_operand_constraints(loc.with_content(ty.clone()), ty1, ty2);
+                ty
+            }
+            BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shr | BinOp::Shl => {
+                self.add_operand_constraints(loc.with_content(Ty::Int), ty1, ty2);
                 Ty::Int
             }
             BinOp::Or | BinOp::And => {
-                self.add_constraint(Ty::Bool, ty1.content, ty1.loc);
-                self.add_constraint(Ty::Bool, ty2.content, ty2.loc);
+                self.add_operand_constraints(loc.with_content(Ty::Bool), ty1, ty2);
                 Ty::Bool
             }
             BinOp::Lt | BinOp::Gt | BinOp::Lte | BinOp::Gte => {
-                self.add_constraint(Ty::Int, ty1.content, ty1.loc);
-                self.add_constraint(Ty::Int, ty2.content, ty2.loc);
+                let ty = self.operand_ty(&[&ty1, &ty2], &[Ty::Float, Ty::Char]);
+                self.add_operand_constraints(loc.with_content(ty), ty1, ty2);
                 Ty::Bool
             }
             BinOp::Eq | BinOp::Neq => {
-                self.add_constraint(ty1.content, ty2.content, ty2.loc);
+                self.add_constraint(ty1, ty2);
                 Ty::Bool
             }
+            BinOp::Pipe => unreachable!("Pipes are lowered to applications"),
         };
         Ok(loc.with_content(ty))
     }
+
+    /// Adds the constraints stating that both operands of a binary operation have the `expected`
+    /// type.
+    ///
+    /// If both operands are already known to have the same type, a single constraint is added, so
+    /// an operation like `"a" + "b"` is reported once instead of once per operand.
+    fn add_operand_constraints(
+        &mut self,
+        expected: Located<Ty>,
+        ty1: Located<Ty>,
+        ty2: Located<Ty>,
+    ) {
+        let (mut content1, mut content2) = (ty1.content.clone(), ty2.content.clone());
+        self.unifier.replace(&mut content1);
+        self.unifier.replace(&mut content2);
+        self.add_constraint(expected.clone(), ty1);
+        if content1 != content2 {
+            self.add_constraint(expected, ty2);
+        }
+    }
+
+    /// Returns the type that the operands of an overloaded operator must have.
+    ///
+    /// Overloaded operators work with integers and with the `others` types, but all the operands
+    /// must have the same type. This type is the first of `others` that is the type of an operand
+    /// and `Int` otherwise. If the type of an operand is a type variable, the constraints collected
+    /// so far are solved to find out if its type is already known.
+    fn operand_ty(&mut self, operands: &[&Located<Ty>], others: &[Ty]) -> Ty {
+        let mut tys: Vec<Ty> = operands.iter().map(|ty| ty.content.clone()).collect();
+        if tys.iter().any(|ty| matches!(ty, Ty::Var(_))) {
+            self.solve();
+            for ty in &mut tys {
+                self.unifier.replace(ty);
+            }
+        }
+        let ty = others.iter().find(|other| tys.contains(other));
+        ty.cloned().unwrap_or(Ty::Int)
+    }
+
     /// Returns the type of an application.
     ///
     /// If an application is well-typed, there must exist a type `X` such that the first term has
@@ -273,19 +607,82 @@ impl<'a> Context<'a> {
         t1: &Located<Term<'a>>,
         t2: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        let ty1 = self.type_of(t1)?.content;
-        let ty2 = self.type_of(t2)?;
+        match t1.content {
+            Term::PrimFn(Primitive::ToString) => return self.type_of_to_string(loc, t1, t2),
+            Term::PrimFn(Primitive::Len) => return self.type_of_len(loc, t1, t2),
+            _ => (),
+        }
+        let ty1 = self.type_of(t1);
+        let ty2 = self.type_of(t2);
         let ty = self.new_ty();
 
         self.add_constraint(
             ty1,
-            Ty::Arrow(Box::new(ty2.content), Box::new(ty.clone())),
-            ty2.loc,
+            ty2.map(|ty2| Ty::Arrow(Box::new(ty2), Box::new(ty.clone()))),
         );
 
         Ok(loc.with_content(ty))
     }
 
+    /// Returns the type of an application of the `to_string` primitive.
+    ///
+    /// The argument must have type `Int`, `Float`, `Bool`, `Char`, `String` or `Unit`, otherwise
+    /// this method returns an error stating that it cannot be converted to a string. If the type
+    /// of the argument is a type variable, the constraints collected so far are solved to find out
+    /// if its type is already known, and it must be `Int` if it is not, like for the operands of
+    /// overloaded operators. The returned type is `String`.
+    fn type_of_to_string(
+        &mut self,
+        loc: Location,
+        prim: &Located<Term<'a>>,
+        term: &Located<Term<'a>>,
+    ) -> TyResult<Located<Ty>> {
+        let ty = self.type_of(term);
+        let prim_ty = Ty::Arrow(Box::new(ty.content.clone()), Box::new(Ty::String));
+        self.tys.push((&prim.content, prim_ty));
+        let mut content = ty.content.clone();
+        if let Ty::Var(_) = content {
+            self.solve();
+            self.unifier.replace(&mut content);
+        }
+        match content {
+            Ty::Int | Ty::Float | Ty::Bool | Ty::Char | Ty::String | Ty::Unit => (),
+            Ty::Var(_) => self.add_constraint(term.loc.with_content(Ty::Int), ty),
+            content => return Err(TyError::NotPrintable(ty.loc.with_content(content))),
+        }
+        Ok(loc.with_content(Ty::String))
+    }
+
+    /// Returns the type of an application of the `len` primitive.
+    ///
+    /// The argument can be a string. Otherwise, it must be a list like when `len` is not applied.
+    /// If the type of the argument is a type variable, the constraints collected so far are solved
+    /// to find out if it is already known to be `String`. The returned type is `Int`.
+    fn type_of_len(
+        &mut self,
+        loc: Location,
+        prim: &Located<Term<'a>>,
+        term: &Located<Term<'a>>,
+    ) -> TyResult<Located<Ty>> {
+        let ty = self.type_of(term);
+        let mut content = ty.content.clone();
+        if let Ty::Var(_) = content {
+            self.solve();
+            self.unifier.replace(&mut content);
+        }
+        if content == Ty::String {
+            let prim_ty = Ty::Arrow(Box::new(Ty::String), Box::new(Ty::Int));
+            self.tys.push((&prim.content, prim_ty));
+        } else {
+            let prim_ty = self.type_of(prim);
+            self.add_constraint(
+                prim_ty,
+                ty.map(|ty| Ty::Arrow(Box::new(ty), Box::new(Ty::Int))),
+            );
+        }
+        Ok(loc.with_content(Ty::Int))
+    }
+
     /// Returns the type of a let binding.
     ///
     /// Typing a let binding requires adding a type binding for the name in the context. The name
@@ -297,7 +694,18 @@ impl<'a> Context<'a> {
     ///
     /// If the let binding is recursive. A type binding with the name and the type provided by the
     /// annotation is added to the context before inferring any type in order to guarantee that the
-    /// name of the let binding will be in scope.
+    /// name of the let binding will be in scope. The type of the first term must also coincide with
+    /// the annotation. This binding is not polymorphic, so recursive calls must use the same types.
+    /// The return type in the annotation is a type variable if the user did not write it. The
+    /// first term must constrain that type, as explained in [`check_return_ty`].
+    ///
+    /// The name is binded to the generalization of the type of the first term while typing the
+    /// second term, so it can be used with different types there.
+    ///
+    /// If the let binding is mutable, the name is binded to the type of the first term without
+    /// generalizing it, and it can be assigned while typing the second term. Unless the first
+    /// term is a function, the types inside the channel types of the first term are not
+    /// generalized either.
     ///
     /// Like when typing abstractions, the type binding added to the context must be removed to
     /// avoid leaking the binding to the outer scopes. This function returns an error if it is not
@@ -310,42 +718,138 @@ impl<'a> Context<'a> {
         t1: &Located<Term<'a>>,
         t2: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        match kind {
-            LetKind::NonRec(opt_ty) => {
-                let ty1 = self.type_of(t1)?;
+        let symbol = self.symbols.intern(name.content);
+        let ty1 = match kind {
+            LetKind::NonRec(opt_ty) | LetKind::Mut(opt_ty) => {
+                let ty1 = self.type_of(t1);
 
                 if let Some(ty) = opt_ty {
-                    self.add_constraint(ty.content.clone(), ty1.content.clone(), ty1.loc);
+                    let ty = ty.loc.with_content(self.annotation_ty(&ty.content));
+                    self.add_constraint(ty, ty1.clone());
                 }
 
-                self.inner.push(TyBinding {
-                    name: name.content,
-                    ty: ty1.content,
-                });
+                ty1
             }
             LetKind::Rec(ty) => {
+                let ty = ty.loc.with_content(self.annotation_ty(&ty.content));
                 self.inner.push(TyBinding {
-                    name: name.content,
-                    ty: ty.content.clone(),
+                    name: symbol,
+                    scheme: Scheme::mono(ty.content.clone()),
+                    mutable: false,
                 });
 
-                // FIXME: Should a recursive function match the type of its signature? It is even
-                // possible that the body has a different type and still satisfies all the
-                // constraints?
-                self.type_of(t1)?;
+                // The body must have the type of the annotation. Otherwise, the types of
+                // parameters without annotations would not be linked to the ones in the
+                // annotation.
+                let ty1 = self.type_of(t1);
+                self.add_constraint(ty, ty1.clone());
+                self.inner.pop().unwrap();
+
+                ty1
+            }
+        };
+
+        // Mutable bindings are not generalized, otherwise values of different types could be
+        // assigned to them.
+        let mutable = matches!(kind, LetKind::Mut(_));
+        let scheme = if mutable {
+            Scheme::mono(ty1.content)
+        } else {
+            let mut scheme = self.generalize(ty1.content);
+            // The types of the values sent to a channel are not generalized either, otherwise
+            // values of different types could be sent to it. Functions create their channels each
+            // time they are called, so their types are still generalized.
+            if !matches!(t1.content, Term::Abs(..)) {
+                let mut chan_vars = Vec::new();
+                scheme.ty.chan_vars(&mut chan_vars);
+                scheme.vars.retain(|index| !chan_vars.contains(index));
             }
+            scheme
         };
+        if let LetKind::Rec(_) = kind {
+            if let Err(error) = check_return_ty(name, t1, &scheme) {
+                self.errors.push(error);
+            }
+        }
+        self.inner.push(TyBinding {
+            name: symbol,
+            scheme,
+            mutable,
+        });
 
-        let ty2 = self.type_of(t2)?.content;
+        let ty2 = self.type_of(t2).content;
         self.inner.pop().unwrap();
         Ok(Located::new(ty2, loc))
     }
 
+    /// Returns the type of a group of mutually recursive functions.
+    ///
+    /// Each function is bound to the type of its annotation while typing the bodies of all of
+    /// them, and each body must have the type of its annotation. Then, each function is bound to
+    /// the generalization of its type while typing the term that follows the group, which is the
+    /// returned type.
+    fn type_of_let_rec(
+        &mut self,
+        loc: Location,
+        fns: &[(Located<Name<'a>>, Located<Ty>, Located<Term<'a>>)],
+        term: &Located<Term<'a>>,
+    ) -> TyResult<Located<Ty>> {
+        let mut names = Vec::new();
+        let mut tys = Vec::new();
+        for (name, ty, _) in fns {
+            let name = self.symbols.intern(name.content);
+            let ty = ty.loc.with_content(self.annotation_ty(&ty.content));
+            self.inner.push(TyBinding {
+                name,
+                scheme: Scheme::mono(ty.content.clone()),
+                mutable: false,
+            });
+            names.push(name);
+            tys.push(ty);
+        }
+
+        let mut body_tys = Vec::new();
+        for (ty, (_, _, body)) in tys.into_iter().zip(fns) {
+            let body_ty = self.type_of(body);
+            self.add_constraint(ty, body_ty.clone());
+            body_tys.push(body_ty);
+        }
+        for _ in fns {
+            self.inner.pop().unwrap();
+        }
+
+        for ((name, body_ty), (fn_name, _, body)) in names.into_iter().zip(body_tys).zip(fns) {
+            let scheme = self.generalize(body_ty.content);
+            if let Err(error) = check_return_ty(fn_name, body, &scheme) {
+                self.errors.push(error);
+            }
+            self.inner.push(TyBinding {
+                name,
+                scheme,
+                mutable: false,
+            });
+        }
+
+        let ty = self.type_of(term).content;
+        for _ in fns {
+            self.inner.pop().unwrap();
+        }
+        Ok(Located::new(ty, loc))
+    }
+
     /// Returns the type of a conditional.
     ///
     /// Typing a conditional requires that the condition has type `Bool` and that both branches
     /// have the same type, both constraints are added accordingly. The returned type is the one of
     /// the first branch.
+    ///
+    /// If the second branch is `unit`, which is the case for conditionals without an `else`
+    /// block, the first branch is the one expected to have type `Unit`.
+    ///
+    /// The expected type of the condition is located at the code between the start of the
+    /// conditional and the condition, which is the `if` or `while` keyword. Conditionals lowered
+    /// from `elif` branches start at their condition, so their expected type is located at the
+    /// condition itself.
     fn type_of_cond(
         &mut self,
         loc: Location,
@@ -353,14 +857,23 @@ impl<'a> Context<'a> {
         t2: &Located<Term<'a>>,
         t3: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        let ty1 = self.type_of(t1)?;
-        let ty2 = self.type_of(t2)?.content;
-        let ty3 = self.type_of(t3)?;
+        let ty1 = self.type_of(t1);
+        let ty2 = self.type_of(t2);
+        let ty3 = self.type_of(t3);
 
-        self.add_constraint(Ty::Bool, ty1.content, ty1.loc);
-        self.add_constraint(ty2.clone(), ty3.content, ty3.loc);
+        let keyword = if t1.loc.start > loc.start {
+            Location::new(loc.start, t1.loc.start)
+        } else {
+            ty1.loc
+        };
+        self.add_constraint(keyword.with_content(Ty::Bool), ty1);
+        if let Term::Lit(Literal::Unit) = t3.content {
+            self.add_constraint(ty3, ty2.clone());
+        } else {
+            self.add_constraint(ty2.clone(), ty3);
+        }
 
-        Ok(loc.with_content(ty2))
+        Ok(loc.with_content(ty2.content))
     }
 
     /// Returns the type of a sequence.
@@ -374,11 +887,38 @@ impl<'a> Context<'a> {
         t1: &Located<Term<'a>>,
         t2: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        let ty1 = self.type_of(t1)?;
-        self.add_constraint(Ty::Unit, ty1.content, ty1.loc);
+        let ty1 = self.type_of(t1);
+        self.add_constraint(ty1.loc.with_content(Ty::Unit), ty1);
         // FIXME: this is the only method that doesn't use the location of the Term to reflect its
         // own location. If we can this, all the `type_of_*` methods could return `TyResult<Ty>`
-        self.type_of(t2)
+        Ok(self.type_of(t2))
+    }
+
+    /// Returns the type of an assignment.
+    ///
+    /// The assigned name must be bound by a mutable let binding, otherwise this method returns an
+    /// error. Typing an assignment adds a constraint enforcing that the assigned term has the type
+    /// of the binding. The returned type is `Unit`.
+    fn type_of_assign(
+        &mut self,
+        loc: Location,
+        name: &Located<Name<'a>>,
+        term: &Located<Term<'a>>,
+    ) -> TyResult<Located<Ty>> {
+        let bind = self
+            .symbols
+            .get(name.content)
+            .and_then(|symbol| self.inner.iter().rev().find(|bind| bind.name == symbol))
+            .ok_or_else(|| TyError::Unbounded(name.loc.with_content(name.content.0.to_string())))?;
+        if !bind.mutable {
+            return Err(TyError::Immutable(
+                name.loc.with_content(name.content.0.to_string()),
+            ));
+        }
+        let expected = name.loc.with_content(bind.scheme.ty.clone());
+        let ty = self.type_of(term);
+        self.add_constraint(expected, ty);
+        Ok(loc.with_content(Ty::Unit))
     }
 
     /// Returns the type of a primitive function.
@@ -387,13 +927,324 @@ impl<'a> Context<'a> {
     ///
     /// - The `print` function has type `X -> Unit` for any `X`. Thus, a new variable is added to
     /// the typing context to represent this `X`.
+    /// - The `concat` function has type `String -> String -> String`.
+    /// - The `int_to_float` function has type `Int -> Float`.
+    /// - The `float_to_int` function has type `Float -> Int`.
+    /// - The `char_to_int` function has type `Char -> Int`.
+    /// - The `int_to_char` function has type `Int -> Char`.
+    /// - The `head` function has type `[X] -> X` for any `X`.
+    /// - The `tail` function has type `[X] -> [X]` for any `X`.
+    /// - The `len` function has type `[X] -> Int` for any `X`. Applications of `len` can also
+    ///   take strings, as explained in the `type_of_len` method.
+    /// - The `push` function has type `[X] -> X -> [X]` for any `X`.
+    /// - The `get` function has type `[X] -> Int -> X` for any `X`.
+    /// - The `to_string` function has type `Int -> String`. Applications of `to_string` can also
+    ///   convert other types, as explained in the `type_of_to_string` method.
+    /// - The `read_line` and `read_int` primitives do not take arguments, so their types are the
+    ///   types of the values they read: `String` and `Int` respectively.
+    /// - The `assert` function has type `Bool -> Unit`.
+    /// - The `abs` and `sign` functions have type `Int -> Int`.
+    /// - The `min`, `max`, `gcd`, `pow` and `random` functions have type `Int -> Int -> Int`.
+    /// - The `substring` function has type `String -> Int -> Int -> String`.
+    /// - The `char_at` function has type `String -> Int -> Char`.
+    /// - The `parse_int` function has type `String -> Int`.
+    /// - The `spawn` function has type `(Unit -> X) -> Unit` for any `X`.
+    /// - The `channel` primitive does not take arguments, so its type is the type of the channel
+    ///   it creates: `Chan(X)` for any `X`.
+    /// - The `send` function has type `Chan(X) -> X -> Unit` for any `X`.
+    /// - The `recv` function has type `Chan(X) -> X` for any `X`.
+    /// - The `force` function has type `Lazy(X) -> X` for any `X`.
+    ///
+    /// A new type variable is added to the typing context for each use of a polymorphic
+    /// primitive, like for `print`.
     fn type_of_prim_fn(&mut self, loc: Location, prim: Primitive) -> TyResult<Located<Ty>> {
         let ty = match prim {
             Primitive::Print => {
                 let ty = self.new_ty();
                 Ty::Arrow(Box::new(ty), Box::new(Ty::Unit))
             }
+            Primitive::Concat => Ty::Arrow(
+                Box::new(Ty::String),
+                Box::new(Ty::Arrow(Box::new(Ty::String), Box::new(Ty::String))),
+            ),
+            Primitive::IntToFloat => Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Float)),
+            Primitive::FloatToInt => Ty::Arrow(Box::new(Ty::Float), Box::new(Ty::Int)),
+            Primitive::CharToInt => Ty::Arrow(Box::new(Ty::Char), Box::new(Ty::Int)),
+            Primitive::IntToChar => Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Char)),
+            Primitive::ToString => Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::String)),
+            Primitive::ReadLine => Ty::String,
+            Primitive::ReadInt => Ty::Int,
+            Primitive::Assert => Ty::Arrow(Box::new(Ty::Bool), Box::new(Ty::Unit)),
+            Primitive::Abs | Primitive::Sign => Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)),
+            Primitive::Min
+            | Primitive::Max
+            | Primitive::Gcd
+            | Primitive::Pow
+            | Primitive::Random => Ty::Arrow(
+                Box::new(Ty::Int),
+                Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int))),
+            ),
+            Primitive::Substring => Ty::Arrow(
+                Box::new(Ty::String),
+                Box::new(Ty::Arrow(
+                    Box::new(Ty::Int),
+                    Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::String))),
+                )),
+            ),
+            Primitive::CharAt => Ty::Arrow(
+                Box::new(Ty::String),
+                Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Char))),
+            ),
+            Primitive::ParseInt => Ty::Arrow(Box::new(Ty::String), Box::new(Ty::Int)),
+            Primitive::Head
+            | Primitive::Tail
+            | Primitive::Len
+            | Primitive::Push
+            | Primitive::Get => {
+                let elem = self.new_ty();
+                let list = Ty::List(Box::new(elem.clone()));
+                let ret = match prim {
+                    Primitive::Head => elem,
+                    Primitive::Tail => list.clone(),
+                    Primitive::Len => Ty::Int,
+                    Primitive::Push => Ty::Arrow(Box::new(elem), Box::new(list.clone())),
+                    Primitive::Get => Ty::Arrow(Box::new(Ty::Int), Box::new(elem)),
+                    _ => unreachable!(),
+                };
+                Ty::Arrow(Box::new(list), Box::new(ret))
+            }
+            Primitive::Spawn => {
+                let ty = self.new_ty();
+                let func = Ty::Arrow(Box::new(Ty::Unit), Box::new(ty));
+                Ty::Arrow(Box::new(func), Box::new(Ty::Unit))
+            }
+            Primitive::Channel => Ty::Chan(Box::new(self.new_ty())),
+            Primitive::Send | Primitive::Recv => {
+                let elem = self.new_ty();
+                let chan = Ty::Chan(Box::new(elem.clone()));
+                let ret = match prim {
+                    Primitive::Send => Ty::Arrow(Box::new(elem), Box::new(Ty::Unit)),
+                    Primitive::Recv => elem,
+                    _ => unreachable!(),
+                };
+                Ty::Arrow(Box::new(chan), Box::new(ret))
+            }
+            Primitive::Force => {
+                let value = self.new_ty();
+                Ty::Arrow(Box::new(Ty::Lazy(Box::new(value.clone()))), Box::new(value))
+            }
         };
         Ok(loc.with_content(ty))
     }
+
+    /// Returns the variant bound to `name` in the current scope.
+    ///
+    /// If the variant is not in the current scope, this method returns an error stating that the
+    /// name is unbounded.
+    fn variant(&self, loc: Location, name: Name<'a>) -> TyResult<&VariantBinding> {
+        self.symbols
+            .get(name)
+            .and_then(|symbol| {
+                self.variants
+                    .iter()
+                    .rev()
+                    .find(|variant| variant.name == symbol)
+            })
+            .ok_or_else(|| TyError::Unbounded(loc.with_content(name.0.to_string())))
+    }
+
+    /// Returns the type of the enum of a variant and the types of its fields, with each type
+    /// parameter of the enum replaced by a new type variable.
+    fn instantiate_variant(&mut self, loc: Location, name: Name<'a>) -> TyResult<(Ty, Vec<Ty>)> {
+        let variant = self.variant(loc, name)?;
+        let mut ty = variant.ty.clone();
+        let mut fields = variant.fields.clone();
+        for index in variant.params.clone() {
+            let new = self.new_ty();
+            replace_var(&mut ty, index, &new);
+            for field in &mut fields {
+                replace_var(field, index, &new);
+            }
+        }
+        Ok((ty, fields))
+    }
+
+    /// Returns the type of an enum declaration.
+    ///
+    /// Typing an enum declaration requires adding its variants to the context and then typing the
+    /// term that follows it, which is the type of the declaration. The variants must be removed
+    /// from the context afterwards, like the bindings done by let bindings.
+    ///
+    /// The type variables in the fields of the variants are the type parameters of the enum, in
+    /// the order they are found. An enum `E` with parameters `a1`, ..., `an` has type
+    /// `E(a1, ..., an)`.
+    ///
+    /// This rule does not add new constraints because a declaration does not impose any
+    /// restriction by itself.
+    fn type_of_enum(
+        &mut self,
+        loc: Location,
+        name: &Located<Name<'a>>,
+        variants: &[Variant<'a>],
+        term: &Located<Term<'a>>,
+    ) -> TyResult<Located<Ty>> {
+        let fields: Vec<Vec<Ty>> = variants
+            .iter()
+            .map(|variant| {
+                variant
+                    .fields
+                    .iter()
+                    .map(|field| self.annotation_ty(field))
+                    .collect()
+            })
+            .collect();
+        let mut params = Vec::new();
+        for field in fields.iter().flatten() {
+            field.vars(&mut params);
+        }
+        let args = params.iter().map(|index| Ty::Var(*index)).collect();
+        let ty = Ty::Adt(name.content.0.to_string(), args);
+
+        let len = self.variants.len();
+        for (variant, fields) in variants.iter().zip(fields) {
+            let name = self.symbols.intern(variant.name.content);
+            self.variants.push(VariantBinding {
+                name,
+                ty: ty.clone(),
+                fields,
+                params: params.clone(),
+            });
+        }
+
+        let ty = self.type_of(term).content;
+        self.variants.truncate(len);
+        Ok(Located::new(ty, loc))
+    }
+
+    /// Returns the type of the constructor of a variant.
+    ///
+    /// The constructor of a variant with fields `T1`, ..., `Tn` of an enum `E` has type
+    /// `T1 -> ... -> Tn -> E`. If the variant does not have fields, its type is just `E`. The type
+    /// parameters of the enum are replaced by new type variables in each constructor.
+    ///
+    /// This rule does not add new constraints because the type of a constructor is decided by the
+    /// declaration of its enum.
+    fn type_of_constructor(&mut self, loc: Location, name: &Name<'a>) -> TyResult<Located<Ty>> {
+        let (ty, fields) = self.instantiate_variant(loc, *name)?;
+        let ty = fields
+            .into_iter()
+            .rev()
+            .fold(ty, |ty, field| Ty::Arrow(Box::new(field), Box::new(ty)));
+        Ok(loc.with_content(ty))
+    }
+
+    /// Returns the type of a match.
+    ///
+    /// Typing a match requires that each pattern has the type of the scrutinee and that the bodies
+    /// of all the arms have the same type, constraints are added accordingly:
+    ///
+    /// - A literal pattern has the type of the literal.
+    /// - A variant pattern has the type of the enum of the variant. It must have one name for each
+    ///   field of the variant, which are bound to the types of the fields while typing the body of
+    ///   the arm.
+    /// - The `_` pattern can have any type.
+    ///
+    /// The returned type is the one of the body of the first arm.
+    fn type_of_match(
+        &mut self,
+        loc: Location,
+        term: &Located<Term<'a>>,
+        arms: &[(Located<Pattern<'a>>, Located<Term<'a>>)],
+    ) -> TyResult<Located<Ty>> {
+        let ty = self.type_of(term);
+        let mut arms_ty: Option<Located<Ty>> = None;
+
+        for (pattern, body) in arms {
+            let len = self.inner.len();
+            match &pattern.content {
+                Pattern::Wildcard => (),
+                Pattern::Literal(lit) => {
+                    let lit_ty = self.type_of_lit(pattern.loc, lit)?;
+                    self.add_constraint(ty.clone(), lit_ty);
+                }
+                Pattern::Variant(name, binders) => {
+                    let (variant_ty, fields) = self.instantiate_variant(name.loc, name.content)?;
+                    if fields.len() != binders.len() {
+                        return Err(TyError::FieldCount {
+                            variant: name.loc.with_content(name.content.0.to_string()),
+                            expected: fields.len(),
+                            found: binders.len(),
+                        });
+                    }
+                    let variant_ty = pattern.loc.with_content(variant_ty);
+                    self.add_constraint(ty.clone(), variant_ty);
+                    for (binder, field) in binders.iter().zip(fields) {
+                        let name = self.symbols.intern(binder.content);
+                        self.inner.push(TyBinding {
+                            name,
+                            scheme: Scheme::mono(field),
+                            mutable: false,
+                        });
+                    }
+                }
+            }
+
+            let body_ty = self.type_of(body);
+            self.inner.truncate(len);
+            match &arms_ty {
+                Some(arms_ty) => self.add_constraint(arms_ty.clone(), body_ty),
+                None => arms_ty = Some(body_ty),
+            }
+        }
+
+        let ty = arms_ty.expect("Matches have at least one arm").content;
+        Ok(loc.with_content(ty))
+    }
+}
+
+/// Replaces each occurrence of the type variable with `index` inside `ty` by `new`.
+fn replace_var(ty: &mut Ty, index: usize, new: &Ty) {
+    match ty {
+        Ty::Bool | Ty::Int | Ty::Float | Ty::Unit | Ty::Char | Ty::String => (),
+        Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => replace_var(ty, index, new),
+        Ty::Adt(_, args) => {
+            for arg in args {
+                replace_var(arg, index, new);
+            }
+        }
+        Ty::Arrow(ty1, ty2) => {
+            replace_var(ty1, index, new);
+            replace_var(ty2, index, new);
+        }
+        Ty::Var(inner) => {
+            if *inner == index {
+                *ty = new.clone();
+            }
+        }
+    }
+}
+
+/// Returns an error if the return type of the recursive function `name`, whose body is `body`, is
+/// a quantified type variable of `scheme` that does not appear in the types of its parameters.
+///
+/// Such a return type is not constrained by the body of the function nor by its parameters, so it
+/// cannot be inferred.
+fn check_return_ty(
+    name: &Located<Name<'_>>,
+    mut body: &Located<Term<'_>>,
+    scheme: &Scheme,
+) -> TyResult<()> {
+    let mut ty = &scheme.ty;
+    let mut param_vars = Vec::new();
+    while let (Term::Abs(_, _, inner), Ty::Arrow(param_ty, ret_ty)) = (&body.content, ty) {
+        param_ty.vars(&mut param_vars);
+        body = inner;
+        ty = ret_ty;
+    }
+    match ty {
+        Ty::Var(index) if scheme.vars.contains(index) && !param_vars.contains(index) => Err(
+            TyError::AmbiguousReturn(name.loc.with_content(name.content.0.to_string())),
+        ),
+        _ => Ok(()),
+    }
 }
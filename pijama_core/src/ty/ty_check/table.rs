@@ -0,0 +1,87 @@
+//! The types of the terms of a program.
+use alloc::collections::BTreeMap;
+use core::marker::PhantomData;
+
+use pijama_ast::{Located, NodeId};
+
+use crate::{
+    mir::{visit::Visitor, Term},
+    ty::Ty,
+};
+
+/// The type inferred for each term of a type-checked program.
+///
+/// Terms are identified by their address, so the table borrows the MIR it was built from to make
+/// sure it is not moved or modified while the table is in use. Terms that are not part of that
+/// MIR do not have a type in the table. The terms lowered from a node can also be looked up by the
+/// [`NodeId`] of the node.
+///
+/// The types are the ones found once every constraint of the program is solved. The terms inside
+/// a polymorphic definition keep the type variables that were quantified, and each use of the
+/// definition has the type it was instantiated with.
+#[derive(Debug)]
+pub struct TyTable<'t, 'a> {
+    ty: Located<Ty>,
+    tys: BTreeMap<*const Term<'a>, Ty>,
+    /// The first term with each id, in the order of the source code.
+    nodes: BTreeMap<NodeId, *const Term<'a>>,
+    mir: PhantomData<&'t Term<'a>>,
+}
+
+impl<'t, 'a> TyTable<'t, 'a> {
+    pub(super) fn new(
+        mir: &'t Located<Term<'a>>,
+        ty: Located<Ty>,
+        tys: BTreeMap<*const Term<'a>, Ty>,
+    ) -> Self {
+        let mut nodes = Nodes(BTreeMap::new());
+        nodes.visit_term(mir);
+        TyTable {
+            ty,
+            tys,
+            nodes: nodes.0,
+            mir: PhantomData,
+        }
+    }
+
+    /// Returns the type of the whole program.
+    pub fn ty(&self) -> &Located<Ty> {
+        &self.ty
+    }
+
+    /// Returns the type of `term`, or `None` if the term is not part of the type-checked MIR.
+    pub fn get(&self, term: &Term<'a>) -> Option<&Ty> {
+        self.tys.get(&(term as *const _))
+    }
+
+    /// Returns the type of the term lowered from the node with the id `id`, or `None` if no term
+    /// of the type-checked MIR has that id.
+    ///
+    /// Terms copied by the optimizations of the MIR keep their id, so several terms can have the
+    /// same one. The type of the first of them in the order of the source code is returned.
+    pub fn get_node(&self, id: NodeId) -> Option<&Ty> {
+        self.tys.get(self.nodes.get(&id)?)
+    }
+
+    /// Returns the number of terms with a type.
+    pub fn len(&self) -> usize {
+        self.tys.len()
+    }
+
+    /// Returns `true` if no term has a type.
+    pub fn is_empty(&self) -> bool {
+        self.tys.is_empty()
+    }
+}
+
+/// Visitor collecting the first term with each id.
+struct Nodes<'a>(BTreeMap<NodeId, *const Term<'a>>);
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for Nodes<'a> {
+    fn visit_term(&mut self, term: &'t Located<Term<'a>>) {
+        if !term.id.is_dummy() {
+            self.0.entry(term.id).or_insert(&term.content);
+        }
+        self.super_term(term);
+    }
+}
@@ -6,17 +6,18 @@
 //!
 //! This algorithm is based on Chapter 22 of the _Types and Programming Languages_ book by Benjamin
 //! Pierce.
-use std::collections::VecDeque;
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
 
-use pijama_ast::Located;
+use pijama_ast::{Located, Location};
 
-use crate::ty::{ty_check::Context, Ty, TyError, TyResult};
+use crate::ty::{Ty, TyError};
 
 /// Solves the constraints created by the `Context` type.
 ///
 /// This type is able to find a set of `Substitution`s such that the program that produced the
-/// `Context`'s `Constraint`s is well-typed.
-#[derive(Debug)]
+/// `Context`'s `Constraint`s is well-typed. The constraints can be solved in many steps: the
+/// substitutions found in each step are kept and applied to the constraints of the next ones.
+#[derive(Debug, Default)]
 pub struct Unifier {
     /// Substitutions that make the program well-typed.
     substitutions: Vec<Substitution>,
@@ -25,18 +26,23 @@ pub struct Unifier {
 }
 
 impl Unifier {
-    /// Creates a new `Unifier` from a `Context`.
+    /// Solves a set of constraints.
     ///
-    /// Consumes the constraints collected by the `Context` and then tries to unify those
-    /// constraints using the `unify` method. If this process is successful, a new `Unifier` is
-    /// returned ready to be used to replace type variables.
-    pub(super) fn from_ctx(ctx: Context) -> TyResult<Self> {
-        let mut unif = Unifier {
-            substitutions: Default::default(),
-            constraints: ctx.constraints,
-        };
-        unif.unify()?;
-        Ok(unif)
+    /// The substitutions found before are applied to `constraints` and then they are unified using
+    /// the `unify` method. The constraints that cannot be satisfied are pushed to `errors` as type
+    /// mismatches, and the `Unifier` is ready to be used to replace type variables afterwards.
+    pub(super) fn solve(
+        &mut self,
+        mut constraints: VecDeque<Located<Constraint>>,
+        errors: &mut Vec<TyError>,
+    ) {
+        for constr in &mut constraints {
+            let Constraint { lhs, rhs, .. } = &mut constr.content;
+            self.replace(lhs);
+            self.replace(rhs);
+        }
+        self.constraints = constraints;
+        self.unify(errors)
     }
 
     /// Replaces the type variables inside a type.
@@ -54,7 +60,7 @@ impl Unifier {
     /// field.
     fn apply_substitution(&mut self, subst: &Substitution) {
         for constr in &mut self.constraints {
-            let Constraint { lhs, rhs } = &mut constr.content;
+            let Constraint { lhs, rhs, .. } = &mut constr.content;
             subst.apply(lhs);
             subst.apply(rhs);
         }
@@ -73,18 +79,21 @@ impl Unifier {
     /// Solves the unification problem.
     ///
     /// This method is the core of this module. It takes care of populating the `substitutions`
-    /// field. If this method returns without errors, the `Unifier` is ready to be used to
-    /// `replace` type variables and the program can be assumed to be well-typed.
-    fn unify(&mut self) -> TyResult<()> {
+    /// field. If no errors are pushed to `errors`, the `Unifier` is ready to be used to `replace`
+    /// type variables and the program can be assumed to be well-typed.
+    ///
+    /// A constraint that cannot be satisfied is skipped after pushing its error, so the remaining
+    /// constraints are still solved and several mismatches can be reported at once.
+    fn unify(&mut self, errors: &mut Vec<TyError>) {
         // If there are constraints to be solved, take one.
         if let Some(constr) = self.constraints.pop_back() {
             let loc = constr.loc;
-            let Constraint { lhs, rhs } = constr.content;
+            let Constraint { lhs, rhs, origin } = constr.content;
 
             match (lhs, rhs) {
                 // If both sides of the constraint are equal, nothing needs to be done. We can skip
                 // this constraint and go ahead with the other rules.
-                (lhs, rhs) if lhs == rhs => self.unify()?,
+                (lhs, rhs) if lhs == rhs => self.unify(errors),
 
                 // If the left-hand side is a type variable and this variable is not on the
                 // right-hand side we replace the left-hand side type by the right-hand side in all
@@ -92,7 +101,7 @@ impl Unifier {
                 (Ty::Var(index), rhs) if !rhs.contains(index) => {
                     let subst = Substitution::new(Ty::Var(index), rhs);
                     self.apply_substitution(&subst);
-                    self.unify()?;
+                    self.unify(errors);
                     self.add_substitution(subst);
                 }
 
@@ -102,7 +111,7 @@ impl Unifier {
                 (lhs, Ty::Var(index)) if !lhs.contains(index) => {
                     let subst = Substitution::new(Ty::Var(index), lhs);
                     self.apply_substitution(&subst);
-                    self.unify()?;
+                    self.unify(errors);
                     self.add_substitution(subst);
                 }
 
@@ -111,23 +120,55 @@ impl Unifier {
                 // prioritize them.
                 (Ty::Arrow(s1, s2), Ty::Arrow(t1, t2)) => {
                     self.constraints
-                        .push_back(Located::new(Constraint::new(*s1, *t1), loc));
+                        .push_back(Located::new(Constraint::new(*s1, *t1, origin), loc));
+                    self.constraints
+                        .push_back(Located::new(Constraint::new(*s2, *t2, origin), loc));
+                    self.unify(errors);
+                }
+
+                // If both sides are list types, their elements must have the same type.
+                (Ty::List(s1), Ty::List(t1)) => {
+                    self.constraints
+                        .push_back(Located::new(Constraint::new(*s1, *t1, origin), loc));
+                    self.unify(errors);
+                }
+
+                // If both sides are channel types, their values must have the same type.
+                (Ty::Chan(s1), Ty::Chan(t1)) => {
+                    self.constraints
+                        .push_back(Located::new(Constraint::new(*s1, *t1, origin), loc));
+                    self.unify(errors);
+                }
+
+                // If both sides are lazy types, their values must have the same type.
+                (Ty::Lazy(s1), Ty::Lazy(t1)) => {
                     self.constraints
-                        .push_back(Located::new(Constraint::new(*s2, *t2), loc));
-                    self.unify()?;
+                        .push_back(Located::new(Constraint::new(*s1, *t1, origin), loc));
+                    self.unify(errors);
                 }
 
-                // Otherwise, this constraint cannot be satisfied and we raise an error.
+                // If both sides are the same enum type, their type arguments must be the same.
+                (Ty::Adt(s, s_args), Ty::Adt(t, t_args))
+                    if s == t && s_args.len() == t_args.len() =>
+                {
+                    for (s1, t1) in Vec::from(s_args).into_iter().zip(Vec::from(t_args)) {
+                        self.constraints
+                            .push_back(Located::new(Constraint::new(s1, t1, origin), loc));
+                    }
+                    self.unify(errors);
+                }
+
+                // Otherwise, this constraint cannot be satisfied and we report an error before
+                // going ahead with the other constraints.
                 (lhs, rhs) => {
-                    return Err(TyError::Mismatch {
-                        expected: lhs,
-                        found: Located::new(rhs, loc),
+                    errors.push(TyError::Mismatch {
+                        expected: Box::new(Located::new(lhs, origin)),
+                        found: Box::new(Located::new(rhs, loc)),
                     });
+                    self.unify(errors);
                 }
             }
         }
-        // If there are no more constrains, we are done.
-        Ok(())
     }
 }
 
@@ -153,6 +194,12 @@ impl Substitution {
         } else if let Ty::Arrow(ty1, ty2) = ty {
             self.apply(ty1);
             self.apply(ty2);
+        } else if let Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) = ty {
+            self.apply(ty);
+        } else if let Ty::Adt(_, args) = ty {
+            for arg in args {
+                self.apply(arg);
+            }
         }
     }
 }
@@ -168,11 +215,16 @@ pub struct Constraint {
     ///
     /// It usually represents the type found when creating a constraint.
     rhs: Ty,
+    /// Location of the term or annotation that imposes the left-hand side.
+    ///
+    /// It is used to point to the origin of the expected type if the constraint cannot be
+    /// satisfied.
+    origin: Location,
 }
 
 impl Constraint {
     /// Creates a new constraint.
-    pub fn new(lhs: Ty, rhs: Ty) -> Self {
-        Constraint { lhs, rhs }
+    pub fn new(lhs: Ty, rhs: Ty, origin: Location) -> Self {
+        Constraint { lhs, rhs, origin }
     }
 }
@@ -0,0 +1,176 @@
+//! Exhaustiveness and reachability of the arms of matches.
+//!
+//! A match is exhaustive if every value of its scrutinee is matched by one of its arms, and an arm
+//! is unreachable if every value it matches is matched by a previous arm.
+//!
+//! The values of a scrutinee are split by their constructors: the variants of its enum, `true` and
+//! `false` for booleans, `unit` for the unit type, and each number, character or string for
+//! integers, floats, characters and strings. Patterns cannot be nested and the names bound by a
+//! variant pattern match any value, so each pattern matches either a single constructor or, if it
+//! is `_`, all of them. This means that an arm is reachable if and only if it matches a
+//! constructor that no previous arm matches.
+//!
+//! The constructors of a scrutinee are found from the patterns instead of from its type, which
+//! requires the match to be well-typed.
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use pijama_ast::{Literal, Located, Location, Name, Pattern};
+
+use crate::mir::{
+    visit::{Arm, Visitor},
+    Term, Variant,
+};
+
+/// The result of analyzing the arms of a match.
+pub(crate) struct Coverage {
+    /// The location of the scrutinee.
+    pub(crate) loc: Location,
+    /// The patterns matching the values that no arm matches.
+    pub(crate) missing: Vec<String>,
+    /// The locations of the patterns of the arms that are never taken.
+    pub(crate) unreachable: Vec<Location>,
+}
+
+/// Returns the coverage of each match inside `term`, in the order they appear in the source.
+pub(crate) fn coverage(term: &Located<Term<'_>>) -> Vec<Coverage> {
+    let mut visitor = CoverageVisitor {
+        enums: Vec::new(),
+        coverages: Vec::new(),
+    };
+    visitor.visit_term(term);
+    visitor.coverages
+}
+
+/// Visitor pushing the coverage of each match it visits.
+struct CoverageVisitor<'t, 'a> {
+    /// The variants of the enums declared in the current scope, innermost last.
+    enums: Vec<&'t [Variant<'a>]>,
+    coverages: Vec<Coverage>,
+}
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for CoverageVisitor<'t, 'a> {
+    fn visit_enum(
+        &mut self,
+        name: &'t Located<Name<'a>>,
+        variants: &'t [Variant<'a>],
+        t1: &'t Located<Term<'a>>,
+    ) {
+        self.enums.push(variants);
+        self.super_enum(name, variants, t1);
+        self.enums.pop().unwrap();
+    }
+
+    fn visit_match(&mut self, t1: &'t Located<Term<'a>>, arms: &'t [Arm<'a>]) {
+        self.coverages
+            .push(match_coverage(t1.loc, arms, &self.enums));
+        self.super_match(t1, arms);
+    }
+}
+
+/// Returns the coverage of a match whose scrutinee is located at `loc`.
+fn match_coverage(
+    loc: Location,
+    arms: &[(Located<Pattern<'_>>, Located<Term<'_>>)],
+    enums: &[&[Variant<'_>]],
+) -> Coverage {
+    let constructors = constructors(arms, enums);
+    let mut covered = Vec::new();
+    let mut exhaustive = false;
+    let mut unreachable = Vec::new();
+
+    for (pattern, _) in arms {
+        if exhaustive {
+            unreachable.push(pattern.loc);
+            continue;
+        }
+        match key(&pattern.content) {
+            // `_` matches every constructor.
+            None => exhaustive = true,
+            Some(key) if covered.contains(&key) => unreachable.push(pattern.loc),
+            Some(key) => {
+                covered.push(key);
+                if let Some(constructors) = &constructors {
+                    exhaustive = constructors.iter().all(|(key, _)| covered.contains(key));
+                }
+            }
+        }
+    }
+
+    let missing = match constructors {
+        _ if exhaustive => Vec::new(),
+        Some(constructors) => constructors
+            .into_iter()
+            .filter(|(key, _)| !covered.contains(key))
+            .map(|(_, pattern)| pattern)
+            .collect(),
+        // There are infinitely many constructors, so only `_` can cover the missing ones.
+        None => vec!["_".to_string()],
+    };
+
+    Coverage {
+        loc,
+        missing,
+        unreachable,
+    }
+}
+
+/// Returns the key of the constructor matched by `pattern`, or `None` if it matches all of them.
+fn key(pattern: &Pattern<'_>) -> Option<String> {
+    match pattern {
+        Pattern::Wildcard => None,
+        Pattern::Literal(lit) => Some(lit.to_string()),
+        Pattern::Variant(name, _) => Some(name.content.0.to_string()),
+    }
+}
+
+/// Returns the key and a pattern for each constructor of the scrutinee of a match with `arms`.
+///
+/// Returns `None` if there are infinitely many constructors or if they cannot be found because
+/// every pattern is `_`.
+fn constructors(
+    arms: &[(Located<Pattern<'_>>, Located<Term<'_>>)],
+    enums: &[&[Variant<'_>]],
+) -> Option<Vec<(String, String)>> {
+    let pattern = arms
+        .iter()
+        .map(|(pattern, _)| &pattern.content)
+        .find(|pattern| !matches!(pattern, Pattern::Wildcard))?;
+    match pattern {
+        Pattern::Wildcard
+        | Pattern::Literal(Literal::Number(_))
+        | Pattern::Literal(Literal::Float(_))
+        | Pattern::Literal(Literal::Char(_))
+        | Pattern::Literal(Literal::Str(_)) => None,
+        Pattern::Literal(Literal::Bool(_)) => Some(vec![
+            ("true".to_string(), "true".to_string()),
+            ("false".to_string(), "false".to_string()),
+        ]),
+        Pattern::Literal(Literal::Unit) => Some(vec![("unit".to_string(), "unit".to_string())]),
+        Pattern::Variant(name, _) => {
+            let variants = enums.iter().rev().find(|variants| {
+                variants
+                    .iter()
+                    .any(|variant| variant.name.content == name.content)
+            })?;
+            let constructors = variants
+                .iter()
+                .map(|variant| {
+                    let key = variant.name.content.0.to_string();
+                    let pattern = if variant.fields.is_empty() {
+                        key.clone()
+                    } else {
+                        let fields = vec!["_"; variant.fields.len()];
+                        format!("{}({})", key, fields.join(", "))
+                    };
+                    (key, pattern)
+                })
+                .collect();
+            Some(constructors)
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 
 use std::include_str;
 
 use pijama_core::{
-    lir::Term, machine::MachineBuilder, mir::Term as MirTerm, parser::parse, ty::ty_check,
+    bytecode, lir::Term, machine::MachineBuilder, mir::Term as MirTerm, parser::parse, ty::ty_check,
 };
-use pijama_driver::LangResult;
+use pijama_driver::{prelude::PRELUDE, LangResult};
 
 fn compile(input: &str) -> LangResult<Term> {
     let ast = parse(input)?;
@@ -104,6 +104,52 @@ fn complex_calling(c: &mut Criterion) {
     });
 }
 
+/// Measures how the cost of a call grows with the calls around it.
+///
+/// Applying a closure only instantiates its code, but the arguments of calls that are not in tail
+/// position are passed by name: `n` is bound to `n - 1` of the calling call, and so on. Each call
+/// evaluates these subtractions again, so the number of steps, and not the cost of each step,
+/// grows quadratically with `n`.
+fn argument_chains(c: &mut Criterion) {
+    let mut group = c.benchmark_group("argument_chains");
+    for n in [100, 200, 400].iter() {
+        let input = format!(
+            "fn sum(n: Int): Int do if n == 0 do 0 else n + sum(n - 1) end end\nsum({})",
+            n
+        );
+        let term = compile(&input).unwrap();
+        let mut machine = MachineBuilder::default().build();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &term, |b, term| {
+            b.iter(|| machine.evaluate(term.clone()))
+        });
+    }
+    group.finish();
+}
+
+fn bytecode(c: &mut Criterion) {
+    let inputs = [
+        ("factorial_tail_bytecode", include_str!("factorial_tail.pj")),
+        ("fibonacci_bytecode", include_str!("fibonacci.pj")),
+        ("ackermann_bytecode", include_str!("ackermann.pj")),
+    ];
+    for (name, input) in inputs.iter() {
+        let program = bytecode::compile(&compile(input).unwrap());
+        let mut machine = MachineBuilder::default().build();
+        c.bench_function(name, |b| b.iter(|| machine.evaluate_bytecode(&program)));
+    }
+}
+
+/// Measures the front end of the compiler on the prelude, which is the largest program around.
+fn frontend(c: &mut Criterion) {
+    let ast = parse(PRELUDE).unwrap();
+    let mir = MirTerm::from_ast(ast.clone()).unwrap();
+    c.bench_function("frontend_parse", |b| b.iter(|| parse(PRELUDE)));
+    c.bench_function("frontend_lower", |b| {
+        b.iter(|| MirTerm::from_ast(ast.clone()))
+    });
+    c.bench_function("frontend_check", |b| b.iter(|| ty_check(&mir)));
+}
+
 criterion_group!(
     benches,
     arithmetic,
@@ -117,6 +163,9 @@ criterion_group!(
     calling,
     complex_calling,
     fancy_max,
-    step
+    step,
+    argument_chains,
+    bytecode,
+    frontend
 );
 criterion_main!(benches);
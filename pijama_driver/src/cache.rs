@@ -0,0 +1,265 @@
+//! Incremental type checking.
+//!
+//! A [`Cache`] remembers the type of each top-level definition of a program, keyed by a hash of
+//! the definition and of the types of the names it uses. Checking a program with a cache that was
+//! used to check a previous version of it only type-checks the definitions that changed or whose
+//! dependencies changed their type. Hosts that check the same program after every edit can keep a
+//! cache around, or persist it between runs since it can be serialized. A cache can also be used
+//! by the type-checking stage of a [`Compilation`](crate::Compilation), see
+//! [`Compilation::check_with_cache`](crate::Compilation::check_with_cache).
+//!
+//! Every statement at the top level of the program is a definition: functions, bindings and
+//! expressions evaluated for their side effects. Only the types of definitions that type-check
+//! are cached. If any definition has an error, the whole program is checked again like
+//! [`check`](crate::check) does, so the reported error is the same one reported without the
+//! cache.
+//!
+//! Only types are cached: the driver has no optimization passes yet, so there are no other
+//! per-definition results worth keeping.
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+use pijama_ast::{Located, Name};
+use pijama_core::{
+    mir::Term as MirTerm,
+    ty::{ty_check, Ty},
+};
+
+use crate::{lower, LangResult};
+
+/// A cache of the types of top-level definitions.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Cache {
+    types: HashMap<u64, Ty>,
+    #[serde(skip)]
+    stats: CacheStats,
+}
+
+/// How many definitions were found in the cache during a check.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct CacheStats {
+    /// Definitions whose type was found in the cache.
+    pub hits: usize,
+    /// Definitions that had to be type-checked.
+    pub misses: usize,
+}
+
+impl Cache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses, lowers and type-checks `input` like [`check`](crate::check), reusing the types of
+    /// the definitions that did not change since the last check.
+    ///
+    /// After the check, the cache only keeps the definitions of `input`, so checking many
+    /// versions of a program does not make the cache grow.
+    pub fn check(&mut self, input: &str) -> LangResult<Ty> {
+        self.check_mir(&lower(input)?)
+    }
+
+    /// Type-checks the lowered program `mir` like [`Cache::check`].
+    ///
+    /// This is how a [`Compilation`](crate::Compilation) uses the cache, see
+    /// [`Compilation::check_with_cache`](crate::Compilation::check_with_cache).
+    pub(crate) fn check_mir(&mut self, mir: &Located<MirTerm<'_>>) -> LangResult<Ty> {
+        self.stats = CacheStats::default();
+        let mut used = HashMap::new();
+        let ty = self.check_definitions(mir, &mut used);
+        self.types = used;
+
+        match ty {
+            Some(ty) => Ok(ty),
+            None => Ok(ty_check(mir)?.content),
+        }
+    }
+
+    /// Returns how many definitions were found in the cache during the last check.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Returns the number of cached definitions.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Returns `true` if there are no cached definitions.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Type-checks each top-level definition of `term` and returns the type of the program.
+    ///
+    /// The types of the definitions are inserted in `used`. Returns `None` if any definition
+    /// cannot be checked on its own.
+    fn check_definitions<'a>(
+        &mut self,
+        mut term: &Located<MirTerm<'a>>,
+        used: &mut HashMap<u64, Ty>,
+    ) -> Option<Ty> {
+        let mut env = Vec::new();
+        loop {
+            match &term.content {
+                MirTerm::Let(kind, name, t1, t2) => {
+                    // Check the definition as a program that returns the bound value.
+                    let def = term.loc.with_content(MirTerm::Let(
+                        kind.clone(),
+                        *name,
+                        t1.clone(),
+                        Box::new(name.loc.with_content(MirTerm::Var(name.content))),
+                    ));
+                    let ty = self.check_definition(&def, &env, used)?;
+                    env.push((name.content, ty));
+                    term = t2;
+                }
+                MirTerm::LetRec(fns, t1) => {
+                    // Check each function as a program that returns it, they are bound together
+                    // after checking all of them.
+                    let mut tys = Vec::new();
+                    for (name, _, _) in fns {
+                        let def = term.loc.with_content(MirTerm::LetRec(
+                            fns.clone(),
+                            Box::new(name.loc.with_content(MirTerm::Var(name.content))),
+                        ));
+                        tys.push((name.content, self.check_definition(&def, &env, used)?));
+                    }
+                    env.extend(tys);
+                    term = t1;
+                }
+                MirTerm::Seq(t1, t2) => {
+                    if self.check_definition(t1, &env, used)? != Ty::Unit {
+                        return None;
+                    }
+                    term = t2;
+                }
+                _ => return self.check_definition(term, &env, used),
+            }
+        }
+    }
+
+    /// Returns the type of `term` when the names in `env` are bound to their types.
+    fn check_definition<'a>(
+        &mut self,
+        term: &Located<MirTerm<'a>>,
+        env: &[(Name<'a>, Ty)],
+        used: &mut HashMap<u64, Ty>,
+    ) -> Option<Ty> {
+        let mut names = HashSet::new();
+        vars(term, &mut names);
+        // The visible binding of each name used by `term`, in the order they were defined.
+        let deps: Vec<_> = env
+            .iter()
+            .enumerate()
+            .filter(|(index, (name, _))| {
+                names.contains(name.0) && env[index + 1..].iter().all(|(other, _)| other != name)
+            })
+            .map(|(_, binding)| binding)
+            .collect();
+
+        let key = key(term, &deps);
+        let ty = match self.types.get(&key) {
+            Some(ty) => {
+                self.stats.hits += 1;
+                ty.clone()
+            }
+            None => {
+                self.stats.misses += 1;
+                // Bind the dependencies by wrapping `term` in an abstraction for each one of them.
+                let mut wrapped = term.clone();
+                for (name, ty) in deps.iter().rev() {
+                    let abs =
+                        MirTerm::Abs(term.loc.with_content(*name), ty.clone(), Box::new(wrapped));
+                    wrapped = term.loc.with_content(abs);
+                }
+                let mut ty = ty_check(&wrapped).ok()?.content;
+                for _ in &deps {
+                    ty = match ty {
+                        Ty::Arrow(_, ty) => *ty,
+                        _ => unreachable!("Abstractions must have arrow types"),
+                    };
+                }
+                // A type with variables can be constrained further by the rest of the program,
+                // so it cannot be checked on its own.
+                if !ty.is_concrete() {
+                    return None;
+                }
+                ty
+            }
+        };
+        used.insert(key, ty.clone());
+        Some(ty)
+    }
+}
+
+/// Inserts the names of all the variables used in `term` in `names`.
+fn vars<'a>(term: &Located<MirTerm<'a>>, names: &mut HashSet<&'a str>) {
+    match &term.content {
+        MirTerm::Var(name) => {
+            names.insert(name.0);
+        }
+        MirTerm::Lit(_) | MirTerm::PrimFn(_) | MirTerm::Native(..) | MirTerm::Constructor(_) => (),
+        MirTerm::Abs(_, _, t)
+        | MirTerm::UnaryOp(_, t)
+        | MirTerm::Enum(_, _, t)
+        | MirTerm::Ascription(t, _)
+        | MirTerm::Lazy(t) => vars(t, names),
+        MirTerm::BinaryOp(_, t1, t2)
+        | MirTerm::App(t1, t2)
+        | MirTerm::Let(_, _, t1, t2)
+        | MirTerm::Seq(t1, t2) => {
+            vars(t1, names);
+            vars(t2, names);
+        }
+        MirTerm::Cond(t1, t2, t3) => {
+            vars(t1, names);
+            vars(t2, names);
+            vars(t3, names);
+        }
+        MirTerm::LetRec(fns, t) => {
+            for (_, _, body) in fns {
+                vars(body, names);
+            }
+            vars(t, names);
+        }
+        MirTerm::Assign(name, t) => {
+            names.insert(name.content.0);
+            vars(t, names);
+        }
+        MirTerm::List(elems) => {
+            for elem in elems {
+                vars(elem, names);
+            }
+        }
+        MirTerm::Match(t, arms) => {
+            vars(t, names);
+            for (_, body) in arms {
+                vars(body, names);
+            }
+        }
+    }
+}
+
+/// Returns the key of a definition in the cache.
+///
+/// The key is computed with FNV-1a instead of the standard library hasher because its result must
+/// not change between runs for persisted caches to be useful.
+fn key(term: &Located<MirTerm<'_>>, deps: &[&(Name<'_>, Ty)]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut write = |text: &str| {
+        // A byte that cannot appear in UTF-8 text separates the strings.
+        for byte in text.bytes().chain(Some(0xff)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    // Displaying a term ignores its locations, so moving a definition does not change its key.
+    write(&term.content.to_string());
+    for (name, ty) in deps {
+        write(name.0);
+        write(&ty.to_string());
+    }
+    hash
+}
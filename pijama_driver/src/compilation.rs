@@ -0,0 +1,171 @@
+//! Staged compilation of a program.
+//!
+//! A [`Compilation`] runs the steps of [`pipeline::compile`](crate::pipeline::compile) one at a
+//! time: [`parse`](Compilation::parse), [`lower`](Compilation::lower),
+//! [`check`](Compilation::check), [`to_lir`](Compilation::to_lir) and
+//! [`evaluate`](Compilation::evaluate). Each stage runs the stages before it that did not run yet
+//! and keeps its result, so tools like formatters, language servers or IR dumpers can inspect the
+//! program at any stage and then continue compiling it from there without running the earlier
+//! stages again.
+//!
+//! Tools that compile many versions of the same program, like language servers after each edit,
+//! can also keep a [`Cache`] to only type-check the definitions that changed, see
+//! [`Compilation::check_with_cache`].
+use pijama_ast::{Block, Located};
+use pijama_core::{
+    bytecode,
+    lint::lint,
+    lir::Term as LirTerm,
+    machine::{arithmetic::Arithmetic, env::Output, Machine, MachineBuilder, Value},
+    mir::{optimize_with, Term as MirTerm},
+    ty::{ty_check, Ty},
+};
+
+use crate::{
+    pipeline::{Backend, Options},
+    prelude::with_prelude,
+    Cache, LangError, LangResult, Source, Warning,
+};
+
+/// The MIR of a program that type-checks.
+#[derive(Debug, Clone)]
+pub struct TypedMir<'a> {
+    /// The MIR, before any pass is run over it.
+    pub mir: Located<MirTerm<'a>>,
+    /// The type of the program.
+    pub ty: Ty,
+    /// The warnings found in the program.
+    pub warnings: Vec<Warning>,
+}
+
+/// The compilation of a program, one stage at a time.
+///
+/// If a stage fails, its error is returned by that stage and by every stage after it.
+pub struct Compilation<'a> {
+    options: Options,
+    /// The program, until it is parsed.
+    source: Option<Box<dyn FnOnce() -> LangResult<Located<Block<'a>>> + 'a>>,
+    /// The error found while parsing the program, which cannot be parsed again.
+    error: Option<LangError>,
+    ast: Option<Located<Block<'a>>>,
+    mir: Option<Located<MirTerm<'a>>>,
+    typed: Option<TypedMir<'a>>,
+    lir: Option<LirTerm>,
+}
+
+impl<'a> Compilation<'a> {
+    /// Creates the compilation of `input`, running the passes and using the backend chosen in
+    /// `options`.
+    ///
+    /// No stage is run until one of them is asked for.
+    pub fn new(input: impl Source<'a> + 'a, options: Options) -> Self {
+        Compilation {
+            options,
+            source: Some(Box::new(move || input.ast())),
+            error: None,
+            ast: None,
+            mir: None,
+            typed: None,
+            lir: None,
+        }
+    }
+
+    /// Returns the options of the compilation.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Parses the program, adding the definitions of the [`prelude`](crate::prelude) it uses if
+    /// [`Options::prelude`] is set.
+    pub fn parse(&mut self) -> LangResult<&Located<Block<'a>>> {
+        if self.ast.is_none() {
+            if let Some(error) = &self.error {
+                return Err(error.clone());
+            }
+            let source = self.source.take().expect("The program is parsed only once");
+            match source() {
+                Ok(ast) if self.options.prelude => self.ast = Some(with_prelude(ast)),
+                Ok(ast) => self.ast = Some(ast),
+                Err(error) => {
+                    self.error = Some(error.clone());
+                    return Err(error);
+                }
+            }
+        }
+        Ok(self.ast.as_ref().unwrap())
+    }
+
+    /// Lowers the parsed program to the MIR.
+    pub fn lower(&mut self) -> LangResult<&Located<MirTerm<'a>>> {
+        if self.mir.is_none() {
+            let ast = self.parse()?.clone();
+            self.mir = Some(MirTerm::from_ast(ast)?);
+        }
+        Ok(self.mir.as_ref().unwrap())
+    }
+
+    /// Type-checks and lints the MIR of the program.
+    pub fn check(&mut self) -> LangResult<&TypedMir<'a>> {
+        self.check_with(|mir| Ok(ty_check(mir)?.content))
+    }
+
+    /// Type-checks and lints the MIR of the program like [`Compilation::check`], reusing the
+    /// types in `cache` of the top-level definitions that did not change.
+    ///
+    /// See the [`cache`](crate::cache) module for details on which definitions are checked again.
+    pub fn check_with_cache(&mut self, cache: &mut Cache) -> LangResult<&TypedMir<'a>> {
+        self.check_with(|mir| cache.check_mir(mir))
+    }
+
+    fn check_with(
+        &mut self,
+        ty_check: impl FnOnce(&Located<MirTerm<'a>>) -> LangResult<Ty>,
+    ) -> LangResult<&TypedMir<'a>> {
+        if self.typed.is_none() {
+            let mir = self.lower()?.clone();
+            let ty = ty_check(&mir)?;
+            let warnings = lint(&mir);
+            self.typed = Some(TypedMir { mir, ty, warnings });
+        }
+        Ok(self.typed.as_ref().unwrap())
+    }
+
+    /// Runs the passes chosen in the options over the type-checked program and lowers it to the
+    /// LIR.
+    pub fn to_lir(&mut self) -> LangResult<&LirTerm> {
+        if self.lir.is_none() {
+            let mir = self.check()?.mir.clone();
+            let (mir_passes, lir_passes) = self.options.split_passes();
+            let mir = optimize_with(mir, &mir_passes);
+            self.lir = Some(LirTerm::from_mir_with(mir, &lir_passes));
+        }
+        Ok(self.lir.as_ref().unwrap())
+    }
+
+    /// Evaluates the program with a default machine and returns its value.
+    ///
+    /// Returns `None` if the program evaluates to a function.
+    pub fn evaluate(&mut self) -> LangResult<Option<Value>> {
+        self.evaluate_with_machine(MachineBuilder::default().build())
+    }
+
+    /// Evaluates the program using `machine` and the backend chosen in the options, and returns
+    /// its value.
+    ///
+    /// The program is not consumed, so it can be evaluated again. Returns `None` if the program
+    /// evaluates to a function.
+    pub fn evaluate_with_machine<W: Output, A: Arithmetic>(
+        &mut self,
+        mut machine: Machine<W, A>,
+    ) -> LangResult<Option<Value>> {
+        let lir = self.to_lir()?.clone();
+        let res = match self.options.backend {
+            Backend::Machine => machine.evaluate(lir),
+            Backend::Bytecode => machine.evaluate_bytecode(&bytecode::compile(&lir)),
+            #[cfg(feature = "jit")]
+            Backend::Jit => machine.evaluate_jit(lir),
+        }?;
+        let ty = &self.typed.as_ref().unwrap().ty;
+        Ok(Value::from_term(&res, ty))
+    }
+}
@@ -0,0 +1,180 @@
+//! Compiler configuration.
+//!
+//! A [`Config`] can be read from a TOML file (by default `pijama.toml` in the current directory)
+//! and from environment variables prefixed with `PIJAMA_`. Environment variables take precedence
+//! over the values in the file. Embedders can also build a `Config` by hand.
+//!
+//! The recognized keys and their environment variables are:
+//!
+//! | key              | environment variable    | values                      |
+//! |------------------|-------------------------|-----------------------------|
+//! | `opt_level`      | `PIJAMA_OPT_LEVEL`      | `0`, `1`, ...               |
+//! | `warnings`       | `PIJAMA_WARNINGS`       | `allow`, `warn`, `deny`     |
+//! | `fuel`           | `PIJAMA_FUEL`           | any non-negative integer    |
+//! | `color`          | `PIJAMA_COLOR`          | `auto`, `always`, `never`   |
+//! | `overflow_check` | `PIJAMA_OVERFLOW_CHECK` | `true`, `false`             |
+use serde::Deserialize;
+use thiserror::Error;
+
+use std::{fs::read_to_string, io, path::Path, str::FromStr};
+
+/// Name of the configuration file looked up by [`Config::load`].
+pub const CONFIG_FILE: &str = "pijama.toml";
+
+/// Prefix shared by all the environment variables read by [`Config::merge_env`].
+const ENV_PREFIX: &str = "PIJAMA_";
+
+/// The type returned by fallible functions in this module.
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
+/// An error while loading the configuration.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    #[error("Could not read configuration file: {0}")]
+    Io(#[from] io::Error),
+    /// The configuration file is not valid.
+    #[error("Invalid configuration file: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// An environment variable has an invalid value.
+    #[error("Invalid value `{value}` for environment variable `{var}`")]
+    Env { var: String, value: String },
+}
+
+/// Compiler configuration.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Optimization level. `0` disables all optimizations.
+    pub opt_level: u8,
+    /// How warnings should be reported.
+    pub warnings: WarningLevel,
+    /// Maximum number of evaluation steps. `None` means there is no limit.
+    pub fuel: Option<u64>,
+    /// Whether diagnostics should be colored.
+    pub color: ColorChoice,
+    /// Whether integer overflow should stop the execution.
+    pub overflow_check: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            opt_level: 0,
+            warnings: WarningLevel::Warn,
+            fuel: None,
+            color: ColorChoice::Auto,
+            overflow_check: false,
+        }
+    }
+}
+
+impl Config {
+    /// Parses a configuration from the contents of a TOML file.
+    ///
+    /// Missing keys take their default value.
+    pub fn from_toml(input: &str) -> ConfigResult<Self> {
+        Ok(toml::from_str(input)?)
+    }
+
+    /// Reads the configuration from the TOML file in `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> ConfigResult<Self> {
+        Self::from_toml(&read_to_string(path)?)
+    }
+
+    /// Loads the configuration used by the driver.
+    ///
+    /// The configuration is read from `path` or, if it is `None`, from [`CONFIG_FILE`] in the
+    /// current directory if such file exists. Then the values of the process' environment
+    /// variables are merged on top of it.
+    pub fn load(path: Option<&Path>) -> ConfigResult<Self> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(CONFIG_FILE).is_file() => Self::from_file(CONFIG_FILE)?,
+            None => Self::default(),
+        };
+        config.merge_env(std::env::vars())?;
+        Ok(config)
+    }
+
+    /// Overrides the fields of this configuration with the values of the `PIJAMA_*` variables in
+    /// `vars`. Any other variable is ignored.
+    pub fn merge_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> ConfigResult<()> {
+        for (var, value) in vars {
+            let key = match var.strip_prefix(ENV_PREFIX) {
+                Some(key) => key,
+                None => continue,
+            };
+            match key {
+                "OPT_LEVEL" => self.opt_level = parse_env(&var, &value)?,
+                "WARNINGS" => self.warnings = parse_env(&var, &value)?,
+                "FUEL" => self.fuel = Some(parse_env(&var, &value)?),
+                "COLOR" => self.color = parse_env(&var, &value)?,
+                "OVERFLOW_CHECK" => self.overflow_check = parse_env(&var, &value)?,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the value of an environment variable.
+fn parse_env<T: FromStr>(var: &str, value: &str) -> ConfigResult<T> {
+    value.parse().map_err(|_| ConfigError::Env {
+        var: var.to_owned(),
+        value: value.to_owned(),
+    })
+}
+
+/// How warnings should be reported.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningLevel {
+    /// Warnings are not reported.
+    Allow,
+    /// Warnings are reported but do not stop the compilation.
+    Warn,
+    /// Warnings are reported as errors.
+    Deny,
+}
+
+impl FromStr for WarningLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "allow" => Ok(WarningLevel::Allow),
+            "warn" => Ok(WarningLevel::Warn),
+            "deny" => Ok(WarningLevel::Deny),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether an output should be colored.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Use colors if the environment seems to support them.
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(()),
+        }
+    }
+}
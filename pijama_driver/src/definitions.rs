@@ -0,0 +1,182 @@
+//! Definitions added to the start of a program.
+//!
+//! The prelude and the modules imported by a program are added to it as definitions before its
+//! first line. Only the definitions used by the program, directly or through other added
+//! definitions, are kept. They keep their locations, moved to the file they were parsed from, so
+//! diagnostics can point to them, see [`SourceMap`](crate::SourceMap).
+use std::collections::HashSet;
+
+use pijama_ast::{
+    ty::TyAnnotation, visitor::NodeVisitor, Arm, Block, Branch, FileId, Located, Name, Node,
+    Pattern,
+};
+
+/// Adds the nodes of `definitions` used by `program` before its first line, keeping their order.
+///
+/// Each node of `definitions` can only use the names defined by the nodes before it.
+pub(crate) fn prepend_used<'a>(
+    mut program: Located<Block<'a>>,
+    definitions: Block<'a>,
+) -> Located<Block<'a>> {
+    let mut names = Names::default();
+    names.visit_block(&program.content);
+    for node in definitions.into_iter().rev() {
+        if defines(&node).iter().any(|name| names.0.contains(name)) {
+            names.visit_node(&node);
+            program.content.push_front(node);
+        }
+    }
+
+    program
+}
+
+/// Moves the locations and ids of `definitions`, which were parsed from `file`, to that file.
+pub(crate) fn move_to_file(definitions: &mut Block<'_>, file: FileId) {
+    definitions.iter_mut().for_each(|node| relocate(node, file));
+}
+
+/// Returns the names defined by a top-level node.
+fn defines<'a>(node: &Located<Node<'a>>) -> Vec<&'a str> {
+    match &node.content {
+        Node::LetBind(annotation, _) | Node::LetMut(annotation, _) => {
+            vec![annotation.item.content.0]
+        }
+        Node::FnDef(name, _, _, _) => vec![name.content.0],
+        Node::EnumDef(_, variants) => variants
+            .iter()
+            .map(|variant| variant.name.content.0)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Visitor collecting the names used by a block, including the variants used in patterns.
+#[derive(Default)]
+struct Names<'a>(HashSet<&'a str>);
+
+impl<'a> NodeVisitor<'a> for Names<'a> {
+    fn visit_fn_def(
+        &mut self,
+        _name: &Located<Name<'a>>,
+        _args: &[TyAnnotation<Name<'a>>],
+        body: &TyAnnotation<Block<'a>>,
+    ) {
+        // The name of a function is only used if the function is recursive.
+        self.visit_block(&body.item.content);
+    }
+
+    fn visit_arm(&mut self, arm: &Arm<'a>) {
+        if let Pattern::Variant(name, _) = &arm.pattern.content {
+            self.0.insert(name.content.0);
+        }
+        self.super_arm(arm);
+    }
+
+    fn visit_name(&mut self, name: &Name<'a>) {
+        self.0.insert(name.0);
+    }
+}
+
+/// Moves the location and id of `node` and of everything inside it to `file`.
+fn relocate(node: &mut Located<Node<'_>>, file: FileId) {
+    node.loc.file = file;
+    node.id = node.id.in_file(file);
+    match &mut node.content {
+        Node::BinaryOp(_, node1, node2) => {
+            relocate(node1, file);
+            relocate(node2, file);
+        }
+        Node::UnaryOp(_, node) | Node::Ascription(node, _) | Node::Lazy(node) => {
+            relocate(node, file)
+        }
+        Node::Operator(_, node1, node2) => {
+            for node in node1.iter_mut().chain(node2) {
+                relocate(node, file);
+            }
+        }
+        Node::LetBind(annotation, node) | Node::LetMut(annotation, node) => {
+            relocate_annotation(annotation, file);
+            relocate(node, file);
+        }
+        Node::LetIn(annotation, node1, node2) => {
+            relocate_annotation(annotation, file);
+            relocate(node1, file);
+            relocate(node2, file);
+        }
+        Node::Assign(name, node) => {
+            name.loc.file = file;
+            relocate(node, file);
+        }
+        Node::Cond(if_branch, branches, else_blk) => {
+            for Branch { cond, body } in Some(if_branch).into_iter().chain(branches) {
+                relocate_block(cond, file);
+                relocate_block(body, file);
+            }
+            relocate_block(else_blk, file);
+        }
+        Node::While(Branch { cond, body }) => {
+            relocate_block(cond, file);
+            relocate_block(body, file);
+        }
+        Node::Block(block) => relocate_block(block, file),
+        Node::For(name, start, end, body) => {
+            name.loc.file = file;
+            relocate(start, file);
+            relocate(end, file);
+            relocate_block(body, file);
+        }
+        Node::FnDef(name, args, body, _) => {
+            name.loc.file = file;
+            args.iter_mut()
+                .for_each(|annotation| relocate_annotation(annotation, file));
+            body.ty.loc.file = file;
+            relocate_block(&mut body.item, file);
+        }
+        Node::AnonFn(args, body) => {
+            args.iter_mut()
+                .for_each(|annotation| relocate_annotation(annotation, file));
+            body.ty.loc.file = file;
+            relocate_block(&mut body.item, file);
+        }
+        Node::Call(func, args) => {
+            relocate(func, file);
+            args.iter_mut().for_each(|node| relocate(node, file));
+        }
+        Node::List(elems) | Node::Interpolation(elems) => {
+            elems.iter_mut().for_each(|node| relocate(node, file))
+        }
+        Node::EnumDef(name, variants) => {
+            name.loc.file = file;
+            for variant in variants {
+                variant.name.loc.file = file;
+                for field in &mut variant.fields {
+                    field.loc.file = file;
+                }
+            }
+        }
+        Node::Match(node, arms) => {
+            relocate(node, file);
+            for Arm { pattern, body } in arms {
+                pattern.loc.file = file;
+                if let Pattern::Variant(name, binders) = &mut pattern.content {
+                    name.loc.file = file;
+                    for binder in binders {
+                        binder.loc.file = file;
+                    }
+                }
+                relocate_block(body, file);
+            }
+        }
+        Node::Literal(_) | Node::Name(_) | Node::PrimFn(_) | Node::Import(_) | Node::Error => (),
+    }
+}
+
+fn relocate_block(blk: &mut Located<Block<'_>>, file: FileId) {
+    blk.loc.file = file;
+    blk.content.iter_mut().for_each(|node| relocate(node, file));
+}
+
+fn relocate_annotation(annotation: &mut TyAnnotation<Name<'_>>, file: FileId) {
+    annotation.item.loc.file = file;
+    annotation.ty.loc.file = file;
+}
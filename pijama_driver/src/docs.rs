@@ -0,0 +1,57 @@
+//! Documentation of the functions of a program.
+//!
+//! Functions can be documented with doc comments, lines starting with `##` right before the
+//! definition. The [`extract`] function collects the doc comments of the top-level functions of a
+//! program together with their types, so tools can generate documentation for the program.
+use serde::Serialize;
+
+use pijama_ast::{Block, Located, Location, Node};
+use pijama_core::ty::Ty;
+
+use crate::{check, LangResult};
+
+/// The documentation of a top-level function.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct FnDoc {
+    /// The name of the function.
+    pub name: String,
+    /// The type of the function, as inferred by the type checker.
+    pub ty: Ty,
+    /// The text of the doc comment, with its lines separated by line breaks.
+    pub text: String,
+    /// The location of the function definition.
+    pub loc: Location,
+}
+
+/// Returns the documentation of each top-level function of `ast` with a doc comment, in order.
+///
+/// The imports of `ast` must be already resolved, like in any other parsed [`Source`]. The type
+/// of each function is found by checking the program up to its definition, so this fails with
+/// the first error found in those definitions.
+///
+/// [`Source`]: crate::Source
+pub fn extract<'a>(ast: &Located<Block<'a>>) -> LangResult<Vec<FnDoc>> {
+    let mut docs = Vec::new();
+    for (index, node) in ast.content.iter().enumerate() {
+        let (name, lines) = match &node.content {
+            Node::FnDef(name, _, _, lines) if !lines.is_empty() => (name, lines),
+            _ => continue,
+        };
+        // The functions defined right after this one can be called by it, so they are checked
+        // together.
+        let group = ast.content.iter().skip(index + 1).take_while(
+            |node| matches!(&node.content, Node::FnDef(other, ..) if other.content != name.content),
+        );
+        let mut program: Block<'a> = ast.content.iter().take(index + 1).cloned().collect();
+        program.extend(group.cloned());
+        program.push_back(name.loc.with_content(Node::Name(name.content)));
+
+        docs.push(FnDoc {
+            name: name.content.0.to_owned(),
+            ty: check(ast.loc.with_content(program))?,
+            text: lines.join("\n"),
+            loc: node.loc,
+        });
+    }
+    Ok(docs)
+}
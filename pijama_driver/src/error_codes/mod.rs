@@ -0,0 +1,73 @@
+//! Extended explanations for error codes.
+//!
+//! Every error reported by the compiler has a stable code that can be looked up with
+//! [`explain`]. The code of an error is returned by [`LangError::code`](crate::LangError::code).
+//!
+//! | code    | error                                                                 |
+//! |---------|-----------------------------------------------------------------------|
+//! | `E0001` | [`ParsingError`](pijama_core::parser::ParsingError)                   |
+//! | `E0001` | [`LowerError::Unparsed`](pijama_core::mir::LowerError)                |
+//! | `E0002` | [`TyError::Mismatch`](pijama_core::ty::TyError)                       |
+//! | `E0003` | [`TyError::Unbounded`](pijama_core::ty::TyError)                      |
+//! | `E0004` | [`TyError::AmbiguousReturn`](pijama_core::ty::TyError)                |
+//! | `E0005` | [`LowerError::AnonWithTy`](pijama_core::mir::LowerError)              |
+//! | `E0006` | [`CodegenError::Unsupported`](pijama_core::codegen::CodegenError)     |
+//! | `E0007` | [`TyError::FieldCount`](pijama_core::ty::TyError)                     |
+//! | `E0008` | [`TyError::NonExhaustive`](pijama_core::ty::TyError)                  |
+//! | `E0009` | [`LowerError::UnresolvedImport`](pijama_core::mir::LowerError)        |
+//! | `E0010` | [`TyError::Immutable`](pijama_core::ty::TyError)                      |
+//! | `E0011` | [`TyError::NotPrintable`](pijama_core::ty::TyError)                   |
+//! | `E0012` | [`RuntimeError::InvalidInt`](pijama_core::machine::RuntimeError)      |
+//! | `E0013` | [`RuntimeError::AssertionFailed`](pijama_core::machine::RuntimeError) |
+//! | `E0014` | [`RuntimeError::DivisionByZero`](pijama_core::machine::RuntimeError)  |
+//! | `E0015` | [`RuntimeError::Overflow`](pijama_core::machine::RuntimeError)        |
+//! | `E0016` | [`RuntimeError::OutOfFuel`](pijama_core::machine::RuntimeError)       |
+//! | `E0017` | [`RuntimeError::CapabilityDisabled`](pijama_core::machine::RuntimeError) |
+//! | `E0018` | [`RuntimeError::Deadlock`](pijama_core::machine::RuntimeError)        |
+//! | `E0019` | [`RuntimeError::StackOverflow`](pijama_core::machine::RuntimeError)   |
+//! | `E0020` | [`RuntimeError::Nondeterministic`](pijama_core::machine::RuntimeError) |
+//! | `E0021` | [`RuntimeError::InvalidChar`](pijama_core::machine::RuntimeError)     |
+//! | `E0022` | [`RuntimeError::IndexOutOfBounds`](pijama_core::machine::RuntimeError) |
+//! | `E0023` | [`TyError::IntOutOfRange`](pijama_core::ty::TyError)                  |
+//! | `E0024` | [`RuntimeError::OutputFailed`](pijama_core::machine::RuntimeError)    |
+//! | `E0025` | [`RuntimeError::InvalidRange`](pijama_core::machine::RuntimeError)    |
+
+/// All the error codes with their explanations, sorted by code.
+pub const ERROR_CODES: &[(&str, &str)] = &[
+    ("E0001", include_str!("E0001.md")),
+    ("E0002", include_str!("E0002.md")),
+    ("E0003", include_str!("E0003.md")),
+    ("E0004", include_str!("E0004.md")),
+    ("E0005", include_str!("E0005.md")),
+    ("E0006", include_str!("E0006.md")),
+    ("E0007", include_str!("E0007.md")),
+    ("E0008", include_str!("E0008.md")),
+    ("E0009", include_str!("E0009.md")),
+    ("E0010", include_str!("E0010.md")),
+    ("E0011", include_str!("E0011.md")),
+    ("E0012", include_str!("E0012.md")),
+    ("E0013", include_str!("E0013.md")),
+    ("E0014", include_str!("E0014.md")),
+    ("E0015", include_str!("E0015.md")),
+    ("E0016", include_str!("E0016.md")),
+    ("E0017", include_str!("E0017.md")),
+    ("E0018", include_str!("E0018.md")),
+    ("E0019", include_str!("E0019.md")),
+    ("E0020", include_str!("E0020.md")),
+    ("E0021", include_str!("E0021.md")),
+    ("E0022", include_str!("E0022.md")),
+    ("E0023", include_str!("E0023.md")),
+    ("E0024", include_str!("E0024.md")),
+    ("E0025", include_str!("E0025.md")),
+];
+
+/// Returns the extended explanation of `code`, or `None` if the code does not exist.
+///
+/// The explanation is written in Markdown and has examples of code that causes the error and of
+/// how to fix it.
+pub fn explain(code: &str) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|(other, _)| *other == code)
+        .map(|(_, explanation)| *explanation)
+}
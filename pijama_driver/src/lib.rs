@@ -1,58 +1,442 @@
 use thiserror::Error;
 
-use std::io::Write;
+use std::{io::Write, path::PathBuf};
 
 use pijama_core::{
+    codegen::{js, rust, wasm, CodegenError},
+    lint::lint,
     lir::Term as LirTerm,
     machine::{
-        arithmetic::{Arithmetic, CheckedArithmetic, OverflowArithmetic},
-        Machine, MachineBuilder,
+        arithmetic::{Arithmetic, CheckedArithmetic, WrappingArithmetic},
+        native::NativeFn,
+        Machine, MachineBuilder, RuntimeError, Value,
     },
-    mir::{LowerError, Term as MirTerm},
-    parser::{parse, ParsingError},
-    ty::{ty_check, TyError},
+    mir::{LetKind, LowerError, Term as MirTerm},
+    parser::{parse_recovering, ParsingError},
+    ty::{ty_check, ty_check_terms, Ty, TyError},
 };
 
-pub type LangResult<'a, T> = Result<T, LangError<'a>>;
+use pijama_ast::{Block, Located, Location, Name};
 
-#[derive(Error, Debug, Eq, PartialEq)]
-pub enum LangError<'a> {
+pub mod cache;
+pub mod compilation;
+pub mod config;
+mod definitions;
+pub mod docs;
+pub mod error_codes;
+pub mod modules;
+pub mod pipeline;
+pub mod prelude;
+pub mod program;
+pub mod query;
+pub mod source_map;
+pub mod stack_trace;
+pub mod suggest;
+pub mod timings;
+
+pub use cache::Cache;
+pub use compilation::Compilation;
+pub use config::Config;
+pub use modules::Modules;
+pub use pijama_core::lint::Warning;
+pub use program::Program;
+pub use source_map::SourceMap;
+pub use stack_trace::Call;
+
+use pipeline::{compile_timed, CompiledProgram, Options};
+use prelude::with_prelude;
+use stack_trace::Callees;
+use timings::{Phase, Timings};
+
+pub type LangResult<T> = Result<T, LangError>;
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum LangError {
     #[error("{0}")]
     Ty(#[from] TyError),
     #[error("{0}")]
-    Parse(ParsingError<'a>),
+    Parse(ParsingError),
     #[error("{0}")]
     Lower(#[from] LowerError),
+    #[error("{0}")]
+    Codegen(#[from] CodegenError),
+    /// An error found while evaluating the program.
+    #[error("{0}")]
+    Runtime(#[from] RuntimeError),
+    /// A runtime error found inside some function calls, see the [`stack_trace`] module.
+    #[error("{error}")]
+    Traced {
+        /// The error, which is a [`LangError::Runtime`].
+        error: Box<LangError>,
+        /// The calls that were being evaluated when the error was found, innermost first. It
+        /// has at least one call.
+        calls: Vec<Call>,
+    },
+    /// An error in a module imported by the program, see the [`modules`] module.
+    #[error("{}: {error}", .path.display())]
+    Module {
+        /// The path of the module.
+        path: PathBuf,
+        /// The source code of the module, which the location of the error refers to.
+        input: String,
+        error: Box<LangError>,
+    },
+    /// Several errors found in the same phase, in the order they were found.
+    ///
+    /// It has at least two errors and none of them is a [`LangError::Multiple`].
+    #[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    Multiple(Vec<LangError>),
+}
+
+impl LangError {
+    /// Returns the location of the error.
+    ///
+    /// The location of a [`LangError::Module`] refers to the source code of the module.
+    pub fn loc(&self) -> Location {
+        match self {
+            LangError::Ty(error) => error.loc(),
+            LangError::Parse(error) => error.loc(),
+            LangError::Lower(error) => error.loc(),
+            LangError::Codegen(error) => error.loc(),
+            LangError::Runtime(error) => error.loc(),
+            LangError::Module { error, .. } | LangError::Traced { error, .. } => error.loc(),
+            LangError::Multiple(errors) => errors[0].loc(),
+        }
+    }
+
+    /// Returns the stable code of the error.
+    ///
+    /// The [`error_codes`] module has an extended explanation for each code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LangError::Ty(error) => error.code(),
+            LangError::Parse(error) => error.code(),
+            LangError::Lower(error) => error.code(),
+            LangError::Codegen(error) => error.code(),
+            LangError::Runtime(error) => error.code(),
+            LangError::Module { error, .. } | LangError::Traced { error, .. } => error.code(),
+            LangError::Multiple(errors) => errors[0].code(),
+        }
+    }
+
+    /// Returns the errors inside a [`LangError::Multiple`], or this error if it is a single one.
+    ///
+    /// The location and the code of a [`LangError::Multiple`] are the ones of its first error.
+    pub fn errors(&self) -> &[LangError] {
+        match self {
+            LangError::Multiple(errors) => errors,
+            error => core::slice::from_ref(error),
+        }
+    }
 }
 
-impl<'a> From<ParsingError<'a>> for LangError<'a> {
-    fn from(err: ParsingError<'a>) -> Self {
+impl From<ParsingError> for LangError {
+    fn from(err: ParsingError) -> Self {
         LangError::Parse(err)
     }
 }
 
-pub fn run_with_machine<W: Write, A: Arithmetic>(
-    input: &str,
+impl From<Vec<ParsingError>> for LangError {
+    /// Wraps the errors returned by the recovering parser, which are never empty.
+    fn from(mut errors: Vec<ParsingError>) -> Self {
+        if errors.len() == 1 {
+            LangError::Parse(errors.remove(0))
+        } else {
+            LangError::Multiple(errors.into_iter().map(LangError::Parse).collect())
+        }
+    }
+}
+
+impl From<Vec<TyError>> for LangError {
+    /// Wraps the errors returned by the type checker, which are never empty.
+    fn from(mut errors: Vec<TyError>) -> Self {
+        if errors.len() == 1 {
+            LangError::Ty(errors.remove(0))
+        } else {
+            LangError::Multiple(errors.into_iter().map(LangError::Ty).collect())
+        }
+    }
+}
+
+/// The source code of a program.
+///
+/// A program can be written in a single string or be loaded from a file with the modules it
+/// imports, see the [`modules`] module. Programs that are already parsed are sources too.
+pub trait Source<'a> {
+    /// Returns the parsed program, with its imports resolved.
+    fn ast(self) -> LangResult<Located<Block<'a>>>;
+}
+
+/// Programs in strings report every statement that cannot be parsed, see
+/// [`parse_recovering`].
+impl<'a> Source<'a> for &'a str {
+    fn ast(self) -> LangResult<Located<Block<'a>>> {
+        let (block, errors) = parse_recovering(self);
+        if errors.is_empty() {
+            Ok(block)
+        } else {
+            Err(errors.into())
+        }
+    }
+}
+
+impl<'a> Source<'a> for &'a String {
+    fn ast(self) -> LangResult<Located<Block<'a>>> {
+        self.as_str().ast()
+    }
+}
+
+impl<'a> Source<'a> for Located<Block<'a>> {
+    fn ast(self) -> LangResult<Located<Block<'a>>> {
+        Ok(self)
+    }
+}
+
+/// Parses, lowers and type-checks `input` without evaluating it, returning the type of the
+/// program.
+pub fn check<'a>(input: impl Source<'a>) -> LangResult<Ty> {
+    check_with_warnings(input).0
+}
+
+/// Checks `input` like [`check`], also returning the warnings found in the program.
+///
+/// Warnings are only reported for programs that type-check.
+pub fn check_with_warnings<'a>(input: impl Source<'a>) -> (LangResult<Ty>, Vec<Warning>) {
+    match check_and_lint(input) {
+        Ok((ty, warnings)) => (Ok(ty), warnings),
+        Err(err) => (Err(err), Vec::new()),
+    }
+}
+
+fn check_and_lint<'a>(input: impl Source<'a>) -> LangResult<(Ty, Vec<Warning>)> {
+    let mir = lower(input)?;
+    let ty = ty_check(&mir)?.content;
+    Ok((ty, lint(&mir)))
+}
+
+/// Parses, lowers and type-checks `input` and emits it as a JavaScript script.
+///
+/// See [`pijama_core::codegen::js`] for details on how the program is translated.
+pub fn emit_js<'a>(input: impl Source<'a>) -> LangResult<String> {
+    let mir = lower(input)?;
+    ty_check(&mir)?;
+    Ok(js::emit(&mir)?)
+}
+
+/// Parses, lowers and type-checks `input` and emits it as a public Rust function called `name`
+/// that returns the value of the program.
+///
+/// See [`pijama_core::codegen::rust`] for details on how the program is translated.
+pub fn emit_rust<'a>(input: impl Source<'a>, name: &str) -> LangResult<String> {
+    let mir = lower(input)?;
+    let tys = ty_check_terms(&mir)?;
+    Ok(rust::emit(&mir, &tys, name)?)
+}
+
+/// Parses, lowers and type-checks `input` and compiles it into a WebAssembly module.
+///
+/// See [`pijama_core::codegen::wasm`] for details on how the program is compiled.
+pub fn emit_wasm<'a>(input: impl Source<'a>) -> LangResult<Vec<u8>> {
+    let mir = lower(input)?;
+    ty_check(&mir)?;
+    Ok(wasm::compile(&LirTerm::from_mir(mir))?)
+}
+
+pub fn run_with_machine<'a, W: Write, A: Arithmetic>(
+    input: impl Source<'a>,
+    machine: Machine<W, A>,
+) -> LangResult<()> {
+    run_with_timings(input, machine, &mut Timings::default(), &mut Vec::new())
+}
+
+/// Runs the whole pipeline like [`run_with_machine`], recording the duration of each phase in
+/// `timings` and pushing the warnings found in the program to `warnings`.
+///
+/// Warnings are only reported for programs that type-check, before they are evaluated.
+pub fn run_with_timings<'a, W: Write, A: Arithmetic>(
+    input: impl Source<'a>,
+    machine: Machine<W, A>,
+    timings: &mut Timings,
+    warnings: &mut Vec<Warning>,
+) -> LangResult<()> {
+    run_with_options(input, machine, &Options::default(), timings, warnings)
+}
+
+/// Runs the whole pipeline like [`run_with_timings`], running the passes chosen in `options`
+/// before evaluating the program with the backend chosen in `options`.
+///
+/// See [`pipeline::compile`] for details on how the program is compiled. Runtime errors found
+/// inside some function calls are returned with the calls as a [`LangError::Traced`]. Whether the
+/// calls are known depends on the backend and the passes, see the [`stack_trace`] module.
+pub fn run_with_options<'a, W: Write, A: Arithmetic>(
+    input: impl Source<'a>,
     mut machine: Machine<W, A>,
+    options: &Options,
+    timings: &mut Timings,
+    warnings: &mut Vec<Warning>,
+) -> LangResult<()> {
+    let CompiledProgram {
+        mir,
+        lir,
+        warnings: found,
+        bytecode,
+        ..
+    } = compile_timed(
+        input,
+        &Options {
+            int_width: machine.int_width(),
+            ..options.clone()
+        },
+        machine.natives(),
+        &[],
+        timings,
+    )?;
+    warnings.extend(found);
+    let callees = Callees::new(&mir);
+    let result = timings.time(Phase::Evaluation, || match (bytecode, options.backend) {
+        (Some(program), _) => machine.evaluate_bytecode(&program),
+        #[cfg(feature = "jit")]
+        (None, pipeline::Backend::Jit) => machine.evaluate_jit(lir),
+        (None, _) => machine.evaluate(lir),
+    });
+    match result {
+        Ok(_) => Ok(()),
+        Err(error) => {
+            let calls = callees.calls(machine.stack_trace());
+            let error = LangError::Runtime(error);
+            if calls.is_empty() {
+                Err(error)
+            } else {
+                Err(LangError::Traced {
+                    error: Box::new(error),
+                    calls,
+                })
+            }
+        }
+    }
+}
+
+pub fn run<'a>(input: impl Source<'a>, overflow_check: bool) -> LangResult<()> {
+    run_timed(
+        input,
+        &Config {
+            overflow_check,
+            ..Config::default()
+        },
+        &mut Timings::default(),
+        &mut Vec::new(),
+    )
+}
+
+/// Runs `input` like [`run`], also returning the warnings found in the program.
+pub fn run_with_warnings<'a>(
+    input: impl Source<'a>,
+    overflow_check: bool,
+) -> (LangResult<()>, Vec<Warning>) {
+    let mut warnings = Vec::new();
+    let result = run_timed(
+        input,
+        &Config {
+            overflow_check,
+            ..Config::default()
+        },
+        &mut Timings::default(),
+        &mut warnings,
+    );
+    (result, warnings)
+}
+
+/// Runs the whole pipeline like [`run`], with the optimization level, the fuel and the overflow
+/// checks chosen in `config`, recording the duration of each phase in `timings` and pushing the
+/// warnings found in the program to `warnings`.
+///
+/// The other fields of `config` are about reporting and are ignored.
+pub fn run_timed<'a>(
+    input: impl Source<'a>,
+    config: &Config,
+    timings: &mut Timings,
+    warnings: &mut Vec<Warning>,
 ) -> LangResult<()> {
-    let ast = parse(input)?;
-    let mir = MirTerm::from_ast(ast)?;
-    let _ty = ty_check(&mir)?;
-    let lir = LirTerm::from_mir(mir);
-    let _res = machine.evaluate(lir);
-    Ok(())
-}
-
-pub fn run(input: &str, overflow_check: bool) -> LangResult<()> {
-    if overflow_check {
-        let machine = MachineBuilder::default()
-            .with_arithmetic(CheckedArithmetic)
-            .build();
-        run_with_machine(input, machine)
+    let options = Options {
+        opt_level: config.opt_level,
+        ..Options::default()
+    };
+    let builder = match config.fuel {
+        Some(fuel) => MachineBuilder::default().with_fuel(fuel),
+        None => MachineBuilder::default(),
+    };
+    if config.overflow_check {
+        let machine = builder.with_arithmetic(CheckedArithmetic).build();
+        run_with_options(input, machine, &options, timings, warnings)
     } else {
-        let machine = MachineBuilder::default()
-            .with_arithmetic(OverflowArithmetic)
-            .build();
-        run_with_machine(input, machine)
+        let machine = builder.with_arithmetic(WrappingArithmetic).build();
+        run_with_options(input, machine, &options, timings, warnings)
+    }
+}
+
+/// Runs `input` with the variables in `bindings` defined at the top level and returns the value
+/// of the program.
+///
+/// Each binding behaves as if it were defined with a type-annotated `let` before the first line
+/// of `input`. If the same name appears more than once, the last binding shadows the others.
+/// The program is evaluated with a default machine.
+///
+/// Returns `None` if the program evaluates to a function.
+pub fn run_with_bindings<'a>(
+    input: impl Source<'a>,
+    bindings: &[(&'a str, Value)],
+) -> LangResult<Option<Value>> {
+    let CompiledProgram { lir, ty, .. } =
+        pipeline::compile_with_bindings(input, &Options::default(), bindings)?;
+    let res = MachineBuilder::default().build().evaluate(lir)?;
+    Ok(Value::from_term(&res, &ty))
+}
+
+/// Parses `input`, adds the definitions of the prelude it uses and lowers it to MIR.
+///
+/// See the [`prelude`] module for details on how the prelude is added.
+fn lower<'a>(input: impl Source<'a>) -> LangResult<Located<MirTerm<'a>>> {
+    let ast = with_prelude(input.ast()?);
+    Ok(MirTerm::from_ast(ast)?)
+}
+
+/// Wraps `term` in a `let` for each binding.
+fn with_bindings<'a>(
+    mut term: Located<MirTerm<'a>>,
+    bindings: &[(&'a str, Value)],
+) -> Located<MirTerm<'a>> {
+    let loc = Location::new(0, 0);
+    for (name, value) in bindings.iter().rev() {
+        let kind = LetKind::NonRec(Some(loc.with_content(value.ty())));
+        let value = value.to_mir(loc);
+        term = term.loc.with_content(MirTerm::Let(
+            kind,
+            loc.with_content(Name(name)),
+            Box::new(value),
+            Box::new(term),
+        ));
+    }
+    term
+}
+
+/// Wraps `term` in a `let` for each native function registered on the machine.
+///
+/// Functions registered later shadow the ones registered before with the same name.
+fn with_natives<'a>(
+    mut term: Located<MirTerm<'a>>,
+    natives: &'a [NativeFn],
+) -> Located<MirTerm<'a>> {
+    let loc = Location::new(0, 0);
+    for (index, native) in natives.iter().enumerate().rev() {
+        let ty = native.ty();
+        let kind = LetKind::NonRec(Some(loc.with_content(ty.clone())));
+        let value = loc.with_content(MirTerm::Native(index, ty));
+        term = term.loc.with_content(MirTerm::Let(
+            kind,
+            loc.with_content(Name(native.name())),
+            Box::new(value),
+            Box::new(term),
+        ));
     }
+    term
 }
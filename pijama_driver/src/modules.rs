@@ -0,0 +1,500 @@
+//! Programs split across several files.
+//!
+//! A file can import other files, called modules, with `import` statements at its top level. The
+//! path of an imported module is relative to the directory of the file importing it. The
+//! top-level definitions of a module, which are its let bindings, functions and enums with their
+//! variants, can be used by the importing file qualified by the namespace of the module, which is
+//! the name of its file without the extension:
+//!
+//! ```pijama
+//! import "shapes/geometry.pj"
+//!
+//! geometry::area(geometry::Rect(2, 3))
+//! ```
+//!
+//! A program and every module it imports, directly or through other modules, are loaded with
+//! [`Modules::load`]. The loaded program is a [`Source`], so it can be checked, compiled and run
+//! like a program written in a single string.
+//!
+//! Each module is type-checked on its own before the program, so the errors in a module are
+//! reported in its own file as a [`LangError::Module`]. Then, the definitions of the modules used
+//! by the program are added before its first line, like the definitions of the
+//! [`prelude`](crate::prelude). The expressions at the top level of a module are never evaluated.
+use thiserror::Error;
+
+use std::{
+    fs::{canonicalize, read_to_string},
+    io,
+    path::{Path, PathBuf},
+};
+
+use pijama_ast::{
+    ty::{Ty, TyAnnotation},
+    Arm, Block, Branch, FileId, Located, Name, Node, Pattern,
+};
+use pijama_core::parser::parse;
+
+use crate::{
+    check,
+    definitions::{move_to_file, prepend_used},
+    prelude::PRELUDE_FILE,
+    LangError, LangResult, Source, SourceMap,
+};
+
+/// An error while loading a program and its modules.
+#[derive(Error, Debug)]
+pub enum LoadError {
+    /// A file could not be read.
+    #[error("Could not read {}: {error}", .path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        error: io::Error,
+    },
+    /// A module imports itself, directly or through other modules.
+    #[error("Module {} imports itself", .0.display())]
+    Cycle(PathBuf),
+    /// Two different modules have the same namespace.
+    #[error("Modules {} and {} have the same namespace", .0.display(), .1.display())]
+    Namespace(PathBuf, PathBuf),
+}
+
+/// A program loaded from a file, with the modules it imports.
+#[derive(Debug)]
+pub struct Modules {
+    /// The loaded files, each one after the modules it imports. The program is the last one.
+    modules: Vec<Module>,
+}
+
+#[derive(Debug)]
+struct Module {
+    /// The path of the file, joined to the directory of the file importing it.
+    path: PathBuf,
+    /// The canonical path of the file, which is the same for every import of the file.
+    canonical: PathBuf,
+    source: String,
+    namespace: String,
+    /// The indices of the modules imported by this one, directly or through other modules, in
+    /// increasing order.
+    dependencies: Vec<usize>,
+    /// The qualified names of the top-level definitions.
+    names: Vec<String>,
+}
+
+impl Module {
+    /// Returns the qualified version of `name`, which must be defined at the top level.
+    fn qualify(&self, name: &str) -> &str {
+        self.names
+            .iter()
+            .find(|qualified| &qualified[self.namespace.len() + 2..] == name)
+            .expect("The names of a module are collected when it is loaded")
+    }
+}
+
+impl Modules {
+    /// Loads the program in `path` and the modules it imports.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let mut modules = Modules {
+            modules: Vec::new(),
+        };
+        modules.load_module(path.as_ref().to_owned(), &mut Vec::new())?;
+        Ok(modules)
+    }
+
+    /// Returns the path of the program.
+    pub fn path(&self) -> &Path {
+        &self.program().path
+    }
+
+    /// Returns the source code of the program.
+    pub fn input(&self) -> &str {
+        &self.program().source
+    }
+
+    /// Returns a source map with the program and every module it imports.
+    ///
+    /// The program is [`FileId::MAIN`] and the modules have the ids after the prelude, in the
+    /// order they were loaded.
+    pub fn source_map(&self) -> SourceMap {
+        let program = self.program();
+        let mut map = SourceMap::new(program.path.display().to_string(), program.source.as_str());
+        for (index, module) in self.modules[..self.modules.len() - 1].iter().enumerate() {
+            let file = map.add(module.path.display().to_string(), module.source.as_str());
+            debug_assert_eq!(self.file(index), file);
+        }
+        map
+    }
+
+    /// Returns the id of the file of the module with `index` in [`Modules::source_map`].
+    fn file(&self, index: usize) -> FileId {
+        if index == self.modules.len() - 1 {
+            FileId::MAIN
+        } else {
+            FileId(PRELUDE_FILE.0 + 1 + index as u32)
+        }
+    }
+
+    fn program(&self) -> &Module {
+        self.modules.last().expect("The program is always loaded")
+    }
+
+    /// Loads the module in `path` and the modules it imports, returning its index.
+    ///
+    /// `stack` has the canonical paths of the modules being loaded, which import this one.
+    fn load_module(&mut self, path: PathBuf, stack: &mut Vec<PathBuf>) -> Result<usize, LoadError> {
+        let io_error = |error| LoadError::Io {
+            path: path.clone(),
+            error,
+        };
+        let canonical = canonicalize(&path).map_err(io_error)?;
+        if stack.contains(&canonical) {
+            return Err(LoadError::Cycle(path));
+        }
+        if let Some(index) = self
+            .modules
+            .iter()
+            .position(|module| module.canonical == canonical)
+        {
+            return Ok(index);
+        }
+
+        let source = read_to_string(&path).map_err(io_error)?;
+        let namespace = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (imports, names) = {
+            // Parsing errors are reported when the program is checked.
+            let nodes = parse(&source).map(|blk| blk.content).unwrap_or_default();
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            let imports: Vec<_> = nodes
+                .iter()
+                .filter_map(|node| match &node.content {
+                    Node::Import(import) => Some(dir.join(import)),
+                    _ => None,
+                })
+                .collect();
+            let mut names = Vec::new();
+            for name in nodes.iter().flat_map(defines) {
+                let name = format!("{}::{}", namespace, name);
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            (imports, names)
+        };
+
+        stack.push(canonical.clone());
+        let mut dependencies = Vec::new();
+        for import in imports {
+            let index = self.load_module(import, stack)?;
+            dependencies.push(index);
+            dependencies.extend_from_slice(&self.modules[index].dependencies);
+        }
+        stack.pop();
+        dependencies.sort_unstable();
+        dependencies.dedup();
+
+        // The definitions of the program are not qualified, so it can have any namespace.
+        if !stack.is_empty() {
+            if let Some(other) = self
+                .modules
+                .iter()
+                .find(|module| module.namespace == namespace)
+            {
+                return Err(LoadError::Namespace(other.path.clone(), path));
+            }
+        }
+
+        self.modules.push(Module {
+            path,
+            canonical,
+            source,
+            namespace,
+            dependencies,
+            names,
+        });
+        Ok(self.modules.len() - 1)
+    }
+
+    /// Returns the module with `index` without its imports, adding the definitions of the
+    /// modules it uses before its first line.
+    fn module_ast(&self, index: usize) -> LangResult<Located<Block<'_>>> {
+        let module = &self.modules[index];
+        let mut program = module.source.as_str().ast()?;
+        program
+            .content
+            .retain(|node| !matches!(node.content, Node::Import(_)));
+
+        let mut definitions = Block::new();
+        for &dependency in &module.dependencies {
+            definitions.extend(self.definitions(dependency)?);
+        }
+        Ok(prepend_used(program, definitions))
+    }
+
+    /// Returns the top-level definitions of the module with `index`, qualified by its namespace.
+    fn definitions(&self, index: usize) -> LangResult<Block<'_>> {
+        let module = &self.modules[index];
+        let mut nodes = parse(&module.source)
+            .map_err(|error| self.module_error(index, error.into()))?
+            .content;
+        move_to_file(&mut nodes, self.file(index));
+
+        let mut qualifier = Qualifier {
+            module,
+            defined: Vec::new(),
+            locals: Vec::new(),
+        };
+        Ok(nodes
+            .into_iter()
+            .filter_map(|mut node| {
+                if qualifier.definition(&mut node) {
+                    Some(node)
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Wraps `error`, found while checking the module with `index`, in a [`LangError::Module`].
+    fn module_error(&self, index: usize, error: LangError) -> LangError {
+        match error {
+            LangError::Module { .. } => error,
+            error => {
+                let module = &self.modules[index];
+                LangError::Module {
+                    path: module.path.clone(),
+                    input: module.source.clone(),
+                    error: Box::new(error),
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Source<'a> for &'a Modules {
+    /// Checks every module imported by the program and returns the program with the definitions
+    /// of the modules it uses.
+    fn ast(self) -> LangResult<Located<Block<'a>>> {
+        let program = self.modules.len() - 1;
+        for index in 0..program {
+            self.module_ast(index)
+                .and_then(check)
+                .map_err(|error| self.module_error(index, error))?;
+        }
+        self.module_ast(program)
+    }
+}
+
+/// Returns the names defined by a top-level node, including the names of enum types.
+fn defines<'a>(node: &Located<Node<'a>>) -> Vec<&'a str> {
+    match &node.content {
+        Node::LetBind(annotation, _) | Node::LetMut(annotation, _) => {
+            vec![annotation.item.content.0]
+        }
+        Node::FnDef(name, _, _, _) => vec![name.content.0],
+        Node::EnumDef(name, variants) => Some(name)
+            .into_iter()
+            .chain(variants.iter().map(|variant| &variant.name))
+            .map(|name| name.content.0)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Qualifies the top-level definitions of a module and their uses.
+struct Qualifier<'a> {
+    module: &'a Module,
+    /// The top-level names defined so far.
+    defined: Vec<&'a str>,
+    /// The names bound inside the definition being qualified, which shadow the top-level ones.
+    locals: Vec<&'a str>,
+}
+
+impl<'a> Qualifier<'a> {
+    /// Qualifies `node` if it is a definition, returning `false` if it is not.
+    fn definition(&mut self, node: &mut Located<Node<'a>>) -> bool {
+        match &mut node.content {
+            Node::LetBind(annotation, body) | Node::LetMut(annotation, body) => {
+                self.node(body);
+                self.ty(&mut annotation.ty.content);
+                self.define(&mut annotation.item);
+            }
+            Node::FnDef(name, args, body, _) => {
+                // Functions can be recursive, so they are defined before their body.
+                self.define(name);
+                self.function(args, body);
+            }
+            Node::EnumDef(name, variants) => {
+                self.define(name);
+                for variant in variants.iter_mut() {
+                    self.define(&mut variant.name);
+                }
+                for field in variants.iter_mut().flat_map(|variant| &mut variant.fields) {
+                    self.ty(&mut field.content);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn define(&mut self, name: &mut Located<Name<'a>>) {
+        self.defined.push(name.content.0);
+        self.rename(&mut name.content);
+    }
+
+    /// Returns the qualified version of `name` if it refers to a top-level definition.
+    fn qualified(&self, name: &str) -> Option<&'a str> {
+        if !self.locals.contains(&name) && self.defined.contains(&name) {
+            Some(self.module.qualify(name))
+        } else {
+            None
+        }
+    }
+
+    fn rename(&self, name: &mut Name<'a>) {
+        if let Some(qualified) = self.qualified(name.0) {
+            name.0 = qualified;
+        }
+    }
+
+    fn node(&mut self, node: &mut Located<Node<'a>>) {
+        match &mut node.content {
+            Node::BinaryOp(_, node1, node2) => {
+                self.node(node1);
+                self.node(node2);
+            }
+            Node::UnaryOp(_, node) | Node::Ascription(node, _) | Node::Lazy(node) => {
+                self.node(node)
+            }
+            Node::Operator(_, node1, node2) => {
+                for node in node1.iter_mut().chain(node2) {
+                    self.node(node);
+                }
+            }
+            // Local definitions are only bound until the end of the block containing them.
+            Node::LetBind(annotation, body) | Node::LetMut(annotation, body) => {
+                self.node(body);
+                self.ty(&mut annotation.ty.content);
+                self.locals.push(annotation.item.content.0);
+            }
+            Node::LetIn(annotation, value, body) => {
+                self.node(value);
+                self.ty(&mut annotation.ty.content);
+                let len = self.locals.len();
+                self.locals.push(annotation.item.content.0);
+                self.node(body);
+                self.locals.truncate(len);
+            }
+            Node::Assign(name, body) => {
+                self.node(body);
+                self.rename(&mut name.content);
+            }
+            Node::FnDef(name, args, body, _) => {
+                self.locals.push(name.content.0);
+                self.function(args, body);
+            }
+            Node::EnumDef(name, variants) => {
+                self.locals.push(name.content.0);
+                self.locals
+                    .extend(variants.iter().map(|variant| variant.name.content.0));
+                for field in variants.iter_mut().flat_map(|variant| &mut variant.fields) {
+                    self.ty(&mut field.content);
+                }
+            }
+            Node::Cond(if_branch, branches, else_blk) => {
+                for Branch { cond, body } in Some(if_branch).into_iter().chain(branches) {
+                    self.block(cond);
+                    self.block(body);
+                }
+                self.block(else_blk);
+            }
+            Node::While(Branch { cond, body }) => {
+                self.block(cond);
+                self.block(body);
+            }
+            Node::Block(block) => self.block(block),
+            Node::For(name, start, end, body) => {
+                self.node(start);
+                self.node(end);
+                // The loop variable is only bound inside the body.
+                let len = self.locals.len();
+                self.locals.push(name.content.0);
+                self.block(body);
+                self.locals.truncate(len);
+            }
+            Node::AnonFn(args, body) => self.function(args, body),
+            Node::Call(func, args) => {
+                self.node(func);
+                for arg in args {
+                    self.node(arg);
+                }
+            }
+            Node::List(elems) | Node::Interpolation(elems) => {
+                for elem in elems {
+                    self.node(elem);
+                }
+            }
+            Node::Match(node, arms) => {
+                self.node(node);
+                for Arm { pattern, body } in arms {
+                    let len = self.locals.len();
+                    if let Pattern::Variant(name, binders) = &mut pattern.content {
+                        self.rename(&mut name.content);
+                        self.locals
+                            .extend(binders.iter().map(|binder| binder.content.0));
+                    }
+                    self.block(body);
+                    self.locals.truncate(len);
+                }
+            }
+            Node::Name(name) => self.rename(name),
+            Node::Literal(_) | Node::PrimFn(_) | Node::Import(_) | Node::Error => (),
+        }
+    }
+
+    fn block(&mut self, blk: &mut Located<Block<'a>>) {
+        let len = self.locals.len();
+        for node in &mut blk.content {
+            self.node(node);
+        }
+        self.locals.truncate(len);
+    }
+
+    fn function(
+        &mut self,
+        args: &mut [TyAnnotation<Name<'a>>],
+        body: &mut TyAnnotation<Block<'a>>,
+    ) {
+        let len = self.locals.len();
+        for arg in args.iter_mut() {
+            self.ty(&mut arg.ty.content);
+            self.locals.push(arg.item.content.0);
+        }
+        self.ty(&mut body.ty.content);
+        self.block(&mut body.item);
+        self.locals.truncate(len);
+    }
+
+    fn ty(&self, ty: &mut Ty) {
+        match ty {
+            Ty::List(ty) | Ty::Chan(ty) | Ty::Lazy(ty) => self.ty(ty),
+            Ty::Arrow(ty1, ty2) => {
+                self.ty(ty1);
+                self.ty(ty2);
+            }
+            Ty::Adt(name, args) => {
+                if let Some(qualified) = self.qualified(name) {
+                    *name = qualified.to_owned();
+                }
+                for arg in args {
+                    self.ty(arg);
+                }
+            }
+            _ => (),
+        }
+    }
+}
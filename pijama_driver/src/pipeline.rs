@@ -0,0 +1,250 @@
+//! A configurable compilation pipeline.
+//!
+//! [`compile`] parses, lowers and type-checks a program like the rest of the driver, and then runs
+//! the optimization and transformation passes chosen in [`Options`] before lowering the program
+//! to the LIR. The returned [`CompiledProgram`] has the result of each step, so embedders can
+//! evaluate it with their own machine or inspect the program between passes. To run the steps
+//! one at a time, see [`Compilation`](crate::Compilation).
+use pijama_ast::{IntWidth, Located};
+use pijama_core::{
+    bytecode::{self, Program},
+    lint::lint,
+    lir::{Pass as LirPass, Term as LirTerm},
+    machine::{native::NativeFn, Value},
+    mir::{optimize_with, Pass as MirPass, Term as MirTerm},
+    ty::{ty_check_with_width, Ty},
+};
+
+use crate::{
+    prelude::with_prelude,
+    timings::{Phase, Timings},
+    with_bindings, with_natives, LangResult, Source, Warning,
+};
+
+/// The number of applications unfolded by the specialization pass run at optimization level `2`
+/// and above, see [`MirPass::Specialization`].
+pub const SPECIALIZATION_UNFOLDINGS: usize = 32;
+
+/// A pass that can be run by the pipeline.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PassId {
+    /// A pass over the type-checked MIR.
+    Mir(MirPass),
+    /// A pass over the LIR, run before its abstractions are converted to closures.
+    Lir(LirPass),
+}
+
+impl PassId {
+    /// Returns the passes run at optimization level `opt_level`, in order.
+    ///
+    /// Level `0` runs no passes, level `1` runs every pass of [`MirPass::ALL`] and level `2` and
+    /// above also specialize functions before them.
+    pub fn for_opt_level(opt_level: u8) -> Vec<PassId> {
+        let mut passes = Vec::new();
+        if opt_level >= 2 {
+            passes.push(MirPass::Specialization {
+                unfoldings: SPECIALIZATION_UNFOLDINGS,
+            });
+        }
+        if opt_level >= 1 {
+            passes.extend_from_slice(MirPass::ALL);
+        }
+        passes.into_iter().map(PassId::Mir).collect()
+    }
+}
+
+/// The options of the pipeline.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Optimization level, which chooses the passes run before `passes`, see
+    /// [`PassId::for_opt_level`].
+    pub opt_level: u8,
+    /// The passes run after the ones of `opt_level`.
+    ///
+    /// The passes over the MIR are always run before the ones over the LIR, and each group runs in
+    /// the order given here.
+    pub passes: Vec<PassId>,
+    /// Whether the definitions of the [`prelude`](crate::prelude) are available to the program.
+    pub prelude: bool,
+    /// Whether the program is kept after each pass, see [`CompiledProgram::stages`].
+    pub keep_stages: bool,
+    /// How the program is evaluated by [`run_with_options`](crate::run_with_options).
+    pub backend: Backend,
+    /// The width of the integers of the machine evaluating the program. Integer literals that do
+    /// not fit in it are reported when type-checking. [`run_with_options`](crate::run_with_options)
+    /// ignores it and uses the width of its machine instead.
+    pub int_width: IntWidth,
+}
+
+/// How a compiled program is evaluated.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Backend {
+    /// The LIR is evaluated by the machine, step by step.
+    #[default]
+    Machine,
+    /// The LIR is compiled to bytecode, see [`pijama_core::bytecode`], which runs faster but
+    /// ignores the fuel, the statistics and the hook of the machine. Programs using threads are
+    /// evaluated by the machine. The runtime errors of compiled programs have no stack trace,
+    /// see [`stack_trace`](crate::stack_trace#which-runs-have-traces).
+    Bytecode,
+    /// The LIR is compiled to native code, see [`pijama_core::jit`], falling back to the machine
+    /// for the programs it does not support. Compiled programs ignore the fuel, the statistics and
+    /// the hook of the machine, and their runtime errors have no stack trace, see
+    /// [`stack_trace`](crate::stack_trace#which-runs-have-traces).
+    #[cfg(feature = "jit")]
+    Jit,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            opt_level: 0,
+            passes: Vec::new(),
+            prelude: true,
+            keep_stages: false,
+            backend: Backend::default(),
+            int_width: IntWidth::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Returns every pass to run, in order.
+    pub fn all_passes(&self) -> Vec<PassId> {
+        let mut passes = PassId::for_opt_level(self.opt_level);
+        passes.extend_from_slice(&self.passes);
+        passes
+    }
+
+    /// Returns every pass over the MIR and every pass over the LIR, in the order they run.
+    pub(crate) fn split_passes(&self) -> (Vec<MirPass>, Vec<LirPass>) {
+        let (mut mir_passes, mut lir_passes) = (Vec::new(), Vec::new());
+        for pass in self.all_passes() {
+            match pass {
+                PassId::Mir(pass) => mir_passes.push(pass),
+                PassId::Lir(pass) => lir_passes.push(pass),
+            }
+        }
+        (mir_passes, lir_passes)
+    }
+}
+
+/// The program after a pass of the pipeline.
+#[derive(Debug, Clone)]
+pub enum Stage<'a> {
+    /// The MIR after a pass.
+    Mir(Located<MirTerm<'a>>),
+    /// The LIR after a pass, with its abstractions converted to closures.
+    Lir(LirTerm),
+}
+
+/// The result of compiling a program with [`compile`].
+#[derive(Debug, Clone)]
+pub struct CompiledProgram<'a> {
+    /// The type of the program.
+    pub ty: Ty,
+    /// The warnings found in the program, before any pass is run.
+    pub warnings: Vec<Warning>,
+    /// The MIR after every pass over it was run.
+    pub mir: Located<MirTerm<'a>>,
+    /// The LIR that can be evaluated.
+    pub lir: LirTerm,
+    /// The program after each pass, in the order they were run. It is empty unless
+    /// [`Options::keep_stages`] is set.
+    pub stages: Vec<(PassId, Stage<'a>)>,
+    /// The LIR compiled to bytecode. It is `None` unless [`Options::backend`] is
+    /// [`Backend::Bytecode`] and the program does not use threads.
+    pub bytecode: Option<Program>,
+}
+
+/// Compiles `input` running the passes chosen in `options`.
+pub fn compile<'a>(input: impl Source<'a>, options: &Options) -> LangResult<CompiledProgram<'a>> {
+    compile_timed(input, options, &[], &[], &mut Timings::default())
+}
+
+/// Compiles `input` like [`compile`], with the variables in `bindings` defined at the top level.
+///
+/// See [`run_with_bindings`](crate::run_with_bindings) for details on how the bindings are
+/// defined.
+pub fn compile_with_bindings<'a>(
+    input: impl Source<'a>,
+    options: &Options,
+    bindings: &[(&'a str, Value)],
+) -> LangResult<CompiledProgram<'a>> {
+    compile_timed(input, options, &[], bindings, &mut Timings::default())
+}
+
+/// Compiles `input` like [`compile`], with a binding for each function in `natives` and each
+/// value in `bindings`, recording the duration of each phase in `timings`.
+///
+/// The optimization phase is only recorded if there are passes over the MIR.
+pub(crate) fn compile_timed<'a: 'n, 'n>(
+    input: impl Source<'a>,
+    options: &Options,
+    natives: &'n [NativeFn],
+    bindings: &[(&'n str, Value)],
+    timings: &mut Timings,
+) -> LangResult<CompiledProgram<'n>> {
+    let ast = timings.time(Phase::Parsing, || {
+        input.ast().map(|ast| {
+            if options.prelude {
+                with_prelude(ast)
+            } else {
+                ast
+            }
+        })
+    })?;
+    let names: Vec<&str> = natives
+        .iter()
+        .map(NativeFn::name)
+        .chain(bindings.iter().map(|(name, _)| *name))
+        .collect();
+    let mir = timings.time(Phase::Lowering, || MirTerm::from_ast_in(ast, &names))?;
+    let mut mir = with_natives(with_bindings(mir, bindings), natives);
+    let ty = timings
+        .time(Phase::TypeChecking, || {
+            ty_check_with_width(&mir, options.int_width)
+        })?
+        .content;
+    let warnings = lint(&mir);
+
+    let (mir_passes, lir_passes) = options.split_passes();
+
+    let mut stages = Vec::new();
+    if !mir_passes.is_empty() {
+        mir = timings.time(Phase::Optimization, || {
+            mir_passes.iter().fold(mir, |mir, pass| {
+                let mir = optimize_with(mir, &[*pass]);
+                if options.keep_stages {
+                    stages.push((PassId::Mir(*pass), Stage::Mir(mir.clone())));
+                }
+                mir
+            })
+        });
+    }
+    if options.keep_stages {
+        // The LIR passes run over nameless terms, so each stage lowers the MIR again.
+        for len in 1..=lir_passes.len() {
+            let lir = LirTerm::from_mir_with(mir.clone(), &lir_passes[..len]);
+            stages.push((PassId::Lir(lir_passes[len - 1]), Stage::Lir(lir)));
+        }
+    }
+    let lir = timings.time(Phase::LirLowering, || {
+        LirTerm::from_mir_with(mir.clone(), &lir_passes)
+    });
+    let bytecode = match options.backend {
+        Backend::Bytecode if bytecode::supports(&lir) => {
+            Some(timings.time(Phase::Bytecode, || bytecode::compile(&lir)))
+        }
+        _ => None,
+    };
+
+    Ok(CompiledProgram {
+        ty,
+        warnings,
+        mir,
+        lir,
+        stages,
+        bytecode,
+    })
+}
@@ -0,0 +1,28 @@
+//! The prelude, which is implicitly available to every program.
+//!
+//! The prelude is the Pijama program in [`PRELUDE`], which declares the `Option` and `Result`
+//! enums and functions to work with them like `unwrap_or`, `map` and `is_some`. Its definitions
+//! are added before the first line of a program, so the program can shadow any of them.
+//!
+//! Only the definitions used by the program, directly or through other definitions of the
+//! prelude, are added. This way, programs that do not use the prelude are compiled as if it did
+//! not exist. The locations of the added definitions refer to [`PRELUDE_FILE`].
+use pijama_ast::{Block, FileId, Located};
+use pijama_core::parser::parse;
+
+use crate::definitions::{move_to_file, prepend_used};
+
+/// The source code of the prelude.
+pub const PRELUDE: &str = include_str!("prelude.pj");
+
+/// The file of the prelude in every [`SourceMap`](crate::SourceMap).
+pub const PRELUDE_FILE: FileId = FileId(1);
+
+/// Adds the definitions of the prelude used by `program` before its first line.
+pub(crate) fn with_prelude(program: Located<Block<'_>>) -> Located<Block<'_>> {
+    let mut prelude = parse(PRELUDE)
+        .expect("The prelude is a valid program")
+        .content;
+    move_to_file(&mut prelude, PRELUDE_FILE);
+    prepend_used(program, prelude)
+}
@@ -0,0 +1,214 @@
+//! Compiled programs that the host can run and call into.
+use pijama_ast::{Located, Location, Name};
+use pijama_core::{
+    lir::Term as LirTerm,
+    machine::{
+        arithmetic::Arithmetic,
+        env::Output,
+        value::{FromPijama, ToPijama},
+        Machine, MachineBuilder, RuntimeError, Value,
+    },
+    mir::Term as MirTerm,
+    ty::{ty_check, Ty, TyError},
+};
+
+use crate::{
+    pipeline::{compile, CompiledProgram, Options},
+    LangResult,
+};
+
+/// A program that has been parsed, lowered and type-checked.
+///
+/// Besides running the program as a whole, the host can call any function defined at the top
+/// level of the program with [`Program::call`].
+///
+/// A program is immutable once compiled and every run uses its own machine, so programs are
+/// `Send + Sync` and the same program can be run concurrently from many threads.
+#[derive(Debug, Clone)]
+pub struct Program<'a> {
+    mir: Located<MirTerm<'a>>,
+    lir: LirTerm,
+    ty: Ty,
+}
+
+// Hosts share programs between threads, this fails to compile if that stops being possible.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Program<'static>>();
+};
+
+impl<'a> Program<'a> {
+    /// Compiles `input` into a program.
+    ///
+    /// The definitions of the [`prelude`](crate::prelude) are available to the program.
+    pub fn compile(input: &'a str) -> LangResult<Self> {
+        Self::compile_with(input, true)
+    }
+
+    /// Compiles `input` into a program without the definitions of the
+    /// [`prelude`](crate::prelude), so the program can only use the names it defines.
+    pub fn compile_without_prelude(input: &'a str) -> LangResult<Self> {
+        Self::compile_with(input, false)
+    }
+
+    fn compile_with(input: &'a str, prelude: bool) -> LangResult<Self> {
+        let options = Options {
+            prelude,
+            ..Options::default()
+        };
+        let CompiledProgram { mir, lir, ty, .. } = compile(input, &options)?;
+        Ok(Program { mir, lir, ty })
+    }
+
+    /// Returns the type of the program.
+    pub fn ty(&self) -> &Ty {
+        &self.ty
+    }
+
+    /// Runs the program and returns its value.
+    ///
+    /// Returns `None` if the program evaluates to a function.
+    pub fn run(&self) -> Result<Option<Value>, RuntimeError> {
+        self.run_with_machine(MachineBuilder::default().build())
+    }
+
+    /// Runs the program and returns its value as a `T`.
+    ///
+    /// Returns a type error, without running the program, if the type of the program is not the
+    /// one of `T`.
+    pub fn run_as<T: FromPijama>(&self) -> LangResult<T> {
+        check_ty::<T>(&self.ty, self.mir.loc)?;
+        let value = self.run()?;
+        Ok(value.and_then(T::from_value).unwrap())
+    }
+
+    /// Runs the program using `machine` and returns its value.
+    ///
+    /// Returns `None` if the program evaluates to a function.
+    pub fn run_with_machine<W: Output, A: Arithmetic>(
+        &self,
+        mut machine: Machine<W, A>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let res = machine.evaluate(self.lir.clone())?;
+        Ok(Value::from_term(&res, &self.ty))
+    }
+
+    /// Calls the top-level function `name` with `args` and returns its result.
+    ///
+    /// The call replaces the last expression of the program, so every top-level definition is in
+    /// scope. The call is type-checked before being evaluated, so calling a function that does not
+    /// exist or with arguments of the wrong type returns a type error.
+    ///
+    /// Returns `None` if the call evaluates to a function, e.g. when not all the arguments were
+    /// passed.
+    pub fn call(&self, name: &str, args: &[Value]) -> LangResult<Option<Value>> {
+        let (mir, ty) = self.check_call(name, args)?;
+        Ok(evaluate(mir, &ty)?)
+    }
+
+    /// Calls the top-level function `name` with the arguments in the tuple `args` and returns its
+    /// result as a `R`.
+    ///
+    /// This is the typed version of [`Program::call`]. Besides the type errors returned by
+    /// [`Program::call`], it returns a type error if the call does not have the type of `R`.
+    pub fn call_as<R: FromPijama>(&self, name: &str, args: impl Arguments) -> LangResult<R> {
+        let (mir, ty) = self.check_call(name, &args.values())?;
+        check_ty::<R>(&ty, mir.loc)?;
+        let value = evaluate(mir, &ty)?;
+        Ok(value.and_then(R::from_value).unwrap())
+    }
+
+    /// Replaces the last expression of the program with a call to `name` with `args` and
+    /// type-checks the result.
+    fn check_call<'b>(
+        &self,
+        name: &'b str,
+        args: &[Value],
+    ) -> LangResult<(Located<MirTerm<'b>>, Ty)>
+    where
+        'a: 'b,
+    {
+        let loc = Location::new(0, 0);
+        let mut call = loc.with_content(MirTerm::Var(Name(name)));
+        for arg in args {
+            let arg = arg.to_mir(loc);
+            call = loc.with_content(MirTerm::App(Box::new(call), Box::new(arg)));
+        }
+        let mir = replace_tail(self.mir.clone(), call);
+        let ty = ty_check(&mir)?.content;
+        Ok((mir, ty))
+    }
+}
+
+/// Tuples of arguments for [`Program::call_as`].
+///
+/// This is implemented for `()` and for tuples of up to three values implementing [`ToPijama`].
+pub trait Arguments {
+    /// Converts the arguments into values.
+    fn values(self) -> Vec<Value>;
+}
+
+impl Arguments for () {
+    fn values(self) -> Vec<Value> {
+        Vec::new()
+    }
+}
+
+macro_rules! impl_arguments {
+    ($($arg:ident),+) => {
+        impl<$($arg: ToPijama),+> Arguments for ($($arg,)+) {
+            #[allow(non_snake_case)]
+            fn values(self) -> Vec<Value> {
+                let ($($arg,)+) = self;
+                vec![$($arg.to_value()),+]
+            }
+        }
+    };
+}
+
+impl_arguments!(A);
+impl_arguments!(A, B);
+impl_arguments!(A, B, C);
+
+/// Returns a type error located at `loc` if `ty` is not the type of `T`.
+fn check_ty<T: FromPijama>(ty: &Ty, loc: Location) -> Result<(), TyError> {
+    let expected = T::ty();
+    if *ty == expected {
+        Ok(())
+    } else {
+        Err(TyError::Mismatch {
+            expected: Box::new(loc.with_content(expected)),
+            found: Box::new(loc.with_content(ty.clone())),
+        })
+    }
+}
+
+/// Replaces the last expression of `term` with `call`, keeping all the bindings and the
+/// expressions sequenced before it.
+fn replace_tail<'a>(
+    term: Located<MirTerm<'a>>,
+    call: Located<MirTerm<'a>>,
+) -> Located<MirTerm<'a>> {
+    let loc = term.loc;
+    match term.content {
+        MirTerm::Let(kind, name, t1, t2) => {
+            let t2 = Box::new(replace_tail(*t2, call));
+            loc.with_content(MirTerm::Let(kind, name, t1, t2))
+        }
+        MirTerm::LetRec(fns, t1) => {
+            let t1 = Box::new(replace_tail(*t1, call));
+            loc.with_content(MirTerm::LetRec(fns, t1))
+        }
+        MirTerm::Seq(t1, t2) => {
+            let t2 = Box::new(replace_tail(*t2, call));
+            loc.with_content(MirTerm::Seq(t1, t2))
+        }
+        _ => call,
+    }
+}
+
+fn evaluate(mir: Located<MirTerm>, ty: &Ty) -> Result<Option<Value>, RuntimeError> {
+    let lir = LirTerm::from_mir(mir);
+    let res = MachineBuilder::default().build().evaluate(lir)?;
+    Ok(Value::from_term(&res, ty))
+}
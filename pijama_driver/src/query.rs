@@ -0,0 +1,86 @@
+//! Queries about the terms of a program, for editor integration.
+//!
+//! Queries run the same pipeline as [`check`](crate::check) and answer questions about a position
+//! of the input, so editors do not have to lower and type-check programs themselves. They return
+//! `None` if the program does not type-check.
+use pijama_ast::{FileId, Located, Location, NodeId};
+use pijama_core::{
+    mir::{visit::Visitor, Term as MirTerm},
+    ty::{ty_check_terms, Ty},
+};
+
+use crate::lower;
+
+/// Returns the type and location of the innermost term of `input` covering the byte at `offset`.
+///
+/// The innermost term is the one with the shortest location. Terms added by the lowering share
+/// the location of the code they come from, so the deepest of them is used if several terms have
+/// the same location. Returns `None` if the program does not type-check or no term covers
+/// `offset`.
+pub fn type_at(input: &str, offset: usize) -> Option<(Ty, Location)> {
+    let mir = lower(input).ok()?;
+    let table = ty_check_terms(&mir).ok()?;
+    let mut innermost = Innermost {
+        offset,
+        nodes_only: false,
+        found: None,
+    };
+    innermost.visit_term(&mir);
+    let term = innermost.found?;
+    let ty = table.get(&term.content)?.clone();
+    Some((ty, term.loc))
+}
+
+/// Returns the id and location of the innermost node of `input` covering the byte at `offset`.
+///
+/// Unlike [`type_at`], terms added by the lowering are skipped, so the id always refers to a node
+/// written in `input`. The id stays the same as long as the code before the node does not
+/// change, so editors can keep it between edits and pass it to [`type_of_node`]. Returns `None`
+/// if the program does not type-check or no node covers `offset`.
+pub fn node_at(input: &str, offset: usize) -> Option<(NodeId, Location)> {
+    let mir = lower(input).ok()?;
+    ty_check_terms(&mir).ok()?;
+    let mut innermost = Innermost {
+        offset,
+        nodes_only: true,
+        found: None,
+    };
+    innermost.visit_term(&mir);
+    let term = innermost.found?;
+    Some((term.id, term.loc))
+}
+
+/// Returns the type of the node of `input` with the id `id`.
+///
+/// Returns `None` if the program does not type-check or no term was lowered from the node.
+pub fn type_of_node(input: &str, id: NodeId) -> Option<Ty> {
+    let mir = lower(input).ok()?;
+    let table = ty_check_terms(&mir).ok()?;
+    table.get_node(id).cloned()
+}
+
+/// Visitor finding the innermost term covering `offset`.
+struct Innermost<'t, 'a> {
+    offset: usize,
+    /// Whether to skip the terms that were not lowered from a node.
+    nodes_only: bool,
+    found: Option<&'t Located<MirTerm<'a>>>,
+}
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for Innermost<'t, 'a> {
+    fn visit_term(&mut self, term: &'t Located<MirTerm<'a>>) {
+        let loc = term.loc;
+        let skipped = self.nodes_only && term.id.is_dummy();
+        if !skipped && loc.file == FileId::MAIN && loc.start <= self.offset && self.offset < loc.end
+        {
+            let len = loc.end - loc.start;
+            if self
+                .found
+                .is_none_or(|found| len <= found.loc.end - found.loc.start)
+            {
+                self.found = Some(term);
+            }
+        }
+        self.super_term(term);
+    }
+}
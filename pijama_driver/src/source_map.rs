@@ -0,0 +1,71 @@
+//! The source files of a program.
+//!
+//! Locations refer to a file by its [`FileId`]. The program being compiled is always
+//! [`FileId::MAIN`] and the [prelude](crate::prelude) is always
+//! [`PRELUDE_FILE`](crate::prelude::PRELUDE_FILE). Imported modules get the ids after them, see
+//! [`Modules::source_map`](crate::Modules::source_map).
+use pijama_ast::{FileId, Location};
+
+use crate::prelude::{PRELUDE, PRELUDE_FILE};
+
+/// The files of a program, each one with a stable id.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    /// The name and source code of each file, indexed by their ids.
+    files: Vec<(String, String)>,
+}
+
+impl SourceMap {
+    /// Creates a source map for the program `source`, displayed as `name`.
+    ///
+    /// The source map also has the prelude.
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        let mut map = SourceMap { files: Vec::new() };
+        map.add(name, source);
+        let prelude = map.add("<prelude>", PRELUDE);
+        debug_assert_eq!(PRELUDE_FILE, prelude);
+        map
+    }
+
+    /// Adds a file and returns its id.
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len() as u32);
+        self.files.push((name.into(), source.into()));
+        id
+    }
+
+    /// Returns the name of `file`, or `None` if the file is not in the source map.
+    pub fn name(&self, file: FileId) -> Option<&str> {
+        self.files
+            .get(file.0 as usize)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the source code of `file`, or `None` if the file is not in the source map.
+    pub fn source(&self, file: FileId) -> Option<&str> {
+        self.files
+            .get(file.0 as usize)
+            .map(|(_, source)| source.as_str())
+    }
+
+    /// Returns the code at `loc`, or `None` if its file is not in the source map.
+    pub fn snippet(&self, loc: Location) -> Option<&str> {
+        self.source(loc.file)?.get(loc.start..loc.end)
+    }
+
+    /// Returns the id of the file named `name`.
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|(file, _)| file == name)
+            .map(|index| FileId(index as u32))
+    }
+
+    /// Returns the id, name and source code of every file, in the order they were added.
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &str, &str)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, (name, source))| (FileId(index as u32), name.as_str(), source.as_str()))
+    }
+}
@@ -0,0 +1,112 @@
+//! Stack traces of runtime errors.
+//!
+//! When a runtime error stops the evaluation, the machine keeps the locations of the calls that
+//! were being evaluated, see [`Machine::stack_trace`](pijama_core::machine::Machine::stack_trace).
+//! These locations are looked up in the applications of the MIR of the program to name each call
+//! after the function it calls. The programs run by [`run_with_options`](crate::run_with_options)
+//! return their runtime errors with these calls as a [`LangError::Traced`](crate::LangError::Traced).
+//!
+//! # Which runs have traces
+//!
+//! Only the machine records the calls it evaluates, so whether an error is traced depends on how
+//! the program was compiled and evaluated:
+//!
+//! - With [`Backend::Machine`](crate::pipeline::Backend::Machine), every call that was being
+//!   evaluated is in the trace.
+//! - With [`Backend::Bytecode`](crate::pipeline::Backend::Bytecode) and the `Jit` backend, the
+//!   errors of compiled programs are never traced. The programs these backends fall back to the
+//!   machine for are traced like with the machine.
+//! - The inlining and specialization passes of the MIR replace some calls by the body of the
+//!   called function, so these calls are missing from the trace. The remaining calls keep their
+//!   names.
+//! - The [CPS](pijama_core::lir::Pass::Cps) pass puts every call in tail position, so no call is
+//!   being evaluated when an error is found and the error is never traced.
+use pijama_ast::{Located, Location};
+use pijama_core::mir::{visit::Visitor, Term as MirTerm};
+
+/// A call that was being evaluated when a runtime error stopped the evaluation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Call {
+    /// The name of the called function, or `None` if the function is not called by its name,
+    /// like an anonymous function or a function returned by another call.
+    pub name: Option<String>,
+    /// The location of the call.
+    pub loc: Location,
+}
+
+/// The functions called by the applications of a program, used to name the calls of its stack
+/// traces.
+///
+/// The table is built before evaluating the program, since the MIR can borrow the native
+/// functions of the machine evaluating it.
+pub(crate) struct Callees {
+    /// The location of each application with the function it calls.
+    callees: Vec<(Location, Callee)>,
+}
+
+/// The function called by an application.
+enum Callee {
+    Named(String),
+    Anonymous,
+    Primitive,
+}
+
+impl Callees {
+    /// Returns the table of the applications of `mir`.
+    pub(crate) fn new(mir: &Located<MirTerm<'_>>) -> Self {
+        let mut callees = Callees {
+            callees: Vec::new(),
+        };
+        callees.visit_term(mir);
+        callees
+    }
+
+    /// Returns the calls at the locations of `trace`, innermost first.
+    ///
+    /// Calls to primitives are left out, since they cannot call other functions and runtime
+    /// errors are already located at the primitive that failed.
+    pub(crate) fn calls(&self, trace: &[Location]) -> Vec<Call> {
+        trace
+            .iter()
+            .filter_map(|loc| {
+                let name = match self.callees.iter().find(|(found, _)| found == loc) {
+                    Some((_, Callee::Primitive)) => return None,
+                    Some((_, Callee::Named(name))) => Some(name.clone()),
+                    Some((_, Callee::Anonymous)) | None => None,
+                };
+                Some(Call { name, loc: *loc })
+            })
+            .collect()
+    }
+}
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for Callees {
+    fn visit_term(&mut self, term: &'t Located<MirTerm<'a>>) {
+        let loc = term.loc;
+        if let MirTerm::App(..) = &term.content {
+            // The applications to several arguments share their location, so only the outermost
+            // one is kept.
+            let mut head = term;
+            while let MirTerm::App(t1, t2) = &head.content {
+                if head.loc != loc {
+                    break;
+                }
+                self.visit_term(t2);
+                head = t1;
+            }
+            let mut callee = &head.content;
+            while let MirTerm::Ascription(t1, _) = callee {
+                callee = &t1.content;
+            }
+            let callee = match callee {
+                MirTerm::PrimFn(_) | MirTerm::Native(..) => Callee::Primitive,
+                MirTerm::Var(name) => Callee::Named(name.0.to_owned()),
+                _ => Callee::Anonymous,
+            };
+            self.callees.push((loc, callee));
+            self.visit_term(head);
+        } else {
+            self.super_term(term);
+        }
+    }
+}
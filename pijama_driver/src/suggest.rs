@@ -0,0 +1,221 @@
+//! Machine-applicable suggestions to fix errors.
+//!
+//! A [`Suggestion`] is a replacement for a range of the input that fixes an error. Suggestions
+//! are only made for some errors and they are always verified before being returned: applying a
+//! suggestion is guaranteed to fix the error it was made for.
+//!
+//! Suggestions are made for:
+//! - Parsing errors that are fixed by adding a missing `end` at the end of the input.
+//! - Recursive functions whose return type cannot be inferred, when exactly one basic type makes
+//!   the program well-typed.
+//! - Names that are not bounded but are similar to a name defined in the program.
+use serde::Serialize;
+
+use pijama_ast::{Located, Location, Name, Pattern};
+use pijama_core::{mir::Term as MirTerm, parser::parse, ty::TyError};
+
+use crate::{check, LangError};
+
+/// A change to the input that fixes an error.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Suggestion {
+    /// A short description of the change, like ``replace with `fact` ``.
+    pub message: String,
+    /// The range of the input to be replaced. It is empty if the change is an insertion.
+    pub loc: Location,
+    /// The text that replaces the range.
+    pub replacement: String,
+}
+
+impl Suggestion {
+    /// Returns `input` with the suggestion applied.
+    pub fn apply(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len() + self.replacement.len());
+        output.push_str(&input[..self.loc.start]);
+        output.push_str(&self.replacement);
+        output.push_str(&input[self.loc.end..]);
+        output
+    }
+}
+
+/// Returns a suggestion to fix `error`, which must be an error found while checking `input`.
+///
+/// Returns `None` if there is no suggestion for this kind of error or if none of the candidates
+/// fixes it.
+pub fn suggest(input: &str, error: &LangError) -> Option<Suggestion> {
+    match error {
+        LangError::Parse(_) => suggest_end(input),
+        LangError::Ty(TyError::AmbiguousReturn(name)) => suggest_return_ty(input, name.loc),
+        LangError::Ty(TyError::Unbounded(name)) => suggest_name(input, name),
+        _ => None,
+    }
+}
+
+/// Suggests adding an `end` at the end of the input.
+fn suggest_end(input: &str) -> Option<Suggestion> {
+    let loc = Location::new(input.len(), input.len());
+    let suggestion = Suggestion {
+        message: "insert `end`".to_owned(),
+        loc,
+        replacement: "\nend".to_owned(),
+    };
+    parse(&suggestion.apply(input)).ok().map(|_| suggestion)
+}
+
+/// Suggests a return type annotation for the recursive function named at `loc`.
+fn suggest_return_ty(input: &str, loc: Location) -> Option<Suggestion> {
+    let end = params_end(input, loc.start)?;
+    let loc = Location::new(end, end);
+    let mut candidates = ["Int", "Bool", "Unit"].iter().filter_map(|ty| {
+        let suggestion = Suggestion {
+            message: format!("insert `: {}`", ty),
+            loc,
+            replacement: format!(": {}", ty),
+        };
+        check(&suggestion.apply(input)).ok().map(|_| suggestion)
+    });
+    match (candidates.next(), candidates.next()) {
+        (Some(suggestion), None) => Some(suggestion),
+        _ => None,
+    }
+}
+
+/// Returns the position right after the parameters of the function whose name starts at `start`.
+fn params_end(input: &str, start: usize) -> Option<usize> {
+    let open = start + input[start..].find('(')?;
+    let mut depth = 0usize;
+    for (offset, c) in input[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 => return Some(open + offset + 1),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Suggests replacing `name` by the most similar name defined in the program.
+fn suggest_name(input: &str, name: &Located<String>) -> Option<Suggestion> {
+    let ast = parse(input).ok()?;
+    let mir = MirTerm::from_ast(ast).ok()?;
+    let mut names = Vec::new();
+    binders(&mir, &mut names);
+
+    // Allow one edit for every three characters, so very short names get no suggestions.
+    let max_distance = name.content.chars().count() / 3;
+    let mut candidates: Vec<_> = names
+        .into_iter()
+        .map(|candidate| (distance(&name.content, candidate.0), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort_by_key(|(distance, candidate)| (*distance, candidate.0));
+    candidates.dedup_by_key(|(_, candidate)| *candidate);
+
+    let loc = Location::new(name.loc.start, name.loc.start + name.content.len());
+    candidates.into_iter().find_map(|(_, candidate)| {
+        let suggestion = Suggestion {
+            message: format!("replace with `{}`", candidate),
+            loc,
+            replacement: candidate.0.to_owned(),
+        };
+        let fixed = match check(&suggestion.apply(input)) {
+            Err(error) => !error.errors().iter().any(|error| {
+                matches!(error, LangError::Ty(TyError::Unbounded(other)) if other.loc.start == loc.start)
+            }),
+            Ok(_) => true,
+        };
+        if fixed {
+            Some(suggestion)
+        } else {
+            None
+        }
+    })
+}
+
+/// Pushes the names bound anywhere in `term` to `names`.
+fn binders<'a>(term: &Located<MirTerm<'a>>, names: &mut Vec<Name<'a>>) {
+    match &term.content {
+        MirTerm::Var(_)
+        | MirTerm::Lit(_)
+        | MirTerm::PrimFn(_)
+        | MirTerm::Native(..)
+        | MirTerm::Constructor(_) => (),
+        MirTerm::Abs(name, _, body) => {
+            names.push(name.content);
+            binders(body, names);
+        }
+        MirTerm::UnaryOp(_, t)
+        | MirTerm::Assign(_, t)
+        | MirTerm::Ascription(t, _)
+        | MirTerm::Lazy(t) => binders(t, names),
+        MirTerm::BinaryOp(_, t1, t2) | MirTerm::App(t1, t2) | MirTerm::Seq(t1, t2) => {
+            binders(t1, names);
+            binders(t2, names);
+        }
+        MirTerm::Let(_, name, t1, t2) => {
+            names.push(name.content);
+            binders(t1, names);
+            binders(t2, names);
+        }
+        MirTerm::LetRec(fns, t) => {
+            for (name, _, body) in fns {
+                names.push(name.content);
+                binders(body, names);
+            }
+            binders(t, names);
+        }
+        MirTerm::Cond(t1, t2, t3) => {
+            binders(t1, names);
+            binders(t2, names);
+            binders(t3, names);
+        }
+        MirTerm::List(elems) => {
+            for elem in elems {
+                binders(elem, names);
+            }
+        }
+        MirTerm::Enum(name, variants, t) => {
+            names.push(name.content);
+            names.extend(variants.iter().map(|variant| variant.name.content));
+            binders(t, names);
+        }
+        MirTerm::Match(t, arms) => {
+            binders(t, names);
+            for (pattern, body) in arms {
+                if let Pattern::Variant(_, fields) = &pattern.content {
+                    names.extend(fields.iter().map(|field| field.content));
+                }
+                binders(body, names);
+            }
+        }
+    }
+}
+
+/// Returns the edit distance between two strings, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // `d[i][j]` is the distance between the first `i` characters of `a` and the first `j`
+    // characters of `b`.
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,87 @@
+//! Instrumentation for the compilation pipeline.
+//!
+//! The [`Timings`] type records how long each [`Phase`] of the pipeline took. It is filled by
+//! [`run_with_timings`](crate::run_with_timings) and can be displayed as a small report.
+use std::{
+    fmt::{Display, Formatter, Result},
+    time::{Duration, Instant},
+};
+
+/// A phase of the compilation pipeline.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Phase {
+    /// Parsing the source code into an AST.
+    Parsing,
+    /// Lowering the AST into the MIR.
+    Lowering,
+    /// Type-checking the MIR.
+    TypeChecking,
+    /// Running the optimization passes over the MIR.
+    Optimization,
+    /// Lowering the MIR into the LIR.
+    LirLowering,
+    /// Compiling the LIR to bytecode, only for the bytecode backend.
+    Bytecode,
+    /// Evaluating the LIR.
+    Evaluation,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Phase::Parsing => write!(f, "parsing"),
+            Phase::Lowering => write!(f, "lowering"),
+            Phase::TypeChecking => write!(f, "type checking"),
+            Phase::Optimization => write!(f, "optimization"),
+            Phase::LirLowering => write!(f, "LIR lowering"),
+            Phase::Bytecode => write!(f, "bytecode compilation"),
+            Phase::Evaluation => write!(f, "evaluation"),
+        }
+    }
+}
+
+/// Time taken by each phase of the pipeline.
+///
+/// Phases are stored in the order they were run. If the pipeline stops early because of an error,
+/// only the phases that actually ran are recorded.
+#[derive(Debug, Default, Clone)]
+pub struct Timings {
+    phases: Vec<(Phase, Duration)>,
+}
+
+impl Timings {
+    /// Runs `f` and records the time it took as the duration of `phase`.
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.phases.push((phase, start.elapsed()));
+        result
+    }
+
+    /// Returns the duration of `phase` if it was run.
+    pub fn get(&self, phase: Phase) -> Option<Duration> {
+        self.phases
+            .iter()
+            .find(|(phase2, _)| *phase2 == phase)
+            .map(|(_, duration)| *duration)
+    }
+
+    /// Returns an iterator over the recorded phases and their durations.
+    pub fn iter(&self) -> impl Iterator<Item = &(Phase, Duration)> {
+        self.phases.iter()
+    }
+
+    /// Returns the sum of the durations of all the recorded phases.
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        for (phase, duration) in &self.phases {
+            writeln!(f, "{:<15}{:>12.3?}", phase.to_string(), duration)?;
+        }
+        write!(f, "{:<15}{:>12.3?}", "total", self.total())
+    }
+}
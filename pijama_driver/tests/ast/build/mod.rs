@@ -0,0 +1,76 @@
+use pijama_ast::{
+    build::{self, DUMMY_LOC},
+    node,
+    ty::Ty,
+    BinOp, Literal, Name, Node, UnOp,
+};
+use pijama_core::parser::parse;
+
+#[test]
+fn same_as_parser() {
+    let input = include_str!("program.pj");
+    let expected = vec![
+        build::fn_def(
+            "area",
+            vec![("width", Ty::Int), ("height", Ty::Int)],
+            Ty::Int,
+            vec![node!(width * height)],
+        ),
+        node!(xs: [Int] = [1, area(2, 3), -4]),
+        node!(let mut total = 0),
+        build::cond(
+            vec![node!(total < 10)],
+            vec![node!(print(to_string(total)))],
+            vec![node!(print("too big"))],
+        ),
+        node!(ok = !(total == (len(xs)))),
+    ];
+    assert_eq!(expected, Vec::from(parse(input).unwrap().content));
+}
+
+#[test]
+fn literals() {
+    assert_eq!(Node::Literal(Literal::Number(-1)), node!(-1).content);
+    assert_eq!(Node::Literal(Literal::Float(2.5)), node!(2.5).content);
+    assert_eq!(Node::Literal(Literal::Char('a')), node!('a').content);
+    assert_eq!(Node::Literal(Literal::Str("a".into())), node!("a").content);
+    assert_eq!(Node::Literal(Literal::Bool(true)), node!(true).content);
+    assert_eq!(Node::Literal(Literal::Unit), node!(unit).content);
+}
+
+#[test]
+fn operators() {
+    assert_eq!(
+        build::binary(
+            BinOp::Add,
+            build::binary(BinOp::Mul, node!(x), node!(2)),
+            node!(1)
+        ),
+        node!((x * 2) + 1)
+    );
+    assert_eq!(
+        build::unary(UnOp::Neg, build::binary(BinOp::Sub, node!(x), node!(y))),
+        node!(-x - y)
+    );
+    assert_eq!(
+        build::binary(BinOp::Pipe, node!(xs), build::name("sum")),
+        node!(xs |> sum)
+    );
+    assert_eq!(Node::Name(Name("len")), node!(len).content);
+}
+
+#[test]
+fn embedded_nodes() {
+    let body = build::anon_fn(vec![("x", Ty::Missing)], Ty::Missing, vec![node!(x + 1)]);
+    let node = node!(inc = { body.clone() });
+    assert_eq!(build::let_bind("inc", Ty::Missing, body), node);
+    assert_eq!(
+        build::let_bind(
+            "f",
+            Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)),
+            node!(g)
+        ),
+        node!(f: { Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)) } = g)
+    );
+    assert_eq!(DUMMY_LOC, node.loc);
+}
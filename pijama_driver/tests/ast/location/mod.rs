@@ -0,0 +1,59 @@
+use pijama_ast::{LineCol, LineIndex, Location};
+
+fn pos(line: usize, column: usize) -> LineCol {
+    LineCol { line, column }
+}
+
+#[test]
+fn lines() {
+    let index = LineIndex::new("x = 1\n\nprint(x)\n");
+    assert_eq!(4, index.line_count());
+    assert_eq!(0, index.line(0));
+    // Line breaks are part of the line they end.
+    assert_eq!(0, index.line(5));
+    assert_eq!(1, index.line(6));
+    assert_eq!(2, index.line(7));
+    assert_eq!(3, index.line(100));
+    assert_eq!(Some(7..16), index.line_range(2));
+    assert_eq!(Some(16..16), index.line_range(3));
+    assert_eq!(None, index.line_range(4));
+    assert_eq!(Some("print(x)"), index.line_text(2));
+    assert_eq!(Some(""), index.line_text(1));
+}
+
+#[test]
+fn line_col() {
+    let input = "x = 1\nprint(x)";
+    let index = LineIndex::new(input);
+    assert_eq!(pos(0, 0), index.line_col(0));
+    assert_eq!(pos(1, 6), index.line_col(12));
+    assert_eq!(pos(1, 8), index.line_col(100));
+
+    let loc = Location::new(input.find("print").unwrap(), input.len());
+    assert_eq!(pos(1, 0), loc.to_line_col(&index));
+    assert_eq!(pos(1, 0)..pos(1, 8), loc.to_line_col_range(&index));
+}
+
+#[test]
+fn columns_count_chars() {
+    let input = "s = \"ñandú\" ++ x";
+    let index = LineIndex::new(input);
+    let offset = input.find("++").unwrap();
+    assert_eq!(pos(0, 12), index.line_col(offset));
+    assert_eq!(Some(offset), index.offset(pos(0, 12)));
+    // Offsets inside a character are placed at its start.
+    let inside = input.find('ñ').unwrap() + 1;
+    assert_eq!(pos(0, 5), index.line_col(inside));
+}
+
+#[test]
+fn offset() {
+    let index = LineIndex::new("x = 1\nprint(x)\n");
+    assert_eq!(Some(0), index.offset(pos(0, 0)));
+    assert_eq!(Some(12), index.offset(pos(1, 6)));
+    // The end of a line is right before its line break.
+    assert_eq!(Some(5), index.offset(pos(0, 5)));
+    assert_eq!(None, index.offset(pos(0, 6)));
+    assert_eq!(Some(15), index.offset(pos(2, 0)));
+    assert_eq!(None, index.offset(pos(3, 0)));
+}
@@ -1,2 +1,6 @@
+mod build;
 mod fail;
+mod location;
+mod owned;
 mod pass;
+mod symbol;
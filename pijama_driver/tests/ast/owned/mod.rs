@@ -0,0 +1,39 @@
+use pijama_ast::{
+    analysis::RecursionChecker,
+    owned::{as_block, block_to_owned, OwnedBlock},
+    Node,
+};
+use pijama_core::parser::parse;
+
+fn parse_owned(input: &str) -> OwnedBlock {
+    block_to_owned(&parse(input).unwrap().content)
+}
+
+#[test]
+fn round_trip() {
+    let input = include_str!("round_trip.pj");
+    let block = parse(input).unwrap().content;
+    let owned = block_to_owned(&block);
+    assert_eq!(block, as_block(&owned));
+}
+
+#[test]
+fn outlives_input() {
+    let input = include_str!("round_trip.pj").to_owned();
+    let owned = parse_owned(&input);
+    drop(input);
+
+    let expected = parse_owned(include_str!("round_trip.pj"));
+    assert_eq!(expected, owned);
+}
+
+#[test]
+fn analysis_on_owned() {
+    let owned = parse_owned("fn fact(n: Int): Int do if n <= 0 do 1 else n * fact(n - 1) end end");
+    match owned[0].content.as_node() {
+        Node::FnDef(name, _, body, _) => {
+            assert!(RecursionChecker::run(name.content, &body.item.content))
+        }
+        node => panic!("unexpected node {:?}", node),
+    }
+}
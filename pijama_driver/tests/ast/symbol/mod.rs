@@ -0,0 +1,41 @@
+use pijama_ast::{symbol::SymbolTable, Name};
+
+use crate::run;
+
+#[test]
+fn intern_is_idempotent() {
+    let mut table = SymbolTable::new();
+    let x = table.intern(Name("x"));
+    let y = table.intern(Name("y"));
+    assert_ne!(x, y);
+    assert_eq!(x, table.intern(Name("x")));
+    assert_eq!(2, table.len());
+}
+
+#[test]
+fn resolve() {
+    let mut table = SymbolTable::new();
+    let symbols: Vec<_> = ["foo", "bar", "baz"]
+        .iter()
+        .map(|name| table.intern(Name(name)))
+        .collect();
+    for (index, symbol) in symbols.iter().enumerate() {
+        assert_eq!(index, symbol.index());
+    }
+    assert_eq!(Name("bar"), table.resolve(symbols[1]));
+}
+
+#[test]
+fn get_unknown() {
+    let mut table = SymbolTable::new();
+    assert!(table.is_empty());
+    table.intern(Name("x"));
+    assert_eq!(None, table.get(Name("y")));
+    assert!(table.get(Name("x")).is_some());
+}
+
+#[test]
+fn shadowing() {
+    let input = "x = 1\nfn f(x: Int): Int do x + 1 end\nx = f(x)\nprint(x)";
+    assert_eq!("2\n", run(input).unwrap());
+}
@@ -0,0 +1,105 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use pijama_core::{
+    lir::Term,
+    machine::{env::Env, CancellationToken, Cancelled, MachineBuilder},
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Polls `future` until it is ready, calling `on_pending` every time it yields. Returns the
+/// output of the future and the number of times it yielded.
+fn block_on<F: Future>(future: F, mut on_pending: impl FnMut(usize)) -> (F::Output, usize) {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut pending = 0;
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return (output, pending),
+            Poll::Pending => {
+                pending += 1;
+                on_pending(pending);
+            }
+        }
+    }
+}
+
+fn lower(input: &str) -> Term {
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    Term::from_mir(mir)
+}
+
+#[test]
+fn yields_periodically() {
+    let term = lower(include_str!("factorial.pj"));
+    let mut output = Vec::new();
+    let mut machine = MachineBuilder::new(Env::new(&mut output))
+        .with_yield_interval(10)
+        .build();
+    let (result, pending) = block_on(
+        machine.evaluate_async(term, CancellationToken::new()),
+        |_| (),
+    );
+    assert!(result.is_ok());
+    assert!(pending > 1, "{}", pending);
+    assert_eq!("3628800\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn same_result_as_sync() {
+    let mut output = Vec::new();
+    let mut machine = MachineBuilder::new(Env::new(&mut output))
+        .with_yield_interval(1)
+        .build();
+    let (result, _) = block_on(
+        machine.evaluate_async(
+            lower(include_str!("countdown.pj")),
+            CancellationToken::new(),
+        ),
+        |_| (),
+    );
+    let expected = MachineBuilder::new(Env::new(Vec::new()))
+        .build()
+        .evaluate(lower(include_str!("countdown.pj")));
+    assert_eq!(Ok(expected), result);
+    // The argument of `print` is printed once it is fully evaluated.
+    assert_eq!("0\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn cancel() {
+    let term = lower("fn loop(): Unit do loop() end\nloop()");
+    let token = CancellationToken::new();
+    let mut machine = MachineBuilder::new(Env::new(Vec::new()))
+        .with_yield_interval(100)
+        .build();
+    let (result, pending) = block_on(machine.evaluate_async(term, token.clone()), |pending| {
+        if pending == 3 {
+            token.cancel();
+        }
+    });
+    assert_eq!(Err(Cancelled), result);
+    assert_eq!(3, pending);
+}
+
+#[test]
+fn cancelled_before_start() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let mut machine = MachineBuilder::new(Env::new(Vec::new())).build();
+    let (result, pending) = block_on(machine.evaluate_async(lower("1 + 1"), token), |_| ());
+    assert_eq!(Err(Cancelled), result);
+    assert_eq!(0, pending);
+}
@@ -0,0 +1,82 @@
+use pijama_core::{
+    machine::Value,
+    ty::{Ty, TyError},
+};
+use pijama_driver::{
+    pipeline::{compile_with_bindings, Options},
+    run_with_bindings, LangError,
+};
+
+use crate::machine_builder;
+
+#[test]
+fn threshold() {
+    let input = include_str!("threshold.pj");
+    let bindings = [("threshold", Value::Int(5)), ("enabled", Value::Bool(true))];
+    assert_eq!(Ok(Some(Value::Int(5))), run_with_bindings(input, &bindings));
+
+    let bindings = [
+        ("threshold", Value::Int(5)),
+        ("enabled", Value::Bool(false)),
+    ];
+    assert_eq!(Ok(Some(Value::Int(0))), run_with_bindings(input, &bindings));
+}
+
+#[test]
+fn last_binding_shadows() {
+    let bindings = [("x", Value::Int(1)), ("x", Value::Int(2))];
+    assert_eq!(Ok(Some(Value::Int(2))), run_with_bindings("x", &bindings));
+}
+
+#[test]
+fn program_shadows_binding() {
+    let bindings = [("x", Value::Int(1))];
+    assert_eq!(
+        Ok(Some(Value::Bool(true))),
+        run_with_bindings("x = true\nx", &bindings)
+    );
+}
+
+#[test]
+fn binding_is_typed() {
+    let bindings = [("x", Value::Unit)];
+    let result = run_with_bindings("x + 1", &bindings);
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn missing_binding() {
+    let result = run_with_bindings("x", &[]);
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Unbounded(_)))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn function_result() {
+    assert_eq!(Ok(None), run_with_bindings("fn(x: Int) do x end", &[]));
+}
+
+#[test]
+fn pipeline() {
+    let options = Options {
+        opt_level: 1,
+        ..Options::default()
+    };
+    let bindings = [("limit", Value::Int(100))];
+    let program = compile_with_bindings("print(limit * 2)", &options, &bindings).unwrap();
+    assert_eq!(Ty::Unit, program.ty);
+
+    let mut output = Vec::default();
+    machine_builder(&mut output)
+        .build()
+        .evaluate(program.lir)
+        .unwrap();
+    assert_eq!("200\n", String::from_utf8(output).unwrap());
+}
@@ -0,0 +1,228 @@
+use pijama_ast::Location;
+use pijama_core::{
+    bytecode,
+    machine::{MachineBuilder, RuntimeError},
+};
+use pijama_driver::{
+    pipeline::{compile, Backend, Options},
+    run_with_options,
+    timings::{Phase, Timings},
+    LangError, LangResult,
+};
+
+use crate::machine_builder;
+
+fn run_with(input: &str, backend: Backend, stdin: &'static str) -> (LangResult<()>, String) {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_input(stdin.as_bytes())
+        .build();
+    let options = Options {
+        backend,
+        ..Options::default()
+    };
+    let result = run_with_options(
+        input,
+        machine,
+        &options,
+        &mut Timings::default(),
+        &mut Vec::new(),
+    );
+    (result, String::from_utf8(output).unwrap())
+}
+
+fn run_bytecode(input: &str) -> (LangResult<()>, String) {
+    run_with(input, Backend::Bytecode, "")
+}
+
+/// Runs `input` with the machine, dropping the stack trace of its runtime error since the
+/// bytecode backend does not record calls.
+fn run_untraced(input: &str) -> (LangResult<()>, String) {
+    match run_with(input, Backend::Machine, "") {
+        (Err(LangError::Traced { error, .. }), output) => (Err(*error), output),
+        result => result,
+    }
+}
+
+#[test]
+fn same_random_numbers() {
+    let input = include_str!("../eval/random.pj");
+    let mut outputs = Vec::new();
+    for backend in [Backend::Machine, Backend::Bytecode] {
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output).with_seed(7).build();
+        let options = Options {
+            backend,
+            ..Options::default()
+        };
+        run_with_options(
+            input,
+            machine,
+            &options,
+            &mut Timings::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        outputs.push(String::from_utf8(output).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+fn same_results() {
+    let inputs = [
+        include_str!("../eval/ackermann.pj"),
+        include_str!("../eval/add_overflow_fails.pj"),
+        include_str!("../eval/and_short_circuit.pj"),
+        include_str!("../eval/arithmetic.pj"),
+        include_str!("../eval/assertion_fails.pj"),
+        include_str!("../eval/assertions.pj"),
+        include_str!("../eval/bit_and.pj"),
+        include_str!("../eval/bit_not.pj"),
+        include_str!("../eval/bit_or.pj"),
+        include_str!("../eval/bit_shift_l.pj"),
+        include_str!("../eval/bit_shift_r.pj"),
+        include_str!("../eval/bit_xor.pj"),
+        include_str!("../eval/calling.pj"),
+        include_str!("../eval/char_conversion.pj"),
+        include_str!("../eval/char_match.pj"),
+        include_str!("../eval/complex_calling.pj"),
+        include_str!("../eval/cond_without_else.pj"),
+        include_str!("../eval/deep_recursion.pj"),
+        include_str!("../eval/division_by_zero.pj"),
+        include_str!("../eval/elif_chain.pj"),
+        include_str!("../eval/empty_head.pj"),
+        include_str!("../eval/enum_list_sum.pj"),
+        include_str!("../eval/enum_rose_tree.pj"),
+        include_str!("../eval/enum_shapes.pj"),
+        include_str!("../eval/factorial.pj"),
+        include_str!("../eval/factorial_tail.pj"),
+        include_str!("../eval/fancy_max.pj"),
+        include_str!("../eval/fibonacci.pj"),
+        include_str!("../eval/fibonacci_tail.pj"),
+        include_str!("../eval/float_arithmetic.pj"),
+        include_str!("../eval/float_conversion.pj"),
+        include_str!("../eval/gcd.pj"),
+        include_str!("../eval/invalid_char.pj"),
+        include_str!("../eval/lazy.pj"),
+        include_str!("../eval/list_comparison.pj"),
+        include_str!("../eval/list_index.pj"),
+        include_str!("../eval/list_ops.pj"),
+        include_str!("../eval/logic.pj"),
+        include_str!("../eval/logic_not.pj"),
+        include_str!("../eval/match_literal.pj"),
+        include_str!("../eval/math_overflow.pj"),
+        include_str!("../eval/math_primitives.pj"),
+        include_str!("../eval/mutable_counter.pj"),
+        include_str!("../eval/mutable_snapshot.pj"),
+        include_str!("../eval/mutual_recursion.pj"),
+        include_str!("../eval/mutual_tail_calls.pj"),
+        include_str!("../eval/neg_overflow_fails.pj"),
+        include_str!("../eval/number_bases_arithmetic.pj"),
+        include_str!("../eval/number_bases_cmp.pj"),
+        include_str!("../eval/or_short_circuit.pj"),
+        include_str!("../eval/overflow_semantics.pj"),
+        include_str!("../eval/pipe.pj"),
+        include_str!("../eval/print_complex_fn.pj"),
+        include_str!("../eval/print_print.pj"),
+        include_str!("../eval/print_redefine.pj"),
+        include_str!("../eval/print_simple.pj"),
+        include_str!("../eval/print_simple_fn.pj"),
+        include_str!("../eval/print_variant.pj"),
+        include_str!("../eval/sequence_scope.pj"),
+        include_str!("../eval/shared_argument.pj"),
+        include_str!("../eval/step.pj"),
+        include_str!("../eval/string_concat.pj"),
+        include_str!("../eval/string_escapes.pj"),
+        include_str!("../eval/string_index.pj"),
+        include_str!("../eval/string_interpolation.pj"),
+        include_str!("../eval/string_match.pj"),
+        include_str!("../eval/string_primitives.pj"),
+        include_str!("../eval/substring_range.pj"),
+        include_str!("../eval/tail_calls.pj"),
+        include_str!("../eval/threads.pj"),
+    ];
+    for input in inputs.iter() {
+        assert_eq!(run_untraced(input), run_bytecode(input), "{}", input);
+    }
+}
+
+#[test]
+fn division_by_zero() {
+    let input = include_str!("../eval/division_by_zero.pj");
+    let (result, output) = run_bytecode(input);
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::DivisionByZero(
+            Location::new(47, 60)
+        ))),
+        result
+    );
+    assert_eq!("5\n", output);
+}
+
+#[test]
+fn read_input() {
+    let input = include_str!("../eval/read_input.pj");
+    let stdin = "Pijama\n 41 \r\n";
+    let expected = run_with(input, Backend::Machine, stdin);
+    assert_eq!(expected, run_with(input, Backend::Bytecode, stdin));
+    assert_eq!("Hello, Pijama! 42\n1\n", expected.1);
+}
+
+#[test]
+fn values() {
+    let inputs = [
+        "push([1, 2], 1 + 2)",
+        "enum Shape do\n Circle(Int)\n Rect(Int, Int)\nend\nRect(1, 2 * 3)",
+        "fn add(x: Int, y: Int): Int do x + y end\nadd(1)",
+        "push([1])",
+        "to_string(1 + 2)",
+    ];
+    for input in inputs.iter() {
+        let lir = compile(*input, &Options::default()).unwrap().lir;
+        let program = bytecode::compile(&lir);
+        let expected = MachineBuilder::default().build().evaluate(lir);
+        let value = MachineBuilder::default()
+            .build()
+            .evaluate_bytecode(&program);
+        assert_eq!(
+            expected.map(|value| value.to_string()),
+            value.map(|value| value.to_string()),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn timings() {
+    let input = include_str!("../eval/factorial.pj");
+    for backend in [Backend::Machine, Backend::Bytecode].iter() {
+        let mut output = Vec::default();
+        let mut timings = Timings::default();
+        let options = Options {
+            backend: *backend,
+            ..Options::default()
+        };
+        let machine = machine_builder(&mut output).build();
+        run_with_options(input, machine, &options, &mut timings, &mut Vec::new()).unwrap();
+        let compiled = timings.get(Phase::Bytecode).is_some();
+        assert_eq!(*backend == Backend::Bytecode, compiled);
+        assert_eq!("3628800\n", String::from_utf8(output).unwrap());
+    }
+}
+
+#[test]
+fn threads_run_on_the_machine() {
+    let input = include_str!("../eval/threads.pj");
+    let mut output = Vec::default();
+    let mut timings = Timings::default();
+    let options = Options {
+        backend: Backend::Bytecode,
+        ..Options::default()
+    };
+    let machine = machine_builder(&mut output).build();
+    run_with_options(input, machine, &options, &mut timings, &mut Vec::new()).unwrap();
+    assert_eq!(None, timings.get(Phase::Bytecode));
+    assert_eq!("5050\n42\n100\n1\n", String::from_utf8(output).unwrap());
+}
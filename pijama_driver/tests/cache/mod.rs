@@ -0,0 +1,88 @@
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::{cache::CacheStats, check, Cache, LangError};
+
+const PROGRAM: &str = include_str!("program.pj");
+
+fn stats(hits: usize, misses: usize) -> CacheStats {
+    CacheStats { hits, misses }
+}
+
+#[test]
+fn same_result_as_check() {
+    let mut cache = Cache::new();
+    assert_eq!(check(PROGRAM), cache.check(PROGRAM));
+    assert_eq!(stats(0, 5), cache.stats());
+    assert_eq!(5, cache.len());
+}
+
+#[test]
+fn unchanged_program() {
+    let mut cache = Cache::new();
+    cache.check(PROGRAM).unwrap();
+    assert_eq!(Ok(Ty::Bool), cache.check(PROGRAM));
+    assert_eq!(stats(5, 0), cache.stats());
+}
+
+#[test]
+fn changed_body() {
+    let mut cache = Cache::new();
+    cache.check(PROGRAM).unwrap();
+    // `quadruple` keeps its type, so the definitions using it are still cached.
+    let input = PROGRAM.replace("double(double(x))", "double(x) + double(x)");
+    assert_eq!(Ok(Ty::Bool), cache.check(&input));
+    assert_eq!(stats(4, 1), cache.stats());
+}
+
+#[test]
+fn changed_type() {
+    let mut cache = Cache::new();
+    cache.check(PROGRAM).unwrap();
+    // `double` now returns `Bool`, so `quadruple` has to be checked again.
+    let input = PROGRAM.replace("x * 2", "x > 2");
+    let result = cache.check(&input);
+    assert_eq!(check(&input), result);
+    // Each use of the result of `double` as an integer is reported.
+    let errors = result.as_ref().unwrap_err().errors();
+    assert!(
+        errors.len() > 1
+            && errors
+                .iter()
+                .all(|error| matches!(error, LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn moved_definition() {
+    let mut cache = Cache::new();
+    cache.check(PROGRAM).unwrap();
+    let input = format!("\n\n{}", PROGRAM);
+    assert_eq!(Ok(Ty::Bool), cache.check(&input));
+    assert_eq!(stats(5, 0), cache.stats());
+}
+
+#[test]
+fn only_keeps_last_program() {
+    let mut cache = Cache::new();
+    cache.check(PROGRAM).unwrap();
+    cache.check("x = 1\nx + 1").unwrap();
+    assert_eq!(2, cache.len());
+}
+
+#[test]
+fn polymorphic_definition() {
+    let mut cache = Cache::new();
+    let input = "p = print\np(1)\np(true)";
+    assert_eq!(check(input), cache.check(input));
+}
+
+#[test]
+fn persisted() {
+    let mut cache = Cache::new();
+    cache.check(PROGRAM).unwrap();
+    let json = serde_json::to_string(&cache).unwrap();
+    let mut cache: Cache = serde_json::from_str(&json).unwrap();
+    assert_eq!(Ok(Ty::Bool), cache.check(PROGRAM));
+    assert_eq!(stats(5, 0), cache.stats());
+}
@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+
+use pijama_ast::Location;
+use pijama_core::machine::{Capabilities, Capability, RuntimeError};
+use pijama_driver::{
+    pipeline::{Backend, Options},
+    run_with_options,
+    timings::Timings,
+    LangError, LangResult,
+};
+
+use crate::machine_builder;
+
+fn backends() -> Vec<Backend> {
+    vec![
+        Backend::Machine,
+        Backend::Bytecode,
+        #[cfg(feature = "jit")]
+        Backend::Jit,
+    ]
+}
+
+fn run_with(input: &str, capabilities: Capabilities, backend: Backend) -> (LangResult<()>, String) {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_input(&b"7\n"[..])
+        .with_capabilities(capabilities)
+        .build();
+    let options = Options {
+        backend,
+        ..Options::default()
+    };
+    let result = run_with_options(
+        input,
+        machine,
+        &options,
+        &mut Timings::default(),
+        &mut Vec::new(),
+    );
+    (result, String::from_utf8(output).unwrap())
+}
+
+fn disabled(start: usize, end: usize, capability: Capability) -> LangResult<()> {
+    Err(LangError::Runtime(RuntimeError::CapabilityDisabled(
+        Location::new(start, end),
+        capability,
+    )))
+}
+
+#[test]
+fn allowed_by_default() {
+    let capabilities = Capabilities::default();
+    for capability in [Capability::Print, Capability::Read, Capability::Random] {
+        assert!(capabilities.allows(capability));
+        assert!(!Capabilities::none().allows(capability));
+    }
+}
+
+#[test]
+fn denied_print() {
+    let input = "print(read_int())\nprint(2)";
+    for backend in backends() {
+        let capabilities = Capabilities::all().deny(Capability::Print);
+        assert_eq!(
+            (disabled(0, 17, Capability::Print), String::new()),
+            run_with(input, capabilities, backend)
+        );
+    }
+}
+
+#[test]
+fn denied_read() {
+    let input = "print(1)\nprint(read_int())";
+    for backend in backends() {
+        let capabilities = Capabilities::all().deny(Capability::Read);
+        assert_eq!(
+            (disabled(15, 25, Capability::Read), "1\n".to_owned()),
+            run_with(input, capabilities, backend)
+        );
+    }
+}
+
+#[test]
+fn denied_random() {
+    let input = "print(random(1, 6))";
+    for backend in backends() {
+        let capabilities = Capabilities::none().allow(Capability::Print);
+        assert_eq!(
+            (disabled(6, 18, Capability::Random), String::new()),
+            run_with(input, capabilities, backend)
+        );
+    }
+}
+
+#[test]
+fn mocked_capabilities() {
+    let input = "print(read_int() + random(1, 6))\nprint(read_line())\nprint('x')";
+    for backend in backends() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let printed = Arc::clone(&lines);
+        let capabilities = Capabilities::none()
+            .mock_print(move |line| printed.lock().unwrap().push(line.to_owned()))
+            .mock_read(&b"40\nmocked\n"[..])
+            .mock_random(|lo, hi| lo + hi - 5);
+        assert_eq!(
+            (Ok(()), String::new()),
+            run_with(input, capabilities, backend)
+        );
+        assert_eq!(["42", "mocked", "x"], lines.lock().unwrap()[..]);
+    }
+}
+
+#[test]
+fn mocked_print_with_jit_program() {
+    // Programs the JIT supports only print integers, and they must still use the mock.
+    let input = "print(1 + 2)";
+    for backend in backends() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let printed = Arc::clone(&lines);
+        let capabilities = Capabilities::all()
+            .mock_print(move |line| printed.lock().unwrap().push(line.to_owned()));
+        assert_eq!(
+            (Ok(()), String::new()),
+            run_with(input, capabilities, backend)
+        );
+        assert_eq!(["3"], lines.lock().unwrap()[..]);
+    }
+}
+
+#[test]
+fn denied_capability_message() {
+    let error = RuntimeError::CapabilityDisabled(Location::new(0, 8), Capability::Print);
+    assert_eq!("The `print` capability is disabled", error.to_string());
+    assert_eq!("E0017", error.code());
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use pijama_core::{
+    lir::Term::{self, *},
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+use crate::run;
+
+fn lower(input: &str) -> Term {
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    Term::from_mir(mir)
+}
+
+#[test]
+fn no_abstractions_left() {
+    let lir = lower(include_str!("adder.pj"));
+    assert!(!format!("{:?}", lir).contains("Abs("));
+}
+
+#[test]
+fn captured_variables() {
+    let lir = lower(include_str!("nested.pj"));
+    // The outer function does not capture anything and its body is a closure capturing `x`, which
+    // is the variable `1` of its code.
+    match lir {
+        Closure(code, env) => {
+            assert!(env.is_empty());
+            match &*code {
+                Closure(code, env) => {
+                    assert_eq!(env, &[Arc::new(Var(0))]);
+                    assert_eq!("(_1 + _0)", code.to_string());
+                }
+                term => panic!("Expected a closure, found {:?}", term),
+            }
+        }
+        term => panic!("Expected a closure, found {:?}", term),
+    }
+    // Closures are shown as the abstractions they were converted from.
+    assert_eq!(
+        "(λ. (λ. (_1 + _0)))",
+        lower(include_str!("nested.pj")).to_string()
+    );
+}
+
+#[test]
+fn evaluation() {
+    assert_eq!("3\n", run(include_str!("adder.pj")).unwrap());
+}
+
+#[test]
+fn arms_binding_names() {
+    let lir = lower(include_str!("arm.pj"));
+    // The constructor of the enum is bound by the arm of a match, which captures nothing.
+    let function = match &lir {
+        Match(_, arms) => match &*arms[0].1 {
+            Closure(code, env) if env.is_empty() => Arc::clone(code),
+            term => panic!("Expected a closure, found {:?}", term),
+        },
+        term => panic!("Expected a match, found {:?}", term),
+    };
+    let body = match &*function {
+        Closure(code, _) => Arc::clone(code),
+        term => panic!("Expected a closure, found {:?}", term),
+    };
+    // The arm inside the function captures its parameter, which is placed after the names bound
+    // by the pattern in the code of the arm.
+    match &*body {
+        Match(_, arms) => match &*arms[0].1 {
+            Closure(code, env) => {
+                assert_eq!(env, &[Arc::new(Var(0))]);
+                assert_eq!("((_1 + _0) + _2)", code.to_string());
+            }
+            term => panic!("Expected a closure, found {:?}", term),
+        },
+        term => panic!("Expected a match, found {:?}", term),
+    }
+    // The arms are shown as they were before the conversion.
+    assert_eq!(
+        "(match ((λ. (λ. Pair(_1, _0)))) with #0/1 => (λ. (match ((_1 1) 2) with #0/2 => ((_1 + _0) + _2))))",
+        lir.to_string()
+    );
+}
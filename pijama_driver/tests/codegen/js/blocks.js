@@ -0,0 +1,16 @@
+const pick = (flag) => {
+    if (flag) {
+        return 1n;
+    } else {
+        return -1n;
+    }
+};
+const y = (() => {
+    if (pick(true) > 0n) {
+        const z = 10n;
+        return z * z;
+    } else {
+        return 0n;
+    }
+})();
+console.log(String(y));
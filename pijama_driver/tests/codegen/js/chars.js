@@ -0,0 +1,7 @@
+const is_digit = (c) => ("0" <= c) && (c <= "9");
+const next = (c) => String.fromCodePoint(Number(BigInt(c.codePointAt(0)) + 1n));
+if (is_digit("7")) {
+    console.log(String(next("'")));
+} else {
+    console.log(String("\n"));
+}
@@ -0,0 +1,27 @@
+const Circle = (x0) => ({ tag: "Circle", fields: [x0], toString() { return "Circle(" + this.fields.join(", ") + ")"; } });
+const Rect = (x0) => (x1) => ({ tag: "Rect", fields: [x0, x1], toString() { return "Rect(" + this.fields.join(", ") + ")"; } });
+const Empty = { tag: "Empty", fields: [], toString() { return "Empty"; } };
+const area = (shape) => (() => {
+    const $m = shape;
+    if ($m.tag === "Circle") {
+        const r = $m.fields[0];
+        return (3n * r) * r;
+    }
+    if ($m.tag === "Rect") {
+        const w = $m.fields[0];
+        const h = $m.fields[1];
+        return w * h;
+    }
+    if ($m.tag === "Empty") {
+        return 0n;
+    }
+    throw new Error("No arm matches " + String($m));
+})();
+console.log(String((area(Circle(2n)) + area(Rect(2n)(3n))) + area(Empty)));
+console.log(String((() => {
+    const $m = 2n;
+    if ($m === 1n) {
+        return Rect(1n)(1n);
+    }
+    return Empty;
+})()));
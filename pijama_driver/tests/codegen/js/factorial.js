@@ -0,0 +1,9 @@
+const fact = (n) => (acc) => {
+    if (n <= 0n) {
+        return acc;
+    } else {
+        return fact(n - 1n)(acc * n);
+    }
+};
+const x = fact(10n)(1n);
+console.log(String(x));
@@ -0,0 +1,4 @@
+const mean = (x) => (y) => (x + y) / 2.0;
+const round = (x) => BigInt(Math.trunc(x + 0.5));
+const m = mean(Number(3n))(-0.0015);
+console.log(String(round(m) * 2n));
@@ -0,0 +1,12 @@
+const evens = (n) => {
+    const for$0 = (end) => (i) => {
+        if (i < end) {
+            console.log(String(2n * i));
+            return for$0(end)(i + 1n);
+        } else {
+            return undefined;
+        }
+    };
+    return for$0(n)(0n);
+};
+evens(3n);
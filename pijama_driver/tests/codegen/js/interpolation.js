@@ -0,0 +1,2 @@
+const describe = (n) => (String(n) + " is ") + String(((n % 2n) === 0n) ? "even" : "odd");
+console.log(String(describe(3n)));
@@ -0,0 +1,7 @@
+const compute = (u) => {
+    console.log(String("computing"));
+    return 42n;
+};
+const value = { thunk: () => compute(undefined) };
+console.log(String("before forcing"));
+console.log(String(((l) => { if (l.thunk) { l.value = l.thunk(); l.thunk = undefined; } return l.value; })(value) + ((l) => { if (l.thunk) { l.value = l.thunk(); l.thunk = undefined; } return l.value; })(value)));
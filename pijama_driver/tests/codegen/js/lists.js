@@ -0,0 +1,3 @@
+const last = (xs) => xs[Number(BigInt(xs.length) - 1n)];
+const xs = [...[0n, 1n, 2n].slice(1), 3n];
+console.log(String([xs[0], last(xs)]));
@@ -0,0 +1,3 @@
+const lcm = (a) => (b) => ((n) => (n < 0n ? -n : n))(a * b) / ((a) => (b) => { a = a < 0n ? -a : a; b = b < 0n ? -b : b; while (b !== 0n) { [a, b] = [b, a % b]; } return a; })(a)(b);
+console.log(String(lcm(4n)(6n) + ((n) => (n > 0n ? 1n : n < 0n ? -1n : 0n))(-3n)));
+console.log(String(((a) => (b) => (a < b ? a : b))(((a) => (b) => (b < 0n ? 1n / a ** -b : a ** b))(2n)(10n))(((a) => (b) => (a > b ? a : b))(1000n)(((a) => (b) => (b < 0n ? 1n / a ** -b : a ** b))(10n)(-2n)))));
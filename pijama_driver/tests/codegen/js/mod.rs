@@ -0,0 +1,187 @@
+use std::include_str;
+
+use pijama_core::{codegen::CodegenError, ty::TyError};
+use pijama_driver::{emit_js, LangError, LangResult};
+
+#[test]
+fn factorial() -> LangResult<()> {
+    let input = include_str!("factorial.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("factorial.js"), output);
+    Ok(())
+}
+
+#[test]
+fn math() -> LangResult<()> {
+    let input = include_str!("math.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("math.js"), output);
+    Ok(())
+}
+
+#[test]
+fn text() -> LangResult<()> {
+    let input = include_str!("text.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("text.js"), output);
+    Ok(())
+}
+
+#[test]
+fn random() -> LangResult<()> {
+    let input = include_str!("random.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("random.js"), output);
+    Ok(())
+}
+
+#[test]
+fn shadowing() -> LangResult<()> {
+    let input = include_str!("shadowing.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("shadowing.js"), output);
+    Ok(())
+}
+
+#[test]
+fn blocks() -> LangResult<()> {
+    let input = include_str!("blocks.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("blocks.js"), output);
+    Ok(())
+}
+
+#[test]
+fn mutual() -> LangResult<()> {
+    let input = include_str!("mutual.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("mutual.js"), output);
+    Ok(())
+}
+
+#[test]
+fn for_loop() -> LangResult<()> {
+    let input = include_str!("for_loop.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("for_loop.js"), output);
+    Ok(())
+}
+
+#[test]
+fn mutable() -> LangResult<()> {
+    let input = include_str!("mutable.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("mutable.js"), output);
+    Ok(())
+}
+
+#[test]
+fn lazy() -> LangResult<()> {
+    let input = include_str!("lazy.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("lazy.js"), output);
+    Ok(())
+}
+
+#[test]
+fn while_loop() -> LangResult<()> {
+    let input = include_str!("while_loop.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("while_loop.js"), output);
+    Ok(())
+}
+
+#[test]
+fn nullary() -> LangResult<()> {
+    let input = include_str!("nullary.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("nullary.js"), output);
+    Ok(())
+}
+
+#[test]
+fn enums() -> LangResult<()> {
+    let input = include_str!("enums.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("enums.js"), output);
+    Ok(())
+}
+
+#[test]
+fn strings() -> LangResult<()> {
+    let input = include_str!("strings.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("strings.js"), output);
+    Ok(())
+}
+
+#[test]
+fn interpolation() -> LangResult<()> {
+    let input = include_str!("interpolation.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("interpolation.js"), output);
+    Ok(())
+}
+
+#[test]
+fn floats() -> LangResult<()> {
+    let input = include_str!("floats.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("floats.js"), output);
+    Ok(())
+}
+
+#[test]
+fn chars() -> LangResult<()> {
+    let input = include_str!("chars.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("chars.js"), output);
+    Ok(())
+}
+
+#[test]
+fn read_input() -> LangResult<()> {
+    let input = include_str!("read_input.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("read_input.js"), output);
+    Ok(())
+}
+
+#[test]
+fn lists() -> LangResult<()> {
+    let input = include_str!("lists.pj");
+    let output = emit_js(input)?;
+    assert_eq!(include_str!("lists.js"), output);
+    Ok(())
+}
+
+#[test]
+fn immediately_applied() {
+    assert_eq!(
+        Ok("((x) => x + 1n)(1n);\n".to_owned()),
+        emit_js("(fn(x: Int) do x + 1 end)(1)")
+    );
+}
+
+#[test]
+fn ill_typed() {
+    let result = emit_js("1 + true");
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn threads() {
+    let result = emit_js("c = channel()\nspawn(fn(u: Unit) do send(c, 1) end)\nprint(recv(c))");
+    assert!(
+        matches!(
+            result,
+            Err(LangError::Codegen(CodegenError::Unsupported(_)))
+        ),
+        "{:?}",
+        result
+    );
+}
@@ -0,0 +1,20 @@
+const triangle = (n) => {
+    let i = 0n;
+    let total = 0n;
+    (() => {
+        const while$0 = () => {
+            if (i < n) {
+                (() => {
+                    i = i + 1n;
+                    total = total + i;
+                })();
+                return while$0();
+            } else {
+                return undefined;
+            }
+        };
+        return while$0();
+    })();
+    return total;
+};
+console.log(String(triangle(10n)));
@@ -0,0 +1,15 @@
+const is_even = (n) => {
+    if (n === 0n) {
+        return true;
+    } else {
+        return is_odd(n - 1n);
+    }
+};
+const is_odd = (n) => {
+    if (n === 0n) {
+        return false;
+    } else {
+        return is_even(n - 1n);
+    }
+};
+console.log(String(is_even(10n)));
@@ -0,0 +1,9 @@
+const answer = 42n;
+const loop = () => {
+    if (false) {
+        return true;
+    } else {
+        return loop();
+    }
+};
+console.log(String((answer === 42n) || loop()));
@@ -0,0 +1,2 @@
+const roll = ((a) => (b) => { if (a > b) { [a, b] = [b, a]; } return a + BigInt(Math.floor(Math.random() * Number(b - a + 1n))); })(1n);
+console.log(String(roll(6n) + ((a) => (b) => { if (a > b) { [a, b] = [b, a]; } return a + BigInt(Math.floor(Math.random() * Number(b - a + 1n))); })(10n)(0n)));
@@ -0,0 +1,3 @@
+const name = (() => { const fs = require("fs"); const byte = Buffer.alloc(1); const bytes = []; while (fs.readSync(0, byte, 0, 1) === 1 && byte[0] !== 10) { bytes.push(byte[0]); } return Buffer.from(bytes).toString().replace(/\r$/, ""); })();
+const n = BigInt((() => { const fs = require("fs"); const byte = Buffer.alloc(1); const bytes = []; while (fs.readSync(0, byte, 0, 1) === 1 && byte[0] !== 10) { bytes.push(byte[0]); } return Buffer.from(bytes).toString().replace(/\r$/, ""); })().trim());
+console.log(String((("Hello, " + String(name)) + "! ") + String(n + 1n)));
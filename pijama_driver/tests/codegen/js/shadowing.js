@@ -0,0 +1,7 @@
+const x = 1n;
+const x$1 = x + 1n;
+const add = (x$2) => {
+    const x$3 = x$2 * 2n;
+    return x$3 + 1n;
+};
+console.log(String(add(x$1)));
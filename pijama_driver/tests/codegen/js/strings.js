@@ -0,0 +1,10 @@
+const greet = (name) => "Hello, " + (name + "!");
+const prefix = ((a) => (b) => a + b)("\"quoted\"\n");
+const greeting = (() => {
+    const $m = greet("world");
+    if ($m === "Hello, world!") {
+        return prefix("ok");
+    }
+    return "unexpected";
+})();
+console.log(String(greeting));
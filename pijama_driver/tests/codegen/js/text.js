@@ -0,0 +1,14 @@
+const reverse = (s) => {
+    if (BigInt(s.length) === 0n) {
+        return "";
+    } else {
+        return reverse(s.slice(Number(1n), Number(BigInt(s.length)))) + String(s[Number(0n)]);
+    }
+};
+const greeting = "¡Hola, mundo!";
+console.log(String(BigInt(greeting.length)));
+console.log(String(greeting.slice(Number(7n), Number(12n))));
+console.log(String(greeting[Number(0n)]));
+console.log(String(reverse("pijama")));
+console.log(String(BigInt(" 42 ".trim()) + BigInt("-1".trim())));
+console.log(String(String(BigInt([1n, 2n].length) === 2n) + String(7n)));
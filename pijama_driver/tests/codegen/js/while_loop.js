@@ -0,0 +1,12 @@
+const countdown = (n) => {
+    const while$0 = () => {
+        if (n > 0n) {
+            console.log(String(n));
+            return while$0();
+        } else {
+            return undefined;
+        }
+    };
+    return while$0();
+};
+countdown(0n);
@@ -0,0 +1,3 @@
+mod js;
+mod rust;
+mod wasm;
@@ -0,0 +1,14 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn is_digit(c: char) -> bool {
+        ('0' <= c) && (c <= '9')
+    }
+    fn next(c: char) -> char {
+        std::convert::TryFrom::try_from((c as i64) + 1).ok().and_then(std::char::from_u32).expect("Invalid character")
+    }
+    if is_digit('7') {
+        println!("{}", next('\''))
+    } else {
+        println!("{}", '\n')
+    }
+}
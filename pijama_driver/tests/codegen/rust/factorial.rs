@@ -0,0 +1,12 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn fact(n: i64, acc: i64) -> i64 {
+        if n <= 0 {
+            acc
+        } else {
+            fact(n - 1, acc * n)
+        }
+    }
+    let x: i64 = fact(10, 1);
+    println!("{:?}", x)
+}
@@ -0,0 +1,11 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn mean(x: f64, y: f64) -> f64 {
+        (x + y) / 2.0
+    }
+    fn round(x: f64) -> i64 {
+        (x + 0.5) as i64
+    }
+    let m: f64 = mean(3 as f64, -0.0015);
+    println!("{:?}", round(m) * 2)
+}
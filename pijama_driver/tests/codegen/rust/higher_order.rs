@@ -0,0 +1,16 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn twice(f: &dyn Fn(i64) -> i64, x: i64) -> i64 {
+        f(f(x))
+    }
+    let offset: i64 = 3;
+    let shift = |x: i64| -> i64 { x + offset };
+    fn apply_n(f: &dyn Fn(i64) -> i64, n: i64, x: i64) -> i64 {
+        if n == 0 {
+            x
+        } else {
+            apply_n(f, n - 1, twice(f, x))
+        }
+    }
+    println!("{:?}", apply_n(&shift, 2, 0))
+}
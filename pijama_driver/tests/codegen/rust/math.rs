@@ -0,0 +1,61 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn lcm(a: i64, b: i64) -> i64 {
+        i64::abs(a * b) / {
+            let (n1, n2): (i64, i64) = (a, b);
+            let (mut a, mut b) = (n1.abs(), n2.abs());
+            while b != 0 {
+                let r = a % b;
+                a = b;
+                b = r;
+            }
+            a
+        }
+    }
+    println!("{:?}", lcm(4, 6) + i64::signum(-3));
+    println!("{:?}", i64::min({
+        let (n1, n2): (i64, i64) = (2, 10);
+        let (mut base, mut exp, mut result) = (n1, n2, 1);
+        if exp < 0 {
+            assert!(base != 0, "Division by zero");
+            result = match base {
+                1 => 1,
+                -1 if exp % 2 == 0 => 1,
+                -1 => -1,
+                _ => 0,
+            };
+        }
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base *= base;
+            }
+        }
+        result
+    }, i64::max(1000, {
+        let (n1, n2): (i64, i64) = (10, -2);
+        let (mut base, mut exp, mut result) = (n1, n2, 1);
+        if exp < 0 {
+            assert!(base != 0, "Division by zero");
+            result = match base {
+                1 => 1,
+                -1 if exp % 2 == 0 => 1,
+                -1 => -1,
+                _ => 0,
+            };
+        }
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base *= base;
+            }
+        }
+        result
+    })))
+}
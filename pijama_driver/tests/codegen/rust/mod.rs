@@ -0,0 +1,166 @@
+use std::include_str;
+
+use pijama_core::codegen::CodegenError;
+use pijama_driver::{emit_rust, LangError, LangResult};
+
+#[test]
+fn factorial() -> LangResult<()> {
+    let input = include_str!("factorial.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("factorial.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn math() -> LangResult<()> {
+    let input = include_str!("math.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("math.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn higher_order() -> LangResult<()> {
+    let input = include_str!("higher_order.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("higher_order.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn mutual() -> LangResult<()> {
+    let input = include_str!("mutual.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("mutual.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn nullary() -> LangResult<()> {
+    let input = include_str!("nullary.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("nullary.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn shadowing() -> LangResult<()> {
+    let input = include_str!("shadowing.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("shadowing.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn mutable() -> LangResult<()> {
+    let input = include_str!("mutable.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("mutable.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn floats() -> LangResult<()> {
+    let input = include_str!("floats.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("floats.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn chars() -> LangResult<()> {
+    let input = include_str!("chars.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("chars.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn read_int() -> LangResult<()> {
+    let input = include_str!("read_int.pj");
+    let output = emit_rust(input, "run")?;
+    assert_eq!(include_str!("read_int.rs"), output);
+    Ok(())
+}
+
+#[test]
+fn returns_value() {
+    assert_eq!(
+        Ok("#[allow(unused, arithmetic_overflow)]\npub fn answer() -> bool {\n    (1i64 + 1i64) == 2i64\n}\n".to_owned()),
+        emit_rust("1 + 1 == 2", "answer")
+    );
+}
+
+fn unsupported(input: &str) {
+    let result = emit_rust(input, "run");
+    assert!(
+        matches!(
+            result,
+            Err(LangError::Codegen(CodegenError::Unsupported(_)))
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn partial_application() {
+    unsupported("fn add(x: Int, y: Int): Int do x + y end\ninc = add(1)\ninc(2)");
+}
+
+#[test]
+fn returns_function() {
+    unsupported("fn adder(x: Int): Int -> Int do fn(y: Int) do x + y end end\nadd_one = adder(1)\nadd_one(2)");
+}
+
+#[test]
+fn recursive_capture() {
+    unsupported(
+        "step = 2\nfn down(n: Int): Int do if n <= 0 do n else down(n - step) end end\ndown(9)",
+    );
+}
+
+#[test]
+fn enums() {
+    unsupported("enum Bit do One Zero end\nmatch One with One do 1 end Zero do 0 end end");
+}
+
+#[test]
+fn strings() {
+    unsupported("print(concat(\"a\", \"b\"))");
+}
+
+#[test]
+fn string_primitives() {
+    unsupported("fn first(s: String): Char do char_at(s, 0) end\nfirst");
+}
+
+#[test]
+fn random() {
+    unsupported("print(random(1, 6))");
+}
+
+#[test]
+fn threads() {
+    unsupported("c = channel()\nspawn(fn(u: Unit) do send(c, 1) end)\nprint(recv(c))");
+}
+
+#[test]
+fn lazy() {
+    unsupported("x = lazy 1 + 2\nprint(force(x))");
+}
+
+#[test]
+fn lists() {
+    unsupported("print(len([1, 2]))");
+}
+
+#[test]
+fn unannotated_param() {
+    unsupported("fn add(x, y) do x + y end\nadd(1, 2)");
+}
+
+#[test]
+fn generic_return_type() {
+    unsupported("fn id(x: Int): a do x end\nid(1)");
+}
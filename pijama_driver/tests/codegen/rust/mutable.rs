@@ -0,0 +1,11 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn double(n: i64) -> i64 {
+        let mut x: i64 = n;
+        x = x * 2;
+        x
+    }
+    let mut y: i64 = double(3);
+    y = y + 1;
+    println!("{:?}", y)
+}
@@ -0,0 +1,18 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    fn is_even(n: i64) -> bool {
+        if n == 0 {
+            true
+        } else {
+            is_odd(n - 1)
+        }
+    }
+    fn is_odd(n: i64) -> bool {
+        if n == 0 {
+            false
+        } else {
+            is_even(n - 1)
+        }
+    }
+    println!("{:?}", is_even(10))
+}
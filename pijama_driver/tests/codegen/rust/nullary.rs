@@ -0,0 +1,12 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    let answer: i64 = 42;
+    fn r#loop() -> bool {
+        if false {
+            true
+        } else {
+            r#loop()
+        }
+    }
+    println!("{:?}", (answer == 42) || r#loop())
+}
@@ -0,0 +1,9 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    let n: i64 = {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line).expect("Cannot read the input");
+        line.trim().parse::<i64>().expect("Cannot read the line as an integer")
+    };
+    println!("{:?}", n + 1)
+}
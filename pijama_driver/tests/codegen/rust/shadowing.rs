@@ -0,0 +1,10 @@
+#[allow(unused, arithmetic_overflow)]
+pub fn run() {
+    let x: i64 = 1;
+    let x: i64 = x + 1;
+    fn add(x: i64) -> i64 {
+        let x: i64 = x * 2;
+        x + 1
+    }
+    println!("{:?}", add(x))
+}
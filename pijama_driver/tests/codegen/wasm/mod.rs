@@ -0,0 +1,121 @@
+use pijama_core::codegen::CodegenError;
+use pijama_driver::{emit_wasm, LangError};
+
+fn unsupported(input: &str) {
+    let result = emit_wasm(input);
+    assert!(
+        matches!(
+            result,
+            Err(LangError::Codegen(CodegenError::Unsupported(_)))
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn header() {
+    let module = emit_wasm("1 + 2").unwrap();
+    assert_eq!(b"\0asm\x01\0\0\0", &module[..8]);
+}
+
+#[test]
+fn floats() {
+    unsupported("print(1.5 + 2.0)");
+}
+
+#[test]
+fn strings() {
+    unsupported("print(concat(\"a\", \"b\"))");
+}
+
+#[test]
+fn lists() {
+    unsupported("print(len([1, 2]))");
+}
+
+#[test]
+fn enums() {
+    unsupported("enum Bit do One Zero end\nmatch One with One do 1 end Zero do 0 end end");
+}
+
+#[cfg(feature = "wasmtime")]
+mod run {
+    use pijama_core::lir::Term;
+    use pijama_driver::{
+        emit_wasm,
+        pipeline::{compile, Options},
+    };
+    use wasmtime::{Caller, Engine, Linker, Module, Store};
+
+    use crate::machine_builder;
+
+    /// Runs the module emitted for `input`, returning the value of `main` and the printed output.
+    fn run_wasm(input: &str) -> (Term, String) {
+        let engine = Engine::default();
+        let module = Module::new(&engine, emit_wasm(input).unwrap()).unwrap();
+        let mut store = Store::new(&engine, String::new());
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap("env", "print", |mut caller: Caller<'_, String>, n: i64| {
+                caller.data_mut().push_str(&format!("{}\n", n));
+            })
+            .unwrap();
+        let instance = linker.instantiate(&mut store, &module).unwrap();
+        let main = instance
+            .get_typed_func::<(), i64>(&mut store, "main")
+            .unwrap();
+        let value = main.call(&mut store, ()).unwrap();
+        (Term::Lit(value), store.into_data())
+    }
+
+    fn run_machine(input: &str) -> (Term, String) {
+        let lir = compile(input, &Options::default()).unwrap().lir;
+        let mut output = Vec::default();
+        let value = machine_builder(&mut output).build().evaluate(lir).unwrap();
+        (value, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn same_results() {
+        let inputs = [
+            include_str!("../../eval/ackermann.pj"),
+            include_str!("../../eval/arithmetic.pj"),
+            include_str!("../../eval/bit_and.pj"),
+            include_str!("../../eval/bit_not.pj"),
+            include_str!("../../eval/bit_or.pj"),
+            include_str!("../../eval/bit_shift_l.pj"),
+            include_str!("../../eval/bit_shift_r.pj"),
+            include_str!("../../eval/bit_xor.pj"),
+            include_str!("../../eval/calling.pj"),
+            include_str!("../../eval/complex_calling.pj"),
+            include_str!("../../eval/cond_without_else.pj"),
+            include_str!("../../eval/elif_chain.pj"),
+            include_str!("../../eval/factorial.pj"),
+            include_str!("../../eval/factorial_tail.pj"),
+            include_str!("../../eval/fancy_max.pj"),
+            include_str!("../../eval/fibonacci.pj"),
+            include_str!("../../eval/fibonacci_tail.pj"),
+            include_str!("../../eval/gcd.pj"),
+            include_str!("../../eval/logic.pj"),
+            include_str!("../../eval/logic_not.pj"),
+            include_str!("../../eval/match_literal.pj"),
+            include_str!("../../eval/number_bases_arithmetic.pj"),
+            include_str!("../../eval/number_bases_cmp.pj"),
+            include_str!("../../eval/print_complex_fn.pj"),
+            include_str!("../../eval/print_print.pj"),
+            include_str!("../../eval/sequence_scope.pj"),
+            include_str!("../../eval/shared_argument.pj"),
+            include_str!("../../eval/step.pj"),
+        ];
+        for input in inputs.iter() {
+            assert_eq!(run_machine(input), run_wasm(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn partial_application() {
+        let input = "fn add(x: Int, y: Int): Int do x + y end\nadd3 = add(3)\nadd3(add3(4))";
+        assert_eq!((Term::Lit(10), String::new()), run_wasm(input));
+    }
+}
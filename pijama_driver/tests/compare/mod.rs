@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use pijama_ast::BinOp;
+use pijama_core::{
+    lir::{
+        compare::Difference,
+        Term::{self, *},
+    },
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+fn lower(input: &str) -> Term {
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    Term::from_mir(mir)
+}
+
+fn add(t1: Term, t2: Term) -> Term {
+    BinaryOp(BinOp::Add, Arc::new(t1), Arc::new(t2))
+}
+
+#[test]
+fn locations_are_ignored() {
+    let t1 = lower("print(10 / 2)");
+    let t2 = lower("print(10/2)");
+    assert_ne!(t1, t2);
+    assert!(t1.alpha_eq(&t2));
+    assert!(!t1.alpha_eq(&lower("print(10 / 3)")));
+}
+
+#[test]
+fn names_are_ignored() {
+    let input = include_str!("../closure/arm.pj");
+    let renamed = input
+        .replace("(n)", "(m)")
+        .replace("Pair(x, y) do x + y + n", "Pair(a, b) do a + b + m");
+    assert_ne!(input, renamed);
+    assert!(lower(input).alpha_eq(&lower(&renamed)));
+}
+
+#[test]
+fn closures_are_abstractions() {
+    let lir = lower(include_str!("../closure/nested.pj"));
+    let nested = |body| Abs(Arc::new(Abs(Arc::new(body))));
+    assert!(lir.alpha_eq(&nested(add(Var(1), Var(0)))));
+    assert_eq!(
+        Some(Difference {
+            left: Arc::new(Var(1)),
+            right: Arc::new(Var(0)),
+        }),
+        lir.diff(&nested(add(Var(0), Var(1))))
+    );
+}
+
+#[test]
+fn cells_and_channels_are_renamed() {
+    let app = |t1, t2| App(Arc::new(t1), Arc::new(t2));
+    assert!(app(Cell(3), Cell(3)).alpha_eq(&app(Cell(0), Cell(0))));
+    assert!(app(Cell(3), Chan(3)).alpha_eq(&app(Cell(0), Chan(1))));
+    assert!(!app(Cell(0), Cell(1)).alpha_eq(&app(Cell(0), Cell(0))));
+    assert!(!app(Cell(0), Cell(0)).alpha_eq(&app(Cell(0), Cell(1))));
+}
+
+#[test]
+fn first_difference() {
+    let diff = lower("print(1 + 2)\nprint(3)").diff(&lower("print(1 + 4)\nprint(5)"));
+    assert_eq!(
+        Some(Difference {
+            left: Arc::new(Lit(2)),
+            right: Arc::new(Lit(4)),
+        }),
+        diff
+    );
+    assert_eq!("`2` differs from `4`", diff.unwrap().to_string());
+    assert_eq!(None, lower("print(1 + 2)").diff(&lower("print(1 + 2)")));
+}
+
+#[test]
+fn floats_are_compared_by_bits() {
+    assert!(Float(f64::NAN).alpha_eq(&Float(f64::NAN)));
+    assert!(!Float(0.0).alpha_eq(&Float(-0.0)));
+}
@@ -0,0 +1,103 @@
+use pijama_core::{machine::Value, ty::Ty};
+use pijama_driver::{cache::CacheStats, pipeline::Options, Cache, Compilation};
+
+use crate::machine_builder;
+
+#[test]
+fn stages() {
+    let mut compilation = Compilation::new("x = 2 * 3\nprint(x)\nx + 1", Options::default());
+    assert_eq!(3, compilation.parse().unwrap().content.len());
+    assert!(compilation
+        .lower()
+        .unwrap()
+        .content
+        .to_string()
+        .contains("2 * 3"));
+    assert_eq!(Ty::Int, compilation.check().unwrap().ty);
+    assert!(compilation.to_lir().unwrap().to_string().contains("2 * 3"));
+
+    let mut output = Vec::default();
+    let value = compilation
+        .evaluate_with_machine(machine_builder(&mut output).build())
+        .unwrap();
+    assert_eq!(Some(Value::Int(7)), value);
+    assert_eq!("6\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn later_stages_run_earlier_ones() {
+    let mut compilation = Compilation::new("1 + 2", Options::default());
+    assert_eq!(Some(Value::Int(3)), compilation.evaluate().unwrap());
+    assert_eq!(Some(Value::Int(3)), compilation.evaluate().unwrap());
+    assert_eq!(1, compilation.parse().unwrap().content.len());
+}
+
+#[test]
+fn passes_run_before_lir() {
+    let options = Options {
+        opt_level: 1,
+        ..Options::default()
+    };
+    let mut compilation = Compilation::new("x = 2 * 3\nx + 1", options);
+    assert!(compilation
+        .check()
+        .unwrap()
+        .mir
+        .content
+        .to_string()
+        .contains("2 * 3"));
+    assert!(!compilation.to_lir().unwrap().to_string().contains("2 * 3"));
+    assert_eq!(Some(Value::Int(7)), compilation.evaluate().unwrap());
+}
+
+#[test]
+fn prelude() {
+    let input = "is_some(Some(1))";
+    let mut compilation = Compilation::new(input, Options::default());
+    assert_eq!(Ty::Bool, compilation.check().unwrap().ty);
+
+    let options = Options {
+        prelude: false,
+        ..Options::default()
+    };
+    let mut compilation = Compilation::new(input, options);
+    assert_eq!("E0003", compilation.check().unwrap_err().code());
+}
+
+#[test]
+fn warnings() {
+    let mut compilation = Compilation::new("unit\nprint(1)", Options::default());
+    assert_eq!(1, compilation.check().unwrap().warnings.len());
+}
+
+#[test]
+fn errors_are_returned_by_later_stages() {
+    let mut compilation = Compilation::new("x = (1", Options::default());
+    let error = compilation.parse().unwrap_err();
+    assert_eq!(error, compilation.parse().unwrap_err());
+    assert_eq!(error, compilation.evaluate().unwrap_err());
+
+    let mut compilation = Compilation::new("x: Int = true", Options::default());
+    assert!(compilation.lower().is_ok());
+    let error = compilation.check().unwrap_err();
+    assert_eq!("E0002", error.code());
+    assert_eq!(error, compilation.to_lir().unwrap_err());
+}
+
+#[test]
+fn cache() {
+    let mut cache = Cache::new();
+    let input = "fn double(x: Int): Int do x * 2 end\ny = double(2)\nprint(y)";
+    let mut compilation = Compilation::new(input, Options::default());
+    compilation.check_with_cache(&mut cache).unwrap();
+    assert_eq!(CacheStats { hits: 0, misses: 3 }, cache.stats());
+
+    let input = input.replace("double(2)", "double(3)");
+    let mut compilation = Compilation::new(input.as_str(), Options::default());
+    assert_eq!(
+        Ty::Unit,
+        compilation.check_with_cache(&mut cache).unwrap().ty
+    );
+    assert_eq!(CacheStats { hits: 2, misses: 1 }, cache.stats());
+    assert_eq!(Some(Value::Unit), compilation.evaluate().unwrap());
+}
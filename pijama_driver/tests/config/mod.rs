@@ -0,0 +1,58 @@
+use pijama_driver::config::{ColorChoice, Config, ConfigError, WarningLevel};
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(var, value)| (var.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn empty_file_is_default() {
+    let config = Config::from_toml("").unwrap();
+    assert_eq!(Config::default(), config);
+}
+
+#[test]
+fn all_keys() {
+    let input = include_str!("all_keys.toml");
+    let config = Config::from_toml(input).unwrap();
+    let expected = Config {
+        opt_level: 2,
+        warnings: WarningLevel::Deny,
+        fuel: Some(1000),
+        color: ColorChoice::Never,
+        overflow_check: true,
+    };
+    assert_eq!(expected, config);
+}
+
+#[test]
+fn unknown_key() {
+    let err = Config::from_toml("optimize = true").unwrap_err();
+    assert!(matches!(err, ConfigError::Toml(_)), "{:?}", err);
+}
+
+#[test]
+fn env_overrides_file() {
+    let mut config = Config::from_toml("color = \"always\"\nopt_level = 1").unwrap();
+    config
+        .merge_env(vars(&[
+            ("PIJAMA_COLOR", "never"),
+            ("PIJAMA_FUEL", "10"),
+            ("PIJAMA_OVERFLOW_CHECK", "true"),
+            ("COLOR", "always"),
+        ]))
+        .unwrap();
+    assert_eq!(ColorChoice::Never, config.color);
+    assert_eq!(Some(10), config.fuel);
+    assert!(config.overflow_check);
+    assert_eq!(1, config.opt_level);
+}
+
+#[test]
+fn invalid_env_value() {
+    let err = Config::default()
+        .merge_env(vars(&[("PIJAMA_WARNINGS", "loud")]))
+        .unwrap_err();
+    assert!(matches!(err, ConfigError::Env { .. }), "{:?}", err);
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use pijama_ast::Location;
+use pijama_core::{
+    lir::{Pass, Term},
+    machine::RuntimeError,
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+use crate::{machine_builder, panic_after, run};
+
+fn run_cps_with_input(input: &str, stdin: &'static str) -> (Result<Term, RuntimeError>, String) {
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    let term = Term::from_mir_with(mir, &[Pass::Cps]);
+    let mut output = Vec::default();
+    let result = machine_builder(&mut output)
+        .with_input(stdin.as_bytes())
+        .build()
+        .evaluate(term);
+    (result, String::from_utf8(output).unwrap())
+}
+
+/// Returns the number of steps taken to evaluate `input` after running `passes`.
+fn steps(input: &str, passes: &[Pass]) -> u64 {
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    let term = Term::from_mir_with(mir, passes);
+    let mut output = Vec::default();
+    let (result, stats) = machine_builder(&mut output)
+        .build()
+        .evaluate_with_stats(term);
+    result.unwrap();
+    stats.steps
+}
+
+fn run_cps(input: &str) -> String {
+    let (result, output) = run_cps_with_input(input, "");
+    result.unwrap();
+    output
+}
+
+#[test]
+fn same_output() {
+    let inputs = [
+        include_str!("../eval/factorial.pj"),
+        include_str!("../eval/fibonacci_tail.pj"),
+        include_str!("../eval/mutual_recursion.pj"),
+        include_str!("../eval/and_short_circuit.pj"),
+        include_str!("../eval/enum_shapes.pj"),
+        include_str!("../eval/list_ops.pj"),
+        include_str!("../eval/lazy.pj"),
+        include_str!("../eval/mutable_counter.pj"),
+        include_str!("../eval/print_print.pj"),
+        include_str!("../eval/shared_argument.pj"),
+        include_str!("../eval/string_match.pj"),
+        include_str!("../eval/string_primitives.pj"),
+        include_str!("../eval/threads.pj"),
+    ];
+    for input in inputs {
+        assert_eq!(run(input).unwrap(), run_cps(input));
+    }
+}
+
+#[test]
+fn deep_recursion() {
+    // The recursive call is not in tail position, so the evaluation only grows the continuation.
+    assert_eq!("200010000\n", run_cps(include_str!("sum.pj")));
+}
+
+#[test]
+fn tail_calls() {
+    panic_after(Duration::from_secs(10), || {
+        let output = run_cps(include_str!("../eval/mutual_tail_calls.pj"));
+        assert_eq!("1\n", output);
+    })
+}
+
+#[test]
+fn cost_follows_the_machine() {
+    // Arguments are passed by name, so each call runs the subtractions of all the calls around it
+    // and the number of steps grows quadratically with or without the pass, which only multiplies
+    // it by a constant.
+    let input = "fn sum(n: Int): Int do if n == 0 do 0 else n + sum(n - 1) end end\nsum(400)";
+    assert!(steps(input, &[Pass::Cps]) <= 7 * steps(input, &[]));
+    // Tail calls bind their arguments strictly, so the number of steps grows linearly.
+    let input = include_str!("../eval/mutual_tail_calls.pj");
+    let half = input.replace("is_even(20000)", "is_even(10000)");
+    assert!(steps(input, &[Pass::Cps]) <= 2 * steps(&half, &[Pass::Cps]) + 100);
+}
+
+#[test]
+fn runtime_errors_keep_locations() {
+    let input = include_str!("../eval/read_int_invalid.pj");
+    let (result, output) = run_cps_with_input(input, "forty-one\n");
+    assert_eq!(
+        Err(RuntimeError::InvalidInt(
+            Location::new(20, 30),
+            "forty-one".to_owned()
+        )),
+        result
+    );
+    assert_eq!("before\n", output);
+}
@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use pijama_ast::Location;
+use pijama_core::{
+    lir::Term,
+    machine::debug::{Control, DebugState, EvalEvent},
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+use crate::machine_builder;
+
+fn lir(input: &str) -> Term {
+    Term::from_mir(MirTerm::from_ast(parse(input).unwrap()).unwrap())
+}
+
+#[test]
+fn function_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    let recorded = events.clone();
+    machine.set_hook(move |event| {
+        let event = match event {
+            EvalEvent::Enter { loc, arg, .. } => format!("enter {} at {}", arg, loc.start),
+            EvalEvent::Exit { loc, value } => format!("exit {} at {}", value, loc.start),
+            EvalEvent::Breakpoint { .. } => unreachable!(),
+        };
+        recorded.lock().unwrap().push(event);
+        Control::Continue
+    });
+    machine.evaluate(lir(include_str!("program.pj"))).unwrap();
+    assert_eq!(
+        vec!["enter 2 at 44", "exit 3 at 44"],
+        *events.lock().unwrap()
+    );
+    assert_eq!("9\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn pause_at_breakpoint() {
+    let input = include_str!("lines.pj");
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    let breakpoints = Arc::new(Mutex::new(Vec::new()));
+    let reached = breakpoints.clone();
+    machine.set_hook(move |event| match event {
+        EvalEvent::Breakpoint { breakpoint, .. } => {
+            reached.lock().unwrap().push(*breakpoint);
+            Control::Pause
+        }
+        _ => Control::Continue,
+    });
+    // The second line.
+    machine.add_breakpoint(Location::new(9, 17));
+
+    let term = match machine.debug(lir(input)) {
+        Ok(DebugState::Paused(term)) => term,
+        result => panic!("{:?}", result),
+    };
+    assert_eq!(vec![Location::new(9, 17)], *breakpoints.lock().unwrap());
+    // The evaluation pauses after the step that reached the breakpoint.
+    assert!(matches!(machine.debug(term), Ok(DebugState::Done(_))));
+    assert_eq!(1, breakpoints.lock().unwrap().len());
+    assert_eq!("1\n2\n3\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn evaluate_resumes() {
+    let input = include_str!("lines.pj");
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    machine.set_hook(|_| Control::Pause);
+    machine.add_breakpoint(Location::new(0, 26));
+    machine.evaluate(lir(input)).unwrap();
+    assert_eq!("1\n2\n3\n", String::from_utf8(output).unwrap());
+}
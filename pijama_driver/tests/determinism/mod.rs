@@ -0,0 +1,172 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use pijama_ast::{Location, Primitive};
+use pijama_core::{
+    bytecode,
+    lir::Term,
+    machine::{env::Env, CancellationToken, Capabilities, MachineBuilder, RuntimeError},
+    mir::Term as MirTerm,
+    parser::parse,
+};
+use pijama_driver::{
+    pipeline::{Backend, Options},
+    run_with_options,
+    timings::Timings,
+    LangError, LangResult,
+};
+
+use crate::machine_builder;
+
+fn backends() -> Vec<Backend> {
+    vec![
+        Backend::Machine,
+        Backend::Bytecode,
+        #[cfg(feature = "jit")]
+        Backend::Jit,
+    ]
+}
+
+fn run_deterministic(input: &str, backend: Backend) -> (LangResult<()>, String) {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_input(&b"7\n"[..])
+        .deterministic()
+        .build();
+    let options = Options {
+        backend,
+        ..Options::default()
+    };
+    let result = run_with_options(
+        input,
+        machine,
+        &options,
+        &mut Timings::default(),
+        &mut Vec::new(),
+    );
+    (result, String::from_utf8(output).unwrap())
+}
+
+fn lower(input: &str) -> Term {
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    Term::from_mir(mir)
+}
+
+fn nondeterministic(start: usize, end: usize, prim: Primitive) -> RuntimeError {
+    RuntimeError::Nondeterministic(Location::new(start, end), prim)
+}
+
+#[test]
+fn rejects_effects_before_evaluating() {
+    let cases = [
+        ("print(1)", nondeterministic(0, 8, Primitive::Print)),
+        (
+            "x = 1 + 2\nx + read_int()",
+            nondeterministic(14, 24, Primitive::ReadInt),
+        ),
+        (
+            "name = read_line()\nconcat(\"hi \", name)",
+            nondeterministic(7, 18, Primitive::ReadLine),
+        ),
+        (
+            "assert(1 == 1)\nrandom(1, 6)",
+            nondeterministic(15, 27, Primitive::Random),
+        ),
+    ];
+    for (input, error) in cases.iter() {
+        for backend in backends() {
+            assert_eq!(
+                (Err(LangError::Runtime(error.clone())), String::new()),
+                run_deterministic(input, backend),
+                "{}",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn rejects_effects_that_are_never_evaluated() {
+    let input = "fn roll(): Int do random(1, 6) end\n\nif false do roll() else 3 end";
+    for backend in backends() {
+        assert_eq!(
+            (
+                Err(LangError::Runtime(nondeterministic(
+                    18,
+                    30,
+                    Primitive::Random
+                ))),
+                String::new()
+            ),
+            run_deterministic(input, backend)
+        );
+    }
+}
+
+#[test]
+fn rejects_mocked_effects() {
+    let term = lower("random(1, 6)");
+    let mut machine = MachineBuilder::new(Env::new(Vec::new()))
+        .with_capabilities(Capabilities::all().mock_random(|lo, _| lo))
+        .deterministic()
+        .build();
+    assert_eq!(
+        Err(nondeterministic(0, 12, Primitive::Random)),
+        machine.evaluate(term)
+    );
+}
+
+#[test]
+fn runs_pure_programs() {
+    let input = "fn f(x: Float): Float do x * 0.1 + 0.2 end\n\nconcat(to_string(f(3.0)), \"!\")";
+    let term = lower(input);
+    let expected = MachineBuilder::new(Env::new(Vec::new()))
+        .build()
+        .evaluate(term.clone())
+        .unwrap();
+    let mut machine = MachineBuilder::new(Env::new(Vec::new()))
+        .deterministic()
+        .build();
+    assert_eq!(Ok(expected.clone()), machine.evaluate(term.clone()));
+    assert_eq!(
+        Ok(expected),
+        machine.evaluate_bytecode(&bytecode::compile(&term))
+    );
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+#[test]
+fn rejects_effects_in_every_entry_point() {
+    let term = lower("1 + read_int()");
+    let error = nondeterministic(4, 14, Primitive::ReadInt);
+    let mut output = Vec::new();
+    let mut machine = MachineBuilder::new(Env::new(&mut output))
+        .deterministic()
+        .build();
+
+    let mut steps = machine.steps(term.clone());
+    assert_eq!(None, steps.next());
+    assert_eq!(Some(&error), steps.error());
+
+    assert_eq!(Err(error.clone()), machine.evaluate_resumable(term.clone()));
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut evaluation = pin!(machine.evaluate_async(term.clone(), CancellationToken::new()));
+    assert_eq!(
+        Poll::Ready(Ok(Err(error))),
+        evaluation.as_mut().poll(&mut cx)
+    );
+
+    // The machine can still evaluate pure terms afterwards.
+    assert_eq!(Ok(Term::Lit(3)), machine.evaluate(lower("1 + 2")));
+}
@@ -0,0 +1,28 @@
+use pijama_ast::Location;
+use pijama_core::{parser::parse, ty::Ty};
+use pijama_driver::{docs::extract, LangError};
+
+#[test]
+fn documented_fns() {
+    let input = include_str!("docs.pj");
+    let docs = extract(&parse(input).unwrap()).unwrap();
+
+    assert_eq!(2, docs.len());
+    assert_eq!("is_even", docs[0].name);
+    assert_eq!(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Bool)), docs[0].ty);
+    assert_eq!("Returns `true` if `n` is even.", docs[0].text);
+    assert_eq!(Location::new(34, 111), docs[0].loc);
+    assert_eq!("id", docs[1].name);
+    assert!(matches!(&docs[1].ty, Ty::Arrow(t1, t2) if t1 == t2));
+    assert_eq!(
+        "Returns its argument.\n\nWorks with any type.",
+        docs[1].text
+    );
+}
+
+#[test]
+fn ill_typed_fn() {
+    let input = "## Negates `b`.\nfn negate(b: Bool) do b + 1 end";
+    let err = extract(&parse(input).unwrap()).unwrap_err();
+    assert!(matches!(err, LangError::Ty(_)));
+}
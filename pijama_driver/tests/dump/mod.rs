@@ -0,0 +1,62 @@
+use pijama_ast::Located;
+use pijama_core::{
+    mir::{dump, optimize, Term},
+    parser::parse,
+    ty::ty_check_terms,
+};
+
+fn lower(input: &str) -> Located<Term<'_>> {
+    Term::from_ast(parse(input).unwrap()).unwrap()
+}
+
+#[test]
+fn program() {
+    let mir = lower(include_str!("program.pj"));
+    let table = ty_check_terms(&mir).unwrap();
+    let expected = "\
+let add : Unit @0..31
+  abs (x : Int) : Int -> Int -> Int @0..31
+    abs (y : ?X0) : Int -> Int @0..31
+      binary + : Int @21..26
+        var x : Int @21..22
+        var y : Int @25..26
+  if : Unit @31..85
+    binary > : Bool @34..47
+      app : Int @34..43
+        app : Int -> Int @34..43
+          var add : Int -> Int -> Int @34..37
+          lit 1 : Int @38..39
+        lit 2 : Int @41..42
+      lit 2 : Int @46..47
+    app : Unit @51..62
+      prim print : Bool -> Unit @51..56
+      lit true : Bool @57..58
+    app : Unit @68..80
+      prim print : Bool -> Unit @68..73
+      lit false : Bool @74..75";
+    assert_eq!(expected, dump(&mir, &table));
+}
+
+#[test]
+fn optimized() {
+    let mir = optimize(lower(include_str!("program.pj")));
+    let table = ty_check_terms(&mir).unwrap();
+    assert_eq!(
+        "\
+app : Unit @51..62
+  prim print : Bool -> Unit @51..56
+  lit true : Bool @57..58",
+        dump(&mir, &table)
+    );
+}
+
+#[test]
+fn terms_without_types() {
+    let mir = lower("x = 1\nx");
+    let other = lower("x = 1\nx");
+    let table = ty_check_terms(&other).unwrap();
+    assert_eq!(
+        "let x @0..5\n  lit 1 @4..5\n  var x @6..7",
+        dump(&mir, &table)
+    );
+}
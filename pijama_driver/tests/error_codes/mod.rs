@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+
+use pijama_core::machine::{
+    arithmetic::CheckedArithmetic, env::Env, Capabilities, Capability, MachineBuilder,
+};
+use pijama_driver::{
+    check, emit_rust,
+    error_codes::{explain, ERROR_CODES},
+    run_with_machine, LangResult,
+};
+
+/// Returns the examples of `explanation` with whether they should fail to compile and whether
+/// they should fail to run.
+fn examples(explanation: &str) -> Vec<(String, bool, bool)> {
+    let mut examples = Vec::new();
+    let mut lines = explanation.lines();
+    while let Some(line) = lines.next() {
+        if let Some(info) = line.strip_prefix("```pijama") {
+            let code: Vec<_> = lines.by_ref().take_while(|line| *line != "```").collect();
+            examples.push((
+                code.join("\n"),
+                info == ",compile_fail",
+                info == ",run_fail",
+            ));
+        }
+    }
+    examples
+}
+
+/// Checks `input` and emits it as Rust, so code generation errors are also reported.
+///
+/// The examples of `E0018` use threads, the ones of `E0022` use lists and the ones of `E0025` use
+/// strings, which cannot be emitted as Rust, so they are only checked.
+fn compile(code: &str, input: &str) -> LangResult<()> {
+    check(input)?;
+    if !["E0018", "E0022", "E0025"].contains(&code) {
+        emit_rust(input, "run")?;
+    }
+    Ok(())
+}
+
+/// An output that cannot be written.
+struct Closed;
+
+impl Write for Closed {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `input` with `forty-two` as its only line of input, checked arithmetic, a limited
+/// number of steps, a depth that fits in the stack of the test threads and without the `print`
+/// capability.
+///
+/// The erroneous examples of the other codes fail before printing anything. The examples of
+/// `E0020` are run by a deterministic machine, which would reject the examples of the other codes
+/// that print, and the ones of `E0024` print to an output that cannot be written.
+fn run(code: &str, input: &str) -> LangResult<()> {
+    if code == "E0024" {
+        return run_with_machine(input, MachineBuilder::new(Env::new(Closed)).build());
+    }
+    let env = Env::new(Vec::new()).with_input(&b"forty-two\n"[..]);
+    let mut builder = MachineBuilder::new(env)
+        .with_arithmetic(CheckedArithmetic)
+        .with_fuel(100_000)
+        .with_max_depth(100)
+        .with_capabilities(Capabilities::all().deny(Capability::Print));
+    if code == "E0020" {
+        builder = builder.deterministic();
+    }
+    run_with_machine(input, builder.build())
+}
+
+#[test]
+fn codes_are_sorted() {
+    let codes: Vec<_> = ERROR_CODES.iter().map(|(code, _)| *code).collect();
+    let mut sorted = codes.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(sorted, codes);
+}
+
+#[test]
+fn explain_unknown_code() {
+    assert_eq!(None, explain("E9999"));
+    assert_eq!(None, explain("e0001"));
+}
+
+#[test]
+fn examples_have_the_right_code() {
+    for (code, explanation) in ERROR_CODES {
+        assert_eq!(Some(*explanation), explain(code));
+        let examples = examples(explanation);
+        assert!(
+            examples
+                .iter()
+                .any(|(_, fails, run_fails)| *fails || *run_fails),
+            "{} has no erroneous example",
+            code
+        );
+        for (input, fails, run_fails) in examples {
+            match compile(code, &input) {
+                Err(err) if fails => assert_eq!(*code, err.code(), "{}", input),
+                Ok(_) if !fails => (),
+                result => panic!("unexpected result for {}: {:?}\n{}", code, result, input),
+            }
+            if run_fails {
+                match run(code, &input) {
+                    Err(err) => assert_eq!(*code, err.code(), "{}", input),
+                    Ok(()) => panic!("{} did not fail to run\n{}", code, input),
+                }
+            }
+        }
+    }
+}
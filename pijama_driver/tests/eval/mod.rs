@@ -1,13 +1,26 @@
-use std::{include_str, time::Duration};
+use std::{fmt::Debug, include_str, time::Duration};
 
 use pijama_ast::Literal;
-use pijama_core::{lir::Term, machine::env::Env};
-use pijama_driver::{run_with_machine, LangError, LangResult};
+use pijama_ast::Location;
+use pijama_core::machine::{
+    arithmetic::{Arithmetic, CheckedArithmetic, SaturatingArithmetic, WrappingArithmetic},
+    RuntimeError,
+};
+use pijama_core::machine::{env::Env, MachineBuilder};
+use pijama_driver::{run_with_machine, Call, LangError, LangResult};
 
 use crate::{machine_builder, panic_after, run};
 
+/// Returns the runtime error of `result` and the calls of its stack trace.
+fn traced<T: Debug>(result: LangResult<T>) -> (LangError, Vec<Call>) {
+    match result {
+        Err(LangError::Traced { error, calls }) => (*error, calls),
+        result => panic!("expected an error with a stack trace, found {:?}", result),
+    }
+}
+
 #[test]
-fn arithmetic() -> LangResult<'static, ()> {
+fn arithmetic() -> LangResult<()> {
     let input = include_str!("arithmetic.pj");
     let output = run(input)?;
     assert_eq!("121\n", output);
@@ -15,7 +28,7 @@ fn arithmetic() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn logic() -> LangResult<'static, ()> {
+fn logic() -> LangResult<()> {
     let input = include_str!("logic.pj");
     let output = run(input)?;
     assert_eq!("0\n", output);
@@ -23,7 +36,7 @@ fn logic() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn factorial() -> LangResult<'static, ()> {
+fn factorial() -> LangResult<()> {
     let input = include_str!("factorial.pj");
     let output = run(input)?;
     assert_eq!("3628800\n", output);
@@ -31,7 +44,7 @@ fn factorial() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn factorial_tail() -> LangResult<'static, ()> {
+fn factorial_tail() -> LangResult<()> {
     let input = include_str!("factorial_tail.pj");
     let output = run(input)?;
     assert_eq!("3628800\n", output);
@@ -39,7 +52,7 @@ fn factorial_tail() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn fancy_max() -> LangResult<'static, ()> {
+fn fancy_max() -> LangResult<()> {
     let input = include_str!("fancy_max.pj");
     let output = run(input)?;
     assert_eq!("10\n", output);
@@ -47,7 +60,7 @@ fn fancy_max() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn fibonacci() -> LangResult<'static, ()> {
+fn fibonacci() -> LangResult<()> {
     let input = include_str!("fibonacci.pj");
     let output = run(input)?;
     assert_eq!("21\n", output);
@@ -55,7 +68,7 @@ fn fibonacci() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn fibonacci_tail() -> LangResult<'static, ()> {
+fn fibonacci_tail() -> LangResult<()> {
     let input = include_str!("fibonacci_tail.pj");
     let output = run(input)?;
     assert_eq!("21\n", output);
@@ -63,7 +76,7 @@ fn fibonacci_tail() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn gcd() -> LangResult<'static, ()> {
+fn gcd() -> LangResult<()> {
     let input = include_str!("gcd.pj");
     let output = run(input)?;
     assert_eq!("1\n", output);
@@ -71,7 +84,32 @@ fn gcd() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn ackermann() -> LangResult<'static, ()> {
+fn mutual_recursion() -> LangResult<()> {
+    let input = include_str!("mutual_recursion.pj");
+    let output = run(input)?;
+    assert_eq!("111\n", output);
+    Ok(())
+}
+
+#[test]
+fn tail_calls() -> LangResult<()> {
+    // Tail calls run in constant stack, so this does not overflow the stack of the test thread.
+    let input = include_str!("tail_calls.pj");
+    let output = run(input)?;
+    assert_eq!("20000\n", output);
+    Ok(())
+}
+
+#[test]
+fn mutual_tail_calls() -> LangResult<()> {
+    let input = include_str!("mutual_tail_calls.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn ackermann() -> LangResult<()> {
     let input = include_str!("ackermann.pj");
     let output = run(input)?;
     assert_eq!("5\n", output);
@@ -79,7 +117,7 @@ fn ackermann() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn calling() -> LangResult<'static, ()> {
+fn calling() -> LangResult<()> {
     let input = include_str!("calling.pj");
     let output = run(input)?;
     assert_eq!("1\n", output);
@@ -87,7 +125,7 @@ fn calling() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn complex_calling() -> LangResult<'static, ()> {
+fn complex_calling() -> LangResult<()> {
     let input = include_str!("complex_calling.pj");
     let output = run(input)?;
     assert_eq!("1\n", output);
@@ -95,7 +133,15 @@ fn complex_calling() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn step() -> LangResult<'static, ()> {
+fn shared_argument() -> LangResult<()> {
+    let input = include_str!("shared_argument.pj");
+    let output = run(input)?;
+    assert_eq!("81\n", output);
+    Ok(())
+}
+
+#[test]
+fn step() -> LangResult<()> {
     let input = include_str!("step.pj");
     let output = run(input)?;
     assert_eq!("1\n", output);
@@ -103,7 +149,23 @@ fn step() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn bit_and() -> LangResult<'static, ()> {
+fn bit_not() -> LangResult<()> {
+    let input = include_str!("bit_not.pj");
+    let output = run(input)?;
+    assert_eq!("-65\n", output);
+    Ok(())
+}
+
+#[test]
+fn logic_not() -> LangResult<()> {
+    let input = include_str!("logic_not.pj");
+    let output = run(input)?;
+    assert_eq!("0\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn bit_and() -> LangResult<()> {
     let input = include_str!("bit_and.pj");
     let output = run(input)?;
     assert_eq!("64\n", output);
@@ -111,7 +173,7 @@ fn bit_and() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn bit_or() -> LangResult<'static, ()> {
+fn bit_or() -> LangResult<()> {
     let input = include_str!("bit_or.pj");
     let output = run(input)?;
     assert_eq!("192\n", output);
@@ -119,7 +181,7 @@ fn bit_or() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn bit_xor() -> LangResult<'static, ()> {
+fn bit_xor() -> LangResult<()> {
     let input = include_str!("bit_xor.pj");
     let output = run(input)?;
     assert_eq!("128\n", output);
@@ -127,7 +189,7 @@ fn bit_xor() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn bit_shift_l() -> LangResult<'static, ()> {
+fn bit_shift_l() -> LangResult<()> {
     let input = include_str!("bit_shift_l.pj");
     let output = run(input)?;
     assert_eq!("128\n", output);
@@ -135,7 +197,7 @@ fn bit_shift_l() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn bit_shift_r() -> LangResult<'static, ()> {
+fn bit_shift_r() -> LangResult<()> {
     let input = include_str!("bit_shift_r.pj");
     let output = run(input)?;
     assert_eq!("32\n", output);
@@ -143,7 +205,7 @@ fn bit_shift_r() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn or_short_circuit() -> LangResult<'static, ()> {
+fn or_short_circuit() -> LangResult<()> {
     panic_after(Duration::from_secs(1), || {
         let input = include_str!("or_short_circuit.pj");
         let output = run(input)?;
@@ -153,7 +215,7 @@ fn or_short_circuit() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn and_short_circuit() -> LangResult<'static, ()> {
+fn and_short_circuit() -> LangResult<()> {
     panic_after(Duration::from_secs(1), || {
         let input = include_str!("and_short_circuit.pj");
         let output = run(input)?;
@@ -163,7 +225,7 @@ fn and_short_circuit() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn print_simple() -> LangResult<'static, ()> {
+fn print_simple() -> LangResult<()> {
     let input = include_str!("print_simple.pj");
     let output = run(input)?;
     assert_eq!("10\n", output);
@@ -171,7 +233,7 @@ fn print_simple() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn print_simple_fn() -> LangResult<'static, ()> {
+fn print_simple_fn() -> LangResult<()> {
     let input = include_str!("print_simple_fn.pj");
     let output = run(input)?;
     assert_eq!("(λ. _0)\n", output);
@@ -179,7 +241,7 @@ fn print_simple_fn() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn print_complex_fn() -> LangResult<'static, ()> {
+fn print_complex_fn() -> LangResult<()> {
     let input = include_str!("print_complex_fn.pj");
     let output = run(input)?;
     assert_eq!("1\n", output);
@@ -187,7 +249,7 @@ fn print_complex_fn() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn print_print() -> LangResult<'static, ()> {
+fn print_print() -> LangResult<()> {
     let input = include_str!("print_print.pj");
     let output = run(input)?;
     assert_eq!("10\n0\n", output);
@@ -195,14 +257,34 @@ fn print_print() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn print_redefine() {
+fn print_to_boxed_writer() -> LangResult<()> {
+    let input = include_str!("print_print.pj");
+    let mut output = Vec::default();
+    let writer: Box<dyn std::io::Write> = Box::new(&mut output);
+    let machine = MachineBuilder::new(Env::new(writer)).build();
+    run_with_machine(input, machine)?;
+    assert_eq!("10\n0\n", String::from_utf8(output).unwrap());
+    Ok(())
+}
+
+#[test]
+fn print_redefine() -> LangResult<()> {
     let input = include_str!("print_redefine.pj");
-    let err = run(input).unwrap_err();
-    assert!(matches!(err, LangError::Parse(_)))
+    let output = run(input)?;
+    assert_eq!("", output);
+    Ok(())
+}
+
+#[test]
+fn shadow_primitives() -> LangResult<()> {
+    let input = include_str!("shadow_primitives.pj");
+    let output = run(input)?;
+    assert_eq!("-6\n3\n3\n", output);
+    Ok(())
 }
 
 #[test]
-fn number_bases_cmp() -> LangResult<'static, ()> {
+fn number_bases_cmp() -> LangResult<()> {
     let input = include_str!("number_bases_cmp.pj");
     let output = run(input)?;
     assert_eq!("1\n", output);
@@ -210,7 +292,7 @@ fn number_bases_cmp() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn number_bases_arithmetic() -> LangResult<'static, ()> {
+fn number_bases_arithmetic() -> LangResult<()> {
     let input = include_str!("number_bases_arithmetic.pj");
     let output = run(input)?;
     assert_eq!("2271532\n", output);
@@ -218,15 +300,719 @@ fn number_bases_arithmetic() -> LangResult<'static, ()> {
 }
 
 #[test]
-#[should_panic]
-fn add_overflow_panics() {
-    let input = include_str!("add_overflow_panics.pj");
-    run(input).ok();
+fn out_of_fuel() {
+    fn run_with_fuel(input: &str, fuel: u64) -> (LangResult<()>, String) {
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output).with_fuel(fuel).build();
+        let result = run_with_machine(input, machine);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    let input = include_str!("out_of_fuel.pj");
+    let (result, _) = run_with_fuel(input, 1000);
+    let (error, calls) = traced(result);
+    assert_eq!(
+        LangError::Runtime(RuntimeError::OutOfFuel(Location::new(53, 61))),
+        error
+    );
+    // The recursive calls are tail calls, so they replace the first one.
+    assert_eq!(
+        [Call {
+            name: Some("count".to_owned()),
+            loc: Location::new(53, 61)
+        }],
+        calls[..]
+    );
+
+    // The step that runs out of fuel does not print.
+    let input = include_str!("print_print.pj");
+    let steps = (0..).find(|fuel| run_with_fuel(input, *fuel).0.is_ok());
+    let steps = steps.unwrap();
+    assert_eq!(run(input).unwrap(), run_with_fuel(input, steps).1);
+    let (result, output) = run_with_fuel(input, steps - 1);
+    assert!(
+        matches!(result, Err(LangError::Runtime(RuntimeError::OutOfFuel(_)))),
+        "{:?}",
+        result
+    );
+    assert_eq!(
+        run(input).unwrap().lines().count() - 1,
+        output.lines().count()
+    );
+}
+
+#[test]
+fn add_overflow_fails() {
+    let input = include_str!("add_overflow_fails.pj");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::Overflow(Location::new(
+            0, 23
+        )))),
+        run(input)
+    );
+}
+
+#[test]
+fn overflow_semantics() {
+    fn run_with<A: Arithmetic>(arithmetic: A) -> (LangResult<()>, String) {
+        let input = include_str!("overflow_semantics.pj");
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output)
+            .with_arithmetic(arithmetic)
+            .build();
+        let result = run_with_machine(input, machine);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    assert_eq!(
+        (
+            Ok(()),
+            "-9223372036854775808\n9223372036854775807\n-2\n64\n".to_owned()
+        ),
+        run_with(WrappingArithmetic)
+    );
+    assert_eq!(
+        (
+            Ok(()),
+            "9223372036854775807\n-9223372036854775808\n9223372036854775807\n9223372036854775807\n"
+                .to_owned()
+        ),
+        run_with(SaturatingArithmetic)
+    );
+    assert_eq!(
+        (
+            Err(LangError::Runtime(RuntimeError::Overflow(Location::new(
+                30, 35
+            )))),
+            String::new()
+        ),
+        run_with(CheckedArithmetic)
+    );
+}
+
+#[test]
+fn math_primitives() -> LangResult<()> {
+    let input = include_str!("math_primitives.pj");
+    let output = run(input)?;
+    assert_eq!("10\n-99\n-6\n6\n81\n0\n5\n", output);
+    Ok(())
+}
+
+#[test]
+fn math_overflow() {
+    fn run_with<A: Arithmetic>(arithmetic: A) -> (LangResult<()>, String) {
+        let input = include_str!("math_overflow.pj");
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output)
+            .with_arithmetic(arithmetic)
+            .build();
+        let result = run_with_machine(input, machine);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    assert_eq!(
+        (
+            Ok(()),
+            "-6289078614652622815\n-9223372036854775808\n".to_owned()
+        ),
+        run_with(WrappingArithmetic)
+    );
+    assert_eq!(
+        (
+            Ok(()),
+            "9223372036854775807\n9223372036854775807\n".to_owned()
+        ),
+        run_with(SaturatingArithmetic)
+    );
+    assert_eq!(
+        (
+            Err(LangError::Runtime(RuntimeError::Overflow(Location::new(
+                6, 16
+            )))),
+            String::new()
+        ),
+        run_with(CheckedArithmetic)
+    );
+}
+
+#[test]
+fn zero_to_negative_power() {
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::DivisionByZero(
+            Location::new(6, 16)
+        ))),
+        run("print(pow(0, -1))")
+    );
+}
+
+#[test]
+fn random_with_seed() {
+    fn run_with_seed(seed: u64) -> String {
+        let input = include_str!("random.pj");
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output).with_seed(seed).build();
+        run_with_machine(input, machine).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    let output = run_with_seed(42);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(22, lines.len());
+    for roll in &lines[..20] {
+        assert!((1..=6).contains(&roll.parse::<i64>().unwrap()), "{}", roll);
+    }
+    assert_eq!(["1", "3"], lines[20..]);
+    assert_eq!(output, run_with_seed(42));
+    assert_ne!(output, run_with_seed(43));
 }
 
 #[test]
-#[should_panic]
-fn neg_overflow_panics() {
-    let input = include_str!("neg_overflow_panics.pj");
-    run(input).ok();
+fn threads() -> LangResult<()> {
+    let input = include_str!("threads.pj");
+    let output = run(input)?;
+    assert_eq!("5050\n42\n100\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn let_in() -> LangResult<()> {
+    let input = include_str!("let_in.pj");
+    let output = run(input)?;
+    assert_eq!("25\n15\n2\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn lazy() -> LangResult<()> {
+    let input = include_str!("lazy.pj");
+    let output = run(input)?;
+    assert_eq!("100\nbefore forcing\ncomputing\n84\n", output);
+    Ok(())
+}
+
+#[test]
+fn deadlock() {
+    let input = include_str!("deadlock.pj");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::Deadlock(Location::new(
+            62, 69
+        )))),
+        run(input)
+    );
+}
+
+#[test]
+fn recv_without_threads_deadlocks() {
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::Deadlock(Location::new(
+            20, 27
+        )))),
+        run("c = channel()\nprint(recv(c))")
+    );
+}
+
+#[test]
+fn stack_overflow() {
+    let input = include_str!("stack_overflow.pj");
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output).with_max_depth(100).build();
+    let (error, calls) = traced(run_with_machine(input, machine));
+    assert_eq!(
+        LangError::Runtime(RuntimeError::StackOverflow(Location::new(51, 61))),
+        error
+    );
+    // The recursive calls are nested inside the call at the top level.
+    let (first, recursive) = calls.split_last().unwrap();
+    assert_eq!(Location::new(92, 103), first.loc);
+    assert!(recursive.len() > 1);
+    for call in recursive {
+        assert_eq!(Location::new(51, 61), call.loc);
+    }
+    assert!(calls.iter().all(|call| call.name.as_deref() == Some("sum")));
+    assert_eq!("55\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn stack_trace() {
+    let input = include_str!("stack_trace.pj");
+    let mut output = Vec::default();
+    let (error, calls) = traced(run_with_machine(
+        input,
+        machine_builder(&mut output).build(),
+    ));
+    assert_eq!(
+        LangError::Runtime(RuntimeError::AssertionFailed(Location::new(29, 42))),
+        error
+    );
+    // The calls start at the ones in the last line.
+    let calls: Vec<_> = calls
+        .iter()
+        .map(|call| (call.name.as_deref(), call.loc.start))
+        .collect();
+    assert_eq!(
+        [
+            (Some("check"), input.rfind("check(x - 1)").unwrap()),
+            (Some("f"), input.find("f(x)").unwrap()),
+            (Some("apply"), input.rfind("apply(").unwrap()),
+        ],
+        calls[..]
+    );
+    assert_eq!("2\n", String::from_utf8(output).unwrap());
+
+    // Errors outside of any call have no stack trace.
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::AssertionFailed(
+            Location::new(0, 13)
+        ))),
+        run("assert(false)")
+    );
+}
+
+#[test]
+fn deep_recursion() -> LangResult<()> {
+    let input = include_str!("deep_recursion.pj");
+    let output = run(input)?;
+    assert_eq!("125250\n", output);
+    Ok(())
+}
+
+#[test]
+fn deep_recursion_never_crashes() {
+    // Deeper calls either finish or stop with a located error, even in unoptimized builds and on
+    // the test threads, whose stack is smaller than the one of the main thread.
+    for n in &[1600, 3200] {
+        let input = include_str!("deep_recursion.pj").replace("sum(500)", &format!("sum({})", n));
+        match run(&input) {
+            Ok(output) => assert_eq!(format!("{}\n", n * (n + 1) / 2), output),
+            result => {
+                let (error, _) = traced(result);
+                assert!(
+                    matches!(error, LangError::Runtime(RuntimeError::StackOverflow(_))),
+                    "expected a stack overflow, found {:?}",
+                    error
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn deep_calls() -> LangResult<()> {
+    // The limit counts calls, not the evaluations each call nests. Arguments are passed by name,
+    // so the program binds `n - 1` before the call to keep each call from evaluating the
+    // subtractions of all the calls before it.
+    let output = run(include_str!("deep_calls.pj"))?;
+    assert_eq!("50005000\n", output);
+    Ok(())
+}
+
+#[test]
+fn stack_overflow_default_depth() {
+    // The stack grows as needed, so the default depth is reached without overflowing the stack
+    // of the test thread, which is smaller than the one of the main thread.
+    let input = include_str!("deep_calls.pj").replace("sum(10000)", "sum(100000)");
+    let (error, calls) = traced(run(&input));
+    assert_eq!(
+        LangError::Runtime(RuntimeError::StackOverflow(Location::new(97, 103))),
+        error
+    );
+    // The call to `print` counts towards the limit but is not traced, since it is a primitive.
+    assert_eq!(19_999, calls.len());
+}
+
+#[test]
+fn neg_overflow_fails() {
+    let input = include_str!("neg_overflow_fails.pj");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::Overflow(Location::new(
+            0, 23
+        )))),
+        run(input)
+    );
+}
+
+#[test]
+fn enum_shapes() -> LangResult<()> {
+    let input = include_str!("enum_shapes.pj");
+    let output = run(input)?;
+    assert_eq!("18\n", output);
+    Ok(())
+}
+
+#[test]
+fn enum_rose_tree() -> LangResult<()> {
+    let input = include_str!("enum_rose_tree.pj");
+    let output = run(input)?;
+    assert_eq!("6\n", output);
+    Ok(())
+}
+
+#[test]
+fn enum_list_sum() -> LangResult<()> {
+    let input = include_str!("enum_list_sum.pj");
+    let output = run(input)?;
+    assert_eq!("6\n", output);
+    Ok(())
+}
+
+#[test]
+fn match_literal() -> LangResult<()> {
+    let input = include_str!("match_literal.pj");
+    let output = run(input)?;
+    assert_eq!("5\n", output);
+    Ok(())
+}
+
+#[test]
+fn print_variant() -> LangResult<()> {
+    let input = include_str!("print_variant.pj");
+    let output = run(input)?;
+    assert_eq!("Both(2, 1)\n", output);
+    Ok(())
+}
+
+#[test]
+fn sequence_scope() -> LangResult<()> {
+    let input = include_str!("sequence_scope.pj");
+    let output = run(input)?;
+    assert_eq!("5\n", output);
+    Ok(())
+}
+
+#[test]
+fn pipe() -> LangResult<()> {
+    let input = include_str!("pipe.pj");
+    let output = run(input)?;
+    assert_eq!("16\n7\n2\n", output);
+    Ok(())
+}
+
+#[test]
+fn elif_chain() -> LangResult<()> {
+    let input = include_str!("elif_chain.pj");
+    let output = run(input)?;
+    assert_eq!("90\n", output);
+    Ok(())
+}
+
+#[test]
+fn cond_without_else() -> LangResult<()> {
+    let input = include_str!("cond_without_else.pj");
+    let output = run(input)?;
+    assert_eq!("3\n2\n", output);
+    Ok(())
+}
+
+#[test]
+fn mutable_counter() -> LangResult<()> {
+    let input = include_str!("mutable_counter.pj");
+    let output = run(input)?;
+    assert_eq!("55\n", output);
+    Ok(())
+}
+
+#[test]
+fn mutable_snapshot() -> LangResult<()> {
+    let input = include_str!("mutable_snapshot.pj");
+    let output = run(input)?;
+    assert_eq!("1\n11\n", output);
+    Ok(())
+}
+
+#[test]
+fn string_concat() -> LangResult<()> {
+    let input = include_str!("string_concat.pj");
+    let output = run(input)?;
+    assert_eq!("Hello, world!\n", output);
+    Ok(())
+}
+
+#[test]
+fn string_match() -> LangResult<()> {
+    let input = include_str!("string_match.pj");
+    let output = run(input)?;
+    assert_eq!("0\n", output);
+    Ok(())
+}
+
+#[test]
+fn string_escapes() -> LangResult<()> {
+    let input = include_str!("string_escapes.pj");
+    let output = run(input)?;
+    assert_eq!("say \"hi\"\\\nbye\n", output);
+    Ok(())
+}
+
+#[test]
+fn string_interpolation() -> LangResult<()> {
+    let input = include_str!("string_interpolation.pj");
+    let output = run(input)?;
+    assert_eq!(
+        "Hello, Pijama! You have 4 messages.\n1.5 c 1 {count}\n",
+        output
+    );
+    Ok(())
+}
+
+#[test]
+fn float_arithmetic() -> LangResult<()> {
+    let input = include_str!("float_arithmetic.pj");
+    let output = run(input)?;
+    assert_eq!("2.5\n", output);
+    Ok(())
+}
+
+#[test]
+fn float_conversion() -> LangResult<()> {
+    let input = include_str!("float_conversion.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn char_conversion() -> LangResult<()> {
+    let input = include_str!("char_conversion.pj");
+    let output = run(input)?;
+    assert_eq!("z\n", output);
+    Ok(())
+}
+
+#[test]
+fn char_match() -> LangResult<()> {
+    let input = include_str!("char_match.pj");
+    let output = run(input)?;
+    assert_eq!("71\n", output);
+    Ok(())
+}
+
+#[test]
+fn invalid_char() {
+    let input = include_str!("invalid_char.pj");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::InvalidChar(
+            Location::new(0, 15),
+            -1
+        ))),
+        run(input)
+    );
+}
+
+#[test]
+fn list_ops() -> LangResult<()> {
+    let input = include_str!("list_ops.pj");
+    let output = run(input)?;
+    assert_eq!("[10, 2, 3]\n", output);
+    Ok(())
+}
+
+#[test]
+fn list_comparison() -> LangResult<()> {
+    let input = include_str!("list_comparison.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn empty_head() {
+    let input = include_str!("empty_head.pj");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::IndexOutOfBounds(
+            Location::new(6, 14),
+            0,
+            0
+        ))),
+        run(input)
+    );
+}
+
+#[test]
+fn list_index() {
+    let input = include_str!("list_index.pj");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::IndexOutOfBounds(
+            Location::new(6, 20),
+            2,
+            2
+        ))),
+        run(input)
+    );
+}
+
+#[test]
+fn string_index() {
+    let input = include_str!("string_index.pj");
+    let loc = Location::new(6, 23);
+    // The error points at the call, without the text around it.
+    assert_eq!("char_at(\"abc\", 3)", &input[loc.start..loc.end]);
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::IndexOutOfBounds(
+            loc, 3, 3
+        ))),
+        run(input)
+    );
+}
+
+#[test]
+fn substring_range() {
+    let input = include_str!("substring_range.pj");
+    let loc = Location::new(0, 25);
+    // The error points at the call, without the text around it.
+    assert_eq!("substring(\"pijama\", 4, 2)", &input[loc.start..loc.end]);
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::InvalidRange(loc, 4, 2))),
+        run(input)
+    );
+    // A start out of bounds is reported before the range.
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::IndexOutOfBounds(
+            Location::new(0, 25),
+            7,
+            6
+        ))),
+        run("substring(\"pijama\", 7, 2)")
+    );
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::IndexOutOfBounds(
+            Location::new(0, 25),
+            7,
+            6
+        ))),
+        run("substring(\"pijama\", 2, 7)")
+    );
+    // An empty range is valid.
+    assert_eq!(Ok("\n".to_owned()), run("print(substring(\"abc\", 1, 1))"));
+}
+
+#[test]
+fn string_primitives() -> LangResult<()> {
+    let input = include_str!("string_primitives.pj");
+    let output = run(input)?;
+    assert_eq!("13\nmundo\n¡\namajip\n41\n17\n", output);
+    Ok(())
+}
+
+#[test]
+fn parse_int_invalid() {
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::InvalidInt(
+            Location::new(6, 22),
+            "4 2".to_owned()
+        ))),
+        run("print(parse_int(\"4 2\"))")
+    );
+}
+
+/// Runs `input` reading the lines of `stdin`.
+fn run_with_input(input: &str, stdin: &'static str) -> (LangResult<()>, String) {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_input(stdin.as_bytes())
+        .build();
+    let result = run_with_machine(input, machine);
+    (result, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn read_input() -> LangResult<()> {
+    let input = include_str!("read_input.pj");
+    let (result, output) = run_with_input(input, "Pijama\n 41 \r\n");
+    result?;
+    assert_eq!("Hello, Pijama! 42\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn read_int_invalid() {
+    let input = include_str!("read_int_invalid.pj");
+    let (result, output) = run_with_input(input, "forty-one\n");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::InvalidInt(
+            Location::new(20, 30),
+            "forty-one".to_owned()
+        ))),
+        result
+    );
+    assert_eq!("before\n", output);
+}
+
+#[test]
+fn read_int_past_the_end() {
+    let input = include_str!("read_int_invalid.pj");
+    let (result, _) = run_with_input(input, "");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::InvalidInt(
+            Location::new(20, 30),
+            String::new()
+        ))),
+        result
+    );
+}
+
+#[test]
+fn assertions() -> LangResult<()> {
+    let input = include_str!("assertions.pj");
+    let output = run(input)?;
+    assert_eq!("done\n", output);
+    Ok(())
+}
+
+#[test]
+fn assertion_fails() {
+    let input = include_str!("assertion_fails.pj");
+    let mut output = Vec::default();
+    let result = run_with_machine(input, machine_builder(&mut output).build());
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::AssertionFailed(
+            Location::new(29, 47)
+        ))),
+        result
+    );
+    assert_eq!("3\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn division_by_zero() {
+    let input = include_str!("division_by_zero.pj");
+    let mut output = Vec::default();
+    let result = run_with_machine(input, machine_builder(&mut output).build());
+    let call = Call {
+        name: Some("average".to_owned()),
+        loc: Location::new(94, 108),
+    };
+    assert_eq!(
+        Err(LangError::Traced {
+            error: Box::new(LangError::Runtime(RuntimeError::DivisionByZero(
+                Location::new(47, 60)
+            ))),
+            calls: vec![call],
+        }),
+        result
+    );
+    assert_eq!("5\n", String::from_utf8(output).unwrap());
+
+    let result = run("print(7 % 0)");
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::DivisionByZero(
+            Location::new(6, 11)
+        ))),
+        result
+    );
+}
+
+#[test]
+fn block() -> LangResult<()> {
+    let input = include_str!("block.pj");
+    let output = run(input)?;
+    assert_eq!("21\n10\n7\n10\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn operator() -> LangResult<()> {
+    let input = include_str!("operator.pj");
+    let output = run(input)?;
+    assert_eq!("10\n24\n9\n9\n7\n42\n6\n", output);
+    Ok(())
 }
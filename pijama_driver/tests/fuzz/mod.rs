@@ -0,0 +1,79 @@
+use arbitrary::Unstructured;
+
+use pijama_ast::{
+    fuzz::{program, well_typed},
+    Node,
+};
+use pijama_core::ty::Ty;
+use pijama_driver::{pipeline::Options, Compilation, LangError};
+
+use crate::machine_builder;
+
+/// Returns `len` pseudo-random bytes for each one of `count` seeds.
+fn inputs(count: u64, len: usize) -> impl Iterator<Item = Vec<u8>> {
+    (1..=count).map(move |seed| {
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn arbitrary_programs_compile_or_fail() {
+    for bytes in inputs(500, 512) {
+        let mut u = Unstructured::new(&bytes);
+        let block = program(&mut u).unwrap();
+        // Arbitrary programs can loop forever, so they are only compiled.
+        let _ = Compilation::new(block, Options::default()).to_lir();
+    }
+}
+
+#[test]
+fn arbitrary_nodes() {
+    let bytes: Vec<u8> = inputs(1, 256).next().unwrap();
+    let mut u = Unstructured::new(&bytes);
+    let node: Node = u.arbitrary().unwrap();
+    assert_ne!(Node::Import(String::new()), node);
+    let _: pijama_ast::ty::Ty = u.arbitrary().unwrap();
+
+    // Running out of bytes stops the recursion instead of failing.
+    let mut u = Unstructured::new(&[]);
+    assert!(u.arbitrary::<Node>().is_ok());
+    assert!(well_typed(&mut u).is_ok());
+}
+
+#[test]
+fn well_typed_programs_run() {
+    for bytes in inputs(300, 512) {
+        let mut u = Unstructured::new(&bytes);
+        let block = well_typed(&mut u).unwrap();
+        let debug = format!("{:#?}", block.content);
+
+        for opt_level in 0..=2 {
+            let options = Options {
+                opt_level,
+                ..Options::default()
+            };
+            let mut compilation = Compilation::new(block.clone(), options);
+
+            let ty = &compilation
+                .check()
+                .unwrap_or_else(|err| panic!("{:?}\n{}", err, debug))
+                .ty;
+            assert!(*ty == Ty::Int || *ty == Ty::Bool, "{}", ty);
+
+            let mut output = Vec::new();
+            match compilation.evaluate_with_machine(machine_builder(&mut output).build()) {
+                Ok(value) => assert!(value.is_some()),
+                Err(LangError::Runtime(_)) => (),
+                Err(err) => panic!("{:?}\n{}", err, debug),
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::convert::TryFrom;
+
+use pijama_ast::{IntWidth, Located, Location};
+use pijama_core::{
+    codegen::{wasm, CodegenError},
+    lir::Term,
+    machine::{
+        arithmetic::{Arithmetic, CheckedArithmetic, WrappingArithmetic},
+        RuntimeError, Value,
+    },
+    ty::TyError,
+};
+use pijama_driver::{run_with_machine, LangError, LangResult};
+use serde_json::json;
+
+use crate::{machine_builder, run};
+
+fn run_i128(input: &str) -> LangResult<String> {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_int_width(IntWidth::I128)
+        .build();
+    run_with_machine(input, machine)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn i128() -> LangResult<()> {
+    let input = include_str!("i128.pj");
+    assert_eq!(
+        "9223372036854775808\n-9223372036854775809\n265252859812191058636308480000000\n",
+        run_i128(input)?
+    );
+    Ok(())
+}
+
+#[test]
+fn max_overflow() {
+    fn run_with<A: Arithmetic>(arithmetic: A) -> (LangResult<()>, String) {
+        let input = include_str!("max_overflow.pj");
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output)
+            .with_arithmetic(arithmetic)
+            .with_int_width(IntWidth::I128)
+            .build();
+        let result = run_with_machine(input, machine);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    assert_eq!(
+        (
+            Ok(()),
+            "-170141183460469231731687303715884105728\n".to_owned()
+        ),
+        run_with(WrappingArithmetic)
+    );
+    assert_eq!(
+        (
+            Err(LangError::Runtime(RuntimeError::Overflow(Location::new(
+                50, 55
+            )))),
+            String::new()
+        ),
+        run_with(CheckedArithmetic)
+    );
+}
+
+#[test]
+fn out_of_range() {
+    let input = include_str!("out_of_range.pj");
+    match run(input).unwrap_err() {
+        LangError::Ty(err) => {
+            assert_eq!(
+                TyError::IntOutOfRange {
+                    literal: Located::new(9223372036854775808, Location::new(61, 62)),
+                    width: IntWidth::I64,
+                },
+                err
+            );
+            assert_eq!(61, err.loc().start);
+        }
+        err => panic!("expected a type error, found {:?}", err),
+    }
+    assert_eq!(
+        "9223372036854775807\n-9223372036854775808\n9223372036854775808\n",
+        run_i128(input).unwrap()
+    );
+}
+
+#[test]
+fn parse_int() {
+    let input = "print(parse_int(\"9223372036854775808\"))";
+    assert!(matches!(
+        run(input),
+        Err(LangError::Runtime(RuntimeError::InvalidInt(..)))
+    ));
+    assert_eq!(Ok("9223372036854775808\n".to_owned()), run_i128(input));
+}
+
+#[test]
+fn float_to_int() {
+    let input = "print(float_to_int(1.0e30))";
+    assert_eq!(Ok("9223372036854775807\n".to_owned()), run(input));
+    assert_eq!(
+        Ok("1000000000000000019884624838656\n".to_owned()),
+        run_i128(input)
+    );
+}
+
+#[test]
+fn json() {
+    let n = i128::from(u64::MAX);
+    assert_eq!(Ok(Value::Int(n)), Value::try_from(json!(u64::MAX)));
+    assert_eq!(json!(u64::MAX), serde_json::Value::from(Value::Int(n)));
+    // Integers that do not fit in a JSON integer become floats.
+    assert_eq!(
+        json!(-1.7014118346046923e38),
+        serde_json::Value::from(Value::Int(i128::MIN))
+    );
+}
+
+#[test]
+fn wasm_is_unsupported() {
+    let term = Term::from(i128::from(i64::MAX) + 1);
+    assert!(matches!(
+        wasm::compile(&term),
+        Err(CodegenError::Unsupported(_))
+    ));
+}
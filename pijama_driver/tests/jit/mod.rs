@@ -0,0 +1,273 @@
+use std::io::{self, Write};
+
+use pijama_ast::{IntWidth, Location};
+use pijama_core::{
+    jit,
+    machine::{env::Env, MachineBuilder, RuntimeError},
+};
+use pijama_driver::{
+    pipeline::{compile, Backend, Options},
+    run_with_options,
+    timings::Timings,
+    LangError, LangResult,
+};
+
+use crate::machine_builder;
+
+fn run_with(input: &str, backend: Backend) -> (LangResult<()>, String) {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output).build();
+    let options = Options {
+        backend,
+        ..Options::default()
+    };
+    let result = run_with_options(
+        input,
+        machine,
+        &options,
+        &mut Timings::default(),
+        &mut Vec::new(),
+    );
+    (result, String::from_utf8(output).unwrap())
+}
+
+/// Runs `input` like `run_with`, dropping the stack trace of the error, which is only kept by the
+/// machine.
+fn run_untraced(input: &str, backend: Backend) -> (LangResult<()>, String) {
+    match run_with(input, backend) {
+        (Err(LangError::Traced { error, .. }), output) => (Err(*error), output),
+        result => result,
+    }
+}
+
+fn supports(input: &str) -> bool {
+    jit::supports(&compile(input, &Options::default()).unwrap().lir)
+}
+
+#[test]
+fn same_results() {
+    let inputs = [
+        include_str!("../eval/ackermann.pj"),
+        include_str!("../eval/add_overflow_fails.pj"),
+        include_str!("../eval/and_short_circuit.pj"),
+        include_str!("../eval/arithmetic.pj"),
+        include_str!("../eval/assertion_fails.pj"),
+        include_str!("../eval/assertions.pj"),
+        include_str!("../eval/bit_and.pj"),
+        include_str!("../eval/bit_not.pj"),
+        include_str!("../eval/bit_or.pj"),
+        include_str!("../eval/bit_shift_l.pj"),
+        include_str!("../eval/bit_shift_r.pj"),
+        include_str!("../eval/bit_xor.pj"),
+        include_str!("../eval/calling.pj"),
+        include_str!("../eval/char_conversion.pj"),
+        include_str!("../eval/char_match.pj"),
+        include_str!("../eval/complex_calling.pj"),
+        include_str!("../eval/cond_without_else.pj"),
+        include_str!("../eval/division_by_zero.pj"),
+        include_str!("../eval/elif_chain.pj"),
+        include_str!("../eval/enum_list_sum.pj"),
+        include_str!("../eval/enum_rose_tree.pj"),
+        include_str!("../eval/enum_shapes.pj"),
+        include_str!("../eval/factorial.pj"),
+        include_str!("../eval/factorial_tail.pj"),
+        include_str!("../eval/fancy_max.pj"),
+        include_str!("../eval/fibonacci.pj"),
+        include_str!("../eval/fibonacci_tail.pj"),
+        include_str!("../eval/float_arithmetic.pj"),
+        include_str!("../eval/float_conversion.pj"),
+        include_str!("../eval/gcd.pj"),
+        include_str!("../eval/list_comparison.pj"),
+        include_str!("../eval/list_ops.pj"),
+        include_str!("../eval/logic.pj"),
+        include_str!("../eval/logic_not.pj"),
+        include_str!("../eval/match_literal.pj"),
+        include_str!("../eval/mutable_counter.pj"),
+        include_str!("../eval/mutable_snapshot.pj"),
+        include_str!("../eval/mutual_recursion.pj"),
+        include_str!("../eval/mutual_tail_calls.pj"),
+        include_str!("../eval/neg_overflow_fails.pj"),
+        include_str!("../eval/number_bases_arithmetic.pj"),
+        include_str!("../eval/number_bases_cmp.pj"),
+        include_str!("../eval/or_short_circuit.pj"),
+        include_str!("../eval/overflow_semantics.pj"),
+        include_str!("../eval/pipe.pj"),
+        include_str!("../eval/print_complex_fn.pj"),
+        include_str!("../eval/print_print.pj"),
+        include_str!("../eval/print_redefine.pj"),
+        include_str!("../eval/print_simple.pj"),
+        include_str!("../eval/print_simple_fn.pj"),
+        include_str!("../eval/print_variant.pj"),
+        include_str!("../eval/sequence_scope.pj"),
+        include_str!("../eval/shared_argument.pj"),
+        include_str!("../eval/step.pj"),
+        include_str!("../eval/string_concat.pj"),
+        include_str!("../eval/string_escapes.pj"),
+        include_str!("../eval/string_interpolation.pj"),
+        include_str!("../eval/string_match.pj"),
+        include_str!("../eval/string_primitives.pj"),
+        include_str!("../eval/tail_calls.pj"),
+    ];
+    for input in inputs.iter() {
+        assert_eq!(
+            run_untraced(input, Backend::Machine),
+            run_untraced(input, Backend::Jit),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn supported() {
+    let inputs = [
+        include_str!("../eval/ackermann.pj"),
+        include_str!("../eval/add_overflow_fails.pj"),
+        include_str!("../eval/arithmetic.pj"),
+        include_str!("../eval/assertion_fails.pj"),
+        include_str!("../eval/complex_calling.pj"),
+        include_str!("../eval/division_by_zero.pj"),
+        include_str!("../eval/factorial.pj"),
+        include_str!("../eval/factorial_tail.pj"),
+        include_str!("../eval/fibonacci.pj"),
+        include_str!("../eval/gcd.pj"),
+        include_str!("../eval/logic.pj"),
+        include_str!("../eval/match_literal.pj"),
+        include_str!("../eval/print_complex_fn.pj"),
+        include_str!("../eval/step.pj"),
+        include_str!("../eval/tail_calls.pj"),
+    ];
+    for input in inputs.iter() {
+        assert!(supports(input), "{}", input);
+    }
+}
+
+#[test]
+fn unsupported() {
+    let inputs = [
+        include_str!("../eval/float_arithmetic.pj"),
+        include_str!("../eval/list_ops.pj"),
+        include_str!("../eval/mutual_recursion.pj"),
+        include_str!("../eval/shared_argument.pj"),
+        include_str!("../eval/string_concat.pj"),
+    ];
+    for input in inputs.iter() {
+        assert!(!supports(input), "{}", input);
+    }
+}
+
+#[test]
+fn division_by_zero() {
+    let input = include_str!("../eval/division_by_zero.pj");
+    let (result, output) = run_with(input, Backend::Jit);
+    assert_eq!(
+        Err(LangError::Runtime(RuntimeError::DivisionByZero(
+            Location::new(47, 60)
+        ))),
+        result
+    );
+    assert_eq!("5\n", output);
+}
+
+#[test]
+fn fallback_is_traced() {
+    let input = "fn half(x: Int, y: Float): Int do x / 0 end\nprint(half(1, 1.0))";
+    assert!(!supports(input));
+    let (result, _) = run_with(input, Backend::Jit);
+    assert!(
+        matches!(result, Err(LangError::Traced { .. })),
+        "{:?}",
+        result
+    );
+    assert_eq!(
+        run_with(input, Backend::Machine),
+        run_with(input, Backend::Jit)
+    );
+}
+
+#[test]
+fn unused_argument() {
+    let input = "fn first(x: Int, y: Int): Int do x end\nprint(first(1, 1 / 0))";
+    let (result, output) = run_with(input, Backend::Jit);
+    assert!(supports(input));
+    assert_eq!(Ok(()), result);
+    assert_eq!("1\n", output);
+}
+
+#[test]
+fn lazy_arguments() {
+    let input = "fn fib(n: Int): Int do if n < 2 do n else fib(n - 1) + fib(n - 2) end end
+fn pick(c: Bool, x: Int, y: Int): Int do if c do x else y end end
+fn sum(n: Int, acc: Int): Int do if n == 0 do acc else sum(n - 1, acc + pick(n % 2 == 0, fib(n % 10), 1 / 0)) end end
+print(pick(true, fib(10), fib(1 / 0)))
+print(sum(40, 0))";
+    assert!(supports(input));
+    assert_eq!(
+        run_untraced(input, Backend::Machine),
+        run_untraced(input, Backend::Jit)
+    );
+}
+
+#[test]
+fn wide_integers_fall_back() {
+    let input = "x = 9223372036854775807\nprint(x + 1)";
+    assert!(supports(input));
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_int_width(IntWidth::I128)
+        .build();
+    let options = Options {
+        backend: Backend::Jit,
+        ..Options::default()
+    };
+    let result = run_with_options(
+        input,
+        machine,
+        &options,
+        &mut Timings::default(),
+        &mut Vec::new(),
+    );
+    assert_eq!(Ok(()), result);
+    assert_eq!("9223372036854775808\n", String::from_utf8(output).unwrap());
+}
+
+/// An output that cannot be written.
+struct Closed;
+
+impl Write for Closed {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn output_failed() {
+    let input = "print(1)\nprint(2)";
+    assert!(supports(input));
+    for backend in [Backend::Machine, Backend::Jit] {
+        let machine = MachineBuilder::new(Env::new(Closed)).build();
+        let options = Options {
+            backend,
+            ..Options::default()
+        };
+        let result = run_with_options(
+            input,
+            machine,
+            &options,
+            &mut Timings::default(),
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            Err(LangError::Runtime(RuntimeError::OutputFailed(
+                Location::new(0, 8)
+            ))),
+            result,
+            "{:?}",
+            backend
+        );
+    }
+}
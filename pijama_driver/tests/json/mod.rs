@@ -0,0 +1,87 @@
+use std::convert::TryFrom;
+
+use pijama_ast::Int;
+use pijama_core::{
+    machine::{value::FromJsonError, Value},
+    ty::Ty,
+};
+use pijama_driver::run_with_bindings;
+use serde_json::json;
+
+#[test]
+fn to_json() {
+    assert_eq!(json!(-3), serde_json::Value::from(Value::Int(-3)));
+    assert_eq!(json!(true), serde_json::Value::from(Value::Bool(true)));
+    assert_eq!(json!(null), serde_json::Value::from(Value::Unit));
+    assert_eq!(json!(2.5), serde_json::Value::from(Value::Float(2.5)));
+    assert_eq!(
+        json!("pijama"),
+        serde_json::Value::from(Value::Str("pijama".to_owned()))
+    );
+    assert_eq!(
+        json!([1, 2]),
+        serde_json::Value::from(Value::List(Ty::Int, vec![Value::Int(1), Value::Int(2)]))
+    );
+}
+
+#[test]
+fn from_json() {
+    assert_eq!(
+        Ok(Value::Int(Int::from(i64::MIN))),
+        Value::try_from(json!(i64::MIN))
+    );
+    assert_eq!(Ok(Value::Bool(false)), Value::try_from(json!(false)));
+    assert_eq!(Ok(Value::Unit), Value::try_from(json!(null)));
+    assert_eq!(Ok(Value::Float(2.5)), Value::try_from(json!(2.5)));
+    assert_eq!(
+        Ok(Value::Str("pijama".to_owned())),
+        Value::try_from(json!("pijama"))
+    );
+    assert_eq!(
+        Ok(Value::List(
+            Ty::List(Box::new(Ty::Bool)),
+            vec![Value::List(Ty::Bool, vec![Value::Bool(true)])]
+        )),
+        Value::try_from(json!([[true]]))
+    );
+}
+
+#[test]
+fn no_equivalent() {
+    for json in [json!([]), json!([1, true]), json!({ "x": 1 })] {
+        assert_eq!(Err(FromJsonError(json.clone())), Value::try_from(json));
+    }
+}
+
+#[test]
+fn transform() {
+    let input = include_str!("discount.pj");
+    let data = json!({ "price": 120, "member": true });
+
+    let bindings: Vec<(&str, Value)> = data
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(name, value)| (name.as_str(), Value::try_from(value.clone()).unwrap()))
+        .collect();
+    let result = run_with_bindings(input, &bindings).unwrap().unwrap();
+
+    assert_eq!(json!(108), serde_json::Value::from(result));
+}
+
+#[test]
+fn list_and_string_bindings() {
+    let data = json!({ "name": "pijama", "scores": [3, 4, 5] });
+
+    let bindings: Vec<(&str, Value)> = data
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(name, value)| (name.as_str(), Value::try_from(value.clone()).unwrap()))
+        .collect();
+    let result = run_with_bindings("[len(name), len(scores)]", &bindings)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(json!([6, 3]), serde_json::Value::from(result));
+}
@@ -0,0 +1,92 @@
+use pijama_core::lex::{tokens, Token};
+
+/// Returns the code and kind of each token of `input`.
+fn lex(input: &str) -> Vec<(&str, Token)> {
+    tokens(input)
+        .map(|token| (&input[token.loc.start..token.loc.end], token.content))
+        .collect()
+}
+
+#[test]
+fn program() {
+    let input = include_str!("program.pj");
+    let tokens = lex(input);
+    assert_eq!(
+        vec![
+            ("# Sums the digits.", Token::Comment),
+            (
+                "## Returns the sum of the digits of `n`.",
+                Token::DocComment
+            ),
+            ("fn", Token::Keyword),
+            ("digits", Token::Name),
+            ("(", Token::Punctuation),
+            ("n", Token::Name),
+            (":", Token::Punctuation),
+            ("Int", Token::Type),
+            (")", Token::Punctuation),
+            (":", Token::Punctuation),
+            ("Int", Token::Type),
+            ("do", Token::Keyword),
+        ],
+        tokens[..12]
+    );
+    assert!(tokens.contains(&("<", Token::Operator)));
+    assert!(tokens.contains(&("mut", Token::Keyword)));
+    assert!(tokens.contains(&("print", Token::Primitive)));
+
+    let range = tokens
+        .iter()
+        .position(|token| *token == ("..", Token::Punctuation));
+    let range = range.unwrap();
+    assert_eq!(("0", Token::Number), tokens[range - 1]);
+    assert_eq!(("0x10", Token::Number), tokens[range + 1]);
+
+    // Every character except spaces and line breaks is part of a token.
+    let code: String = tokens.iter().map(|(code, _)| *code).collect();
+    let expected: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    assert_eq!(expected, code.replace(' ', ""));
+}
+
+#[test]
+fn interpolation() {
+    use Token::*;
+    assert_eq!(
+        vec![
+            ("\"total: ", String),
+            ("{", Punctuation),
+            ("total", Name),
+            ("*", Operator),
+            ("1.5e2", Number),
+            ("}", Punctuation),
+            (", ", String),
+            ("{", Punctuation),
+            ("'c'", Char),
+            ("}", Punctuation),
+            (" /* not a comment */\"", String),
+        ],
+        lex(r#""total: {total * 1.5e2}, {'c'} /* not a comment */""#)
+    );
+    assert_eq!(vec![("\"a \\{b\"", String)], lex(r#""a \{b""#));
+}
+
+#[test]
+fn invalid_code() {
+    use Token::*;
+    assert_eq!(
+        vec![
+            ("x", Name),
+            ("=", Operator),
+            ("\"unclosed", String),
+            ("y", Name),
+            ("$", Unknown),
+            ("if", Keyword),
+            ("/* unclosed", Comment),
+        ],
+        lex("x = \"unclosed\ny $ if /* unclosed")
+    );
+    assert_eq!(
+        vec![("\"", String), ("{", Punctuation), ("true", Constant)],
+        lex("\"{true")
+    );
+}
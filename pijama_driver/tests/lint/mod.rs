@@ -0,0 +1,120 @@
+use pijama_ast::Location;
+use pijama_core::{lint::Warning, ty::Ty};
+use pijama_driver::{check_with_warnings, run_with_warnings};
+
+#[test]
+fn no_effect() {
+    let input = include_str!("no_effect.pj");
+    let (ty, warnings) = check_with_warnings(input);
+    assert_eq!(Ok(Ty::Unit), ty);
+    assert_eq!(
+        vec![
+            Warning::NoEffect(Location::new(28, 29)),
+            Warning::NoEffect(Location::new(65, 69)),
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn effects_are_not_linted() {
+    let (ty, warnings) = check_with_warnings("print(1)\nprint(2)\n3");
+    assert_eq!(Ok(Ty::Int), ty);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn ill_typed_programs_are_not_linted() {
+    let (result, warnings) = run_with_warnings("unit\ntrue + 1", false);
+    assert!(result.is_err());
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn run_reports_warnings() {
+    let (result, warnings) = run_with_warnings("unit\nprint(2)", false);
+    assert_eq!(Ok(()), result);
+    assert_eq!(vec![Warning::NoEffect(Location::new(0, 1))], warnings);
+}
+
+#[test]
+fn constant_condition() {
+    let input = include_str!("constant_condition.pj");
+    let (ty, warnings) = check_with_warnings(input);
+    assert_eq!(Ok(Ty::Unit), ty);
+    assert_eq!(
+        vec![
+            Warning::ConstantCondition(Location::new(31, 37), true),
+            Warning::ConstantCondition(Location::new(89, 91), false),
+            Warning::UnreachableBranch(Location::new(110, 111)),
+            Warning::UnreachableBranch(Location::new(168, 169)),
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn unreachable_arm() {
+    let input = include_str!("unreachable_arm.pj");
+    let (ty, warnings) = check_with_warnings(input);
+    assert_eq!(Ok(Ty::Int), ty);
+    assert_eq!(
+        vec![
+            Warning::UnreachableArm(Location::new(101, 104)),
+            Warning::UnreachableArm(Location::new(136, 137)),
+            Warning::UnreachableArm(Location::new(222, 223)),
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn unused() {
+    let input = include_str!("unused.pj");
+    let (ty, warnings) = check_with_warnings(input);
+    assert_eq!(Ok(Ty::Int), ty);
+    assert_eq!(
+        vec![
+            Warning::UnusedParam(Location::new(17, 18)),
+            Warning::UnusedFunction(Location::new(47, 59)),
+            Warning::UnusedBinding(Location::new(246, 252)),
+            Warning::UnusedBinding(Location::new(302, 309)),
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn prelude_is_not_linted() {
+    let (ty, warnings) = check_with_warnings("is_some(Some(1))");
+    assert_eq!(Ok(Ty::Bool), ty);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn constant_folding() {
+    let input = include_str!("constant_folding.pj");
+    let (ty, warnings) = check_with_warnings(input);
+    assert_eq!(Ok(Ty::Unit), ty);
+    assert_eq!(
+        vec![
+            Warning::ConstantCondition(Location::new(33, 42), true),
+            Warning::ConstantCondition(Location::new(78, 93), false),
+            Warning::UnreachableBranch(Location::new(105, 113)),
+            Warning::ConstantCondition(Location::new(123, 138), true),
+            Warning::UnreachableBranch(Location::new(176, 184)),
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn self_assignment() {
+    let input = include_str!("self_assignment.pj");
+    let (ty, warnings) = check_with_warnings(input);
+    assert_eq!(Ok(Ty::Int), ty);
+    assert_eq!(
+        vec![Warning::SelfAssignment(Location::new(39, 52))],
+        warnings
+    );
+}
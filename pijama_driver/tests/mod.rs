@@ -6,10 +6,49 @@ use pijama_core::machine::{arithmetic::CheckedArithmetic, env::Env, MachineBuild
 use pijama_driver::{run_with_machine, LangResult};
 
 mod ast;
+mod async_eval;
+mod bindings;
+mod bytecode;
+mod cache;
+mod capabilities;
+mod closure;
+mod codegen;
+mod compare;
+mod compilation;
+mod config;
+mod cps;
+mod debug;
+mod determinism;
+mod docs;
+mod dump;
+mod error_codes;
 mod eval;
+mod fuzz;
+mod int_width;
+#[cfg(feature = "jit")]
+mod jit;
+mod json;
+mod lex;
+mod lint;
+mod modules;
+mod native;
+mod node_id;
+mod optimize;
 mod parse;
+mod pipeline;
+mod prelude;
+mod pretty;
+mod program;
+mod query;
+mod serde;
+mod snapshot;
+mod stats;
+mod suggest;
+mod timings;
+mod trace;
 mod type_check;
 mod util;
+mod visit;
 
 fn machine_builder(output: &mut Vec<u8>) -> MachineBuilder<&mut Vec<u8>, CheckedArithmetic> {
     MachineBuilder::default()
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use pijama_ast::FileId;
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::{check, emit_js, modules::LoadError, run_with_machine, LangError, Modules};
+
+use crate::machine_builder;
+
+fn path(file: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/modules")
+        .join(file)
+}
+
+#[test]
+fn imports() {
+    let modules = Modules::load(path("main.pj")).unwrap();
+    let mut output = Vec::default();
+    run_with_machine(&modules, machine_builder(&mut output).build()).unwrap();
+    assert_eq!("23\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn top_level_expressions_are_not_emitted() {
+    let modules = Modules::load(path("main.pj")).unwrap();
+    let output = emit_js(&modules).unwrap();
+    assert!(output.contains("const geometry$area = "), "{}", output);
+    assert!(!output.contains("999"), "{}", output);
+}
+
+#[test]
+fn modules_are_checked() {
+    let modules = Modules::load(path("util/numbers.pj")).unwrap();
+    assert_eq!(Ok(Ty::Unit), check(&modules));
+}
+
+#[test]
+fn error_in_module() {
+    let modules = Modules::load(path("errors/main.pj")).unwrap();
+    match check(&modules).unwrap_err() {
+        LangError::Module { path, error, .. } => {
+            assert!(path.ends_with("broken.pj"), "{}", path.display());
+            assert!(matches!(*error, LangError::Ty(_)), "{:?}", error);
+        }
+        error => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn runtime_error_in_module() {
+    let modules = Modules::load(path("errors/runtime.pj")).unwrap();
+    let mut output = Vec::default();
+    let err = run_with_machine(&modules, machine_builder(&mut output).build()).unwrap_err();
+    let loc = err.loc();
+    let files = modules.source_map();
+    assert!(files.name(loc.file).unwrap().ends_with("divide.pj"));
+    assert_eq!(Some("a / b"), files.snippet(loc));
+}
+
+#[test]
+fn source_map() {
+    let modules = Modules::load(path("main.pj")).unwrap();
+    let files = modules.source_map();
+    let names: Vec<_> = files.files().map(|(_, name, _)| name).collect();
+    assert_eq!(4, names.len(), "{:?}", names);
+    assert!(names[0].ends_with("main.pj"));
+    assert_eq!("<prelude>", names[1]);
+    assert!(names[2].ends_with("geometry.pj"));
+    assert!(names[3].ends_with("numbers.pj"));
+    assert_eq!(Some(FileId::MAIN), files.find(names[0]));
+    assert_eq!(Some(modules.input()), files.source(FileId::MAIN));
+}
+
+#[test]
+fn cycle() {
+    let err = Modules::load(path("cycle/first.pj")).unwrap_err();
+    assert!(matches!(err, LoadError::Cycle(_)), "{:?}", err);
+}
+
+#[test]
+fn missing_module() {
+    let err = Modules::load(path("missing.pj")).unwrap_err();
+    assert!(matches!(err, LoadError::Io { .. }), "{:?}", err);
+}
+
+#[test]
+fn unqualified_names_are_unbounded() {
+    let modules = Modules::load(path("errors/unqualified.pj")).unwrap();
+    let err = check(&modules).unwrap_err();
+    assert!(
+        matches!(err, LangError::Ty(TyError::Unbounded(_))),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn unresolved_import() {
+    let input = "import \"geometry.pj\"\ngeometry::square(2)";
+    assert_eq!("E0009", check(input).unwrap_err().code());
+}
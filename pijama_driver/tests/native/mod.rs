@@ -0,0 +1,182 @@
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc,
+};
+
+use pijama_ast::Int;
+use pijama_core::{
+    machine::{
+        native::{NativeFn, NativeModule},
+        Value,
+    },
+    ty::{Ty, TyError},
+};
+use pijama_driver::{run_with_machine, LangError, LangResult};
+
+use crate::machine_builder;
+
+struct MathModule;
+
+impl NativeModule for MathModule {
+    fn functions(self) -> Vec<NativeFn> {
+        vec![
+            NativeFn::new("max", vec![Ty::Int, Ty::Int], Ty::Int, |args| {
+                match (&args[0], &args[1]) {
+                    (Value::Int(a), Value::Int(b)) => Value::Int(*a.max(b)),
+                    _ => unreachable!(),
+                }
+            }),
+            NativeFn::new("min", vec![Ty::Int, Ty::Int], Ty::Int, |args| {
+                match (&args[0], &args[1]) {
+                    (Value::Int(a), Value::Int(b)) => Value::Int(*a.min(b)),
+                    _ => unreachable!(),
+                }
+            }),
+            NativeFn::new("is_even", vec![Ty::Int], Ty::Bool, |args| match args[0] {
+                Value::Int(n) => Value::Bool(n % 2 == 0),
+                _ => unreachable!(),
+            }),
+        ]
+    }
+}
+
+fn run_with_math(input: &str) -> LangResult<String> {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output).with_module(MathModule).build();
+    run_with_machine(input, machine)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn clamp() {
+    let input = include_str!("clamp.pj");
+    assert_eq!(Ok("10\n".to_owned()), run_with_math(input));
+}
+
+#[test]
+fn returns_bool() {
+    assert_eq!(
+        Ok("1\n".to_owned()),
+        run_with_math("print(is_even(4) && !is_even(7))")
+    );
+}
+
+#[test]
+fn partial_application() {
+    let input = "at_least_three = max(3)\nprint(at_least_three(1) + at_least_three(5))";
+    assert_eq!(Ok("8\n".to_owned()), run_with_math(input));
+}
+
+#[test]
+fn arguments_are_evaluated() {
+    assert_eq!(
+        Ok("6\n".to_owned()),
+        run_with_math("print(max(2 * 3, 1 + 1))")
+    );
+}
+
+#[test]
+fn wrong_argument_type() {
+    let result = run_with_math("max(true, 1)");
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn program_shadows_native() {
+    let input = "fn max(x: Int, y: Int): Int do x end\nprint(max(1, 2))";
+    assert_eq!(Ok("1\n".to_owned()), run_with_math(input));
+}
+
+#[test]
+fn later_registration_shadows() {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_module(MathModule)
+        .with_native(NativeFn::new("is_even", vec![Ty::Int], Ty::Bool, |_| {
+            Value::Bool(false)
+        }))
+        .build();
+    run_with_machine("print(is_even(2))", machine).unwrap();
+    assert_eq!("0\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn native_shadows_primitive() {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_native(NativeFn::new("abs", vec![Ty::Int], Ty::Int, |_| {
+            Value::Int(0)
+        }))
+        .build();
+    run_with_machine("print(abs(-3))", machine).unwrap();
+    assert_eq!("0\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn unregistered_native() {
+    let mut output = Vec::default();
+    let result = run_with_machine("is_even(2)", machine_builder(&mut output).build());
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Unbounded(_)))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn from_fn() {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_native(NativeFn::from_fn("clamp", |n: Int, lo: Int, hi: Int| {
+            n.max(lo).min(hi)
+        }))
+        .with_native(NativeFn::from_fn("is_zero", |n: Int| n == 0))
+        .build();
+    run_with_machine("print(clamp(12, 0, 10))\nprint(is_zero(0))", machine).unwrap();
+    assert_eq!("10\n1\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn from_fn_collections() {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_native(NativeFn::from_fn("range", |n: Int| {
+            (0..n).collect::<Vec<Int>>()
+        }))
+        .with_native(NativeFn::from_fn("total", |xs: Vec<Int>| {
+            xs.iter().sum::<Int>()
+        }))
+        .with_native(NativeFn::from_fn("shout", |s: String| s.to_uppercase()))
+        .with_native(NativeFn::from_fn("half", |x: f64| x / 2.0))
+        .build();
+    let input = "print(total(range(5)))\nprint(shout(\"hi\"))\nprint(half(3.0))";
+    run_with_machine(input, machine).unwrap();
+    assert_eq!("10\nHI\n1.5\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn from_fn_types() {
+    let native = NativeFn::from_fn("select", |b: bool, n: Int, _: ()| if b { n } else { 0 });
+    assert_eq!("Bool -> Int -> Unit -> Int", native.ty().to_string());
+    let native = NativeFn::from_fn("join", |xs: Vec<String>, x: f64| format!("{:?}{}", xs, x));
+    assert_eq!("[String] -> Float -> String", native.ty().to_string());
+}
+
+#[test]
+fn while_loop() {
+    // The condition is the only part of the loop that can change between iterations.
+    let calls = Arc::new(AtomicI64::new(0));
+    let counter = Arc::clone(&calls);
+    let tick = NativeFn::new("tick", vec![Ty::Unit], Ty::Bool, move |_| {
+        Value::Bool(counter.fetch_add(1, Ordering::SeqCst) < 3)
+    });
+
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output).with_native(tick).build();
+    run_with_machine("while tick(unit) do unit end", machine).unwrap();
+    assert_eq!(4, calls.load(Ordering::SeqCst));
+}
@@ -0,0 +1,134 @@
+use pijama_ast::{visitor::NodeVisitor, Located, Location, Node, NodeId};
+use pijama_core::{
+    lir::Term,
+    mir::Term as MirTerm,
+    parser::{parse, parse_recovering},
+    ty::{ty_check_terms, Ty},
+};
+use pijama_driver::{
+    prelude::PRELUDE_FILE,
+    query::{node_at, type_of_node},
+};
+
+/// Collects the id and location of each node, in the order they are visited.
+#[derive(Default)]
+struct Ids(Vec<(NodeId, Location)>);
+
+impl<'a> NodeVisitor<'a> for Ids {
+    fn visit_node(&mut self, node: &Located<Node<'a>>) {
+        self.0.push((node.id, node.loc));
+        self.super_node(node);
+    }
+}
+
+fn ids(input: &str) -> Vec<(NodeId, Location)> {
+    let mut ids = Ids::default();
+    ids.visit_block(&parse(input).unwrap().content);
+    ids.0
+}
+
+#[test]
+fn numbers_nodes_in_source_order() {
+    let input = include_str!("../query/program.pj");
+    let expected: Vec<_> = [
+        (0, 23),
+        (13, 18),
+        (13, 14),
+        (17, 18),
+        (23, 33),
+        (27, 33),
+        (27, 30),
+        (31, 32),
+        (34, 40),
+        (34, 35),
+        (39, 40),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(index, (start, end))| (NodeId::new(index as u32), Location::new(*start, *end)))
+    .collect();
+    assert_eq!(expected, ids(input));
+}
+
+#[test]
+fn ids_do_not_depend_on_later_code() {
+    let before = ids("x = 1 + 2\nx");
+    let after = ids("x = 1 + 2\nx * 3 + 4");
+    assert_eq!(before[..3], after[..3]);
+}
+
+#[test]
+fn recovered_nodes_are_numbered() {
+    let (blk, errors) = parse_recovering("x = 1 +\ny = 2\ny");
+    assert!(!errors.is_empty());
+    let mut ids = Ids::default();
+    ids.visit_block(&blk.content);
+    assert!(ids.0.iter().all(|(id, _)| !id.is_dummy()));
+}
+
+#[test]
+fn type_table_by_node() {
+    let mir = MirTerm::from_ast(parse("fn inc(x) do x + 1 end\ninc(2) == 3").unwrap()).unwrap();
+    let table = ty_check_terms(&mir).unwrap();
+    let arrow = Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int));
+    // The function definition, the `x + 1` in its body and the comparison.
+    assert_eq!(Some(&arrow), table.get_node(NodeId::new(0)));
+    assert_eq!(Some(&Ty::Int), table.get_node(NodeId::new(1)));
+    assert_eq!(Some(&Ty::Bool), table.get_node(NodeId::new(4)));
+    assert_eq!(None, table.get_node(NodeId::new(100)));
+    assert_eq!(None, table.get_node(NodeId::DUMMY));
+}
+
+#[test]
+fn ids_survive_desugaring() {
+    // The loop is desugared into a recursive function, but its condition and body keep the ids
+    // of their nodes.
+    let input = "n = 0\nwhile n < 3 do print(n) end";
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    let table = ty_check_terms(&mir).unwrap();
+    let ids = ids(input);
+    let (cond, _) = ids
+        .iter()
+        .find(|(_, loc)| *loc == Location::new(12, 17))
+        .unwrap();
+    assert_eq!(Some(&Ty::Bool), table.get_node(*cond));
+}
+
+#[test]
+fn span_table_of_nameless_terms() {
+    let mir = MirTerm::from_ast(parse("1 + read_int()").unwrap()).unwrap();
+    let (_, spans) = Term::from_mir_with_spans(mir, &[]);
+    assert_eq!(2, spans.len());
+    assert_eq!(Some(NodeId::new(0)), spans.node(Location::new(0, 14)));
+    assert_eq!(Some(NodeId::new(2)), spans.node(Location::new(4, 14)));
+    assert_eq!(Some(Location::new(4, 14)), spans.loc(NodeId::new(2)));
+    assert_eq!(None, spans.node(Location::new(0, 1)));
+}
+
+#[test]
+fn editor_queries_by_node() {
+    let input = include_str!("../query/program.pj");
+    // The `inc` in `inc(2)`.
+    let (id, loc) = node_at(input, 28).unwrap();
+    assert_eq!((NodeId::new(6), Location::new(27, 30)), (id, loc));
+    assert_eq!(
+        Some(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int))),
+        type_of_node(input, id)
+    );
+    // The id still refers to the same node after editing the end of the program.
+    let edited = format!("{}y + 1 == 4\n", &input[..34]);
+    assert_eq!(Some((id, loc)), node_at(&edited, 28));
+    assert_eq!(None, node_at(input, input.len()));
+}
+
+#[test]
+fn prelude_nodes_are_in_their_file() {
+    // The third node is the `None` of the program, and `is_some` in the prelude.
+    let id = NodeId::new(2);
+    let input = "is_some(None)";
+    assert!(matches!(type_of_node(input, id), Some(Ty::Adt(_, _))));
+    match type_of_node(input, id.in_file(PRELUDE_FILE)) {
+        Some(Ty::Arrow(_, ret)) => assert_eq!(Ty::Bool, *ret),
+        ty => panic!("unexpected type {:?}", ty),
+    }
+}
@@ -0,0 +1,147 @@
+use pijama_core::{
+    lir::Term as LirTerm,
+    mir::{optimize, optimize_with, Pass, Term},
+    parser::parse,
+};
+
+use crate::{machine_builder, run};
+
+fn optimized(input: &str) -> String {
+    let mir = Term::from_ast(parse(input).unwrap()).unwrap();
+    optimize(mir).content.to_string()
+}
+
+fn optimized_with(input: &str, passes: &[Pass]) -> String {
+    let mir = Term::from_ast(parse(input).unwrap()).unwrap();
+    optimize_with(mir, passes).content.to_string()
+}
+
+fn evaluated_with(input: &str, passes: &[Pass]) -> String {
+    let mir = Term::from_ast(parse(input).unwrap()).unwrap();
+    let lir = LirTerm::from_mir(optimize_with(mir, passes));
+    let mut output = Vec::default();
+    machine_builder(&mut output).build().evaluate(lir).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn folding() {
+    assert_eq!("7", optimized(include_str!("folding.pj")));
+}
+
+#[test]
+fn shadowing() {
+    assert_eq!(
+        "(let f : Int -> Int = (λn:Int. (n + 1)) in ((f 10) + 10))",
+        optimized_with(
+            include_str!("shadowing.pj"),
+            &[Pass::ConstantFolding, Pass::DeadCode]
+        )
+    );
+    assert_eq!("21", optimized(include_str!("shadowing.pj")));
+}
+
+#[test]
+fn inlining() {
+    assert_eq!("7", optimized(include_str!("inlining.pj")));
+}
+
+#[test]
+fn captured_names_are_not_inlined() {
+    // The body of `g` uses the `x` bound outside of `h`, which is shadowed by the parameter of
+    // `h`, so `g` is not inlined inside `h`.
+    assert_eq!(
+        "(let x = 1 in (let g : Int -> Int = (λy:Int. (x + y)) in (print (g 2))))",
+        optimized_with(
+            include_str!("capture.pj"),
+            &[Pass::Inlining, Pass::DeadCode]
+        )
+    );
+}
+
+#[test]
+fn recursive_functions_are_not_inlined() {
+    assert_eq!(
+        "(let rec fact : Int -> Int = (λn:Int. (if (n <= 0) then 1 else (n * (fact (n - 1))))) in (fact 5))",
+        optimized(include_str!("recursive.pj"))
+    );
+}
+
+#[test]
+fn overflow_is_not_folded() {
+    assert_eq!(
+        "(print (9223372036854775807 + 1))",
+        optimized(include_str!("overflow.pj"))
+    );
+}
+
+#[test]
+fn dead_code() {
+    assert_eq!("false", optimized(include_str!("dead_code.pj")));
+}
+
+#[test]
+fn unused_bindings() {
+    // Operations can fail and reading the input is an effect, so those bindings are kept.
+    assert_eq!(
+        "(let a = 1 in (let b = (a + 1) in (print a) ; (let c = read_int in unit)))",
+        optimized_with(include_str!("unused.pj"), &[Pass::DeadCode])
+    );
+    assert_eq!(
+        "(print 1) ; (let c = read_int in unit)",
+        optimized(include_str!("unused.pj"))
+    );
+}
+
+#[test]
+fn passes() {
+    let input = include_str!("passes.pj");
+    assert_eq!(
+        "(let x = 3 in (print x) ; (if (x > 2) then (print 1) else (print 2)))",
+        optimized_with(input, &[Pass::DeadCode])
+    );
+    assert_eq!(
+        "(print 3) ; (print 1)",
+        optimized_with(input, &[Pass::ConstantFolding])
+    );
+    let mir = Term::from_ast(parse(input).unwrap()).unwrap();
+    assert_eq!(mir.content.to_string(), optimized_with(input, &[]));
+}
+
+#[test]
+fn specialization() {
+    let input = include_str!("pow.pj");
+    assert_eq!(
+        "(let x = read_int in (print (x * (x * (x * 1)))))",
+        optimized_with(
+            input,
+            &[Pass::Specialization { unfoldings: 16 }, Pass::DeadCode]
+        )
+    );
+    // Only the first application is unfolded, and the rest of the recursion is evaluated as before.
+    assert_eq!(
+        "(let rec pow : Int -> Int -> Int = (λbase:Int. (λn:Int. (if (n == 0) then 1 else (base * ((pow base) (n - 1)))))) in (let x = read_int in (print (x * ((pow x) 2)))))",
+        optimized_with(input, &[Pass::Specialization { unfoldings: 1 }])
+    );
+}
+
+#[test]
+fn specialization_keeps_the_results() {
+    let inputs = [
+        include_str!("specialize.pj"),
+        include_str!("../eval/ackermann.pj"),
+        include_str!("../eval/factorial.pj"),
+        include_str!("../eval/fibonacci.pj"),
+        include_str!("../eval/gcd.pj"),
+        include_str!("../eval/mutual_recursion.pj"),
+        include_str!("../eval/shared_argument.pj"),
+        include_str!("../eval/tail_calls.pj"),
+    ];
+    for input in inputs {
+        let output = run(input).unwrap();
+        for unfoldings in [0, 1, 8, 64] {
+            let passes = [Pass::Specialization { unfoldings }];
+            assert_eq!(output, evaluated_with(input, &passes));
+        }
+    }
+}
@@ -10,3 +10,114 @@ fn consecutive_comments() {
     let err = run(input).unwrap_err();
     assert!(matches!(err, LangError::Parse(_)))
 }
+
+#[test]
+fn unterminated_block_comment() {
+    let input = include_str!("unterminated_block_comment.pj");
+    match run(input).unwrap_err() {
+        LangError::Parse(err) => assert_eq!(Some("Unterminated block comment"), err.context()),
+        err => panic!("expected a parsing error, found {:?}", err),
+    }
+}
+
+#[test]
+fn undocumented_doc_comment() {
+    let input = include_str!("undocumented_doc_comment.pj");
+    match run(input).unwrap_err() {
+        LangError::Parse(err) => assert_eq!(
+            Some("Expected function definition after doc comment"),
+            err.context()
+        ),
+        err => panic!("expected a parsing error, found {:?}", err),
+    }
+}
+
+#[test]
+fn unterminated_string() {
+    let input = include_str!("unterminated_string.pj");
+    let err = run(input).unwrap_err();
+    assert!(matches!(err, LangError::Parse(_)))
+}
+
+#[test]
+fn empty_char() {
+    let input = include_str!("empty_char.pj");
+    let err = run(input).unwrap_err();
+    assert!(matches!(err, LangError::Parse(_)))
+}
+
+#[test]
+fn unclosed_list() {
+    let input = include_str!("unclosed_list.pj");
+    let err = run(input).unwrap_err();
+    assert!(matches!(err, LangError::Parse(_)))
+}
+
+#[test]
+fn import_without_path() {
+    let input = include_str!("import_without_path.pj");
+    let err = run(input).unwrap_err();
+    assert!(matches!(err, LangError::Parse(_)))
+}
+
+#[test]
+fn number_out_of_range() {
+    let input = include_str!("number_out_of_range.pj");
+    match run(input).unwrap_err() {
+        LangError::Parse(err) => {
+            assert_eq!(
+                Some("Integer literal out of range for type Int"),
+                err.context()
+            );
+            assert_eq!(10, err.loc().start);
+        }
+        err => panic!("expected a parsing error, found {:?}", err),
+    }
+}
+
+#[test]
+fn error_outlives_input() {
+    let err = {
+        let input = String::from("x = (1 +");
+        run(&input).unwrap_err()
+    };
+    match err {
+        LangError::Parse(err) => assert_eq!(1, err.line()),
+        err => panic!("expected a parsing error, found {:?}", err),
+    }
+}
+
+#[test]
+fn expected_tokens() {
+    let cases: &[(&str, &[&str], usize)] = &[
+        ("if x print(x) end", &["do"], 5),
+        ("f(1, 2 3)", &[",", ")"], 7),
+        ("fn f(x Int) do x end", &[",", ")"], 7),
+        ("xs = [1, 2\nxs", &[",", "]"], 11),
+        ("for i in 1 do i end", &[".."], 11),
+    ];
+    for (input, expected, start) in cases {
+        match run(input).unwrap_err() {
+            LangError::Parse(err) => {
+                assert_eq!(*expected, err.expected(), "{}", input);
+                assert_eq!(*start, err.loc().start, "{}", input);
+            }
+            err => panic!("expected a parsing error, found {:?}", err),
+        }
+    }
+}
+
+#[test]
+fn section_operand() {
+    let input = include_str!("section_operand.pj");
+    match run(input).unwrap_err() {
+        LangError::Parse(err) => assert_eq!(
+            Some(
+                "The operand of a section must be in brackets unless it binds more tightly than \
+                 the operator"
+            ),
+            err.context()
+        ),
+        err => panic!("expected a parsing error, found {:?}", err),
+    }
+}
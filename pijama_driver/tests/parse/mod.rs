@@ -1,2 +1,4 @@
 mod fail;
 mod pass;
+mod recover;
+mod ty;
@@ -3,34 +3,41 @@ use std::include_str;
 use pijama_ast::{
     self,
     ty::{Ty, TyAnnotation},
+    Arm,
     BinOp::*,
     Block, Branch,
     Node::*,
-    UnOp,
+    Pattern, UnOp, Variant,
 };
-use pijama_core::parser::parse;
+use pijama_core::{mir::Term, parser::parse};
 use pijama_driver::LangResult;
 
 use crate::util::DummyLoc;
 
 #[test]
-fn name() -> LangResult<'static, ()> {
+fn name() -> LangResult<()> {
     let input = include_str!("name.pj");
     let result = parse(input)?.content;
     let expected = vec![
         Name(pijama_ast::Name("x")).loc(),
         Name(pijama_ast::Name("foo")).loc(),
         Name(pijama_ast::Name("foo_bar")).loc(),
+        Name(pijama_ast::Name("área")).loc(),
+        Name(pijama_ast::Name("λ")).loc(),
+        Name(pijama_ast::Name("x1")).loc(),
     ];
 
     assert_eq!(expected[0], result[0], "single letter");
     assert_eq!(expected[1], result[1], "word");
     assert_eq!(expected[2], result[2], "snake case");
+    assert_eq!(expected[3], result[3], "non-ascii letters");
+    assert_eq!(expected[4], result[4], "greek letter");
+    assert_eq!(expected[5], result[5], "digits");
     Ok(())
 }
 
 #[test]
-fn single_comment() -> LangResult<'static, ()> {
+fn single_comment() -> LangResult<()> {
     let input = include_str!("single_comment.pj");
     let result = parse(input)?.content;
     let expected = vec![Name(pijama_ast::Name("foo_bar")).loc()];
@@ -40,7 +47,17 @@ fn single_comment() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn literal() -> LangResult<'static, ()> {
+fn block_comment() -> LangResult<()> {
+    let input = include_str!("block_comment.pj");
+    let result = parse(input)?.content;
+    let expected = [Name(pijama_ast::Name("foo_bar")).loc()];
+
+    assert_eq!(expected[0], result[0], "nested");
+    Ok(())
+}
+
+#[test]
+fn literal() -> LangResult<()> {
     let input = include_str!("literal.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -68,7 +85,208 @@ fn literal() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn binary_op() -> LangResult<'static, ()> {
+fn digit_separators() -> LangResult<()> {
+    let input = include_str!("digit_separators.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Literal(pijama_ast::Literal::Number(1_000_000)).loc(),
+        Literal(pijama_ast::Literal::Number(0xFFFF)).loc(),
+        Literal(pijama_ast::Literal::Number(0b1010_1010)).loc(),
+        Literal(pijama_ast::Literal::Number(-1000)).loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "decimal");
+    assert_eq!(expected[1], result[1], "hexadecimal");
+    assert_eq!(expected[2], result[2], "binary");
+    assert_eq!(expected[3], result[3], "negative");
+    Ok(())
+}
+
+#[test]
+fn char() -> LangResult<()> {
+    let input = include_str!("char.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Literal(pijama_ast::Literal::Char('a')).loc(),
+        Literal(pijama_ast::Literal::Char('\n')).loc(),
+        Literal(pijama_ast::Literal::Char('\'')).loc(),
+        Literal(pijama_ast::Literal::Char('\\')).loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("c").loc(),
+                ty: Ty::Char.loc(),
+            },
+            Box::new(Literal(pijama_ast::Literal::Char('x')).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "character");
+    assert_eq!(expected[1], result[1], "newline");
+    assert_eq!(expected[2], result[2], "quote");
+    assert_eq!(expected[3], result[3], "backslash");
+    assert_eq!(expected[4], result[4], "char type");
+    Ok(())
+}
+
+#[test]
+fn string() -> LangResult<()> {
+    let input = include_str!("string.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Literal(pijama_ast::Literal::Str("hello, world".to_owned())).loc(),
+        Literal(pijama_ast::Literal::Str(String::new())).loc(),
+        Literal(pijama_ast::Literal::Str("a\"b\\c\n".to_owned())).loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("s").loc(),
+                ty: Ty::String.loc(),
+            },
+            Box::new(Literal(pijama_ast::Literal::Str("x".to_owned())).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "string");
+    assert_eq!(expected[1], result[1], "empty string");
+    assert_eq!(expected[2], result[2], "escapes");
+    assert_eq!(expected[3], result[3], "string type");
+    Ok(())
+}
+
+#[test]
+fn interpolation() -> LangResult<()> {
+    let input = include_str!("interpolation.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Interpolation(
+            vec![
+                Literal(pijama_ast::Literal::Str("x is ".to_owned())).loc(),
+                Name(pijama_ast::Name("x")).loc(),
+                Literal(pijama_ast::Literal::Str("!".to_owned())).loc(),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .loc(),
+        Interpolation(
+            vec![BinaryOp(
+                Add,
+                Box::new(Name(pijama_ast::Name("a")).loc()),
+                Box::new(Literal(pijama_ast::Literal::Number(1)).loc()),
+            )
+            .loc()]
+            .into_iter()
+            .collect(),
+        )
+        .loc(),
+        Literal(pijama_ast::Literal::Str("{x}".to_owned())).loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "interpolation");
+    assert_eq!(expected[1], result[1], "spaces");
+    assert_eq!(expected[2], result[2], "escaped brace");
+    Ok(())
+}
+
+#[test]
+fn float() -> LangResult<()> {
+    let input = include_str!("float.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Literal(pijama_ast::Literal::Float(1.5)).loc(),
+        Literal(pijama_ast::Literal::Float(-0.25)).loc(),
+        Literal(pijama_ast::Literal::Float(2e10)).loc(),
+        Literal(pijama_ast::Literal::Float(1.5e-3)).loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("x").loc(),
+                ty: Ty::Float.loc(),
+            },
+            Box::new(Literal(pijama_ast::Literal::Float(1.0)).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "fractional part");
+    assert_eq!(expected[1], result[1], "negative float");
+    assert_eq!(expected[2], result[2], "exponent");
+    assert_eq!(expected[3], result[3], "fractional part and exponent");
+    assert_eq!(expected[4], result[4], "float type");
+    Ok(())
+}
+
+#[test]
+fn list() -> LangResult<()> {
+    let input = include_str!("list.pj");
+    let result = parse(input)?.content;
+    let number = |n| Literal(pijama_ast::Literal::Number(n)).loc();
+    let boolean = |b| Literal(pijama_ast::Literal::Bool(b)).loc();
+    let expected = [
+        List(vec![number(1), number(2), number(3)].into_iter().collect()).loc(),
+        List(Block::default()).loc(),
+        List(
+            vec![
+                List(vec![boolean(true)].into_iter().collect()).loc(),
+                List(vec![boolean(false), boolean(true)].into_iter().collect()).loc(),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("xs").loc(),
+                ty: Ty::List(Box::new(Ty::Int)).loc(),
+            },
+            Box::new(List(vec![number(1)].into_iter().collect()).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "list");
+    assert_eq!(expected[1], result[1], "empty list");
+    assert_eq!(expected[2], result[2], "nested lists over many lines");
+    assert_eq!(expected[3], result[3], "list type");
+    Ok(())
+}
+
+#[test]
+fn import() -> LangResult<()> {
+    let input = include_str!("import.pj");
+    let result = parse(input)?.content;
+    let area = || Name(pijama_ast::Name("geometry::area")).loc();
+    let expected = [
+        Import("geometry.pj".to_owned()).loc(),
+        Import("../shapes/circle.pj".to_owned()).loc(),
+        area(),
+        Call(
+            Box::new(area()),
+            vec![Literal(pijama_ast::Literal::Number(1)).loc()]
+                .into_iter()
+                .collect(),
+        )
+        .loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("side").loc(),
+                ty: Ty::Adt("geometry::Shape".to_owned(), Vec::new()).loc(),
+            },
+            Box::new(Name(pijama_ast::Name("geometry::Square")).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "import");
+    assert_eq!(expected[1], result[1], "import from parent directory");
+    assert_eq!(expected[2], result[2], "qualified name");
+    assert_eq!(expected[3], result[3], "qualified call");
+    assert_eq!(expected[4], result[4], "qualified enum type");
+    Ok(())
+}
+
+#[test]
+fn binary_op() -> LangResult<()> {
     let input = include_str!("bin_op.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -113,7 +331,7 @@ fn binary_op() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn unary_op() -> LangResult<'static, ()> {
+fn unary_op() -> LangResult<()> {
     let input = include_str!("un_op.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -125,17 +343,91 @@ fn unary_op() -> LangResult<'static, ()> {
         )
         .loc(),
         UnaryOp(UnOp::Not, Box::new(Name(pijama_ast::Name("x")).loc())).loc(),
+        UnaryOp(UnOp::BitNot, Box::new(Name(pijama_ast::Name("x")).loc())).loc(),
     ];
 
     assert_eq!(expected[0], result[0], "minus");
     assert_eq!(expected[1], result[1], "not");
     assert_eq!(expected[2], result[2], "double");
     assert_eq!(expected[3], result[3], "brackets");
+    assert_eq!(expected[4], result[4], "bit not");
+    Ok(())
+}
+
+#[test]
+fn let_in() -> LangResult<()> {
+    let input = include_str!("let_in.pj");
+    let result = parse(input)?.content;
+    let x = || Name(pijama_ast::Name("x")).loc();
+    let one = || Literal(pijama_ast::Literal::Number(1)).loc();
+    let annotation = |ty: Ty| TyAnnotation {
+        item: pijama_ast::Name("x").loc(),
+        ty: ty.loc(),
+    };
+    let expected = [
+        LetIn(annotation(Ty::Missing), Box::new(one()), Box::new(x())).loc(),
+        LetIn(
+            annotation(Ty::Int),
+            Box::new(Name(pijama_ast::Name("y")).loc()),
+            Box::new(BinaryOp(Add, Box::new(x()), Box::new(one())).loc()),
+        )
+        .loc(),
+        Call(
+            Box::new(Name(pijama_ast::Name("f")).loc()),
+            Some(LetIn(annotation(Ty::Missing), Box::new(one()), Box::new(x())).loc())
+                .into_iter()
+                .collect(),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "simple");
+    assert_eq!(expected[1], result[1], "annotated with line break");
+    assert_eq!(expected[2], result[2], "argument");
     Ok(())
 }
 
 #[test]
-fn logic_op() -> LangResult<'static, ()> {
+fn let_in_lowers_like_let_bind() -> LangResult<()> {
+    let expression = Term::from_ast(parse("let x: Int = 1 in x + 1")?)?;
+    let statement = Term::from_ast(parse("x: Int = 1\nx + 1")?)?;
+    assert_eq!(
+        statement.content.to_string(),
+        expression.content.to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn lazy() -> LangResult<()> {
+    let input = include_str!("lazy.pj");
+    let result = parse(input)?.content;
+    let x = || Name(pijama_ast::Name("x")).loc();
+    let one = || Literal(pijama_ast::Literal::Number(1)).loc();
+    let expected = [
+        Lazy(Box::new(x())).loc(),
+        Lazy(Box::new(
+            BinaryOp(Add, Box::new(x()), Box::new(one())).loc(),
+        ))
+        .loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("x").loc(),
+                ty: Ty::Lazy(Box::new(Ty::Int)).loc(),
+            },
+            Box::new(Lazy(Box::new(one())).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "name");
+    assert_eq!(expected[1], result[1], "binary operation");
+    assert_eq!(expected[2], result[2], "annotated");
+    Ok(())
+}
+
+#[test]
+fn logic_op() -> LangResult<()> {
     let input = include_str!("logic_op.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -180,7 +472,7 @@ fn logic_op() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn bit_op() -> LangResult<'static, ()> {
+fn bit_op() -> LangResult<()> {
     let input = include_str!("bit_op.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -239,7 +531,7 @@ fn bit_op() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn let_bind() -> LangResult<'static, ()> {
+fn let_bind() -> LangResult<()> {
     let input = include_str!("let_bind.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -297,17 +589,30 @@ fn let_bind() -> LangResult<'static, ()> {
             ),
         )
         .loc(),
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("f").loc(),
+                ty: Ty::Arrow(
+                    Box::new(Ty::Var("a".to_owned())),
+                    Box::new(Ty::Var("a".to_owned())),
+                )
+                .loc(),
+            },
+            Box::new(Name(pijama_ast::Name("y")).loc()),
+        )
+        .loc(),
     ];
 
     assert_eq!(expected[0], result[0], "simple");
     assert_eq!(expected[1], result[1], "bind to bin op");
     assert_eq!(expected[2], result[2], "type binding");
     assert_eq!(expected[3], result[3], "bind to nameless function");
+    assert_eq!(expected[4], result[4], "type variable");
     Ok(())
 }
 
 #[test]
-fn cond() -> LangResult<'static, ()> {
+fn cond() -> LangResult<()> {
     let input = include_str!("cond.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -364,7 +669,58 @@ fn cond() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn elif() -> LangResult<'static, ()> {
+fn cond_without_else() -> LangResult<()> {
+    let input = include_str!("cond_without_else.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Cond(
+            Branch {
+                cond: vec![Name(pijama_ast::Name("x")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                body: vec![Name(pijama_ast::Name("y")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+            },
+            vec![],
+            Block::new().loc(),
+        )
+        .loc(),
+        Cond(
+            Branch {
+                cond: vec![Name(pijama_ast::Name("a")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                body: vec![Name(pijama_ast::Name("b")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+            },
+            vec![Branch {
+                cond: vec![Name(pijama_ast::Name("c")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                body: vec![Name(pijama_ast::Name("d")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+            }],
+            Block::new().loc(),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "without elif");
+    assert_eq!(expected[1], result[1], "with elif");
+    Ok(())
+}
+
+#[test]
+fn elif() -> LangResult<()> {
     let input = include_str!("elif.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -462,7 +818,191 @@ fn elif() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn call() -> LangResult<'static, ()> {
+fn while_loop() -> LangResult<()> {
+    let input = include_str!("while_loop.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        While(Branch {
+            cond: vec![Name(pijama_ast::Name("x")).loc()]
+                .into_iter()
+                .collect::<Block<'_>>()
+                .loc(),
+            body: vec![Name(pijama_ast::Name("y")).loc()]
+                .into_iter()
+                .collect::<Block<'_>>()
+                .loc(),
+        })
+        .loc(),
+        While(Branch {
+            cond: vec![Name(pijama_ast::Name("u")).loc()]
+                .into_iter()
+                .collect::<Block<'_>>()
+                .loc(),
+            body: vec![
+                Name(pijama_ast::Name("v")).loc(),
+                Name(pijama_ast::Name("w")).loc(),
+            ]
+            .into_iter()
+            .collect::<Block<'_>>()
+            .loc(),
+        })
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "simple blocks");
+    assert_eq!(expected[1], result[1], "long blocks");
+    Ok(())
+}
+
+#[test]
+fn let_mut() -> LangResult<()> {
+    let input = include_str!("let_mut.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        LetMut(
+            TyAnnotation {
+                item: pijama_ast::Name("x").loc(),
+                ty: Ty::Missing.loc(),
+            },
+            Box::new(Name(pijama_ast::Name("y")).loc()),
+        )
+        .loc(),
+        Assign(
+            pijama_ast::Name("x").loc(),
+            Box::new(
+                BinaryOp(
+                    Add,
+                    Box::new(Name(pijama_ast::Name("x")).loc()),
+                    Box::new(Literal(pijama_ast::Literal::Number(1)).loc()),
+                )
+                .loc(),
+            ),
+        )
+        .loc(),
+        LetMut(
+            TyAnnotation {
+                item: pijama_ast::Name("z").loc(),
+                ty: Ty::Int.loc(),
+            },
+            Box::new(Literal(pijama_ast::Literal::Number(0)).loc()),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "mutable binding");
+    assert_eq!(expected[1], result[1], "assignment");
+    assert_eq!(expected[2], result[2], "type annotation");
+    Ok(())
+}
+
+#[test]
+fn for_loop() -> LangResult<()> {
+    let input = include_str!("for_loop.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        For(
+            pijama_ast::Name("i").loc(),
+            Box::new(Literal(pijama_ast::Literal::Number(0)).loc()),
+            Box::new(Name(pijama_ast::Name("n")).loc()),
+            vec![Name(pijama_ast::Name("i")).loc()]
+                .into_iter()
+                .collect::<Block<'_>>()
+                .loc(),
+        )
+        .loc(),
+        For(
+            pijama_ast::Name("j").loc(),
+            Box::new(Name(pijama_ast::Name("a")).loc()),
+            Box::new(
+                BinaryOp(
+                    Add,
+                    Box::new(Name(pijama_ast::Name("b")).loc()),
+                    Box::new(Literal(pijama_ast::Literal::Number(1)).loc()),
+                )
+                .loc(),
+            ),
+            vec![Call(
+                Box::new(Name(pijama_ast::Name("print")).loc()),
+                vec![Name(pijama_ast::Name("j")).loc()]
+                    .into_iter()
+                    .collect(),
+            )
+            .loc()]
+            .into_iter()
+            .collect::<Block<'_>>()
+            .loc(),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "simple range");
+    assert_eq!(expected[1], result[1], "long blocks");
+    Ok(())
+}
+
+#[test]
+fn pipe() -> LangResult<()> {
+    let input = include_str!("pipe.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        BinaryOp(
+            Pipe,
+            Box::new(
+                BinaryOp(
+                    Pipe,
+                    Box::new(
+                        BinaryOp(
+                            Add,
+                            Box::new(Name(pijama_ast::Name("a")).loc()),
+                            Box::new(Name(pijama_ast::Name("b")).loc()),
+                        )
+                        .loc(),
+                    ),
+                    Box::new(Name(pijama_ast::Name("f")).loc()),
+                )
+                .loc(),
+            ),
+            Box::new(
+                Call(
+                    Box::new(Name(pijama_ast::Name("g")).loc()),
+                    vec![Name(pijama_ast::Name("c")).loc()]
+                        .into_iter()
+                        .collect(),
+                )
+                .loc(),
+            ),
+        )
+        .loc(),
+        BinaryOp(
+            Pipe,
+            Box::new(Name(pijama_ast::Name("x")).loc()),
+            Box::new(
+                AnonFn(
+                    vec![TyAnnotation {
+                        item: pijama_ast::Name("y").loc(),
+                        ty: Ty::Missing.loc(),
+                    }],
+                    TyAnnotation {
+                        item: vec![Name(pijama_ast::Name("y")).loc()]
+                            .into_iter()
+                            .collect::<Block<'_>>()
+                            .loc(),
+                        ty: Ty::Missing.loc(),
+                    },
+                )
+                .loc(),
+            ),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "precedence");
+    assert_eq!(expected[1], result[1], "anonymous function");
+    Ok(())
+}
+
+#[test]
+fn call() -> LangResult<()> {
     let input = include_str!("call.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -512,7 +1052,79 @@ fn call() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn fn_def() -> LangResult<'static, ()> {
+fn ascription() -> LangResult<()> {
+    let input = include_str!("ascription.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Ascription(Box::new(Name(pijama_ast::Name("x")).loc()), Ty::Int.loc()).loc(),
+        Ascription(
+            Box::new(Name(pijama_ast::Name("x")).loc()),
+            Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Bool)).loc(),
+        )
+        .loc(),
+        Ascription(
+            Box::new(List(Block::default()).loc()),
+            Ty::List(Box::new(Ty::Bool)).loc(),
+        )
+        .loc(),
+        Call(
+            Box::new(
+                Ascription(
+                    Box::new(Name(pijama_ast::Name("f")).loc()),
+                    Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)).loc(),
+                )
+                .loc(),
+            ),
+            vec![Name(pijama_ast::Name("x")).loc()]
+                .into_iter()
+                .collect::<Block<'_>>(),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "spaces");
+    assert_eq!(expected[1], result[1], "function type");
+    assert_eq!(expected[2], result[2], "line breaks");
+    assert_eq!(expected[3], result[3], "callee");
+    Ok(())
+}
+
+#[test]
+fn doc_comment() -> LangResult<()> {
+    let input = include_str!("doc_comment.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        FnDef(
+            pijama_ast::Name("foo").loc(),
+            vec![],
+            TyAnnotation {
+                item: Block::default().loc(),
+                ty: Ty::Missing.loc(),
+            },
+            vec!["Does nothing."],
+        )
+        .loc(),
+        FnDef(
+            pijama_ast::Name("bar").loc(),
+            vec![],
+            TyAnnotation {
+                item: Block::default().loc(),
+                ty: Ty::Missing.loc(),
+            },
+            vec!["Does nothing", "", "  twice."],
+        )
+        .loc(),
+        Name(pijama_ast::Name("bar")).loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "single line");
+    assert_eq!(expected[1], result[1], "many lines");
+    assert_eq!(expected[2], result[2], "undocumented");
+    Ok(())
+}
+
+#[test]
+fn fn_def() -> LangResult<()> {
     let input = include_str!("fn_def.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -523,6 +1135,7 @@ fn fn_def() -> LangResult<'static, ()> {
                 item: vec![].into_iter().collect::<Block<'_>>().loc(),
                 ty: Ty::Missing.loc(),
             },
+            vec![],
         )
         .loc(),
         FnDef(
@@ -538,6 +1151,7 @@ fn fn_def() -> LangResult<'static, ()> {
                     .loc(),
                 ty: Ty::Missing.loc(),
             },
+            vec![],
         )
         .loc(),
         FnDef(
@@ -554,6 +1168,7 @@ fn fn_def() -> LangResult<'static, ()> {
                 .loc(),
                 ty: Ty::Unit.loc(),
             },
+            vec![],
         )
         .loc(),
         FnDef(
@@ -578,6 +1193,7 @@ fn fn_def() -> LangResult<'static, ()> {
                 .loc(),
                 ty: Ty::Missing.loc(),
             },
+            vec![],
         )
         .loc(),
         AnonFn(
@@ -608,6 +1224,28 @@ fn fn_def() -> LangResult<'static, ()> {
             },
         )
         .loc(),
+        FnDef(
+            pijama_ast::Name("foo").loc(),
+            vec![
+                TyAnnotation {
+                    item: pijama_ast::Name("x").loc(),
+                    ty: Ty::Missing.loc(),
+                },
+                TyAnnotation {
+                    item: pijama_ast::Name("y").loc(),
+                    ty: Ty::Int.loc(),
+                },
+            ],
+            TyAnnotation {
+                item: vec![Name(pijama_ast::Name("x")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                ty: Ty::Missing.loc(),
+            },
+            vec![],
+        )
+        .loc(),
     ];
 
     assert_eq!(expected[0], result[0], "nullary def");
@@ -616,11 +1254,12 @@ fn fn_def() -> LangResult<'static, ()> {
     assert_eq!(expected[3], result[3], "long body");
     assert_eq!(expected[4], result[4], "nameless");
     assert_eq!(expected[5], result[5], "nameless with space");
+    assert_eq!(expected[6], result[6], "missing param type");
     Ok(())
 }
 
 #[test]
-fn precedence() -> LangResult<'static, ()> {
+fn precedence() -> LangResult<()> {
     let input = include_str!("precedence.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -685,7 +1324,7 @@ fn precedence() -> LangResult<'static, ()> {
 }
 
 #[test]
-fn cmp_and_shift() -> LangResult<'static, ()> {
+fn cmp_and_shift() -> LangResult<()> {
     let input = include_str!("cmp_and_shift.pj");
     let result = parse(input)?.content;
     let expected = vec![
@@ -756,3 +1395,218 @@ fn cmp_and_shift() -> LangResult<'static, ()> {
     assert_eq!(expected[2], result[2], "brackets");
     Ok(())
 }
+
+#[test]
+fn enum_def() -> LangResult<()> {
+    let input = include_str!("enum_def.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        EnumDef(
+            pijama_ast::Name("Bit").loc(),
+            vec![
+                Variant {
+                    name: pijama_ast::Name("One").loc(),
+                    fields: vec![],
+                },
+                Variant {
+                    name: pijama_ast::Name("Zero").loc(),
+                    fields: vec![],
+                },
+            ],
+        )
+        .loc(),
+        EnumDef(
+            pijama_ast::Name("Shape").loc(),
+            vec![
+                Variant {
+                    name: pijama_ast::Name("Circle").loc(),
+                    fields: vec![Ty::Int.loc()],
+                },
+                Variant {
+                    name: pijama_ast::Name("Rect").loc(),
+                    fields: vec![Ty::Int.loc(), Ty::Int.loc()],
+                },
+                Variant {
+                    name: pijama_ast::Name("Empty").loc(),
+                    fields: vec![],
+                },
+            ],
+        )
+        .loc(),
+        FnDef(
+            pijama_ast::Name("area").loc(),
+            vec![TyAnnotation {
+                item: pijama_ast::Name("shape").loc(),
+                ty: Ty::Adt("Shape".to_owned(), Vec::new()).loc(),
+            }],
+            TyAnnotation {
+                item: vec![Literal(pijama_ast::Literal::Number(0)).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                ty: Ty::Int.loc(),
+            },
+            vec![],
+        )
+        .loc(),
+        FnDef(
+            pijama_ast::Name("first").loc(),
+            vec![TyAnnotation {
+                item: pijama_ast::Name("res").loc(),
+                ty: Ty::Adt(
+                    "Result".to_owned(),
+                    vec![Ty::Int, Ty::List(Box::new(Ty::Var("a".to_owned())))],
+                )
+                .loc(),
+            }],
+            TyAnnotation {
+                item: vec![Literal(pijama_ast::Literal::Number(0)).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                ty: Ty::Int.loc(),
+            },
+            vec![],
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "variants without fields");
+    assert_eq!(expected[1], result[1], "variants with fields");
+    assert_eq!(expected[2], result[2], "enum type annotation");
+    assert_eq!(expected[3], result[3], "enum type arguments");
+    Ok(())
+}
+
+#[test]
+fn match_expr() -> LangResult<()> {
+    let input = include_str!("match_expr.pj");
+    let result = parse(input)?.content;
+    let expected = [
+        Match(
+            Box::new(Name(pijama_ast::Name("x")).loc()),
+            vec![Arm {
+                pattern: Pattern::Wildcard.loc(),
+                body: vec![Name(pijama_ast::Name("y")).loc()]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+            }],
+        )
+        .loc(),
+        Match(
+            Box::new(Name(pijama_ast::Name("shape")).loc()),
+            vec![
+                Arm {
+                    pattern: Pattern::Variant(
+                        pijama_ast::Name("Rect").loc(),
+                        vec![pijama_ast::Name("w").loc(), pijama_ast::Name("h").loc()],
+                    )
+                    .loc(),
+                    body: vec![
+                        Name(pijama_ast::Name("w")).loc(),
+                        Name(pijama_ast::Name("h")).loc(),
+                    ]
+                    .into_iter()
+                    .collect::<Block<'_>>()
+                    .loc(),
+                },
+                Arm {
+                    pattern: Pattern::Literal(pijama_ast::Literal::Number(1)).loc(),
+                    body: vec![Name(pijama_ast::Name("z")).loc()]
+                        .into_iter()
+                        .collect::<Block<'_>>()
+                        .loc(),
+                },
+            ],
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "wildcard");
+    assert_eq!(expected[1], result[1], "variant and literal");
+    Ok(())
+}
+
+#[test]
+fn block() -> LangResult<()> {
+    let input = include_str!("block.pj");
+    let result = parse(input)?.content;
+    let x = || Name(pijama_ast::Name("x")).loc();
+    let y = || Name(pijama_ast::Name("y")).loc();
+    let expected = [
+        LetBind(
+            TyAnnotation {
+                item: pijama_ast::Name("x").loc(),
+                ty: Ty::Missing.loc(),
+            },
+            Box::new(
+                Block(Block::from([Literal(pijama_ast::Literal::Number(1)).loc()]).loc()).loc(),
+            ),
+        )
+        .loc(),
+        Call(
+            Box::new(Name(pijama_ast::Name("f")).loc()),
+            Block::from([Block(
+                Block::from([
+                    LetBind(
+                        TyAnnotation {
+                            item: pijama_ast::Name("y").loc(),
+                            ty: Ty::Missing.loc(),
+                        },
+                        Box::new(Literal(pijama_ast::Literal::Number(2)).loc()),
+                    )
+                    .loc(),
+                    y(),
+                ])
+                .loc(),
+            )
+            .loc()]),
+        )
+        .loc(),
+        While(Branch {
+            cond: Block::from([x()]).loc(),
+            body: Block::from([x()]).loc(),
+        })
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "let initializer");
+    assert_eq!(expected[1], result[1], "call argument");
+    assert_eq!(expected[2], result[2], "do at the start of a line");
+    Ok(())
+}
+
+#[test]
+fn operator() -> LangResult<()> {
+    let input = include_str!("operator.pj");
+    let result = parse(input)?.content;
+    let x = || Name(pijama_ast::Name("x")).loc();
+    let number = |n| Literal(pijama_ast::Literal::Number(n)).loc();
+    let sum = || BinaryOp(Add, Box::new(x()), Box::new(number(2))).loc();
+    let expected = [
+        Operator(Add, None, None).loc(),
+        Operator(Sub, None, Some(Box::new(number(1)))).loc(),
+        Operator(
+            Add,
+            Some(Box::new(
+                BinaryOp(Mul, Box::new(x()), Box::new(number(2))).loc(),
+            )),
+            None,
+        )
+        .loc(),
+        Operator(Mul, Some(Box::new(sum())), None).loc(),
+        Call(
+            Box::new(Operator(Lte, None, None).loc()),
+            Block::from([x(), Name(pijama_ast::Name("y")).loc()]),
+        )
+        .loc(),
+    ];
+
+    assert_eq!(expected[0], result[0], "operator");
+    assert_eq!(expected[1], result[1], "missing first operand");
+    assert_eq!(expected[2], result[2], "missing second operand");
+    assert_eq!(expected[3], result[3], "operand in brackets");
+    assert_eq!(expected[4], result[4], "call");
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::include_str;
+
+use pijama_ast::{Located, Node};
+use pijama_core::{
+    mir::LowerError,
+    parser::{parse, parse_recovering},
+};
+use pijama_driver::{check, LangError};
+
+use crate::run;
+
+/// Returns the code of each node of `input` that could not be parsed.
+fn unparsed<'a>(input: &'a str, nodes: &[Located<Node<'_>>]) -> Vec<&'a str> {
+    nodes
+        .iter()
+        .filter(|node| node.content == Node::Error)
+        .map(|node| &input[node.loc.start..node.loc.end])
+        .collect()
+}
+
+#[test]
+fn program() {
+    let input = include_str!("program.pj");
+    let (block, errors) = parse_recovering(input);
+    let nodes: Vec<_> = block.content.into_iter().collect();
+
+    assert_eq!(vec!["x = 1 +", "y = * 3"], unparsed(input, &nodes));
+    assert_eq!(4, nodes.len());
+    assert!(matches!(nodes[1].content, Node::FnDef(..)));
+    assert!(matches!(nodes[3].content, Node::Call(..)));
+
+    assert_eq!(2, errors.len());
+    assert_eq!(parse(input).unwrap_err(), errors[0]);
+    assert_eq!(9, errors[1].line());
+}
+
+#[test]
+fn valid_program() {
+    let input = "x = 1\nfn f(y: Int): Int do\n    y + x\nend\nf(2)";
+    let (block, errors) = parse_recovering(input);
+    assert!(errors.is_empty());
+    assert_eq!(parse(input).unwrap(), block);
+}
+
+#[test]
+fn unclosed() {
+    // The missing `end` closes the function at the end of the input.
+    let input = include_str!("unclosed.pj");
+    let (block, errors) = parse_recovering(input);
+    let nodes: Vec<_> = block.content.into_iter().collect();
+    assert_eq!(vec![input.trim()], unparsed(input, &nodes));
+    assert_eq!(vec![parse(input).unwrap_err()], errors);
+}
+
+#[test]
+fn comment_after_start() {
+    let input = "x = 1\n# A comment.\ny = 2";
+    let (block, errors) = parse_recovering(input);
+    let nodes: Vec<_> = block.content.into_iter().collect();
+    assert_eq!(vec!["# A comment."], unparsed(input, &nodes));
+    assert_eq!(vec![parse(input).unwrap_err()], errors);
+}
+
+#[test]
+fn do_blocks() {
+    // The `do` blocks have their own `end`, unlike the `do` of conditionals.
+    let input = "x = do\n    1\nend\nif x > 0\ndo\n    y = do 2 end\nend\nz = * 3";
+    let (block, errors) = parse_recovering(input);
+    let nodes: Vec<_> = block.content.into_iter().collect();
+    assert_eq!(vec!["z = * 3"], unparsed(input, &nodes));
+    assert_eq!(3, nodes.len());
+    assert_eq!(1, errors.len());
+}
+
+#[test]
+fn assignments_are_resolved() {
+    let input = "let mut x = 1\nx = 2 +\nx = 3";
+    let (block, errors) = parse_recovering(input);
+    assert_eq!(1, errors.len());
+    assert!(matches!(block.content[2].content, Node::Assign(..)));
+}
+
+#[test]
+fn every_error_is_reported() {
+    let input = include_str!("program.pj");
+    match run(input).unwrap_err() {
+        LangError::Multiple(errors) => {
+            assert_eq!(2, errors.len());
+            assert!(errors
+                .iter()
+                .all(|error| matches!(error, LangError::Parse(_))));
+        }
+        err => panic!("expected several parsing errors, found {:?}", err),
+    }
+}
+
+#[test]
+fn error_nodes_are_not_lowered() {
+    let input = include_str!("program.pj");
+    let (block, _) = parse_recovering(input);
+    assert!(matches!(
+        check(block),
+        Err(LangError::Lower(LowerError::Unparsed(_)))
+    ));
+}
@@ -0,0 +1,55 @@
+use pijama_core::ty::{parse, Ty};
+
+fn arrow(t1: Ty, t2: Ty) -> Ty {
+    Ty::Arrow(Box::new(t1), Box::new(t2))
+}
+
+#[test]
+fn base() {
+    assert_eq!(Ok(Ty::Int), parse("Int"));
+    assert_eq!(Ok(Ty::String), parse("String"));
+    assert_eq!(Ok(Ty::List(Box::new(Ty::Bool))), parse("[Bool]"));
+    assert_eq!(Ok(Ty::Chan(Box::new(Ty::Int))), parse("Chan(Int)"));
+    assert_eq!(
+        Ok(Ty::Adt(
+            "Result".to_owned(),
+            Box::new([Ty::Int, Ty::String])
+        )),
+        parse("Result(Int, String)")
+    );
+}
+
+#[test]
+fn arrow_is_right_associative() {
+    let expected = arrow(Ty::Int, arrow(Ty::Int, Ty::Bool));
+    assert_eq!(Ok(expected.clone()), parse("Int -> Int -> Bool"));
+    assert_eq!(Ok(expected), parse("Int -> (Int -> Bool)"));
+    assert_eq!(
+        Ok(arrow(arrow(Ty::Int, Ty::Int), Ty::Bool)),
+        parse("(Int -> Int) -> Bool")
+    );
+}
+
+#[test]
+fn type_variables() {
+    assert_eq!(
+        Ok(arrow(
+            Ty::List(Box::new(Ty::Var(0))),
+            arrow(Ty::Var(1), Ty::Var(0))
+        )),
+        parse("[a] -> b -> a")
+    );
+}
+
+#[test]
+fn surrounding_spaces() {
+    assert_eq!(Ok(arrow(Ty::Unit, Ty::Int)), parse("  Unit->Int\n"));
+}
+
+#[test]
+fn not_a_single_type() {
+    assert!(parse("").is_err());
+    assert!(parse("Int Bool").is_err());
+    assert!(parse("Int ->").is_err());
+    assert!(parse("x: Int").is_err());
+}
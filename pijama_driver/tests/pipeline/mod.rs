@@ -0,0 +1,120 @@
+use pijama_core::{
+    lir::Pass as LirPass,
+    mir::{Pass as MirPass, Term as MirTerm},
+    parser::parse,
+};
+use pijama_driver::{
+    pipeline::{compile, CompiledProgram, Options, PassId, Stage},
+    run_with_options,
+    timings::{Phase, Timings},
+};
+
+use crate::machine_builder;
+
+fn evaluate(program: CompiledProgram) -> String {
+    let mut output = Vec::default();
+    machine_builder(&mut output)
+        .build()
+        .evaluate(program.lir)
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+fn with_opt_level(opt_level: u8) -> Options {
+    Options {
+        opt_level,
+        ..Options::default()
+    }
+}
+
+#[test]
+fn no_passes_by_default() {
+    let input = include_str!("folding.pj");
+    let program = compile(input, &Options::default()).unwrap();
+    let mir = MirTerm::from_ast(parse(input).unwrap()).unwrap();
+    assert_eq!(mir.content.to_string(), program.mir.content.to_string());
+    assert!(program.stages.is_empty());
+    assert_eq!("7\n", evaluate(program));
+}
+
+#[test]
+fn opt_levels() {
+    assert!(PassId::for_opt_level(0).is_empty());
+    let program = compile(include_str!("folding.pj"), &with_opt_level(1)).unwrap();
+    assert_eq!("(print 7)", program.mir.content.to_string());
+
+    let input = include_str!("power.pj");
+    let program = compile(input, &with_opt_level(2)).unwrap();
+    assert_eq!("(print 1024)", program.mir.content.to_string());
+    for opt_level in 0..=3 {
+        let program = compile(input, &with_opt_level(opt_level)).unwrap();
+        assert_eq!("1024\n", evaluate(program));
+    }
+}
+
+#[test]
+fn stages() {
+    let options = Options {
+        opt_level: 1,
+        passes: vec![PassId::Lir(LirPass::Cps)],
+        keep_stages: true,
+        ..Options::default()
+    };
+    let program = compile(include_str!("folding.pj"), &options).unwrap();
+    let passes: Vec<_> = program.stages.iter().map(|(pass, _)| *pass).collect();
+    assert_eq!(options.all_passes(), passes);
+    match &program.stages[1] {
+        (PassId::Mir(MirPass::ConstantFolding), Stage::Mir(mir)) => {
+            assert_eq!("(print 7)", mir.content.to_string())
+        }
+        stage => panic!("Expected the MIR after constant folding, found {:?}", stage),
+    }
+    assert!(matches!(program.stages[3], (_, Stage::Lir(_))));
+    assert_eq!("7\n", evaluate(program));
+}
+
+#[test]
+fn mir_passes_run_first() {
+    let options = Options {
+        passes: vec![
+            PassId::Lir(LirPass::Cps),
+            PassId::Mir(MirPass::ConstantFolding),
+        ],
+        keep_stages: true,
+        ..Options::default()
+    };
+    let program = compile(include_str!("folding.pj"), &options).unwrap();
+    let passes: Vec<_> = program.stages.iter().map(|(pass, _)| *pass).collect();
+    assert_eq!(
+        vec![
+            PassId::Mir(MirPass::ConstantFolding),
+            PassId::Lir(LirPass::Cps)
+        ],
+        passes
+    );
+}
+
+#[test]
+fn prelude() {
+    let input = "print(unwrap_or(None, 1))";
+    assert!(compile(input, &Options::default()).is_ok());
+    let options = Options {
+        prelude: false,
+        ..Options::default()
+    };
+    assert!(compile(input, &options).is_err());
+}
+
+#[test]
+fn optimization_timings() {
+    let input = include_str!("folding.pj");
+    for (opt_level, optimized) in [(0, false), (1, true)] {
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output).build();
+        let mut timings = Timings::default();
+        let options = with_opt_level(opt_level);
+        run_with_options(input, machine, &options, &mut timings, &mut Vec::new()).unwrap();
+        assert_eq!(optimized, timings.get(Phase::Optimization).is_some());
+        assert_eq!("7\n", String::from_utf8(output).unwrap());
+    }
+}
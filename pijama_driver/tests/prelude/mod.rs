@@ -0,0 +1,56 @@
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::{check, emit_js, prelude::PRELUDE, LangError, LangResult, Program};
+
+use crate::run;
+
+#[test]
+fn prelude_is_valid() {
+    assert_eq!(Ok(Ty::Unit), check(PRELUDE));
+}
+
+#[test]
+fn option() -> LangResult<()> {
+    let input = include_str!("option.pj");
+    let output = run(input)?;
+    assert_eq!("108\n", output);
+    Ok(())
+}
+
+#[test]
+fn result() {
+    let input = include_str!("result.pj");
+    assert_eq!(Ok(Ty::Bool), check(input));
+}
+
+#[test]
+fn shadowing() {
+    let input = include_str!("shadowing.pj");
+    assert_eq!(Ok(Ty::List(Box::new(Ty::Int))), check(input));
+}
+
+#[test]
+fn unused_definitions_are_not_emitted() -> LangResult<()> {
+    assert_eq!("1n + 2n;\n", emit_js("1 + 2")?);
+    let output = emit_js("is_some(None)")?;
+    assert!(output.contains("const is_some"), "{}", output);
+    assert!(
+        !output.contains("unwrap_or") && !output.contains("Ok"),
+        "{}",
+        output
+    );
+    Ok(())
+}
+
+#[test]
+fn without_prelude() {
+    let result = Program::compile_without_prelude("unwrap_or(Some(1), 0)");
+    assert!(
+        matches!(
+            &result,
+            Err(LangError::Multiple(errors))
+                if errors.iter().all(|error| matches!(error, LangError::Ty(TyError::Unbounded(_))))
+        ),
+        "{:?}",
+        result
+    );
+}
@@ -0,0 +1,46 @@
+use pijama_core::{lir::Term, mir::Term as MirTerm, parser::parse};
+
+fn lir(input: &str) -> Term {
+    Term::from_mir(MirTerm::from_ast(parse(input).unwrap()).unwrap())
+}
+
+#[test]
+fn one_line() {
+    let term = lir(include_str!("program.pj"));
+    assert_eq!(
+        "(let c = (λa. (λb. (a + b))) in (let d = (c 1 2) in (print d)))",
+        term.to_pretty_string(80)
+    );
+    assert_eq!(term.to_pretty_string(80), term.pretty().to_string());
+}
+
+#[test]
+fn narrow() {
+    let term = lir(include_str!("program.pj"));
+    let expected = "(let c =
+  (λa.
+    (λb. (a + b))) in
+(let d =
+  (c 1 2) in
+(print d)))";
+    assert_eq!(expected, term.to_pretty_string(20));
+    assert_eq!(expected, format!("{:20}", term.pretty()));
+}
+
+#[test]
+fn matches() {
+    let term = lir(include_str!("shape.pj"));
+    let expected = "(let c =
+  (λa.
+    (λb. Rect(a, b))) in
+(let d =
+  Empty in
+(let h =
+  (λe.
+    (match e with
+      | #0(f, g) =>
+        (f * g)
+      | #1() => 0)) in
+(h (c 2 3)))))";
+    assert_eq!(expected, term.to_pretty_string(20));
+}
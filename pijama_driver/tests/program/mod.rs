@@ -0,0 +1,136 @@
+use std::thread;
+
+use pijama_ast::Int;
+use pijama_core::{
+    machine::{env::Env, MachineBuilder, Value},
+    ty::{Ty, TyError},
+};
+use pijama_driver::{LangError, Program};
+
+fn program() -> Program<'static> {
+    Program::compile(include_str!("shapes.pj")).unwrap()
+}
+
+#[test]
+fn run() {
+    let program = program();
+    assert_eq!(&Ty::Int, program.ty());
+    assert_eq!(Ok(Some(Value::Int(1))), program.run());
+}
+
+#[test]
+fn call() {
+    let program = program();
+    let args = [Value::Int(3), Value::Int(4)];
+    assert_eq!(Ok(Some(Value::Int(12))), program.call("area", &args));
+    assert_eq!(
+        Ok(Some(Value::Bool(false))),
+        program.call("is_square", &args)
+    );
+    assert_eq!(
+        Ok(Some(Value::Int(120))),
+        program.call("fact", &[Value::Int(5)])
+    );
+}
+
+#[test]
+fn call_partially_applied() {
+    assert_eq!(Ok(None), program().call("area", &[Value::Int(3)]));
+}
+
+#[test]
+fn call_wrong_type() {
+    let result = program().call("area", &[Value::Int(3), Value::Bool(true)]);
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn call_missing_function() {
+    let result = program().call("perimeter", &[Value::Int(3), Value::Int(4)]);
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Unbounded(_)))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn run_as() {
+    assert_eq!(Ok(1), program().run_as::<Int>());
+}
+
+#[test]
+fn run_as_wrong_type() {
+    let result = program().run_as::<bool>();
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn call_as() {
+    let program = program();
+    assert_eq!(Ok(12), program.call_as::<Int>("area", (3, 4)));
+    assert_eq!(Ok(true), program.call_as::<bool>("is_square", (2, 2)));
+    assert_eq!(Ok(120), program.call_as::<Int>("fact", (5,)));
+}
+
+#[test]
+fn collections() {
+    let input = "fn count(xs: [Int], s: String): Int do len(xs) + len(s) end\n[1.5, 2.5]";
+    let program = Program::compile(input).unwrap();
+    assert_eq!(Ok(vec![1.5, 2.5]), program.run_as::<Vec<f64>>());
+    let args: (Vec<Int>, String) = (vec![1, 2], "abc".to_owned());
+    assert_eq!(Ok(5), program.call_as::<Int>("count", args));
+}
+
+#[test]
+fn call_as_partially_applied() {
+    let result = program().call_as::<Int>("area", (3,));
+    assert!(
+        matches!(result, Err(LangError::Ty(TyError::Mismatch { .. }))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn concurrent_calls() {
+    let program = program();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|n| {
+                let program = &program;
+                scope.spawn(move || program.call("fact", &[Value::Int(n)]))
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        let expected: Vec<_> = [1, 1, 2, 6, 24, 120, 720, 5040]
+            .iter()
+            .map(|&n| Ok(Some(Value::Int(n))))
+            .collect();
+        assert_eq!(expected, results);
+    });
+}
+
+#[test]
+fn concurrent_runs_with_own_output() {
+    let program =
+        Program::compile("fn area(w: Int, h: Int): Int do w * h end\nprint(area(2, 3))").unwrap();
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let mut output = Vec::new();
+                let machine = MachineBuilder::new(Env::new(&mut output)).build();
+                assert_eq!(Ok(Some(Value::Unit)), program.run_with_machine(machine));
+                assert_eq!("6\n", String::from_utf8(output).unwrap());
+            });
+        }
+    });
+}
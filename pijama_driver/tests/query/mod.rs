@@ -0,0 +1,33 @@
+use pijama_ast::Location;
+use pijama_core::ty::Ty;
+use pijama_driver::query::type_at;
+
+#[test]
+fn innermost_term() {
+    let input = include_str!("program.pj");
+    // The `x` in `x + 1`.
+    assert_eq!(type_at(input, 13), Some((Ty::Int, Location::new(13, 14))));
+    // The `+` is only covered by the whole operation.
+    assert_eq!(type_at(input, 15), Some((Ty::Int, Location::new(13, 18))));
+    // The `inc` in `inc(2)`.
+    assert_eq!(
+        type_at(input, 28),
+        Some((
+            Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)),
+            Location::new(27, 30)
+        ))
+    );
+    // The `==` in `y == 3`.
+    assert_eq!(type_at(input, 36), Some((Ty::Bool, Location::new(34, 40))));
+}
+
+#[test]
+fn outside_of_the_program() {
+    let input = include_str!("program.pj");
+    assert_eq!(type_at(input, input.len()), None);
+}
+
+#[test]
+fn ill_typed_program() {
+    assert_eq!(type_at("x = 1\nx && true", 6), None);
+}
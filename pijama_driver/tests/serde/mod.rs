@@ -0,0 +1,65 @@
+use pijama_ast::{owned::OwnedBlock, Block, Located};
+use pijama_core::{
+    lir::Term as LirTerm,
+    mir::Term,
+    parser::parse,
+    ty::{ty_check, TyError},
+};
+
+#[test]
+fn ast_round_trip() {
+    let input = include_str!("program.pj");
+    let ast = parse(input).unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    let ast2: Located<Block> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ast, ast2);
+    assert_eq!(ast.loc, ast2.loc);
+}
+
+#[test]
+fn owned_ast_round_trip() {
+    let input = include_str!("program.pj");
+    let ast = pijama_ast::owned::block_to_owned(&parse(input).unwrap().content);
+    let json = serde_json::to_value(&ast).unwrap();
+    let ast2: OwnedBlock = serde_json::from_value(json).unwrap();
+    assert_eq!(ast, ast2);
+}
+
+#[test]
+fn mir_round_trip() {
+    let input = include_str!("program.pj");
+    let mir = Term::from_ast(parse(input).unwrap()).unwrap();
+    let json = serde_json::to_string(&mir).unwrap();
+    let mir2: Located<Term> = serde_json::from_str(&json).unwrap();
+    assert_eq!(mir.to_string(), mir2.to_string());
+    assert_eq!(mir.loc, mir2.loc);
+}
+
+#[test]
+fn lir_round_trip() {
+    let input = include_str!("program.pj");
+    let lir = LirTerm::from_mir(Term::from_ast(parse(input).unwrap()).unwrap());
+    let json = serde_json::to_value(&lir).unwrap();
+    let lir2: LirTerm = serde_json::from_value(json).unwrap();
+    assert_eq!(lir, lir2);
+}
+
+#[test]
+fn ty_error_round_trip() {
+    let input = "if 1 do true else false end";
+    let mir = Term::from_ast(parse(input).unwrap()).unwrap();
+    let err = ty_check(&mir).unwrap_err().remove(0);
+    let json = serde_json::to_string(&err).unwrap();
+    let err2: TyError = serde_json::from_str(&json).unwrap();
+    assert_eq!(err, err2);
+    assert_eq!(err.loc(), err2.loc());
+}
+
+#[test]
+fn parsing_error() {
+    let err = parse("fn foo(x: Int do x end").unwrap_err();
+    let json = serde_json::to_value(&err).unwrap();
+    assert_eq!(json["loc"]["start"], 14);
+    assert_eq!(json["line"], 1);
+    assert!(json["context"].is_string());
+}
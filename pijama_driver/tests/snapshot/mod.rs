@@ -0,0 +1,106 @@
+use pijama_ast::Location;
+use pijama_core::{
+    lir::Term,
+    machine::{
+        debug::{Control, EvalEvent},
+        Progress, RuntimeError, Snapshot,
+    },
+};
+use pijama_driver::pipeline::{compile, Options};
+
+use crate::machine_builder;
+
+fn lir(input: &str) -> Term {
+    compile(input, &Options::default()).unwrap().lir
+}
+
+/// Evaluates `input` with a new machine for every `fuel` steps, passing the snapshots between them
+/// as bytes.
+fn run_in_slices(input: &str, fuel: u64) -> (Term, String, usize) {
+    let mut output = Vec::default();
+    let mut slices = 1;
+    let mut progress = machine_builder(&mut output)
+        .with_fuel(fuel)
+        .build()
+        .evaluate_resumable(lir(input))
+        .unwrap();
+    loop {
+        match progress {
+            Progress::Paused(snapshot) => {
+                let snapshot = Snapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+                let machine = machine_builder(&mut output).with_fuel(fuel).build();
+                progress = { machine }.resume(snapshot).unwrap();
+                slices += 1;
+            }
+            Progress::Done(value) => {
+                return (value, String::from_utf8(output).unwrap(), slices);
+            }
+        }
+    }
+}
+
+#[test]
+fn resume_after_fuel() {
+    let inputs = [
+        include_str!("../eval/factorial.pj"),
+        include_str!("../eval/mutable_counter.pj"),
+        include_str!("../eval/mutable_snapshot.pj"),
+        include_str!("../eval/print_print.pj"),
+        include_str!("../eval/string_interpolation.pj"),
+        include_str!("../eval/threads.pj"),
+    ];
+    for input in inputs.iter() {
+        let mut output = Vec::default();
+        let value = machine_builder(&mut output)
+            .build()
+            .evaluate(lir(input))
+            .unwrap();
+        let (resumed, resumed_output, slices) = run_in_slices(input, 1);
+        assert!(slices > 1, "{}", input);
+        assert_eq!(value, resumed, "{}", input);
+        assert_eq!(String::from_utf8(output).unwrap(), resumed_output);
+    }
+}
+
+#[test]
+fn resume_after_hook() {
+    let input = include_str!("../eval/factorial.pj");
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    machine.set_hook(|event| match event {
+        EvalEvent::Enter { .. } => Control::Pause,
+        _ => Control::Continue,
+    });
+    let snapshot = match machine.evaluate_resumable(lir(input)) {
+        Ok(Progress::Paused(snapshot)) => snapshot,
+        result => panic!("{:?}", result),
+    };
+    assert_eq!("", String::from_utf8(output).unwrap());
+
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    assert!(matches!(machine.resume(snapshot), Ok(Progress::Done(_))));
+    assert_eq!("3628800\n", String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn errors_are_not_paused() {
+    let input = include_str!("../eval/division_by_zero.pj");
+    let mut output = Vec::default();
+    let result = machine_builder(&mut output)
+        .with_fuel(1000)
+        .build()
+        .evaluate_resumable(lir(input));
+    assert_eq!(
+        Err(RuntimeError::DivisionByZero(Location::new(47, 60))),
+        result
+    );
+}
+
+#[test]
+fn resume_with_non_finite_floats() {
+    let input = "nan = 0.0 / 0.0\ninf = 1.0 / 0.0\nprint(nan)\nprint(inf)\nprint(-inf)";
+    let (_, output, slices) = run_in_slices(input, 1);
+    assert!(slices > 1);
+    assert_eq!("NaN\ninf\n-inf\n", output);
+}
@@ -0,0 +1,49 @@
+use pijama_core::{
+    lir::Term,
+    machine::{RuntimeError, Stats},
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+use crate::machine_builder;
+
+fn lir(input: &str) -> Term {
+    Term::from_mir(MirTerm::from_ast(parse(input).unwrap()).unwrap())
+}
+
+fn evaluate(input: &str, fuel: Option<u64>) -> (Result<Term, RuntimeError>, Stats) {
+    let mut output = Vec::default();
+    let mut builder = machine_builder(&mut output);
+    if let Some(fuel) = fuel {
+        builder = builder.with_fuel(fuel);
+    }
+    builder.build().evaluate_with_stats(lir(input))
+}
+
+#[test]
+fn counters() {
+    let (result, stats) = evaluate(include_str!("program.pj"), None);
+    assert!(result.is_ok());
+    // `twice` takes two arguments and applies `inc` twice. The allocations are the cell of
+    // `total`, the string returned by `to_string` and the concatenated string.
+    assert_eq!(
+        Stats {
+            steps: 18,
+            applications: 4,
+            max_depth: 10,
+            allocations: 3,
+        },
+        stats
+    );
+}
+
+#[test]
+fn steps_are_the_fuel() {
+    let input = include_str!("program.pj");
+    let (_, stats) = evaluate(input, None);
+    assert!(evaluate(input, Some(stats.steps)).0.is_ok());
+
+    let (result, partial) = evaluate(input, Some(stats.steps - 1));
+    assert!(matches!(result, Err(RuntimeError::OutOfFuel(_))));
+    assert_eq!(stats.steps - 1, partial.steps);
+}
@@ -0,0 +1,71 @@
+use pijama_ast::Location;
+use pijama_driver::{
+    check,
+    suggest::{suggest, Suggestion},
+};
+use serde_json::json;
+
+/// Returns the suggestion for the error in `input`, checking that applying it fixes the program.
+fn fix(input: &str) -> Option<Suggestion> {
+    let error = check(input).unwrap_err();
+    let suggestion = suggest(input, &error)?;
+    let fixed = suggestion.apply(input);
+    assert!(check(&fixed).is_ok(), "{}", fixed);
+    Some(suggestion)
+}
+
+#[test]
+fn missing_end() {
+    let input = include_str!("missing_end.pj");
+    let suggestion = fix(input).unwrap();
+    assert_eq!("insert `end`", suggestion.message);
+    assert_eq!(Location::new(input.len(), input.len()), suggestion.loc);
+}
+
+#[test]
+fn missing_return_ty() {
+    let input = include_str!("missing_return_ty.pj");
+    let suggestion = fix(input).unwrap();
+    assert_eq!("insert `: Int`", suggestion.message);
+    assert_eq!(Location::new(20, 20), suggestion.loc);
+}
+
+#[test]
+fn ambiguous_return_ty() {
+    let input = "fn forever(n: Int) do forever(n) end\nunit";
+    assert_eq!(None, fix(input));
+}
+
+#[test]
+fn misspelled_name() {
+    let input = include_str!("misspelled_name.pj");
+    let suggestion = fix(input).unwrap();
+    assert_eq!("replace with `total`", suggestion.message);
+    assert_eq!(Location::new(27, 32), suggestion.loc);
+    assert_eq!("total", suggestion.replacement);
+}
+
+#[test]
+fn short_names_are_not_replaced() {
+    assert_eq!(None, fix("x = 1\nprint(y)"));
+}
+
+#[test]
+fn serialize() {
+    let suggestion = fix("total = 1\nprint(totl)").unwrap();
+    assert_eq!(
+        json!({
+            "message": "replace with `total`",
+            "loc": { "start": 16, "end": 20 },
+            "replacement": "total",
+        }),
+        serde_json::to_value(&suggestion).unwrap()
+    );
+}
+
+#[test]
+fn names_out_of_scope_are_not_suggested() {
+    let input = "fn area(length: Int): Int do length * length end\nprint(lenght)";
+    let error = check(input).unwrap_err();
+    assert_eq!(None, suggest(input, &error));
+}
@@ -0,0 +1,41 @@
+use pijama_driver::{
+    run_with_timings,
+    timings::{Phase, Timings},
+    LangError, LangResult,
+};
+
+use crate::machine_builder;
+
+fn phases(input: &str) -> (LangResult<()>, Vec<Phase>) {
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output).build();
+    let mut timings = Timings::default();
+    let result = run_with_timings(input, machine, &mut timings, &mut Vec::new());
+    (result, timings.iter().map(|(phase, _)| *phase).collect())
+}
+
+#[test]
+fn every_phase_is_recorded() {
+    let (result, phases) = phases("print(1 + 2)");
+    assert!(result.is_ok());
+    assert_eq!(Some(&Phase::Parsing), phases.first());
+    assert_eq!(Some(&Phase::Evaluation), phases.last());
+    assert!(phases.contains(&Phase::TypeChecking));
+}
+
+#[test]
+fn only_run_phases_are_recorded() {
+    let (result, phases) = phases("print(1 + true)");
+    assert!(matches!(result, Err(LangError::Ty(_))));
+    assert_eq!(
+        vec![Phase::Parsing, Phase::Lowering, Phase::TypeChecking],
+        phases
+    );
+}
+
+#[test]
+fn parse_errors_stop_the_timings() {
+    let (result, phases) = phases("print(1 +");
+    assert!(matches!(result, Err(LangError::Parse(_))));
+    assert_eq!(vec![Phase::Parsing], phases);
+}
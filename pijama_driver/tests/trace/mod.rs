@@ -0,0 +1,53 @@
+use pijama_ast::Location;
+use pijama_core::{
+    lir::Term,
+    machine::{RuntimeError, Stats},
+    mir::Term as MirTerm,
+    parser::parse,
+};
+
+use crate::machine_builder;
+
+fn lir(input: &str) -> Term {
+    Term::from_mir(MirTerm::from_ast(parse(input).unwrap()).unwrap())
+}
+
+#[test]
+fn steps() {
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    let steps: Vec<_> = machine
+        .steps(lir(include_str!("program.pj")))
+        .map(|term| term.to_string())
+        .collect();
+    assert_eq!(
+        vec!["(((λ. (_0 + 1)) 2) * 3)", "((2 + 1) * 3)", "(3 * 3)", "9"],
+        steps
+    );
+}
+
+#[test]
+fn one_item_per_step() {
+    let input = include_str!("../stats/program.pj");
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    let (_, Stats { steps, .. }) = machine.evaluate_with_stats(lir(input));
+
+    let mut traced = Vec::default();
+    let mut machine = machine_builder(&mut traced).build();
+    assert_eq!(steps, machine.steps(lir(input)).count() as u64);
+    assert_eq!(output, traced);
+}
+
+#[test]
+fn errors_end_the_steps() {
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output).build();
+    let mut steps = machine.steps(lir("print(1)\nprint(1 / 0)\nprint(2)"));
+    assert_eq!(2, steps.by_ref().count());
+    assert_eq!(
+        Some(&RuntimeError::DivisionByZero(Location::new(15, 20))),
+        steps.error()
+    );
+    assert_eq!(b"1\n", &output[..]);
+}
@@ -6,8 +6,16 @@ use pijama_driver::LangError;
 test_type!(
     wrong_type_minus,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+
+test_type!(
+    wrong_type_bit_not,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     }))
 );
 
@@ -15,8 +23,8 @@ test_type!(
 test_type_for_all_integer_binops!(
     mixed_types_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     })),
     OPERATOR
 );
@@ -25,8 +33,8 @@ test_type_for_all_integer_binops!(
 test_type_for_all_integer_binops!(
     wrong_type_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     })),
     OPERATOR
 );
@@ -6,7 +6,25 @@ use pijama_driver::LangError;
 test_type!(
     bind_bool_to_int,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     }))
 );
+test_type!(
+    assign_other_type,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+test_type!(
+    ascription_other_type,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+test_type!(
+    assign_immutable_in_loop,
+    Err(LangError::Ty(TyError::Immutable("count".to_owned().loc())))
+);
@@ -0,0 +1,20 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    arithmetic,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Char.loc())
+    }))
+);
+
+test_type!(
+    mixed_comparison,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Char.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
@@ -9,8 +9,8 @@ use pijama_driver::LangError;
 test_type_for_all_comparision_binops!(
     wrong_type_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     })),
     OPERATOR
 );
@@ -19,8 +19,8 @@ test_type_for_all_comparision_binops!(
 test_type_for_all_equality_binops!(
     mixed_type_int_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     })),
     OPERATOR
 );
@@ -29,8 +29,8 @@ test_type_for_all_equality_binops!(
 test_type_for_all_equality_binops!(
     mixed_type_bool_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     })),
     OPERATOR
 );
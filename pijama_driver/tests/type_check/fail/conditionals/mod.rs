@@ -6,14 +6,21 @@ use pijama_driver::LangError;
 test_type!(
     wrong_type_cond_input,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     }))
 );
 test_type!(
     mixed_types_cond_result,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+test_type!(
+    cond_without_else_not_unit,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Unit.loc()),
+        found: Box::new(Ty::Int.loc())
     }))
 );
@@ -0,0 +1,65 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    field_count,
+    Err(LangError::Ty(TyError::FieldCount {
+        variant: "Rect".to_owned().loc(),
+        expected: 2,
+        found: 1
+    }))
+);
+
+test_type!(
+    unknown_variant,
+    Err(LangError::Ty(TyError::Unbounded(
+        "Triangle".to_owned().loc()
+    )))
+);
+
+test_type!(
+    arm_mismatch,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+
+test_type!(
+    literal_pattern_mismatch,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Adt("Bit".to_owned(), Box::new([])).loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+
+test_type!(
+    generic_mismatch,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+
+test_type!(
+    non_exhaustive_variants,
+    Err(LangError::Ty(TyError::NonExhaustive(
+        vec!["Rect(_, _)".to_owned(), "Empty".to_owned()].loc()
+    )))
+);
+
+test_type!(
+    non_exhaustive_bool,
+    Err(LangError::Ty(TyError::NonExhaustive(
+        vec!["false".to_owned()].loc()
+    )))
+);
+
+test_type!(
+    non_exhaustive_int,
+    Err(LangError::Ty(TyError::NonExhaustive(
+        vec!["_".to_owned()].loc()
+    )))
+);
@@ -0,0 +1,20 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    mixed_operands,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Float.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+
+test_type!(
+    bit_op,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Float.loc())
+    }))
+);
@@ -6,23 +6,63 @@ use pijama_driver::LangError;
 test_type!(
     wrong_type_fn_call_arg,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     }))
 );
 
 test_type!(
     wrong_return_type_fn_int_to_int,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     }))
 );
 
 test_type!(
     wrong_type_anon_fn_call_arg,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Int,
-        found: Ty::Bool.loc()
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
     }))
 );
+
+test_type!(
+    anon_fn_param_against_expected_type,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+
+test_type!(
+    param_used_with_two_types,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+
+test_type!(
+    infinite_param_type,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Var(0).loc()),
+        found: Box::new(Ty::Arrow(Box::new(Ty::Var(0)), Box::new(Ty::Var(1))).loc())
+    }))
+);
+
+test_type!(
+    pipe_wrong_arg,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+
+test_type!(
+    ambiguous_mutual_return,
+    Err(LangError::Multiple(vec![
+        LangError::Ty(TyError::AmbiguousReturn("ping".to_owned().loc())),
+        LangError::Ty(TyError::AmbiguousReturn("pong".to_owned().loc())),
+    ]))
+);
@@ -0,0 +1,20 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    mixed_elements,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+
+test_type!(
+    not_a_list,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::List(Box::new(Ty::Var(0))).loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
@@ -7,8 +7,8 @@ use pijama_driver::LangError;
 test_type_for_all_logical_binops!(
     wrong_type_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     })),
     OPERATOR
 );
@@ -17,8 +17,8 @@ test_type_for_all_logical_binops!(
 test_type_for_all_logical_binops!(
     mixed_type_placeholder_first_is_bool,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     })),
     OPERATOR
 );
@@ -27,8 +27,8 @@ test_type_for_all_logical_binops!(
 test_type_for_all_logical_binops!(
     mixed_type_placeholder_second_is_bool,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     })),
     OPERATOR
 );
@@ -36,7 +36,15 @@ test_type_for_all_logical_binops!(
 test_type!(
     wrong_type_not,
     Err(LangError::Ty(TyError::Mismatch {
-        expected: Ty::Bool,
-        found: Ty::Int.loc()
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+
+test_type!(
+    wrong_type_assert,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
     }))
 );
@@ -0,0 +1,33 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    wrong_type_while_cond,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+test_type!(
+    wrong_type_while_body,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Unit.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+test_type!(
+    wrong_type_for_var,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Bool.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+test_type!(
+    wrong_type_range,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
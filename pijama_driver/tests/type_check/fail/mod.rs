@@ -1,7 +1,17 @@
 mod arithmetic;
 mod bindings;
+mod channels;
+mod chars;
 mod comparison;
 mod conditionals;
+mod enums;
+mod floats;
 mod functions;
+mod lazy;
+mod lists;
 mod logic;
+mod loops;
+mod origin;
+mod polymorphism;
+mod strings;
 mod variables;
@@ -0,0 +1,85 @@
+use crate::util::dummy_loc;
+
+use pijama_ast::{Located, Location};
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::{check, LangError};
+
+/// Returns the type error of `input` and the location of the origin of its expected type.
+fn origin(input: &str) -> (TyError, Option<Location>) {
+    match check(input) {
+        Err(LangError::Ty(error)) => {
+            let loc = error.origin().map(|expected| expected.loc);
+            (error, loc)
+        }
+        result => panic!("expected a type error, found {:?}", result),
+    }
+}
+
+#[test]
+fn annotation() {
+    let (error, loc) = origin("x: Int = true");
+    assert_eq!(Some(Location::new(3, 4)), loc);
+    assert_eq!(Location::new(9, 10), error.loc());
+}
+
+#[test]
+fn return_annotation() {
+    let (error, loc) = origin("fn f(x: Int): Bool do x end\nf(1)");
+    assert_eq!(Some(Location::new(14, 15)), loc);
+    assert_eq!(Location::new(22, 23), error.loc());
+}
+
+#[test]
+fn after_block_comment() {
+    let (error, loc) = origin("/* a /* b */\nc */\nx: Int = true");
+    assert_eq!(Some(Location::new(21, 22)), loc);
+    assert_eq!(Location::new(27, 28), error.loc());
+}
+
+#[test]
+fn other_branch() {
+    let (error, loc) = origin("if true do 1 else false end");
+    assert_eq!(Some(Location::new(11, 12)), loc);
+    assert_eq!(Location::new(18, 19), error.loc());
+}
+
+#[test]
+fn elif_branch() {
+    let (error, loc) = origin("if true do 1 elif false do true else false end");
+    assert_eq!(Some(Location::new(11, 12)), loc);
+    assert_eq!(Location::new(18, 43), error.loc());
+}
+
+#[test]
+fn function_signature() {
+    let (error, loc) = origin("fn f(x: Int): Int do x end\nf(true)");
+    assert_eq!(Some(Location::new(27, 28)), loc);
+    assert_eq!(Location::new(29, 30), error.loc());
+}
+
+#[test]
+fn unicode_function_name() {
+    let (error, loc) = origin("fn área(x: Int): Int do x end\nárea(true)");
+    assert_eq!(Some(Location::new(31, 36)), loc);
+    assert_eq!(Location::new(37, 38), error.loc());
+}
+
+#[test]
+fn condition() {
+    let (error, loc) = origin("if 1 do 1 else 2 end");
+    assert_eq!(Some(Location::new(0, 3)), loc);
+    assert_eq!(Location::new(3, 4), error.loc());
+}
+
+#[test]
+fn unknown_origin() {
+    let (error, loc) = origin("if true do 1 elif 1 do 1 else 2 end");
+    assert_eq!(None, loc);
+    assert_eq!(
+        TyError::Mismatch {
+            expected: Box::new(Located::new(Ty::Bool, dummy_loc())),
+            found: Box::new(Located::new(Ty::Int, dummy_loc())),
+        },
+        error
+    );
+}
@@ -0,0 +1,34 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    param_not_polymorphic,
+    Err(LangError::Multiple(vec![
+        LangError::Ty(TyError::Mismatch {
+            expected: Box::new(Ty::Bool.loc()),
+            found: Box::new(Ty::Int.loc())
+        }),
+        LangError::Ty(TyError::Mismatch {
+            expected: Box::new(Ty::Bool.loc()),
+            found: Box::new(Ty::Int.loc())
+        })
+    ]))
+);
+
+test_type!(
+    annotated_var_constrained,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
+
+test_type!(
+    instance_mismatch,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::Bool.loc())
+    }))
+);
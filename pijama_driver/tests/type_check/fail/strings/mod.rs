@@ -0,0 +1,42 @@
+use crate::{test_type, util::DummyLoc};
+
+use pijama_core::ty::{Ty, TyError};
+use pijama_driver::LangError;
+
+test_type!(
+    concat_int,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::String.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
+
+test_type!(
+    add_strings,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::Int.loc()),
+        found: Box::new(Ty::String.loc())
+    }))
+);
+
+test_type!(
+    interpolate_list,
+    Err(LangError::Ty(TyError::NotPrintable(
+        Ty::List(Box::new(Ty::Int)).loc()
+    )))
+);
+
+test_type!(
+    to_string_fn,
+    Err(LangError::Ty(TyError::NotPrintable(
+        Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)).loc()
+    )))
+);
+
+test_type!(
+    substring_int,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Box::new(Ty::String.loc()),
+        found: Box::new(Ty::Int.loc())
+    }))
+);
@@ -1,9 +1,33 @@
 use crate::{test_type, util::DummyLoc};
 
-use pijama_core::ty::{TyError};
+use pijama_core::ty::{Ty, TyError};
 use pijama_driver::LangError;
 
 test_type!(
     unbounded,
     Err(LangError::Ty(TyError::Unbounded("x".to_owned().loc())))
 );
+
+test_type!(
+    several_errors,
+    Err(LangError::Multiple(vec![
+        LangError::Ty(TyError::Unbounded("y".to_owned().loc())),
+        LangError::Ty(TyError::Mismatch {
+            expected: Box::new(Ty::Bool.loc()),
+            found: Box::new(Ty::Int.loc())
+        }),
+    ]))
+);
+
+// The mismatch is found when the constraints are solved, after `y` is found to be unbounded, but
+// the errors are reported in the order they appear in the program.
+test_type!(
+    errors_in_order,
+    Err(LangError::Multiple(vec![
+        LangError::Ty(TyError::Mismatch {
+            expected: Box::new(Ty::Bool.loc()),
+            found: Box::new(Ty::Int.loc())
+        }),
+        LangError::Ty(TyError::Unbounded("y".to_owned().loc())),
+    ]))
+);
@@ -4,6 +4,8 @@ use pijama_core::ty::Ty;
 
 // Unary operations
 test_type!(minus_is_int, Ok(Ty::Int));
+test_type!(bit_not_is_int, Ok(Ty::Int));
+test_type!(read_int_is_int, Ok(Ty::Int));
 
 // Test all int binary operators with int arguments
 test_type_for_all_integer_binops!(int_binop_with_placeholder, Ok(Ty::Int), OPERATOR);
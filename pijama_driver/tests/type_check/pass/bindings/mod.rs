@@ -3,3 +3,6 @@ use crate::test_type;
 use pijama_core::ty::Ty;
 
 test_type!(bind_int_to_int, Ok(Ty::Int));
+test_type!(shadow_with_other_type, Ok(Ty::Bool));
+test_type!(assign_mutable, Ok(Ty::Int));
+test_type!(shadow_outside_loop, Ok(Ty::Bool));
@@ -0,0 +1,17 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(inferred, Ok(Ty::Int));
+test_type!(
+    annotated,
+    Ok(Ty::Arrow(
+        Box::new(Ty::Chan(Box::new(Ty::Int))),
+        Box::new(Ty::Arrow(
+            Box::new(Ty::Chan(Box::new(Ty::Int))),
+            Box::new(Ty::Unit)
+        ))
+    ))
+);
+test_type!(spawn, Ok(Ty::Unit));
+test_type!(polymorphic_function, Ok(Ty::Unit));
@@ -0,0 +1,10 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(comparison, Ok(Ty::Bool));
+test_type!(conversion, Ok(Ty::Char));
+test_type!(
+    inferred_param,
+    Ok(Ty::Arrow(Box::new(Ty::Char), Box::new(Ty::Bool)))
+);
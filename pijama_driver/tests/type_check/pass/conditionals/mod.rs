@@ -4,3 +4,4 @@ use pijama_core::ty::Ty;
 
 test_type!(cond_result_bool_is_bool, Ok(Ty::Bool));
 test_type!(cond_result_int_is_int, Ok(Ty::Int));
+test_type!(cond_without_else_is_unit, Ok(Ty::Unit));
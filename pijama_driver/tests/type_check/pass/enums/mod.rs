@@ -0,0 +1,9 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(shapes, Ok(Ty::Int));
+test_type!(recursive, Ok(Ty::Bool));
+test_type!(mutual, Ok(Ty::Adt("Expr".to_owned(), Box::new([]))));
+test_type!(literal_patterns, Ok(Ty::Bool));
+test_type!(generic, Ok(Ty::Adt("Tree".to_owned(), Box::new([Ty::Int]))));
@@ -0,0 +1,11 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(arithmetic, Ok(Ty::Float));
+test_type!(comparison, Ok(Ty::Bool));
+test_type!(conversion, Ok(Ty::Int));
+test_type!(
+    inferred_param,
+    Ok(Ty::Arrow(Box::new(Ty::Float), Box::new(Ty::Float)))
+);
@@ -15,3 +15,39 @@ test_type!(
     anon_fn_from_int_to_int,
     Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
 );
+
+// Functions without parameter types
+test_type!(
+    fn_without_param_types,
+    Ok(Ty::Arrow(
+        Box::new(Ty::Int),
+        Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+    ))
+);
+test_type!(
+    anon_fn_without_param_types,
+    Ok(Ty::Arrow(Box::new(Ty::Bool), Box::new(Ty::Bool)))
+);
+test_type!(
+    anon_fn_with_ascription,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+test_type!(
+    rec_fn_without_param_types,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+test_type!(
+    rec_fn_without_return_type,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+test_type!(higher_order_fn_without_param_types, Ok(Ty::Int));
+test_type!(
+    anon_fn_params_from_expected_type,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+
+// Mutually recursive functions
+test_type!(mutually_rec_fns, Ok(Ty::Bool));
+
+// Pipes
+test_type!(pipe_calls, Ok(Ty::Bool));
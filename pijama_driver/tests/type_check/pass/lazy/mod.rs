@@ -0,0 +1,13 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(inferred, Ok(Ty::Int));
+test_type!(
+    annotated,
+    Ok(Ty::Arrow(
+        Box::new(Ty::Lazy(Box::new(Ty::Int))),
+        Box::new(Ty::Int)
+    ))
+);
+test_type!(polymorphic_function, Ok(Ty::Bool));
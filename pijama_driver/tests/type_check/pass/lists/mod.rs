@@ -0,0 +1,16 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(literal, Ok(Ty::List(Box::new(Ty::Int))));
+test_type!(empty, Ok(Ty::List(Box::new(Ty::Bool))));
+test_type!(empty_with_ascription, Ok(Ty::List(Box::new(Ty::Bool))));
+test_type!(head, Ok(Ty::Int));
+test_type!(nested, Ok(Ty::List(Box::new(Ty::Int))));
+test_type!(
+    inferred_param,
+    Ok(Ty::Arrow(
+        Box::new(Ty::List(Box::new(Ty::Int))),
+        Box::new(Ty::Int)
+    ))
+);
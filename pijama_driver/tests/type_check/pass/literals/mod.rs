@@ -6,3 +6,6 @@ test_type!(true_is_bool, Ok(Ty::Bool));
 test_type!(false_is_bool, Ok(Ty::Bool));
 test_type!(number_is_int, Ok(Ty::Int));
 test_type!(unit_is_unit, Ok(Ty::Unit));
+test_type!(string_is_string, Ok(Ty::String));
+test_type!(float_is_float, Ok(Ty::Float));
+test_type!(char_is_char, Ok(Ty::Char));
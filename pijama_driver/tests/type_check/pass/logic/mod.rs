@@ -7,3 +7,5 @@ test_type!(not_is_bool, Ok(Ty::Bool));
 // Binary operations
 test_type!(and_is_bool, Ok(Ty::Bool));
 test_type!(or_is_bool, Ok(Ty::Bool));
+// Assertions
+test_type!(assert_is_unit, Ok(Ty::Unit));
@@ -0,0 +1,6 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(while_is_unit, Ok(Ty::Unit));
+test_type!(for_is_unit, Ok(Ty::Unit));
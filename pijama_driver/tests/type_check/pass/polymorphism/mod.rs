@@ -0,0 +1,8 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(id_with_many_types, Ok(Ty::Int));
+test_type!(const_without_annotations, Ok(Ty::Int));
+test_type!(compose, Ok(Ty::Bool));
+test_type!(rec_fn, Ok(Ty::Int));
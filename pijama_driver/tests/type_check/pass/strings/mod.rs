@@ -0,0 +1,21 @@
+use crate::test_type;
+
+use pijama_core::ty::Ty;
+
+test_type!(concat, Ok(Ty::String));
+test_type!(comparison, Ok(Ty::Bool));
+test_type!(
+    annotation,
+    Ok(Ty::Arrow(Box::new(Ty::String), Box::new(Ty::String)))
+);
+test_type!(interpolation, Ok(Ty::String));
+test_type!(read_line, Ok(Ty::String));
+test_type!(
+    interpolation_defaults_to_int,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::String)))
+);
+test_type!(primitives, Ok(Ty::Int));
+test_type!(
+    len_of_string,
+    Ok(Ty::Arrow(Box::new(Ty::String), Box::new(Ty::Int)))
+);
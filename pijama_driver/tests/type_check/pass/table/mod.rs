@@ -0,0 +1,78 @@
+use pijama_ast::{Literal, Located};
+use pijama_core::{
+    mir::Term,
+    parser::parse,
+    ty::{ty_check_terms, Ty},
+};
+
+fn lower(input: &str) -> Located<Term<'_>> {
+    Term::from_ast(parse(input).unwrap()).unwrap()
+}
+
+fn arrow(t1: Ty, t2: Ty) -> Ty {
+    Ty::Arrow(Box::new(t1), Box::new(t2))
+}
+
+#[test]
+fn inferred_params() {
+    let mir = lower(include_str!("inferred_params.pj"));
+    let table = ty_check_terms(&mir).unwrap();
+    assert_eq!(table.ty().content, Ty::Int);
+    assert_eq!(table.get(&mir.content), Some(&Ty::Int));
+
+    let (abs, app) = match &mir.content {
+        Term::Let(_, _, t1, t2) => (t1, t2),
+        term => panic!("Expected a let binding, found {}", term),
+    };
+    assert_eq!(table.get(&abs.content), Some(&arrow(Ty::Int, Ty::Int)));
+    match &abs.content {
+        Term::Abs(_, _, body) => assert_eq!(table.get(&body.content), Some(&Ty::Int)),
+        term => panic!("Expected an abstraction, found {}", term),
+    }
+    match &app.content {
+        Term::App(t1, t2) => {
+            assert_eq!(table.get(&t1.content), Some(&arrow(Ty::Int, Ty::Int)));
+            assert_eq!(table.get(&t2.content), Some(&Ty::Int));
+        }
+        term => panic!("Expected an application, found {}", term),
+    }
+}
+
+#[test]
+fn instantiations() {
+    let mir = lower(include_str!("instantiations.pj"));
+    let table = ty_check_terms(&mir).unwrap();
+
+    let (abs, term) = match &mir.content {
+        Term::Let(_, _, t1, t2) => (t1, t2),
+        term => panic!("Expected a let binding, found {}", term),
+    };
+    // The definition keeps its quantified type variable.
+    match table.get(&abs.content) {
+        Some(Ty::Arrow(t1, t2)) => {
+            assert!(matches!(**t1, Ty::Var(_)));
+            assert_eq!(t1, t2);
+        }
+        ty => panic!("Expected a function type, found {:?}", ty),
+    }
+
+    let (t1, t2) = match &term.content {
+        Term::Let(_, _, t1, t2) => (t1, t2),
+        term => panic!("Expected a let binding, found {}", term),
+    };
+    for (app, ty) in [(t1, Ty::Bool), (t2, Ty::Int)] {
+        match &app.content {
+            Term::App(head, _) => {
+                assert_eq!(table.get(&head.content), Some(&arrow(ty.clone(), ty)))
+            }
+            term => panic!("Expected an application, found {}", term),
+        }
+    }
+}
+
+#[test]
+fn terms_outside_the_program_have_no_type() {
+    let mir = lower(include_str!("instantiations.pj"));
+    let table = ty_check_terms(&mir).unwrap();
+    assert_eq!(table.get(&Term::Lit(Literal::Unit)), None);
+}
@@ -0,0 +1,51 @@
+use pijama_ast::{Literal, Located, Location, Name};
+use pijama_core::{
+    mir::{
+        visit::{Folder, Visitor},
+        Term,
+    },
+    parser::parse,
+};
+
+fn lower(input: &str) -> Located<Term<'_>> {
+    Term::from_ast(parse(input).unwrap()).unwrap()
+}
+
+/// Collects the names of the variables in the order they are visited.
+struct Vars<'a>(Vec<Name<'a>>);
+
+impl<'t, 'a: 't> Visitor<'t, 'a> for Vars<'a> {
+    fn visit_var(&mut self, name: Name<'a>) {
+        self.0.push(name);
+    }
+}
+
+/// Replaces every integer literal by its double.
+struct Double;
+
+impl<'a> Folder<'a> for Double {
+    fn fold_lit(&mut self, loc: Location, lit: Literal) -> Located<Term<'a>> {
+        match lit {
+            Literal::Number(n) => loc.with_content(Term::Lit(Literal::Number(2 * n))),
+            lit => self.super_lit(loc, lit),
+        }
+    }
+}
+
+#[test]
+fn visitor() {
+    let mir = lower(include_str!("program.pj"));
+    let mut vars = Vars(Vec::new());
+    vars.visit_term(&mir);
+    let names: Vec<_> = vars.0.iter().map(|name| name.0).collect();
+    assert_eq!(vec!["x", "y", "add", "z"], names);
+}
+
+#[test]
+fn folder() {
+    let mir = lower(include_str!("program.pj"));
+    assert_eq!(
+        "(let add : Int -> Int -> Int = (λx:Int. (λy:Int. (x + y))) in (let z = ((add 2) 4) in (print (z * 4))))",
+        Double.fold_term(mir).content.to_string()
+    );
+}
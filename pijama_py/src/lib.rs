@@ -0,0 +1,296 @@
+//! Python bindings for Pijama.
+//!
+//! This crate builds a Python extension module called `pijama` exposing:
+//!
+//! - `compile(source, opt_level=0, prelude=True)`, which type-checks a program and returns a
+//!   `Program` that can be run many times.
+//! - `compile_file(path, opt_level=0, prelude=True)`, which compiles the program in a file like
+//!   `compile`, along with the modules it imports.
+//! - `run(source, overflow_check=False, opt_level=0, prelude=True)`, which compiles and runs a
+//!   program in one step.
+//!
+//! Programs are compiled by the pipeline of the driver, see [`pijama_driver::pipeline`], so the
+//! definitions of the prelude are available unless `prelude` is false, and `opt_level` chooses
+//! the optimization passes that are run.
+//!
+//! The value of a program is converted to the closest Python value: integers to `int`, booleans
+//! to `bool`, unit to `None`, characters to `str` and functions to an opaque `Function` object.
+//! Anything printed by the program is written to Python's `sys.stdout` as soon as it is printed,
+//! and the lines read by `read_line` and `read_int` come from the standard input of the process.
+//!
+//! Errors are raised as subclasses of `pijama.PijamaError`. Each exception has a `message` and
+//! the `start` and `end` byte offsets of the error in the source code.
+use std::{
+    io::{self, Write},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*, IntoPyObjectExt};
+
+use pijama_core::{
+    lir::Term,
+    machine::{
+        arithmetic::CheckedArithmetic,
+        env::{Env, Stdin},
+        MachineBuilder, Value,
+    },
+    ty::Ty,
+};
+use pijama_driver::{
+    pipeline::{self, CompiledProgram, Options},
+    LangError, Modules, Source,
+};
+
+create_exception!(
+    pijama,
+    PijamaError,
+    PyException,
+    "Base class of all Pijama errors."
+);
+create_exception!(
+    pijama,
+    ParseError,
+    PijamaError,
+    "The source code could not be parsed."
+);
+create_exception!(
+    pijama,
+    LowerError,
+    PijamaError,
+    "The source code could not be lowered."
+);
+create_exception!(
+    pijama,
+    TyError,
+    PijamaError,
+    "The program is not well-typed."
+);
+create_exception!(
+    pijama,
+    EvalError,
+    PijamaError,
+    "The program failed during evaluation."
+);
+
+/// Builds a Python exception from an error and its location.
+fn new_err<E: pyo3::PyTypeInfo>(py: Python, message: String, start: usize, end: usize) -> PyErr {
+    let err = PyErr::new::<E, _>((message.clone(), start, end));
+    let value = err.value(py);
+    // Setting attributes on a freshly created exception cannot fail.
+    value.setattr("message", message).unwrap();
+    value.setattr("start", start).unwrap();
+    value.setattr("end", end).unwrap();
+    err
+}
+
+fn lang_err(py: Python, error: LangError) -> PyErr {
+    let loc = error.loc();
+    let message = error.to_string();
+    match error {
+        LangError::Parse(_) => new_err::<ParseError>(py, message, loc.start, loc.end),
+        LangError::Lower(_) => new_err::<LowerError>(py, message, loc.start, loc.end),
+        LangError::Ty(_) => new_err::<TyError>(py, message, loc.start, loc.end),
+        LangError::Codegen(_) => new_err::<PijamaError>(py, message, loc.start, loc.end),
+        LangError::Runtime(_) => new_err::<EvalError>(py, message, loc.start, loc.end),
+        LangError::Module { error, .. } | LangError::Traced { error, .. } => lang_err(py, *error),
+        // An exception has a single location, so it is raised for the first error.
+        LangError::Multiple(errors) => match errors.into_iter().next() {
+            Some(error) => lang_err(py, error),
+            None => {
+                new_err::<PijamaError>(py, "An empty list of errors was reported".to_owned(), 0, 0)
+            }
+        },
+    }
+}
+
+/// The output of a running program, which writes to Python's `sys.stdout`.
+struct PyStdout<'py> {
+    py: Python<'py>,
+    /// The exception raised by the first write that failed.
+    error: Option<PyErr>,
+}
+
+impl Write for PyStdout<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let output = String::from_utf8_lossy(buf);
+        let result = self
+            .py
+            .import("sys")
+            .and_then(|sys| sys.getattr("stdout")?.call_method1("write", (output,)));
+        match result {
+            Ok(_) => Ok(buf.len()),
+            Err(err) => {
+                let io_err = io::Error::other(err.to_string());
+                self.error.get_or_insert(err);
+                Err(io_err)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A type-checked program.
+#[pyclass(frozen, module = "pijama")]
+struct Program {
+    term: Term,
+    ty: Ty,
+}
+
+#[pymethods]
+impl Program {
+    /// The type of the program.
+    #[getter]
+    fn ty(&self) -> String {
+        self.ty.to_string()
+    }
+
+    /// Runs the program and returns its value.
+    #[pyo3(signature = (overflow_check = false))]
+    fn run(&self, py: Python, overflow_check: bool) -> PyResult<PyObject> {
+        let mut output = PyStdout { py, error: None };
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let builder = MachineBuilder::new(Env::new(&mut output).with_input(Stdin));
+            if overflow_check {
+                builder
+                    .with_arithmetic(CheckedArithmetic)
+                    .build()
+                    .evaluate(self.term.clone())
+            } else {
+                builder.build().evaluate(self.term.clone())
+            }
+        }));
+
+        // A failed write stops the program, so the exception of the write is raised instead.
+        if let Some(err) = output.error {
+            return Err(err);
+        }
+
+        match result {
+            Ok(Ok(term)) => to_python(py, &self.ty, term),
+            Ok(Err(error)) => Err(lang_err(py, error.into())),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|msg| msg.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "Evaluation failed".to_owned());
+                Err(new_err::<EvalError>(py, message, 0, 0))
+            }
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<pijama.Program: {}>", self.ty)
+    }
+}
+
+/// A function returned by a program.
+///
+/// Functions cannot be called from Python, this object only records their type.
+#[pyclass(frozen, module = "pijama")]
+struct Function {
+    ty: Ty,
+}
+
+#[pymethods]
+impl Function {
+    /// The type of the function.
+    #[getter]
+    fn ty(&self) -> String {
+        self.ty.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<pijama.Function: {}>", self.ty)
+    }
+}
+
+/// Converts the result of a program of type `ty` into a Python value.
+fn to_python(py: Python, ty: &Ty, term: Term) -> PyResult<PyObject> {
+    match Value::from_term(&term, ty) {
+        Some(value) => value_to_python(py, value),
+        None => Function { ty: ty.clone() }.into_py_any(py),
+    }
+}
+
+/// Converts a value into a Python value, turning lists into Python lists.
+fn value_to_python(py: Python, value: Value) -> PyResult<PyObject> {
+    match value {
+        Value::Int(n) => n.into_py_any(py),
+        Value::Float(x) => x.into_py_any(py),
+        Value::Bool(b) => b.into_py_any(py),
+        Value::Unit => Ok(py.None()),
+        Value::Char(c) => c.into_py_any(py),
+        Value::Str(string) => string.into_py_any(py),
+        Value::List(_, elems) => elems
+            .into_iter()
+            .map(|elem| value_to_python(py, elem))
+            .collect::<PyResult<Vec<_>>>()?
+            .into_py_any(py),
+    }
+}
+
+/// Compiles `input` with the pipeline of the driver.
+fn compile_with<'a>(
+    py: Python,
+    input: impl Source<'a>,
+    opt_level: u8,
+    prelude: bool,
+) -> PyResult<Program> {
+    let options = Options {
+        opt_level,
+        prelude,
+        ..Options::default()
+    };
+    let CompiledProgram { lir, ty, .. } =
+        pipeline::compile(input, &options).map_err(|err| lang_err(py, err))?;
+    Ok(Program { term: lir, ty })
+}
+
+/// Parses, lowers and type-checks `source`, and runs the passes of `opt_level` over it.
+#[pyfunction]
+#[pyo3(signature = (source, opt_level = 0, prelude = true))]
+fn compile(py: Python, source: &str, opt_level: u8, prelude: bool) -> PyResult<Program> {
+    compile_with(py, source, opt_level, prelude)
+}
+
+/// Compiles the program in the file at `path` like `compile`, with the modules it imports.
+#[pyfunction]
+#[pyo3(signature = (path, opt_level = 0, prelude = true))]
+fn compile_file(py: Python, path: &str, opt_level: u8, prelude: bool) -> PyResult<Program> {
+    let modules =
+        Modules::load(path).map_err(|err| new_err::<PijamaError>(py, err.to_string(), 0, 0))?;
+    compile_with(py, &modules, opt_level, prelude)
+}
+
+/// Compiles and runs `source`, returning its value.
+#[pyfunction]
+#[pyo3(signature = (source, overflow_check = false, opt_level = 0, prelude = true))]
+fn run(
+    py: Python,
+    source: &str,
+    overflow_check: bool,
+    opt_level: u8,
+    prelude: bool,
+) -> PyResult<PyObject> {
+    compile(py, source, opt_level, prelude)?.run(py, overflow_check)
+}
+
+#[pymodule]
+pub fn pijama(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_function(wrap_pyfunction!(compile, m)?)?;
+    m.add_function(wrap_pyfunction!(compile_file, m)?)?;
+    m.add_function(wrap_pyfunction!(run, m)?)?;
+    m.add_class::<Program>()?;
+    m.add_class::<Function>()?;
+    m.add("PijamaError", py.get_type::<PijamaError>())?;
+    m.add("ParseError", py.get_type::<ParseError>())?;
+    m.add("LowerError", py.get_type::<LowerError>())?;
+    m.add("TyError", py.get_type::<TyError>())?;
+    m.add("EvalError", py.get_type::<EvalError>())?;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::ffi::CString;
+
+use pyo3::{prelude::*, types::PyDict, wrap_pymodule};
+
+/// Runs the Python code in `code` with the `pijama` module in scope.
+fn run_python(code: &str) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let locals = PyDict::new(py);
+        locals
+            .set_item("pijama", wrap_pymodule!(pijama_py::pijama)(py))
+            .unwrap();
+        let code = CString::new(code).unwrap();
+        py.run(&code, None, Some(&locals))
+            .unwrap_or_else(|err| panic!("{}", err));
+    })
+}
+
+#[test]
+fn values() {
+    run_python(
+        r#"
+assert pijama.run("1 + 2") == 3
+assert pijama.run("1 < 2") is True
+assert pijama.run("unit") is None
+f = pijama.run("fn(x: Int) do x end")
+assert f.ty == "Int -> Int", f.ty
+"#,
+    );
+}
+
+#[test]
+fn output() {
+    run_python(
+        r#"
+import io, sys
+stdout, sys.stdout = sys.stdout, io.StringIO()
+try:
+    pijama.run("print(3)")
+    output = sys.stdout.getvalue()
+finally:
+    sys.stdout = stdout
+assert output == "3\n", output
+"#,
+    );
+}
+
+#[test]
+fn output_is_not_buffered() {
+    run_python(
+        r#"
+import sys
+class Recorder:
+    def __init__(self):
+        self.writes = []
+    def write(self, text):
+        self.writes.append(text)
+stdout, sys.stdout = sys.stdout, Recorder()
+try:
+    pijama.run("print(1)\nprint(2)")
+    writes = sys.stdout.writes
+finally:
+    sys.stdout = stdout
+assert "".join(writes) == "1\n2\n", writes
+# Each print is written as soon as it runs.
+assert len(writes) > 1, writes
+"#,
+    );
+}
+
+#[test]
+fn output_error() {
+    run_python(
+        r#"
+import sys
+class Closed:
+    def write(self, text):
+        raise ValueError("closed")
+stdout, sys.stdout = sys.stdout, Closed()
+try:
+    pijama.run("print(1)")
+    assert False
+except ValueError as err:
+    assert str(err) == "closed", err
+finally:
+    sys.stdout = stdout
+"#,
+    );
+}
+
+#[test]
+fn program() {
+    run_python(
+        r#"
+program = pijama.compile("fn fact(n: Int): Int do if n <= 0 do 1 else n * fact(n - 1) end end\nfact(5)")
+assert program.ty == "Int"
+assert program.run() == 120
+assert program.run() == 120
+"#,
+    );
+}
+
+#[test]
+fn errors() {
+    run_python(
+        r#"
+try:
+    pijama.compile("x = 1 + true")
+    assert False
+except pijama.TyError as err:
+    assert isinstance(err, pijama.PijamaError)
+    assert err.start == 8, err.start
+    assert err.message.startswith("Type mismatch")
+
+try:
+    pijama.run("fn foo(x: Int do x end")
+    assert False
+except pijama.ParseError:
+    pass
+
+try:
+    pijama.run("9223372036854775807 + 1", overflow_check=True)
+    assert False
+except pijama.EvalError:
+    pass
+"#,
+    );
+}
+
+#[test]
+fn pipeline() {
+    run_python(
+        r#"
+assert pijama.run("unwrap_or(Some(2), 3)") == 2
+try:
+    pijama.compile("unwrap_or(Some(2), 3)", prelude=False)
+    assert False
+except pijama.PijamaError:
+    pass
+source = "fn fact(n: Int): Int do if n <= 0 do 1 else n * fact(n - 1) end end\nfact(5)"
+assert pijama.run(source, opt_level=2) == 120
+"#,
+    );
+}
+
+#[test]
+fn modules() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../pijama_driver/tests/modules/main.pj"
+    );
+    run_python(&format!(
+        r#"
+assert pijama.compile_file({:?}).ty == "Unit"
+try:
+    pijama.compile_file("missing.pj")
+    assert False
+except pijama.PijamaError as err:
+    assert err.message.startswith("Could not read"), err.message
+"#,
+        path
+    ));
+}
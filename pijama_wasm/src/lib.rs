@@ -0,0 +1,190 @@
+//! WebAssembly bindings for a browser playground.
+//!
+//! Build this crate for the `wasm32-unknown-unknown` target and run `wasm-bindgen` (or
+//! `wasm-pack build pijama_wasm --target web`) to get a JavaScript module exposing
+//! `compile_and_run` and `tokens`.
+//!
+//! `compile_and_run(source)` returns a plain object with the following fields:
+//!
+//! - `output`: everything the program printed.
+//! - `ty`: the type of the program or `null` if it could not be type-checked.
+//! - `value`: the value of the program formatted as a string or `null` if it did not run or
+//!   stopped with a runtime error.
+//! - `diagnostics`: an array of `{ severity, kind, message, start, end, line, column }` objects.
+//!   `start` and `end` are byte offsets, `line` and `column` are 1-based. Every statement that
+//!   cannot be parsed has its own diagnostic.
+//!
+//! Integer operations wrap around when overflowing.
+//!
+//! `tokens(source)` returns an array of `{ kind, start, end }` objects to highlight the source
+//! code, even if it has syntax errors. `kind` is the kind of the token in snake case, like
+//! `"keyword"` or `"doc_comment"`, see [`pijama_core::lex::Token`].
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use pijama_ast::LineIndex;
+use pijama_core::{
+    lex::{self, Token},
+    lir::Term,
+    machine::{env::Env, MachineBuilder, Value},
+    mir::Term as MirTerm,
+    ty::{ty_check, Ty},
+};
+use pijama_driver::{LangError, Source};
+
+/// The result of compiling and running a program.
+#[derive(Debug, Default, Serialize)]
+pub struct Outcome {
+    /// Everything printed by the program.
+    pub output: String,
+    /// The type of the program.
+    pub ty: Option<String>,
+    /// The value of the program.
+    pub value: Option<String>,
+    /// The diagnostics emitted while compiling the program.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A message about a region of the source code.
+#[derive(Debug, Serialize)]
+pub struct Diagnostic {
+    /// Severity of the diagnostic. Currently always `"error"`.
+    pub severity: &'static str,
+    /// The phase that emitted the diagnostic: `"parse"`, `"lower"`, `"type"` or `"runtime"`.
+    pub kind: &'static str,
+    /// Description of the diagnostic.
+    pub message: String,
+    /// Byte offset where the diagnostic starts.
+    pub start: usize,
+    /// Byte offset where the diagnostic ends.
+    pub end: usize,
+    /// Line where the diagnostic starts, starting at 1.
+    pub line: usize,
+    /// Column where the diagnostic starts, starting at 1.
+    pub column: usize,
+}
+
+impl Diagnostic {
+    /// Returns a diagnostic for each error inside `error`.
+    fn all(source: &str, error: LangError) -> Vec<Self> {
+        match error {
+            LangError::Module { input, error, .. } => Diagnostic::all(&input, *error),
+            LangError::Multiple(errors) => errors
+                .into_iter()
+                .flat_map(|error| Diagnostic::all(source, error))
+                .collect(),
+            error => vec![Diagnostic::new(source, error)],
+        }
+    }
+
+    fn new(source: &str, error: LangError) -> Self {
+        let kind = match error {
+            LangError::Parse(_) => "parse",
+            LangError::Lower(_) => "lower",
+            LangError::Ty(_) => "type",
+            LangError::Codegen(_) => "codegen",
+            LangError::Runtime(_) => "runtime",
+            LangError::Module { input, error, .. } => return Diagnostic::new(&input, *error),
+            LangError::Traced { error, .. } => return Diagnostic::new(source, *error),
+            LangError::Multiple(mut errors) => return Diagnostic::new(source, errors.remove(0)),
+        };
+        let loc = error.loc();
+        let pos = loc.to_line_col(&LineIndex::new(source));
+        Diagnostic {
+            severity: "error",
+            kind,
+            message: error.to_string(),
+            start: loc.start,
+            end: loc.end,
+            line: pos.line + 1,
+            column: pos.column + 1,
+        }
+    }
+}
+
+/// Compiles and runs `source`, collecting its output, value and diagnostics.
+pub fn run_source(source: &str) -> Outcome {
+    let mut outcome = Outcome::default();
+    match compile(source) {
+        Ok((term, ty)) => {
+            let mut output = Vec::new();
+            let res = MachineBuilder::new(Env::new(&mut output))
+                .build()
+                .evaluate(term);
+            outcome.output = String::from_utf8_lossy(&output).into_owned();
+            match res {
+                Ok(term) => outcome.value = Some(format_value(&ty, &term)),
+                Err(error) => outcome
+                    .diagnostics
+                    .push(Diagnostic::new(source, error.into())),
+            }
+            outcome.ty = Some(ty.to_string());
+        }
+        Err(error) => outcome.diagnostics = Diagnostic::all(source, error),
+    }
+    outcome
+}
+
+fn compile(source: &str) -> Result<(Term, Ty), LangError> {
+    let ast = source.ast()?;
+    let mir = MirTerm::from_ast(ast)?;
+    let ty = ty_check(&mir)?.content;
+    Ok((Term::from_mir(mir), ty))
+}
+
+/// Formats the value of a program of type `ty`.
+fn format_value(ty: &Ty, term: &Term) -> String {
+    match Value::from_term(term, ty) {
+        Some(value) => value.to_string(),
+        None => format!("<function: {}>", ty),
+    }
+}
+
+/// Compiles and runs `source`. See the crate documentation for the shape of the returned value.
+#[wasm_bindgen]
+pub fn compile_and_run(source: &str) -> Result<JsValue, JsValue> {
+    let outcome = run_source(source);
+    serde_wasm_bindgen::to_value(&outcome).map_err(Into::into)
+}
+
+/// A token of the source code.
+#[derive(Debug, Serialize)]
+pub struct Highlight {
+    /// The kind of the token in snake case.
+    pub kind: &'static str,
+    /// Byte offset where the token starts.
+    pub start: usize,
+    /// Byte offset where the token ends.
+    pub end: usize,
+}
+
+/// Returns the tokens of `source`.
+pub fn highlight(source: &str) -> Vec<Highlight> {
+    lex::tokens(source)
+        .map(|token| Highlight {
+            kind: match token.content {
+                Token::Keyword => "keyword",
+                Token::Name => "name",
+                Token::Primitive => "primitive",
+                Token::Type => "type",
+                Token::Constant => "constant",
+                Token::Number => "number",
+                Token::Char => "char",
+                Token::String => "string",
+                Token::Operator => "operator",
+                Token::Punctuation => "punctuation",
+                Token::Comment => "comment",
+                Token::DocComment => "doc_comment",
+                Token::Unknown => "unknown",
+            },
+            start: token.loc.start,
+            end: token.loc.end,
+        })
+        .collect()
+}
+
+/// Splits `source` in tokens. See the crate documentation for the shape of the returned value.
+#[wasm_bindgen]
+pub fn tokens(source: &str) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&highlight(source)).map_err(Into::into)
+}
@@ -0,0 +1,66 @@
+use pijama_wasm::{highlight, run_source};
+
+#[test]
+fn output_and_value() {
+    let outcome = run_source("x = 1 < 2\nx");
+    assert_eq!("", outcome.output);
+    assert_eq!(Some("Bool".to_owned()), outcome.ty);
+    assert_eq!(Some("true".to_owned()), outcome.value);
+    assert!(outcome.diagnostics.is_empty());
+
+    let outcome = run_source("print(2)");
+    assert_eq!("2\n", outcome.output);
+    assert_eq!(Some("unit".to_owned()), outcome.value);
+}
+
+#[test]
+fn function_value() {
+    let outcome = run_source("fn(x: Int) do x end");
+    assert_eq!(Some("<function: Int -> Int>".to_owned()), outcome.value);
+}
+
+#[test]
+fn diagnostics() {
+    let outcome = run_source("x = 1\ny = x + true");
+    assert_eq!(None, outcome.ty);
+    assert_eq!(None, outcome.value);
+    assert_eq!(1, outcome.diagnostics.len());
+    let diagnostic = &outcome.diagnostics[0];
+    assert_eq!("type", diagnostic.kind);
+    assert_eq!((2, 9), (diagnostic.line, diagnostic.column));
+    assert_eq!(14, diagnostic.start);
+}
+
+#[test]
+fn parse_diagnostics() {
+    let outcome = run_source(
+        "x = 1 +
+y = 2
+z = * 3",
+    );
+    let lines: Vec<_> = outcome
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.kind, diagnostic.line))
+        .collect();
+    assert_eq!(vec![("parse", 1), ("parse", 3)], lines);
+}
+
+#[test]
+fn tokens() {
+    let kinds: Vec<_> = highlight("x = \"a{1}")
+        .iter()
+        .map(|token| (token.kind, token.start, token.end))
+        .collect();
+    assert_eq!(
+        vec![
+            ("name", 0, 1),
+            ("operator", 2, 3),
+            ("string", 4, 6),
+            ("punctuation", 6, 7),
+            ("number", 7, 8),
+            ("punctuation", 8, 9),
+        ],
+        kinds
+    );
+}